@@ -9,7 +9,7 @@ use sol_ui::{
     element::{column, container, row, text},
     interaction::Interactable,
     layer::{LayerOptions, MouseButton},
-    style::TextStyle,
+    style::{LineHeight, TextStyle},
 };
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -56,7 +56,7 @@ fn main() {
                                     TextStyle {
                                         size: 28.0,
                                         color: colors::BLACK,
-                                        line_height: 1.2,
+                                        line_height: LineHeight::Relative(1.2),
                                         ..Default::default()
                                     },
                                 )
@@ -67,7 +67,7 @@ fn main() {
                                     TextStyle {
                                         size: 14.0,
                                         color: colors::GRAY_600,
-                                        line_height: 1.4,
+                                        line_height: LineHeight::Relative(1.4),
                                         ..Default::default()
                                     },
                                 )
@@ -85,7 +85,7 @@ fn main() {
                                                     TextStyle {
                                                         size: 16.0,
                                                         color: colors::GRAY_700,
-                                                        line_height: 1.2,
+                                                        line_height: LineHeight::Relative(1.2),
                                                         ..Default::default()
                                                     },
                                                 )
@@ -104,7 +104,7 @@ fn main() {
                                                     TextStyle {
                                                         size: 16.0,
                                                         color: colors::GRAY_700,
-                                                        line_height: 1.2,
+                                                        line_height: LineHeight::Relative(1.2),
                                                         ..Default::default()
                                                     },
                                                 )
@@ -125,7 +125,7 @@ fn main() {
                                                             TextStyle {
                                                                 size: 14.0,
                                                                 color: colors::GRAY_500,
-                                                                line_height: 1.2,
+                                                                line_height: LineHeight::Relative(1.2),
                                                                 ..Default::default()
                                                             },
                                                         )
@@ -144,7 +144,7 @@ fn main() {
                                             TextStyle {
                                                 size: 14.0,
                                                 color: colors::GRAY_600,
-                                                line_height: 1.2,
+                                                line_height: LineHeight::Relative(1.2),
                                                 ..Default::default()
                                             },
                                         )
@@ -155,7 +155,7 @@ fn main() {
                                             TextStyle {
                                                 size: 14.0,
                                                 color: colors::GRAY_600,
-                                                line_height: 1.2,
+                                                line_height: LineHeight::Relative(1.2),
                                                 ..Default::default()
                                             },
                                         )
@@ -187,7 +187,7 @@ fn draggable_item(label: &str, color: Srgba<f32>) -> impl sol_ui::element::Eleme
                 TextStyle {
                     size: 14.0,
                     color: colors::WHITE,
-                    line_height: 1.2,
+                    line_height: LineHeight::Relative(1.2),
                     ..Default::default()
                 },
             )
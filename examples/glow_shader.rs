@@ -1,10 +1,12 @@
 //! Glow shader example demonstrating luminous effects
 
+#[cfg(target_os = "macos")]
 use sol_ui::{
     app::app,
     layer::{LayerManager, LayerOptions},
 };
 
+#[cfg(target_os = "macos")]
 fn main() {
     app()
         .title("Toy UI - Glow Shader Example")
@@ -61,3 +63,8 @@ fn main() {
         })
         .run();
 }
+
+#[cfg(not(target_os = "macos"))]
+fn main() {
+    eprintln!("This example draws with Metal shaders, so it only runs on macOS");
+}
@@ -1,3 +1,4 @@
+#[cfg(target_os = "macos")]
 use sol_ui::{
     app::app,
     color::colors,
@@ -6,13 +7,17 @@ use sol_ui::{
     platform::{KeyboardShortcut, Menu, MenuBar, MenuItem},
     style::TextStyle,
 };
+#[cfg(target_os = "macos")]
 use std::cell::RefCell;
+#[cfg(target_os = "macos")]
 use std::rc::Rc;
+#[cfg(target_os = "macos")]
 use std::sync::{
     atomic::{AtomicUsize, Ordering},
     Arc,
 };
 
+#[cfg(target_os = "macos")]
 fn main() {
     // Shared state for demonstrating menu actions
     let action_count = Arc::new(AtomicUsize::new(0));
@@ -177,3 +182,8 @@ fn main() {
         })
         .run();
 }
+
+#[cfg(not(target_os = "macos"))]
+fn main() {
+    eprintln!("This example shows the native menu bar, so it only runs on macOS");
+}
@@ -15,7 +15,7 @@ use sol_ui::{
     element::{button, checkbox, column, container, row, text, CheckboxInteractable},
     layer::LayerOptions,
     storage::{AutoSaver, Storage, StorageConfig},
-    style::TextStyle,
+    style::{LineHeight, TextStyle},
 };
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -138,7 +138,7 @@ fn main() {
                                     TextStyle {
                                         color: text_color,
                                         size: 28.0,
-                                        line_height: 1.2,
+                                        line_height: LineHeight::Relative(1.2),
                                         ..Default::default()
                                     },
                                 )
@@ -149,7 +149,7 @@ fn main() {
                                     TextStyle {
                                         color: if current.dark_mode { colors::GRAY_400 } else { colors::GRAY_600 },
                                         size: 12.0,
-                                        line_height: 1.2,
+                                        line_height: LineHeight::Relative(1.2),
                                         ..Default::default()
                                     },
                                 )
@@ -164,7 +164,7 @@ fn main() {
                                             TextStyle {
                                                 color: secondary_color,
                                                 size: 18.0,
-                                                line_height: 1.2,
+                                                line_height: LineHeight::Relative(1.2),
                                                 ..Default::default()
                                             },
                                         )
@@ -175,7 +175,7 @@ fn main() {
                                             .label_style(TextStyle {
                                                 color: text_color,
                                                 size: 14.0,
-                                                line_height: 1.2,
+                                                line_height: LineHeight::Relative(1.2),
                                                 ..Default::default()
                                             })
                                             .with_id(1)
@@ -192,7 +192,7 @@ fn main() {
                                             .label_style(TextStyle {
                                                 color: text_color,
                                                 size: 14.0,
-                                                line_height: 1.2,
+                                                line_height: LineHeight::Relative(1.2),
                                                 ..Default::default()
                                             })
                                             .checked_background(colors::GREEN_500)
@@ -210,7 +210,7 @@ fn main() {
                                             .label_style(TextStyle {
                                                 color: text_color,
                                                 size: 14.0,
-                                                line_height: 1.2,
+                                                line_height: LineHeight::Relative(1.2),
                                                 ..Default::default()
                                             })
                                             .checked_background(colors::PURPLE_500)
@@ -228,7 +228,7 @@ fn main() {
                                             .label_style(TextStyle {
                                                 color: text_color,
                                                 size: 14.0,
-                                                line_height: 1.2,
+                                                line_height: LineHeight::Relative(1.2),
                                                 ..Default::default()
                                             })
                                             .checked_background(colors::BLUE_500)
@@ -249,7 +249,7 @@ fn main() {
                                             TextStyle {
                                                 color: secondary_color,
                                                 size: 18.0,
-                                                line_height: 1.2,
+                                                line_height: LineHeight::Relative(1.2),
                                                 ..Default::default()
                                             },
                                         )
@@ -288,7 +288,7 @@ fn main() {
                                                             TextStyle {
                                                                 color: text_color,
                                                                 size: 20.0,
-                                                                line_height: 1.2,
+                                                                line_height: LineHeight::Relative(1.2),
                                                                 ..Default::default()
                                                             },
                                                         )
@@ -361,7 +361,7 @@ fn main() {
                                             TextStyle {
                                                 color: if current.dark_mode { colors::GRAY_300 } else { colors::GRAY_600 },
                                                 size: 14.0,
-                                                line_height: 1.2,
+                                                line_height: LineHeight::Relative(1.2),
                                                 ..Default::default()
                                             },
                                         )
@@ -1,12 +1,16 @@
 //! Example demonstrating raw layers with custom shader code
 
+#[cfg(target_os = "macos")]
 use sol_ui::{
     app::app,
     layer::{LayerManager, LayerOptions},
 };
+#[cfg(target_os = "macos")]
 use tracing::{info, info_span};
+#[cfg(target_os = "macos")]
 use tracing_subscriber::{EnvFilter, fmt};
 
+#[cfg(target_os = "macos")]
 fn main() {
     // Initialize tracing
     let _subscriber = fmt()
@@ -255,3 +259,8 @@ fn main() {
         })
         .run();
 }
+
+#[cfg(not(target_os = "macos"))]
+fn main() {
+    eprintln!("This example draws with Metal shaders, so it only runs on macOS");
+}
@@ -5,6 +5,7 @@
 //! - UI layer with Button elements
 //! - Shared state via Rc<RefCell<>>
 
+#[cfg(target_os = "macos")]
 use sol_ui::{
     app::app,
     color::colors,
@@ -12,9 +13,12 @@ use sol_ui::{
     layer::LayerOptions,
     style::TextStyle,
 };
+#[cfg(target_os = "macos")]
 use std::cell::RefCell;
+#[cfg(target_os = "macos")]
 use std::rc::Rc;
 
+#[cfg(target_os = "macos")]
 fn main() {
     let counter = Rc::new(RefCell::new(0));
 
@@ -135,3 +139,8 @@ fn main() {
         })
         .run();
 }
+
+#[cfg(not(target_os = "macos"))]
+fn main() {
+    eprintln!("This example draws with Metal shaders, so it only runs on macOS");
+}
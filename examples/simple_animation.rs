@@ -1,10 +1,12 @@
 //! Simple animation example showing how to use request_animation_frame
 
+#[cfg(target_os = "macos")]
 use sol_ui::{
     app::app,
     layer::{LayerManager, LayerOptions},
 };
 
+#[cfg(target_os = "macos")]
 fn main() {
     app()
         .title("Toy UI - Simple Animation")
@@ -48,3 +50,8 @@ fn main() {
         })
         .run();
 }
+
+#[cfg(not(target_os = "macos"))]
+fn main() {
+    eprintln!("This example draws with Metal shaders, so it only runs on macOS");
+}
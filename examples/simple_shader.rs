@@ -1,10 +1,12 @@
 //! Simple shader example demonstrating raw layers
 
+#[cfg(target_os = "macos")]
 use sol_ui::{
     app::app,
     layer::{LayerManager, LayerOptions},
 };
 
+#[cfg(target_os = "macos")]
 fn main() {
     app()
         .title("Toy UI - Simple Shader Example")
@@ -30,3 +32,8 @@ fn main() {
         })
         .run();
 }
+
+#[cfg(not(target_os = "macos"))]
+fn main() {
+    eprintln!("This example draws with Metal shaders, so it only runs on macOS");
+}
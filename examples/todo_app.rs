@@ -3,6 +3,7 @@
 //! A complete todo application demonstrating sol-ui capabilities:
 //! - Text input for adding new todos
 //! - Checkboxes for marking complete/incomplete
+//! - Double-click a todo to rename it in place
 //! - Delete buttons for removing todos
 //! - Filter tabs (All/Active/Completed)
 //! - Scroll container for long lists
@@ -12,13 +13,14 @@ use sol_ui::{
     app::app,
     color::{ColorExt, colors},
     element::{
-        checkbox, column, container, row, scroll, text, text_input,
+        checkbox, column, container, editable_text, row, scroll, text, text_input,
         CheckboxInteractable, TextInputInteractable, TextInputState,
     },
-    entity::{new_entity, StateCell},
+    entity::StateCell,
+    geometry::Edges,
     interaction::Interactable,
     layer::{LayerOptions, MouseButton},
-    style::TextStyle,
+    style::{Fill, LineHeight, TextStyle},
 };
 
 /// A single todo item
@@ -44,6 +46,8 @@ struct TodoAppState {
     todos: Vec<TodoItem>,
     next_id: u64,
     filter: FilterMode,
+    /// Text of the todo being typed
+    draft: String,
 }
 
 impl Default for TodoAppState {
@@ -68,12 +72,15 @@ impl Default for TodoAppState {
             ],
             next_id: 4,
             filter: FilterMode::All,
+            draft: String::new(),
         }
     }
 }
 
 impl TodoAppState {
-    fn add_todo(&mut self, text: String) {
+    /// Add the draft as a new todo and clear it
+    fn add_draft(&mut self) {
+        let text = std::mem::take(&mut self.draft);
         if text.trim().is_empty() {
             return;
         }
@@ -91,6 +98,12 @@ impl TodoAppState {
         }
     }
 
+    fn rename_todo(&mut self, id: u64, text: &str) {
+        if let Some(todo) = self.todos.iter_mut().find(|t| t.id == id) {
+            todo.text = text.to_string();
+        }
+    }
+
     fn delete_todo(&mut self, id: u64) {
         self.todos.retain(|t| t.id != id);
     }
@@ -126,6 +139,7 @@ impl TodoAppState {
 fn main() {
     // Shared app state - StateCell handles lazy initialization
     let app_state = StateCell::new();
+    let input_state = StateCell::new();
 
     app()
         .title("Todo App - sol-ui")
@@ -153,12 +167,12 @@ fn main() {
                         })
                         .unwrap_or_default();
 
-                    // Create input state for new todo
-                    let input_state = new_entity(TextInputState::default());
+                    // Cursor and selection for the new todo input; its text
+                    // is bound to the draft
+                    let input_entity = input_state.get_or_init(TextInputState::default);
 
                     // Clone state entity for callbacks
                     let state_for_add = state_entity.clone();
-                    let input_for_add = input_state.clone();
 
                     Box::new(
                         container()
@@ -177,8 +191,13 @@ fn main() {
                                         "todos",
                                         TextStyle {
                                             color: colors::RED_400.with_alpha(0.3),
+                                            fill: Some(Fill::linear_gradient(
+                                                colors::RED_400.with_alpha(0.4),
+                                                colors::PURPLE_400.with_alpha(0.4),
+                                                0.0,
+                                            )),
                                             size: 64.0,
-                                            line_height: 1.2,
+                                            line_height: LineHeight::Relative(1.2),
                                             ..Default::default()
                                         },
                                     )),
@@ -197,26 +216,14 @@ fn main() {
                                             .items_center()
                                             .gap(8.0)
                                             .child(
-                                                text_input(input_state.clone())
+                                                text_input(input_entity)
                                                     .width(380.0)
                                                     .height(40.0)
                                                     .placeholder("What needs to be done?")
                                                     .text_size(18.0)
-                                                    .on_submit({
-                                                        let state = state_for_add.clone();
-                                                        let input = input_for_add.clone();
-                                                        move |text| {
-                                                            if !text.trim().is_empty() {
-                                                                state.update(|s| {
-                                                                    s.add_todo(text.to_string());
-                                                                });
-                                                                // Clear the input
-                                                                input.update(|s| {
-                                                                    s.text.clear();
-                                                                    s.cursor = 0;
-                                                                });
-                                                            }
-                                                        }
+                                                    .bind_text(&state_entity, |s| &mut s.draft)
+                                                    .on_submit(move |_| {
+                                                        state_for_add.update(|s| s.add_draft());
                                                     })
                                                     .interactive_input(),
                                             ),
@@ -235,6 +242,7 @@ fn main() {
                                             let todo_text = todo.text.clone();
                                             let todo_completed = todo.completed;
                                             let state_for_toggle = state_entity.clone();
+                                            let state_for_rename = state_entity.clone();
                                             let state_for_delete = state_entity.clone();
 
                                             list = list.child(
@@ -265,19 +273,25 @@ fn main() {
                                                             .child(
                                                                 container()
                                                                     .width(320.0)
-                                                                    .child(text(
-                                                                        todo_text,
-                                                                        TextStyle {
-                                                                            color: if todo_completed {
-                                                                                colors::GRAY_400
-                                                                            } else {
-                                                                                colors::BLACK
-                                                                            },
-                                                                            size: 16.0,
-                                                                            line_height: 1.2,
-                                                                            ..Default::default()
-                                                                        },
-                                                                    )),
+                                                                    .child(
+                                                                        editable_text(todo_text)
+                                                                            .with_key(format!("todo:{}", todo_id))
+                                                                            .text_style(TextStyle {
+                                                                                color: if todo_completed {
+                                                                                    colors::GRAY_400
+                                                                                } else {
+                                                                                    colors::BLACK
+                                                                                },
+                                                                                size: 16.0,
+                                                                                line_height: LineHeight::Relative(1.2),
+                                                                                ..Default::default()
+                                                                            })
+                                                                            .on_commit(move |text| {
+                                                                                state_for_rename.update(|s| {
+                                                                                    s.rename_todo(todo_id, text);
+                                                                                });
+                                                                            }),
+                                                                    ),
                                                             )
                                                             // Delete button
                                                             .child(
@@ -292,12 +306,13 @@ fn main() {
                                                                         TextStyle {
                                                                             color: colors::RED_500,
                                                                             size: 20.0,
-                                                                            line_height: 1.2,
+                                                                            line_height: LineHeight::Relative(1.2),
                                                                             ..Default::default()
                                                                         },
                                                                     ))
                                                                     .interactive()
                                                                     .with_id(1000 + todo_id as i32)
+                                                                    .hit_slop(Edges::all(4.0))
                                                                     .hover_overlay(colors::RED_500.with_alpha(0.1))
                                                                     .on_click(move |btn, _, _, _, _| {
                                                                         if btn == MouseButton::Left {
@@ -328,7 +343,7 @@ fn main() {
                                                         TextStyle {
                                                             color: colors::GRAY_400,
                                                             size: 16.0,
-                                                            line_height: 1.2,
+                                                            line_height: LineHeight::Relative(1.2),
                                                             ..Default::default()
                                                         },
                                                     )),
@@ -361,7 +376,7 @@ fn main() {
                                                 TextStyle {
                                                     color: colors::GRAY_500,
                                                     size: 14.0,
-                                                    line_height: 1.2,
+                                                    line_height: LineHeight::Relative(1.2),
                                                     ..Default::default()
                                                 },
                                             ))
@@ -411,7 +426,7 @@ fn main() {
                                                                 TextStyle {
                                                                     color: colors::GRAY_500,
                                                                     size: 14.0,
-                                                                    line_height: 1.2,
+                                                                    line_height: LineHeight::Relative(1.2),
                                                                     ..Default::default()
                                                                 },
                                                             ))
@@ -471,7 +486,7 @@ fn filter_button(
                     colors::GRAY_500
                 },
                 size: 14.0,
-                line_height: 1.2,
+                line_height: LineHeight::Relative(1.2),
                 ..Default::default()
             },
         ))
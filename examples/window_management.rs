@@ -10,6 +10,7 @@
 //! - Cmd+M: Minimize window
 //! - Escape: Exit fullscreen (if in fullscreen)
 
+#[cfg(target_os = "macos")]
 use sol_ui::{
     app::app,
     color::colors,
@@ -17,10 +18,13 @@ use sol_ui::{
     layer::{InputEvent, Key, LayerOptions},
     style::TextStyle,
 };
+#[cfg(target_os = "macos")]
 use std::cell::RefCell;
+#[cfg(target_os = "macos")]
 use std::rc::Rc;
 
 /// State to track window information
+#[cfg(target_os = "macos")]
 struct WindowState {
     width: f32,
     height: f32,
@@ -32,6 +36,7 @@ struct WindowState {
     event_log: Vec<String>,
 }
 
+#[cfg(target_os = "macos")]
 impl WindowState {
     fn new() -> Self {
         Self {
@@ -55,6 +60,7 @@ impl WindowState {
     }
 }
 
+#[cfg(target_os = "macos")]
 fn main() {
     let state = Rc::new(RefCell::new(WindowState::new()));
     let state_for_handler = state.clone();
@@ -325,3 +331,8 @@ fn main() {
         })
         .run();
 }
+
+#[cfg(not(target_os = "macos"))]
+fn main() {
+    eprintln!("This example controls a native window, so it only runs on macOS");
+}
@@ -1,44 +1,187 @@
 use crate::{
-    entity::EntityStore,
-    layer::{InputEvent, LayerManager},
-    platform::{create_app_menu, mac::metal_renderer::MetalRenderer, MenuBar, Window},
+    caret::take_next_blink,
+    clock::set_frame_time,
+    debug::{record_frame_time, record_input_latency, record_renderer_stats},
+    entity::{EntityReadMode, EntityStore, clear_entity_store, set_entity_store},
+    image::RgbaImage,
+    interaction::coalesce::{CoalescedEvent, set_pointer_samples},
+    layer::{LayerError, LayerErrorHandler, LayerManager},
+    notifier::{Notifier, notifier},
+    quality::{BuiltinDegradation, QualityGovernor},
+    recording::{InputPlayback, InputRecorder},
+    renderer::{FrameInfo, Renderer},
+    software_renderer::SoftwareRenderer,
     task::{TaskRunner, clear_task_runner, set_task_runner},
     text_system::TextSystem,
 };
+#[cfg(target_os = "macos")]
+use crate::{
+    layer::InputEvent,
+    platform::{
+        Display, MenuBar, Window, WindowKind, create_app_menu, displays,
+        mac::{
+            AppLifecycleEvent, install_app_delegate,
+            metal_renderer::{DEFAULT_BLURRED_SHADOW_THRESHOLD, MetalRenderer, MetalTarget},
+            take_lifecycle_events, wake_event_loop,
+        },
+    },
+    vibrancy::material_regions,
+};
+use glam::Vec2;
+use std::path::PathBuf;
 use std::time::Instant;
-use tracing::{debug, info, info_span};
+use tracing::{debug, error, info, info_span};
 
+#[cfg(target_os = "macos")]
 use cocoa::base::{YES, id};
+#[cfg(target_os = "macos")]
 use metal::{CommandQueue, Device};
+#[cfg(target_os = "macos")]
 use objc::{class, msg_send, sel, sel_impl};
 
+#[cfg(target_os = "macos")]
 use std::sync::Arc;
 
 /// Callback type for handling window-level events
+#[cfg(target_os = "macos")]
 pub type WindowEventHandler = Box<dyn FnMut(&InputEvent, &Window)>;
 
-pub struct App {
+/// Callback type for frames rendered by the headless backend
+pub type FrameHandler = Box<dyn FnMut(&RgbaImage)>;
+
+/// Callbacks for app launch, reopen and termination
+#[derive(Default)]
+struct LifecycleCallbacks {
+    #[cfg(target_os = "macos")]
+    on_launch: Option<Box<dyn FnOnce(&Window)>>,
+    #[cfg(target_os = "macos")]
+    on_reopen: Option<Box<dyn FnMut(&Window)>>,
+    #[cfg(target_os = "macos")]
+    on_displays_changed: Option<Box<dyn FnMut(&[Display], &Window)>>,
+    on_will_terminate: Option<Box<dyn FnOnce()>>,
+}
+
+/// What an app renders its frames with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    /// A window drawn on the GPU with Metal
+    #[cfg(target_os = "macos")]
+    #[default]
+    Metal,
+    /// Offscreen images drawn on the CPU by the
+    /// [software renderer](crate::software_renderer::SoftwareRenderer)
+    ///
+    /// Builds and runs anywhere, including CI machines without a GPU or a
+    /// display. There's no window, so input only comes from
+    /// [`AppBuilder::playback_input`], and the app exits once no frames,
+    /// background tasks or playback are left. Frames are handed to
+    /// [`AppBuilder::on_frame`].
+    #[cfg_attr(not(target_os = "macos"), default)]
+    Headless,
+}
+
+/// What an app's frames are rendered into
+enum Surface {
+    #[cfg(target_os = "macos")]
+    Window(Box<WindowSurface>),
+    Headless(Box<HeadlessSurface>),
+}
+
+/// A window drawn with Metal
+#[cfg(target_os = "macos")]
+struct WindowSurface {
     window: Arc<Window>,
     device: Device,
     command_queue: CommandQueue,
     renderer: MetalRenderer,
+    /// Blur threshold to restore when blurred shadows are brought back
+    blurred_shadow_threshold: Option<f32>,
+}
+
+/// An image drawn by the software renderer, with no window
+struct HeadlessSurface {
+    renderer: SoftwareRenderer,
+    image: RgbaImage,
+    /// Size in points
+    size: Vec2,
+    scale_factor: f32,
+    frame_handler: Option<FrameHandler>,
+    /// Number of frames to stop after, even if more are requested
+    max_frames: Option<u64>,
+}
+
+/// What a surface needs from the app to render a frame of its layers
+struct FrameContext<'a> {
+    layer_manager: &'a mut LayerManager,
+    text_system: &'a mut TextSystem,
+    entity_store: &'a mut EntityStore,
+    elapsed_time: f32,
+    overlays_only: bool,
+}
+
+pub struct App {
+    surface: Surface,
     layer_manager: LayerManager,
     text_system: TextSystem,
     entity_store: EntityStore,
     task_runner: TaskRunner,
+    /// Frames and layer invalidations requested from other threads
+    notifier: Notifier,
     last_window_size: Option<(f32, f32)>,
     animation_frame_requested: bool,
+    /// Frame clock time a blinking caret next needs a frame at
+    next_caret_blink: Option<f32>,
     start_time: Instant,
+    #[cfg(target_os = "macos")]
     window_event_handler: Option<WindowEventHandler>,
+    #[cfg(target_os = "macos")]
+    fullscreen_handler: Option<Box<dyn FnMut(bool, &Window)>>,
+    lifecycle: LifecycleCallbacks,
+    /// Active input recorder and the path it is saved to on exit
+    input_recorder: Option<(InputRecorder, PathBuf)>,
+    /// Active input playback, which also drives the fixed frame clock
+    input_playback: Option<InputPlayback>,
+    /// Platform timestamps of input events waiting for their frame to be presented
+    pending_input_times: Vec<Instant>,
+    /// Degrades rendering quality when frames run over budget
+    quality_governor: Option<QualityGovernor>,
 }
 
 pub struct AppBuilder {
     width: f64,
     height: f64,
     title: String,
+    backend: Backend,
+    #[cfg(target_os = "macos")]
+    titlebar_hidden: bool,
+    #[cfg(target_os = "macos")]
+    window_buttons_hidden: bool,
+    #[cfg(target_os = "macos")]
+    window_kind: WindowKind,
+    #[cfg(target_os = "macos")]
+    window_shadow: bool,
+    #[cfg(target_os = "macos")]
+    window_corner_radius: f32,
+    #[cfg(target_os = "macos")]
+    start_fullscreen: bool,
+    #[cfg(target_os = "macos")]
+    blurred_shadow_threshold: Option<f32>,
+    quality_governor: Option<QualityGovernor>,
+    entity_read_mode: EntityReadMode,
+    scale_factor: Option<f32>,
     layer_setup: Box<dyn FnOnce(&mut LayerManager)>,
+    #[cfg(target_os = "macos")]
     menu_setup: Option<Box<dyn FnOnce(&str) -> MenuBar>>,
+    #[cfg(target_os = "macos")]
     window_event_handler: Option<WindowEventHandler>,
+    #[cfg(target_os = "macos")]
+    fullscreen_handler: Option<Box<dyn FnMut(bool, &Window)>>,
+    frame_handler: Option<FrameHandler>,
+    max_frames: Option<u64>,
+    lifecycle: LifecycleCallbacks,
+    layer_error_handler: Option<LayerErrorHandler>,
+    record_input_path: Option<PathBuf>,
+    playback_input_path: Option<PathBuf>,
 }
 
 pub fn app() -> AppBuilder {
@@ -51,9 +194,37 @@ impl AppBuilder {
             width: 800.0,
             height: 600.0,
             title: "Toy UI App".to_string(),
+            backend: Backend::default(),
+            #[cfg(target_os = "macos")]
+            titlebar_hidden: false,
+            #[cfg(target_os = "macos")]
+            window_buttons_hidden: false,
+            #[cfg(target_os = "macos")]
+            window_kind: WindowKind::Normal,
+            #[cfg(target_os = "macos")]
+            window_shadow: true,
+            #[cfg(target_os = "macos")]
+            window_corner_radius: 0.0,
+            #[cfg(target_os = "macos")]
+            start_fullscreen: false,
+            #[cfg(target_os = "macos")]
+            blurred_shadow_threshold: Some(DEFAULT_BLURRED_SHADOW_THRESHOLD),
+            quality_governor: None,
+            entity_read_mode: EntityReadMode::default(),
+            scale_factor: None,
             layer_setup: Box::new(|_| {}),
+            #[cfg(target_os = "macos")]
             menu_setup: None,
+            #[cfg(target_os = "macos")]
             window_event_handler: None,
+            #[cfg(target_os = "macos")]
+            fullscreen_handler: None,
+            frame_handler: None,
+            max_frames: None,
+            lifecycle: LifecycleCallbacks::default(),
+            layer_error_handler: None,
+            record_input_path: None,
+            playback_input_path: None,
         }
     }
 
@@ -68,6 +239,185 @@ impl AppBuilder {
         self
     }
 
+    /// Choose what renders the app's frames
+    ///
+    /// Defaults to a Metal window on macOS and to [`Backend::Headless`]
+    /// elsewhere.
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Set a handler called with each frame the headless backend renders
+    ///
+    /// Use it to save screenshots or compare frames against golden images
+    /// in CI. Not called for frames rendered into a window.
+    ///
+    /// # Example
+    /// ```ignore
+    /// app()
+    ///     .backend(Backend::Headless)
+    ///     .on_frame(|frame| {
+    ///         let _ = std::fs::write("frame.png", frame.to_png().unwrap());
+    ///     })
+    ///     .run();
+    /// ```
+    pub fn on_frame<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(&RgbaImage) + 'static,
+    {
+        self.frame_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Stop a headless app after it has rendered `max_frames` frames
+    ///
+    /// A headless app otherwise runs until nothing requests another frame,
+    /// so UI that keeps animating, like a loading spinner, would never
+    /// finish. Not used for apps rendering into a window.
+    pub fn max_frames(mut self, max_frames: u64) -> Self {
+        self.max_frames = Some(max_frames);
+        self
+    }
+
+    /// Hide the window's titlebar so the app can draw its own chrome
+    ///
+    /// Content extends to the top of the window under the window buttons.
+    /// Mark the app's titlebar with `.window_drag_region()` to keep the
+    /// window movable.
+    ///
+    /// # Example
+    /// ```ignore
+    /// app()
+    ///     .titlebar_hidden()
+    ///     .with_layers(|layers| {
+    ///         layers.add_ui_layer(0, LayerOptions::default().with_input(), || {
+    ///             Box::new(
+    ///                 column().child(row().height(28.0).interactive().window_drag_region()),
+    ///             )
+    ///         });
+    ///     })
+    ///     .run();
+    /// ```
+    #[cfg(target_os = "macos")]
+    pub fn titlebar_hidden(mut self) -> Self {
+        self.titlebar_hidden = true;
+        self
+    }
+
+    /// Open a Spotlight-style launcher window: a centered, floating panel
+    /// with no titlebar or window buttons and rounded corners, that doesn't
+    /// steal focus from other apps
+    ///
+    /// Sets the size to 640×400 and the corner radius to 12; call
+    /// [`size`](Self::size) or [`window_corner_radius`](Self::window_corner_radius)
+    /// afterwards to change them. Pair with a [`launcher`](crate::element::launcher)
+    /// element and a [`global_hotkey`](crate::platform::global_hotkey) to
+    /// summon it. See [`WindowKind::Panel`].
+    ///
+    /// # Example
+    /// ```ignore
+    /// app()
+    ///     .panel()
+    ///     .with_layers(|layers| {
+    ///         layers.add_ui_layer(0, LayerOptions::default().with_input(), || {
+    ///             Box::new(
+    ///                 launcher(commands())
+    ///                     .with_key("commands")
+    ///                     .on_submit(|_, command| command.run()),
+    ///             )
+    ///         });
+    ///     })
+    ///     .run();
+    /// ```
+    #[cfg(target_os = "macos")]
+    pub fn panel(mut self) -> Self {
+        self.window_kind = WindowKind::Panel;
+        self.titlebar_hidden = true;
+        self.window_buttons_hidden = true;
+        self.window_corner_radius = 12.0;
+        self.width = 640.0;
+        self.height = 400.0;
+        self
+    }
+
+    /// Show or hide the window's drop shadow
+    ///
+    /// Shown by default.
+    #[cfg(target_os = "macos")]
+    pub fn window_shadow(mut self, has_shadow: bool) -> Self {
+        self.window_shadow = has_shadow;
+        self
+    }
+
+    /// Clip the window to rounded corners of `radius` points
+    ///
+    /// Use with [`titlebar_hidden`](Self::titlebar_hidden) for HUDs and
+    /// launchers drawn entirely by the app. See
+    /// [`Window::set_corner_radius`].
+    #[cfg(target_os = "macos")]
+    pub fn window_corner_radius(mut self, radius: f32) -> Self {
+        self.window_corner_radius = radius;
+        self
+    }
+
+    /// Open the window in its own fullscreen space
+    ///
+    /// The user can still leave fullscreen with the green window button or
+    /// the Escape key; use [`Window::set_fullscreen`] to control it after
+    /// launch.
+    #[cfg(target_os = "macos")]
+    pub fn start_fullscreen(mut self, fullscreen: bool) -> Self {
+        self.start_fullscreen = fullscreen;
+        self
+    }
+
+    /// Set the blur radius, in points, above which shadows are blurred on the
+    /// GPU instead of approximated, or `None` to always approximate
+    ///
+    /// Defaults to 16. Blurred shadows look right at any radius and are
+    /// cached per frame size, so they're cheap once rendered; lower the
+    /// threshold for more accurate medium shadows.
+    #[cfg(target_os = "macos")]
+    pub fn blurred_shadow_threshold(mut self, threshold: Option<f32>) -> Self {
+        self.blurred_shadow_threshold = threshold;
+        self
+    }
+
+    /// Degrade rendering quality when frames run over budget, restoring it
+    /// when they speed up again
+    ///
+    /// Off by default. See [`QualityGovernor`] for the built-in degradations
+    /// and for adding the app's own.
+    pub fn quality_governor(mut self, governor: QualityGovernor) -> Self {
+        self.quality_governor = Some(governor);
+        self
+    }
+
+    /// Set how entity reads see updates made while a frame is painting
+    ///
+    /// Only affects types registered with
+    /// [`EntityStore::double_buffer`]. Defaults to
+    /// [`EntityReadMode::FrameConsistent`].
+    pub fn entity_read_mode(mut self, mode: EntityReadMode) -> Self {
+        self.entity_read_mode = mode;
+        self
+    }
+
+    /// Render at `scale` device pixels per point, whatever the display's
+    /// backing scale
+    ///
+    /// Simulates 1x, 2x or 3x displays, so issues tied to the scale factor
+    /// can be reproduced on any machine and golden images compared
+    /// deterministically. The window keeps its size in points, and the
+    /// system resamples the output to fit it. Scales that aren't positive
+    /// are ignored. The headless backend renders at this scale, or 1x if
+    /// it isn't forced.
+    pub fn force_scale_factor(mut self, scale: f32) -> Self {
+        self.scale_factor = Some(scale).filter(|scale| scale.is_finite() && *scale > 0.0);
+        self
+    }
+
     pub fn with_layers<F>(mut self, setup: F) -> Self
     where
         F: FnOnce(&mut LayerManager) + 'static,
@@ -96,6 +446,7 @@ impl AppBuilder {
     ///     })
     ///     .run();
     /// ```
+    #[cfg(target_os = "macos")]
     pub fn with_menu_bar<F>(mut self, setup: F) -> Self
     where
         F: FnOnce(&str) -> MenuBar + 'static,
@@ -128,6 +479,7 @@ impl AppBuilder {
     ///     })
     ///     .run();
     /// ```
+    #[cfg(target_os = "macos")]
     pub fn on_window_event<F>(mut self, handler: F) -> Self
     where
         F: FnMut(&InputEvent, &Window) + 'static,
@@ -136,14 +488,155 @@ impl AppBuilder {
         self
     }
 
+    /// Set a handler called when the window enters or leaves fullscreen,
+    /// with whether it's now fullscreen
+    ///
+    /// Called once the transition finishes, whether the app or the user
+    /// started it. Entities can be updated from the handler, e.g. to hide
+    /// custom window chrome while fullscreen.
+    ///
+    /// # Example
+    /// ```ignore
+    /// app()
+    ///     .on_fullscreen_change(move |fullscreen, _window| {
+    ///         update_entity(&chrome, |c| c.visible = !fullscreen);
+    ///     })
+    ///     .run();
+    /// ```
+    #[cfg(target_os = "macos")]
+    pub fn on_fullscreen_change<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(bool, &Window) + 'static,
+    {
+        self.fullscreen_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Set a handler called once the app has finished launching, before the
+    /// first frame is rendered
+    ///
+    /// Entities can be created and read from the handler, e.g. to restore
+    /// state saved by [`AppBuilder::on_will_terminate`].
+    #[cfg(target_os = "macos")]
+    pub fn on_launch<F>(mut self, handler: F) -> Self
+    where
+        F: FnOnce(&Window) + 'static,
+    {
+        self.lifecycle.on_launch = Some(Box::new(handler));
+        self
+    }
+
+    /// Set a handler called when the dock icon is clicked while the app is
+    /// running
+    ///
+    /// Minimized windows are restored as usual; use this to do more, such as
+    /// bringing the window to the front.
+    #[cfg(target_os = "macos")]
+    pub fn on_reopen<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(&Window) + 'static,
+    {
+        self.lifecycle.on_reopen = Some(Box::new(handler));
+        self
+    }
+
+    /// Set a handler called with the connected displays when one is
+    /// connected, disconnected, rearranged or changes resolution
+    ///
+    /// Use it to move the window back on screen or onto a preferred
+    /// display with [`Window::move_to_display`].
+    #[cfg(target_os = "macos")]
+    pub fn on_displays_changed<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(&[Display], &Window) + 'static,
+    {
+        self.lifecycle.on_displays_changed = Some(Box::new(handler));
+        self
+    }
+
+    /// Set a handler called once before the app exits, whether it was quit
+    /// from the menu or its window was closed
+    ///
+    /// Entities can still be read from the handler, so it's the place to
+    /// flush persisted state and release resources.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let storage = Storage::default();
+    /// app()
+    ///     .on_will_terminate(move || {
+    ///         if let Some(notes) = read_entity(&notes, |n| n.clone()) {
+    ///             let _ = storage.save("notes", &notes);
+    ///         }
+    ///     })
+    ///     .run();
+    /// ```
+    pub fn on_will_terminate<F>(mut self, handler: F) -> Self
+    where
+        F: FnOnce() + 'static,
+    {
+        self.lifecycle.on_will_terminate = Some(Box::new(handler));
+        self
+    }
+
+    /// Set a handler that is called when a layer panics.
+    ///
+    /// Each layer renders and handles input inside a panic boundary. When a layer
+    /// panics, it is replaced by an error placeholder for that frame, the panic is
+    /// logged to the layer manager's console, and this handler is invoked. Other
+    /// layers keep rendering and receiving input.
+    ///
+    /// # Example
+    /// ```ignore
+    /// app()
+    ///     .title("My App")
+    ///     .on_layer_error(|error| {
+    ///         eprintln!("Layer {} crashed: {}", error.layer_index, error.message);
+    ///     })
+    ///     .run();
+    /// ```
+    pub fn on_layer_error<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(&LayerError) + 'static,
+    {
+        self.layer_error_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Record mouse and keyboard input to a file.
+    ///
+    /// Events are tagged with the frame they were processed on and written as
+    /// JSON when the app exits. Replay the file with [`AppBuilder::playback_input`].
+    pub fn record_input(mut self, path: impl Into<PathBuf>) -> Self {
+        self.record_input_path = Some(path.into());
+        self
+    }
+
+    /// Replay input previously captured with [`AppBuilder::record_input`].
+    ///
+    /// Live mouse and keyboard input is ignored while the recording plays, and
+    /// the app runs on a fixed frame clock so playback is deterministic.
+    /// Window events still come from the live window.
+    pub fn playback_input(mut self, path: impl Into<PathBuf>) -> Self {
+        self.playback_input_path = Some(path.into());
+        self
+    }
+
     pub fn run(mut self) {
         let layer_setup = std::mem::replace(&mut self.layer_setup, Box::new(|_| {}));
-        let window_event_handler = self.window_event_handler.take();
-        let app = self.build(window_event_handler);
+        let app = match self.backend {
+            #[cfg(target_os = "macos")]
+            Backend::Metal => {
+                let window_event_handler = self.window_event_handler.take();
+                self.build(window_event_handler)
+            }
+            Backend::Headless => self.build_headless(),
+        };
         app.run(layer_setup);
     }
 
-    fn build(self, window_event_handler: Option<WindowEventHandler>) -> App {
+    #[cfg(target_os = "macos")]
+    fn build(mut self, window_event_handler: Option<WindowEventHandler>) -> App {
         let _build_span = info_span!("app_build").entered();
         let build_start = Instant::now();
 
@@ -152,11 +645,12 @@ impl AppBuilder {
         info!("Initializing NSApplication");
         let ns_app: id = unsafe { msg_send![class!(NSApplication), sharedApplication] };
         let _: () = unsafe { msg_send![ns_app, setActivationPolicy: 0] }; // NSApplicationActivationPolicyRegular
+        install_app_delegate();
         info!("NSApplication initialized in {:?}", start.elapsed());
 
         // Create app menu
         let start = Instant::now();
-        if let Some(menu_setup) = self.menu_setup {
+        if let Some(menu_setup) = self.menu_setup.take() {
             let menu_bar = menu_setup(&self.title);
             menu_bar.build();
         } else {
@@ -177,7 +671,29 @@ impl AppBuilder {
         // Create window
         let start = Instant::now();
         info!("Creating window: {}x{}", self.width, self.height);
-        let window = Window::new(self.width, self.height, &self.title, &device);
+        let window = Window::with_kind(
+            self.width,
+            self.height,
+            &self.title,
+            &device,
+            self.window_kind,
+        );
+        if self.titlebar_hidden {
+            window.set_titlebar_hidden(true);
+        }
+        if self.window_buttons_hidden {
+            window.set_window_buttons_hidden(true);
+        }
+        if !self.window_shadow {
+            window.set_has_shadow(false);
+        }
+        if self.window_corner_radius > 0.0 {
+            window.set_corner_radius(self.window_corner_radius);
+        }
+        if let Some(scale) = self.scale_factor {
+            info!("Forcing scale factor {}", scale);
+            window.force_scale_factor(Some(scale));
+        }
         info!("Window created in {:?}", start.elapsed());
 
         // Create and initialize renderer
@@ -187,13 +703,9 @@ impl AppBuilder {
         if let Err(e) = renderer.initialize() {
             panic!("Failed to initialize renderer: {}", e);
         }
+        renderer.set_blurred_shadow_threshold(self.blurred_shadow_threshold);
         info!("Metal renderer initialized in {:?}", start.elapsed());
 
-        // Create layer manager
-        let start = Instant::now();
-        let _layer_manager = LayerManager::new();
-        info!("Layer manager created in {:?}", start.elapsed());
-
         // Create text system
         let start = Instant::now();
         info!("Creating text system");
@@ -202,31 +714,272 @@ impl AppBuilder {
 
         // Activate app and bring to front
         let start = Instant::now();
+        let _: () = unsafe { msg_send![ns_app, finishLaunching] };
         let _: () = unsafe { msg_send![ns_app, activateIgnoringOtherApps: YES] };
         info!("App activated in {:?}", start.elapsed());
 
+        if self.start_fullscreen {
+            window.set_fullscreen(true);
+        }
+
+        let surface = WindowSurface {
+            window,
+            device,
+            command_queue,
+            renderer,
+            blurred_shadow_threshold: self.blurred_shadow_threshold,
+        };
+        let mut app = self.build_app(Surface::Window(Box::new(surface)), text_system);
+        app.window_event_handler = window_event_handler;
+        // Updates queued from other threads wake the loop so they're applied
+        app.entity_store.set_waker(wake_event_loop);
+        app.notifier.set_waker(wake_event_loop);
+
         info!("Total app build time: {:?}", build_start.elapsed());
+        app
+    }
+
+    fn build_headless(mut self) -> App {
+        let _build_span = info_span!("app_build").entered();
+
+        let size = Vec2::new(self.width as f32, self.height as f32);
+        let scale_factor = self.scale_factor.unwrap_or(1.0);
+        info!(
+            "Rendering {} headless at {}x{} and {}x scale",
+            self.title, size.x, size.y, scale_factor
+        );
+        let mut renderer = SoftwareRenderer::new();
+        if let Err(e) = renderer.initialize() {
+            panic!("Failed to initialize renderer: {}", e);
+        }
+        let pixels = (size * scale_factor).ceil();
+        let surface = HeadlessSurface {
+            renderer,
+            image: RgbaImage::new(pixels.x as u32, pixels.y as u32),
+            size,
+            scale_factor,
+            frame_handler: self.frame_handler.take(),
+            max_frames: self.max_frames,
+        };
+        self.build_app(Surface::Headless(Box::new(surface)), TextSystem::headless())
+    }
+
+    /// Set up everything the app needs besides what it renders into
+    fn build_app(self, surface: Surface, text_system: TextSystem) -> App {
+        // Create layer manager
+        let start = Instant::now();
+        let mut layer_manager = LayerManager::new();
+        if let Some(handler) = self.layer_error_handler {
+            layer_manager.set_error_handler(handler);
+        }
+        info!("Layer manager created in {:?}", start.elapsed());
 
         // Create entity store
-        let entity_store = EntityStore::new();
+        let mut entity_store = EntityStore::new();
+        entity_store.set_read_mode(self.entity_read_mode);
 
         // Create task runner for background tasks
         let task_runner = TaskRunner::new();
 
+        // Set up input recording and playback
+        let input_recorder = self
+            .record_input_path
+            .map(|path| (InputRecorder::new(), path));
+        let input_playback =
+            self.playback_input_path
+                .and_then(|path| match InputPlayback::load(&path) {
+                    Ok(playback) => {
+                        info!(
+                            "Playing back {} recorded input events from {}",
+                            playback.recording().events.len(),
+                            path.display()
+                        );
+                        Some(playback)
+                    }
+                    Err(e) => {
+                        error!("Failed to load input recording {}: {}", path.display(), e);
+                        None
+                    }
+                });
+
         App {
-            window,
-            device,
-            command_queue,
-            renderer,
-            layer_manager: _layer_manager,
+            surface,
+            layer_manager,
             text_system,
             entity_store,
             task_runner,
+            notifier: notifier(),
             last_window_size: None,
             animation_frame_requested: false,
+            next_caret_blink: None,
             start_time: Instant::now(),
-            window_event_handler,
+            #[cfg(target_os = "macos")]
+            window_event_handler: None,
+            #[cfg(target_os = "macos")]
+            fullscreen_handler: self.fullscreen_handler,
+            lifecycle: self.lifecycle,
+            input_recorder,
+            input_playback,
+            pending_input_times: Vec::new(),
+            quality_governor: self.quality_governor,
+        }
+    }
+}
+
+impl Surface {
+    /// Size in points
+    fn size(&self) -> (f32, f32) {
+        match self {
+            #[cfg(target_os = "macos")]
+            Surface::Window(surface) => surface.window.size(),
+            Surface::Headless(surface) => surface.size.into(),
+        }
+    }
+
+    /// Apply or lift a built-in degradation the renderer handles
+    #[cfg(target_os = "macos")]
+    fn degrade(&mut self, builtin: BuiltinDegradation, degraded: bool) {
+        let Surface::Window(surface) = self else {
+            return;
+        };
+        let renderer = &mut surface.renderer;
+        match builtin {
+            BuiltinDegradation::BlurredShadows => {
+                let threshold = surface.blurred_shadow_threshold;
+                renderer.set_blurred_shadow_threshold(if degraded { None } else { threshold });
+            }
+            BuiltinDegradation::Shadows => renderer.set_shadows_enabled(!degraded),
+            BuiltinDegradation::Antialiasing => renderer.set_antialiasing(!degraded),
+            BuiltinDegradation::CoarseText => {}
+        }
+    }
+
+    /// The software renderer has no quality settings to lower
+    #[cfg(not(target_os = "macos"))]
+    fn degrade(&mut self, _builtin: BuiltinDegradation, _degraded: bool) {}
+}
+
+#[cfg(target_os = "macos")]
+impl WindowSurface {
+    /// Render a frame into the window's next drawable, returning whether
+    /// another frame was requested, or `None` if there was no drawable to
+    /// render into, and how long was spent waiting for the drawable
+    fn render(&mut self, frame: &mut FrameContext) -> (Option<bool>, std::time::Duration) {
+        self.renderer.begin_frame();
+
+        // Get the next drawable from the Metal layer
+        let start = Instant::now();
+        let drawable = {
+            let _drawable_span = info_span!("get_next_drawable").entered();
+            self.window.metal_layer().next_drawable()
+        };
+        let drawable_wait = start.elapsed();
+        let Some(drawable) = drawable else {
+            eprintln!("Failed to get next drawable");
+            return (None, drawable_wait);
+        };
+        debug!("Next drawable acquired in {:?}", drawable_wait);
+
+        // Get window size and scale factor
+        let start = Instant::now();
+        let size = self.window.size();
+        let scale_factor = self.window.scale_factor();
+        debug!("Window size/scale retrieved in {:?}", start.elapsed());
+
+        // Create command buffer
+        let command_buffer = {
+            let start = Instant::now();
+            let _cmd_span = info_span!("create_command_buffer").entered();
+            let buffer = self.command_queue.new_command_buffer();
+            debug!("Command buffer created in {:?}", start.elapsed());
+            buffer
+        };
+
+        let mut target = MetalTarget {
+            command_buffer,
+            drawable,
+        };
+        let mut animation_frame_requested = frame.render_layers(
+            &mut self.renderer,
+            &mut target,
+            (size.0, size.1).into(),
+            scale_factor,
+        );
+        if !frame.overlays_only {
+            self.window.set_material_regions(&material_regions());
+        }
+
+        // Backdrop blurs copy from the drawable, so it's only made readable
+        // while one is drawn. A blur skipped because the drawable wasn't
+        // readable yet shows on the next frame
+        let readable = self.renderer.needs_readable_drawable();
+        let layer = self.window.metal_layer();
+        if layer.framebuffer_only() == readable {
+            layer.set_framebuffer_only(!readable);
+            animation_frame_requested |= readable;
+        }
+
+        // Present drawable and commit
+        {
+            let start = Instant::now();
+            let _present_span = info_span!("present_and_commit").entered();
+            command_buffer.present_drawable(drawable);
+            command_buffer.commit();
+            debug!("Present and commit completed in {:?}", start.elapsed());
+        }
+
+        (Some(animation_frame_requested), drawable_wait)
+    }
+}
+
+impl HeadlessSurface {
+    /// Render a frame into the image and hand it to the frame handler,
+    /// returning whether another frame was requested
+    fn render(&mut self, frame: &mut FrameContext) -> bool {
+        self.renderer.begin_frame();
+        let animation_frame_requested = frame.render_layers(
+            &mut self.renderer,
+            &mut self.image,
+            self.size,
+            self.scale_factor,
+        );
+        if let Some(ref mut handler) = self.frame_handler {
+            handler(&self.image);
         }
+        animation_frame_requested
+    }
+}
+
+impl FrameContext<'_> {
+    /// Render every layer with `renderer` into `target`, returning whether
+    /// any of them requested another frame
+    fn render_layers<R: Renderer>(
+        &mut self,
+        renderer: &mut R,
+        target: &mut R::Target<'_>,
+        size: Vec2,
+        scale_factor: f32,
+    ) -> bool {
+        let _render_span = info_span!("layer_manager_render").entered();
+        let frame = FrameInfo {
+            size,
+            scale_factor,
+            elapsed_time: self.elapsed_time,
+        };
+        let animation_frame_requested = if self.overlays_only {
+            self.layer_manager.render_overlays(
+                renderer,
+                target,
+                frame,
+                self.text_system,
+                self.entity_store,
+            )
+        } else {
+            self.layer_manager
+                .render(renderer, target, frame, self.text_system, self.entity_store)
+        };
+        record_renderer_stats(renderer.stats(self.text_system));
+        animation_frame_requested
     }
 }
 
@@ -248,6 +1001,7 @@ impl App {
         let mut frame_count = 0u64;
         let loop_start = Instant::now();
         let mut first_frame_completed = false;
+        let mut last_frame_start: Option<Instant> = None;
 
         loop {
             // Set task runner for this frame (allows spawn_task to work)
@@ -259,34 +1013,143 @@ impl App {
                 debug!("Processed {} completed background tasks", completed_tasks);
             }
 
-            // Use non-blocking event handling if animation frame was requested
-            // or if there are pending background tasks
-            let should_continue = if self.animation_frame_requested || self.task_runner.has_pending() {
-                self.window.handle_events_non_blocking()
-            } else {
-                self.window.handle_events()
+            // Use non-blocking event handling if animation frame was requested,
+            // if there are pending background tasks, or while playing back input
+            let playback_active = self
+                .input_playback
+                .as_ref()
+                .is_some_and(|playback| !playback.is_finished(frame_count));
+            let busy =
+                self.animation_frame_requested || self.task_runner.has_pending() || playback_active;
+            let should_continue = match self.surface {
+                #[cfg(target_os = "macos")]
+                Surface::Window(ref surface) => {
+                    if busy {
+                        surface.window.handle_events_non_blocking()
+                    } else if let Some(at) = self.next_caret_blink {
+                        // Sleep until the caret blinks, unless input comes first
+                        let deadline =
+                            self.start_time + std::time::Duration::from_secs_f32(at.max(0.0));
+                        surface.window.handle_events_until(deadline)
+                    } else {
+                        surface.window.handle_events()
+                    }
+                }
+                // Nothing reaches a headless app from outside, so it's done
+                // once it has drawn a frame and has nothing left to do, or
+                // has drawn as many frames as it was allowed
+                Surface::Headless(ref surface) => {
+                    (busy || frame_count == 0)
+                        && surface.max_frames.is_none_or(|max| frame_count < max)
+                }
             };
 
-            if !should_continue {
+            // Apply entity updates queued through store handles, including
+            // any that woke the loop
+            let queued_updates = self.entity_store.apply_queued_updates();
+
+            // Invalidate layers as requested from other threads
+            let notification = self.notifier.take();
+            if let Some(ref notification) = notification {
+                if notification.invalidate_all {
+                    self.layer_manager.invalidate_all();
+                }
+                for &z_index in &notification.invalidated_layers {
+                    self.layer_manager.invalidate_layer(z_index);
+                }
+            }
+
+            #[cfg(target_os = "macos")]
+            let terminate_requested = self.handle_lifecycle_events();
+            #[cfg(not(target_os = "macos"))]
+            let terminate_requested = false;
+
+            if !should_continue || terminate_requested {
                 clear_task_runner();
+                self.shutdown();
                 break;
             }
 
+            // Playback uses a fixed frame clock so animations replay
+            // identically. Input handlers see this frame's time too.
+            let elapsed_time = match self.input_playback {
+                Some(ref playback) => playback.elapsed_time(frame_count),
+                None => self.start_time.elapsed().as_secs_f32(),
+            };
+            set_frame_time(elapsed_time);
+
             // Process input events
-            let input_events = self.window.get_pending_input_events();
+            let mut input_events: Vec<CoalescedEvent> = match self.surface {
+                #[cfg(target_os = "macos")]
+                Surface::Window(ref surface) => surface.window.take_coalesced_input_events(),
+                Surface::Headless(_) => Vec::new(),
+            };
             for event in &input_events {
-                // First, call the window event handler if configured
-                if let Some(ref mut handler) = self.window_event_handler {
-                    handler(event, &self.window);
+                if !event.event.is_window_event() {
+                    self.pending_input_times.push(event.timestamp);
+                }
+            }
+            if let Some(ref mut playback) = self.input_playback {
+                // Live mouse and keyboard input is replaced by the recording
+                input_events.retain(|event| event.event.is_window_event());
+                input_events.extend(playback.events_for_frame(frame_count, Instant::now()));
+                self.pending_input_times.clear();
+            }
+            for coalesced in &input_events {
+                if let Some((ref mut recorder, _)) = self.input_recorder {
+                    recorder.record(frame_count, coalesced);
+                }
+                let CoalescedEvent { event, samples, .. } = coalesced;
+
+                // First, call the window event handlers if configured
+                #[cfg(target_os = "macos")]
+                if let Surface::Window(ref surface) = self.surface {
+                    if let Some(ref mut handler) = self.window_event_handler {
+                        handler(event, &surface.window);
+                    }
+                    if let Some(ref mut handler) = self.fullscreen_handler {
+                        let fullscreen = match event {
+                            InputEvent::WindowEnteredFullscreen => Some(true),
+                            InputEvent::WindowExitedFullscreen => Some(false),
+                            _ => None,
+                        };
+                        if let Some(fullscreen) = fullscreen {
+                            set_entity_store(&mut self.entity_store);
+                            handler(fullscreen, &surface.window);
+                            clear_entity_store();
+                        }
+                    }
                 }
                 // Then pass to layer manager for UI handling
+                set_pointer_samples(samples.clone());
                 self.layer_manager.handle_input(event);
             }
+            set_pointer_samples(Vec::new());
+
+            // Frames that only advance a caret blink can reuse the last layout
+            let overlays_only = self.next_caret_blink.is_some()
+                && !self.animation_frame_requested
+                && input_events.is_empty()
+                && completed_tasks == 0
+                && queued_updates == 0
+                && notification.is_none()
+                && !playback_active;
 
             let frame_start = Instant::now();
             let _frame_span = info_span!("frame", frame_number = frame_count).entered();
-            self.render_frame();
+            let drawable_wait = self.render_frame(elapsed_time, overlays_only);
             let frame_time = frame_start.elapsed();
+            // Waiting on a drawable is vsync, not work that degrading helps
+            let cpu_time = frame_time.saturating_sub(drawable_wait);
+            let interval =
+                last_frame_start.map_or(std::time::Duration::ZERO, |last| frame_start - last);
+            last_frame_start = Some(frame_start);
+            record_frame_time(cpu_time, interval);
+            self.govern_quality(cpu_time);
+
+            if let Some((ref mut recorder, _)) = self.input_recorder {
+                recorder.end_frame(frame_count);
+            }
 
             if !first_frame_completed {
                 info!(
@@ -310,7 +1173,7 @@ impl App {
             clear_task_runner();
 
             // Frame rate limiting: target 120 FPS (8.33ms per frame)
-            if self.animation_frame_requested || self.task_runner.has_pending() {
+            if self.animation_frame_requested || self.task_runner.has_pending() || playback_active {
                 const TARGET_FRAME_TIME: std::time::Duration =
                     std::time::Duration::from_micros(8_333);
                 if let Some(sleep_duration) = TARGET_FRAME_TIME.checked_sub(frame_time) {
@@ -320,14 +1183,102 @@ impl App {
         }
     }
 
-    fn render_frame(&mut self) {
+    /// Run the handlers for launch, reopen and display changes reported by
+    /// the app delegate, returning whether the app was asked to quit
+    #[cfg(target_os = "macos")]
+    fn handle_lifecycle_events(&mut self) -> bool {
+        let Surface::Window(ref surface) = self.surface else {
+            return false;
+        };
+        let mut terminate_requested = false;
+        for event in take_lifecycle_events() {
+            match event {
+                AppLifecycleEvent::Launched => {
+                    if let Some(handler) = self.lifecycle.on_launch.take() {
+                        set_entity_store(&mut self.entity_store);
+                        handler(&surface.window);
+                        clear_entity_store();
+                    }
+                }
+                AppLifecycleEvent::Reopen { .. } => {
+                    if let Some(ref mut handler) = self.lifecycle.on_reopen {
+                        set_entity_store(&mut self.entity_store);
+                        handler(&surface.window);
+                        clear_entity_store();
+                    }
+                }
+                AppLifecycleEvent::DisplaysChanged => {
+                    surface.window.restore_forced_scale_factor();
+                    if let Some(ref mut handler) = self.lifecycle.on_displays_changed {
+                        set_entity_store(&mut self.entity_store);
+                        handler(&displays(), &surface.window);
+                        clear_entity_store();
+                    }
+                }
+                AppLifecycleEvent::TerminateRequested => terminate_requested = true,
+            }
+        }
+        terminate_requested
+    }
+
+    /// Let the quality governor react to the CPU time of the last frame
+    fn govern_quality(&mut self, frame_time: std::time::Duration) {
+        let Some(ref mut governor) = self.quality_governor else {
+            return;
+        };
+        let surface = &mut self.surface;
+        let text_system = &mut self.text_system;
+        let changed = governor.record_frame(frame_time, |builtin, degraded| match builtin {
+            BuiltinDegradation::CoarseText => text_system.set_coarse_glyph_sizes(degraded),
+            _ => surface.degrade(builtin, degraded),
+        });
+        if changed {
+            info!(
+                "Frame took {:?} against a {:?} budget; degraded: [{}]",
+                frame_time,
+                governor.budget(),
+                governor.degraded().collect::<Vec<_>>().join(", ")
+            );
+            // Draw a frame with the change even if nothing is animating
+            self.animation_frame_requested = true;
+        }
+    }
+
+    /// Run the will-terminate handler and save anything pending before exit
+    fn shutdown(&mut self) {
+        info!("Shutting down");
+        if let Some(handler) = self.lifecycle.on_will_terminate.take() {
+            set_entity_store(&mut self.entity_store);
+            handler();
+            clear_entity_store();
+        }
+        self.save_input_recording();
+    }
+
+    /// Write the input recording to disk, if recording is enabled
+    fn save_input_recording(&mut self) {
+        if let Some((recorder, path)) = self.input_recorder.take() {
+            let event_count = recorder.event_count();
+            match recorder.finish().save(&path) {
+                Ok(()) => info!(
+                    "Saved {} recorded input events to {}",
+                    event_count,
+                    path.display()
+                ),
+                Err(e) => error!("Failed to save input recording {}: {}", path.display(), e),
+            }
+        }
+    }
+
+    /// Render a frame, returning how long was spent waiting for a drawable
+    fn render_frame(&mut self, elapsed_time: f32, overlays_only: bool) -> std::time::Duration {
         let frame_start = Instant::now();
 
         // Clear text system frame caches
         self.text_system.begin_frame();
 
         // Check if window size changed
-        let current_size = self.window.size();
+        let current_size = self.surface.size();
         if let Some(last_size) = self.last_window_size {
             if last_size != current_size {
                 debug!("Window resized from {:?} to {:?}", last_size, current_size);
@@ -337,78 +1288,166 @@ impl App {
         }
         self.last_window_size = Some(current_size);
 
-        // Get the next drawable from the Metal layer
-        let drawable = {
-            let start = Instant::now();
-            let _drawable_span = info_span!("get_next_drawable").entered();
-            match self.window.metal_layer().next_drawable() {
-                Some(drawable) => {
-                    debug!("Next drawable acquired in {:?}", start.elapsed());
-                    drawable
-                }
-                None => {
-                    eprintln!("Failed to get next drawable");
-                    return;
-                }
+        let mut frame = FrameContext {
+            layer_manager: &mut self.layer_manager,
+            text_system: &mut self.text_system,
+            entity_store: &mut self.entity_store,
+            elapsed_time,
+            overlays_only,
+        };
+        let (animation_frame_requested, drawable_wait) = match self.surface {
+            #[cfg(target_os = "macos")]
+            Surface::Window(ref mut surface) => surface.render(&mut frame),
+            Surface::Headless(ref mut surface) => {
+                (Some(surface.render(&mut frame)), std::time::Duration::ZERO)
             }
         };
-
-        // Get window size and scale factor
-        let start = Instant::now();
-        let size = self.window.size();
-        let scale_factor = self.window.scale_factor();
-        debug!("Window size/scale retrieved in {:?}", start.elapsed());
-
-        // Create command buffer
-        let command_buffer = {
-            let start = Instant::now();
-            let _cmd_span = info_span!("create_command_buffer").entered();
-            let buffer = self.command_queue.new_command_buffer();
-            debug!("Command buffer created in {:?}", start.elapsed());
-            buffer
+        let Some(animation_frame_requested) = animation_frame_requested else {
+            return drawable_wait;
         };
+        self.animation_frame_requested = animation_frame_requested;
+        self.next_caret_blink = take_next_blink();
 
-        // Render all layers using the layer manager
-        {
-            let _render_span = info_span!("layer_manager_render").entered();
-
-            // Calculate elapsed time since app start for animations
-            let elapsed_time = self.start_time.elapsed().as_secs_f32();
-
-            // Render all layers and check if any requested animation frame
-            self.animation_frame_requested = self.layer_manager.render(
-                &mut self.renderer,
-                &command_buffer,
-                &drawable,
-                (size.0, size.1).into(),
-                &mut self.text_system,
-                &mut self.entity_store,
-                scale_factor,
-                elapsed_time,
-            );
+        // Input handled this frame is now on its way to the screen
+        let presented = Instant::now();
+        for received in self.pending_input_times.drain(..) {
+            record_input_latency(presented.saturating_duration_since(received));
         }
 
-        // Present drawable and commit
-        {
-            let start = Instant::now();
-            let _present_span = info_span!("present_and_commit").entered();
-            command_buffer.present_drawable(&drawable);
-            command_buffer.commit();
-            debug!("Present and commit completed in {:?}", start.elapsed());
+        debug!("Total frame time: {:?}", frame_start.elapsed());
+        drawable_wait
+    }
+
+    /// The Metal window being rendered into, if the app has one
+    #[cfg(target_os = "macos")]
+    fn window_surface(&self) -> Option<&WindowSurface> {
+        match self.surface {
+            Surface::Window(ref surface) => Some(surface),
+            Surface::Headless(_) => None,
         }
+    }
 
-        debug!("Total frame time: {:?}", frame_start.elapsed());
+    #[cfg(target_os = "macos")]
+    pub fn device(&self) -> Option<&Device> {
+        self.window_surface().map(|surface| &surface.device)
+    }
+
+    #[cfg(target_os = "macos")]
+    pub fn command_queue(&self) -> Option<&CommandQueue> {
+        self.window_surface().map(|surface| &surface.command_queue)
+    }
+
+    #[cfg(target_os = "macos")]
+    pub fn window(&self) -> Option<&Window> {
+        self.window_surface().map(|surface| &*surface.window)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        color::colors,
+        element::{button, container},
+        layer::LayerOptions,
+    };
+    use std::{cell::RefCell, rc::Rc};
+
+    #[test]
+    fn test_headless_app_renders_until_idle() {
+        let frames = Rc::new(RefCell::new(Vec::new()));
+        let captured = frames.clone();
+        app()
+            .backend(Backend::Headless)
+            .size(4.0, 2.0)
+            .force_scale_factor(2.0)
+            .with_layers(|layers| {
+                layers.add_ui_layer(0, LayerOptions::default(), || {
+                    Box::new(container().size(2.0, 2.0).background(colors::RED))
+                });
+            })
+            .on_frame(move |frame| captured.borrow_mut().push(frame.clone()))
+            .run();
+
+        let frames = frames.borrow();
+        assert_eq!(frames.len(), 1);
+        assert_eq!((frames[0].width(), frames[0].height()), (8, 4));
+        assert_eq!(frames[0].pixel(1, 1), Some([255, 0, 0, 255]));
+        assert_eq!(frames[0].pixel(6, 1), Some([242, 242, 242, 255]));
+    }
+
+    #[test]
+    fn test_headless_app_stops_after_max_frames() {
+        let frame_count = Rc::new(RefCell::new(0));
+        let counter = frame_count.clone();
+        app()
+            .backend(Backend::Headless)
+            .size(100.0, 40.0)
+            .max_frames(3)
+            .with_layers(|layers| {
+                layers.add_ui_layer(0, LayerOptions::default(), || {
+                    // The spinner requests a frame every time it's painted
+                    Box::new(button("Saving").loading(true))
+                });
+            })
+            .on_frame(move |_| *counter.borrow_mut() += 1)
+            .run();
+
+        assert_eq!(*frame_count.borrow(), 3);
+    }
+
+    #[test]
+    fn test_will_terminate_runs_once_after_the_last_frame() {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let on_frame = calls.clone();
+        let on_terminate = calls.clone();
+        app()
+            .backend(Backend::Headless)
+            .size(4.0, 2.0)
+            .on_frame(move |_| on_frame.borrow_mut().push("frame"))
+            .on_will_terminate(move || {
+                // Entities can still be read while shutting down
+                assert!(crate::entity::try_with_entity_store(|_| ()).is_some());
+                on_terminate.borrow_mut().push("terminate");
+            })
+            .run();
+
+        assert_eq!(*calls.borrow(), ["frame", "terminate"]);
+    }
+
+    #[test]
+    fn test_force_scale_factor_ignores_invalid_scales() {
+        assert_eq!(app().force_scale_factor(2.0).scale_factor, Some(2.0));
+        assert_eq!(app().force_scale_factor(0.0).scale_factor, None);
+        assert_eq!(app().force_scale_factor(f32::NAN).scale_factor, None);
     }
 
-    pub fn device(&self) -> &Device {
-        &self.device
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn test_window_options_default_to_a_normal_window() {
+        let builder = app();
+        assert_eq!(builder.backend, Backend::Metal);
+        assert_eq!(builder.window_kind, WindowKind::Normal);
+        assert!(builder.window_shadow);
+        assert_eq!(builder.window_corner_radius, 0.0);
     }
 
-    pub fn command_queue(&self) -> &CommandQueue {
-        &self.command_queue
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn test_panel_sets_window_options() {
+        let builder = app().panel();
+        assert_eq!(builder.window_kind, WindowKind::Panel);
+        assert!(builder.titlebar_hidden);
+        assert!(builder.window_buttons_hidden);
+        assert_eq!(builder.window_corner_radius, 12.0);
     }
 
-    pub fn window(&self) -> &Window {
-        &self.window
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn test_window_options_override_panel_defaults() {
+        let builder = app().panel().window_shadow(false).window_corner_radius(4.0);
+        assert_eq!(builder.window_kind, WindowKind::Panel);
+        assert!(!builder.window_shadow);
+        assert_eq!(builder.window_corner_radius, 4.0);
     }
 }
@@ -0,0 +1,250 @@
+//! Shared text caret blinking and rendering
+//!
+//! Text elements paint their caret with [`PaintContext::paint_caret`], which
+//! records where the caret sits in the draw list. Its visibility is decided
+//! by the frame clock when the layer is presented, so a blink frame only
+//! redraws the cached layer content instead of rebuilding its layout, and
+//! the app sleeps until the next blink instead of drawing every frame.
+//!
+//! [`PaintContext::paint_caret`]: crate::render::PaintContext::paint_caret
+
+use crate::{
+    color::{Color, colors},
+    geometry::Rect,
+};
+use std::cell::{Cell, RefCell};
+use std::time::Duration;
+
+thread_local! {
+    static CARET_CONFIG: RefCell<CaretConfig> = RefCell::new(CaretConfig::from_system());
+    static NEXT_BLINK: Cell<Option<f32>> = const { Cell::new(None) };
+}
+
+/// How the arrow keys move a caret through mixed-direction text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CaretMovement {
+    /// Left and right move the caret that way on screen, like native macOS
+    /// text views
+    #[default]
+    Visual,
+    /// Left and right step backward and forward through the string, even
+    /// where right-to-left text runs the other way on screen
+    Logical,
+}
+
+/// How text carets look and blink
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaretConfig {
+    /// Whether the caret blinks at all
+    pub blink: bool,
+    /// How long the caret stays visible in each blink cycle
+    pub blink_on: Duration,
+    /// How long the caret stays hidden in each blink cycle
+    pub blink_off: Duration,
+    /// How long the caret stays solid after typing or moving it
+    pub typing_delay: Duration,
+    /// Caret width in points
+    pub width: f32,
+    /// Caret color, used unless an element overrides it
+    pub color: Color,
+    /// How the arrow keys move the caret
+    pub movement: CaretMovement,
+}
+
+impl Default for CaretConfig {
+    fn default() -> Self {
+        Self {
+            blink: true,
+            blink_on: Duration::from_millis(500),
+            blink_off: Duration::from_millis(500),
+            typing_delay: Duration::from_millis(500),
+            width: 2.0,
+            color: colors::BLACK,
+            movement: CaretMovement::default(),
+        }
+    }
+}
+
+impl CaretConfig {
+    /// Default config, with blink timing taken from the system text settings
+    ///
+    /// Respects the `NSTextInsertionPointBlinkPeriodOn`/`Off` user defaults
+    /// (and the older combined `NSTextInsertionPointBlinkPeriod`), in
+    /// milliseconds. A very long period turns blinking off.
+    pub fn from_system() -> Self {
+        let mut config = Self::default();
+
+        #[cfg(target_os = "macos")]
+        {
+            use crate::platform::mac::user_default_f64;
+
+            let millis = |ms: f64| Duration::from_secs_f64(ms.max(0.0) / 1000.0);
+            if let Some(period) = user_default_f64("NSTextInsertionPointBlinkPeriod") {
+                config.blink_on = millis(period / 2.0);
+                config.blink_off = millis(period / 2.0);
+            }
+            if let Some(on) = user_default_f64("NSTextInsertionPointBlinkPeriodOn") {
+                config.blink_on = millis(on);
+            }
+            if let Some(off) = user_default_f64("NSTextInsertionPointBlinkPeriodOff") {
+                config.blink_off = millis(off);
+            }
+        }
+
+        const NEVER: Duration = Duration::from_secs(60);
+        if config.blink_off.is_zero() || config.blink_on >= NEVER {
+            config.blink = false;
+        }
+        config
+    }
+
+    /// Whether a caret last moved at `last_activity` is visible at `now`
+    ///
+    /// Both times are frame clock seconds. The caret stays solid for
+    /// `typing_delay` after activity, then starts its blink cycle visible.
+    pub fn is_visible(&self, now: f32, last_activity: f32) -> bool {
+        let since = (now - last_activity).max(0.0);
+        let typing_delay = self.typing_delay.as_secs_f32();
+        if !self.blink || since < typing_delay {
+            return true;
+        }
+
+        let on = self.blink_on.as_secs_f32();
+        let cycle = on + self.blink_off.as_secs_f32();
+        if cycle <= 0.0 {
+            return true;
+        }
+        (since - typing_delay) % cycle < on
+    }
+
+    /// When a caret last moved at `last_activity` next appears or disappears,
+    /// after `now`
+    ///
+    /// `None` if the caret doesn't blink. Both times and the result are
+    /// frame clock seconds.
+    pub fn next_transition(&self, now: f32, last_activity: f32) -> Option<f32> {
+        let on = self.blink_on.as_secs_f32();
+        let cycle = on + self.blink_off.as_secs_f32();
+        if !self.blink || cycle <= 0.0 {
+            return None;
+        }
+
+        let typing_delay = self.typing_delay.as_secs_f32();
+        let since = (now - last_activity).max(0.0);
+        if since < typing_delay {
+            // Solid until the delay ends, then visible for the first `on`
+            return Some(last_activity + typing_delay + on);
+        }
+        let phase = (since - typing_delay) % cycle;
+        Some(if phase < on {
+            now + on - phase
+        } else {
+            now + cycle - phase
+        })
+    }
+}
+
+/// Get the caret config for this thread
+pub fn caret_config() -> CaretConfig {
+    CARET_CONFIG.with(|c| c.borrow().clone())
+}
+
+/// Replace the caret config, e.g. to apply a theme's caret color
+pub fn set_caret_config(config: CaretConfig) {
+    CARET_CONFIG.with(|c| *c.borrow_mut() = config);
+}
+
+/// Ask for a frame at frame clock time `at`, when a caret blinks
+///
+/// Keeps the earliest time asked for since the last
+/// [`take_next_blink`].
+pub(crate) fn schedule_blink(at: f32) {
+    NEXT_BLINK.with(|next| {
+        let earliest = next.get().map_or(at, |scheduled| scheduled.min(at));
+        next.set(Some(earliest));
+    });
+}
+
+/// Take the frame clock time of the next caret blink, if any caret blinks
+pub(crate) fn take_next_blink() -> Option<f32> {
+    NEXT_BLINK.with(|next| next.take())
+}
+
+/// A caret painted into a draw list
+#[derive(Debug, Clone)]
+pub struct Caret {
+    /// Caret rectangle in screen coordinates
+    pub bounds: Rect,
+    /// Color override; `None` uses the configured caret color
+    pub color: Option<Color>,
+    /// Frame time of the last edit or caret movement
+    pub last_activity: f32,
+    /// Index of the caret's rect among the draw list's commands
+    pub(crate) command: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> CaretConfig {
+        CaretConfig {
+            blink: true,
+            blink_on: Duration::from_millis(500),
+            blink_off: Duration::from_millis(250),
+            typing_delay: Duration::from_millis(300),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_caret_solid_while_typing() {
+        let config = config();
+        assert!(config.is_visible(10.0, 10.0));
+        assert!(config.is_visible(10.29, 10.0));
+    }
+
+    #[test]
+    fn test_caret_blink_cycle() {
+        let config = config();
+        // Typing delay ends at 0.3s, then 0.5s on and 0.25s off
+        assert!(config.is_visible(0.5, 0.0));
+        assert!(!config.is_visible(0.9, 0.0));
+        assert!(config.is_visible(1.1, 0.0));
+    }
+
+    #[test]
+    fn test_caret_next_transition() {
+        let config = config();
+        let next = |now| config.next_transition(now, 0.0).unwrap();
+        // Solid while typing until 0.3s, then on until 0.8s and off until 1.05s
+        assert!((next(0.1) - 0.8).abs() < 1e-5);
+        assert!((next(0.5) - 0.8).abs() < 1e-5);
+        assert!((next(0.9) - 1.05).abs() < 1e-5);
+        assert!((next(1.1) - 1.55).abs() < 1e-5);
+
+        let solid = CaretConfig {
+            blink: false,
+            ..config
+        };
+        assert_eq!(solid.next_transition(0.9, 0.0), None);
+    }
+
+    #[test]
+    fn test_schedule_blink_keeps_earliest() {
+        schedule_blink(2.0);
+        schedule_blink(1.5);
+        schedule_blink(3.0);
+        assert_eq!(take_next_blink(), Some(1.5));
+        assert_eq!(take_next_blink(), None);
+    }
+
+    #[test]
+    fn test_caret_blink_disabled() {
+        let config = CaretConfig {
+            blink: false,
+            ..config()
+        };
+        assert!(config.is_visible(0.9, 0.0));
+    }
+}
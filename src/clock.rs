@@ -0,0 +1,21 @@
+//! The frame clock
+//!
+//! Elements that animate or record when something happened read the time
+//! from here instead of the system clock. It holds still for the whole of a
+//! frame, so everything painted in one frame agrees on the time, and input
+//! playback can drive it at a fixed rate so recordings replay identically.
+
+use std::cell::Cell;
+
+thread_local! {
+    static FRAME_TIME: Cell<f32> = const { Cell::new(0.0) };
+}
+
+/// Seconds on the frame clock at the start of the current frame
+pub fn frame_time() -> f32 {
+    FRAME_TIME.with(|t| t.get())
+}
+
+pub(crate) fn set_frame_time(time: f32) {
+    FRAME_TIME.with(|t| t.set(time));
+}
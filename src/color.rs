@@ -159,6 +159,133 @@ pub trait ColorExt {
     /// assert_eq!(arr, [255, 255, 255, 255]);
     /// ```
     fn as_u8_arr(&self) -> [u8; 4];
+
+    /// Create an opaque color from HSL components.
+    ///
+    /// # Arguments
+    /// * `h` - Hue in degrees (wraps around, 0.0 = red)
+    /// * `s` - Saturation (0.0 to 1.0)
+    /// * `l` - Lightness (0.0 = black, 1.0 = white)
+    ///
+    /// # Examples
+    /// ```
+    /// use sol_ui::color::{Color, ColorExt};
+    /// let orange = Color::hsl(30.0, 1.0, 0.5);
+    /// ```
+    fn hsl(h: f32, s: f32, l: f32) -> Self;
+
+    /// Create a color from HSL components and alpha.
+    ///
+    /// See [`hsl`](Self::hsl).
+    fn hsla(h: f32, s: f32, l: f32, a: f32) -> Self;
+
+    /// Convert the color to HSL, as `(hue in degrees, saturation, lightness)`.
+    ///
+    /// Grays have a hue of 0.
+    fn to_hsl(&self) -> (f32, f32, f32);
+
+    /// Create an opaque color from OKLCH components.
+    ///
+    /// OKLCH is perceptually uniform: colors with the same lightness look
+    /// equally light whatever their hue, which makes it the space to
+    /// generate palettes in. Colors outside the sRGB gamut are clipped.
+    ///
+    /// # Arguments
+    /// * `l` - Perceived lightness (0.0 = black, 1.0 = white)
+    /// * `c` - Chroma (0.0 = gray, up to about 0.37 for the most vivid sRGB colors)
+    /// * `h` - Hue in degrees (wraps around)
+    ///
+    /// # Examples
+    /// ```
+    /// use sol_ui::color::{Color, ColorExt};
+    /// // Same lightness and chroma, different hues
+    /// let accent = Color::oklch(0.65, 0.15, 250.0);
+    /// let warning = Color::oklch(0.65, 0.15, 70.0);
+    /// ```
+    fn oklch(l: f32, c: f32, h: f32) -> Self;
+
+    /// Create a color from OKLCH components and alpha.
+    ///
+    /// See [`oklch`](Self::oklch).
+    fn oklcha(l: f32, c: f32, h: f32, a: f32) -> Self;
+
+    /// Convert the color to OKLCH, as `(lightness, chroma, hue in degrees)`.
+    ///
+    /// Grays have a hue of 0.
+    fn to_oklch(&self) -> (f32, f32, f32);
+
+    /// Blend towards `other` by `t` (0.0 = self, 1.0 = other).
+    ///
+    /// Colors are mixed in Oklab, so midpoints don't turn muddy or dark the
+    /// way they do when mixing sRGB components. Alpha is mixed linearly.
+    ///
+    /// # Examples
+    /// ```
+    /// use sol_ui::color::{Color, ColorExt, colors};
+    /// let tinted = colors::WHITE.mix(colors::BLUE_500, 0.1);
+    /// ```
+    fn mix(self, other: Self, t: f32) -> Self;
+
+    /// Make the color lighter by `amount` (0.0 to 1.0) of the way to white,
+    /// keeping its hue and chroma.
+    fn lighten(self, amount: f32) -> Self;
+
+    /// Make the color darker by `amount` (0.0 to 1.0) of the way to black,
+    /// keeping its hue and chroma.
+    fn darken(self, amount: f32) -> Self;
+
+    /// Make the color more vivid, scaling its chroma by `1.0 + amount`.
+    fn saturate(self, amount: f32) -> Self;
+
+    /// Make the color grayer, scaling its chroma by `1.0 - amount`.
+    fn desaturate(self, amount: f32) -> Self;
+
+    /// WCAG relative luminance (0.0 = black, 1.0 = white), ignoring alpha.
+    fn relative_luminance(&self) -> f32;
+
+    /// WCAG contrast ratio between two colors, from 1.0 (identical) to 21.0
+    /// (black on white). Alpha is ignored.
+    ///
+    /// # Examples
+    /// ```
+    /// use sol_ui::color::{Color, ColorExt, colors};
+    /// let ratio = colors::BLACK.contrast_ratio(colors::WHITE);
+    /// assert!((ratio - 21.0).abs() < 0.01);
+    /// ```
+    fn contrast_ratio(&self, other: Self) -> f32;
+
+    /// Whether text in this color on `background` meets a WCAG level.
+    ///
+    /// # Examples
+    /// ```
+    /// use sol_ui::color::{Color, ColorExt, WcagLevel, colors};
+    /// assert!(colors::GRAY_800.meets_contrast(colors::WHITE, WcagLevel::Aa));
+    /// ```
+    fn meets_contrast(&self, background: Self, level: WcagLevel) -> bool;
+}
+
+/// WCAG 2.1 contrast requirements for text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WcagLevel {
+    /// AA for large text (18pt, or 14pt bold): 3:1
+    AaLarge,
+    /// AA for body text: 4.5:1
+    Aa,
+    /// AAA for large text: 4.5:1
+    AaaLarge,
+    /// AAA for body text: 7:1
+    Aaa,
+}
+
+impl WcagLevel {
+    /// The minimum contrast ratio for this level
+    pub fn min_ratio(self) -> f32 {
+        match self {
+            WcagLevel::AaLarge => 3.0,
+            WcagLevel::Aa | WcagLevel::AaaLarge => 4.5,
+            WcagLevel::Aaa => 7.0,
+        }
+    }
 }
 
 impl ColorExt for Color {
@@ -232,4 +359,210 @@ impl ColorExt for Color {
             (self.alpha * 255.0) as u8,
         ]
     }
+
+    fn hsl(h: f32, s: f32, l: f32) -> Self {
+        Self::hsla(h, s, l, 1.0)
+    }
+
+    fn hsla(h: f32, s: f32, l: f32, a: f32) -> Self {
+        let h = h.rem_euclid(360.0) / 60.0;
+        let s = s.clamp(0.0, 1.0);
+        let l = l.clamp(0.0, 1.0);
+        let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = l - chroma / 2.0;
+        Srgba::new(r + m, g + m, b + m, a)
+    }
+
+    fn to_hsl(&self) -> (f32, f32, f32) {
+        let (r, g, b) = (self.red, self.green, self.blue);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let l = (max + min) / 2.0;
+        let delta = max - min;
+        if delta <= f32::EPSILON {
+            return (0.0, 0.0, l);
+        }
+        let s = delta / (1.0 - (2.0 * l - 1.0).abs());
+        let h = if max == r {
+            ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            (b - r) / delta + 2.0
+        } else {
+            (r - g) / delta + 4.0
+        };
+        (h * 60.0, s, l)
+    }
+
+    fn oklch(l: f32, c: f32, h: f32) -> Self {
+        Self::oklcha(l, c, h, 1.0)
+    }
+
+    fn oklcha(l: f32, c: f32, h: f32, a: f32) -> Self {
+        let h = h.to_radians();
+        from_oklab([l, c.max(0.0) * h.cos(), c.max(0.0) * h.sin()], a)
+    }
+
+    fn to_oklch(&self) -> (f32, f32, f32) {
+        let [l, a, b] = to_oklab(self);
+        let c = a.hypot(b);
+        // Hue is meaningless for grays, and noisy near them
+        let h = if c < 1e-4 {
+            0.0
+        } else {
+            b.atan2(a).to_degrees().rem_euclid(360.0)
+        };
+        (l, c, h)
+    }
+
+    fn mix(self, other: Self, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let from = to_oklab(&self);
+        let to = to_oklab(&other);
+        let lab = std::array::from_fn(|i| from[i] + (to[i] - from[i]) * t);
+        from_oklab(lab, self.alpha + (other.alpha - self.alpha) * t)
+    }
+
+    fn lighten(self, amount: f32) -> Self {
+        let (l, c, h) = self.to_oklch();
+        Self::oklcha(l + (1.0 - l) * amount.clamp(0.0, 1.0), c, h, self.alpha)
+    }
+
+    fn darken(self, amount: f32) -> Self {
+        let (l, c, h) = self.to_oklch();
+        Self::oklcha(l * (1.0 - amount.clamp(0.0, 1.0)), c, h, self.alpha)
+    }
+
+    fn saturate(self, amount: f32) -> Self {
+        let (l, c, h) = self.to_oklch();
+        Self::oklcha(l, c * (1.0 + amount.max(0.0)), h, self.alpha)
+    }
+
+    fn desaturate(self, amount: f32) -> Self {
+        let (l, c, h) = self.to_oklch();
+        Self::oklcha(l, c * (1.0 - amount.clamp(0.0, 1.0)), h, self.alpha)
+    }
+
+    fn relative_luminance(&self) -> f32 {
+        let [r, g, b] = [self.red, self.green, self.blue].map(to_linear);
+        0.2126 * r + 0.7152 * g + 0.0722 * b
+    }
+
+    fn contrast_ratio(&self, other: Self) -> f32 {
+        let a = self.relative_luminance();
+        let b = other.relative_luminance();
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    fn meets_contrast(&self, background: Self, level: WcagLevel) -> bool {
+        self.contrast_ratio(background) >= level.min_ratio()
+    }
+}
+
+/// Decode an sRGB component to linear light
+fn to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encode a linear light component as sRGB
+fn from_linear(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Convert to Oklab `[L, a, b]`
+fn to_oklab(color: &Color) -> [f32; 3] {
+    let [r, g, b] = [color.red, color.green, color.blue].map(to_linear);
+    let l = (0.41222147 * r + 0.53633254 * g + 0.051445993 * b).cbrt();
+    let m = (0.2119035 * r + 0.6806995 * g + 0.10739696 * b).cbrt();
+    let s = (0.08830246 * r + 0.28171884 * g + 0.6299787 * b).cbrt();
+    [
+        0.21045426 * l + 0.7936178 * m - 0.004072047 * s,
+        1.9779985 * l - 2.4285922 * m + 0.4505937 * s,
+        0.025904037 * l + 0.78277177 * m - 0.80867577 * s,
+    ]
+}
+
+/// Convert from Oklab `[L, a, b]`, clipping to the sRGB gamut
+fn from_oklab([l, a, b]: [f32; 3], alpha: f32) -> Color {
+    let l_ = (l + 0.39633778 * a + 0.21580376 * b).powi(3);
+    let m_ = (l - 0.105561346 * a - 0.06385417 * b).powi(3);
+    let s_ = (l - 0.08948418 * a - 1.2914855 * b).powi(3);
+    let rgb = [
+        4.0767417 * l_ - 3.3077116 * m_ + 0.23096994 * s_,
+        -1.268438 * l_ + 2.6097574 * m_ - 0.34131938 * s_,
+        -0.0041960863 * l_ - 0.7034186 * m_ + 1.7076147 * s_,
+    ]
+    .map(|c| from_linear(c.clamp(0.0, 1.0)));
+    Srgba::new(rgb[0], rgb[1], rgb[2], alpha)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Color, b: Color) {
+        let close = |x: f32, y: f32| (x - y).abs() < 1e-3;
+        assert!(
+            close(a.red, b.red) && close(a.green, b.green) && close(a.blue, b.blue),
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn test_hsl_round_trip() {
+        assert_close(Color::hsl(0.0, 1.0, 0.5), Color::rgb(1.0, 0.0, 0.0));
+        assert_close(Color::hsl(210.0, 0.5, 0.25), Color::rgb(0.125, 0.25, 0.375));
+        let (h, s, l) = Color::hex("#204060").to_hsl();
+        assert_close(Color::hsl(h, s, l), Color::hex("#204060"));
+        assert_eq!(colors::GRAY_500.to_hsl().1, 0.0);
+    }
+
+    #[test]
+    fn test_oklch_round_trip() {
+        let (l, c, _) = colors::WHITE.to_oklch();
+        assert!((l - 1.0).abs() < 1e-3 && c < 1e-3);
+        for color in [colors::BLUE_500, colors::RED_500, Color::hex("#3c8a2e")] {
+            let (l, c, h) = color.to_oklch();
+            assert_close(Color::oklch(l, c, h), color);
+        }
+    }
+
+    #[test]
+    fn test_mix_and_adjust() {
+        assert_close(colors::RED.mix(colors::BLUE, 0.0), colors::RED);
+        assert_close(colors::RED.mix(colors::BLUE, 1.0), colors::BLUE);
+        let gray = colors::BLACK.mix(colors::WHITE, 0.5);
+        assert!((gray.to_oklch().0 - 0.5).abs() < 1e-3);
+
+        let base = colors::BLUE_500;
+        assert!(base.lighten(0.3).to_oklch().0 > base.to_oklch().0);
+        assert!(base.darken(0.3).to_oklch().0 < base.to_oklch().0);
+        assert!(base.desaturate(1.0).to_oklch().1 < 1e-3);
+    }
+
+    #[test]
+    fn test_contrast_ratio() {
+        assert!((colors::BLACK.contrast_ratio(colors::WHITE) - 21.0).abs() < 0.01);
+        assert!((colors::RED.contrast_ratio(colors::RED) - 1.0).abs() < 1e-6);
+        assert!(colors::BLACK.meets_contrast(colors::WHITE, WcagLevel::Aaa));
+        assert!(!colors::GRAY_300.meets_contrast(colors::WHITE, WcagLevel::AaLarge));
+    }
 }
@@ -0,0 +1,129 @@
+//! Debug panel for stepping through recorded entity mutations
+
+use crate::{
+    color::{Color, ColorExt, colors},
+    entity::{EntityHistory, context::try_with_entity_store},
+    geometry::Rect,
+    render::{PaintContext, PaintText},
+    style::TextStyle,
+};
+use glam::Vec2;
+
+/// Lists recent entity mutations from the store's history
+///
+/// Mutations after the history cursor have been stepped back over and are
+/// dimmed. `[` and `]` step backward and forward while the panel is open.
+pub struct EntityHistoryPanel {
+    max_rows: usize,
+}
+
+impl EntityHistoryPanel {
+    pub fn new() -> Self {
+        Self { max_rows: 14 }
+    }
+
+    /// Undo the most recent applied mutation, returning whether it moved
+    pub fn step_back(&self) -> bool {
+        try_with_entity_store(|store| store.step_back()).unwrap_or(false)
+    }
+
+    /// Reapply the next mutation, returning whether it moved
+    pub fn step_forward(&self) -> bool {
+        try_with_entity_store(|store| store.step_forward()).unwrap_or(false)
+    }
+
+    /// Paint the panel
+    pub fn paint(&self, viewport: Rect, ctx: &mut PaintContext) {
+        let title_height = 20.0;
+        let line_height = 13.0;
+        let panel_bounds = Rect::from_pos_size(
+            viewport.pos + Vec2::new(8.0, 28.0),
+            Vec2::new(
+                320.0,
+                title_height + 8.0 + self.max_rows as f32 * line_height,
+            ),
+        );
+
+        // Background
+        ctx.paint_solid_quad(panel_bounds, Color::rgba(0.05, 0.05, 0.05, 0.9));
+        ctx.paint_solid_quad(
+            Rect::from_pos_size(
+                panel_bounds.pos,
+                Vec2::new(panel_bounds.size.x, title_height),
+            ),
+            Color::rgba(0.15, 0.15, 0.15, 1.0),
+        );
+
+        let rows = try_with_entity_store(|store| store.history().map(|h| self.rows(h))).flatten();
+        let title = match &rows {
+            Some((position, len, _)) => format!("History ({position}/{len})  [ ] to step"),
+            None => "History (off)".to_string(),
+        };
+        ctx.paint_text(PaintText {
+            position: panel_bounds.pos + Vec2::new(8.0, 4.0),
+            text: title,
+            style: TextStyle {
+                size: 11.0,
+                color: colors::WHITE,
+                ..Default::default()
+            },
+            measured_size: None,
+        });
+
+        let Some((_, _, rows)) = rows else {
+            return;
+        };
+        let content_y = panel_bounds.pos.y + title_height + 4.0;
+        let max_chars = (panel_bounds.size.x / 6.5) as usize;
+        for (i, (mut line, applied)) in rows.into_iter().enumerate() {
+            if line.chars().count() > max_chars {
+                line = line.chars().take(max_chars - 3).collect();
+                line.push_str("...");
+            }
+            let color = if applied {
+                Color::rgba(0.8, 0.8, 0.8, 1.0)
+            } else {
+                Color::rgba(0.4, 0.4, 0.4, 1.0)
+            };
+            ctx.paint_text(PaintText {
+                position: Vec2::new(panel_bounds.pos.x + 8.0, content_y + i as f32 * line_height),
+                text: line,
+                style: TextStyle {
+                    size: 10.0,
+                    color,
+                    ..Default::default()
+                },
+                measured_size: None,
+            });
+        }
+    }
+
+    /// The cursor, entry count and the most recent rows, each with whether
+    /// it's currently applied
+    fn rows(&self, history: &EntityHistory) -> (usize, usize, Vec<(String, bool)>) {
+        let skip = history.len().saturating_sub(self.max_rows);
+        let rows = history
+            .entries()
+            .enumerate()
+            .skip(skip)
+            .map(|(i, entry)| {
+                let change = entry.diff.lines().last().unwrap_or("");
+                let line = format!(
+                    "{:>7.2}s {}#{} {}",
+                    entry.timestamp.as_secs_f32(),
+                    entry.short_type_name(),
+                    entry.entity.index(),
+                    change
+                );
+                (line, i < history.position())
+            })
+            .collect();
+        (history.position(), history.len(), rows)
+    }
+}
+
+impl Default for EntityHistoryPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,162 @@
+//! Compact frame rate readout that works without the debug overlay
+
+use super::metrics::{renderer_stats, with_frame_metrics};
+use crate::{
+    color::{Color, ColorExt, colors},
+    element::{Element, LayoutContext},
+    geometry::{Corners, Edges, Rect},
+    render::{PaintContext, PaintQuad, PaintText},
+    style::TextStyle,
+};
+use glam::Vec2;
+use std::time::Duration;
+use taffy::prelude::*;
+
+const WIDTH: f32 = 132.0;
+const HEIGHT: f32 = 52.0;
+const PADDING: f32 = 6.0;
+
+/// Show the app's frame rate, recent frame times and draw calls
+///
+/// Reads the metrics the app records every frame, so it can go in any
+/// layer without enabling the debug overlay. It updates when its layer
+/// paints. The frame rate counts every frame that reached the screen, while
+/// the frame times and sparkline show the CPU time of each frame, without
+/// waiting for vsync.
+///
+/// ```ignore
+/// root.child(fps_overlay().budget(Duration::from_micros(8_333)))
+/// ```
+pub fn fps_overlay() -> FpsOverlay {
+    FpsOverlay {
+        budget: Duration::from_micros(16_667),
+    }
+}
+
+/// Element drawing the frame rate readout
+pub struct FpsOverlay {
+    budget: Duration,
+}
+
+impl FpsOverlay {
+    /// Set the frame budget the sparkline is drawn against
+    ///
+    /// Frames over budget are drawn in yellow, and over twice the budget in
+    /// red. Defaults to 60 frames per second.
+    pub fn budget(mut self, budget: Duration) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Color for a frame taking `frame_time`
+    fn frame_color(&self, frame_time: Duration) -> Color {
+        if frame_time <= self.budget {
+            colors::GREEN
+        } else if frame_time <= self.budget * 2 {
+            colors::YELLOW
+        } else {
+            colors::RED
+        }
+    }
+}
+
+impl Element for FpsOverlay {
+    fn layout(&mut self, ctx: &mut LayoutContext) -> NodeId {
+        ctx.request_layout(Style {
+            size: Size {
+                width: length(WIDTH),
+                height: length(HEIGHT),
+            },
+            flex_shrink: 0.0,
+            ..Default::default()
+        })
+    }
+
+    fn paint(&mut self, bounds: Rect, ctx: &mut PaintContext) {
+        if !ctx.is_visible(&bounds) {
+            return;
+        }
+
+        ctx.paint_quad(PaintQuad {
+            bounds,
+            corner_radii: Corners::all(6.0),
+            fill: Color::rgba(0.0, 0.0, 0.0, 0.75),
+            border_widths: Edges::zero(),
+            border_color: colors::TRANSPARENT,
+        });
+
+        let (fps, frame_times) = with_frame_metrics(|metrics| {
+            let frame_times: Vec<Duration> = metrics.history().map(|m| m.frame_time).collect();
+            (metrics.presented_fps(), frame_times)
+        });
+        let latest = frame_times.last().copied().unwrap_or_default();
+
+        ctx.paint_text(PaintText {
+            position: bounds.pos + Vec2::new(PADDING, PADDING - 2.0),
+            text: format!("{:.0} FPS", fps),
+            style: TextStyle {
+                size: 12.0,
+                color: self.frame_color(latest),
+                ..Default::default()
+            },
+            measured_size: None,
+        });
+        ctx.paint_text(PaintText {
+            position: bounds.pos + Vec2::new(PADDING + 52.0, PADDING),
+            text: format!(
+                "{:.1}ms {}dc",
+                latest.as_secs_f32() * 1000.0,
+                renderer_stats().draw_calls
+            ),
+            style: TextStyle {
+                size: 10.0,
+                color: Color::rgba(0.8, 0.8, 0.8, 1.0),
+                ..Default::default()
+            },
+            measured_size: None,
+        });
+
+        self.paint_sparkline(bounds, &frame_times, ctx);
+    }
+}
+
+impl FpsOverlay {
+    /// Bars for recent frame times, scaled so the budget is halfway up
+    fn paint_sparkline(&self, bounds: Rect, frame_times: &[Duration], ctx: &mut PaintContext) {
+        let graph_height = 24.0;
+        let graph = Rect::from_pos_size(
+            Vec2::new(
+                bounds.pos.x + PADDING,
+                bounds.pos.y + bounds.size.y - graph_height - PADDING,
+            ),
+            Vec2::new(bounds.size.x - PADDING * 2.0, graph_height),
+        );
+
+        // Budget line
+        ctx.paint_solid_quad(
+            Rect::from_pos_size(
+                Vec2::new(graph.pos.x, graph.pos.y + graph_height / 2.0),
+                Vec2::new(graph.size.x, 1.0),
+            ),
+            Color::rgba(0.4, 0.4, 0.4, 0.8),
+        );
+
+        let bar_width = 2.0;
+        let max_bars = (graph.size.x / bar_width) as usize;
+        let skip = frame_times.len().saturating_sub(max_bars);
+        let scale = self.budget.as_secs_f32() * 2.0;
+        for (i, frame_time) in frame_times.iter().skip(skip).enumerate() {
+            let height = (frame_time.as_secs_f32() / scale).min(1.0) * graph_height;
+            ctx.paint_solid_quad(
+                Rect::from_pos_size(
+                    Vec2::new(
+                        graph.pos.x + i as f32 * bar_width,
+                        graph.pos.y + graph_height - height,
+                    ),
+                    Vec2::new(bar_width - 0.5, height),
+                ),
+                self.frame_color(*frame_time),
+            );
+        }
+    }
+}
@@ -1,14 +1,22 @@
 //! Hit test visualization
 //!
 //! Visualizes hit test regions and z-order for debugging interaction issues.
+//! Hovering a region shows its details, and clicking it copies its element
+//! ID to the clipboard.
 
 use crate::{
     color::{Color, ColorExt, colors},
-    geometry::{Edges, Rect},
+    geometry::{Edges, Point, Rect},
+    interaction,
     render::{PaintContext, PaintQuad, PaintText},
     style::TextStyle,
 };
 use glam::Vec2;
+use std::cmp::Reverse;
+
+/// Offset of the info box from the mouse
+const INFO_OFFSET: Vec2 = Vec2::new(14.0, 14.0);
+const INFO_LINE_HEIGHT: f32 = 13.0;
 
 /// A hit test entry for visualization
 #[derive(Debug, Clone)]
@@ -16,12 +24,14 @@ pub struct HitTestEntry {
     pub element_id: u64,
     pub bounds: Rect,
     pub z_index: i32,
+    pub layer_index: usize,
 }
 
 /// Hit test visualization overlay
 pub struct HitTestVisualization {
     entries: Vec<HitTestEntry>,
-    hovered_entry: Option<usize>,
+    /// Mouse position, kept across frames as entries are re-registered
+    hover_position: Option<Vec2>,
     show_z_index: bool,
     show_element_id: bool,
 }
@@ -30,7 +40,7 @@ impl HitTestVisualization {
     pub fn new() -> Self {
         Self {
             entries: Vec::new(),
-            hovered_entry: None,
+            hover_position: None,
             show_z_index: true,
             show_element_id: true,
         }
@@ -42,29 +52,52 @@ impl HitTestVisualization {
             element_id,
             bounds,
             z_index,
+            layer_index: 0,
         });
     }
 
+    /// Register the entries the interaction system hit tests against
+    pub fn register_entries(&mut self, entries: &[interaction::HitTestEntry]) {
+        self.entries
+            .extend(entries.iter().map(|entry| HitTestEntry {
+                element_id: entry.element_id.0,
+                bounds: entry.bounds,
+                z_index: entry.z_index,
+                layer_index: entry.layer_index,
+            }));
+    }
+
     /// Clear all entries
     pub fn clear(&mut self) {
         self.entries.clear();
-        self.hovered_entry = None;
     }
 
-    /// Set the hovered entry based on mouse position
+    /// Set the mouse position used to find the hovered entry
     pub fn update_hover(&mut self, position: Vec2) {
-        // Find the topmost entry under the mouse
-        self.hovered_entry = None;
-        let mut highest_z = i32::MIN;
-
-        for (i, entry) in self.entries.iter().enumerate() {
-            if entry.bounds.contains(crate::geometry::Point::new(position.x, position.y)) {
-                if entry.z_index > highest_z {
-                    highest_z = entry.z_index;
-                    self.hovered_entry = Some(i);
-                }
-            }
-        }
+        self.hover_position = Some(position);
+    }
+
+    /// The topmost entry under the mouse, as the interaction system would
+    /// pick it
+    pub fn hovered_entry(&self) -> Option<&HitTestEntry> {
+        let position = self.hover_position?;
+        self.entries
+            .iter()
+            .filter(|entry| entry.bounds.contains(Point::new(position.x, position.y)))
+            .min_by_key(|entry| (Reverse(entry.z_index), Reverse(entry.layer_index)))
+    }
+
+    /// Copy the hovered entry's element ID to the clipboard
+    ///
+    /// Returns the ID copied, if the mouse is over an entry and the
+    /// platform has a clipboard.
+    pub fn copy_hovered_id(&self) -> Option<u64> {
+        let element_id = self.hovered_entry()?.element_id;
+        #[cfg(target_os = "macos")]
+        let copied = crate::platform::Clipboard::copy(&element_id.to_string());
+        #[cfg(not(target_os = "macos"))]
+        let copied = false;
+        copied.then_some(element_id)
     }
 
     /// Toggle z-index display
@@ -82,17 +115,21 @@ impl HitTestVisualization {
         self.entries.len()
     }
 
-    /// Paint the hit test visualization
-    pub fn paint(&self, ctx: &mut PaintContext) {
+    /// Paint the hit test visualization, with details of the hovered entry
+    pub fn paint(&self, viewport: Rect, ctx: &mut PaintContext) {
         // Sort entries by z-index (lowest first so higher ones render on top)
-        let mut sorted_entries: Vec<(usize, &HitTestEntry)> =
-            self.entries.iter().enumerate().collect();
-        sorted_entries.sort_by_key(|(_, e)| e.z_index);
+        let mut sorted_entries: Vec<&HitTestEntry> = self.entries.iter().collect();
+        sorted_entries.sort_by_key(|e| e.z_index);
 
-        for (index, entry) in sorted_entries {
-            let is_hovered = self.hovered_entry == Some(index);
+        let hovered = self.hovered_entry();
+        for entry in sorted_entries {
+            let is_hovered = hovered.is_some_and(|h| std::ptr::eq(h, entry));
             self.paint_entry(entry, is_hovered, ctx);
         }
+
+        if let (Some(entry), Some(position)) = (hovered, self.hover_position) {
+            paint_info(entry, position, viewport, ctx);
+        }
     }
 
     fn paint_entry(&self, entry: &HitTestEntry, is_hovered: bool, ctx: &mut PaintContext) {
@@ -146,6 +183,58 @@ impl HitTestVisualization {
     }
 }
 
+/// Details of `entry` in a box beside the mouse, kept inside `viewport`
+fn paint_info(entry: &HitTestEntry, mouse: Vec2, viewport: Rect, ctx: &mut PaintContext) {
+    let lines = info_lines(entry);
+    let width = lines.iter().map(|l| l.len()).max().unwrap_or(0) as f32 * 6.0 + 16.0;
+    let size = Vec2::new(width, lines.len() as f32 * INFO_LINE_HEIGHT + 10.0);
+
+    // Flip to the other side of the mouse rather than run off the edge
+    let mut pos = mouse + INFO_OFFSET;
+    if pos.x + size.x > viewport.max().x {
+        pos.x = mouse.x - INFO_OFFSET.x - size.x;
+    }
+    if pos.y + size.y > viewport.max().y {
+        pos.y = mouse.y - INFO_OFFSET.y - size.y;
+    }
+    let pos = pos.max(viewport.pos);
+
+    ctx.paint_solid_quad(
+        Rect::from_pos_size(pos, size),
+        Color::rgba(0.05, 0.05, 0.05, 0.9),
+    );
+    for (i, line) in lines.into_iter().enumerate() {
+        ctx.paint_text(PaintText {
+            position: pos + Vec2::new(8.0, 5.0 + i as f32 * INFO_LINE_HEIGHT),
+            text: line,
+            style: TextStyle {
+                size: 10.0,
+                color: if i == 0 {
+                    colors::WHITE
+                } else {
+                    Color::rgba(0.8, 0.8, 0.8, 1.0)
+                },
+                ..Default::default()
+            },
+            measured_size: None,
+        });
+    }
+}
+
+/// Text shown when hovering `entry`
+fn info_lines(entry: &HitTestEntry) -> Vec<String> {
+    let bounds = entry.bounds;
+    vec![
+        format!("id {}", entry.element_id),
+        format!("layer {}  z {}", entry.layer_index, entry.z_index),
+        format!(
+            "{:.0},{:.0}  {:.0}x{:.0}",
+            bounds.pos.x, bounds.pos.y, bounds.size.x, bounds.size.y
+        ),
+        "click to copy id".to_string(),
+    ]
+}
+
 impl Default for HitTestVisualization {
     fn default() -> Self {
         Self::new()
@@ -169,3 +258,27 @@ fn hsv_to_rgb(h: f32, s: f32, v: f32) -> Color {
 
     Color::rgba(r + m, g + m, b + m, 1.0)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hovered_entry_is_topmost() {
+        let mut viz = HitTestVisualization::new();
+        viz.register_entry(1, Rect::new(0.0, 0.0, 100.0, 100.0), 0);
+        viz.register_entry(2, Rect::new(10.0, 10.0, 20.0, 20.0), 5);
+        viz.register_entry(3, Rect::new(10.0, 10.0, 20.0, 20.0), 5);
+        assert!(viz.hovered_entry().is_none());
+
+        viz.update_hover(Vec2::new(15.0, 15.0));
+        assert_eq!(viz.hovered_entry().map(|e| e.element_id), Some(2));
+        viz.update_hover(Vec2::new(50.0, 50.0));
+        assert_eq!(viz.hovered_entry().map(|e| e.element_id), Some(1));
+
+        // The mouse position survives re-registering entries each frame
+        viz.clear();
+        viz.register_entry(4, Rect::new(40.0, 40.0, 20.0, 20.0), 0);
+        assert_eq!(viz.hovered_entry().map(|e| e.element_id), Some(4));
+    }
+}
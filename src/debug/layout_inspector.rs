@@ -1,12 +1,17 @@
 //! Layout inspector for debugging Taffy layout
+//!
+//! Hovering a node draws its box model (margin, border, padding, content and
+//! the gaps between its children) and lists the Taffy style it was laid out with.
 
 use crate::{
     color::{Color, ColorExt, colors},
     geometry::Rect,
+    layout_engine::TaffyLayoutEngine,
     render::{PaintContext, PaintText},
     style::TextStyle,
 };
 use glam::Vec2;
+use taffy::prelude::*;
 
 /// Information about a layout node
 #[derive(Debug, Clone)]
@@ -18,19 +23,155 @@ pub struct LayoutNodeInfo {
     pub flex_direction: Option<String>,
     pub justify_content: Option<String>,
     pub align_items: Option<String>,
+    /// Resolved padding as `[top, right, bottom, left]`
     pub padding: Option<[f32; 4]>,
+    /// Resolved margin as `[top, right, bottom, left]`
     pub margin: Option<[f32; 4]>,
+    /// Resolved border widths as `[top, right, bottom, left]`
+    pub border: Option<[f32; 4]>,
     pub gap: Option<f32>,
+    /// Space between adjacent children along the main axis
+    pub gap_regions: Vec<Rect>,
+    /// Style properties that differ from the Taffy defaults
+    pub style: Vec<String>,
     pub children_count: usize,
     pub depth: usize,
 }
 
+/// The nested boxes of a node, from the margin edge inwards
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoxModel {
+    pub margin_box: Rect,
+    pub border_box: Rect,
+    pub padding_box: Rect,
+    pub content_box: Rect,
+}
+
+impl BoxModel {
+    /// Build the box model for a node from its border box and resolved edges
+    pub fn from_node(node: &LayoutNodeInfo) -> Self {
+        let zero = [0.0; 4];
+        let border_box = node.bounds;
+        let padding_box = inset(border_box, node.border.unwrap_or(zero));
+        Self {
+            margin_box: outset(border_box, node.margin.unwrap_or(zero)),
+            border_box,
+            padding_box,
+            content_box: inset(padding_box, node.padding.unwrap_or(zero)),
+        }
+    }
+}
+
+/// Shrink a rect by `[top, right, bottom, left]` edges
+fn inset(rect: Rect, edges: [f32; 4]) -> Rect {
+    let [top, right, bottom, left] = edges;
+    Rect::new(
+        rect.pos.x + left,
+        rect.pos.y + top,
+        (rect.size.x - left - right).max(0.0),
+        (rect.size.y - top - bottom).max(0.0),
+    )
+}
+
+/// Grow a rect by `[top, right, bottom, left]` edges
+fn outset(rect: Rect, edges: [f32; 4]) -> Rect {
+    let [top, right, bottom, left] = edges;
+    Rect::new(
+        rect.pos.x - left,
+        rect.pos.y - top,
+        rect.size.x + left + right,
+        rect.size.y + top + bottom,
+    )
+}
+
+fn edges(rect: taffy::Rect<f32>) -> [f32; 4] {
+    [rect.top, rect.right, rect.bottom, rect.left]
+}
+
+/// Find the empty space between adjacent children along the main axis
+///
+/// Each region spans the cross axis of `content`, like the gap shading in
+/// browser devtools.
+pub fn gap_regions(content: Rect, children: &[Rect], horizontal: bool) -> Vec<Rect> {
+    let main = |r: &Rect| {
+        if horizontal {
+            (r.pos.x, r.size.x)
+        } else {
+            (r.pos.y, r.size.y)
+        }
+    };
+
+    let mut sorted: Vec<Rect> = children.to_vec();
+    sorted.sort_by(|a, b| main(a).0.total_cmp(&main(b).0));
+
+    sorted
+        .windows(2)
+        .filter_map(|pair| {
+            let (start, size) = main(&pair[0]);
+            let end = start + size;
+            let next = main(&pair[1]).0;
+            if next - end < 0.5 {
+                return None;
+            }
+            Some(if horizontal {
+                Rect::new(end, content.pos.y, next - end, content.size.y)
+            } else {
+                Rect::new(content.pos.x, end, content.size.x, next - end)
+            })
+        })
+        .collect()
+}
+
+/// Describe the style properties that differ from `Style::default()`
+pub fn style_summary(style: &Style) -> Vec<String> {
+    let default = Style::default();
+    let mut lines = Vec::new();
+
+    macro_rules! changed {
+        ($($field:ident),* $(,)?) => {
+            $(
+                if style.$field != default.$field {
+                    lines.push(format!("{}: {:?}", stringify!($field), style.$field));
+                }
+            )*
+        };
+    }
+
+    changed!(
+        display,
+        position,
+        overflow,
+        inset,
+        size,
+        min_size,
+        max_size,
+        aspect_ratio,
+        margin,
+        padding,
+        border,
+        flex_direction,
+        flex_wrap,
+        flex_basis,
+        flex_grow,
+        flex_shrink,
+        align_items,
+        align_self,
+        align_content,
+        justify_content,
+        gap,
+    );
+
+    lines
+}
+
 /// Layout inspector for visualizing Taffy layout tree
 pub struct LayoutInspector {
     nodes: Vec<LayoutNodeInfo>,
     selected_node: Option<u64>,
+    hovered_node: Option<u64>,
     show_tree: bool,
     show_details: bool,
+    show_box_model: bool,
 }
 
 impl LayoutInspector {
@@ -38,8 +179,94 @@ impl LayoutInspector {
         Self {
             nodes: Vec::new(),
             selected_node: None,
+            hovered_node: None,
             show_tree: true,
             show_details: true,
+            show_box_model: true,
+        }
+    }
+
+    /// Register every node under `root` from a computed layout
+    ///
+    /// `skip` leaves out a subtree, such as the node of the overlay doing the
+    /// inspecting.
+    pub fn collect(&mut self, engine: &TaffyLayoutEngine, root: NodeId, skip: Option<NodeId>) {
+        self.collect_node(engine, root, Vec2::ZERO, 0, skip);
+    }
+
+    fn collect_node(
+        &mut self,
+        engine: &TaffyLayoutEngine,
+        node: NodeId,
+        parent_pos: Vec2,
+        depth: usize,
+        skip: Option<NodeId>,
+    ) {
+        if Some(node) == skip {
+            return;
+        }
+        let (Some(layout), Some(style)) = (engine.layout(node), engine.style(node)) else {
+            return;
+        };
+
+        let pos = parent_pos + Vec2::new(layout.location.x, layout.location.y);
+        let bounds = Rect::from_pos_size(pos, Vec2::new(layout.size.width, layout.size.height));
+        let children: Vec<NodeId> = engine
+            .children(node)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|child| Some(*child) != skip)
+            .collect();
+
+        let padding = edges(layout.padding);
+        let border = edges(layout.border);
+        let is_flex = style.display == Display::Flex;
+        let is_row = matches!(
+            style.flex_direction,
+            FlexDirection::Row | FlexDirection::RowReverse
+        );
+
+        let gap_regions = if is_flex && style.flex_wrap == FlexWrap::NoWrap {
+            let child_bounds: Vec<Rect> = children
+                .iter()
+                .filter_map(|child| engine.layout(*child))
+                .map(|l| {
+                    Rect::from_pos_size(
+                        pos + Vec2::new(l.location.x, l.location.y),
+                        Vec2::new(l.size.width, l.size.height),
+                    )
+                })
+                .collect();
+            let content = inset(inset(bounds, border), padding);
+            gap_regions(content, &child_bounds, is_row)
+        } else {
+            Vec::new()
+        };
+        let gap = gap_regions
+            .iter()
+            .map(|r| if is_row { r.size.x } else { r.size.y })
+            .reduce(f32::min);
+
+        self.nodes.push(LayoutNodeInfo {
+            node_id: u64::from(node),
+            bounds,
+            computed_size: bounds.size,
+            computed_position: pos,
+            flex_direction: is_flex.then(|| format!("{:?}", style.flex_direction)),
+            justify_content: style.justify_content.map(|j| format!("{:?}", j)),
+            align_items: style.align_items.map(|a| format!("{:?}", a)),
+            padding: Some(padding),
+            margin: Some(edges(layout.margin)),
+            border: Some(border),
+            gap,
+            gap_regions,
+            style: style_summary(style),
+            children_count: children.len(),
+            depth,
+        });
+
+        for child in children {
+            self.collect_node(engine, child, pos, depth + 1, skip);
         }
     }
 
@@ -51,6 +278,19 @@ impl LayoutInspector {
     /// Clear all nodes
     pub fn clear(&mut self) {
         self.nodes.clear();
+        self.hovered_node = None;
+    }
+
+    /// Update the hovered node from the mouse position
+    pub fn hover(&mut self, position: Option<Vec2>) {
+        self.hovered_node = position
+            .and_then(|p| self.find_node_at(p))
+            .map(|n| n.node_id);
+    }
+
+    /// Get the hovered node
+    pub fn hovered_node(&self) -> Option<u64> {
+        self.hovered_node
     }
 
     /// Select a node for detailed inspection
@@ -91,27 +331,74 @@ impl LayoutInspector {
         self.show_details = !self.show_details;
     }
 
+    /// Toggle the box model overlay on the hovered node
+    pub fn toggle_box_model(&mut self) {
+        self.show_box_model = !self.show_box_model;
+    }
+
     /// Paint the layout inspector panel
     pub fn paint(&self, viewport: Rect, ctx: &mut PaintContext) {
         if self.nodes.is_empty() {
             return;
         }
 
+        let hovered = self
+            .hovered_node
+            .and_then(|id| self.nodes.iter().find(|n| n.node_id == id));
+
+        // Paint the box model under the panels
+        if self.show_box_model
+            && let Some(node) = hovered
+        {
+            self.paint_box_model(node, ctx);
+        }
+
         // Paint layout tree panel on the left
         if self.show_tree {
             self.paint_tree_panel(viewport, ctx);
         }
 
-        // Paint details panel for selected node
+        // Paint details panel for the hovered node, falling back to the selection
         if self.show_details {
-            if let Some(node_id) = self.selected_node {
-                if let Some(node) = self.nodes.iter().find(|n| n.node_id == node_id) {
-                    self.paint_details_panel(node, viewport, ctx);
-                }
+            let node = hovered.or_else(|| {
+                self.selected_node
+                    .and_then(|id| self.nodes.iter().find(|n| n.node_id == id))
+            });
+            if let Some(node) = node {
+                self.paint_details_panel(node, viewport, ctx);
             }
         }
     }
 
+    fn paint_box_model(&self, node: &LayoutNodeInfo, ctx: &mut PaintContext) {
+        let model = BoxModel::from_node(node);
+
+        // Same palette as browser devtools
+        paint_ring(
+            model.margin_box,
+            model.border_box,
+            Color::rgba(0.97, 0.6, 0.3, 0.4),
+            ctx,
+        );
+        paint_ring(
+            model.border_box,
+            model.padding_box,
+            Color::rgba(1.0, 0.85, 0.4, 0.5),
+            ctx,
+        );
+        paint_ring(
+            model.padding_box,
+            model.content_box,
+            Color::rgba(0.55, 0.75, 0.45, 0.4),
+            ctx,
+        );
+        ctx.paint_solid_quad(model.content_box, Color::rgba(0.4, 0.6, 0.85, 0.35));
+
+        for gap in &node.gap_regions {
+            ctx.paint_solid_quad(*gap, Color::rgba(0.7, 0.4, 0.85, 0.45));
+        }
+    }
+
     fn paint_tree_panel(&self, viewport: Rect, ctx: &mut PaintContext) {
         let panel_width = 200.0;
         let panel_bounds = Rect::from_pos_size(
@@ -186,8 +473,10 @@ impl LayoutInspector {
     }
 
     fn paint_details_panel(&self, node: &LayoutNodeInfo, viewport: Rect, ctx: &mut PaintContext) {
-        let panel_width = 220.0;
-        let panel_height = 200.0;
+        let panel_width = 280.0;
+        let line_height = 14.0;
+        let line_count = 10 + node.style.len();
+        let panel_height = (36.0 + line_count as f32 * line_height).min(viewport.size.y - 16.0);
         let panel_bounds = Rect::from_pos_size(
             viewport.pos + Vec2::new(viewport.size.x - panel_width - 8.0, 8.0),
             Vec2::new(panel_width, panel_height),
@@ -210,7 +499,6 @@ impl LayoutInspector {
 
         // Details
         let mut y = 28.0;
-        let line_height = 14.0;
 
         let details = [
             format!("Position: ({:.0}, {:.0})", node.bounds.pos.x, node.bounds.pos.y),
@@ -222,13 +510,23 @@ impl LayoutInspector {
             node.align_items.clone().map_or(String::new(), |a| format!("Align: {}", a)),
             node.padding.map_or(String::new(), |p| format!("Padding: [{:.0},{:.0},{:.0},{:.0}]", p[0], p[1], p[2], p[3])),
             node.margin.map_or(String::new(), |m| format!("Margin: [{:.0},{:.0},{:.0},{:.0}]", m[0], m[1], m[2], m[3])),
+            node.border.map_or(String::new(), |b| format!("Border: [{:.0},{:.0},{:.0},{:.0}]", b[0], b[1], b[2], b[3])),
             node.gap.map_or(String::new(), |g| format!("Gap: {:.0}", g)),
         ];
-
-        for detail in details {
+        let style_header = if node.style.is_empty() {
+            "Style: default".to_string()
+        } else {
+            "Style:".to_string()
+        };
+        let style_lines = node.style.iter().map(|line| format!("  {}", line));
+
+        for detail in details.into_iter().chain([style_header]).chain(style_lines) {
             if detail.is_empty() {
                 continue;
             }
+            if y > panel_bounds.size.y - line_height {
+                break;
+            }
 
             ctx.paint_text(PaintText {
                 position: panel_bounds.pos + Vec2::new(8.0, y),
@@ -251,3 +549,83 @@ impl Default for LayoutInspector {
         Self::new()
     }
 }
+
+/// Fill the area between `outer` and `inner` with four strips
+fn paint_ring(outer: Rect, inner: Rect, color: Color, ctx: &mut PaintContext) {
+    let inner_right = inner.pos.x + inner.size.x;
+    let inner_bottom = inner.pos.y + inner.size.y;
+    let top = inner.pos.y - outer.pos.y;
+    let bottom = outer.pos.y + outer.size.y - inner_bottom;
+    let left = inner.pos.x - outer.pos.x;
+    let right = outer.pos.x + outer.size.x - inner_right;
+
+    // Top and bottom span the full width, left and right fill the remaining height
+    let strips = [
+        Rect::new(outer.pos.x, outer.pos.y, outer.size.x, top),
+        Rect::new(outer.pos.x, inner_bottom, outer.size.x, bottom),
+        Rect::new(outer.pos.x, inner.pos.y, left, inner.size.y),
+        Rect::new(inner_right, inner.pos.y, right, inner.size.y),
+    ];
+
+    for strip in strips {
+        if strip.size.x > 0.0 && strip.size.y > 0.0 {
+            ctx.paint_solid_quad(strip, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_box_model_edges() {
+        let node = LayoutNodeInfo {
+            node_id: 1,
+            bounds: Rect::new(10.0, 10.0, 100.0, 50.0),
+            computed_size: Vec2::new(100.0, 50.0),
+            computed_position: Vec2::new(10.0, 10.0),
+            flex_direction: None,
+            justify_content: None,
+            align_items: None,
+            padding: Some([4.0, 4.0, 4.0, 4.0]),
+            margin: Some([8.0, 0.0, 8.0, 2.0]),
+            border: Some([1.0, 1.0, 1.0, 1.0]),
+            gap: None,
+            gap_regions: Vec::new(),
+            style: Vec::new(),
+            children_count: 0,
+            depth: 0,
+        };
+
+        let model = BoxModel::from_node(&node);
+        assert_eq!(model.margin_box, Rect::new(8.0, 2.0, 102.0, 66.0));
+        assert_eq!(model.padding_box, Rect::new(11.0, 11.0, 98.0, 48.0));
+        assert_eq!(model.content_box, Rect::new(15.0, 15.0, 90.0, 40.0));
+    }
+
+    #[test]
+    fn test_gap_regions_between_children() {
+        let content = Rect::new(0.0, 0.0, 100.0, 20.0);
+        // Out of order on purpose; touching children have no gap
+        let children = [
+            Rect::new(50.0, 0.0, 20.0, 20.0),
+            Rect::new(0.0, 0.0, 40.0, 20.0),
+            Rect::new(70.0, 0.0, 10.0, 20.0),
+        ];
+
+        let gaps = gap_regions(content, &children, true);
+        assert_eq!(gaps, vec![Rect::new(40.0, 0.0, 10.0, 20.0)]);
+    }
+
+    #[test]
+    fn test_style_summary_lists_changes() {
+        assert!(style_summary(&Style::default()).is_empty());
+
+        let style = Style {
+            flex_grow: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(style_summary(&style), vec!["flex_grow: 1.0".to_string()]);
+    }
+}
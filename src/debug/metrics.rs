@@ -7,14 +7,178 @@ use crate::{
     style::TextStyle,
 };
 use glam::Vec2;
+use std::cell::RefCell;
 use std::collections::VecDeque;
 use std::time::{Duration, Instant};
 
+thread_local! {
+    static INPUT_LATENCY: RefCell<InputLatency> = RefCell::new(InputLatency::new(256));
+    static RENDERER_STATS: RefCell<RendererStats> = RefCell::new(RendererStats::default());
+    static FRAME_METRICS: RefCell<PerformanceMetrics> = RefCell::new(PerformanceMetrics::new());
+}
+
+/// Record the CPU time the app spent on a frame, not counting waiting for a
+/// drawable, and the time since the previous frame started
+pub(crate) fn record_frame_time(cpu_time: Duration, interval: Duration) {
+    FRAME_METRICS.with(|m| {
+        m.borrow_mut().record_frame(FrameMetrics {
+            frame_time: cpu_time,
+            interval,
+            ..Default::default()
+        })
+    });
+}
+
+/// Read the frame times of the app's recent frames
+///
+/// Recorded for every frame the app renders, whether or not a
+/// [`DebugOverlay`](super::DebugOverlay) is tracking its own metrics.
+pub fn with_frame_metrics<R>(f: impl FnOnce(&PerformanceMetrics) -> R) -> R {
+    FRAME_METRICS.with(|m| f(&m.borrow()))
+}
+
+/// Record the time from an input event reaching the platform to the frame
+/// that handled it being committed for presentation
+pub fn record_input_latency(latency: Duration) {
+    INPUT_LATENCY.with(|l| l.borrow_mut().record(latency));
+}
+
+/// Get a snapshot of the recent input latency samples
+pub fn input_latency() -> InputLatency {
+    INPUT_LATENCY.with(|l| l.borrow().clone())
+}
+
+/// Record the renderer's GPU resource usage for the frame just rendered
+pub fn record_renderer_stats(stats: RendererStats) {
+    RENDERER_STATS.with(|s| *s.borrow_mut() = stats);
+}
+
+/// Get the renderer's GPU resource usage from the last rendered frame
+pub fn renderer_stats() -> RendererStats {
+    RENDERER_STATS.with(|s| s.borrow().clone())
+}
+
+/// GPU memory and atlas usage of the renderer
+///
+/// Per-frame counts cover the last rendered frame; steady growth in buffer
+/// bytes or a full, busy glyph atlas usually points at a regression.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RendererStats {
+    /// Bytes of vertex and uniform buffers allocated in the last frame
+    pub vertex_buffer_bytes: u64,
+    /// Number of draw calls in the last frame
+    pub draw_calls: usize,
+    /// Number of glyph atlas textures
+    pub atlas_pages: usize,
+    /// Number of glyphs cached in the atlas
+    pub atlas_glyphs: usize,
+    /// Atlas pixels allocated to glyphs, including padding
+    pub atlas_used_pixels: u64,
+    /// Total atlas pixels across all pages
+    pub atlas_total_pixels: u64,
+    /// GPU memory used by the atlas textures
+    pub atlas_bytes: u64,
+    /// Number of render pipelines kept by the renderer
+    pub pipeline_count: usize,
+    /// Number of pipelines compiled in the last frame, e.g. for custom shaders
+    pub pipelines_compiled: usize,
+    /// Bytes of offscreen textures allocated in the last frame for captures
+    /// and shadow blurs
+    pub offscreen_texture_bytes: u64,
+    /// GPU memory held by cached blurred shadow masks
+    pub shadow_cache_bytes: u64,
+    /// GPU memory held by uploaded image textures and the image atlas
+    pub image_cache_bytes: u64,
+}
+
+impl RendererStats {
+    /// Percentage of the glyph atlas in use
+    pub fn atlas_occupancy(&self) -> f32 {
+        if self.atlas_total_pixels > 0 {
+            (self.atlas_used_pixels as f32 / self.atlas_total_pixels as f32) * 100.0
+        } else {
+            0.0
+        }
+    }
+
+    /// Total GPU memory allocated in the last frame plus the atlas textures,
+    /// cached shadows and images
+    pub fn total_bytes(&self) -> u64 {
+        self.vertex_buffer_bytes
+            + self.atlas_bytes
+            + self.offscreen_texture_bytes
+            + self.shadow_cache_bytes
+            + self.image_cache_bytes
+    }
+}
+
+/// Rolling window of input-to-present latency samples
+#[derive(Debug, Clone)]
+pub struct InputLatency {
+    samples: VecDeque<Duration>,
+    max_samples: usize,
+}
+
+impl InputLatency {
+    pub fn new(max_samples: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(max_samples),
+            max_samples,
+        }
+    }
+
+    /// Add a sample, dropping the oldest one when full
+    pub fn record(&mut self, latency: Duration) {
+        if self.samples.len() >= self.max_samples {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(latency);
+    }
+
+    /// Number of samples in the window
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Get the latency at percentile `p` (0.0 to 1.0) using nearest rank
+    pub fn percentile(&self, p: f32) -> Option<Duration> {
+        if self.samples.is_empty() {
+            return None;
+        }
+
+        let mut sorted: Vec<Duration> = self.samples.iter().copied().collect();
+        sorted.sort();
+        let rank = (p.clamp(0.0, 1.0) * sorted.len() as f32).ceil() as usize;
+        Some(sorted[rank.saturating_sub(1)])
+    }
+
+    /// Median latency
+    pub fn p50(&self) -> Option<Duration> {
+        self.percentile(0.5)
+    }
+
+    /// 95th percentile latency
+    pub fn p95(&self) -> Option<Duration> {
+        self.percentile(0.95)
+    }
+
+    /// Remove all samples
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+}
+
 /// Metrics for a single frame
 #[derive(Debug, Clone, Default)]
 pub struct FrameMetrics {
     /// Total frame time
     pub frame_time: Duration,
+    /// Time since the previous frame started, or zero if unknown
+    pub interval: Duration,
     /// Time spent in layout phase
     pub layout_time: Duration,
     /// Time spent in paint phase
@@ -84,13 +248,21 @@ impl PerformanceMetrics {
     pub fn frame_end(&mut self) {
         if let Some(start) = self.frame_start.take() {
             self.current_frame.frame_time = start.elapsed();
+            self.record_frame(self.current_frame.clone());
+        }
+    }
 
-            // Store in history
-            if self.history.len() >= self.max_history {
-                self.history.pop_front();
-            }
-            self.history.push_back(self.current_frame.clone());
+    /// Add a finished frame's metrics to the history
+    pub fn record_frame(&mut self, frame: FrameMetrics) {
+        if self.history.len() >= self.max_history {
+            self.history.pop_front();
         }
+        self.history.push_back(frame);
+    }
+
+    /// Frames in the history, oldest first
+    pub fn history(&self) -> impl ExactSizeIterator<Item = &FrameMetrics> {
+        self.history.iter()
     }
 
     /// Record layout phase timing
@@ -130,6 +302,27 @@ impl PerformanceMetrics {
         }
     }
 
+    /// Frames shown per second, from the intervals between frame starts
+    ///
+    /// Unlike [`average_fps`](Self::average_fps) this counts time spent
+    /// waiting for vsync or idle between frames, so it's the rate frames
+    /// actually reach the screen. Frames without an interval are skipped.
+    pub fn presented_fps(&self) -> f32 {
+        let intervals = self
+            .history
+            .iter()
+            .map(|m| m.interval)
+            .filter(|interval| !interval.is_zero());
+        let (count, total) = intervals.fold((0, Duration::ZERO), |(count, total), interval| {
+            (count + 1, total + interval)
+        });
+        if total.is_zero() {
+            0.0
+        } else {
+            count as f32 / total.as_secs_f32()
+        }
+    }
+
     /// Get average frame time
     pub fn average_frame_time(&self) -> Duration {
         if self.history.is_empty() {
@@ -186,7 +379,7 @@ impl PerformanceMetrics {
     /// Paint the metrics panel
     pub fn paint(&self, viewport: Rect, ctx: &mut PaintContext) {
         let panel_width = 180.0;
-        let panel_height = if self.show_graph { 140.0 } else { 80.0 };
+        let panel_height = if self.show_graph { 176.0 } else { 116.0 };
         let panel_bounds = Rect::from_pos_size(
             viewport.pos + Vec2::new(viewport.size.x - panel_width - 8.0, 28.0),
             Vec2::new(panel_width, panel_height),
@@ -232,6 +425,9 @@ impl PerformanceMetrics {
                         latest.culled_count,
                         latest.culled_count + latest.rendered_count
                     ),
+                    input_latency_label(&input_latency()),
+                    gpu_memory_label(&renderer_stats()),
+                    atlas_label(&renderer_stats()),
                 ];
 
                 for stat in stats {
@@ -313,3 +509,121 @@ impl Default for PerformanceMetrics {
         Self::new()
     }
 }
+
+fn input_latency_label(latency: &InputLatency) -> String {
+    match (latency.p50(), latency.p95()) {
+        (Some(p50), Some(p95)) => format!(
+            "Input: p50 {:.1}ms p95 {:.1}ms",
+            p50.as_secs_f32() * 1000.0,
+            p95.as_secs_f32() * 1000.0
+        ),
+        _ => "Input: no samples".to_string(),
+    }
+}
+
+fn gpu_memory_label(stats: &RendererStats) -> String {
+    format!(
+        "GPU: {} buffers, {} offscreen, {} shadows, {} images",
+        format_bytes(stats.vertex_buffer_bytes),
+        format_bytes(stats.offscreen_texture_bytes),
+        format_bytes(stats.shadow_cache_bytes),
+        format_bytes(stats.image_cache_bytes)
+    )
+}
+
+fn atlas_label(stats: &RendererStats) -> String {
+    format!(
+        "Atlas: {:.0}% of {} ({} glyphs), {} pipelines",
+        stats.atlas_occupancy(),
+        stats.atlas_pages,
+        stats.atlas_glyphs,
+        stats.pipeline_count + stats.pipelines_compiled
+    )
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const KB: f32 = 1024.0;
+    let bytes = bytes as f32;
+    if bytes >= KB * KB {
+        format!("{:.1}MB", bytes / (KB * KB))
+    } else {
+        format!("{:.1}KB", bytes / KB)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_latency_percentiles() {
+        let mut latency = InputLatency::new(100);
+        assert_eq!(latency.p50(), None);
+
+        for ms in 1..=100 {
+            latency.record(Duration::from_millis(ms));
+        }
+        assert_eq!(latency.p50(), Some(Duration::from_millis(50)));
+        assert_eq!(latency.p95(), Some(Duration::from_millis(95)));
+        assert_eq!(latency.percentile(0.0), Some(Duration::from_millis(1)));
+    }
+
+    #[test]
+    fn test_input_latency_window_drops_oldest() {
+        let mut latency = InputLatency::new(2);
+        latency.record(Duration::from_millis(100));
+        latency.record(Duration::from_millis(1));
+        latency.record(Duration::from_millis(2));
+        assert_eq!(latency.len(), 2);
+        assert_eq!(latency.percentile(1.0), Some(Duration::from_millis(2)));
+    }
+
+    #[test]
+    fn test_renderer_stats_occupancy() {
+        let mut stats = RendererStats::default();
+        assert_eq!(stats.atlas_occupancy(), 0.0);
+
+        stats.atlas_used_pixels = 1024;
+        stats.atlas_total_pixels = 4096;
+        assert_eq!(stats.atlas_occupancy(), 25.0);
+        assert_eq!(format_bytes(1536), "1.5KB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0MB");
+    }
+
+    fn frame(cpu_ms: u64, interval_ms: u64) -> FrameMetrics {
+        FrameMetrics {
+            frame_time: Duration::from_millis(cpu_ms),
+            interval: Duration::from_millis(interval_ms),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_presented_fps_counts_time_between_frames() {
+        let mut metrics = PerformanceMetrics::new();
+        assert_eq!(metrics.presented_fps(), 0.0);
+
+        // 4ms of work each, presented every 20ms
+        metrics.record_frame(frame(4, 0));
+        for _ in 0..4 {
+            metrics.record_frame(frame(4, 20));
+        }
+        assert!((metrics.presented_fps() - 50.0).abs() < 0.01);
+        // Frame times alone would claim far more
+        assert!((metrics.average_fps() - 250.0).abs() < 0.01);
+        assert_eq!(metrics.average_frame_time(), Duration::from_millis(4));
+    }
+
+    #[test]
+    fn test_frame_history_keeps_recent_frames() {
+        let mut metrics = PerformanceMetrics::new();
+        for ms in 0..200 {
+            metrics.record_frame(frame(ms, 10));
+        }
+        assert_eq!(metrics.history().len(), 120);
+        assert_eq!(
+            metrics.history().next().map(|m| m.frame_time),
+            Some(Duration::from_millis(80))
+        );
+    }
+}
@@ -7,29 +7,51 @@
 //! - Performance metrics
 //! - Entity inspector
 //! - Debug console/logging
+//! - Entity mutation history
+//! - A compact FPS readout for any layer
+//! - Per-element paint profiling
 
 mod bounds_overlay;
 mod console;
+mod entity_history;
+mod fps_overlay;
 mod hit_test_viz;
 mod layout_inspector;
 mod metrics;
+mod paint_profiler;
 mod state;
 
 pub use bounds_overlay::BoundsOverlay;
 pub use console::{DebugConsole, LogEntry, LogLevel};
+pub use entity_history::EntityHistoryPanel;
+pub use fps_overlay::{FpsOverlay, fps_overlay};
 pub use hit_test_viz::HitTestVisualization;
-pub use layout_inspector::LayoutInspector;
-pub use metrics::{FrameMetrics, PerformanceMetrics};
+pub use layout_inspector::{BoxModel, LayoutInspector, LayoutNodeInfo};
+pub(crate) use metrics::record_frame_time;
+pub use metrics::{
+    FrameMetrics, InputLatency, PerformanceMetrics, RendererStats, input_latency,
+    record_input_latency, record_renderer_stats, renderer_stats, with_frame_metrics,
+};
+pub use paint_profiler::{
+    ElementPaintStats, PaintProfilerPanel, most_expensive_elements, paint_profile, paint_profiling,
+    set_paint_profiling,
+};
+pub(crate) use paint_profiler::{
+    attribute_element_id, begin_element_paint, begin_layer_paint_profile, end_element_paint,
+    end_paint_profile_frame,
+};
 pub use state::{DebugPanel, DebugState};
 
 use crate::{
     color::{Color, ColorExt, colors},
     element::{Element, LayoutContext},
     geometry::Rect,
+    interaction::HitTestEntry,
     layer::Key,
     render::PaintContext,
 };
 use glam::Vec2;
+use std::cell::{Ref, RefCell};
 use taffy::prelude::*;
 
 /// Debug overlay that renders all active debug visualizations
@@ -37,10 +59,12 @@ pub struct DebugOverlay {
     state: DebugState,
     bounds_overlay: BoundsOverlay,
     hit_test_viz: HitTestVisualization,
-    #[allow(dead_code)]
-    layout_inspector: LayoutInspector,
+    /// Rebuilt from the computed layout each time the overlay paints
+    layout_inspector: RefCell<LayoutInspector>,
     metrics: PerformanceMetrics,
     console: DebugConsole,
+    entity_history: EntityHistoryPanel,
+    paint_profiler: PaintProfilerPanel,
 }
 
 impl DebugOverlay {
@@ -49,9 +73,11 @@ impl DebugOverlay {
             state: DebugState::new(),
             bounds_overlay: BoundsOverlay::new(),
             hit_test_viz: HitTestVisualization::new(),
-            layout_inspector: LayoutInspector::new(),
+            layout_inspector: RefCell::new(LayoutInspector::new()),
             metrics: PerformanceMetrics::new(),
             console: DebugConsole::new(100),
+            entity_history: EntityHistoryPanel::new(),
+            paint_profiler: PaintProfilerPanel::new(),
         }
     }
 
@@ -116,6 +142,26 @@ impl DebugOverlay {
                         self.state.toggle_panel(DebugPanel::Console);
                         true
                     }
+                    // F7 toggles entity history
+                    Key::F7 => {
+                        self.state.toggle_panel(DebugPanel::History);
+                        true
+                    }
+                    // F8 toggles the paint profiler, profiling only while it's open
+                    Key::F8 => {
+                        self.state.toggle_panel(DebugPanel::Profiler);
+                        set_paint_profiling(self.state.is_panel_enabled(DebugPanel::Profiler));
+                        true
+                    }
+                    // Brackets step through entity history while it's open
+                    Key::LeftBracket if self.state.is_panel_enabled(DebugPanel::History) => {
+                        self.entity_history.step_back();
+                        true
+                    }
+                    Key::RightBracket if self.state.is_panel_enabled(DebugPanel::History) => {
+                        self.entity_history.step_forward();
+                        true
+                    }
                     _ => false,
                 }
             }
@@ -123,6 +169,31 @@ impl DebugOverlay {
         }
     }
 
+    /// Track the mouse for hover details in the layout and hit test panels
+    pub fn handle_mouse_move(&mut self, position: Vec2) {
+        self.state.update_mouse_position(position);
+        self.hit_test_viz.update_hover(position);
+    }
+
+    /// Handle a click, returns true if the click was consumed
+    ///
+    /// While the hit test visualization is open, clicking a region copies
+    /// its element ID to the clipboard.
+    pub fn handle_click(&mut self, position: Vec2) -> bool {
+        if !self.state.is_enabled() || !self.state.is_panel_enabled(DebugPanel::HitTest) {
+            return false;
+        }
+        self.handle_mouse_move(position);
+        match self.hit_test_viz.copy_hovered_id() {
+            Some(element_id) => {
+                self.console
+                    .info(format!("Copied element ID {element_id} to the clipboard"));
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Record the start of a frame
     pub fn frame_start(&mut self) {
         self.metrics.frame_start();
@@ -178,6 +249,11 @@ impl DebugOverlay {
         self.hit_test_viz.register_entry(element_id, bounds, z_index);
     }
 
+    /// Register the entries a layer's interaction system hit tests against
+    pub fn register_hit_test_entries(&mut self, entries: &[HitTestEntry]) {
+        self.hit_test_viz.register_entries(entries);
+    }
+
     /// Clear frame-specific debug data
     pub fn clear_frame_data(&mut self) {
         self.bounds_overlay.clear();
@@ -198,6 +274,11 @@ impl DebugOverlay {
     pub fn metrics(&self) -> &PerformanceMetrics {
         &self.metrics
     }
+
+    /// Get the layout inspector
+    pub fn layout_inspector(&self) -> Ref<'_, LayoutInspector> {
+        self.layout_inspector.borrow()
+    }
 }
 
 impl Default for DebugOverlay {
@@ -207,10 +288,14 @@ impl Default for DebugOverlay {
 }
 
 /// Create a debug overlay element for rendering
+///
+/// The layout inspector reads the layout of the layer the overlay is painted
+/// in, so add it as the last child of the UI it should inspect.
 pub fn debug_overlay(overlay: &DebugOverlay, viewport: Rect) -> impl Element + '_ {
     DebugOverlayElement {
         overlay,
         viewport,
+        node: None,
     }
 }
 
@@ -218,19 +303,23 @@ struct DebugOverlayElement<'a> {
     overlay: &'a DebugOverlay,
     #[allow(dead_code)]
     viewport: Rect,
+    /// The overlay's own layout node, left out of the layout inspector
+    node: Option<NodeId>,
 }
 
 impl<'a> Element for DebugOverlayElement<'a> {
     fn layout(&mut self, ctx: &mut LayoutContext) -> taffy::NodeId {
         // Full screen overlay
-        ctx.request_layout(Style {
+        let node = ctx.request_layout(Style {
             position: Position::Absolute,
             size: Size {
                 width: Dimension::percent(1.0),
                 height: Dimension::percent(1.0),
             },
             ..Default::default()
-        })
+        });
+        self.node = Some(node);
+        node
     }
 
     fn paint(&mut self, bounds: Rect, ctx: &mut PaintContext) {
@@ -244,7 +333,18 @@ impl<'a> Element for DebugOverlayElement<'a> {
         }
 
         if self.overlay.state.is_panel_enabled(DebugPanel::HitTest) {
-            self.overlay.hit_test_viz.paint(ctx);
+            self.overlay.hit_test_viz.paint(bounds, ctx);
+        }
+
+        // Inspect the node under the mouse using this frame's layout
+        if self.overlay.state.is_panel_enabled(DebugPanel::Layout) {
+            let mut inspector = self.overlay.layout_inspector.borrow_mut();
+            inspector.clear();
+            if let Some(root) = ctx.layout_engine.root() {
+                inspector.collect(ctx.layout_engine, root, self.node);
+            }
+            inspector.hover(self.overlay.state.mouse_position());
+            inspector.paint(bounds, ctx);
         }
 
         // Paint metrics panel in top-right corner
@@ -257,6 +357,16 @@ impl<'a> Element for DebugOverlayElement<'a> {
             self.overlay.console.paint(bounds, ctx);
         }
 
+        // Paint entity history in top-left corner
+        if self.overlay.state.is_panel_enabled(DebugPanel::History) {
+            self.overlay.entity_history.paint(bounds, ctx);
+        }
+
+        // Paint the most expensive elements below the metrics
+        if self.overlay.state.is_panel_enabled(DebugPanel::Profiler) {
+            self.overlay.paint_profiler.paint(bounds, ctx);
+        }
+
         // Paint debug mode indicator
         self.paint_indicator(bounds, ctx);
     }
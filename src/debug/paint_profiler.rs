@@ -0,0 +1,430 @@
+//! Per-element paint profiling
+//!
+//! While profiling is on, each boxed element's paint is timed and its draw
+//! commands counted. Both are attributed to an ID made from the element's
+//! type and its position among its parent's children, so the same widget
+//! keeps the same ID from frame to frame. The figures are the element's own,
+//! leaving out its children, so a container isn't blamed for an expensive
+//! widget inside it.
+//!
+//! The debug overlay's profiler panel (F8) lists the most expensive
+//! elements, and [`paint_profile`] returns the same figures for logging.
+
+use crate::{
+    color::{Color, ColorExt, colors},
+    geometry::Rect,
+    interaction::ElementId,
+    render::{PaintContext, PaintText},
+    style::TextStyle,
+};
+use glam::Vec2;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::time::{Duration, Instant};
+
+/// Weight of the latest frame in an element's average paint time
+const SMOOTHING: f64 = 0.1;
+/// Frames an element can go unpainted before its figures are dropped
+const STALE_FRAMES: u64 = 120;
+
+thread_local! {
+    static PROFILING: Cell<bool> = const { Cell::new(false) };
+    static PROFILER: RefCell<PaintProfiler> = RefCell::new(PaintProfiler::default());
+}
+
+/// Whether element paints are being profiled
+pub fn paint_profiling() -> bool {
+    PROFILING.with(|profiling| profiling.get())
+}
+
+/// Turn per-element paint profiling on or off
+///
+/// Off by default, since timing every element adds to each frame. Turning
+/// it off drops the figures collected so far.
+pub fn set_paint_profiling(enabled: bool) {
+    PROFILING.with(|profiling| profiling.set(enabled));
+    if !enabled {
+        PROFILER.with(|profiler| *profiler.borrow_mut() = PaintProfiler::default());
+    }
+}
+
+/// Paint costs of recently painted elements, most expensive first
+pub fn paint_profile() -> Vec<ElementPaintStats> {
+    PROFILER.with(|profiler| profiler.borrow().sorted())
+}
+
+/// The `count` elements with the highest average paint time
+pub fn most_expensive_elements(count: usize) -> Vec<ElementPaintStats> {
+    let mut elements = paint_profile();
+    elements.truncate(count);
+    elements
+}
+
+/// Start timing an element's paint, with `draw_commands` already in the
+/// draw list
+pub(crate) fn begin_element_paint(name: &'static str, draw_commands: usize) {
+    if !paint_profiling() {
+        return;
+    }
+    PROFILER.with(|profiler| profiler.borrow_mut().begin(name, draw_commands));
+}
+
+/// Finish timing the innermost element being painted
+pub(crate) fn end_element_paint(draw_commands: usize) {
+    if !paint_profiling() {
+        return;
+    }
+    PROFILER.with(|profiler| profiler.borrow_mut().end(draw_commands));
+}
+
+/// Label the element being painted with the ID it registered
+pub(crate) fn attribute_element_id(element_id: ElementId) {
+    if paint_profiling() {
+        PROFILER.with(|profiler| {
+            if let Some(scope) = profiler.borrow_mut().stack.last_mut() {
+                scope.element_id.get_or_insert(element_id);
+            }
+        });
+    }
+}
+
+/// Start attributing paints to the layer at `z_index`
+pub(crate) fn begin_layer_paint_profile(z_index: i32) {
+    if paint_profiling() {
+        PROFILER.with(|profiler| profiler.borrow_mut().begin_layer(z_index));
+    }
+}
+
+/// Fold the frame's paints into each element's figures
+pub(crate) fn end_paint_profile_frame() {
+    if paint_profiling() {
+        PROFILER.with(|profiler| profiler.borrow_mut().end_frame());
+    }
+}
+
+/// Paint cost of one element, from [`paint_profile`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElementPaintStats {
+    /// Stays the same while the element keeps its type and place in the tree
+    pub id: u64,
+    /// The element's type, without its module path or generic parameters
+    pub name: &'static str,
+    /// Child indices from the layer's root element down to this one
+    pub path: Vec<usize>,
+    /// ID the element registered for hit testing, if any
+    pub element_id: Option<ElementId>,
+    /// Draw commands the element added itself, in the last frame it painted
+    pub draw_commands: usize,
+    /// Time spent in the element's own paint, averaged over recent frames
+    pub paint_time: Duration,
+}
+
+impl ElementPaintStats {
+    /// The element's name and path, e.g. `Button @0.2.1`
+    pub fn label(&self) -> String {
+        let path: Vec<String> = self.path.iter().map(usize::to_string).collect();
+        format!("{} @{}", self.name, path.join("."))
+    }
+}
+
+/// An element whose paint is being timed
+struct Scope {
+    id: u64,
+    index: usize,
+    name: &'static str,
+    started: Instant,
+    draw_commands: usize,
+    children: usize,
+    child_time: Duration,
+    child_commands: usize,
+    element_id: Option<ElementId>,
+}
+
+struct Profiled {
+    stats: ElementPaintStats,
+    last_frame: u64,
+    /// Whether `stats` holds a paint time yet
+    sampled: bool,
+    /// Time and commands painted so far this frame
+    pending: Option<(Duration, usize)>,
+}
+
+#[derive(Default)]
+struct PaintProfiler {
+    /// Parent ID for the roots of the layer being painted
+    layer_seed: u64,
+    roots: usize,
+    stack: Vec<Scope>,
+    frame: u64,
+    elements: HashMap<u64, Profiled>,
+}
+
+impl PaintProfiler {
+    fn begin_layer(&mut self, z_index: i32) {
+        self.layer_seed = stable_hash(&("layer", z_index));
+        self.roots = 0;
+        // A panic mid-paint can leave scopes behind
+        self.stack.clear();
+    }
+
+    fn begin(&mut self, name: &'static str, draw_commands: usize) {
+        let (parent, index) = match self.stack.last_mut() {
+            Some(parent) => {
+                parent.children += 1;
+                (parent.id, parent.children - 1)
+            }
+            None => {
+                self.roots += 1;
+                (self.layer_seed, self.roots - 1)
+            }
+        };
+        self.stack.push(Scope {
+            id: stable_hash(&(parent, index, name)),
+            index,
+            name,
+            started: Instant::now(),
+            draw_commands,
+            children: 0,
+            child_time: Duration::ZERO,
+            child_commands: 0,
+            element_id: None,
+        });
+    }
+
+    fn end(&mut self, draw_commands: usize) {
+        let Some(scope) = self.stack.pop() else {
+            return;
+        };
+        let time = scope.started.elapsed();
+        let commands = draw_commands.saturating_sub(scope.draw_commands);
+        if let Some(parent) = self.stack.last_mut() {
+            parent.child_time += time;
+            parent.child_commands += commands;
+        }
+        let own_time = time.saturating_sub(scope.child_time);
+        let own_commands = commands.saturating_sub(scope.child_commands);
+
+        let stack = &self.stack;
+        let profiled = self.elements.entry(scope.id).or_insert_with(|| Profiled {
+            stats: ElementPaintStats {
+                id: scope.id,
+                name: short_type_name(scope.name),
+                path: stack
+                    .iter()
+                    .map(|parent| parent.index)
+                    .chain([scope.index])
+                    .collect(),
+                element_id: None,
+                draw_commands: 0,
+                paint_time: Duration::ZERO,
+            },
+            last_frame: 0,
+            sampled: false,
+            pending: None,
+        });
+        profiled.last_frame = self.frame;
+        if scope.element_id.is_some() {
+            profiled.stats.element_id = scope.element_id;
+        }
+        let (pending_time, pending_commands) = profiled.pending.get_or_insert_default();
+        *pending_time += own_time;
+        *pending_commands += own_commands;
+    }
+
+    fn end_frame(&mut self) {
+        for profiled in self.elements.values_mut() {
+            let Some((time, commands)) = profiled.pending.take() else {
+                continue;
+            };
+            let stats = &mut profiled.stats;
+            stats.draw_commands = commands;
+            stats.paint_time = if profiled.sampled {
+                stats.paint_time.mul_f64(1.0 - SMOOTHING) + time.mul_f64(SMOOTHING)
+            } else {
+                time
+            };
+            profiled.sampled = true;
+        }
+        let frame = self.frame;
+        self.elements
+            .retain(|_, profiled| frame - profiled.last_frame <= STALE_FRAMES);
+        self.frame += 1;
+    }
+
+    fn sorted(&self) -> Vec<ElementPaintStats> {
+        let mut elements: Vec<ElementPaintStats> = self
+            .elements
+            .values()
+            .filter(|profiled| profiled.sampled)
+            .map(|profiled| profiled.stats.clone())
+            .collect();
+        elements.sort_by_key(|stats| std::cmp::Reverse(stats.paint_time));
+        elements
+    }
+}
+
+fn stable_hash(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// `name` without its module path or generic parameters
+fn short_type_name(name: &'static str) -> &'static str {
+    let path = &name[..name.find('<').unwrap_or(name.len())];
+    path.rsplit("::").next().unwrap_or(path)
+}
+
+/// Debug panel listing the elements that take longest to paint
+pub struct PaintProfilerPanel {
+    max_rows: usize,
+}
+
+impl PaintProfilerPanel {
+    pub fn new() -> Self {
+        Self { max_rows: 10 }
+    }
+
+    /// Paint the panel
+    pub fn paint(&self, viewport: Rect, ctx: &mut PaintContext) {
+        let title_height = 20.0;
+        let line_height = 13.0;
+        let panel_width = 340.0;
+        // Below the metrics panel
+        let panel_bounds = Rect::from_pos_size(
+            viewport.pos + Vec2::new(viewport.size.x - panel_width - 8.0, 212.0),
+            Vec2::new(
+                panel_width,
+                title_height + 8.0 + self.max_rows as f32 * line_height,
+            ),
+        );
+
+        // Background
+        ctx.paint_solid_quad(panel_bounds, Color::rgba(0.05, 0.05, 0.05, 0.9));
+        ctx.paint_solid_quad(
+            Rect::from_pos_size(
+                panel_bounds.pos,
+                Vec2::new(panel_bounds.size.x, title_height),
+            ),
+            Color::rgba(0.15, 0.15, 0.15, 1.0),
+        );
+
+        let title = if paint_profiling() {
+            "Paint cost (own time, draw commands)"
+        } else {
+            "Paint cost (profiling off)"
+        };
+        ctx.paint_text(PaintText {
+            position: panel_bounds.pos + Vec2::new(8.0, 4.0),
+            text: title.to_string(),
+            style: TextStyle {
+                size: 11.0,
+                color: colors::WHITE,
+                ..Default::default()
+            },
+            measured_size: None,
+        });
+
+        let content_y = panel_bounds.pos.y + title_height + 4.0;
+        let max_chars = ((panel_bounds.size.x - 100.0) / 6.5) as usize;
+        for (i, stats) in most_expensive_elements(self.max_rows)
+            .into_iter()
+            .enumerate()
+        {
+            let mut label = stats.label();
+            if label.chars().count() > max_chars {
+                label = label.chars().take(max_chars - 3).collect();
+                label.push_str("...");
+            }
+            let y = content_y + i as f32 * line_height;
+            ctx.paint_text(PaintText {
+                position: Vec2::new(panel_bounds.pos.x + 8.0, y),
+                text: label,
+                style: TextStyle {
+                    size: 10.0,
+                    color: Color::rgba(0.8, 0.8, 0.8, 1.0),
+                    ..Default::default()
+                },
+                measured_size: None,
+            });
+            ctx.paint_text(PaintText {
+                position: Vec2::new(panel_bounds.pos.x + panel_bounds.size.x - 92.0, y),
+                text: format!(
+                    "{:>6.3}ms {:>4}",
+                    stats.paint_time.as_secs_f64() * 1000.0,
+                    stats.draw_commands
+                ),
+                style: TextStyle {
+                    size: 10.0,
+                    color: colors::YELLOW,
+                    ..Default::default()
+                },
+                measured_size: None,
+            });
+        }
+    }
+}
+
+impl Default for PaintProfilerPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Paint a root with two children, the second of them drawing
+    /// `commands` itself
+    fn paint_frame(commands: usize) {
+        begin_layer_paint_profile(0);
+        begin_element_paint("app::Root", 0);
+        begin_element_paint("sol_ui::element::Text", 0);
+        end_element_paint(1);
+        begin_element_paint("sol_ui::element::Canvas<f32>", 1);
+        attribute_element_id(ElementId::new(7));
+        end_element_paint(1 + commands);
+        end_element_paint(1 + commands);
+        end_paint_profile_frame();
+    }
+
+    #[test]
+    fn test_attributes_own_draw_commands() {
+        set_paint_profiling(true);
+        paint_frame(5);
+        paint_frame(5);
+        let profile = paint_profile();
+        set_paint_profiling(false);
+
+        assert_eq!(profile.len(), 3);
+        let canvas = profile.iter().find(|s| s.name == "Canvas").unwrap();
+        assert_eq!(canvas.draw_commands, 5);
+        assert_eq!(canvas.path, vec![0, 1]);
+        assert_eq!(canvas.element_id, Some(ElementId::new(7)));
+        assert_eq!(canvas.label(), "Canvas @0.1");
+        let root = profile.iter().find(|s| s.name == "Root").unwrap();
+        assert_eq!(root.draw_commands, 0);
+    }
+
+    #[test]
+    fn test_ids_are_stable_across_frames() {
+        set_paint_profiling(true);
+        paint_frame(1);
+        let first: Vec<u64> = paint_profile().iter().map(|s| s.id).collect();
+        paint_frame(1);
+        let second = paint_profile();
+        set_paint_profiling(false);
+
+        assert_eq!(second.len(), 3);
+        assert!(second.iter().all(|s| first.contains(&s.id)));
+    }
+
+    #[test]
+    fn test_nothing_recorded_while_off() {
+        set_paint_profiling(false);
+        paint_frame(1);
+        assert!(paint_profile().is_empty());
+    }
+}
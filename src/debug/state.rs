@@ -17,6 +17,10 @@ pub enum DebugPanel {
     Inspector,
     /// Debug console
     Console,
+    /// Entity mutation history
+    History,
+    /// Most expensive elements to paint
+    Profiler,
 }
 
 impl DebugPanel {
@@ -29,6 +33,8 @@ impl DebugPanel {
             DebugPanel::Metrics => "F4",
             DebugPanel::Inspector => "F5",
             DebugPanel::Console => "F6",
+            DebugPanel::History => "F7",
+            DebugPanel::Profiler => "F8",
         }
     }
 
@@ -41,6 +47,8 @@ impl DebugPanel {
             DebugPanel::Metrics => "Metrics",
             DebugPanel::Inspector => "Inspector",
             DebugPanel::Console => "Console",
+            DebugPanel::History => "History",
+            DebugPanel::Profiler => "Profiler",
         }
     }
 }
@@ -0,0 +1,524 @@
+//! Static element trees described in JSON, reloaded when the file changes
+//!
+//! A [`UiNode`] describes containers and text with their styles. Load one
+//! with [`UiNode::parse`] or [`UiNode::load`], or watch a file with
+//! [`UiDocument::watch`] so edits show up in the running app without
+//! recompiling:
+//!
+//! ```json
+//! {
+//!     "type": "column",
+//!     "style": { "padding": 16, "gap": 8, "background": "#1e1e1e", "corner_radius": 8 },
+//!     "children": [
+//!         { "type": "text", "text": "Inbox", "style": { "size": 20, "weight": 600 } },
+//!         { "type": "row", "style": { "gap": 4, "items": "center" }, "children": [] }
+//!     ]
+//! }
+//! ```
+//!
+//! ```ignore
+//! // Once, e.g. when the app launches
+//! let inbox = UiDocument::watch("ui/inbox.json");
+//!
+//! // While building the tree each frame
+//! root.child(ui_document(&inbox))
+//! ```
+//!
+//! Unknown fields are errors rather than being ignored, so a misspelled
+//! style shows up instead of silently doing nothing. While a watched file
+//! fails to load, the error is shown above the last tree that loaded.
+
+use crate::{
+    color::{Color, ColorExt},
+    element::{Container, Element, LayoutContext, column, container, row, text},
+    entity::{Entity, EntityStoreHandle, WeakEntity, entity_store_handle, new_entity, observe},
+    error::{SolResult, SolResultExt},
+    geometry::Rect,
+    render::PaintContext,
+    style::{FontWeight, LineHeight, Overflow, TextStyle},
+};
+use serde::{Deserialize, Deserializer};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime};
+use taffy::prelude::NodeId;
+use tracing::{error, warn};
+
+/// How often watched files are checked for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// An element in a declarative tree
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum UiNode {
+    /// A [`container`] with no direction set
+    Container {
+        #[serde(default)]
+        style: NodeStyle,
+        #[serde(default)]
+        children: Vec<UiNode>,
+    },
+    /// A horizontal [`row`]
+    Row {
+        #[serde(default)]
+        style: NodeStyle,
+        #[serde(default)]
+        children: Vec<UiNode>,
+    },
+    /// A vertical [`column`]
+    Column {
+        #[serde(default)]
+        style: NodeStyle,
+        #[serde(default)]
+        children: Vec<UiNode>,
+    },
+    /// A run of [`text`]
+    Text {
+        text: String,
+        #[serde(default)]
+        style: NodeTextStyle,
+    },
+}
+
+impl UiNode {
+    /// Parse a tree from JSON
+    pub fn parse(source: &str) -> SolResult<Self> {
+        Ok(serde_json::from_str(source)?)
+    }
+
+    /// Load a tree from a JSON file
+    pub fn load(path: impl AsRef<Path>) -> SolResult<Self> {
+        let path = path.as_ref();
+        std::fs::read_to_string(path)
+            .map_err(Into::into)
+            .and_then(|source| Self::parse(&source))
+            .with_context(|| format!("Failed to load {}", path.display()))
+    }
+
+    /// Build the element the node describes
+    pub fn build(&self) -> Box<dyn Element> {
+        match self {
+            UiNode::Container { style, children } => {
+                Box::new(build_container(container(), style, children))
+            }
+            UiNode::Row { style, children } => Box::new(build_container(row(), style, children)),
+            UiNode::Column { style, children } => {
+                Box::new(build_container(column(), style, children))
+            }
+            UiNode::Text {
+                text: content,
+                style,
+            } => Box::new(text(content.clone(), style.to_text_style())),
+        }
+    }
+}
+
+fn build_container(mut container: Container, style: &NodeStyle, children: &[UiNode]) -> Container {
+    container = style.apply(container);
+    for child in children {
+        container = container.child(child.build());
+    }
+    container
+}
+
+/// Layout and decoration of a container node
+///
+/// Lengths are in points. Anything left out keeps the container's default.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NodeStyle {
+    pub width: Option<Length>,
+    pub height: Option<Length>,
+    pub min_width: Option<f32>,
+    pub max_width: Option<f32>,
+    pub min_height: Option<f32>,
+    pub max_height: Option<f32>,
+    pub padding: Option<f32>,
+    pub padding_x: Option<f32>,
+    pub padding_y: Option<f32>,
+    pub margin: Option<f32>,
+    pub gap: Option<f32>,
+    /// Flex grow factor
+    pub grow: Option<f32>,
+    /// Main axis alignment
+    pub justify: Option<Justify>,
+    /// Cross axis alignment
+    pub items: Option<Align>,
+    pub background: Option<HexColor>,
+    pub border: Option<Border>,
+    pub corner_radius: Option<f32>,
+    /// Clip children to the bounds; scrolling needs state, so isn't offered
+    pub clip: bool,
+}
+
+/// Builder method setting one numeric container property
+type LengthSetter = fn(Container, f32) -> Container;
+
+impl NodeStyle {
+    /// Apply the style to a container
+    pub fn apply(&self, mut container: Container) -> Container {
+        container = match self.width {
+            Some(Length::Points(width)) => container.width(width),
+            Some(Length::Full) => container.width_full(),
+            None => container,
+        };
+        container = match self.height {
+            Some(Length::Points(height)) => container.height(height),
+            Some(Length::Full) => container.height_full(),
+            None => container,
+        };
+        let lengths: [(Option<f32>, LengthSetter); 10] = [
+            (self.min_width, Container::min_width),
+            (self.max_width, Container::max_width),
+            (self.min_height, Container::min_height),
+            (self.max_height, Container::max_height),
+            (self.padding, Container::padding),
+            (self.padding_x, Container::padding_x),
+            (self.padding_y, Container::padding_y),
+            (self.margin, Container::margin),
+            (self.gap, Container::gap),
+            (self.grow, Container::flex_grow),
+        ];
+        for (value, set) in lengths {
+            if let Some(value) = value {
+                container = set(container, value);
+            }
+        }
+        container = match self.justify {
+            Some(Justify::Start) => container.justify_start(),
+            Some(Justify::End) => container.justify_end(),
+            Some(Justify::Center) => container.justify_center(),
+            Some(Justify::Between) => container.justify_between(),
+            Some(Justify::Around) => container.justify_around(),
+            Some(Justify::Evenly) => container.justify_evenly(),
+            None => container,
+        };
+        container = match self.items {
+            Some(Align::Start) => container.items_start(),
+            Some(Align::End) => container.items_end(),
+            Some(Align::Center) => container.items_center(),
+            Some(Align::Stretch) => container.items_stretch(),
+            Some(Align::Baseline) => container.items_baseline(),
+            None => container,
+        };
+        if let Some(HexColor(color)) = self.background {
+            container = container.background(color);
+        }
+        if let Some(border) = &self.border {
+            container = container.border(border.color.0, border.width);
+        }
+        if let Some(radius) = self.corner_radius {
+            container = container.corner_radius(radius);
+        }
+        if self.clip {
+            container = container.overflow(Overflow::Clip);
+        }
+        container
+    }
+}
+
+/// Style of a text node
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NodeTextStyle {
+    pub size: Option<f32>,
+    pub color: Option<HexColor>,
+    /// Font weight from 1 to 1000, where 400 is normal and 700 bold
+    pub weight: Option<f32>,
+    pub line_height: Option<f32>,
+}
+
+impl NodeTextStyle {
+    /// The text style, with anything left out taken from the default
+    pub fn to_text_style(&self) -> TextStyle {
+        let mut style = TextStyle::default();
+        if let Some(size) = self.size {
+            style.size = size;
+        }
+        if let Some(HexColor(color)) = self.color {
+            style.color = color;
+        }
+        if let Some(weight) = self.weight {
+            style.weight = FontWeight::new(weight);
+        }
+        if let Some(line_height) = self.line_height {
+            style.line_height = LineHeight::Relative(line_height);
+        }
+        style
+    }
+}
+
+/// A width or height: points, or `"full"` to fill the parent
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Length {
+    Points(f32),
+    Full,
+}
+
+impl<'de> Deserialize<'de> for Length {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Points(f32),
+            Keyword(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Points(points) => Ok(Length::Points(points)),
+            Raw::Keyword(keyword) if keyword == "full" => Ok(Length::Full),
+            Raw::Keyword(keyword) => Err(serde::de::Error::custom(format!(
+                "expected a number or \"full\", got {keyword:?}"
+            ))),
+        }
+    }
+}
+
+/// Main axis alignment of a container's children
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Justify {
+    Start,
+    End,
+    Center,
+    Between,
+    Around,
+    Evenly,
+}
+
+/// Cross axis alignment of a container's children
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Align {
+    Start,
+    End,
+    Center,
+    Stretch,
+    Baseline,
+}
+
+/// A container border
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Border {
+    pub color: HexColor,
+    #[serde(default = "default_border_width")]
+    pub width: f32,
+}
+
+fn default_border_width() -> f32 {
+    1.0
+}
+
+/// A color written as a hex string, such as `"#1e1e1e"` or `"#0000ff80"`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HexColor(pub Color);
+
+impl<'de> Deserialize<'de> for HexColor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        Color::try_hex(&hex)
+            .map(HexColor)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid hex color {hex:?}")))
+    }
+}
+
+/// A tree loaded from a file, reloaded whenever the file changes
+///
+/// The file is checked a few times a second on a background thread, which
+/// stops when the document is dropped.
+pub struct UiDocument {
+    path: PathBuf,
+    /// The last tree that loaded
+    root: Option<UiNode>,
+    /// Why the latest version of the file didn't load
+    error: Option<String>,
+    /// Cleared on drop to stop the watcher thread
+    watching: Arc<AtomicBool>,
+}
+
+impl UiDocument {
+    /// Load a file and keep watching it for changes
+    ///
+    /// Must be called while the entity store is available, such as during
+    /// rendering or in a lifecycle handler.
+    pub fn watch(path: impl Into<PathBuf>) -> Entity<UiDocument> {
+        let path = path.into();
+        let watching = Arc::new(AtomicBool::new(true));
+        let mut document = UiDocument {
+            path: path.clone(),
+            root: None,
+            error: None,
+            watching: watching.clone(),
+        };
+        let modified = modified_time(&path);
+        document.set_loaded(UiNode::load(&path).map_err(|e| e.to_string()));
+        let entity = new_entity(document);
+
+        let handle = entity_store_handle();
+        let weak = entity.downgrade();
+        let spawned = std::thread::Builder::new()
+            .name("ui-document-watcher".to_string())
+            .spawn(move || watch_file(path, modified, weak, handle, watching));
+        if let Err(e) = spawned {
+            error!("Failed to start watching UI document: {}", e);
+        }
+        entity
+    }
+
+    /// The file the document is loaded from
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The last tree that loaded, if any has
+    pub fn root(&self) -> Option<&UiNode> {
+        self.root.as_ref()
+    }
+
+    /// Why the file's latest contents didn't load, if they didn't
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Take a load result, keeping the last good tree on failure
+    fn set_loaded(&mut self, result: Result<UiNode, String>) {
+        match result {
+            Ok(root) => {
+                self.root = Some(root);
+                self.error = None;
+            }
+            Err(error) => {
+                warn!("{}", error);
+                self.error = Some(error);
+            }
+        }
+    }
+}
+
+impl Drop for UiDocument {
+    fn drop(&mut self) {
+        self.watching.store(false, Ordering::Relaxed);
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Reload `path` into the document whenever its modification time changes
+fn watch_file(
+    path: PathBuf,
+    mut last_modified: Option<SystemTime>,
+    document: WeakEntity<UiDocument>,
+    handle: EntityStoreHandle,
+    watching: Arc<AtomicBool>,
+) {
+    while watching.load(Ordering::Relaxed) {
+        std::thread::sleep(POLL_INTERVAL);
+        let modified = modified_time(&path);
+        if modified == last_modified {
+            continue;
+        }
+        last_modified = modified;
+        // Editors that save by replacing the file leave it missing briefly
+        if modified.is_none() {
+            continue;
+        }
+        let result = UiNode::load(&path).map_err(|e| e.to_string());
+        handle.update(&document, move |document| document.set_loaded(result));
+    }
+}
+
+/// Show the current tree of a watched document
+pub fn ui_document(document: &Entity<UiDocument>) -> DocumentView {
+    DocumentView {
+        document: document.clone(),
+        content: None,
+    }
+}
+
+/// Element showing a [`UiDocument`], rebuilt when the document reloads
+pub struct DocumentView {
+    document: Entity<UiDocument>,
+    content: Option<Box<dyn Element>>,
+}
+
+impl Element for DocumentView {
+    fn layout(&mut self, ctx: &mut LayoutContext) -> NodeId {
+        let (root, error) = observe(&self.document, |document| {
+            (document.root.clone(), document.error.clone())
+        })
+        .unwrap_or_default();
+
+        let tree = root.map(|root| root.build());
+        let content: Box<dyn Element> = match (error, tree) {
+            (None, Some(tree)) => tree,
+            (error, tree) => {
+                let mut content = column();
+                if let Some(error) = error {
+                    let style = TextStyle {
+                        size: 12.0,
+                        color: Color::hex("#ff453a"),
+                        font_family: "monospace",
+                        ..Default::default()
+                    };
+                    content = content.padding(8.0).child(text(error, style));
+                }
+                if let Some(tree) = tree {
+                    content = content.child(tree);
+                }
+                Box::new(content)
+            }
+        };
+        self.content.insert(content).layout(ctx)
+    }
+
+    fn paint(&mut self, bounds: Rect, ctx: &mut PaintContext) {
+        if let Some(content) = &mut self.content {
+            content.paint(bounds, ctx);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tree() {
+        let node = UiNode::parse(
+            r##"{
+                "type": "column",
+                "style": { "padding": 16, "width": "full", "background": "#102030" },
+                "children": [{ "type": "text", "text": "Hi", "style": { "size": 20 } }]
+            }"##,
+        )
+        .unwrap();
+
+        let UiNode::Column { style, children } = node else {
+            panic!("expected a column, got {:?}", node);
+        };
+        assert_eq!(style.padding, Some(16.0));
+        assert_eq!(style.width, Some(Length::Full));
+        assert_eq!(style.background, Some(HexColor(Color::hex("#102030"))));
+        assert_eq!(
+            children,
+            vec![UiNode::Text {
+                text: "Hi".to_string(),
+                style: NodeTextStyle {
+                    size: Some(20.0),
+                    ..Default::default()
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_fields_and_bad_colors() {
+        assert!(UiNode::parse(r#"{ "type": "row", "style": { "paddin": 4 } }"#).is_err());
+        assert!(UiNode::parse(r#"{ "type": "row", "style": { "background": "red" } }"#).is_err());
+        assert!(UiNode::parse(r##"{ "type": "text", "text": "a", "colour": "#fff" }"##).is_err());
+    }
+}
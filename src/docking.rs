@@ -0,0 +1,1181 @@
+//! Docking layout for tool windows
+//!
+//! A [`DockLayout`] describes where each panel lives: docked to one of the
+//! window edges, tabbed into the center area, or floating above everything
+//! else. Panels that share an edge are shown as tabs. The layout is plain data,
+//! so it can be stored in an entity, mutated from event handlers, and
+//! serialized to restore the arrangement in the next session.
+//!
+//! [`DockView`] renders a layout. Panel content is supplied each frame by id:
+//!
+//! ```ignore
+//! let dock = new_entity(storage.load("dock")?.unwrap_or_else(|| {
+//!     let mut layout = DockLayout::new();
+//!     layout.dock("files", DockTarget::Edge(DockEdge::Left));
+//!     layout.dock("editor", DockTarget::Center);
+//!     layout.dock("terminal", DockTarget::Edge(DockEdge::Bottom));
+//!     layout
+//! }));
+//!
+//! dock_view(dock.clone())
+//!     .with_key("main")
+//!     .panel("files", "Files", file_tree())
+//!     .panel("editor", "Editor", editor())
+//!     .panel("terminal", "Terminal", terminal())
+//! ```
+//!
+//! Dragging a tab onto a window edge docks it there, dropping it on another
+//! panel adds it as a tab, and holding Alt while dropping floats it. Dragging
+//! the splitter next to an edge panel resizes it.
+
+use crate::{
+    color::{Color, ColorExt, colors},
+    element::{Element, LayoutContext},
+    entity::{Entity, read_entity, update_entity},
+    geometry::{Corners, Edges, Rect},
+    interaction::{
+        ElementId, EventHandlers,
+        registry::{get_element_state, register_element},
+    },
+    layer::MouseButton,
+    render::{PaintContext, PaintQuad, PaintText},
+    storage::{Storage, StorageResult},
+    style::TextStyle,
+};
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+use taffy::prelude::*;
+
+/// Default width (left/right) or height (top/bottom) of a docked edge
+pub const DEFAULT_DOCK_SIZE: f32 = 240.0;
+
+/// Smallest size an edge can be resized to
+pub const MIN_DOCK_SIZE: f32 = 80.0;
+
+/// Horizontal padding around a tab title
+const TAB_PADDING: f32 = 12.0;
+
+/// Distance the pointer must move before a tab press becomes a drag
+const DRAG_THRESHOLD: f32 = 4.0;
+
+/// Hit-test z-index of the first floating panel; later panels stack above
+const FLOATING_Z: i32 = 50;
+
+/// Hit-test z-index of the overlay shown while dragging
+const DRAG_OVERLAY_Z: i32 = 200;
+
+/// Window edge a panel group can be docked to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum DockEdge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl DockEdge {
+    /// Whether this edge is sized by width (left/right) rather than height
+    pub fn is_vertical(self) -> bool {
+        matches!(self, DockEdge::Left | DockEdge::Right)
+    }
+}
+
+/// Where a panel can be docked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DockTarget {
+    /// Docked to a window edge
+    Edge(DockEdge),
+    /// Tabbed into the center area
+    Center,
+}
+
+/// Current location of a panel in a layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PanelLocation {
+    Docked(DockTarget),
+    Floating,
+}
+
+/// A group of panels shown as tabs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TabGroup {
+    /// Panel ids, in tab order
+    pub panels: Vec<String>,
+    /// Index of the visible tab
+    pub active: usize,
+    /// Width or height of the group when docked to an edge
+    pub size: f32,
+}
+
+impl TabGroup {
+    fn new(size: f32) -> Self {
+        Self {
+            panels: Vec::new(),
+            active: 0,
+            size,
+        }
+    }
+
+    /// Id of the visible panel
+    pub fn active_panel(&self) -> Option<&str> {
+        self.panels.get(self.active).map(|s| s.as_str())
+    }
+
+    fn remove(&mut self, panel: &str) -> bool {
+        let Some(index) = self.panels.iter().position(|p| p == panel) else {
+            return false;
+        };
+        self.panels.remove(index);
+        if self.active > index || self.active >= self.panels.len() {
+            self.active = self.active.saturating_sub(1);
+        }
+        true
+    }
+}
+
+/// A panel floating above the docked layout
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FloatingPanel {
+    pub panel: String,
+    pub position: Vec2,
+    pub size: Vec2,
+}
+
+/// Arrangement of panels in a dock view
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DockLayout {
+    /// Panel groups docked to window edges
+    pub edges: BTreeMap<DockEdge, TabGroup>,
+    /// Panels tabbed into the center area
+    pub center: TabGroup,
+    /// Floating panels, back to front
+    pub floating: Vec<FloatingPanel>,
+    /// In-progress drag or resize (not persisted)
+    #[serde(skip)]
+    interaction: DockInteraction,
+    /// Area bounds from the last paint, used to resolve drops (not persisted)
+    #[serde(skip)]
+    frame: DockFrame,
+}
+
+impl DockLayout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Dock a panel, moving it from wherever it currently is
+    ///
+    /// The panel becomes the active tab of its new group.
+    pub fn dock(&mut self, panel: impl Into<String>, target: DockTarget) {
+        let panel = panel.into();
+        self.remove(&panel);
+
+        let group = match target {
+            DockTarget::Edge(edge) => self
+                .edges
+                .entry(edge)
+                .or_insert_with(|| TabGroup::new(DEFAULT_DOCK_SIZE)),
+            DockTarget::Center => &mut self.center,
+        };
+        group.panels.push(panel);
+        group.active = group.panels.len() - 1;
+    }
+
+    /// Float a panel at the given position and size
+    pub fn float(&mut self, panel: impl Into<String>, position: Vec2, size: Vec2) {
+        let panel = panel.into();
+        self.remove(&panel);
+        self.floating.push(FloatingPanel {
+            panel,
+            position,
+            size,
+        });
+    }
+
+    /// Remove a panel from the layout, returning whether it was present
+    ///
+    /// Edge groups left without panels are removed as well.
+    pub fn remove(&mut self, panel: &str) -> bool {
+        let mut removed = self.center.remove(panel);
+        for group in self.edges.values_mut() {
+            removed |= group.remove(panel);
+        }
+        self.edges.retain(|_, group| !group.panels.is_empty());
+
+        let floating_count = self.floating.len();
+        self.floating.retain(|f| f.panel != panel);
+        removed || self.floating.len() != floating_count
+    }
+
+    /// Get where a panel currently is
+    pub fn location(&self, panel: &str) -> Option<PanelLocation> {
+        if self.center.panels.iter().any(|p| p == panel) {
+            return Some(PanelLocation::Docked(DockTarget::Center));
+        }
+        for (edge, group) in &self.edges {
+            if group.panels.iter().any(|p| p == panel) {
+                return Some(PanelLocation::Docked(DockTarget::Edge(*edge)));
+            }
+        }
+        self.floating
+            .iter()
+            .any(|f| f.panel == panel)
+            .then_some(PanelLocation::Floating)
+    }
+
+    /// Check if a panel is part of the layout
+    pub fn contains(&self, panel: &str) -> bool {
+        self.location(panel).is_some()
+    }
+
+    /// Make a panel the visible tab of its group, or bring it to the front if floating
+    pub fn activate(&mut self, panel: &str) -> bool {
+        for group in std::iter::once(&mut self.center).chain(self.edges.values_mut()) {
+            if let Some(index) = group.panels.iter().position(|p| p == panel) {
+                group.active = index;
+                return true;
+            }
+        }
+        if let Some(index) = self.floating.iter().position(|f| f.panel == panel) {
+            let floating = self.floating.remove(index);
+            self.floating.push(floating);
+            return true;
+        }
+        false
+    }
+
+    /// Set the size of a docked edge, clamped to [`MIN_DOCK_SIZE`]
+    pub fn resize(&mut self, edge: DockEdge, size: f32) {
+        if let Some(group) = self.edges.get_mut(&edge) {
+            group.size = size.max(MIN_DOCK_SIZE);
+        }
+    }
+
+    /// Move a floating panel
+    pub fn move_floating(&mut self, panel: &str, position: Vec2) {
+        if let Some(floating) = self.floating.iter_mut().find(|f| f.panel == panel) {
+            floating.position = position;
+        }
+    }
+
+    /// Save the arrangement to storage
+    pub fn save(&self, storage: &Storage, name: &str) -> StorageResult<()> {
+        storage.save(name, self)
+    }
+
+    /// Load a saved arrangement from storage
+    pub fn load(storage: &Storage, name: &str) -> StorageResult<Option<Self>> {
+        storage.load(name)
+    }
+
+    /// Handle the end of a tab drag at `position`
+    fn drop_panel(&mut self, panel: &str, position: Vec2, float: bool, edge_zone: f32) {
+        let dragged = match &self.interaction {
+            DockInteraction::DraggingTab {
+                start, grab_offset, ..
+            } => Some((*start, *grab_offset)),
+            _ => None,
+        };
+        self.interaction = DockInteraction::Idle;
+
+        let Some((start, grab_offset)) = dragged else {
+            return;
+        };
+        if (position - start).length() < DRAG_THRESHOLD {
+            self.activate(panel);
+            return;
+        }
+
+        let was_floating = self.location(panel) == Some(PanelLocation::Floating);
+        let target = self.frame.drop_target(position, edge_zone);
+
+        if float || (was_floating && target == DockTarget::Center) {
+            let size = self
+                .floating
+                .iter()
+                .find(|f| f.panel == panel)
+                .map(|f| f.size)
+                .unwrap_or(Vec2::new(320.0, 240.0));
+            if was_floating {
+                self.move_floating(panel, position - grab_offset);
+                self.activate(panel);
+            } else {
+                self.float(panel, position - grab_offset, size);
+            }
+        } else {
+            self.dock(panel, target);
+        }
+    }
+}
+
+/// In-progress pointer interaction in a dock view
+#[derive(Debug, Clone, Default)]
+enum DockInteraction {
+    #[default]
+    Idle,
+    /// A tab is being dragged to a new location
+    DraggingTab {
+        start: Vec2,
+        pointer: Vec2,
+        /// Pointer position relative to the dragged panel's origin
+        grab_offset: Vec2,
+    },
+    /// An edge splitter is being dragged
+    Resizing {
+        edge: DockEdge,
+        start: Vec2,
+        start_size: f32,
+    },
+}
+
+/// Bounds of the docked areas from the last paint
+#[derive(Debug, Clone, Default)]
+struct DockFrame {
+    bounds: Option<Rect>,
+    areas: Vec<(DockTarget, Rect)>,
+}
+
+impl DockFrame {
+    fn edge_at(&self, position: Vec2, edge_zone: f32) -> Option<DockEdge> {
+        let bounds = self.bounds?;
+        let min = bounds.pos;
+        let max = bounds.pos + bounds.size;
+        if position.x < min.x + edge_zone {
+            Some(DockEdge::Left)
+        } else if position.x > max.x - edge_zone {
+            Some(DockEdge::Right)
+        } else if position.y < min.y + edge_zone {
+            Some(DockEdge::Top)
+        } else if position.y > max.y - edge_zone {
+            Some(DockEdge::Bottom)
+        } else {
+            None
+        }
+    }
+
+    /// Resolve where a panel dropped at `position` should be docked
+    ///
+    /// Drops near a window edge dock to that edge, drops over a docked area
+    /// join it as a tab, and anything else goes to the center.
+    fn drop_target(&self, position: Vec2, edge_zone: f32) -> DockTarget {
+        if let Some(edge) = self.edge_at(position, edge_zone) {
+            return DockTarget::Edge(edge);
+        }
+        self.areas
+            .iter()
+            .find(|(_, bounds)| bounds.contains(position.into()))
+            .map(|(target, _)| *target)
+            .unwrap_or(DockTarget::Center)
+    }
+
+    /// Bounds highlighted while hovering a drop target
+    fn drop_preview(&self, position: Vec2, edge_zone: f32) -> Option<Rect> {
+        let bounds = self.bounds?;
+        if let Some(edge) = self.edge_at(position, edge_zone) {
+            let (pos, size) = match edge {
+                DockEdge::Left => (bounds.pos, Vec2::new(DEFAULT_DOCK_SIZE, bounds.size.y)),
+                DockEdge::Right => (
+                    Vec2::new(
+                        bounds.pos.x + bounds.size.x - DEFAULT_DOCK_SIZE,
+                        bounds.pos.y,
+                    ),
+                    Vec2::new(DEFAULT_DOCK_SIZE, bounds.size.y),
+                ),
+                DockEdge::Top => (bounds.pos, Vec2::new(bounds.size.x, DEFAULT_DOCK_SIZE)),
+                DockEdge::Bottom => (
+                    Vec2::new(
+                        bounds.pos.x,
+                        bounds.pos.y + bounds.size.y - DEFAULT_DOCK_SIZE,
+                    ),
+                    Vec2::new(bounds.size.x, DEFAULT_DOCK_SIZE),
+                ),
+            };
+            return Some(Rect::from_pos_size(pos, size));
+        }
+        let target = self.drop_target(position, edge_zone);
+        self.areas
+            .iter()
+            .find(|(t, _)| *t == target)
+            .map(|(_, bounds)| *bounds)
+    }
+}
+
+/// Create a dock view for a layout
+pub fn dock_view(layout: Entity<DockLayout>) -> DockView {
+    DockView::new(layout)
+}
+
+/// Content and title of a panel
+struct DockPanel {
+    title: String,
+    content: Option<Box<dyn Element>>,
+}
+
+/// Which part of the layout an area renders
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AreaSlot {
+    Docked(DockTarget),
+    Floating(usize),
+}
+
+/// A tab in an area's tab bar
+struct DockTab {
+    panel: String,
+    title: String,
+    width: f32,
+    active: bool,
+}
+
+/// A laid-out tab group or floating panel
+struct DockArea {
+    slot: AreaSlot,
+    node: NodeId,
+    /// Whether the area is nested in the middle column
+    in_middle: bool,
+    content_node: NodeId,
+    content: Option<(Box<dyn Element>, NodeId)>,
+    tabs: Vec<DockTab>,
+}
+
+/// Renders a [`DockLayout`] with tab bars, splitters, and floating panels
+pub struct DockView {
+    layout: Entity<DockLayout>,
+    key: String,
+    panels: HashMap<String, DockPanel>,
+
+    // Styling
+    tab_bar_height: f32,
+    splitter_size: f32,
+    edge_drop_zone: f32,
+    background: Color,
+    panel_background: Color,
+    tab_bar_background: Color,
+    active_tab_background: Color,
+    border_color: Color,
+    splitter_hover_color: Color,
+    drop_indicator_color: Color,
+    text_style: TextStyle,
+
+    // Per-frame layout results
+    areas: Vec<DockArea>,
+    splitters: Vec<(DockEdge, NodeId, bool)>,
+    middle_node: Option<NodeId>,
+}
+
+impl DockView {
+    pub fn new(layout: Entity<DockLayout>) -> Self {
+        Self {
+            layout,
+            key: String::new(),
+            panels: HashMap::new(),
+            tab_bar_height: 28.0,
+            splitter_size: 4.0,
+            edge_drop_zone: 48.0,
+            background: colors::GRAY_100,
+            panel_background: colors::WHITE,
+            tab_bar_background: colors::GRAY_200,
+            active_tab_background: colors::WHITE,
+            border_color: colors::GRAY_300,
+            splitter_hover_color: colors::BLUE_400,
+            drop_indicator_color: colors::BLUE_400.with_alpha(0.25),
+            text_style: TextStyle {
+                size: 13.0,
+                color: colors::GRAY_800,
+                ..Default::default()
+            },
+            areas: Vec::new(),
+            splitters: Vec::new(),
+            middle_node: None,
+        }
+    }
+
+    /// Set a unique key used to derive stable element ids
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.key = key.into();
+        self
+    }
+
+    /// Provide the title and content for a panel id
+    ///
+    /// Panels that are not part of the layout are ignored.
+    pub fn panel(
+        mut self,
+        id: impl Into<String>,
+        title: impl Into<String>,
+        content: impl Element + 'static,
+    ) -> Self {
+        self.panels.insert(
+            id.into(),
+            DockPanel {
+                title: title.into(),
+                content: Some(Box::new(content)),
+            },
+        );
+        self
+    }
+
+    /// Set the height of tab bars
+    pub fn tab_bar_height(mut self, height: f32) -> Self {
+        self.tab_bar_height = height;
+        self
+    }
+
+    /// Set the thickness of the splitters between areas
+    pub fn splitter_size(mut self, size: f32) -> Self {
+        self.splitter_size = size;
+        self
+    }
+
+    /// Set background color
+    pub fn background(mut self, color: Color) -> Self {
+        self.background = color;
+        self
+    }
+
+    /// Set the background behind docked panels' content
+    pub fn panel_background(mut self, color: Color) -> Self {
+        self.panel_background = color;
+        self
+    }
+
+    /// Set tab bar colors
+    pub fn tab_colors(mut self, bar: Color, active_tab: Color) -> Self {
+        self.tab_bar_background = bar;
+        self.active_tab_background = active_tab;
+        self
+    }
+
+    /// Set border color
+    pub fn border_color(mut self, color: Color) -> Self {
+        self.border_color = color;
+        self
+    }
+
+    /// Set text style for tab titles
+    pub fn text_style(mut self, style: TextStyle) -> Self {
+        self.text_style = style;
+        self
+    }
+
+    fn id(&self, part: impl std::fmt::Display) -> ElementId {
+        ElementId::stable(format!("dock:{}:{}", self.key, part))
+    }
+
+    /// Lay out a tab bar and the active panel's content
+    fn layout_area(
+        &mut self,
+        ctx: &mut LayoutContext,
+        slot: AreaSlot,
+        panels: &[String],
+        active: usize,
+        mut style: Style,
+        in_middle: bool,
+    ) -> NodeId {
+        let tabs = panels
+            .iter()
+            .enumerate()
+            .map(|(i, id)| {
+                let title = self
+                    .panels
+                    .get(id)
+                    .map(|p| p.title.clone())
+                    .unwrap_or_else(|| id.clone());
+                let width = ctx.measure_text(&title, &self.text_style, None).x + TAB_PADDING * 2.0;
+                DockTab {
+                    panel: id.clone(),
+                    title,
+                    width,
+                    active: i == active,
+                }
+            })
+            .collect();
+
+        let content = panels
+            .get(active)
+            .and_then(|id| self.panels.get_mut(id))
+            .and_then(|p| p.content.take())
+            .map(|mut element| {
+                let node = element.layout(ctx);
+                (element, node)
+            });
+
+        let tab_bar = ctx.request_layout(Style {
+            size: Size {
+                width: Dimension::percent(1.0),
+                height: Dimension::length(self.tab_bar_height),
+            },
+            flex_shrink: 0.0,
+            ..Default::default()
+        });
+        let content_children: Vec<NodeId> = content.iter().map(|(_, node)| *node).collect();
+        let content_node = ctx.request_layout_with_children(
+            Style {
+                flex_grow: 1.0,
+                flex_basis: Dimension::length(0.0),
+                ..Default::default()
+            },
+            &content_children,
+        );
+
+        style.flex_direction = FlexDirection::Column;
+        let node = ctx.request_layout_with_children(style, &[tab_bar, content_node]);
+
+        self.areas.push(DockArea {
+            slot,
+            node,
+            in_middle,
+            content_node,
+            content,
+            tabs,
+        });
+        node
+    }
+
+    fn splitter_node(
+        &mut self,
+        ctx: &mut LayoutContext,
+        edge: DockEdge,
+        in_middle: bool,
+    ) -> NodeId {
+        let size = if edge.is_vertical() {
+            Size {
+                width: Dimension::length(self.splitter_size),
+                height: Dimension::auto(),
+            }
+        } else {
+            Size {
+                width: Dimension::auto(),
+                height: Dimension::length(self.splitter_size),
+            }
+        };
+        let node = ctx.request_layout(Style {
+            size,
+            flex_shrink: 0.0,
+            ..Default::default()
+        });
+        self.splitters.push((edge, node, in_middle));
+        node
+    }
+
+    /// Absolute bounds of a node that is a child of the root or the middle column
+    fn node_bounds(&self, ctx: &PaintContext, root: Rect, node: NodeId, in_middle: bool) -> Rect {
+        let mut origin = root.pos;
+        if in_middle && let Some(middle) = self.middle_node {
+            origin += ctx.layout_engine.layout_bounds(middle).pos;
+        }
+        let local = ctx.layout_engine.layout_bounds(node);
+        Rect::from_pos_size(origin + local.pos, local.size)
+    }
+
+    /// Register handlers for a tab: press starts a drag, release drops it
+    fn register_tab_handlers(&self, id: ElementId, panel: &str, area_origin: Vec2) {
+        let layout = self.layout.clone();
+        let panel = panel.to_string();
+        let edge_zone = self.edge_drop_zone;
+
+        let handlers = EventHandlers::new()
+            .on_mouse_down({
+                let layout = layout.clone();
+                move |button, position, _, _, _| {
+                    if button != MouseButton::Left {
+                        return;
+                    }
+                    update_entity(&layout, |l| {
+                        l.interaction = DockInteraction::DraggingTab {
+                            start: position,
+                            pointer: position,
+                            grab_offset: position - area_origin,
+                        };
+                    });
+                }
+            })
+            .on_mouse_up(move |button, position, _, modifiers| {
+                if button != MouseButton::Left {
+                    return;
+                }
+                update_entity(&layout, |l| {
+                    l.drop_panel(&panel, position, modifiers.alt, edge_zone)
+                });
+            });
+
+        register_element(id, Rc::new(RefCell::new(handlers)));
+    }
+
+    /// Register handlers for an edge splitter
+    fn register_splitter_handlers(&self, id: ElementId, edge: DockEdge) {
+        let layout = self.layout.clone();
+        let handlers = EventHandlers::new()
+            .on_mouse_down({
+                let layout = layout.clone();
+                move |button, position, _, _, _| {
+                    if button != MouseButton::Left {
+                        return;
+                    }
+                    update_entity(&layout, |l| {
+                        let start_size = l.edges.get(&edge).map(|g| g.size).unwrap_or(0.0);
+                        l.interaction = DockInteraction::Resizing {
+                            edge,
+                            start: position,
+                            start_size,
+                        };
+                    });
+                }
+            })
+            .on_mouse_up(move |_, _, _, _| {
+                update_entity(&layout, |l| l.interaction = DockInteraction::Idle);
+            });
+
+        register_element(id, Rc::new(RefCell::new(handlers)));
+    }
+
+    /// Paint one area: tab bar, tabs, and the active panel's content
+    fn paint_area(&mut self, index: usize, bounds: Rect, ctx: &mut PaintContext) {
+        let tab_bar_height = self.tab_bar_height;
+        let area = &self.areas[index];
+        let slot = area.slot;
+
+        ctx.paint_quad(PaintQuad {
+            bounds,
+            fill: self.panel_background,
+            corner_radii: Corners::all(0.0),
+            border_widths: Edges::all(1.0),
+            border_color: self.border_color,
+        });
+        ctx.paint_quad(PaintQuad::filled(
+            Rect::from_pos_size(bounds.pos, Vec2::new(bounds.size.x, tab_bar_height)),
+            self.tab_bar_background,
+        ));
+
+        // Tabs
+        let mut tab_x = bounds.pos.x;
+        let mut tab_hits = Vec::new();
+        for tab in &area.tabs {
+            let tab_bounds = Rect::from_pos_size(
+                Vec2::new(tab_x, bounds.pos.y),
+                Vec2::new(tab.width, tab_bar_height),
+            );
+            tab_x += tab.width;
+
+            let id = self.id(format!("tab:{}", tab.panel));
+            let is_hovered = get_element_state(id).unwrap_or_default().is_hovered;
+            if tab.active {
+                ctx.paint_quad(PaintQuad::filled(tab_bounds, self.active_tab_background));
+            } else if is_hovered {
+                ctx.paint_quad(PaintQuad::filled(
+                    tab_bounds,
+                    self.active_tab_background.with_alpha(0.5),
+                ));
+            }
+            ctx.paint_text(PaintText {
+                position: tab_bounds.pos
+                    + Vec2::new(TAB_PADDING, (tab_bar_height - self.text_style.size) / 2.0),
+                text: tab.title.clone(),
+                style: self.text_style.clone(),
+                measured_size: None,
+            });
+            tab_hits.push((id, tab.panel.clone(), tab_bounds));
+        }
+
+        let z = match slot {
+            AreaSlot::Floating(i) => FLOATING_Z + i as i32 * 10,
+            AreaSlot::Docked(_) => 0,
+        };
+        for (id, panel, tab_bounds) in tab_hits {
+            self.register_tab_handlers(id, &panel, bounds.pos);
+            ctx.register_hit_test(id, tab_bounds, z + 1);
+        }
+
+        // Content, clipped to the area below the tab bar
+        let content_node = self.areas[index].content_node;
+        let content_bounds = {
+            let local = ctx.layout_engine.layout_bounds(content_node);
+            Rect::from_pos_size(bounds.pos + local.pos, local.size)
+        };
+        if let Some((element, node)) = self.areas[index].content.as_mut() {
+            let local = ctx.layout_engine.layout_bounds(*node);
+            let child_bounds = Rect::from_pos_size(content_bounds.pos + local.pos, local.size);
+
+            ctx.draw_list.push_clip(content_bounds);
+            if let Some(builder) = &ctx.hit_test_builder {
+                builder.borrow_mut().push_z_context(z);
+            }
+            element.paint(child_bounds, ctx);
+            if let Some(builder) = &ctx.hit_test_builder {
+                builder.borrow_mut().pop_z_context(z);
+            }
+            ctx.draw_list.pop_clip();
+        }
+
+        // Floating panels block input to the docked areas beneath them
+        if let AreaSlot::Floating(i) = slot {
+            ctx.register_hit_test(self.id(format!("floating:{}", i)), bounds, z - 1);
+        }
+    }
+}
+
+impl Element for DockView {
+    fn layout(&mut self, ctx: &mut LayoutContext) -> NodeId {
+        let layout = read_entity(&self.layout, |l| l.clone()).unwrap_or_default();
+        self.areas.clear();
+        self.splitters.clear();
+
+        let edge_group = |edge: DockEdge| {
+            layout
+                .edges
+                .get(&edge)
+                .filter(|g| !g.panels.is_empty())
+                .cloned()
+        };
+
+        // Middle column: top, center, bottom
+        let mut middle_children = Vec::new();
+        if let Some(group) = edge_group(DockEdge::Top) {
+            let style = Style {
+                size: Size {
+                    width: Dimension::auto(),
+                    height: Dimension::length(group.size),
+                },
+                flex_shrink: 0.0,
+                ..Default::default()
+            };
+            let slot = AreaSlot::Docked(DockTarget::Edge(DockEdge::Top));
+            middle_children.push(self.layout_area(
+                ctx,
+                slot,
+                &group.panels,
+                group.active,
+                style,
+                true,
+            ));
+            middle_children.push(self.splitter_node(ctx, DockEdge::Top, true));
+        }
+        let center_style = Style {
+            flex_grow: 1.0,
+            flex_basis: Dimension::length(0.0),
+            ..Default::default()
+        };
+        middle_children.push(self.layout_area(
+            ctx,
+            AreaSlot::Docked(DockTarget::Center),
+            &layout.center.panels,
+            layout.center.active,
+            center_style,
+            true,
+        ));
+        if let Some(group) = edge_group(DockEdge::Bottom) {
+            let style = Style {
+                size: Size {
+                    width: Dimension::auto(),
+                    height: Dimension::length(group.size),
+                },
+                flex_shrink: 0.0,
+                ..Default::default()
+            };
+            middle_children.push(self.splitter_node(ctx, DockEdge::Bottom, true));
+            let slot = AreaSlot::Docked(DockTarget::Edge(DockEdge::Bottom));
+            middle_children.push(self.layout_area(
+                ctx,
+                slot,
+                &group.panels,
+                group.active,
+                style,
+                true,
+            ));
+        }
+        let middle = ctx.request_layout_with_children(
+            Style {
+                flex_direction: FlexDirection::Column,
+                flex_grow: 1.0,
+                flex_basis: Dimension::length(0.0),
+                ..Default::default()
+            },
+            &middle_children,
+        );
+        self.middle_node = Some(middle);
+
+        // Root row: left, middle, right, then floating panels
+        let side_style = |size: f32| Style {
+            size: Size {
+                width: Dimension::length(size),
+                height: Dimension::auto(),
+            },
+            flex_shrink: 0.0,
+            ..Default::default()
+        };
+        let mut root_children = Vec::new();
+        if let Some(group) = edge_group(DockEdge::Left) {
+            let slot = AreaSlot::Docked(DockTarget::Edge(DockEdge::Left));
+            root_children.push(self.layout_area(
+                ctx,
+                slot,
+                &group.panels,
+                group.active,
+                side_style(group.size),
+                false,
+            ));
+            root_children.push(self.splitter_node(ctx, DockEdge::Left, false));
+        }
+        root_children.push(middle);
+        if let Some(group) = edge_group(DockEdge::Right) {
+            let slot = AreaSlot::Docked(DockTarget::Edge(DockEdge::Right));
+            root_children.push(self.splitter_node(ctx, DockEdge::Right, false));
+            root_children.push(self.layout_area(
+                ctx,
+                slot,
+                &group.panels,
+                group.active,
+                side_style(group.size),
+                false,
+            ));
+        }
+        for (i, floating) in layout.floating.iter().enumerate() {
+            let style = Style {
+                position: Position::Absolute,
+                inset: taffy::geometry::Rect {
+                    left: LengthPercentageAuto::length(floating.position.x),
+                    top: LengthPercentageAuto::length(floating.position.y),
+                    right: LengthPercentageAuto::auto(),
+                    bottom: LengthPercentageAuto::auto(),
+                },
+                size: Size {
+                    width: Dimension::length(floating.size.x),
+                    height: Dimension::length(floating.size.y),
+                },
+                ..Default::default()
+            };
+            let panels = [floating.panel.clone()];
+            root_children.push(self.layout_area(
+                ctx,
+                AreaSlot::Floating(i),
+                &panels,
+                0,
+                style,
+                false,
+            ));
+        }
+
+        ctx.request_layout_with_children(
+            Style {
+                flex_direction: FlexDirection::Row,
+                size: Size {
+                    width: Dimension::percent(1.0),
+                    height: Dimension::percent(1.0),
+                },
+                ..Default::default()
+            },
+            &root_children,
+        )
+    }
+
+    fn paint(&mut self, bounds: Rect, ctx: &mut PaintContext) {
+        if !ctx.is_visible(&bounds) {
+            return;
+        }
+
+        ctx.paint_quad(PaintQuad::filled(bounds, self.background));
+
+        // Docked areas first, floating panels on top in order
+        let mut frame = DockFrame {
+            bounds: Some(bounds),
+            areas: Vec::new(),
+        };
+        for index in 0..self.areas.len() {
+            let area_bounds = self.node_bounds(
+                ctx,
+                bounds,
+                self.areas[index].node,
+                self.areas[index].in_middle,
+            );
+            if let AreaSlot::Docked(target) = self.areas[index].slot {
+                frame.areas.push((target, area_bounds));
+            }
+            self.paint_area(index, area_bounds, ctx);
+        }
+
+        // Splitters
+        for &(edge, node, in_middle) in &self.splitters {
+            let splitter_bounds = self.node_bounds(ctx, bounds, node, in_middle);
+            let id = self.id(format!("splitter:{:?}", edge));
+            let is_hovered = get_element_state(id).unwrap_or_default().is_hovered;
+            ctx.paint_quad(PaintQuad::filled(
+                splitter_bounds,
+                if is_hovered {
+                    self.splitter_hover_color
+                } else {
+                    self.border_color
+                },
+            ));
+            self.register_splitter_handlers(id, edge);
+            ctx.register_hit_test(id, splitter_bounds, 1);
+        }
+
+        let interaction = read_entity(&self.layout, |l| l.interaction.clone()).unwrap_or_default();
+
+        // While dragging, an overlay tracks the pointer over the whole view
+        match &interaction {
+            DockInteraction::DraggingTab { start, pointer, .. }
+                if (*pointer - *start).length() >= DRAG_THRESHOLD =>
+            {
+                if let Some(preview) = frame.drop_preview(*pointer, self.edge_drop_zone) {
+                    ctx.paint_quad(PaintQuad {
+                        bounds: preview,
+                        fill: self.drop_indicator_color,
+                        corner_radii: Corners::all(0.0),
+                        border_widths: Edges::all(2.0),
+                        border_color: self.splitter_hover_color,
+                    });
+                }
+            }
+            _ => {}
+        }
+        if !matches!(interaction, DockInteraction::Idle) {
+            let layout = self.layout.clone();
+            let handlers = EventHandlers::new().on_mouse_move(move |position, _| {
+                update_entity(&layout, |l| match &mut l.interaction {
+                    DockInteraction::DraggingTab { pointer, .. } => *pointer = position,
+                    DockInteraction::Resizing {
+                        edge,
+                        start,
+                        start_size,
+                    } => {
+                        let delta = position - *start;
+                        let delta = match edge {
+                            DockEdge::Left => delta.x,
+                            DockEdge::Right => -delta.x,
+                            DockEdge::Top => delta.y,
+                            DockEdge::Bottom => -delta.y,
+                        };
+                        let (edge, size) = (*edge, *start_size + delta);
+                        l.resize(edge, size);
+                    }
+                    DockInteraction::Idle => {}
+                });
+            });
+            let id = self.id("drag-overlay");
+            register_element(id, Rc::new(RefCell::new(handlers)));
+            ctx.register_hit_test(id, bounds, DRAG_OVERLAY_Z);
+        }
+
+        update_entity(&self.layout, |l| l.frame = frame);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout() -> DockLayout {
+        let mut layout = DockLayout::new();
+        layout.dock("files", DockTarget::Edge(DockEdge::Left));
+        layout.dock("outline", DockTarget::Edge(DockEdge::Left));
+        layout.dock("editor", DockTarget::Center);
+        layout.dock("terminal", DockTarget::Edge(DockEdge::Bottom));
+        layout
+    }
+
+    #[test]
+    fn test_dock_tabs_panels_together() {
+        let layout = layout();
+        let left = &layout.edges[&DockEdge::Left];
+        assert_eq!(left.panels, vec!["files", "outline"]);
+        assert_eq!(left.active_panel(), Some("outline"));
+        assert_eq!(left.size, DEFAULT_DOCK_SIZE);
+    }
+
+    #[test]
+    fn test_moving_last_panel_removes_edge() {
+        let mut layout = layout();
+        layout.dock("terminal", DockTarget::Center);
+        assert!(!layout.edges.contains_key(&DockEdge::Bottom));
+        assert_eq!(
+            layout.location("terminal"),
+            Some(PanelLocation::Docked(DockTarget::Center))
+        );
+    }
+
+    #[test]
+    fn test_remove_keeps_active_tab_in_range() {
+        let mut layout = layout();
+        layout.activate("outline");
+        assert!(layout.remove("outline"));
+        assert_eq!(layout.edges[&DockEdge::Left].active_panel(), Some("files"));
+        assert!(!layout.remove("outline"));
+    }
+
+    #[test]
+    fn test_float_and_bring_to_front() {
+        let mut layout = layout();
+        layout.float("files", Vec2::new(10.0, 10.0), Vec2::new(200.0, 100.0));
+        layout.float("editor", Vec2::new(50.0, 50.0), Vec2::new(200.0, 100.0));
+        assert_eq!(layout.location("files"), Some(PanelLocation::Floating));
+
+        layout.activate("files");
+        assert_eq!(layout.floating.last().unwrap().panel, "files");
+    }
+
+    #[test]
+    fn test_resize_clamps_to_minimum() {
+        let mut layout = layout();
+        layout.resize(DockEdge::Left, 10.0);
+        assert_eq!(layout.edges[&DockEdge::Left].size, MIN_DOCK_SIZE);
+        layout.resize(DockEdge::Right, 300.0);
+        assert!(!layout.edges.contains_key(&DockEdge::Right));
+    }
+
+    #[test]
+    fn test_layout_round_trips_through_json() {
+        let mut layout = layout();
+        layout.float("outline", Vec2::new(5.0, 6.0), Vec2::new(100.0, 80.0));
+        layout.resize(DockEdge::Bottom, 180.0);
+
+        let json = serde_json::to_string(&layout).unwrap();
+        let restored: DockLayout = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.edges, layout.edges);
+        assert_eq!(restored.center, layout.center);
+        assert_eq!(restored.floating, layout.floating);
+    }
+
+    #[test]
+    fn test_drop_target() {
+        let frame = DockFrame {
+            bounds: Some(Rect::new(0.0, 0.0, 800.0, 600.0)),
+            areas: vec![
+                (
+                    DockTarget::Edge(DockEdge::Left),
+                    Rect::new(0.0, 0.0, 200.0, 600.0),
+                ),
+                (DockTarget::Center, Rect::new(204.0, 0.0, 596.0, 600.0)),
+            ],
+        };
+        assert_eq!(
+            frame.drop_target(Vec2::new(790.0, 300.0), 48.0),
+            DockTarget::Edge(DockEdge::Right)
+        );
+        assert_eq!(
+            frame.drop_target(Vec2::new(100.0, 300.0), 48.0),
+            DockTarget::Edge(DockEdge::Left)
+        );
+        assert_eq!(
+            frame.drop_target(Vec2::new(400.0, 300.0), 48.0),
+            DockTarget::Center
+        );
+    }
+
+    #[test]
+    fn test_short_drag_activates_tab() {
+        let mut layout = layout();
+        layout.interaction = DockInteraction::DraggingTab {
+            start: Vec2::new(10.0, 10.0),
+            pointer: Vec2::new(11.0, 10.0),
+            grab_offset: Vec2::ZERO,
+        };
+        layout.drop_panel("files", Vec2::new(11.0, 10.0), false, 48.0);
+
+        assert_eq!(layout.edges[&DockEdge::Left].active_panel(), Some("files"));
+        assert!(matches!(layout.interaction, DockInteraction::Idle));
+    }
+}
@@ -1,34 +1,82 @@
 //! Two-phase element rendering system
 //!
+mod avatar;
+mod badge;
 mod button;
+mod canvas;
 mod checkbox;
+mod code_view;
+mod color_picker;
+mod combobox;
 mod container;
+mod date_picker;
 mod dropdown;
+mod editable_text;
 mod icon;
+mod launcher;
 mod list;
+mod memo;
 mod modal;
+mod popover;
+mod reorder;
+mod rich_text;
 mod scroll;
+mod selection_area;
+mod snapshot;
+mod tag;
 mod text;
 mod text_input;
 mod toast;
 mod tooltip;
 
-pub use button::{Button, button};
+pub use avatar::{Avatar, AvatarSource, avatar, initials};
+pub use badge::{Badge, badge, badge_label};
+pub use button::{Button, ButtonSize, button};
+pub use canvas::{Canvas, CanvasState, canvas};
 pub use checkbox::{Checkbox, CheckboxInteractable, InteractiveCheckbox, checkbox, interactive_checkbox};
+pub use code_view::{
+    CodeSpan, CodeTheme, CodeView, CodeViewState, RustHighlighter, SyntaxHighlighter, Token, TokenKind,
+    code_view, highlight_lines,
+};
+pub use color_picker::{ColorPicker, ColorPickerState, color_picker};
+pub use combobox::{Combobox, ComboboxFilter, ComboboxState, combobox, filter_options};
 pub use container::{Container, column, container, row};
+pub use date_picker::{CalendarDate, DatePicker, DatePickerState, date_picker};
 pub use dropdown::{Dropdown, DropdownOption, DropdownState, dropdown};
+pub use editable_text::{EditableText, EditableTextState, editable_text};
 pub use icon::{Icon, IconButton, IconSource, icon, icon_button, icons};
-pub use list::{List, ListAction, ListItemData, ListState, SelectionMode, list};
+pub use launcher::{Launcher, LauncherState, launcher};
+pub use list::{
+    IndexPath, List, ListAction, ListItemData, ListSection, ListState, SelectionMode, grouped_list,
+    list,
+};
+pub(crate) use memo::end_memo_frame;
+pub use memo::{Memoized, memo};
 pub use modal::{Modal, modal};
-pub use scroll::{ScrollContainer, ScrollState, scroll};
-pub use text::{Text, text};
+pub use popover::{PopoverPlacement, place_popover};
+pub use reorder::ReorderState;
+pub use rich_text::{RichText, TextSpan, link, rich_text, span};
+pub(crate) use scroll::clear_scroll_into_view_requests;
+pub use scroll::{
+    ScrollAlignment, ScrollAxes, ScrollChaining, ScrollContainer, ScrollHandle, ScrollState,
+    ScrollTarget, request_scroll_into_view, scroll,
+};
+pub(crate) use selection_area::clear_painting_areas;
+pub use selection_area::{
+    Selectable, SelectionArea, SelectionAreaState, selectable, selection_area,
+};
+pub use snapshot::{Snapshot, Snapshottable, snapshot};
+pub use tag::{Tag, tag};
+pub use text::{Text, Truncation, text, truncate_text};
 pub use toast::{Toast, ToastPosition, ToastSeverity, toast};
 pub use tooltip::{Tooltip, TooltipPosition, tooltip};
 pub use text_input::{
-    InteractiveTextInput, TextInput, TextInputInteractable, TextInputState, text_input,
+    InputMask, InteractiveTextInput, SelectionGranularity, TextInput, TextInputInteractable,
+    TextInputState, TextValidator, text_input,
 };
 
 use crate::{
+    debug::{begin_element_paint, end_element_paint, paint_profiling},
     geometry::Rect,
     layout_engine::{ElementData, TaffyLayoutEngine},
     layout_id::LayoutId,
@@ -37,6 +85,7 @@ use crate::{
     text_system::TextSystem,
 };
 use glam::Vec2;
+use std::cell::Cell;
 use taffy::prelude::*;
 
 /// Elements participate in a two-phase rendering process
@@ -46,6 +95,33 @@ pub trait Element {
 
     /// Phase 2: Paint using the computed bounds
     fn paint(&mut self, bounds: Rect, ctx: &mut PaintContext);
+
+    /// Name for this element in debugging tools, such as the paint profiler
+    fn debug_name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+}
+
+/// Boxed elements, such as trees built at runtime, can be children too
+///
+/// Their paints are what the paint profiler times.
+impl<E: Element + ?Sized> Element for Box<E> {
+    fn layout(&mut self, ctx: &mut LayoutContext) -> NodeId {
+        (**self).layout(ctx)
+    }
+
+    fn paint(&mut self, bounds: Rect, ctx: &mut PaintContext) {
+        if !paint_profiling() {
+            return (**self).paint(bounds, ctx);
+        }
+        begin_element_paint(self.debug_name(), ctx.draw_list.command_count());
+        (**self).paint(bounds, ctx);
+        end_element_paint(ctx.draw_list.command_count());
+    }
+
+    fn debug_name(&self) -> &'static str {
+        (**self).debug_name()
+    }
 }
 
 /// Context for the layout phase
@@ -77,6 +153,7 @@ impl<'a> LayoutContext<'a> {
         let data = ElementData {
             text: Some((text.to_string(), text_style.clone())),
             background: None,
+            no_wrap: false,
         };
         self.engine.request_layout_with_data(style, data, &[])
     }
@@ -91,15 +168,21 @@ impl<'a> LayoutContext<'a> {
         self.engine.request_layout_with_data(style, data, children)
     }
 
+    /// Lay out `element` on its own and return its size, without painting it
+    ///
+    /// See [`measure_element`].
+    pub fn measure_element(&mut self, element: &mut dyn Element, available_space: Vec2) -> Vec2 {
+        measure_with(
+            element,
+            available_space,
+            self.text_system,
+            self.scale_factor,
+        )
+    }
+
     /// Measure text (for use during layout)
     pub fn measure_text(&mut self, text: &str, style: &TextStyle, max_width: Option<f32>) -> Vec2 {
-        let text_config = crate::text_system::TextConfig {
-            font_stack: parley::FontStack::from(style.font_family),
-            size: style.size,
-            weight: style.weight,
-            color: style.color.clone(),
-            line_height: style.line_height,
-        };
+        let text_config = crate::text_system::TextConfig::from(style);
 
         self.text_system
             .measure_text(text, &text_config, max_width, self.scale_factor)
@@ -138,6 +221,7 @@ impl<'a> LayoutContext<'a> {
         let data = ElementData {
             text: Some((text.to_string(), text_style.clone())),
             background: None,
+            no_wrap: false,
         };
         self.engine
             .request_layout_cached(layout_id, style, data, &[], &[])
@@ -156,3 +240,165 @@ impl<'a> LayoutContext<'a> {
             .request_layout_cached(layout_id, style, data, child_ids, child_nodes)
     }
 }
+
+thread_local! {
+    /// Text system and scale factor of the layer whose elements are being
+    /// built, for [`measure_element`]
+    ///
+    /// A raw pointer for the same reason as the entity store's: the layer
+    /// owns the borrow and only sets it around its render function.
+    static MEASURE_CONTEXT: Cell<Option<(*mut TextSystem, f32)>> = const { Cell::new(None) };
+}
+
+/// Let [`measure_element`] use `text_system` while a layer builds its elements
+///
+/// Call [`clear_measure_context`] before the borrow ends.
+pub(crate) fn set_measure_context(text_system: &mut TextSystem, scale_factor: f32) {
+    MEASURE_CONTEXT.with(|cell| cell.set(Some((text_system as *mut TextSystem, scale_factor))));
+}
+
+/// Stop [`measure_element`] from using the layer's text system
+pub(crate) fn clear_measure_context() {
+    MEASURE_CONTEXT.with(|cell| cell.set(None));
+}
+
+/// Lay out `element` offscreen and return the size it takes up
+///
+/// Runs the element's full layout, measuring its text, in a scratch layout
+/// tree, so an app can size things to their content before building the
+/// real frame: fitting a popover around its contents, placing items in a
+/// masonry layout, or deciding how to truncate. `available_space` bounds
+/// the size on each axis; an infinite component leaves that axis free.
+///
+/// Nothing is painted, and the element can still be added to the frame
+/// afterwards; it's laid out again there.
+///
+/// # Panics
+/// Panics if called outside a layer's render function. During layout, use
+/// [`LayoutContext::measure_element`] instead.
+///
+/// # Example
+/// ```ignore
+/// let mut label = text("Hello", TextStyle::default());
+/// let size = measure_element(&mut label, Vec2::new(200.0, f32::INFINITY));
+/// ```
+pub fn measure_element(element: &mut dyn Element, available_space: Vec2) -> Vec2 {
+    let (text_system, scale_factor) = MEASURE_CONTEXT
+        .with(|cell| cell.get())
+        .expect("measure_element called outside a layer's render function");
+    // Safety: the pointer is only set while the layer's borrow is live
+    let text_system = unsafe { &mut *text_system };
+    measure_with(element, available_space, text_system, scale_factor)
+}
+
+fn measure_with(
+    element: &mut dyn Element,
+    available_space: Vec2,
+    text_system: &mut TextSystem,
+    scale_factor: f32,
+) -> Vec2 {
+    let axis = |space: f32| {
+        if space.is_finite() {
+            AvailableSpace::Definite(space)
+        } else {
+            AvailableSpace::MaxContent
+        }
+    };
+
+    let mut engine = TaffyLayoutEngine::new();
+    let node = element.layout(&mut LayoutContext {
+        engine: &mut engine,
+        text_system,
+        scale_factor,
+    });
+    let available_space = Size {
+        width: axis(available_space.x),
+        height: axis(available_space.y),
+    };
+    match engine.compute_layout(node, available_space, text_system, scale_factor) {
+        Ok(()) => engine.layout_bounds(node).size,
+        Err(_) => Vec2::ZERO,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entity::EntityStore,
+        image::RgbaImage,
+        layer::{LayerManager, LayerOptions},
+        renderer::FrameInfo,
+        software_renderer::SoftwareRenderer,
+        style::TextStyle,
+    };
+    use std::rc::Rc;
+
+    #[test]
+    fn test_measure_container_and_children() {
+        let mut text_system = TextSystem::headless();
+        let mut element = column()
+            .padding(5.0)
+            .gap(10.0)
+            .child(container().size(40.0, 20.0))
+            .child(container().size(30.0, 20.0));
+        let size = measure_with(
+            &mut element,
+            Vec2::new(f32::INFINITY, f32::INFINITY),
+            &mut text_system,
+            1.0,
+        );
+        assert_eq!(size, Vec2::new(50.0, 60.0));
+    }
+
+    #[test]
+    fn test_measure_text_wraps_to_available_width() {
+        let mut text_system = TextSystem::headless();
+        let content = "measure this text offscreen";
+        let unbounded = measure_with(
+            &mut text(content, TextStyle::default()),
+            Vec2::new(f32::INFINITY, f32::INFINITY),
+            &mut text_system,
+            1.0,
+        );
+        let wrapped = measure_with(
+            &mut text(content, TextStyle::default()),
+            Vec2::new(unbounded.x / 2.0, f32::INFINITY),
+            &mut text_system,
+            1.0,
+        );
+        assert!(unbounded.x > 0.0 && unbounded.y > 0.0);
+        assert!(wrapped.x <= unbounded.x / 2.0);
+        assert!(wrapped.y > unbounded.y);
+    }
+
+    #[test]
+    fn test_measure_element_inside_a_layer() {
+        let measured = Rc::new(Cell::new(None));
+        let result = measured.clone();
+        let mut layers = LayerManager::new();
+        layers.add_ui_layer(0, LayerOptions::default(), move || {
+            let mut label = container().size(12.0, 8.0);
+            result.set(Some(measure_element(&mut label, Vec2::new(100.0, 100.0))));
+            Box::new(label)
+        });
+        layers.render(
+            &mut SoftwareRenderer::new(),
+            &mut RgbaImage::new(1, 1),
+            FrameInfo {
+                size: Vec2::new(100.0, 100.0),
+                scale_factor: 1.0,
+                elapsed_time: 0.0,
+            },
+            &mut TextSystem::headless(),
+            &mut EntityStore::new(),
+        );
+        assert_eq!(measured.get(), Some(Vec2::new(12.0, 8.0)));
+    }
+
+    #[test]
+    #[should_panic(expected = "outside a layer's render function")]
+    fn test_measure_element_outside_a_layer_panics() {
+        measure_element(&mut container(), Vec2::ZERO);
+    }
+}
@@ -0,0 +1,190 @@
+//! Avatar element - a circular image, or initials when there's no image
+
+use crate::{
+    color::{Color, colors},
+    element::{Element, LayoutContext, PaintContext},
+    geometry::{Corners, Rect},
+    image::RgbaImage,
+    render::{PaintImage, PaintText},
+    style::{ElementStyle, TextStyle},
+    text_system::TextConfig,
+};
+use std::sync::Arc;
+use taffy::prelude::*;
+
+/// Create an avatar from an image, or from a name shown as its initials
+pub fn avatar(source: impl Into<AvatarSource>) -> Avatar {
+    Avatar::new(source)
+}
+
+/// What an avatar shows
+#[derive(Clone, Debug)]
+pub enum AvatarSource {
+    /// An image, clipped to the avatar's shape
+    Image(Arc<RgbaImage>),
+    /// Up to two letters on a colored background
+    Initials(String),
+}
+
+impl From<Arc<RgbaImage>> for AvatarSource {
+    fn from(image: Arc<RgbaImage>) -> Self {
+        AvatarSource::Image(image)
+    }
+}
+
+impl From<RgbaImage> for AvatarSource {
+    fn from(image: RgbaImage) -> Self {
+        AvatarSource::Image(Arc::new(image))
+    }
+}
+
+impl From<&str> for AvatarSource {
+    fn from(name: &str) -> Self {
+        AvatarSource::Initials(initials(name))
+    }
+}
+
+impl From<String> for AvatarSource {
+    fn from(name: String) -> Self {
+        AvatarSource::Initials(initials(&name))
+    }
+}
+
+/// The initials for a name: the first letters of its first and last words
+///
+/// "Ada Lovelace" and "Ada King Lovelace" both give "AL", and "ada" gives "A".
+pub fn initials(name: &str) -> String {
+    let mut words = name.split_whitespace();
+    let first = words.next().and_then(|word| word.chars().next());
+    let last = words.next_back().and_then(|word| word.chars().next());
+    first
+        .into_iter()
+        .chain(last)
+        .flat_map(char::to_uppercase)
+        .collect()
+}
+
+/// A fixed-size avatar, circular unless given a corner radius
+pub struct Avatar {
+    source: AvatarSource,
+    /// Width and height in logical pixels
+    size: f32,
+    /// Corner radius; `None` makes the avatar a circle
+    corner_radius: Option<f32>,
+    /// Background behind initials
+    background: Color,
+    text_color: Color,
+}
+
+impl Avatar {
+    pub fn new(source: impl Into<AvatarSource>) -> Self {
+        Self {
+            source: source.into(),
+            size: 32.0,
+            corner_radius: None,
+            background: colors::GRAY_300,
+            text_color: colors::GRAY_800,
+        }
+    }
+
+    /// Set the avatar size (both width and height)
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Use a rounded square with this corner radius instead of a circle
+    pub fn corner_radius(mut self, radius: f32) -> Self {
+        self.corner_radius = Some(radius);
+        self
+    }
+
+    /// Set the background color shown behind initials
+    pub fn background(mut self, color: Color) -> Self {
+        self.background = color;
+        self
+    }
+
+    /// Set the color of the initials
+    pub fn text_color(mut self, color: Color) -> Self {
+        self.text_color = color;
+        self
+    }
+
+    fn radius(&self) -> f32 {
+        self.corner_radius.unwrap_or(self.size / 2.0)
+    }
+}
+
+impl Element for Avatar {
+    fn layout(&mut self, ctx: &mut LayoutContext) -> NodeId {
+        ctx.request_layout(Style {
+            size: Size {
+                width: Dimension::length(self.size),
+                height: Dimension::length(self.size),
+            },
+            flex_shrink: 0.0,
+            ..Default::default()
+        })
+    }
+
+    fn paint(&mut self, bounds: Rect, ctx: &mut PaintContext) {
+        if !ctx.is_visible(&bounds) {
+            return;
+        }
+
+        let radius = self.radius();
+        match &self.source {
+            AvatarSource::Image(image) => {
+                ctx.paint_image(PaintImage {
+                    bounds,
+                    image: image.clone(),
+                    corner_radii: Corners::all(radius),
+                });
+            }
+            AvatarSource::Initials(initials) => {
+                ctx.draw_list.add_frame(
+                    bounds,
+                    ElementStyle::new()
+                        .with_background(self.background)
+                        .with_corner_radius(radius),
+                );
+                if initials.is_empty() {
+                    return;
+                }
+
+                let style = TextStyle {
+                    size: (self.size * 0.4).round(),
+                    color: self.text_color,
+                    weight: parley::FontWeight::SEMI_BOLD,
+                    ..Default::default()
+                };
+                let text_size = ctx.text_system.measure_text(
+                    initials,
+                    &TextConfig::from(&style),
+                    None,
+                    ctx.scale_factor,
+                );
+                ctx.paint_text(PaintText {
+                    position: bounds.pos + (bounds.size - text_size) / 2.0,
+                    text: initials.clone(),
+                    style,
+                    measured_size: Some(text_size),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_initials() {
+        assert_eq!(initials("Ada Lovelace"), "AL");
+        assert_eq!(initials("  ada king   lovelace "), "AL");
+        assert_eq!(initials("grace"), "G");
+        assert_eq!(initials(""), "");
+    }
+}
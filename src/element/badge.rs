@@ -0,0 +1,258 @@
+//! Badge element - a count or dot pinned to the corner of another element
+
+use crate::{
+    color::{Color, colors},
+    element::{Element, LayoutContext, PaintContext},
+    geometry::Rect,
+    render::PaintText,
+    style::{ElementStyle, TextStyle},
+};
+use glam::Vec2;
+use taffy::prelude::*;
+
+/// Height of a count badge
+const BADGE_HEIGHT: f32 = 16.0;
+/// Space between the count and the badge's ends
+const BADGE_PADDING: f32 = 5.0;
+/// Diameter of a dot badge
+const DOT_SIZE: f32 = 8.0;
+
+/// Create a badge showing `count`
+///
+/// On its own the badge is an inline pill; given an element with
+/// [`Badge::on`], it's drawn over that element's top right corner.
+pub fn badge(count: u32) -> Badge {
+    Badge::new(count)
+}
+
+/// The text a badge shows for `count`, capped at `max` (e.g. "99+")
+pub fn badge_label(count: u32, max: Option<u32>) -> String {
+    match max {
+        Some(max) if count > max => format!("{}+", max),
+        _ => count.to_string(),
+    }
+}
+
+/// A count or dot badge, optionally anchored to a child element
+pub struct Badge {
+    count: u32,
+    /// Largest count shown before it's written as "max+"
+    max: Option<u32>,
+    /// Show a plain dot instead of the count
+    dot: bool,
+    /// Show the badge when the count is zero
+    show_zero: bool,
+    /// Offset of the badge's center from the child's top right corner
+    offset: Vec2,
+    background: Color,
+    text_color: Color,
+    child: Option<Box<dyn Element>>,
+    child_node: Option<NodeId>,
+    badge_node: Option<NodeId>,
+    /// Label and size measured during layout
+    measured: Option<(String, Vec2)>,
+}
+
+impl Badge {
+    pub fn new(count: u32) -> Self {
+        Self {
+            count,
+            max: Some(99),
+            dot: false,
+            show_zero: false,
+            offset: Vec2::ZERO,
+            background: colors::RED_500,
+            text_color: colors::WHITE,
+            child: None,
+            child_node: None,
+            badge_node: None,
+            measured: None,
+        }
+    }
+
+    /// Pin the badge to the top right corner of `child`
+    pub fn on(mut self, child: impl Element + 'static) -> Self {
+        self.child = Some(Box::new(child));
+        self
+    }
+
+    /// Set the largest count shown in full; larger counts show as "max+"
+    pub fn max(mut self, max: Option<u32>) -> Self {
+        self.max = max;
+        self
+    }
+
+    /// Show a small dot instead of the count
+    pub fn dot(mut self) -> Self {
+        self.dot = true;
+        self
+    }
+
+    /// Show the badge even when the count is zero
+    pub fn show_zero(mut self, show: bool) -> Self {
+        self.show_zero = show;
+        self
+    }
+
+    /// Move the badge from the child's corner, e.g. to tuck it inside a
+    /// rounded child
+    pub fn offset(mut self, offset: Vec2) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Set the badge color
+    pub fn background(mut self, color: Color) -> Self {
+        self.background = color;
+        self
+    }
+
+    /// Set the count color
+    pub fn text_color(mut self, color: Color) -> Self {
+        self.text_color = color;
+        self
+    }
+
+    fn is_shown(&self) -> bool {
+        self.count > 0 || self.show_zero
+    }
+
+    fn text_style(&self) -> TextStyle {
+        TextStyle {
+            size: 11.0,
+            color: self.text_color,
+            weight: parley::FontWeight::SEMI_BOLD,
+            ..Default::default()
+        }
+    }
+
+    /// Measure the badge, or `None` if it's hidden
+    fn measure(&self, ctx: &mut LayoutContext) -> Option<(String, Vec2)> {
+        if !self.is_shown() {
+            return None;
+        }
+        if self.dot {
+            return Some((String::new(), Vec2::splat(DOT_SIZE)));
+        }
+
+        let label = badge_label(self.count, self.max);
+        let text_size = ctx.measure_text(&label, &self.text_style(), None);
+        let width = (text_size.x + BADGE_PADDING * 2.0).max(BADGE_HEIGHT);
+        Some((label, Vec2::new(width.ceil(), BADGE_HEIGHT)))
+    }
+
+    fn paint_badge(&self, bounds: Rect, ctx: &mut PaintContext) {
+        let Some((label, _)) = &self.measured else {
+            return;
+        };
+
+        ctx.draw_list.add_frame(
+            bounds,
+            ElementStyle::new()
+                .with_background(self.background)
+                .with_corner_radius(bounds.size.y / 2.0),
+        );
+        if label.is_empty() {
+            return;
+        }
+
+        let style = self.text_style();
+        let text_size = ctx.text_system.measure_text(
+            label,
+            &crate::text_system::TextConfig::from(&style),
+            None,
+            ctx.scale_factor,
+        );
+        ctx.paint_text(PaintText {
+            position: bounds.pos + (bounds.size - text_size) / 2.0,
+            text: label.clone(),
+            style,
+            measured_size: Some(text_size),
+        });
+    }
+}
+
+impl Element for Badge {
+    fn layout(&mut self, ctx: &mut LayoutContext) -> NodeId {
+        self.measured = self.measure(ctx);
+        let size = self.measured.as_ref().map_or(Vec2::ZERO, |(_, size)| *size);
+
+        let Some(child) = self.child.as_mut() else {
+            self.child_node = None;
+            self.badge_node = None;
+            return ctx.request_layout(Style {
+                size: Size {
+                    width: Dimension::length(size.x),
+                    height: Dimension::length(size.y),
+                },
+                flex_shrink: 0.0,
+                ..Default::default()
+            });
+        };
+
+        let child_node = child.layout(ctx);
+        self.child_node = Some(child_node);
+        let mut children = vec![child_node];
+
+        // Center the badge on the child's top right corner, out of flow so
+        // it doesn't change the child's size
+        self.badge_node = self.measured.is_some().then(|| {
+            ctx.request_layout(Style {
+                position: Position::Absolute,
+                inset: taffy::Rect {
+                    left: LengthPercentageAuto::auto(),
+                    top: LengthPercentageAuto::length(self.offset.y - size.y / 2.0),
+                    right: LengthPercentageAuto::length(-self.offset.x - size.x / 2.0),
+                    bottom: LengthPercentageAuto::auto(),
+                },
+                size: Size {
+                    width: Dimension::length(size.x),
+                    height: Dimension::length(size.y),
+                },
+                ..Default::default()
+            })
+        });
+        children.extend(self.badge_node);
+
+        ctx.request_layout_with_children(Style::default(), &children)
+    }
+
+    fn paint(&mut self, bounds: Rect, ctx: &mut PaintContext) {
+        let Some(child) = self.child.as_mut() else {
+            if ctx.is_visible(&bounds) {
+                self.paint_badge(bounds, ctx);
+            }
+            return;
+        };
+
+        let child_bounds = |node: NodeId, ctx: &PaintContext| {
+            let layout = ctx.layout_engine.layout_bounds(node);
+            Rect::from_pos_size(bounds.pos + layout.pos, layout.size)
+        };
+
+        if let Some(node) = self.child_node {
+            let child_bounds = child_bounds(node, ctx);
+            child.paint(child_bounds, ctx);
+        }
+        // Painted after the child so it sits on top
+        if let Some(node) = self.badge_node {
+            let badge_bounds = child_bounds(node, ctx);
+            if ctx.is_visible(&badge_bounds) {
+                self.paint_badge(badge_bounds, ctx);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_badge_label() {
+        assert_eq!(badge_label(7, Some(99)), "7");
+        assert_eq!(badge_label(99, Some(99)), "99");
+        assert_eq!(badge_label(100, Some(99)), "99+");
+        assert_eq!(badge_label(1000, None), "1000");
+    }
+}
@@ -1,21 +1,25 @@
 use crate::{
+    clock::frame_time,
     color::{colors, Color},
-    element::{Element, LayoutContext, PaintContext},
+    element::{Element, Icon, IconSource, LayoutContext, PaintContext},
     geometry::{Corners, Edges, Rect},
     interaction::{
         registry::{get_element_state, register_element},
-        ElementId, EventHandlers,
+        ElementId, EventHandlers, InteractionState,
     },
-    layer::{Key, MouseButton},
+    layer::{ClickType, Key, Modifiers, MouseButton},
     layout_id::LayoutId,
     render::{PaintQuad, PaintText},
-    style::TextStyle,
+    style::{Outline, StyleState, StyleStates, TextStyle},
 };
 use glam::Vec2;
 use std::cell::RefCell;
 use std::rc::Rc;
 use taffy::prelude::*;
 
+/// Click handler run by Enter and Space, shared with the key handler once set.
+type KeyActivation = Rc<RefCell<Option<Box<dyn FnMut(Modifiers)>>>>;
+
 /// Create a new button element with the given label.
 ///
 /// This is a convenience function equivalent to [`Button::new()`].
@@ -73,6 +77,20 @@ pub fn button(label: impl Into<String>) -> Button {
 /// let btn = button("Unavailable").disabled(true);
 /// ```
 ///
+/// ## Icons, Sizes and Loading
+///
+/// ```
+/// use sol_ui::element::{ButtonSize, button, icons};
+///
+/// let save = button("Save")
+///     .icon(icons::CHECK)
+///     .button_size(ButtonSize::Large)
+///     .loading(true);
+///
+/// // With an empty label only the icon is shown; give it an ID
+/// let settings = button("").with_id("settings_btn").icon(icons::SETTINGS);
+/// ```
+///
 /// # Visual States
 ///
 /// The button displays different backgrounds based on state:
@@ -81,31 +99,36 @@ pub fn button(label: impl Into<String>) -> Button {
 /// - **Pressed**: `press_background` color
 /// - **Focused**: Shows focus ring around button
 /// - **Disabled**: `disabled_background` color (gray)
+/// - **Loading**: a spinner replaces the label, and clicks are ignored
+///
+/// For full control over each state, including border and text colors, pass
+/// a [`StyleStates`] to [`Button::states`].
 ///
 /// # Keyboard Support
 ///
-/// When focused, buttons respond to Enter and Space keys.
+/// Buttons take focus when clicked or tabbed to, and when focused respond to
+/// Enter and Space keys.
 pub struct Button {
     /// Button text label
     label: String,
 
+    /// Icon shown before the label
+    icon: Option<Icon>,
+
+    /// Icon size in logical pixels
+    icon_size: f32,
+
     /// Unique ID for interaction tracking
     id: ElementId,
 
     /// Event handlers
     handlers: Rc<RefCell<EventHandlers>>,
 
-    /// Background color in normal state
-    background: Color,
+    /// Click handler run by Enter and Space, shared with the key handler
+    key_activation: KeyActivation,
 
-    /// Background color when hovered
-    hover_background: Color,
-
-    /// Background color when pressed
-    press_background: Color,
-
-    /// Background color when disabled
-    disabled_background: Color,
+    /// Colors for each interaction state
+    states: StyleStates,
 
     /// Border color
     border_color: Option<Color>,
@@ -119,9 +142,6 @@ pub struct Button {
     /// Text style
     text_style: TextStyle,
 
-    /// Text color when disabled
-    disabled_text_color: Color,
-
     /// Padding around the text
     padding_h: f32,
     padding_v: f32,
@@ -129,6 +149,9 @@ pub struct Button {
     /// Whether the button is disabled
     disabled: bool,
 
+    /// Whether the button shows a spinner instead of its label
+    loading: bool,
+
     /// Explicit width (None = auto-size to content)
     width: Option<taffy::Dimension>,
 
@@ -141,6 +164,10 @@ pub struct Button {
     /// Cached layout node ID
     node_id: Option<NodeId>,
 
+    /// Layout nodes of the icon and label, when laid out side by side
+    icon_node: Option<NodeId>,
+    label_node: Option<NodeId>,
+
     /// Stable layout ID for caching across frames
     layout_id: Option<LayoutId>,
 }
@@ -152,12 +179,17 @@ impl Button {
         let id = ElementId::stable(format!("button:{}", label));
         Self {
             label,
+            icon: None,
+            icon_size: ButtonSize::Medium.icon_size(),
             id,
             handlers: Rc::new(RefCell::new(EventHandlers::new())),
-            background: colors::BLUE_500,
-            hover_background: colors::BLUE_400,
-            press_background: colors::BLUE_600,
-            disabled_background: colors::GRAY_400,
+            key_activation: Rc::new(RefCell::new(None)),
+            states: StyleStates::new()
+                .bg(colors::BLUE_500)
+                .hover_bg(colors::BLUE_400)
+                .pressed_bg(colors::BLUE_600)
+                .disabled_bg(colors::GRAY_400)
+                .disabled_text_color(colors::GRAY_600),
             border_color: None,
             border_width: 0.0,
             corner_radius: 4.0,
@@ -166,14 +198,16 @@ impl Button {
                 color: colors::WHITE,
                 ..Default::default()
             },
-            disabled_text_color: colors::GRAY_600,
             padding_h: 16.0,
             padding_v: 8.0,
             disabled: false,
+            loading: false,
             width: None,
             height: None,
             flex_grow: 0.0,
             node_id: None,
+            icon_node: None,
+            label_node: None,
             layout_id: None,
         }
     }
@@ -192,33 +226,40 @@ impl Button {
 
     /// Set the background color
     pub fn background(mut self, color: Color) -> Self {
-        self.background = color;
+        self.states = self.states.bg(color);
         self
     }
 
     /// Set the hover background color
     pub fn hover_background(mut self, color: Color) -> Self {
-        self.hover_background = color;
+        self.states = self.states.hover_bg(color);
         self
     }
 
     /// Set the press background color
     pub fn press_background(mut self, color: Color) -> Self {
-        self.press_background = color;
+        self.states = self.states.pressed_bg(color);
         self
     }
 
     /// Set all background colors at once (normal, hover, pressed)
     pub fn backgrounds(mut self, normal: Color, hover: Color, pressed: Color) -> Self {
-        self.background = normal;
-        self.hover_background = hover;
-        self.press_background = pressed;
+        self.states = self.states.bg(normal).hover_bg(hover).pressed_bg(pressed);
         self
     }
 
     /// Set the disabled background color
     pub fn disabled_background(mut self, color: Color) -> Self {
-        self.disabled_background = color;
+        self.states = self.states.disabled_bg(color);
+        self
+    }
+
+    /// Replace the colors for every interaction state
+    ///
+    /// Colors left unset fall back to the text style's color, the border
+    /// color, or a transparent background.
+    pub fn states(mut self, states: StyleStates) -> Self {
+        self.states = states;
         self
     }
 
@@ -267,7 +308,7 @@ impl Button {
 
     /// Set disabled text color
     pub fn disabled_text_color(mut self, color: Color) -> Self {
-        self.disabled_text_color = color;
+        self.states = self.states.disabled_text_color(color);
         self
     }
 
@@ -303,6 +344,38 @@ impl Button {
         self
     }
 
+    /// Show a spinner in place of the label and ignore clicks
+    ///
+    /// The button keeps its size, so toggling this doesn't shift the layout.
+    pub fn loading(mut self, loading: bool) -> Self {
+        self.loading = loading;
+        self
+    }
+
+    /// Show an icon before the label
+    ///
+    /// The icon takes the label's color. With an empty label the button shows
+    /// only the icon.
+    pub fn icon(mut self, icon: impl Into<IconSource>) -> Self {
+        self.icon = Some(Icon::new(icon).size(self.icon_size));
+        self
+    }
+
+    /// Set the icon size
+    pub fn icon_size(mut self, size: f32) -> Self {
+        self.icon_size = size;
+        self.icon = self.icon.map(|icon| icon.size(size));
+        self
+    }
+
+    /// Apply a size preset to the text, padding and icon
+    pub fn button_size(self, size: ButtonSize) -> Self {
+        let (horizontal, vertical) = size.padding();
+        self.text_size(size.text_size())
+            .padding_xy(horizontal, vertical)
+            .icon_size(size.icon_size())
+    }
+
     /// Set explicit width
     pub fn width(mut self, width: f32) -> Self {
         self.width = Some(taffy::Dimension::length(width));
@@ -342,7 +415,9 @@ impl Button {
 
     /// Set the click handler (also triggers on Enter/Space when focused)
     /// Handler receives: (button, click_type, position, local_position, modifiers)
-    pub fn on_click<F>(self, handler: F) -> Self
+    ///
+    /// Keyboard activation reports a single left click at the origin.
+    pub fn on_click<F>(mut self, handler: F) -> Self
     where
         F: FnMut(
                 MouseButton,
@@ -352,13 +427,30 @@ impl Button {
                 crate::layer::Modifiers,
             ) + 'static,
     {
-        self.handlers.borrow_mut().on_click = Some(Box::new(handler));
+        let handler = Rc::new(RefCell::new(handler));
+        let click_handler = handler.clone();
+        let key_handler = handler;
+
+        self.handlers.borrow_mut().on_click = Some(Box::new(
+            move |button, click, position, local, modifiers| {
+                (click_handler.borrow_mut())(button, click, position, local, modifiers);
+            },
+        ));
+        self.set_key_activation(Box::new(move |modifiers| {
+            (key_handler.borrow_mut())(
+                MouseButton::Left,
+                ClickType::Single,
+                Vec2::ZERO,
+                Vec2::ZERO,
+                modifiers,
+            );
+        }));
         self
     }
 
     /// Set a simple click handler that doesn't need position info
     /// This also triggers on Enter/Space when the button is focused.
-    pub fn on_click_simple<F>(self, handler: F) -> Self
+    pub fn on_click_simple<F>(mut self, handler: F) -> Self
     where
         F: FnMut() + 'static,
     {
@@ -367,20 +459,56 @@ impl Button {
         let click_handler = handler.clone();
         let key_handler = handler;
 
-        let mut handlers = self.handlers.borrow_mut();
-        handlers.on_click = Some(Box::new(move |_, _, _, _, _| {
+        self.handlers.borrow_mut().on_click = Some(Box::new(move |_, _, _, _, _| {
             (click_handler.borrow_mut())();
         }));
+        self.set_key_activation(Box::new(move |_| (key_handler.borrow_mut())()));
+        self
+    }
 
-        // Also trigger on Enter or Space key
-        handlers.on_key_down = Some(Box::new(move |key, _, _, is_repeat| {
-            if !is_repeat && (key == Key::Return || key == Key::Space) {
-                (key_handler.borrow_mut())();
+    /// Set the key down handler
+    /// Handler receives: (key, modifiers, character, is_repeat)
+    ///
+    /// Runs alongside Enter/Space activation rather than replacing it.
+    pub fn on_key_down<F>(self, handler: F) -> Self
+    where
+        F: FnMut(Key, Modifiers, Option<char>, bool) + 'static,
+    {
+        self.chain_key_down(handler);
+        self
+    }
+
+    /// Run `activate` on Enter or Space, replacing an earlier click handler's
+    /// activation but keeping other key handlers
+    fn set_key_activation(&mut self, activate: Box<dyn FnMut(Modifiers)>) {
+        let installed = self.key_activation.borrow_mut().replace(activate).is_some();
+        if installed {
+            return;
+        }
+        let activation = self.key_activation.clone();
+        self.chain_key_down(move |key, modifiers, _, is_repeat| {
+            if is_repeat || !(key == Key::Return || key == Key::Space) {
+                return;
             }
-        }));
+            if let Some(activate) = &mut *activation.borrow_mut() {
+                activate(modifiers);
+            }
+        });
+    }
 
-        drop(handlers);
-        self
+    /// Add a key down handler, run after any already set
+    fn chain_key_down(
+        &self,
+        mut handler: impl FnMut(Key, Modifiers, Option<char>, bool) + 'static,
+    ) {
+        let mut handlers = self.handlers.borrow_mut();
+        let mut previous = handlers.on_key_down.take();
+        handlers.on_key_down = Some(Box::new(move |key, modifiers, character, is_repeat| {
+            if let Some(previous) = &mut previous {
+                previous(key, modifiers, character, is_repeat);
+            }
+            handler(key, modifiers, character, is_repeat);
+        }));
     }
 
     /// Set the mouse enter handler
@@ -407,12 +535,50 @@ impl Button {
     }
 }
 
-/// Focus ring color for buttons
-const FOCUS_RING_COLOR: Color = colors::BLUE_400;
-/// Focus ring width
-const FOCUS_RING_WIDTH: f32 = 2.0;
-/// Focus ring offset from element bounds
-const FOCUS_RING_OFFSET: f32 = 2.0;
+/// Size presets for [`Button`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ButtonSize {
+    Small,
+    #[default]
+    Medium,
+    Large,
+}
+
+impl ButtonSize {
+    /// Label text size
+    pub fn text_size(self) -> f32 {
+        match self {
+            ButtonSize::Small => 12.0,
+            ButtonSize::Medium => 14.0,
+            ButtonSize::Large => 16.0,
+        }
+    }
+
+    /// Horizontal and vertical padding around the content
+    pub fn padding(self) -> (f32, f32) {
+        match self {
+            ButtonSize::Small => (10.0, 4.0),
+            ButtonSize::Medium => (16.0, 8.0),
+            ButtonSize::Large => (20.0, 10.0),
+        }
+    }
+
+    /// Icon size
+    pub fn icon_size(self) -> f32 {
+        match self {
+            ButtonSize::Small => 14.0,
+            ButtonSize::Medium => 16.0,
+            ButtonSize::Large => 20.0,
+        }
+    }
+}
+
+/// Space between the icon and the label
+const ICON_GAP: f32 = 6.0;
+/// Number of dots in the loading spinner
+const SPINNER_DOTS: usize = 8;
+/// Spinner speed in dots per second
+const SPINNER_SPEED: f32 = 12.0;
 
 impl Element for Button {
     fn layout(&mut self, ctx: &mut LayoutContext) -> NodeId {
@@ -432,8 +598,34 @@ impl Element for Button {
             ..Default::default()
         };
 
-        // Request text layout (button sizes to fit text + padding)
-        let node_id = if let Some(ref layout_id) = self.layout_id {
+        self.icon_node = None;
+        self.label_node = None;
+
+        let node_id = if let Some(icon) = self.icon.as_mut() {
+            // Lay out the icon and label side by side, centered
+            let icon_node = icon.layout(ctx);
+            let mut children = vec![icon_node];
+            self.icon_node = Some(icon_node);
+            if !self.label.is_empty() {
+                let label_node =
+                    ctx.request_text_layout(Style::default(), &self.label, &self.text_style);
+                children.push(label_node);
+                self.label_node = Some(label_node);
+            }
+            let style = Style {
+                display: Display::Flex,
+                flex_direction: FlexDirection::Row,
+                align_items: Some(AlignItems::Center),
+                justify_content: Some(JustifyContent::Center),
+                gap: Size {
+                    width: LengthPercentage::length(ICON_GAP),
+                    height: LengthPercentage::length(0.0),
+                },
+                ..style
+            };
+            ctx.request_layout_with_children(style, &children)
+        } else if let Some(ref layout_id) = self.layout_id {
+            // Request text layout (button sizes to fit text + padding)
             ctx.request_text_layout_cached(layout_id, style, &self.label, &self.text_style)
         } else {
             ctx.request_text_layout(style, &self.label, &self.text_style)
@@ -447,59 +639,70 @@ impl Element for Button {
             return;
         }
 
-        // Register for interaction if not disabled
-        if !self.disabled {
+        // Register for interaction if not disabled or loading
+        let interactive = !self.disabled && !self.loading;
+        if interactive {
             register_element(self.id, self.handlers.clone());
         }
 
         // Get current interaction state
-        let state = get_element_state(self.id).unwrap_or_default();
+        let state = if interactive {
+            get_element_state(self.id).unwrap_or_default()
+        } else {
+            InteractionState::default()
+        };
 
-        // Paint focus ring if focused (paint before background so it appears behind)
-        if state.is_focused && !self.disabled {
-            let focus_bounds = Rect::from_pos_size(
-                bounds.pos - Vec2::splat(FOCUS_RING_OFFSET),
-                bounds.size + Vec2::splat(FOCUS_RING_OFFSET * 2.0),
+        // Paint focus ring if focused
+        if state.is_focused {
+            ctx.paint_outline(
+                bounds,
+                Corners::all(self.corner_radius),
+                Outline::focus_ring(),
             );
-            ctx.paint_quad(PaintQuad {
-                bounds: focus_bounds,
-                fill: colors::TRANSPARENT,
-                corner_radii: Corners::all(self.corner_radius + FOCUS_RING_OFFSET),
-                border_widths: Edges::all(FOCUS_RING_WIDTH),
-                border_color: FOCUS_RING_COLOR,
-            });
         }
 
-        // Determine background color based on state
-        let bg_color = if self.disabled {
-            self.disabled_background
-        } else if state.is_pressed {
-            self.press_background
-        } else if state.is_hovered {
-            self.hover_background
-        } else {
-            self.background
+        // Pick colors for the current state
+        let style_state = StyleState::from_interaction(&state, self.disabled);
+        let resolved = self.states.resolve(style_state);
+        let color = |color: Option<Color>, fallback: Color| {
+            self.states
+                .apply_opacity(color.unwrap_or(fallback), style_state)
         };
 
         // Paint background
         ctx.paint_quad(PaintQuad {
             bounds,
-            fill: bg_color,
+            fill: color(resolved.bg, colors::TRANSPARENT),
             corner_radii: Corners::all(self.corner_radius),
             border_widths: Edges::all(self.border_width),
-            border_color: self.border_color.unwrap_or(colors::TRANSPARENT),
+            border_color: color(resolved.border.or(self.border_color), colors::TRANSPARENT),
         });
 
+        let text_color = color(resolved.text_color, self.text_style.color);
+
+        if self.loading {
+            let radius = self.text_style.size / 2.0;
+            paint_spinner(bounds.pos + bounds.size / 2.0, radius, text_color, ctx);
+        } else if self.icon.is_some() {
+            self.paint_icon_and_label(bounds, text_color, ctx);
+        } else {
+            self.paint_label(bounds, text_color, ctx);
+        }
+
+        // Register as focusable for hit testing if not disabled or loading
+        if interactive {
+            ctx.register_focusable(self.id, bounds, 0);
+        }
+    }
+}
+
+impl Button {
+    /// Paint the label centered within the button
+    fn paint_label(&self, bounds: Rect, text_color: Color, ctx: &mut PaintContext) {
         // Calculate text position (centered within bounds)
         let text_size = ctx.text_system.measure_text(
             &self.label,
-            &crate::text_system::TextConfig {
-                font_stack: parley::FontStack::from(self.text_style.font_family),
-                size: self.text_style.size,
-                weight: self.text_style.weight,
-                color: self.text_style.color.clone(),
-                line_height: self.text_style.line_height,
-            },
+            &crate::text_system::TextConfig::from(&self.text_style),
             None,
             ctx.scale_factor,
         );
@@ -507,13 +710,6 @@ impl Element for Button {
         let text_x = bounds.pos.x + (bounds.size.x - text_size.x) / 2.0;
         let text_y = bounds.pos.y + (bounds.size.y - text_size.y) / 2.0;
 
-        // Paint text
-        let text_color = if self.disabled {
-            self.disabled_text_color
-        } else {
-            self.text_style.color
-        };
-
         ctx.paint_text(PaintText {
             position: Vec2::new(text_x, text_y),
             text: self.label.clone(),
@@ -523,10 +719,100 @@ impl Element for Button {
             },
             measured_size: Some(text_size),
         });
+    }
 
-        // Register as focusable for hit testing if not disabled
-        if !self.disabled {
-            ctx.register_focusable(self.id, bounds, 0);
+    /// Paint the icon and label where layout placed them
+    fn paint_icon_and_label(&mut self, bounds: Rect, text_color: Color, ctx: &mut PaintContext) {
+        let child_bounds = |node: NodeId, ctx: &PaintContext| {
+            let layout = ctx.layout_engine.layout_bounds(node);
+            Rect::from_pos_size(bounds.pos + layout.pos, layout.size)
+        };
+
+        if let (Some(icon), Some(node)) = (self.icon.as_mut(), self.icon_node) {
+            icon.set_color(text_color);
+            icon.paint(child_bounds(node, ctx), ctx);
+        }
+        if let Some(node) = self.label_node {
+            let label_bounds = child_bounds(node, ctx);
+            ctx.paint_text(PaintText {
+                position: label_bounds.pos,
+                text: self.label.clone(),
+                style: TextStyle {
+                    color: text_color,
+                    ..self.text_style.clone()
+                },
+                measured_size: Some(label_bounds.size),
+            });
         }
     }
 }
+
+/// Paint a ring of dots spinning on the frame clock
+fn paint_spinner(center: Vec2, radius: f32, color: Color, ctx: &mut PaintContext) {
+    let dot_size = (radius / 2.5).max(2.0);
+    let head = (frame_time() * SPINNER_SPEED) as usize % SPINNER_DOTS;
+    for i in 0..SPINNER_DOTS {
+        let angle = i as f32 / SPINNER_DOTS as f32 * std::f32::consts::TAU;
+        let position = center + Vec2::new(angle.sin(), -angle.cos()) * (radius - dot_size / 2.0);
+
+        // Dots trailing the head fade out
+        let behind = (head + SPINNER_DOTS - i) % SPINNER_DOTS;
+        let mut color = color;
+        color.alpha *= 1.0 - behind as f32 / SPINNER_DOTS as f32 * 0.75;
+
+        ctx.paint_quad(PaintQuad {
+            bounds: Rect::from_pos_size(
+                position - Vec2::splat(dot_size / 2.0),
+                Vec2::splat(dot_size),
+            ),
+            fill: color,
+            corner_radii: Corners::all(dot_size / 2.0),
+            border_widths: Edges::zero(),
+            border_color: colors::TRANSPARENT,
+        });
+    }
+    ctx.request_animation_frame();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn press(button: &Button, key: Key) {
+        let mut handlers = button.handlers.borrow_mut();
+        let on_key_down = handlers.on_key_down.as_mut().expect("key handler");
+        on_key_down(key, Modifiers::default(), None, false);
+    }
+
+    #[test]
+    fn test_key_handlers_chain_with_activation() {
+        let clicks = Rc::new(Cell::new(0));
+        let keys = Rc::new(Cell::new(0));
+        let (c, k) = (clicks.clone(), keys.clone());
+        let btn = button("Save")
+            .on_key_down(move |_, _, _, _| k.set(k.get() + 1))
+            .on_click_simple(move || c.set(c.get() + 1));
+
+        press(&btn, Key::Return);
+        press(&btn, Key::A);
+        assert_eq!(clicks.get(), 1);
+        assert_eq!(keys.get(), 2);
+    }
+
+    #[test]
+    fn test_on_click_replaces_activation() {
+        let first = Rc::new(Cell::new(0));
+        let second = Rc::new(Cell::new(0));
+        let (a, b) = (first.clone(), second.clone());
+        let btn = button("Save")
+            .on_click_simple(move || a.set(a.get() + 1))
+            .on_click(move |button, click, _, _, _| {
+                assert_eq!((button, click), (MouseButton::Left, ClickType::Single));
+                b.set(b.get() + 1);
+            });
+
+        press(&btn, Key::Space);
+        assert_eq!((first.get(), second.get()), (0, 1));
+    }
+}
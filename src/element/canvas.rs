@@ -0,0 +1,369 @@
+//! Canvas element - a pannable, zoomable viewport for free-form content
+
+use crate::{
+    color::Color,
+    element::{Element, LayoutContext},
+    entity::{Entity, read_entity, update_entity},
+    geometry::{Rect, Transform},
+    interaction::{ElementId, EventHandlers, registry::register_element},
+    layer::MouseButton,
+    render::PaintContext,
+};
+use glam::Vec2;
+use std::cell::RefCell;
+use std::rc::Rc;
+use taffy::prelude::*;
+
+/// Zoom change per point of Cmd+wheel scroll delta
+const WHEEL_ZOOM_SPEED: f32 = 0.01;
+
+/// Pan and zoom of a canvas, persisted via the Entity system
+///
+/// Canvas space is the coordinate space children are placed in. "Screen"
+/// coordinates are the canvas's paint coordinates, which are window
+/// coordinates unless the canvas is nested inside another transform.
+#[derive(Debug, Clone)]
+pub struct CanvasState {
+    /// Offset of the canvas origin from the top-left of the viewport
+    pub offset: Vec2,
+    /// Zoom factor (1.0 = 100%)
+    pub zoom: f32,
+    /// Smallest allowed zoom
+    pub min_zoom: f32,
+    /// Largest allowed zoom
+    pub max_zoom: f32,
+    /// Viewport bounds from the last frame, in screen coordinates
+    pub viewport: Rect,
+    /// Last pointer position while dragging to pan
+    drag_position: Option<Vec2>,
+}
+
+impl Default for CanvasState {
+    fn default() -> Self {
+        Self {
+            offset: Vec2::ZERO,
+            zoom: 1.0,
+            min_zoom: 0.1,
+            max_zoom: 8.0,
+            viewport: Rect::new(0.0, 0.0, 0.0, 0.0),
+            drag_position: None,
+        }
+    }
+}
+
+impl CanvasState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the allowed zoom range
+    pub fn zoom_range(mut self, min: f32, max: f32) -> Self {
+        self.min_zoom = min;
+        self.max_zoom = max;
+        self.zoom = self.zoom.clamp(min, max);
+        self
+    }
+
+    /// Transform from canvas space to screen space
+    pub fn transform(&self) -> Transform {
+        Transform::new(self.viewport.pos + self.offset, self.zoom)
+    }
+
+    /// Convert a screen position (e.g. from a mouse event) to canvas space
+    pub fn screen_to_canvas(&self, point: Vec2) -> Vec2 {
+        self.transform().apply_inverse(point)
+    }
+
+    /// Convert a canvas position to screen space
+    pub fn canvas_to_screen(&self, point: Vec2) -> Vec2 {
+        self.transform().apply(point)
+    }
+
+    /// The part of the canvas currently visible, in canvas space
+    pub fn visible_rect(&self) -> Rect {
+        Rect::from_pos_size(
+            self.screen_to_canvas(self.viewport.pos),
+            self.viewport.size / self.zoom,
+        )
+    }
+
+    /// Move the canvas content by a screen-space delta
+    pub fn pan(&mut self, delta: Vec2) {
+        self.offset += delta;
+    }
+
+    /// Zoom by `factor`, keeping the canvas point under the screen position
+    /// `anchor` in place
+    pub fn zoom_at(&mut self, anchor: Vec2, factor: f32) {
+        let canvas_point = self.screen_to_canvas(anchor);
+        self.zoom = (self.zoom * factor).clamp(self.min_zoom, self.max_zoom);
+        self.offset = anchor - self.viewport.pos - canvas_point * self.zoom;
+    }
+
+    /// Return to the initial pan and zoom
+    pub fn reset(&mut self) {
+        self.offset = Vec2::ZERO;
+        self.zoom = 1.0_f32.clamp(self.min_zoom, self.max_zoom);
+    }
+}
+
+/// Create a canvas bound to a persistent pan/zoom state
+pub fn canvas(state: Entity<CanvasState>) -> Canvas {
+    Canvas::new(state)
+}
+
+/// A viewport that pans and zooms its children
+///
+/// Two-finger scroll pans and pinch or Cmd+wheel zooms around the pointer.
+/// Dragging the background with the pan button also pans. Children are placed
+/// at canvas-space positions with [`child_at`](Self::child_at) and are painted,
+/// culled and hit tested through the canvas transform.
+pub struct Canvas {
+    state: Entity<CanvasState>,
+    element_id: ElementId,
+    style: Style,
+    background: Option<Color>,
+    pan_button: Option<MouseButton>,
+    children: Vec<(Vec2, Box<dyn Element>)>,
+    /// Positioning wrapper and child node for each child
+    child_nodes: Vec<(NodeId, NodeId)>,
+}
+
+impl Canvas {
+    pub fn new(state: Entity<CanvasState>) -> Self {
+        Self {
+            element_id: ElementId::stable(format!("canvas:{:?}", state.id())),
+            state,
+            style: Style {
+                overflow: taffy::Point {
+                    x: taffy::Overflow::Hidden,
+                    y: taffy::Overflow::Hidden,
+                },
+                ..Style::default()
+            },
+            background: None,
+            pan_button: Some(MouseButton::Left),
+            children: Vec::new(),
+            child_nodes: Vec::new(),
+        }
+    }
+
+    /// Set a unique key for this canvas
+    pub fn with_key(mut self, key: impl AsRef<str>) -> Self {
+        self.element_id = ElementId::stable(format!("canvas:{}", key.as_ref()));
+        self
+    }
+
+    /// Set the background color
+    pub fn background(mut self, color: Color) -> Self {
+        self.background = Some(color);
+        self
+    }
+
+    /// Set the mouse button that pans when dragging the background, or
+    /// `None` to pan only with scrolling
+    pub fn pan_button(mut self, button: Option<MouseButton>) -> Self {
+        self.pan_button = button;
+        self
+    }
+
+    /// Add a child at the canvas origin
+    pub fn child(self, child: impl Element + 'static) -> Self {
+        self.child_at(Vec2::ZERO, child)
+    }
+
+    /// Add a child at a position in canvas space
+    pub fn child_at(mut self, position: Vec2, child: impl Element + 'static) -> Self {
+        self.children.push((position, Box::new(child)));
+        self
+    }
+
+    /// Set width
+    pub fn width(mut self, width: f32) -> Self {
+        self.style.size.width = Dimension::length(width);
+        self
+    }
+
+    /// Set height
+    pub fn height(mut self, height: f32) -> Self {
+        self.style.size.height = Dimension::length(height);
+        self
+    }
+
+    /// Set both width and height
+    pub fn size(mut self, width: f32, height: f32) -> Self {
+        self.style.size = Size {
+            width: Dimension::length(width),
+            height: Dimension::length(height),
+        };
+        self
+    }
+
+    /// Set width to 100%
+    pub fn width_full(mut self) -> Self {
+        self.style.size.width = Dimension::percent(1.0);
+        self
+    }
+
+    /// Set height to 100%
+    pub fn height_full(mut self) -> Self {
+        self.style.size.height = Dimension::percent(1.0);
+        self
+    }
+
+    /// Set flex grow
+    pub fn flex_grow(mut self, grow: f32) -> Self {
+        self.style.flex_grow = grow;
+        self
+    }
+
+    fn handlers(&self) -> EventHandlers {
+        let pan_button = self.pan_button;
+        let down_state = self.state.clone();
+        let move_state = self.state.clone();
+        let up_state = self.state.clone();
+        let scroll_state = self.state.clone();
+        let magnify_state = self.state.clone();
+
+        EventHandlers::new()
+            .on_mouse_down(move |button, position, _, _, _| {
+                if Some(button) == pan_button {
+                    update_entity(&down_state, |s| s.drag_position = Some(position));
+                }
+            })
+            .on_mouse_move(move |position, _| {
+                update_entity(&move_state, |s| {
+                    if let Some(last) = s.drag_position {
+                        s.pan(position - last);
+                        s.drag_position = Some(position);
+                    }
+                });
+            })
+            .on_mouse_up(move |button, _, _, _| {
+                if Some(button) == pan_button {
+                    update_entity(&up_state, |s| s.drag_position = None);
+                }
+            })
+            .on_scroll_with_modifiers(move |delta, position, _, modifiers| {
+                update_entity(&scroll_state, |s| {
+                    if modifiers.cmd {
+                        s.zoom_at(position, (delta.y * WHEEL_ZOOM_SPEED).exp());
+                    } else {
+                        s.pan(delta);
+                    }
+                });
+            })
+            .on_magnify(move |magnification, position, _| {
+                update_entity(&magnify_state, |s| {
+                    s.zoom_at(position, (1.0 + magnification).max(0.01));
+                });
+            })
+    }
+}
+
+impl Element for Canvas {
+    fn layout(&mut self, ctx: &mut LayoutContext) -> NodeId {
+        // Each child sits in an absolutely positioned wrapper at its canvas
+        // position, so it keeps its natural size regardless of the viewport
+        self.child_nodes.clear();
+        for (position, child) in &mut self.children {
+            let child_node = child.layout(ctx);
+            let wrapper_style = Style {
+                position: Position::Absolute,
+                inset: taffy::Rect {
+                    left: LengthPercentageAuto::length(position.x),
+                    top: LengthPercentageAuto::length(position.y),
+                    right: LengthPercentageAuto::auto(),
+                    bottom: LengthPercentageAuto::auto(),
+                },
+                ..Style::default()
+            };
+            let wrapper = ctx.request_layout_with_children(wrapper_style, &[child_node]);
+            self.child_nodes.push((wrapper, child_node));
+        }
+
+        let wrappers: Vec<NodeId> = self.child_nodes.iter().map(|(w, _)| *w).collect();
+        ctx.request_layout_with_children(self.style.clone(), &wrappers)
+    }
+
+    fn paint(&mut self, bounds: Rect, ctx: &mut PaintContext) {
+        if !ctx.is_visible(&bounds) {
+            return;
+        }
+
+        if let Some(background) = self.background {
+            ctx.paint_solid_quad(bounds, background);
+        }
+
+        // Only write the viewport when it moves, so observers aren't notified every frame
+        if read_entity(&self.state, |s| s.viewport != bounds).unwrap_or(false) {
+            update_entity(&self.state, |s| s.viewport = bounds);
+        }
+        let transform =
+            read_entity(&self.state, |s| s.transform()).unwrap_or(Transform::new(bounds.pos, 1.0));
+
+        ctx.draw_list.push_clip(bounds);
+        ctx.with_transform(transform, |ctx| {
+            for ((_, child), &(wrapper, child_node)) in
+                self.children.iter_mut().zip(&self.child_nodes)
+            {
+                let wrapper_bounds = ctx.layout_engine.layout_bounds(wrapper);
+                let child_bounds = ctx.layout_engine.layout_bounds(child_node);
+                let bounds =
+                    Rect::from_pos_size(wrapper_bounds.pos + child_bounds.pos, child_bounds.size);
+                if ctx.is_visible(&bounds) {
+                    child.paint(bounds, ctx);
+                }
+            }
+        });
+        ctx.draw_list.pop_clip();
+
+        // Register after the children so they win hit tests over the background
+        register_element(self.element_id, Rc::new(RefCell::new(self.handlers())));
+        ctx.register_hit_test(self.element_id, bounds, 0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> CanvasState {
+        CanvasState {
+            viewport: Rect::new(100.0, 50.0, 400.0, 300.0),
+            ..CanvasState::new()
+        }
+    }
+
+    #[test]
+    fn test_canvas_coordinate_conversion() {
+        let mut state = state();
+        state.pan(Vec2::new(20.0, 10.0));
+        state.zoom = 2.0;
+
+        let point = Vec2::new(5.0, 5.0);
+        let screen = state.canvas_to_screen(point);
+        assert_eq!(screen, Vec2::new(130.0, 70.0));
+        assert_eq!(state.screen_to_canvas(screen), point);
+    }
+
+    #[test]
+    fn test_canvas_zoom_keeps_anchor_fixed() {
+        let mut state = state();
+        let anchor = Vec2::new(250.0, 150.0);
+        let before = state.screen_to_canvas(anchor);
+
+        state.zoom_at(anchor, 2.0);
+        assert_eq!(state.zoom, 2.0);
+        assert!((state.screen_to_canvas(anchor) - before).length() < 1e-4);
+    }
+
+    #[test]
+    fn test_canvas_zoom_clamped() {
+        let mut state = state().zoom_range(0.5, 4.0);
+        state.zoom_at(Vec2::ZERO, 100.0);
+        assert_eq!(state.zoom, 4.0);
+        state.zoom_at(Vec2::ZERO, 0.001);
+        assert_eq!(state.zoom, 0.5);
+    }
+}
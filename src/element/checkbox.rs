@@ -10,7 +10,7 @@ use crate::{
     },
     layer::{Key, MouseButton},
     render::PaintQuad,
-    style::TextStyle,
+    style::{Outline, TextStyle},
 };
 use glam::Vec2;
 use std::cell::RefCell;
@@ -21,12 +21,6 @@ use taffy::prelude::*;
 const DEFAULT_SIZE: f32 = 20.0;
 /// Default gap between checkbox and label
 const DEFAULT_LABEL_GAP: f32 = 8.0;
-/// Focus ring color for checkboxes
-const FOCUS_RING_COLOR: Color = colors::BLUE_400;
-/// Focus ring width
-const FOCUS_RING_WIDTH: f32 = 2.0;
-/// Focus ring offset from element bounds
-const FOCUS_RING_OFFSET: f32 = 2.0;
 
 /// Create a new checkbox element
 pub fn checkbox(checked: bool) -> Checkbox {
@@ -436,19 +430,13 @@ impl Element for Checkbox {
             Vec2::new(self.box_size, self.box_size),
         );
 
-        // Paint focus ring if focused (paint before checkbox so it appears behind)
+        // Paint focus ring if focused
         if state.is_focused && !self.disabled {
-            let focus_bounds = Rect::from_pos_size(
-                checkbox_bounds.pos - Vec2::splat(FOCUS_RING_OFFSET),
-                checkbox_bounds.size + Vec2::splat(FOCUS_RING_OFFSET * 2.0),
+            ctx.paint_outline(
+                checkbox_bounds,
+                Corners::all(self.corner_radius),
+                Outline::focus_ring(),
             );
-            ctx.paint_quad(PaintQuad {
-                bounds: focus_bounds,
-                fill: colors::TRANSPARENT,
-                corner_radii: Corners::all(self.corner_radius + FOCUS_RING_OFFSET),
-                border_widths: Edges::all(FOCUS_RING_WIDTH),
-                border_color: FOCUS_RING_COLOR,
-            });
         }
 
         // Determine colors based on state
@@ -0,0 +1,613 @@
+//! Monospace code view with line numbers and syntax highlighting
+
+use crate::{
+    color::{Color, colors},
+    element::{Element, LayoutContext},
+    entity::{Entity, new_entity, read_entity, update_entity},
+    geometry::{Corners, Edges, Rect},
+    interaction::{ElementId, EventHandlers, registry::register_element},
+    render::{PaintContext, PaintQuad, PaintText},
+    style::{LineHeight, TextStyle},
+};
+use glam::Vec2;
+use std::cell::RefCell;
+use std::ops::Range;
+use std::rc::Rc;
+use taffy::prelude::*;
+
+/// Number of columns a tab character expands to
+const TAB_WIDTH: usize = 4;
+
+/// Kind of a highlighted token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    Keyword,
+    Type,
+    Function,
+    String,
+    Number,
+    Comment,
+    Punctuation,
+    Plain,
+}
+
+/// A highlighted range of the source, in byte offsets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub range: Range<usize>,
+    pub kind: TokenKind,
+}
+
+impl Token {
+    pub fn new(range: Range<usize>, kind: TokenKind) -> Self {
+        Self { range, kind }
+    }
+}
+
+/// Produces tokens for a source string
+///
+/// Tokens may span multiple lines and need not cover the whole source;
+/// uncovered text is drawn as [`TokenKind::Plain`].
+pub trait SyntaxHighlighter {
+    fn highlight(&self, source: &str) -> Vec<Token>;
+}
+
+/// Colors for each token kind
+#[derive(Debug, Clone)]
+pub struct CodeTheme {
+    pub background: Color,
+    pub gutter_background: Color,
+    pub gutter_text: Color,
+    pub plain: Color,
+    pub keyword: Color,
+    pub type_name: Color,
+    pub function: Color,
+    pub string: Color,
+    pub number: Color,
+    pub comment: Color,
+    pub punctuation: Color,
+}
+
+impl CodeTheme {
+    /// Get the color for a token kind
+    pub fn color_for(&self, kind: TokenKind) -> Color {
+        match kind {
+            TokenKind::Keyword => self.keyword,
+            TokenKind::Type => self.type_name,
+            TokenKind::Function => self.function,
+            TokenKind::String => self.string,
+            TokenKind::Number => self.number,
+            TokenKind::Comment => self.comment,
+            TokenKind::Punctuation => self.punctuation,
+            TokenKind::Plain => self.plain,
+        }
+    }
+}
+
+impl Default for CodeTheme {
+    fn default() -> Self {
+        Self {
+            background: colors::WHITE,
+            gutter_background: colors::GRAY_100,
+            gutter_text: colors::GRAY_400,
+            plain: colors::GRAY_900,
+            keyword: colors::PURPLE_600,
+            type_name: colors::BLUE_600,
+            function: colors::BLUE_500,
+            string: colors::GREEN_600,
+            number: colors::ORANGE,
+            comment: colors::GRAY_500,
+            punctuation: colors::GRAY_600,
+        }
+    }
+}
+
+/// A simple highlighter for Rust source
+///
+/// Recognizes keywords, string and char literals, numbers, line and block
+/// comments, capitalized type names, and function calls.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RustHighlighter;
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while",
+];
+
+impl SyntaxHighlighter for RustHighlighter {
+    fn highlight(&self, source: &str) -> Vec<Token> {
+        let bytes = source.as_bytes();
+        let mut tokens = Vec::new();
+        let mut i = 0;
+
+        while i < bytes.len() {
+            let c = bytes[i];
+            let start = i;
+
+            if source[i..].starts_with("//") {
+                i = source[i..].find('\n').map_or(bytes.len(), |n| i + n);
+                tokens.push(Token::new(start..i, TokenKind::Comment));
+            } else if source[i..].starts_with("/*") {
+                i = source[i + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |n| i + 2 + n + 2);
+                tokens.push(Token::new(start..i, TokenKind::Comment));
+            } else if c == b'"' {
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                i = (i + 1).min(bytes.len());
+                tokens.push(Token::new(start..i, TokenKind::String));
+            } else if c == b'\'' && is_char_literal(&source[i..]) {
+                i += source[i + 1..].find('\'').map_or(1, |n| n + 2);
+                tokens.push(Token::new(start..i, TokenKind::String));
+            } else if c.is_ascii_digit() {
+                while i < bytes.len()
+                    && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_' || bytes[i] == b'.')
+                {
+                    // Stop before method calls and ranges like `1..2` or `1.max(2)`
+                    if bytes[i] == b'.' && !bytes.get(i + 1).is_some_and(|b| b.is_ascii_digit()) {
+                        break;
+                    }
+                    i += 1;
+                }
+                tokens.push(Token::new(start..i, TokenKind::Number));
+            } else if c.is_ascii_alphabetic() || c == b'_' {
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                    i += 1;
+                }
+                let word = &source[start..i];
+                let kind = if RUST_KEYWORDS.contains(&word) {
+                    TokenKind::Keyword
+                } else if bytes.get(i) == Some(&b'(') || bytes.get(i) == Some(&b'!') {
+                    TokenKind::Function
+                } else if word.starts_with(|c: char| c.is_ascii_uppercase()) {
+                    TokenKind::Type
+                } else {
+                    TokenKind::Plain
+                };
+                if kind != TokenKind::Plain {
+                    tokens.push(Token::new(start..i, kind));
+                }
+            } else if c.is_ascii_punctuation() {
+                i += 1;
+                tokens.push(Token::new(start..i, TokenKind::Punctuation));
+            } else {
+                // Skip whitespace and non-ASCII characters a whole char at a time
+                i += source[i..].chars().next().map_or(1, |ch| ch.len_utf8());
+            }
+        }
+
+        tokens
+    }
+}
+
+/// Check if a `'` starts a char literal rather than a lifetime
+fn is_char_literal(rest: &str) -> bool {
+    let mut chars = rest.chars().skip(1);
+    matches!(
+        (chars.next(), chars.next()),
+        (Some('\\'), _) | (Some(_), Some('\''))
+    )
+}
+
+/// A run of same-colored text on one line
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeSpan {
+    /// Display column the span starts at, after tab expansion
+    pub column: usize,
+    pub text: String,
+    pub kind: TokenKind,
+}
+
+/// Split highlighted source into per-line spans with tabs expanded
+///
+/// Whitespace is preserved: leading indentation becomes the column of the
+/// first span, and tabs advance to the next multiple of the tab width.
+pub fn highlight_lines(source: &str, tokens: &[Token]) -> Vec<Vec<CodeSpan>> {
+    // Kind of every byte, defaulting to plain text
+    let mut kinds = vec![TokenKind::Plain; source.len()];
+    for token in tokens {
+        let range = token.range.start.min(source.len())..token.range.end.min(source.len());
+        kinds[range].fill(token.kind);
+    }
+
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    for line in source.split('\n') {
+        let mut spans: Vec<CodeSpan> = Vec::new();
+        let mut column = 0;
+        for (i, ch) in line.char_indices() {
+            if ch == '\t' {
+                column += TAB_WIDTH - column % TAB_WIDTH;
+                continue;
+            }
+            if ch == ' ' || ch == '\r' {
+                column += usize::from(ch == ' ');
+                continue;
+            }
+
+            let kind = kinds[offset + i];
+            match spans.last_mut() {
+                Some(span)
+                    if span.kind == kind && span.column + span.text.chars().count() == column =>
+                {
+                    span.text.push(ch);
+                }
+                _ => spans.push(CodeSpan {
+                    column,
+                    text: ch.to_string(),
+                    kind,
+                }),
+            }
+            column += 1;
+        }
+        lines.push(spans);
+        offset += line.len() + 1;
+    }
+    lines
+}
+
+/// Horizontal scroll state for a code view
+#[derive(Debug, Clone, Default)]
+pub struct CodeViewState {
+    /// Horizontal scroll offset in pixels
+    pub scroll_x: f32,
+    /// Largest scroll offset for the current content
+    pub max_scroll_x: f32,
+}
+
+/// Create a code view for a source string
+pub fn code_view(source: impl Into<String>) -> CodeView {
+    CodeView::new(source)
+}
+
+/// A monospace view of source code
+///
+/// Whitespace is preserved, long lines scroll horizontally, and an optional
+/// gutter shows line numbers. Colors come from a pluggable
+/// [`SyntaxHighlighter`] and a [`CodeTheme`].
+///
+/// # Example
+/// ```ignore
+/// code_view(source)
+///     .with_key("snippet")
+///     .highlighter(RustHighlighter)
+///     .line_numbers(true)
+/// ```
+pub struct CodeView {
+    source: String,
+    highlighter: Option<Box<dyn SyntaxHighlighter>>,
+    theme: CodeTheme,
+    element_id: ElementId,
+    state: Option<Entity<CodeViewState>>,
+    show_line_numbers: bool,
+    font_family: &'static str,
+    font_size: f32,
+    line_height: f32,
+    padding: f32,
+    width: Option<f32>,
+    corner_radius: f32,
+
+    // Computed in layout
+    lines: Vec<Vec<CodeSpan>>,
+    char_width: f32,
+    node_id: Option<NodeId>,
+}
+
+impl CodeView {
+    /// Create a new code view
+    ///
+    /// Note: For horizontal scrolling to persist across frames, call
+    /// `.with_key()` or `.state()`.
+    #[allow(deprecated)]
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            highlighter: None,
+            theme: CodeTheme::default(),
+            element_id: ElementId::auto(),
+            state: None,
+            show_line_numbers: false,
+            font_family: "Menlo",
+            font_size: 13.0,
+            line_height: 1.5,
+            padding: 8.0,
+            width: None,
+            corner_radius: 4.0,
+            lines: Vec::new(),
+            char_width: 0.0,
+            node_id: None,
+        }
+    }
+
+    /// Set a unique key for this code view
+    pub fn with_key(mut self, key: impl AsRef<str>) -> Self {
+        self.element_id = ElementId::stable(format!("code-view:{}", key.as_ref()));
+        self
+    }
+
+    /// Bind to a persistent scroll state entity
+    pub fn state(mut self, state: Entity<CodeViewState>) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Set the syntax highlighter
+    pub fn highlighter(mut self, highlighter: impl SyntaxHighlighter + 'static) -> Self {
+        self.highlighter = Some(Box::new(highlighter));
+        self
+    }
+
+    /// Set the color theme
+    pub fn theme(mut self, theme: CodeTheme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Show or hide the line number gutter
+    pub fn line_numbers(mut self, show: bool) -> Self {
+        self.show_line_numbers = show;
+        self
+    }
+
+    /// Set the monospace font family
+    pub fn font_family(mut self, family: &'static str) -> Self {
+        self.font_family = family;
+        self
+    }
+
+    /// Set the font size
+    pub fn font_size(mut self, size: f32) -> Self {
+        self.font_size = size;
+        self
+    }
+
+    /// Set the line height multiplier
+    pub fn line_height(mut self, line_height: f32) -> Self {
+        self.line_height = line_height;
+        self
+    }
+
+    /// Set a fixed width; long lines scroll horizontally within it
+    pub fn width(mut self, width: f32) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Set corner radius
+    pub fn corner_radius(mut self, radius: f32) -> Self {
+        self.corner_radius = radius;
+        self
+    }
+
+    fn text_style(&self, color: Color) -> TextStyle {
+        TextStyle {
+            size: self.font_size,
+            color,
+            font_family: self.font_family,
+            line_height: LineHeight::Relative(self.line_height),
+            ..Default::default()
+        }
+    }
+
+    fn line_pixel_height(&self) -> f32 {
+        self.font_size * self.line_height
+    }
+
+    fn gutter_width(&self) -> f32 {
+        if !self.show_line_numbers {
+            return 0.0;
+        }
+        let digits = self.lines.len().max(1).to_string().len();
+        digits as f32 * self.char_width + self.padding * 2.0
+    }
+
+    fn content_width(&self) -> f32 {
+        let columns = self
+            .lines
+            .iter()
+            .filter_map(|spans| spans.last())
+            .map(|span| span.column + span.text.chars().count())
+            .max()
+            .unwrap_or(0);
+        columns as f32 * self.char_width + self.padding * 2.0
+    }
+}
+
+impl Element for CodeView {
+    fn layout(&mut self, ctx: &mut LayoutContext) -> NodeId {
+        if self.state.is_none() {
+            self.state = Some(new_entity(CodeViewState::default()));
+        }
+
+        let tokens = self
+            .highlighter
+            .as_ref()
+            .map(|h| h.highlight(&self.source))
+            .unwrap_or_default();
+        self.lines = highlight_lines(&self.source, &tokens);
+        self.char_width = ctx
+            .measure_text("0", &self.text_style(self.theme.plain), None)
+            .x;
+
+        let height = self.lines.len() as f32 * self.line_pixel_height() + self.padding * 2.0;
+        let width = match self.width {
+            Some(width) => Dimension::length(width),
+            None => Dimension::percent(1.0),
+        };
+        let style = Style {
+            size: Size {
+                width,
+                height: Dimension::length(height),
+            },
+            ..Default::default()
+        };
+
+        let node_id = ctx.request_layout(style);
+        self.node_id = Some(node_id);
+        node_id
+    }
+
+    fn paint(&mut self, bounds: Rect, ctx: &mut PaintContext) {
+        if !ctx.is_visible(&bounds) {
+            return;
+        }
+
+        ctx.paint_quad(PaintQuad {
+            bounds,
+            fill: self.theme.background,
+            corner_radii: Corners::all(self.corner_radius),
+            border_widths: Edges::zero(),
+            border_color: colors::TRANSPARENT,
+        });
+
+        let line_height = self.line_pixel_height();
+        let gutter_width = self.gutter_width();
+        let code_bounds = Rect::from_pos_size(
+            bounds.pos + Vec2::new(gutter_width, 0.0),
+            Vec2::new((bounds.size.x - gutter_width).max(0.0), bounds.size.y),
+        );
+
+        // Clamp the scroll offset to the current content
+        let max_scroll_x = (self.content_width() - code_bounds.size.x).max(0.0);
+        let scroll_x = self
+            .state
+            .as_ref()
+            .and_then(|s| {
+                update_entity(s, |state| {
+                    state.max_scroll_x = max_scroll_x;
+                    state.scroll_x = state.scroll_x.clamp(0.0, max_scroll_x);
+                });
+                read_entity(s, |state| state.scroll_x)
+            })
+            .unwrap_or(0.0);
+
+        // Gutter with right-aligned line numbers
+        if self.show_line_numbers {
+            ctx.paint_quad(PaintQuad::filled(
+                Rect::from_pos_size(bounds.pos, Vec2::new(gutter_width, bounds.size.y)),
+                self.theme.gutter_background,
+            ));
+            let digits = self.lines.len().max(1).to_string().len();
+            for number in 1..=self.lines.len() {
+                let y = bounds.pos.y + self.padding + (number - 1) as f32 * line_height;
+                let line_rect = Rect::from_pos_size(
+                    Vec2::new(bounds.pos.x, y),
+                    Vec2::new(gutter_width, line_height),
+                );
+                if !ctx.is_visible(&line_rect) {
+                    continue;
+                }
+                let label = number.to_string();
+                let x =
+                    bounds.pos.x + self.padding + (digits - label.len()) as f32 * self.char_width;
+                ctx.paint_text(PaintText {
+                    position: Vec2::new(x, y),
+                    text: label,
+                    style: self.text_style(self.theme.gutter_text),
+                    measured_size: None,
+                });
+            }
+        }
+
+        // Code, clipped to the area right of the gutter
+        ctx.draw_list.push_clip(code_bounds);
+        let origin_x = code_bounds.pos.x + self.padding - scroll_x;
+        for (index, spans) in self.lines.iter().enumerate() {
+            let y = bounds.pos.y + self.padding + index as f32 * line_height;
+            let line_rect = Rect::from_pos_size(
+                Vec2::new(code_bounds.pos.x, y),
+                Vec2::new(code_bounds.size.x, line_height),
+            );
+            if !ctx.is_visible(&line_rect) {
+                continue;
+            }
+            for span in spans {
+                ctx.paint_text(PaintText {
+                    position: Vec2::new(origin_x + span.column as f32 * self.char_width, y),
+                    text: span.text.clone(),
+                    style: self.text_style(self.theme.color_for(span.kind)),
+                    measured_size: None,
+                });
+            }
+        }
+        ctx.draw_list.pop_clip();
+
+        // Horizontal scrolling for long lines
+        if max_scroll_x > 0.0
+            && let Some(state) = self.state.clone()
+        {
+            let handlers = EventHandlers::new().on_scroll(move |delta, _, _| {
+                update_entity(&state, |s| {
+                    s.scroll_x = (s.scroll_x - delta.x).clamp(0.0, s.max_scroll_x);
+                });
+            });
+            register_element(self.element_id, Rc::new(RefCell::new(handlers)));
+            ctx.register_hit_test(self.element_id, code_bounds, 0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(source: &str) -> Vec<(&str, TokenKind)> {
+        RustHighlighter
+            .highlight(source)
+            .into_iter()
+            .map(|t| (&source[t.range], t.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_rust_highlighter_tokens() {
+        let tokens = kinds("let x: Vec<u8> = foo(1.5); // done");
+        assert!(tokens.contains(&("let", TokenKind::Keyword)));
+        assert!(tokens.contains(&("Vec", TokenKind::Type)));
+        assert!(tokens.contains(&("foo", TokenKind::Function)));
+        assert!(tokens.contains(&("1.5", TokenKind::Number)));
+        assert!(tokens.contains(&("// done", TokenKind::Comment)));
+        assert!(!tokens.iter().any(|(text, _)| *text == "x"));
+    }
+
+    #[test]
+    fn test_strings_and_lifetimes() {
+        let tokens = kinds(r#"fn f<'a>(s: &'a str) -> char { "a\"b"; 'c' }"#);
+        assert!(tokens.contains(&(r#""a\"b""#, TokenKind::String)));
+        assert!(tokens.contains(&("'c'", TokenKind::String)));
+        assert!(
+            !tokens
+                .iter()
+                .any(|(text, kind)| text.starts_with("'a") && *kind == TokenKind::String)
+        );
+    }
+
+    #[test]
+    fn test_highlight_lines_preserves_whitespace() {
+        let source = "fn main() {\n\tlet  x = 1;\n}";
+        let lines = highlight_lines(source, &RustHighlighter.highlight(source));
+        assert_eq!(lines.len(), 3);
+
+        // Tab expands to the first tab stop, double space is kept
+        let second = &lines[1];
+        assert_eq!(second[0].column, TAB_WIDTH);
+        assert_eq!(second[0].text, "let");
+        assert_eq!(second[1].column, TAB_WIDTH + 5);
+        assert_eq!(second[1].text, "x");
+    }
+
+    #[test]
+    fn test_multiline_comment_spans_lines() {
+        let source = "/* a\nb */ c";
+        let lines = highlight_lines(source, &RustHighlighter.highlight(source));
+        assert_eq!(lines[0][0].kind, TokenKind::Comment);
+        assert_eq!(lines[1][0].text, "b");
+        assert_eq!(lines[1][0].kind, TokenKind::Comment);
+        assert_eq!(lines[1].last().unwrap().kind, TokenKind::Plain);
+    }
+}
@@ -0,0 +1,264 @@
+//! Color field with a swatch, hex readout and screen eyedropper
+
+use crate::{
+    color::{Color, ColorExt, colors},
+    element::{Element, LayoutContext, icon::Icon, icons},
+    entity::{Entity, read_entity, update_entity},
+    geometry::{Corners, Edges, Rect},
+    interaction::{
+        ElementId, EventHandlers,
+        registry::{get_element_state, register_element},
+    },
+    layer::MouseButton,
+    render::{PaintContext, PaintQuad, PaintText},
+    style::TextStyle,
+};
+use glam::Vec2;
+use std::cell::RefCell;
+use std::rc::Rc;
+use taffy::prelude::*;
+
+/// Height of the field
+const HEIGHT: f32 = 28.0;
+/// Width of the hex readout
+const HEX_WIDTH: f32 = 64.0;
+/// Space between the swatch, readout and eyedropper
+const GAP: f32 = 6.0;
+/// Size of the eyedropper icon
+const ICON_SIZE: f32 = 16.0;
+
+/// Color picker state, persisted via the Entity system
+#[derive(Debug, Clone)]
+pub struct ColorPickerState {
+    color: Color,
+    /// Color picked by the eyedropper, applied on the next layout
+    sampled: Option<Color>,
+    /// Whether the eyedropper is waiting for the user to pick
+    sampling: bool,
+}
+
+impl ColorPickerState {
+    pub fn new(color: Color) -> Self {
+        Self {
+            color,
+            sampled: None,
+            sampling: false,
+        }
+    }
+
+    /// The current color
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    /// Set the color without calling the picker's change handler
+    pub fn set_color(&mut self, color: Color) {
+        self.color = color;
+    }
+
+    /// Whether the eyedropper is waiting for the user to pick a color
+    pub fn is_sampling(&self) -> bool {
+        self.sampling
+    }
+}
+
+/// Called with the color picked by the eyedropper
+type ChangeCallback = Rc<RefCell<Box<dyn FnMut(Color)>>>;
+
+/// Create a color picker showing the color in `state`
+///
+/// # Example
+/// ```ignore
+/// let accent = new_entity(ColorPickerState::new(colors::BLUE_500));
+/// color_picker(accent.clone()).on_change(|color| println!("{:?}", color))
+/// ```
+pub fn color_picker(state: Entity<ColorPickerState>) -> ColorPicker {
+    ColorPicker::new(state)
+}
+
+/// A color swatch with its hex value and an eyedropper button
+///
+/// The eyedropper picks a color from anywhere on screen with
+/// [`sample_screen_color`](crate::platform::sample_screen_color).
+pub struct ColorPicker {
+    state: Entity<ColorPickerState>,
+    eyedropper_id: ElementId,
+    on_change: Option<ChangeCallback>,
+    text_style: TextStyle,
+    eyedropper: Icon,
+}
+
+impl ColorPicker {
+    pub fn new(state: Entity<ColorPickerState>) -> Self {
+        let eyedropper_id = ElementId::stable(format!("color-picker:{:?}", state.id()));
+        Self {
+            state,
+            eyedropper_id,
+            on_change: None,
+            text_style: TextStyle {
+                size: 13.0,
+                color: colors::GRAY_800,
+                ..Default::default()
+            },
+            eyedropper: Icon::new(icons::EYEDROPPER).size(ICON_SIZE),
+        }
+    }
+
+    /// Set a stable key, for pickers sharing a state entity
+    pub fn with_key(mut self, key: impl AsRef<str>) -> Self {
+        self.eyedropper_id = ElementId::stable(format!("color-picker:{}", key.as_ref()));
+        self
+    }
+
+    /// Set the handler called when the eyedropper picks a color
+    pub fn on_change<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(Color) + 'static,
+    {
+        self.on_change = Some(Rc::new(RefCell::new(Box::new(handler))));
+        self
+    }
+
+    /// Set the style of the hex readout
+    pub fn text_style(mut self, style: TextStyle) -> Self {
+        self.text_style = style;
+        self
+    }
+
+    /// Handlers for the eyedropper button
+    fn eyedropper_handlers(&self) -> EventHandlers {
+        let state = self.state.clone();
+        EventHandlers::new().on_click(move |button, _, _, _, _| {
+            if button != MouseButton::Left {
+                return;
+            }
+            #[cfg(target_os = "macos")]
+            {
+                update_entity(&state, |s| s.sampling = true);
+                // The sampler finishes outside of any frame, so the result is
+                // queued for the next one
+                let handle = crate::entity::entity_store_handle();
+                let weak = state.downgrade();
+                crate::platform::sample_screen_color(move |color| {
+                    handle.update(&weak, move |s| {
+                        s.sampling = false;
+                        s.sampled = color;
+                    });
+                });
+            }
+            // Other platforms have no screen to sample from
+            #[cfg(not(target_os = "macos"))]
+            let _ = &state;
+        })
+    }
+}
+
+/// The `#RRGGBB` form of `color`, with alpha appended when it isn't opaque
+fn hex_label(color: Color) -> String {
+    let [r, g, b, a] = color.as_u8_arr();
+    if a == 255 {
+        format!("#{:02X}{:02X}{:02X}", r, g, b)
+    } else {
+        format!("#{:02X}{:02X}{:02X}{:02X}", r, g, b, a)
+    }
+}
+
+impl Element for ColorPicker {
+    fn layout(&mut self, ctx: &mut LayoutContext) -> NodeId {
+        // Apply a color picked since the last frame
+        let sampled = update_entity(&self.state, |s| {
+            let sampled = s.sampled.take();
+            if let Some(color) = sampled {
+                s.color = color;
+            }
+            sampled
+        })
+        .flatten();
+        if let (Some(color), Some(on_change)) = (sampled, &self.on_change) {
+            (on_change.borrow_mut())(color);
+        }
+
+        ctx.request_layout(Style {
+            size: Size {
+                width: length(HEIGHT * 2.0 + HEX_WIDTH + GAP * 2.0),
+                height: length(HEIGHT),
+            },
+            flex_shrink: 0.0,
+            ..Default::default()
+        })
+    }
+
+    fn paint(&mut self, bounds: Rect, ctx: &mut PaintContext) {
+        if !ctx.is_visible(&bounds) {
+            return;
+        }
+        let (color, sampling) =
+            read_entity(&self.state, |s| (s.color, s.sampling)).unwrap_or((colors::BLACK, false));
+
+        // Swatch, with a border so light colors stand out
+        let swatch = Rect::from_pos_size(bounds.pos, Vec2::splat(HEIGHT));
+        ctx.paint_quad(PaintQuad {
+            bounds: swatch,
+            fill: color,
+            corner_radii: Corners::all(4.0),
+            border_widths: Edges::all(1.0),
+            border_color: colors::GRAY_300,
+        });
+
+        ctx.paint_text(PaintText {
+            position: Vec2::new(
+                swatch.pos.x + HEIGHT + GAP,
+                bounds.pos.y + (HEIGHT - self.text_style.size) / 2.0,
+            ),
+            text: hex_label(color),
+            style: self.text_style.clone(),
+            measured_size: None,
+        });
+
+        // Eyedropper button
+        let button = Rect::from_pos_size(
+            Vec2::new(bounds.pos.x + bounds.size.x - HEIGHT, bounds.pos.y),
+            Vec2::splat(HEIGHT),
+        );
+        let state = get_element_state(self.eyedropper_id).unwrap_or_default();
+        let background = if sampling || state.is_pressed {
+            colors::GRAY_300
+        } else if state.is_hovered {
+            colors::GRAY_200
+        } else {
+            colors::TRANSPARENT
+        };
+        ctx.paint_quad(PaintQuad {
+            bounds: button,
+            fill: background,
+            corner_radii: Corners::all(4.0),
+            border_widths: Edges::zero(),
+            border_color: colors::TRANSPARENT,
+        });
+        self.eyedropper.set_color(self.text_style.color);
+        self.eyedropper.paint(
+            Rect::from_pos_size(
+                button.pos + Vec2::splat((HEIGHT - ICON_SIZE) / 2.0),
+                Vec2::splat(ICON_SIZE),
+            ),
+            ctx,
+        );
+
+        register_element(
+            self.eyedropper_id,
+            Rc::new(RefCell::new(self.eyedropper_handlers())),
+        );
+        ctx.register_hit_test(self.eyedropper_id, button, 0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_label() {
+        assert_eq!(hex_label(Color::rgb(1.0, 0.5, 0.0)), "#FF7F00");
+        assert_eq!(hex_label(Color::hex("#12345680")), "#12345680");
+    }
+}
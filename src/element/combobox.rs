@@ -0,0 +1,654 @@
+//! Searchable combobox: a text field with a filtered list of options
+
+use crate::{
+    caret::caret_config,
+    clock::frame_time,
+    color::{Color, ColorExt, colors},
+    element::{
+        Element, LayoutContext, TextInputState,
+        popover::{PopoverPlacement, place_popover},
+    },
+    entity::{Entity, new_entity, read_entity, update_entity},
+    geometry::{Corners, Edges, Rect},
+    interaction::{
+        ElementId, EventHandlers,
+        registry::{get_element_state, register_element},
+    },
+    layer::{Key, MouseButton},
+    render::{PaintContext, PaintQuad, PaintText},
+    style::TextStyle,
+};
+use glam::Vec2;
+use std::cell::RefCell;
+use std::rc::Rc;
+use taffy::prelude::*;
+
+/// State for a combobox, persisted via the Entity system
+#[derive(Debug, Clone, Default)]
+pub struct ComboboxState {
+    /// Text typed into the field
+    pub input: TextInputState,
+    /// Whether the options popover is open
+    pub is_open: bool,
+    /// Highlighted position within the filtered options
+    pub highlighted: Option<usize>,
+    /// Index of the selected option in the full option list
+    pub selected_index: Option<usize>,
+}
+
+impl ComboboxState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open the popover and highlight the first match
+    pub fn open(&mut self) {
+        if !self.is_open {
+            self.is_open = true;
+            self.highlighted = Some(0);
+        }
+    }
+
+    /// Close the popover
+    pub fn close(&mut self) {
+        self.is_open = false;
+        self.highlighted = None;
+    }
+
+    /// Move the highlight by `delta` within `match_count` filtered options
+    pub fn move_highlight(&mut self, delta: isize, match_count: usize) {
+        if match_count == 0 {
+            self.highlighted = None;
+            return;
+        }
+        let current = self.highlighted.unwrap_or(0) as isize;
+        let next = (current + delta).clamp(0, match_count as isize - 1);
+        self.highlighted = Some(next as usize);
+    }
+}
+
+/// Filter function deciding whether an option label matches the query
+pub type ComboboxFilter = Rc<dyn Fn(&str, &str) -> bool>;
+
+/// Default filter: case-insensitive substring match
+fn default_filter(label: &str, query: &str) -> bool {
+    label.to_lowercase().contains(&query.to_lowercase())
+}
+
+/// Indices of options whose labels match the query
+///
+/// An empty query matches every option.
+pub fn filter_options(
+    labels: &[String],
+    query: &str,
+    filter: &dyn Fn(&str, &str) -> bool,
+) -> Vec<usize> {
+    labels
+        .iter()
+        .enumerate()
+        .filter(|(_, label)| query.is_empty() || filter(label, query))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Called with the index and option selected in a combobox
+type ChangeCallback<T> = Rc<RefCell<Box<dyn FnMut(usize, &T)>>>;
+
+/// Create a new combobox with the given options
+pub fn combobox<T: ToString + Clone + 'static>(options: Vec<T>) -> Combobox<T> {
+    Combobox::new(options)
+}
+
+/// A text field that filters a popover list of options as you type
+///
+/// Typing filters the options, Up/Down move the highlight, Enter selects,
+/// and Escape dismisses the list.
+///
+/// # Example
+/// ```ignore
+/// combobox(vec!["Apple", "Banana", "Cherry"])
+///     .with_key("fruit")
+///     .placeholder("Pick a fruit")
+///     .on_change(|index, fruit| println!("Picked {} ({})", fruit, index))
+/// ```
+pub struct Combobox<T: ToString + Clone + 'static> {
+    /// The options available for selection
+    options: Rc<Vec<T>>,
+    /// Display labels for the options
+    labels: Rc<Vec<String>>,
+    /// Placeholder text when the field is empty
+    placeholder: String,
+    /// Element ID for the text field
+    element_id: ElementId,
+    /// Base element ID for option rows
+    options_element_id: ElementId,
+    /// Persistent state entity
+    state: Option<Entity<ComboboxState>>,
+    /// On change callback
+    on_change: Option<ChangeCallback<T>>,
+    /// Option filter
+    filter: ComboboxFilter,
+
+    // Styling
+    /// Width of the combobox
+    width: f32,
+    /// Maximum number of options shown at once
+    max_visible_options: usize,
+    /// Background color
+    background: Color,
+    /// Border color
+    border_color: Color,
+    /// Border color when focused
+    focus_border_color: Color,
+    /// Border width
+    border_width: f32,
+    /// Corner radius
+    corner_radius: f32,
+    /// Text style
+    text_style: TextStyle,
+    /// Placeholder color
+    placeholder_color: Color,
+    /// Background for the highlighted option
+    highlight_background: Color,
+    /// Horizontal padding
+    padding_h: f32,
+    /// Vertical padding
+    padding_v: f32,
+
+    /// Whether the combobox is disabled
+    disabled: bool,
+
+    /// Cached layout node
+    node_id: Option<NodeId>,
+}
+
+impl<T: ToString + Clone + 'static> Combobox<T> {
+    /// Create a new combobox
+    ///
+    /// Note: For stable interaction, call `.with_key()` to set a unique
+    /// identifier that persists across frames.
+    #[allow(deprecated)]
+    pub fn new(options: Vec<T>) -> Self {
+        let labels = options.iter().map(|o| o.to_string()).collect();
+        Self {
+            options: Rc::new(options),
+            labels: Rc::new(labels),
+            placeholder: String::new(),
+            element_id: ElementId::auto(),
+            options_element_id: ElementId::auto(),
+            state: None,
+            on_change: None,
+            filter: Rc::new(default_filter),
+            width: 200.0,
+            max_visible_options: 8,
+            background: colors::WHITE,
+            border_color: colors::GRAY_300,
+            focus_border_color: colors::BLUE_500,
+            border_width: 1.0,
+            corner_radius: 4.0,
+            text_style: TextStyle {
+                size: 14.0,
+                color: colors::BLACK,
+                ..Default::default()
+            },
+            placeholder_color: colors::GRAY_400,
+            highlight_background: colors::BLUE_400.with_alpha(0.2),
+            padding_h: 12.0,
+            padding_v: 8.0,
+            disabled: false,
+            node_id: None,
+        }
+    }
+
+    /// Set a unique key for this combobox
+    pub fn with_key(mut self, key: impl AsRef<str>) -> Self {
+        let key = key.as_ref();
+        self.element_id = ElementId::stable(format!("combobox:{}", key));
+        self.options_element_id = ElementId::stable(format!("combobox-options:{}", key));
+        self
+    }
+
+    /// Bind to a persistent state entity
+    pub fn state(mut self, state: Entity<ComboboxState>) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Set the placeholder text
+    pub fn placeholder(mut self, text: impl Into<String>) -> Self {
+        self.placeholder = text.into();
+        self
+    }
+
+    /// Set a custom filter `(label, query) -> bool`
+    pub fn filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&str, &str) -> bool + 'static,
+    {
+        self.filter = Rc::new(filter);
+        self
+    }
+
+    /// Set the on_change callback, called with the chosen option
+    pub fn on_change<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(usize, &T) + 'static,
+    {
+        self.on_change = Some(Rc::new(RefCell::new(Box::new(handler))));
+        self
+    }
+
+    /// Set the width
+    pub fn width(mut self, width: f32) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Set the maximum number of options visible in the popover
+    pub fn max_visible_options(mut self, count: usize) -> Self {
+        self.max_visible_options = count.max(1);
+        self
+    }
+
+    /// Set background color
+    pub fn background(mut self, color: Color) -> Self {
+        self.background = color;
+        self
+    }
+
+    /// Set border
+    pub fn border(mut self, color: Color, width: f32) -> Self {
+        self.border_color = color;
+        self.border_width = width;
+        self
+    }
+
+    /// Set corner radius
+    pub fn corner_radius(mut self, radius: f32) -> Self {
+        self.corner_radius = radius;
+        self
+    }
+
+    /// Set text style
+    pub fn text_style(mut self, style: TextStyle) -> Self {
+        self.text_style = style;
+        self
+    }
+
+    /// Set disabled state
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+
+    /// Get the current state
+    fn get_state(&self) -> ComboboxState {
+        self.state
+            .as_ref()
+            .and_then(|s| read_entity(s, |state| state.clone()))
+            .unwrap_or_default()
+    }
+
+    /// ID of the option row at `index` in the full option list
+    fn option_id(&self, index: usize) -> ElementId {
+        ElementId::new(self.options_element_id.0.wrapping_add(index as u64 + 1))
+    }
+
+    /// Register keyboard and focus handlers for the text field
+    fn register_field_handlers(&self, state_entity: Entity<ComboboxState>) {
+        let labels = self.labels.clone();
+        let options = self.options.clone();
+        let filter = self.filter.clone();
+        let on_change = self.on_change.clone();
+
+        let handlers = EventHandlers::new()
+            .on_click({
+                let state = state_entity.clone();
+                move |button, _, _, _, _| {
+                    if button == MouseButton::Left {
+                        update_entity(&state, |s| s.open());
+                    }
+                }
+            })
+            .on_key_down({
+                let state = state_entity.clone();
+                move |key, modifiers, character, _is_repeat| {
+                    let mut chosen = None;
+
+                    update_entity(&state, |s| {
+                        s.input.last_activity = frame_time();
+
+                        let matches = filter_options(&labels, &s.input.text, filter.as_ref());
+
+                        match key {
+                            Key::Escape => s.close(),
+                            Key::Up => {
+                                s.open();
+                                s.move_highlight(-1, matches.len());
+                            }
+                            Key::Down => {
+                                if s.is_open {
+                                    s.move_highlight(1, matches.len());
+                                } else {
+                                    s.open();
+                                }
+                            }
+                            Key::Return => {
+                                if s.is_open {
+                                    if let Some(&index) = s.highlighted.and_then(|h| matches.get(h))
+                                    {
+                                        s.input = TextInputState::with_text(labels[index].clone());
+                                        s.selected_index = Some(index);
+                                        chosen = Some(index);
+                                    }
+                                    s.close();
+                                }
+                            }
+                            Key::Backspace => {
+                                s.input.backspace();
+                                s.open();
+                                s.highlighted = Some(0);
+                            }
+                            Key::Delete => {
+                                s.input.delete();
+                                s.open();
+                                s.highlighted = Some(0);
+                            }
+                            Key::Left => s.input.move_left(modifiers.shift),
+                            Key::Right => s.input.move_right(modifiers.shift),
+                            Key::Home => s.input.move_to_start(modifiers.shift),
+                            Key::End => s.input.move_to_end(modifiers.shift),
+                            Key::A if modifiers.cmd => s.input.select_all(),
+                            _ => {
+                                if let Some(c) = character
+                                    && !modifiers.cmd
+                                    && !modifiers.ctrl
+                                    && !c.is_control()
+                                {
+                                    s.input.insert(&c.to_string());
+                                    s.open();
+                                    s.highlighted = Some(0);
+                                }
+                            }
+                        }
+                    });
+
+                    if let (Some(index), Some(handler)) = (chosen, &on_change) {
+                        (handler.borrow_mut())(index, &options[index]);
+                    }
+                }
+            })
+            .on_focus_out({
+                let state = state_entity;
+                move || {
+                    update_entity(&state, |s| s.close());
+                }
+            });
+
+        register_element(self.element_id, Rc::new(RefCell::new(handlers)));
+    }
+
+    /// Register a click handler for one option row
+    fn register_option_handler(&self, index: usize, state_entity: Entity<ComboboxState>) {
+        let label = self.labels[index].clone();
+        let options = self.options.clone();
+        let on_change = self.on_change.clone();
+
+        let handlers = EventHandlers::new().on_click(move |button, _, _, _, _| {
+            if button != MouseButton::Left {
+                return;
+            }
+            update_entity(&state_entity, |s| {
+                s.input = TextInputState::with_text(label.clone());
+                s.selected_index = Some(index);
+                s.close();
+            });
+            if let Some(handler) = &on_change {
+                (handler.borrow_mut())(index, &options[index]);
+            }
+        });
+
+        register_element(self.option_id(index), Rc::new(RefCell::new(handlers)));
+    }
+
+    /// Paint the text field
+    fn paint_field(&self, bounds: Rect, ctx: &mut PaintContext, state: &ComboboxState) {
+        let is_focused = get_element_state(self.element_id)
+            .unwrap_or_default()
+            .is_focused;
+
+        ctx.paint_quad(PaintQuad {
+            bounds,
+            fill: if self.disabled {
+                colors::GRAY_100
+            } else {
+                self.background
+            },
+            corner_radii: Corners::all(self.corner_radius),
+            border_widths: Edges::all(self.border_width),
+            border_color: if is_focused && !self.disabled {
+                self.focus_border_color
+            } else {
+                self.border_color
+            },
+        });
+
+        let text = &state.input.text;
+        let (display_text, color) = if text.is_empty() {
+            (self.placeholder.clone(), self.placeholder_color)
+        } else if self.disabled {
+            (text.clone(), colors::GRAY_500)
+        } else {
+            (text.clone(), self.text_style.color)
+        };
+
+        let text_x = bounds.pos.x + self.padding_h;
+        let text_y = bounds.pos.y + (bounds.size.y - self.text_style.size) / 2.0;
+
+        ctx.paint_text(PaintText {
+            position: Vec2::new(text_x, text_y),
+            text: display_text,
+            style: TextStyle {
+                color,
+                ..self.text_style.clone()
+            },
+            measured_size: None,
+        });
+
+        // Caret
+        if is_focused && !self.disabled {
+            let before_cursor = &text[..state.input.cursor.min(text.len())];
+            let cursor_x = if before_cursor.is_empty() {
+                0.0
+            } else {
+                let config = crate::text_system::TextConfig::from(&self.text_style);
+                ctx.text_system
+                    .measure_text(before_cursor, &config, None, ctx.scale_factor)
+                    .x
+            };
+            ctx.paint_caret(
+                Rect::from_pos_size(
+                    Vec2::new(text_x + cursor_x, bounds.pos.y + self.padding_v),
+                    Vec2::new(caret_config().width, bounds.size.y - self.padding_v * 2.0),
+                ),
+                Some(self.text_style.color),
+                state.input.last_activity,
+            );
+        }
+    }
+
+    /// Paint the filtered options popover
+    fn paint_options(
+        &self,
+        field_bounds: Rect,
+        ctx: &mut PaintContext,
+        state: &ComboboxState,
+        state_entity: &Entity<ComboboxState>,
+    ) {
+        let matches = filter_options(&self.labels, &state.input.text, self.filter.as_ref());
+        let option_height = self.text_style.size + self.padding_v * 2.0;
+
+        let visible = matches.len().min(self.max_visible_options);
+        // Keep the highlighted option in view
+        let first = state
+            .highlighted
+            .map(|h| (h + 1).saturating_sub(visible))
+            .unwrap_or(0);
+        let rows = visible.max(1);
+
+        let list_bounds = place_popover(
+            field_bounds,
+            Vec2::new(field_bounds.size.x, rows as f32 * option_height),
+            *ctx.draw_list.viewport(),
+            PopoverPlacement::Below,
+            2.0,
+        );
+
+        ctx.paint_quad(PaintQuad {
+            bounds: list_bounds,
+            fill: colors::WHITE,
+            corner_radii: Corners::all(self.corner_radius),
+            border_widths: Edges::all(1.0),
+            border_color: colors::GRAY_200,
+        });
+
+        if matches.is_empty() {
+            ctx.paint_text(PaintText {
+                position: list_bounds.pos + Vec2::new(self.padding_h, self.padding_v),
+                text: "No matches".to_string(),
+                style: TextStyle {
+                    color: colors::GRAY_500,
+                    ..self.text_style.clone()
+                },
+                measured_size: None,
+            });
+        }
+
+        for (row, (position, &index)) in matches
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(visible)
+            .enumerate()
+        {
+            let option_bounds = Rect::from_pos_size(
+                Vec2::new(
+                    list_bounds.pos.x,
+                    list_bounds.pos.y + row as f32 * option_height,
+                ),
+                Vec2::new(list_bounds.size.x, option_height),
+            );
+
+            let is_hovered = get_element_state(self.option_id(index))
+                .unwrap_or_default()
+                .is_hovered;
+            if state.highlighted == Some(position) || is_hovered {
+                ctx.paint_quad(PaintQuad::filled(option_bounds, self.highlight_background));
+            }
+
+            ctx.paint_text(PaintText {
+                position: option_bounds.pos + Vec2::new(self.padding_h, self.padding_v),
+                text: self.labels[index].clone(),
+                style: self.text_style.clone(),
+                measured_size: None,
+            });
+
+            self.register_option_handler(index, state_entity.clone());
+            ctx.register_hit_test(self.option_id(index), option_bounds, 100);
+        }
+
+        // Swallow clicks on the popover background
+        ctx.register_hit_test(self.options_element_id, list_bounds, 99);
+    }
+}
+
+impl<T: ToString + Clone + 'static> Element for Combobox<T> {
+    fn layout(&mut self, ctx: &mut LayoutContext) -> NodeId {
+        // Initialize state if needed
+        if self.state.is_none() {
+            self.state = Some(new_entity(ComboboxState::new()));
+        }
+
+        let height = self.text_style.size + self.padding_v * 2.0;
+        let style = Style {
+            size: Size {
+                width: Dimension::length(self.width),
+                height: Dimension::length(height),
+            },
+            ..Default::default()
+        };
+
+        let node_id = ctx.request_layout(style);
+        self.node_id = Some(node_id);
+        node_id
+    }
+
+    fn paint(&mut self, bounds: Rect, ctx: &mut PaintContext) {
+        if !ctx.is_visible(&bounds) {
+            return;
+        }
+
+        let Some(state_entity) = self.state.clone() else {
+            return;
+        };
+        let state = self.get_state();
+
+        self.paint_field(bounds, ctx, &state);
+
+        if self.disabled {
+            return;
+        }
+
+        self.register_field_handlers(state_entity.clone());
+        ctx.register_focusable(self.element_id, bounds, 0);
+
+        if state.is_open {
+            self.paint_options(bounds, ctx, &state, &state_entity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels() -> Vec<String> {
+        ["Apple", "Apricot", "Banana", "Cherry"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_filter_options_case_insensitive() {
+        let labels = labels();
+        assert_eq!(filter_options(&labels, "ap", &default_filter), vec![0, 1]);
+        assert_eq!(filter_options(&labels, "AN", &default_filter), vec![2]);
+        assert!(filter_options(&labels, "kiwi", &default_filter).is_empty());
+    }
+
+    #[test]
+    fn test_empty_query_matches_everything() {
+        let labels = labels();
+        assert_eq!(filter_options(&labels, "", &default_filter).len(), 4);
+    }
+
+    #[test]
+    fn test_custom_filter() {
+        let labels = labels();
+        let prefix = |label: &str, query: &str| label.starts_with(query);
+        assert_eq!(filter_options(&labels, "Ch", &prefix), vec![3]);
+    }
+
+    #[test]
+    fn test_move_highlight_clamps() {
+        let mut state = ComboboxState::new();
+        state.open();
+        state.move_highlight(-1, 3);
+        assert_eq!(state.highlighted, Some(0));
+        state.move_highlight(5, 3);
+        assert_eq!(state.highlighted, Some(2));
+        state.move_highlight(1, 0);
+        assert_eq!(state.highlighted, None);
+    }
+}
@@ -1,10 +1,25 @@
 use crate::{
+    clock::frame_time,
     color::Color,
-    element::{Element, LayoutContext, PaintContext},
+    element::{
+        Element, LayoutContext, PaintContext, ScrollAlignment, ScrollContainer, ScrollHandle,
+        ScrollState,
+        reorder::{
+            REORDER_DRAG_Z, REORDER_PREVIEW_Z, ReorderCallback, ReorderState, along, axis_vec,
+            drag_handler, press_handler, release_handler,
+        },
+        request_scroll_into_view,
+    },
+    entity::{Entity, read_entity, update_entity},
     geometry::{Corners, Edges, Rect},
+    interaction::{ElementId, EventHandlers, current_style_state, registry::register_element},
     layout_id::LayoutId,
     render::PaintQuad,
+    style::{Outline, Overflow, StyleStates},
+    vibrancy::Material,
 };
+use std::cell::RefCell;
+use std::rc::Rc;
 use taffy::prelude::*;
 
 /// Create a new container element.
@@ -125,16 +140,36 @@ pub fn column() -> Container {
 /// | `justify_*` | Main axis alignment |
 /// | `items_*` | Cross axis alignment |
 /// | `flex_grow(f32)` | Grow factor when extra space |
+///
+/// # Overflow
+///
+/// Children paint outside the container's bounds unless
+/// [`overflow`](Self::overflow) clips or scrolls them.
 pub struct Container {
     style: Style,
     background: Option<Color>,
+    material: Option<Material>,
+    /// Blur radius for the container's own content
+    blur: Option<f32>,
+    /// Blur radius for what's painted below the container
+    backdrop_blur: Option<f32>,
     border_color: Option<Color>,
     border_width: f32,
     corner_radius: f32,
+    outline: Option<Outline>,
+    states: StyleStates,
     children: Vec<Box<dyn Element>>,
     child_nodes: Vec<NodeId>,
     /// Stable layout ID for caching across frames
     layout_id: Option<LayoutId>,
+    overflow: Overflow,
+    /// Scroll position for `Overflow::Scroll`
+    scroll_state: Option<Entity<ScrollState>>,
+    /// Scroll container holding the children for `Overflow::Scroll`
+    scroll: Option<ScrollContainer>,
+    /// Drag state for reordering the children, and the callback when a
+    /// child is dragged to a new position
+    reorder: Option<(Entity<ReorderState>, ReorderCallback)>,
 }
 
 impl Container {
@@ -142,12 +177,21 @@ impl Container {
         Self {
             style: Style::default(),
             background: None,
+            material: None,
+            blur: None,
+            backdrop_blur: None,
             border_color: None,
             border_width: 0.0,
             corner_radius: 0.0,
+            outline: None,
+            states: StyleStates::default(),
             children: Vec::new(),
             child_nodes: Vec::new(),
             layout_id: None,
+            overflow: Overflow::Visible,
+            scroll_state: None,
+            scroll: None,
+            reorder: None,
         }
     }
 
@@ -173,6 +217,38 @@ impl Container {
         self
     }
 
+    /// Show a native window material, such as a sidebar's translucent
+    /// blur, behind the container
+    ///
+    /// Anything painted below the container, including lower layers, is
+    /// hidden inside its bounds. A [`background`](Self::background) color
+    /// tints the material.
+    pub fn background_material(mut self, material: Material) -> Self {
+        self.material = Some(material);
+        self
+    }
+
+    /// Blur the container's background and children by `radius` points
+    ///
+    /// The content is rendered offscreen and blurred as a whole, so the
+    /// radius is the blur's standard deviation, as for CSS `blur()`.
+    /// Children painting far outside the container are cut off.
+    pub fn blur(mut self, radius: f32) -> Self {
+        self.blur = Some(radius);
+        self
+    }
+
+    /// Blur whatever is painted below the container, within its bounds
+    ///
+    /// Gives the frosted glass look of sheets and popovers. Pair it with a
+    /// translucent [`background`](Self::background) to tint the blur.
+    /// Unlike [`background_material`](Self::background_material) this blurs
+    /// the app's own content, not the desktop behind the window.
+    pub fn backdrop_blur(mut self, radius: f32) -> Self {
+        self.backdrop_blur = Some(radius);
+        self
+    }
+
     /// Set the border
     pub fn border(mut self, color: Color, width: f32) -> Self {
         self.border_color = Some(color);
@@ -186,12 +262,129 @@ impl Container {
         self
     }
 
+    /// Draw a ring outside the container, without affecting layout
+    ///
+    /// The ring follows the [`corner_radius`](Self::corner_radius) and is
+    /// drawn above the children.
+    pub fn outline(mut self, outline: Outline) -> Self {
+        self.outline = Some(outline);
+        self
+    }
+
+    /// Set background, border colors and outline per interaction state
+    ///
+    /// The state comes from the nearest enclosing interactive element. State
+    /// colors override [`background`](Self::background) and the border
+    /// color; the border width still comes from [`border`](Self::border).
+    pub fn states(mut self, states: StyleStates) -> Self {
+        self.states = states;
+        self
+    }
+
     /// Add a child element
     pub fn child(mut self, child: impl Element + 'static) -> Self {
         self.children.push(Box::new(child));
         self
     }
 
+    /// Set what happens to children that don't fit
+    ///
+    /// `Clip` cuts them off at the container's bounds, following its corner
+    /// radius. `Scroll` puts them in a [`ScrollContainer`]; give it a
+    /// [`scroll_state`](Self::scroll_state) to keep the position across
+    /// frames.
+    pub fn overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        // Hidden overflow also stops the content from setting a minimum size
+        let taffy_overflow = match overflow {
+            Overflow::Visible => taffy::Overflow::Visible,
+            Overflow::Clip | Overflow::Scroll => taffy::Overflow::Hidden,
+        };
+        self.style.overflow = taffy::Point {
+            x: taffy_overflow,
+            y: taffy_overflow,
+        };
+        self
+    }
+
+    /// Set the scroll position entity used with `Overflow::Scroll`
+    pub fn scroll_state(mut self, state: Entity<ScrollState>) -> Self {
+        self.scroll_state = Some(state);
+        self
+    }
+
+    /// Control the scroll position used with `Overflow::Scroll` through a
+    /// [`ScrollHandle`]
+    pub fn scroll_handle(mut self, handle: &ScrollHandle) -> Self {
+        self.scroll_state = Some(handle.state());
+        self
+    }
+
+    /// Let the children be dragged to new positions
+    ///
+    /// `on_move` gets `(from, to)` when a child is dropped somewhere new; the
+    /// app should move child `from` so it ends up at index `to`. Children
+    /// that handle mouse presses themselves can't be dragged by them. The
+    /// drag lives in `state`, which must be kept across frames for a drag to
+    /// last longer than one.
+    ///
+    /// Rows and columns are supported, but not their reversed directions,
+    /// whose children can't be dragged.
+    pub fn reorderable<F>(mut self, state: Entity<ReorderState>, on_move: F) -> Self
+    where
+        F: FnMut(usize, usize) + 'static,
+    {
+        self.reorder = Some((state, Rc::new(RefCell::new(Box::new(on_move)))));
+        self
+    }
+
+    /// Move the children into a scroll container that takes this
+    /// container's place in its parent's layout
+    fn build_scroll(&mut self) -> ScrollContainer {
+        let defaults = Style::default();
+        // Sizing and placement go on the viewport, everything else on the
+        // content inside it
+        let viewport = Style {
+            size: self.style.size,
+            min_size: self.style.min_size,
+            max_size: self.style.max_size,
+            margin: self.style.margin,
+            position: self.style.position,
+            inset: self.style.inset,
+            flex_grow: self.style.flex_grow,
+            flex_shrink: self.style.flex_shrink,
+            flex_basis: self.style.flex_basis,
+            align_self: self.style.align_self,
+            ..defaults.clone()
+        };
+        let mut content = Container::new();
+        content.style = Style {
+            size: defaults.size,
+            min_size: defaults.min_size,
+            max_size: defaults.max_size,
+            margin: defaults.margin,
+            position: defaults.position,
+            inset: defaults.inset,
+            flex_grow: defaults.flex_grow,
+            flex_shrink: defaults.flex_shrink,
+            flex_basis: defaults.flex_basis,
+            align_self: defaults.align_self,
+            overflow: defaults.overflow,
+            ..self.style.clone()
+        };
+        content.children = std::mem::take(&mut self.children);
+        content.reorder = self.reorder.take();
+
+        let mut scroll = ScrollContainer::new().with_style(viewport);
+        if let Some(state) = self.scroll_state.clone() {
+            scroll = scroll.state(state);
+        }
+        if let Some(layout_id) = self.layout_id.clone() {
+            scroll = scroll.layout_id(layout_id);
+        }
+        scroll.child(content)
+    }
+
     /// Set display to flex
     pub fn flex(mut self) -> Self {
         self.style.display = Display::Flex;
@@ -524,6 +717,14 @@ impl Container {
 
 impl Element for Container {
     fn layout(&mut self, ctx: &mut LayoutContext) -> NodeId {
+        if self.overflow == Overflow::Scroll {
+            let scroll = match self.scroll.take() {
+                Some(scroll) => scroll,
+                None => self.build_scroll(),
+            };
+            return self.scroll.insert(scroll).layout(ctx);
+        }
+
         // Layout all children first
         self.child_nodes.clear();
         for child in &mut self.children {
@@ -555,30 +756,266 @@ impl Element for Container {
             return;
         }
 
+        let blur = self.blur.filter(|radius| *radius > 0.0);
+        if let Some(radius) = blur {
+            ctx.draw_list.push_blur(bounds, radius);
+        }
+        self.paint_content(bounds, ctx);
+        if blur.is_some() {
+            ctx.draw_list.pop_blur();
+        }
+    }
+}
+
+impl Container {
+    /// Paint the background, borders and children
+    fn paint_content(&mut self, bounds: Rect, ctx: &mut PaintContext) {
+        let state = current_style_state();
+        let colors = self.states.resolve(state);
+        let background = colors.bg.or(self.background);
+        let border_color = colors.border.or(self.border_color);
+        let outline = colors.outline.or(self.outline);
+
+        if let Some(material) = self.material {
+            ctx.paint_material(bounds, material, self.corner_radius);
+        }
+
+        if let Some(radius) = self.backdrop_blur {
+            ctx.paint_backdrop_blur(bounds, radius, Corners::all(self.corner_radius));
+        }
+
         // Paint background and borders
-        if self.background.is_some() || self.border_color.is_some() {
+        if background.is_some() || border_color.is_some() {
+            let transparent = crate::color::colors::TRANSPARENT;
             ctx.paint_quad(PaintQuad {
                 bounds,
-                fill: self.background.unwrap_or(crate::color::colors::TRANSPARENT),
+                fill: self
+                    .states
+                    .apply_opacity(background.unwrap_or(transparent), state),
                 corner_radii: Corners::all(self.corner_radius),
                 border_widths: Edges::all(self.border_width),
                 border_color: self
-                    .border_color
-                    .unwrap_or(crate::color::colors::TRANSPARENT),
+                    .states
+                    .apply_opacity(border_color.unwrap_or(transparent), state),
             });
         }
 
-        // Paint children with their computed bounds relative to this container
-        for (child, &child_node) in self.children.iter_mut().zip(&self.child_nodes) {
-            // Get child's layout bounds (relative to parent)
-            let child_layout_bounds = ctx.layout_engine.layout_bounds(child_node);
-            // Convert to absolute bounds for painting
-            let child_absolute_bounds = Rect::from_pos_size(
-                bounds.pos + child_layout_bounds.pos,
-                child_layout_bounds.size,
+        if let Some(scroll) = self.scroll.as_mut() {
+            scroll.paint(bounds, ctx);
+        } else {
+            self.paint_children(bounds, ctx);
+        }
+
+        if let Some(outline) = outline {
+            let color = self.states.apply_opacity(outline.color, state);
+            ctx.paint_outline(
+                bounds,
+                Corners::all(self.corner_radius),
+                Outline { color, ..outline },
             );
+        }
+    }
+
+    /// Paint the children, clipping them if the overflow asks for it
+    fn paint_children(&mut self, bounds: Rect, ctx: &mut PaintContext) {
+        let clip = self.overflow == Overflow::Clip;
+        if clip {
+            if self.corner_radius > 0.0 {
+                ctx.draw_list
+                    .push_rounded_clip(bounds, Corners::all(self.corner_radius));
+            } else {
+                ctx.draw_list.push_clip(bounds);
+            }
+        }
 
-            child.paint(child_absolute_bounds, ctx);
+        let reorder = self.reorder.clone().filter(|_| {
+            matches!(
+                self.style.flex_direction,
+                FlexDirection::Row | FlexDirection::Column
+            )
+        });
+        if let Some((state, on_move)) = reorder {
+            self.paint_reorderable(bounds, state, on_move, ctx);
+        } else {
+            // Paint children with their computed bounds relative to this container
+            for (child, &child_node) in self.children.iter_mut().zip(&self.child_nodes) {
+                // Get child's layout bounds (relative to parent)
+                let child_layout_bounds = ctx.layout_engine.layout_bounds(child_node);
+                // Convert to absolute bounds for painting
+                let child_absolute_bounds = Rect::from_pos_size(
+                    bounds.pos + child_layout_bounds.pos,
+                    child_layout_bounds.size,
+                );
+
+                child.paint(child_absolute_bounds, ctx);
+            }
+        }
+
+        if clip {
+            ctx.draw_list.pop_clip();
+        }
+    }
+
+    /// Paint the children around a gap for the one being dragged, which
+    /// floats under the pointer, and let each child be pressed to drag it
+    fn paint_reorderable(
+        &mut self,
+        bounds: Rect,
+        state: Entity<ReorderState>,
+        on_move: ReorderCallback,
+        ctx: &mut PaintContext,
+    ) {
+        let horizontal = self.style.flex_direction == FlexDirection::Row;
+        let transform = ctx.draw_list.current_transform();
+        let child_bounds: Vec<Rect> = self
+            .child_nodes
+            .iter()
+            .map(|&child_node| {
+                let child_layout_bounds = ctx.layout_engine.layout_bounds(child_node);
+                Rect::from_pos_size(
+                    bounds.pos + child_layout_bounds.pos,
+                    child_layout_bounds.size,
+                )
+            })
+            .collect();
+
+        // Ease the gap towards where the dragged child would land
+        let mut moving = false;
+        if read_entity(&state, |s| s.dragging().is_some()).unwrap_or(false) {
+            let items: Vec<(f32, f32)> = child_bounds
+                .iter()
+                .map(|&child| {
+                    let child = transform.apply_rect(child);
+                    (along(child.pos, horizontal), along(child.size, horizontal))
+                })
+                .collect();
+            moving = update_entity(&state, |s| s.step(&items, horizontal, frame_time()))
+                .unwrap_or(false);
+        }
+        let reorder = read_entity(&state, |s| s.clone()).unwrap_or_default();
+        let dragging = reorder.dragging();
+
+        let mut painted = Vec::with_capacity(child_bounds.len());
+        for (index, (child, &child_bounds)) in
+            self.children.iter_mut().zip(&child_bounds).enumerate()
+        {
+            if dragging == Some(index) {
+                painted.push(child_bounds);
+                continue;
+            }
+            let shift = axis_vec(reorder.offset(index) / transform.scale, horizontal);
+            let shifted = Rect::from_pos_size(child_bounds.pos + shift, child_bounds.size);
+            child.paint(shifted, ctx);
+            painted.push(shifted);
+        }
+
+        // The dragged child follows the pointer
+        if let Some((index, origin)) = dragging.zip(reorder.preview_origin()) {
+            let preview = Rect::from_pos_size(transform.apply_inverse(origin), painted[index].size);
+            ctx.draw_list.push_z_index(REORDER_PREVIEW_Z);
+            self.children[index].paint(preview, ctx);
+            ctx.draw_list.pop_z_index();
+            painted[index] = preview;
+        }
+
+        // Register after the children so their own handlers win
+        let reorder_state: fn(&mut ReorderState) -> &mut ReorderState = |s| s;
+        let child_id =
+            |index: usize| ElementId::stable(format!("reorder:{:?}:{}", state.id(), index));
+        for (index, &child_bounds) in painted.iter().enumerate() {
+            let handlers = EventHandlers::new()
+                .on_mouse_down(press_handler(state.clone(), reorder_state, index, None))
+                .on_mouse_up(release_handler(
+                    state.clone(),
+                    reorder_state,
+                    on_move.clone(),
+                ));
+            register_element(child_id(index), Rc::new(RefCell::new(handlers)));
+            ctx.register_hit_test(child_id(index), child_bounds, 0);
+        }
+
+        if reorder.is_pressed() {
+            // Follow the pointer over the whole container while a child is pressed
+            let handlers =
+                EventHandlers::new().on_mouse_move(drag_handler(state.clone(), reorder_state));
+            let drag_id = ElementId::stable(format!("reorder-drag:{:?}", state.id()));
+            register_element(drag_id, Rc::new(RefCell::new(handlers)));
+            ctx.register_hit_test(drag_id, bounds, REORDER_DRAG_Z);
+
+            // Scroll when the pointer nears the edge of a clipping container
+            let screen_bounds = transform.apply_rect(bounds);
+            let visible = ctx
+                .draw_list
+                .current_clip()
+                .and_then(|clip| clip.intersect(&screen_bounds))
+                .unwrap_or(screen_bounds);
+            if let Some(next) = reorder.auto_scroll_target(visible, painted.len(), horizontal) {
+                request_scroll_into_view(child_id(next), ScrollAlignment::Nearest);
+                moving = true;
+            }
+        }
+        if moving {
+            ctx.request_animation_frame();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        color::colors,
+        entity::EntityStore,
+        image::RgbaImage,
+        layer::{LayerManager, LayerOptions},
+        renderer::FrameInfo,
+        software_renderer::SoftwareRenderer,
+        text_system::TextSystem,
+    };
+    use glam::Vec2;
+
+    /// Render a 20x20 container of `overflow` holding a 60x60 red child
+    fn render_overflow(overflow: Overflow) -> RgbaImage {
+        let mut layers = LayerManager::new();
+        layers.add_ui_layer(0, LayerOptions::default(), move || {
+            Box::new(
+                container().size(20.0, 20.0).overflow(overflow).child(
+                    container()
+                        .size(60.0, 60.0)
+                        .flex_shrink(0.0)
+                        .background(colors::RED),
+                ),
+            )
+        });
+        let mut target = RgbaImage::new(1, 1);
+        layers.render(
+            &mut SoftwareRenderer::new(),
+            &mut target,
+            FrameInfo {
+                size: Vec2::new(100.0, 100.0),
+                scale_factor: 1.0,
+                elapsed_time: 0.0,
+            },
+            &mut TextSystem::headless(),
+            &mut EntityStore::new(),
+        );
+        target
+    }
+
+    #[test]
+    fn test_overflow_visible_paints_outside_bounds() {
+        let image = render_overflow(Overflow::Visible);
+        assert_eq!(image.pixel(5, 5), Some([255, 0, 0, 255]));
+        assert_eq!(image.pixel(40, 40), Some([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_overflow_clip_and_scroll_clip_children() {
+        for overflow in [Overflow::Clip, Overflow::Scroll] {
+            let image = render_overflow(overflow);
+            assert_eq!(image.pixel(5, 5), Some([255, 0, 0, 255]), "{overflow:?}");
+            assert_ne!(image.pixel(40, 40), Some([255, 0, 0, 255]), "{overflow:?}");
+            assert_ne!(image.pixel(10, 40), Some([255, 0, 0, 255]), "{overflow:?}");
         }
     }
 }
@@ -0,0 +1,962 @@
+//! Date picker element with a popover calendar
+
+use crate::{
+    color::{Color, ColorExt, colors},
+    element::{
+        Element, LayoutContext,
+        popover::{PopoverPlacement, place_popover},
+    },
+    entity::{Entity, new_entity, read_entity, update_entity},
+    geometry::{Corners, Edges, Rect},
+    interaction::{
+        ElementId, EventHandlers,
+        registry::{get_element_state, register_element},
+    },
+    layer::{Key, MouseButton},
+    render::{PaintContext, PaintQuad, PaintText},
+    style::TextStyle,
+};
+use glam::Vec2;
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use taffy::prelude::*;
+
+const MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+const WEEKDAY_LABELS: [&str; 7] = ["Su", "Mo", "Tu", "We", "Th", "Fr", "Sa"];
+
+/// Size of a day cell in the calendar grid
+const CELL_SIZE: f32 = 32.0;
+/// Padding around the calendar grid
+const CALENDAR_PADDING: f32 = 8.0;
+/// Height of the month header row
+const HEADER_HEIGHT: f32 = 32.0;
+/// Height of the weekday label row
+const WEEKDAY_ROW_HEIGHT: f32 = 24.0;
+/// The grid always shows six weeks so the popover size is stable
+const GRID_ROWS: usize = 6;
+
+/// A calendar date without time zone, modeled after chrono's `NaiveDate`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CalendarDate {
+    year: i32,
+    month: u32,
+    day: u32,
+}
+
+impl CalendarDate {
+    /// Create a date, returning `None` if it does not exist
+    pub fn from_ymd_opt(year: i32, month: u32, day: u32) -> Option<Self> {
+        if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+        Some(Self { year, month, day })
+    }
+
+    /// Today's date in the local time zone
+    pub fn today() -> Self {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        Self::from_unix_time(secs, local_utc_offset(secs))
+    }
+
+    /// The date at `secs` since the Unix epoch, in a time zone
+    /// `utc_offset` seconds ahead of UTC
+    pub fn from_unix_time(secs: i64, utc_offset: i64) -> Self {
+        Self::from_days_since_epoch((secs + utc_offset).div_euclid(86_400))
+    }
+
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    /// Month, starting at 1
+    pub fn month(&self) -> u32 {
+        self.month
+    }
+
+    /// Day of the month, starting at 1
+    pub fn day(&self) -> u32 {
+        self.day
+    }
+
+    /// Day of the week, where 0 is Sunday
+    pub fn weekday(&self) -> u32 {
+        // 1970-01-01 was a Thursday
+        (self.days_since_epoch() + 4).rem_euclid(7) as u32
+    }
+
+    /// Number of days since 1970-01-01
+    pub fn days_since_epoch(&self) -> i64 {
+        let year = self.year as i64 - if self.month <= 2 { 1 } else { 0 };
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let month_from_march = (self.month as i64 + 9) % 12;
+        let day_of_year = (153 * month_from_march + 2) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    /// Create a date from the number of days since 1970-01-01
+    pub fn from_days_since_epoch(days: i64) -> Self {
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_from_march = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u32;
+        let month = if month_from_march < 10 {
+            month_from_march + 3
+        } else {
+            month_from_march - 9
+        } as u32;
+        let year = (year_of_era + era * 400) as i32 + if month <= 2 { 1 } else { 0 };
+        Self { year, month, day }
+    }
+
+    /// Add (or subtract) a number of days
+    pub fn add_days(&self, days: i64) -> Self {
+        Self::from_days_since_epoch(self.days_since_epoch() + days)
+    }
+
+    /// Add (or subtract) a number of months, clamping the day to the target month
+    pub fn add_months(&self, months: i32) -> Self {
+        let total = self.year * 12 + self.month as i32 - 1 + months;
+        let year = total.div_euclid(12);
+        let month = total.rem_euclid(12) as u32 + 1;
+        let day = self.day.min(days_in_month(year, month));
+        Self { year, month, day }
+    }
+
+    /// First day of this date's month
+    pub fn first_of_month(&self) -> Self {
+        Self { day: 1, ..*self }
+    }
+}
+
+impl fmt::Display for CalendarDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Seconds the local time zone is ahead of UTC at `secs` since the epoch,
+/// including daylight saving time
+#[cfg(target_os = "macos")]
+fn local_utc_offset(secs: i64) -> i64 {
+    crate::platform::mac::local_utc_offset(secs)
+}
+
+/// Seconds the local time zone is ahead of UTC; off macOS only UTC is known,
+/// so pass today to [`DatePicker::today`] when it matters
+#[cfg(not(target_os = "macos"))]
+fn local_utc_offset(_secs: i64) -> i64 {
+    0
+}
+
+/// Check if a year is a leap year
+pub fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+/// Number of days in a month (1-12)
+pub fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+/// The dates shown in the calendar grid for a month, starting on Sunday
+fn calendar_grid(month_start: CalendarDate) -> Vec<CalendarDate> {
+    let first = month_start.add_days(-(month_start.weekday() as i64));
+    (0..GRID_ROWS * 7)
+        .map(|i| first.add_days(i as i64))
+        .collect()
+}
+
+/// State for a date picker, persisted via the Entity system
+#[derive(Debug, Clone)]
+pub struct DatePickerState {
+    /// Currently selected date
+    pub selected: Option<CalendarDate>,
+    /// Whether the calendar popover is open
+    pub is_open: bool,
+    /// First day of the month shown in the calendar
+    pub view_month: CalendarDate,
+    /// Date highlighted by keyboard navigation
+    pub focused_date: CalendarDate,
+    /// Date the calendar treats as today
+    pub today: CalendarDate,
+}
+
+impl Default for DatePickerState {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl DatePickerState {
+    pub fn new(selected: Option<CalendarDate>) -> Self {
+        Self::with_today(selected, CalendarDate::today())
+    }
+
+    /// Create state treating `today` as today instead of reading the clock
+    pub fn with_today(selected: Option<CalendarDate>, today: CalendarDate) -> Self {
+        let focused_date = selected.unwrap_or(today);
+        Self {
+            selected,
+            is_open: false,
+            view_month: focused_date.first_of_month(),
+            focused_date,
+            today,
+        }
+    }
+
+    /// Open the calendar on the selected date (or today)
+    pub fn open(&mut self) {
+        if !self.is_open {
+            self.is_open = true;
+            self.focus_date(self.selected.unwrap_or(self.today));
+        }
+    }
+
+    /// Close the calendar
+    pub fn close(&mut self) {
+        self.is_open = false;
+    }
+
+    /// Move keyboard focus to a date, switching the visible month if needed
+    pub fn focus_date(&mut self, date: CalendarDate) {
+        self.focused_date = date;
+        self.view_month = date.first_of_month();
+    }
+}
+
+/// Called with the date picked in a date picker
+type ChangeCallback = Rc<RefCell<Box<dyn FnMut(CalendarDate)>>>;
+
+/// Create a new date picker
+pub fn date_picker() -> DatePicker {
+    DatePicker::new()
+}
+
+/// A date field that opens a calendar popover
+///
+/// The calendar supports month navigation, highlights today, and disables
+/// dates outside the `min_date`/`max_date` range. While focused, arrow keys
+/// move by day or week, PageUp/PageDown move by month, Enter selects, and
+/// Escape closes the calendar.
+///
+/// # Example
+/// ```ignore
+/// date_picker()
+///     .with_key("due-date")
+///     .min_date(CalendarDate::today())
+///     .on_change(|date| println!("Due {}", date))
+/// ```
+pub struct DatePicker {
+    /// Element ID for the date field
+    element_id: ElementId,
+    /// Base element ID for the calendar and its cells
+    calendar_element_id: ElementId,
+    /// Persistent state entity
+    state: Option<Entity<DatePickerState>>,
+    /// Initial selection when the state is created by the picker
+    initial: Option<CalendarDate>,
+    /// Earliest selectable date
+    min_date: Option<CalendarDate>,
+    /// Latest selectable date
+    max_date: Option<CalendarDate>,
+    /// Date to treat as today, or `None` to read the clock
+    today: Option<CalendarDate>,
+    /// On change callback
+    on_change: Option<ChangeCallback>,
+    /// Placeholder text when no date is selected
+    placeholder: String,
+
+    // Styling
+    /// Width of the date field
+    width: f32,
+    /// Background color
+    background: Color,
+    /// Border color
+    border_color: Color,
+    /// Border color when focused
+    focus_border_color: Color,
+    /// Corner radius
+    corner_radius: f32,
+    /// Text style
+    text_style: TextStyle,
+    /// Background for the selected date
+    selected_background: Color,
+    /// Background for the keyboard-focused date
+    highlight_background: Color,
+    /// Ring color marking today
+    today_color: Color,
+
+    /// Whether the picker is disabled
+    disabled: bool,
+
+    /// Cached layout node
+    node_id: Option<NodeId>,
+}
+
+impl DatePicker {
+    /// Create a new date picker
+    ///
+    /// Note: For stable interaction, call `.with_key()` to set a unique
+    /// identifier that persists across frames.
+    #[allow(deprecated)]
+    pub fn new() -> Self {
+        Self {
+            element_id: ElementId::auto(),
+            calendar_element_id: ElementId::auto(),
+            state: None,
+            initial: None,
+            min_date: None,
+            max_date: None,
+            today: None,
+            on_change: None,
+            placeholder: "Select date".to_string(),
+            width: 160.0,
+            background: colors::WHITE,
+            border_color: colors::GRAY_300,
+            focus_border_color: colors::BLUE_500,
+            corner_radius: 4.0,
+            text_style: TextStyle {
+                size: 14.0,
+                color: colors::BLACK,
+                ..Default::default()
+            },
+            selected_background: colors::BLUE_500,
+            highlight_background: colors::BLUE_400.with_alpha(0.2),
+            today_color: colors::BLUE_500,
+            disabled: false,
+            node_id: None,
+        }
+    }
+
+    /// Set a unique key for this date picker
+    pub fn with_key(mut self, key: impl AsRef<str>) -> Self {
+        let key = key.as_ref();
+        self.element_id = ElementId::stable(format!("date-picker:{}", key));
+        self.calendar_element_id = ElementId::stable(format!("date-picker-calendar:{}", key));
+        self
+    }
+
+    /// Bind to a persistent state entity
+    pub fn state(mut self, state: Entity<DatePickerState>) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Set the initially selected date
+    pub fn value(mut self, date: CalendarDate) -> Self {
+        self.initial = Some(date);
+        self
+    }
+
+    /// Set the earliest selectable date
+    pub fn min_date(mut self, date: CalendarDate) -> Self {
+        self.min_date = Some(date);
+        self
+    }
+
+    /// Set the latest selectable date
+    pub fn max_date(mut self, date: CalendarDate) -> Self {
+        self.max_date = Some(date);
+        self
+    }
+
+    /// Treat `date` as today instead of reading the system clock
+    pub fn today(mut self, date: CalendarDate) -> Self {
+        self.today = Some(date);
+        self
+    }
+
+    /// Set the on_change callback
+    pub fn on_change<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(CalendarDate) + 'static,
+    {
+        self.on_change = Some(Rc::new(RefCell::new(Box::new(handler))));
+        self
+    }
+
+    /// Set the placeholder text
+    pub fn placeholder(mut self, text: impl Into<String>) -> Self {
+        self.placeholder = text.into();
+        self
+    }
+
+    /// Set the width of the date field
+    pub fn width(mut self, width: f32) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Set background color
+    pub fn background(mut self, color: Color) -> Self {
+        self.background = color;
+        self
+    }
+
+    /// Set border color
+    pub fn border_color(mut self, color: Color) -> Self {
+        self.border_color = color;
+        self
+    }
+
+    /// Set the color used for the selected date
+    pub fn selected_background(mut self, color: Color) -> Self {
+        self.selected_background = color;
+        self
+    }
+
+    /// Set text style
+    pub fn text_style(mut self, style: TextStyle) -> Self {
+        self.text_style = style;
+        self
+    }
+
+    /// Set disabled state
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+
+    /// Get the current state
+    fn get_state(&self) -> DatePickerState {
+        self.state
+            .as_ref()
+            .and_then(|s| read_entity(s, |state| state.clone()))
+            .unwrap_or_default()
+    }
+
+    /// Sub-element ID within the calendar popover
+    fn calendar_id(&self, offset: u64) -> ElementId {
+        ElementId::new(self.calendar_element_id.0.wrapping_add(offset))
+    }
+
+    /// Register click, keyboard, and focus handlers for the date field
+    fn register_field_handlers(&self, state_entity: Entity<DatePickerState>) {
+        let (min_date, max_date) = (self.min_date, self.max_date);
+        let on_change = self.on_change.clone();
+
+        let handlers = EventHandlers::new()
+            .on_click({
+                let state = state_entity.clone();
+                move |button, _, _, _, _| {
+                    if button == MouseButton::Left {
+                        update_entity(&state, |s| {
+                            if s.is_open {
+                                s.close();
+                            } else {
+                                s.open();
+                            }
+                        });
+                    }
+                }
+            })
+            .on_key_down({
+                let state = state_entity.clone();
+                move |key, modifiers, _character, _is_repeat| {
+                    let mut chosen = None;
+
+                    update_entity(&state, |s| {
+                        if !s.is_open {
+                            if matches!(key, Key::Down | Key::Space | Key::Return) {
+                                s.open();
+                            }
+                            return;
+                        }
+
+                        let target = match key {
+                            Key::Left => s.focused_date.add_days(-1),
+                            Key::Right => s.focused_date.add_days(1),
+                            Key::Up => s.focused_date.add_days(-7),
+                            Key::Down => s.focused_date.add_days(7),
+                            Key::PageUp if modifiers.shift => s.focused_date.add_months(-12),
+                            Key::PageDown if modifiers.shift => s.focused_date.add_months(12),
+                            Key::PageUp => s.focused_date.add_months(-1),
+                            Key::PageDown => s.focused_date.add_months(1),
+                            Key::Home => s.focused_date.first_of_month(),
+                            Key::End => {
+                                let date = s.focused_date;
+                                date.first_of_month()
+                                    .add_days(days_in_month(date.year(), date.month()) as i64 - 1)
+                            }
+                            Key::Return | Key::Space => {
+                                if in_range(s.focused_date, min_date, max_date) {
+                                    s.selected = Some(s.focused_date);
+                                    chosen = s.selected;
+                                    s.close();
+                                }
+                                return;
+                            }
+                            Key::Escape => {
+                                s.close();
+                                return;
+                            }
+                            _ => return,
+                        };
+
+                        s.focus_date(clamp_date(target, min_date, max_date));
+                    });
+
+                    if let (Some(date), Some(handler)) = (chosen, &on_change) {
+                        (handler.borrow_mut())(date);
+                    }
+                }
+            })
+            .on_focus_out({
+                let state = state_entity;
+                move || {
+                    update_entity(&state, |s| s.close());
+                }
+            });
+
+        register_element(self.element_id, Rc::new(RefCell::new(handlers)));
+    }
+
+    /// Paint the date field
+    fn paint_field(&self, bounds: Rect, ctx: &mut PaintContext, state: &DatePickerState) {
+        let is_focused = get_element_state(self.element_id)
+            .unwrap_or_default()
+            .is_focused;
+
+        ctx.paint_quad(PaintQuad {
+            bounds,
+            fill: if self.disabled {
+                colors::GRAY_100
+            } else {
+                self.background
+            },
+            corner_radii: Corners::all(self.corner_radius),
+            border_widths: Edges::all(1.0),
+            border_color: if (is_focused || state.is_open) && !self.disabled {
+                self.focus_border_color
+            } else {
+                self.border_color
+            },
+        });
+
+        let (text, color) = match state.selected {
+            Some(date) if !self.disabled => (date.to_string(), self.text_style.color),
+            Some(date) => (date.to_string(), colors::GRAY_500),
+            None => (self.placeholder.clone(), colors::GRAY_400),
+        };
+
+        ctx.paint_text(PaintText {
+            position: Vec2::new(
+                bounds.pos.x + 12.0,
+                bounds.pos.y + (bounds.size.y - self.text_style.size) / 2.0,
+            ),
+            text,
+            style: TextStyle {
+                color,
+                ..self.text_style.clone()
+            },
+            measured_size: None,
+        });
+    }
+
+    /// Paint the calendar popover and register its hit targets
+    fn paint_calendar(
+        &self,
+        field_bounds: Rect,
+        ctx: &mut PaintContext,
+        state: &DatePickerState,
+        state_entity: &Entity<DatePickerState>,
+    ) {
+        let size = Vec2::new(
+            CELL_SIZE * 7.0 + CALENDAR_PADDING * 2.0,
+            HEADER_HEIGHT
+                + WEEKDAY_ROW_HEIGHT
+                + CELL_SIZE * GRID_ROWS as f32
+                + CALENDAR_PADDING * 2.0,
+        );
+        let calendar_bounds = place_popover(
+            field_bounds,
+            size,
+            *ctx.draw_list.viewport(),
+            PopoverPlacement::Below,
+            2.0,
+        );
+
+        ctx.paint_quad(PaintQuad {
+            bounds: calendar_bounds,
+            fill: colors::WHITE,
+            corner_radii: Corners::all(self.corner_radius),
+            border_widths: Edges::all(1.0),
+            border_color: colors::GRAY_200,
+        });
+
+        let origin = calendar_bounds.pos + Vec2::splat(CALENDAR_PADDING);
+        let small_text = TextStyle {
+            size: 12.0,
+            color: colors::GRAY_500,
+            ..self.text_style.clone()
+        };
+
+        // Month header with previous/next buttons
+        let month = state.view_month;
+        ctx.paint_text(PaintText {
+            position: origin
+                + Vec2::new(
+                    CELL_SIZE + 8.0,
+                    (HEADER_HEIGHT - self.text_style.size) / 2.0,
+                ),
+            text: format!(
+                "{} {}",
+                MONTH_NAMES[month.month() as usize - 1],
+                month.year()
+            ),
+            style: self.text_style.clone(),
+            measured_size: None,
+        });
+
+        let prev_bounds = Rect::from_pos_size(origin, Vec2::splat(CELL_SIZE));
+        let next_bounds = Rect::from_pos_size(
+            origin + Vec2::new(CELL_SIZE * 6.0, 0.0),
+            Vec2::splat(CELL_SIZE),
+        );
+        for (offset, nav_bounds, label, delta) in
+            [(1, prev_bounds, "‹", -1), (2, next_bounds, "›", 1)]
+        {
+            let id = self.calendar_id(offset);
+            if get_element_state(id).unwrap_or_default().is_hovered {
+                ctx.paint_quad(PaintQuad {
+                    bounds: nav_bounds,
+                    fill: colors::GRAY_100,
+                    corner_radii: Corners::all(4.0),
+                    border_widths: Edges::zero(),
+                    border_color: colors::TRANSPARENT,
+                });
+            }
+            ctx.paint_text(PaintText {
+                position: nav_bounds.pos
+                    + Vec2::new(12.0, (CELL_SIZE - self.text_style.size) / 2.0),
+                text: label.to_string(),
+                style: self.text_style.clone(),
+                measured_size: None,
+            });
+
+            let state = state_entity.clone();
+            let handlers = EventHandlers::new().on_click(move |button, _, _, _, _| {
+                if button == MouseButton::Left {
+                    update_entity(&state, |s| s.view_month = s.view_month.add_months(delta));
+                }
+            });
+            register_element(id, Rc::new(RefCell::new(handlers)));
+            ctx.register_hit_test(id, nav_bounds, 100);
+        }
+
+        // Weekday labels
+        let weekday_y = origin.y + HEADER_HEIGHT;
+        for (column, label) in WEEKDAY_LABELS.iter().enumerate() {
+            ctx.paint_text(PaintText {
+                position: Vec2::new(origin.x + column as f32 * CELL_SIZE + 8.0, weekday_y + 4.0),
+                text: label.to_string(),
+                style: small_text.clone(),
+                measured_size: None,
+            });
+        }
+
+        // Day cells
+        let today = state.today;
+        let grid_y = weekday_y + WEEKDAY_ROW_HEIGHT;
+        for (i, date) in calendar_grid(month).into_iter().enumerate() {
+            let cell_bounds = Rect::from_pos_size(
+                Vec2::new(
+                    origin.x + (i % 7) as f32 * CELL_SIZE,
+                    grid_y + (i / 7) as f32 * CELL_SIZE,
+                ),
+                Vec2::splat(CELL_SIZE),
+            );
+            let id = self.calendar_id(3 + i as u64);
+            let enabled = in_range(date, self.min_date, self.max_date);
+            let is_selected = state.selected == Some(date);
+            let is_hovered = enabled && get_element_state(id).unwrap_or_default().is_hovered;
+            let cell_inner = Rect::from_pos_size(
+                cell_bounds.pos + Vec2::splat(2.0),
+                cell_bounds.size - Vec2::splat(4.0),
+            );
+
+            if is_selected {
+                ctx.paint_quad(PaintQuad::filled(cell_inner, self.selected_background));
+            } else if date == state.focused_date || is_hovered {
+                ctx.paint_quad(PaintQuad::filled(cell_inner, self.highlight_background));
+            }
+            if date == today {
+                ctx.paint_quad(PaintQuad {
+                    bounds: cell_inner,
+                    fill: colors::TRANSPARENT,
+                    corner_radii: Corners::all(4.0),
+                    border_widths: Edges::all(1.0),
+                    border_color: self.today_color,
+                });
+            }
+
+            let color = if is_selected {
+                colors::WHITE
+            } else if !enabled {
+                colors::GRAY_300
+            } else if date.month() != month.month() {
+                colors::GRAY_400
+            } else {
+                self.text_style.color
+            };
+            let label = date.day().to_string();
+            let label_x = if label.len() == 1 { 12.0 } else { 8.0 };
+            ctx.paint_text(PaintText {
+                position: cell_bounds.pos + Vec2::new(label_x, (CELL_SIZE - small_text.size) / 2.0),
+                text: label,
+                style: TextStyle {
+                    color,
+                    ..small_text.clone()
+                },
+                measured_size: None,
+            });
+
+            if enabled {
+                let state = state_entity.clone();
+                let on_change = self.on_change.clone();
+                let handlers = EventHandlers::new().on_click(move |button, _, _, _, _| {
+                    if button != MouseButton::Left {
+                        return;
+                    }
+                    update_entity(&state, |s| {
+                        s.selected = Some(date);
+                        s.focus_date(date);
+                        s.close();
+                    });
+                    if let Some(handler) = &on_change {
+                        (handler.borrow_mut())(date);
+                    }
+                });
+                register_element(id, Rc::new(RefCell::new(handlers)));
+                ctx.register_hit_test(id, cell_bounds, 100);
+            }
+        }
+
+        // Swallow clicks on the popover background
+        ctx.register_hit_test(self.calendar_element_id, calendar_bounds, 99);
+    }
+}
+
+impl Default for DatePicker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Check if a date falls within optional bounds
+fn in_range(date: CalendarDate, min: Option<CalendarDate>, max: Option<CalendarDate>) -> bool {
+    min.is_none_or(|min| date >= min) && max.is_none_or(|max| date <= max)
+}
+
+/// Clamp a date into optional bounds
+fn clamp_date(
+    date: CalendarDate,
+    min: Option<CalendarDate>,
+    max: Option<CalendarDate>,
+) -> CalendarDate {
+    let date = min.map_or(date, |min| date.max(min));
+    max.map_or(date, |max| date.min(max))
+}
+
+impl Element for DatePicker {
+    fn layout(&mut self, ctx: &mut LayoutContext) -> NodeId {
+        // Initialize state if needed
+        if self.state.is_none() {
+            let today = self.today.unwrap_or_else(CalendarDate::today);
+            self.state = Some(new_entity(DatePickerState::with_today(self.initial, today)));
+        } else if let (Some(today), Some(state)) = (self.today, &self.state) {
+            update_entity(state, |s| s.today = today);
+        }
+
+        let style = Style {
+            size: Size {
+                width: Dimension::length(self.width),
+                height: Dimension::length(self.text_style.size + 16.0),
+            },
+            ..Default::default()
+        };
+
+        let node_id = ctx.request_layout(style);
+        self.node_id = Some(node_id);
+        node_id
+    }
+
+    fn paint(&mut self, bounds: Rect, ctx: &mut PaintContext) {
+        if !ctx.is_visible(&bounds) {
+            return;
+        }
+
+        let Some(state_entity) = self.state.clone() else {
+            return;
+        };
+        let state = self.get_state();
+
+        self.paint_field(bounds, ctx, &state);
+
+        if self.disabled {
+            return;
+        }
+
+        self.register_field_handlers(state_entity.clone());
+        ctx.register_focusable(self.element_id, bounds, 0);
+
+        if state.is_open {
+            self.paint_calendar(bounds, ctx, &state, &state_entity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> CalendarDate {
+        CalendarDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_from_ymd_validates() {
+        assert!(CalendarDate::from_ymd_opt(2024, 2, 29).is_some());
+        assert!(CalendarDate::from_ymd_opt(2023, 2, 29).is_none());
+        assert!(CalendarDate::from_ymd_opt(2024, 13, 1).is_none());
+        assert!(CalendarDate::from_ymd_opt(2024, 4, 31).is_none());
+    }
+
+    #[test]
+    fn test_epoch_round_trip() {
+        assert_eq!(date(1970, 1, 1).days_since_epoch(), 0);
+        assert_eq!(date(2000, 3, 1).days_since_epoch(), 11_017);
+        for days in [-800_000, -1, 0, 59, 11_016, 19_782, 400_000] {
+            assert_eq!(
+                CalendarDate::from_days_since_epoch(days).days_since_epoch(),
+                days
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_unix_time_follows_the_utc_offset() {
+        // 2024-03-10 02:00 UTC
+        let secs = date(2024, 3, 10).days_since_epoch() * 86_400 + 2 * 3600;
+        assert_eq!(CalendarDate::from_unix_time(secs, 0), date(2024, 3, 10));
+        // Still the evening before west of UTC
+        assert_eq!(
+            CalendarDate::from_unix_time(secs, -8 * 3600),
+            date(2024, 3, 9)
+        );
+        assert_eq!(
+            CalendarDate::from_unix_time(secs, 13 * 3600),
+            date(2024, 3, 10)
+        );
+        // And already tomorrow far enough east
+        let evening = secs + 20 * 3600;
+        assert_eq!(
+            CalendarDate::from_unix_time(evening, 5 * 3600),
+            date(2024, 3, 11)
+        );
+    }
+
+    #[test]
+    fn test_today_uses_the_local_offset() {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        let offset = local_utc_offset(secs);
+        assert!(offset.abs() <= 14 * 3600);
+        // Allow for midnight passing between the two reads
+        let today = CalendarDate::today();
+        let expected = CalendarDate::from_unix_time(secs, offset);
+        assert!(today == expected || today == expected.add_days(1));
+    }
+
+    #[test]
+    fn test_today_can_be_injected() {
+        let today = date(2031, 7, 4);
+        let mut state = DatePickerState::with_today(None, today);
+        assert_eq!(state.today, today);
+        assert_eq!(state.focused_date, today);
+        assert_eq!(state.view_month, date(2031, 7, 1));
+
+        // Reopening without a selection goes back to the injected today
+        state.focus_date(date(2031, 9, 20));
+        state.open();
+        assert_eq!(state.focused_date, today);
+        assert_eq!(state.view_month, date(2031, 7, 1));
+
+        // A selection still wins over today
+        let selected = date(2030, 1, 2);
+        let state = DatePickerState::with_today(Some(selected), today);
+        assert_eq!(state.focused_date, selected);
+    }
+
+    #[test]
+    fn test_weekday() {
+        assert_eq!(date(1970, 1, 1).weekday(), 4);
+        assert_eq!(date(2024, 1, 1).weekday(), 1);
+        assert_eq!(date(2000, 2, 29).weekday(), 2);
+    }
+
+    #[test]
+    fn test_add_days_and_months() {
+        assert_eq!(date(2023, 12, 31).add_days(1), date(2024, 1, 1));
+        assert_eq!(date(2024, 3, 1).add_days(-1), date(2024, 2, 29));
+        assert_eq!(date(2024, 1, 31).add_months(1), date(2024, 2, 29));
+        assert_eq!(date(2024, 1, 15).add_months(-13), date(2022, 12, 15));
+    }
+
+    #[test]
+    fn test_calendar_grid_starts_on_sunday() {
+        let grid = calendar_grid(date(2024, 5, 1));
+        assert_eq!(grid.len(), 42);
+        assert_eq!(grid[0], date(2024, 4, 28));
+        assert_eq!(grid[0].weekday(), 0);
+        assert_eq!(grid[3], date(2024, 5, 1));
+    }
+
+    #[test]
+    fn test_range_constraints() {
+        let min = Some(date(2024, 5, 10));
+        let max = Some(date(2024, 5, 20));
+        assert!(!in_range(date(2024, 5, 9), min, max));
+        assert!(in_range(date(2024, 5, 10), min, max));
+        assert_eq!(clamp_date(date(2024, 6, 1), min, max), date(2024, 5, 20));
+        assert_eq!(clamp_date(date(2024, 1, 1), None, max), date(2024, 1, 1));
+    }
+
+    #[test]
+    fn test_display_is_iso_8601() {
+        assert_eq!(date(2024, 3, 7).to_string(), "2024-03-07");
+    }
+}
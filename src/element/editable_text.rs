@@ -0,0 +1,481 @@
+//! Click-to-edit text: static text that turns into a field in place
+
+use crate::{
+    caret::caret_config,
+    clock::frame_time,
+    color::{Color, ColorExt, colors},
+    element::{Element, LayoutContext, TextInputState},
+    entity::{Entity, new_entity, read_entity, update_entity},
+    geometry::{Corners, Edges, Rect},
+    interaction::{
+        ElementId, EventHandlers,
+        registry::{get_element_state, register_element, request_focus},
+    },
+    layer::{Key, MouseButton},
+    render::{PaintContext, PaintQuad, PaintText},
+    style::TextStyle,
+    text_system::TextConfig,
+};
+use glam::Vec2;
+use std::cell::RefCell;
+use std::rc::Rc;
+use taffy::prelude::*;
+
+/// State for editable text, persisted via the Entity system
+#[derive(Debug, Clone, Default)]
+pub struct EditableTextState {
+    /// The field while editing, or `None` while showing the text
+    pub editing: Option<TextInputState>,
+}
+
+impl EditableTextState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the field is showing
+    pub fn is_editing(&self) -> bool {
+        self.editing.is_some()
+    }
+
+    /// Show the field with `text` selected, so typing replaces it
+    pub fn start_editing(&mut self, text: &str) {
+        let mut input = TextInputState::with_text(text);
+        input.select_all();
+        self.editing = Some(input);
+    }
+
+    /// Stop editing and return the edited text
+    pub fn commit(&mut self) -> Option<String> {
+        self.editing.take().map(|input| input.text)
+    }
+
+    /// Stop editing and discard the edit
+    pub fn cancel(&mut self) {
+        self.editing = None;
+    }
+}
+
+/// Called with the new text when an edit is committed
+type CommitCallback = Rc<RefCell<Box<dyn FnMut(&str)>>>;
+
+/// Create text that can be edited in place
+pub fn editable_text(text: impl Into<String>) -> EditableText {
+    EditableText::new(text)
+}
+
+/// Text that swaps to a field in place when double-clicked, or when Enter is
+/// pressed while it's focused
+///
+/// Enter or moving focus away commits the edit, and Escape discards it. The
+/// text is drawn in the same place and style in both modes.
+///
+/// # Example
+/// ```ignore
+/// editable_text(&item.name)
+///     .with_key(format!("rename:{}", item.id))
+///     .on_commit(move |name| rename(id, name))
+/// ```
+pub struct EditableText {
+    /// The committed text, shown while not editing
+    text: String,
+    /// Placeholder shown when the text is empty
+    placeholder: Option<String>,
+    /// Element ID for interaction
+    element_id: ElementId,
+    /// Persistent state entity
+    state: Option<Entity<EditableTextState>>,
+    /// Called with the new text when an edit is committed
+    on_commit: Option<CommitCallback>,
+    /// Width (None = fit the text)
+    width: Option<f32>,
+    /// Text style
+    text_style: TextStyle,
+    /// Placeholder color
+    placeholder_color: Color,
+    /// Background while editing
+    background: Color,
+    /// Border color while editing
+    border_color: Color,
+    /// Border width while editing
+    border_width: f32,
+    /// Corner radius
+    corner_radius: f32,
+    /// Selection background color
+    selection_color: Color,
+    /// Horizontal padding
+    padding_h: f32,
+    /// Vertical padding
+    padding_v: f32,
+    /// Whether editing is disabled
+    disabled: bool,
+    /// Cached layout node
+    node_id: Option<NodeId>,
+}
+
+impl EditableText {
+    /// Create editable text
+    ///
+    /// Note: For stable interaction, call `.with_key()` to set a unique
+    /// identifier that persists across frames.
+    #[allow(deprecated)]
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            placeholder: None,
+            element_id: ElementId::auto(),
+            state: None,
+            on_commit: None,
+            width: None,
+            text_style: TextStyle {
+                size: 14.0,
+                color: colors::BLACK,
+                ..Default::default()
+            },
+            placeholder_color: colors::GRAY_400,
+            background: colors::WHITE,
+            border_color: colors::BLUE_500,
+            border_width: 1.0,
+            corner_radius: 4.0,
+            selection_color: colors::BLUE_500.with_alpha(0.3),
+            padding_h: 4.0,
+            padding_v: 2.0,
+            disabled: false,
+            node_id: None,
+        }
+    }
+
+    /// Set a unique key for this element
+    pub fn with_key(mut self, key: impl AsRef<str>) -> Self {
+        self.element_id = ElementId::stable(format!("editable-text:{}", key.as_ref()));
+        self
+    }
+
+    /// Bind to a persistent state entity
+    pub fn state(mut self, state: Entity<EditableTextState>) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Set the placeholder text, shown when the text is empty
+    pub fn placeholder(mut self, text: impl Into<String>) -> Self {
+        self.placeholder = Some(text.into());
+        self
+    }
+
+    /// Set the commit callback, called with the edited text when it changed
+    pub fn on_commit<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(&str) + 'static,
+    {
+        self.on_commit = Some(Rc::new(RefCell::new(Box::new(handler))));
+        self
+    }
+
+    /// Set the width (None = fit the text)
+    pub fn width(mut self, width: f32) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Set text style
+    pub fn text_style(mut self, style: TextStyle) -> Self {
+        self.text_style = style;
+        self
+    }
+
+    /// Set the field's background while editing
+    pub fn background(mut self, color: Color) -> Self {
+        self.background = color;
+        self
+    }
+
+    /// Set the field's border while editing
+    pub fn border(mut self, color: Color, width: f32) -> Self {
+        self.border_color = color;
+        self.border_width = width;
+        self
+    }
+
+    /// Set corner radius
+    pub fn corner_radius(mut self, radius: f32) -> Self {
+        self.corner_radius = radius;
+        self
+    }
+
+    /// Set padding around the text, kept in both modes so it doesn't move
+    pub fn padding_xy(mut self, horizontal: f32, vertical: f32) -> Self {
+        self.padding_h = horizontal;
+        self.padding_v = vertical;
+        self
+    }
+
+    /// Set disabled state
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+
+    /// Get the current state
+    fn get_state(&self) -> EditableTextState {
+        self.state
+            .as_ref()
+            .and_then(|s| read_entity(s, |state| state.clone()))
+            .unwrap_or_default()
+    }
+
+    /// Register mouse, keyboard and focus handlers
+    fn register_handlers(&self, state_entity: Entity<EditableTextState>) {
+        // Stop editing, reporting the text if it changed
+        let commit = {
+            let state = state_entity.clone();
+            let original = self.text.clone();
+            let on_commit = self.on_commit.clone();
+            move || {
+                let Some(text) = update_entity(&state, |s| s.commit()).flatten() else {
+                    return;
+                };
+                if text == original {
+                    return;
+                }
+                if let Some(handler) = &on_commit {
+                    (handler.borrow_mut())(&text);
+                }
+            }
+        };
+
+        let handlers = EventHandlers::new()
+            .on_double_click({
+                let state = state_entity.clone();
+                let text = self.text.clone();
+                move |button, _, _, _| {
+                    if button == MouseButton::Left {
+                        update_entity(&state, |s| {
+                            if !s.is_editing() {
+                                s.start_editing(&text);
+                            }
+                        });
+                    }
+                }
+            })
+            .on_key_down({
+                let state = state_entity;
+                let text = self.text.clone();
+                let commit = commit.clone();
+                move |key, modifiers, character, _is_repeat| {
+                    let mut commit_edit = false;
+                    let mut cancel_edit = false;
+                    update_entity(&state, |s| {
+                        let Some(input) = s.editing.as_mut() else {
+                            if key == Key::Return {
+                                s.start_editing(&text);
+                            }
+                            return;
+                        };
+                        input.last_activity = frame_time();
+                        match key {
+                            Key::Return => commit_edit = true,
+                            Key::Escape => cancel_edit = true,
+                            Key::Backspace => input.backspace(),
+                            Key::Delete => input.delete(),
+                            Key::Left => input.move_left(modifiers.shift),
+                            Key::Right => input.move_right(modifiers.shift),
+                            Key::Home => input.move_to_start(modifiers.shift),
+                            Key::End => input.move_to_end(modifiers.shift),
+                            Key::A if modifiers.cmd => input.select_all(),
+                            _ => {
+                                if let Some(c) = character
+                                    && !modifiers.cmd
+                                    && !modifiers.ctrl
+                                    && !c.is_control()
+                                {
+                                    input.insert(&c.to_string());
+                                }
+                            }
+                        }
+                        if cancel_edit {
+                            s.cancel();
+                        }
+                    });
+                    if commit_edit {
+                        commit();
+                    }
+                }
+            })
+            .on_focus_out(commit);
+
+        register_element(self.element_id, Rc::new(RefCell::new(handlers)));
+    }
+
+    /// Paint the field and the text being edited
+    fn paint_field(&self, bounds: Rect, ctx: &mut PaintContext, input: &TextInputState) {
+        ctx.paint_quad(PaintQuad {
+            bounds,
+            fill: self.background,
+            corner_radii: Corners::all(self.corner_radius),
+            border_widths: Edges::all(self.border_width),
+            border_color: self.border_color,
+        });
+
+        let text_area = Rect::from_pos_size(
+            bounds.pos + Vec2::new(self.padding_h, self.padding_v),
+            bounds.size - Vec2::new(self.padding_h, self.padding_v) * 2.0,
+        );
+        let config = TextConfig::from(&self.text_style);
+        let mut offset = |index: usize| {
+            let before = &input.text[..index.min(input.text.len())];
+            if before.is_empty() {
+                0.0
+            } else {
+                ctx.text_system
+                    .measure_text(before, &config, None, ctx.scale_factor)
+                    .x
+            }
+        };
+
+        let selection = input
+            .selection_range()
+            .filter(|(start, end)| start != end)
+            .map(|(start, end)| (offset(start), offset(end)));
+        let cursor_x = offset(input.cursor);
+
+        if let Some((start, end)) = selection {
+            let selection_rect = Rect::from_pos_size(
+                Vec2::new(text_area.pos.x + start, text_area.pos.y),
+                Vec2::new(end - start, text_area.size.y),
+            );
+            ctx.paint_quad(PaintQuad::filled(selection_rect, self.selection_color));
+        }
+
+        ctx.paint_text(PaintText {
+            position: text_area.pos,
+            text: input.text.clone(),
+            style: self.text_style.clone(),
+            measured_size: None,
+        });
+
+        ctx.paint_caret(
+            Rect::from_pos_size(
+                Vec2::new(text_area.pos.x + cursor_x, text_area.pos.y),
+                Vec2::new(caret_config().width, text_area.size.y),
+            ),
+            Some(self.text_style.color),
+            input.last_activity,
+        );
+    }
+
+    /// Paint the committed text, or the placeholder when it's empty
+    fn paint_text(&self, bounds: Rect, ctx: &mut PaintContext) {
+        let (text, color) = match &self.placeholder {
+            Some(placeholder) if self.text.is_empty() => {
+                (placeholder.clone(), self.placeholder_color)
+            }
+            _ if self.disabled => (self.text.clone(), colors::GRAY_500),
+            _ => (self.text.clone(), self.text_style.color),
+        };
+        ctx.paint_text(PaintText {
+            position: bounds.pos + Vec2::new(self.padding_h, self.padding_v),
+            text,
+            style: TextStyle {
+                color,
+                ..self.text_style.clone()
+            },
+            measured_size: None,
+        });
+    }
+}
+
+impl Element for EditableText {
+    fn layout(&mut self, ctx: &mut LayoutContext) -> NodeId {
+        // Initialize state if needed
+        if self.state.is_none() {
+            self.state = Some(new_entity(EditableTextState::new()));
+        }
+
+        let width = self.width.unwrap_or_else(|| {
+            // Fit whichever text is showing, with room for the caret
+            let state = self.get_state();
+            let text = match &state.editing {
+                Some(input) => input.text.as_str(),
+                None if self.text.is_empty() => self.placeholder.as_deref().unwrap_or(""),
+                None => self.text.as_str(),
+            };
+            let text_width = ctx.measure_text(text, &self.text_style, None).x;
+            text_width + caret_config().width + self.padding_h * 2.0
+        });
+        let height = self.text_style.size + self.padding_v * 2.0;
+        let style = Style {
+            size: Size {
+                width: Dimension::length(width),
+                height: Dimension::length(height),
+            },
+            ..Default::default()
+        };
+
+        let node_id = ctx.request_layout(style);
+        self.node_id = Some(node_id);
+        node_id
+    }
+
+    fn paint(&mut self, bounds: Rect, ctx: &mut PaintContext) {
+        if !ctx.is_visible(&bounds) {
+            return;
+        }
+
+        let Some(state_entity) = self.state.clone() else {
+            return;
+        };
+        let state = self.get_state();
+
+        match &state.editing {
+            Some(input) if !self.disabled => self.paint_field(bounds, ctx, input),
+            _ => self.paint_text(bounds, ctx),
+        }
+
+        if self.disabled {
+            return;
+        }
+
+        self.register_handlers(state_entity);
+        ctx.register_focusable(self.element_id, bounds, 0);
+
+        // Typing goes to the field as soon as it appears
+        let is_focused = get_element_state(self.element_id)
+            .unwrap_or_default()
+            .is_focused;
+        if state.is_editing() && !is_focused {
+            request_focus(self.element_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_editing_selects_text() {
+        let mut state = EditableTextState::new();
+        assert!(!state.is_editing());
+
+        state.start_editing("Groceries");
+        let input = state.editing.as_ref().unwrap();
+        assert_eq!(input.selected_text(), Some("Groceries"));
+
+        // Typing replaces the selection
+        state.editing.as_mut().unwrap().insert("Errands");
+        assert_eq!(state.commit(), Some("Errands".to_string()));
+        assert!(!state.is_editing());
+        assert_eq!(state.commit(), None);
+    }
+
+    #[test]
+    fn test_cancel_discards_edit() {
+        let mut state = EditableTextState::new();
+        state.start_editing("Groceries");
+        state.editing.as_mut().unwrap().insert("Errands");
+        state.cancel();
+        assert!(!state.is_editing());
+        assert_eq!(state.commit(), None);
+    }
+}
@@ -85,6 +85,11 @@ impl Icon {
         self
     }
 
+    /// Replace the icon color, for elements that tint icons they own
+    pub(crate) fn set_color(&mut self, color: Color) {
+        self.color = Some(color);
+    }
+
     /// Parse the SVG if not already parsed
     fn ensure_parsed(&mut self) -> Option<&Arc<usvg::Tree>> {
         if self.tree.is_none() {
@@ -228,6 +233,9 @@ pub mod icons {
 
     /// Edit/pencil icon
     pub const EDIT: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round"><path d="M11 4H4a2 2 0 0 0-2 2v14a2 2 0 0 0 2 2h14a2 2 0 0 0 2-2v-7"></path><path d="M18.5 2.5a2.121 2.121 0 0 1 3 3L12 15l-4 1 1-4 9.5-9.5z"></path></svg>"#;
+
+    /// Eyedropper icon
+    pub const EYEDROPPER: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round"><path d="m2 22 1-1h3l9-9"></path><path d="M3 21v-3l9-9"></path><path d="m15 6 3.4-3.4a2.1 2.1 0 1 1 3 3L18 9l.4.4a2.1 2.1 0 1 1-3 3l-3.8-3.8a2.1 2.1 0 1 1 3-3l.4.4Z"></path></svg>"#;
 }
 
 /// Create an icon-only button
@@ -0,0 +1,536 @@
+//! Spotlight-style launcher: a search field over a filtered results list
+
+use crate::{
+    caret::caret_config,
+    clock::frame_time,
+    color::{Color, ColorExt, colors},
+    element::{ComboboxFilter, Element, LayoutContext, TextInputState, filter_options},
+    entity::{Entity, new_entity, read_entity, update_entity},
+    geometry::{Corners, Edges, Rect},
+    interaction::{
+        ElementId, EventHandlers,
+        registry::{get_element_state, register_element, request_focus},
+    },
+    layer::{Key, MouseButton},
+    render::{PaintContext, PaintQuad, PaintText},
+    style::TextStyle,
+    text_system::TextConfig,
+};
+use glam::Vec2;
+use std::cell::RefCell;
+use std::rc::Rc;
+use taffy::prelude::*;
+
+/// State for a launcher, persisted via the Entity system
+#[derive(Debug, Clone, Default)]
+pub struct LauncherState {
+    /// Text typed into the search field
+    pub input: TextInputState,
+    /// Highlighted position within the filtered results
+    pub highlighted: usize,
+}
+
+impl LauncherState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Move the highlight by `delta` within `match_count` results, wrapping
+    /// around at either end
+    pub fn move_highlight(&mut self, delta: isize, match_count: usize) {
+        if match_count == 0 {
+            self.highlighted = 0;
+            return;
+        }
+        let current = self.highlighted.min(match_count - 1) as isize;
+        self.highlighted = (current + delta).rem_euclid(match_count as isize) as usize;
+    }
+
+    /// Clear the query, ready for the launcher to be summoned again
+    pub fn reset(&mut self) {
+        self.input = TextInputState::default();
+        self.highlighted = 0;
+    }
+}
+
+/// Called with the index and item chosen in a launcher
+type SubmitCallback<T> = Rc<RefCell<Box<dyn FnMut(usize, &T)>>>;
+
+/// Called when a launcher is dismissed
+type DismissCallback = Rc<RefCell<Box<dyn FnMut()>>>;
+
+/// Create a launcher searching the given items
+pub fn launcher<T: ToString + Clone + 'static>(items: Vec<T>) -> Launcher<T> {
+    Launcher::new(items)
+}
+
+/// A search field that keeps keyboard focus, over a list of the items
+/// matching the query
+///
+/// Typing filters the results, Up/Down (or Ctrl+P/N) move the highlight,
+/// Enter submits the highlighted item and Escape dismisses the launcher.
+/// Fills its parent; use it as the content of an
+/// [`AppBuilder::panel`](crate::app::AppBuilder::panel) window.
+///
+/// # Example
+/// ```ignore
+/// launcher(vec!["Calculator", "Calendar", "Terminal"])
+///     .with_key("apps")
+///     .placeholder("Open an app")
+///     .on_submit(|_, app| open_app(app))
+///     .on_dismiss(|| hide_launcher())
+/// ```
+pub struct Launcher<T: ToString + Clone + 'static> {
+    /// The items to search
+    items: Rc<Vec<T>>,
+    /// Display labels for the items
+    labels: Rc<Vec<String>>,
+    /// Placeholder text when the field is empty
+    placeholder: String,
+    /// Element ID for the search field
+    element_id: ElementId,
+    /// Base element ID for result rows
+    results_element_id: ElementId,
+    /// Persistent state entity
+    state: Option<Entity<LauncherState>>,
+    /// Called with the item chosen by Enter or a click
+    on_submit: Option<SubmitCallback<T>>,
+    /// Called when Escape is pressed
+    on_dismiss: Option<DismissCallback>,
+    /// Result filter
+    filter: ComboboxFilter,
+
+    // Styling
+    /// Maximum number of results shown at once
+    max_visible_results: usize,
+    /// Background color
+    background: Color,
+    /// Corner radius, matching the panel's
+    corner_radius: f32,
+    /// Style of the query text
+    text_style: TextStyle,
+    /// Style of the result rows
+    result_style: TextStyle,
+    /// Placeholder color
+    placeholder_color: Color,
+    /// Background for the highlighted result
+    highlight_background: Color,
+    /// Height of the search field
+    field_height: f32,
+    /// Horizontal padding
+    padding_h: f32,
+    /// Vertical padding of each result row
+    padding_v: f32,
+}
+
+impl<T: ToString + Clone + 'static> Launcher<T> {
+    /// Create a new launcher
+    ///
+    /// Note: For stable interaction, call `.with_key()` to set a unique
+    /// identifier that persists across frames.
+    #[allow(deprecated)]
+    pub fn new(items: Vec<T>) -> Self {
+        let labels = items.iter().map(|item| item.to_string()).collect();
+        Self {
+            items: Rc::new(items),
+            labels: Rc::new(labels),
+            placeholder: "Search".to_string(),
+            element_id: ElementId::auto(),
+            results_element_id: ElementId::auto(),
+            state: None,
+            on_submit: None,
+            on_dismiss: None,
+            filter: Rc::new(|label: &str, query: &str| {
+                label.to_lowercase().contains(&query.to_lowercase())
+            }),
+            max_visible_results: 8,
+            background: colors::WHITE,
+            corner_radius: 12.0,
+            text_style: TextStyle {
+                size: 22.0,
+                color: colors::BLACK,
+                ..Default::default()
+            },
+            result_style: TextStyle {
+                size: 14.0,
+                color: colors::GRAY_800,
+                ..Default::default()
+            },
+            placeholder_color: colors::GRAY_400,
+            highlight_background: colors::BLUE_500.with_alpha(0.15),
+            field_height: 56.0,
+            padding_h: 16.0,
+            padding_v: 10.0,
+        }
+    }
+
+    /// Set a unique key for this launcher
+    pub fn with_key(mut self, key: impl AsRef<str>) -> Self {
+        let key = key.as_ref();
+        self.element_id = ElementId::stable(format!("launcher:{}", key));
+        self.results_element_id = ElementId::stable(format!("launcher-results:{}", key));
+        self
+    }
+
+    /// Bind to a persistent state entity
+    pub fn state(mut self, state: Entity<LauncherState>) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Set the placeholder text
+    pub fn placeholder(mut self, text: impl Into<String>) -> Self {
+        self.placeholder = text.into();
+        self
+    }
+
+    /// Set a custom filter `(label, query) -> bool`
+    pub fn filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&str, &str) -> bool + 'static,
+    {
+        self.filter = Rc::new(filter);
+        self
+    }
+
+    /// Set the handler called with the chosen item
+    pub fn on_submit<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(usize, &T) + 'static,
+    {
+        self.on_submit = Some(Rc::new(RefCell::new(Box::new(handler))));
+        self
+    }
+
+    /// Set the handler called when Escape is pressed, typically to hide the
+    /// launcher's window
+    pub fn on_dismiss<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut() + 'static,
+    {
+        self.on_dismiss = Some(Rc::new(RefCell::new(Box::new(handler))));
+        self
+    }
+
+    /// Set the maximum number of results visible at once
+    pub fn max_visible_results(mut self, count: usize) -> Self {
+        self.max_visible_results = count.max(1);
+        self
+    }
+
+    /// Set background color
+    pub fn background(mut self, color: Color) -> Self {
+        self.background = color;
+        self
+    }
+
+    /// Set corner radius
+    pub fn corner_radius(mut self, radius: f32) -> Self {
+        self.corner_radius = radius;
+        self
+    }
+
+    /// Set the style of the query text
+    pub fn text_style(mut self, style: TextStyle) -> Self {
+        self.text_style = style;
+        self
+    }
+
+    /// Set the style of the result rows
+    pub fn result_style(mut self, style: TextStyle) -> Self {
+        self.result_style = style;
+        self
+    }
+
+    /// Get the current state
+    fn get_state(&self) -> LauncherState {
+        self.state
+            .as_ref()
+            .and_then(|s| read_entity(s, |state| state.clone()))
+            .unwrap_or_default()
+    }
+
+    /// ID of the result row for the item at `index`
+    fn result_id(&self, index: usize) -> ElementId {
+        ElementId::new(self.results_element_id.0.wrapping_add(index as u64 + 1))
+    }
+
+    /// Height of one result row
+    fn row_height(&self) -> f32 {
+        self.result_style.size + self.padding_v * 2.0
+    }
+
+    /// Register keyboard handlers for the search field
+    fn register_field_handlers(&self, state_entity: Entity<LauncherState>) {
+        let labels = self.labels.clone();
+        let items = self.items.clone();
+        let filter = self.filter.clone();
+        let on_submit = self.on_submit.clone();
+        let on_dismiss = self.on_dismiss.clone();
+
+        let handlers = EventHandlers::new().on_key_down(move |key, modifiers, character, _| {
+            let mut chosen = None;
+            let mut dismissed = false;
+
+            update_entity(&state_entity, |s| {
+                s.input.last_activity = frame_time();
+
+                let matches = filter_options(&labels, &s.input.text, filter.as_ref());
+
+                match key {
+                    Key::Escape => dismissed = true,
+                    Key::Up => s.move_highlight(-1, matches.len()),
+                    Key::Down => s.move_highlight(1, matches.len()),
+                    Key::P if modifiers.ctrl => s.move_highlight(-1, matches.len()),
+                    Key::N if modifiers.ctrl => s.move_highlight(1, matches.len()),
+                    Key::Return => chosen = matches.get(s.highlighted).copied(),
+                    Key::Backspace => {
+                        s.input.backspace();
+                        s.highlighted = 0;
+                    }
+                    Key::Delete => {
+                        s.input.delete();
+                        s.highlighted = 0;
+                    }
+                    Key::Left => s.input.move_left(modifiers.shift),
+                    Key::Right => s.input.move_right(modifiers.shift),
+                    Key::Home => s.input.move_to_start(modifiers.shift),
+                    Key::End => s.input.move_to_end(modifiers.shift),
+                    Key::A if modifiers.cmd => s.input.select_all(),
+                    _ => {
+                        if let Some(c) = character
+                            && !modifiers.cmd
+                            && !modifiers.ctrl
+                            && !c.is_control()
+                        {
+                            s.input.insert(&c.to_string());
+                            s.highlighted = 0;
+                        }
+                    }
+                }
+            });
+
+            if let (Some(index), Some(handler)) = (chosen, &on_submit) {
+                (handler.borrow_mut())(index, &items[index]);
+            }
+            if let (true, Some(handler)) = (dismissed, &on_dismiss) {
+                (handler.borrow_mut())();
+            }
+        });
+
+        register_element(self.element_id, Rc::new(RefCell::new(handlers)));
+    }
+
+    /// Register a click handler for one result row
+    fn register_result_handler(&self, index: usize) {
+        let items = self.items.clone();
+        let on_submit = self.on_submit.clone();
+
+        let handlers = EventHandlers::new().on_click(move |button, _, _, _, _| {
+            if button != MouseButton::Left {
+                return;
+            }
+            if let Some(handler) = &on_submit {
+                (handler.borrow_mut())(index, &items[index]);
+            }
+        });
+
+        register_element(self.result_id(index), Rc::new(RefCell::new(handlers)));
+    }
+
+    /// Paint the search field
+    fn paint_field(&self, bounds: Rect, ctx: &mut PaintContext, state: &LauncherState) {
+        let text = &state.input.text;
+        let (display_text, color) = if text.is_empty() {
+            (self.placeholder.clone(), self.placeholder_color)
+        } else {
+            (text.clone(), self.text_style.color)
+        };
+
+        let text_x = bounds.pos.x + self.padding_h;
+        let text_y = bounds.pos.y + (bounds.size.y - self.text_style.size) / 2.0;
+
+        ctx.paint_text(PaintText {
+            position: Vec2::new(text_x, text_y),
+            text: display_text,
+            style: TextStyle {
+                color,
+                ..self.text_style.clone()
+            },
+            measured_size: None,
+        });
+
+        let is_focused = get_element_state(self.element_id)
+            .unwrap_or_default()
+            .is_focused;
+        if is_focused {
+            let before_cursor = &text[..state.input.cursor.min(text.len())];
+            let cursor_x = if before_cursor.is_empty() {
+                0.0
+            } else {
+                let config = TextConfig::from(&self.text_style);
+                ctx.text_system
+                    .measure_text(before_cursor, &config, None, ctx.scale_factor)
+                    .x
+            };
+            ctx.paint_caret(
+                Rect::from_pos_size(
+                    Vec2::new(text_x + cursor_x, text_y),
+                    Vec2::new(caret_config().width, self.text_style.size),
+                ),
+                Some(self.text_style.color),
+                state.input.last_activity,
+            );
+        }
+    }
+
+    /// Paint the results matching the query
+    fn paint_results(&self, bounds: Rect, ctx: &mut PaintContext, state: &LauncherState) {
+        let matches = filter_options(&self.labels, &state.input.text, self.filter.as_ref());
+        let row_height = self.row_height();
+
+        if matches.is_empty() {
+            ctx.paint_text(PaintText {
+                position: bounds.pos + Vec2::new(self.padding_h, self.padding_v),
+                text: "No results".to_string(),
+                style: TextStyle {
+                    color: colors::GRAY_500,
+                    ..self.result_style.clone()
+                },
+                measured_size: None,
+            });
+            return;
+        }
+
+        let visible = matches
+            .len()
+            .min(self.max_visible_results)
+            .min((bounds.size.y / row_height).floor().max(1.0) as usize);
+        let highlighted = state.highlighted.min(matches.len() - 1);
+        // Keep the highlighted result in view
+        let first = (highlighted + 1).saturating_sub(visible);
+
+        for (row, (position, &index)) in matches
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(visible)
+            .enumerate()
+        {
+            let row_bounds = Rect::from_pos_size(
+                Vec2::new(bounds.pos.x, bounds.pos.y + row as f32 * row_height),
+                Vec2::new(bounds.size.x, row_height),
+            );
+
+            let is_hovered = get_element_state(self.result_id(index))
+                .unwrap_or_default()
+                .is_hovered;
+            if position == highlighted || is_hovered {
+                ctx.paint_quad(PaintQuad {
+                    bounds: Rect::from_pos_size(
+                        row_bounds.pos + Vec2::new(6.0, 0.0),
+                        row_bounds.size - Vec2::new(12.0, 0.0),
+                    ),
+                    fill: self.highlight_background,
+                    corner_radii: Corners::all(6.0),
+                    border_widths: Edges::zero(),
+                    border_color: colors::TRANSPARENT,
+                });
+            }
+
+            ctx.paint_text(PaintText {
+                position: row_bounds.pos + Vec2::new(self.padding_h, self.padding_v),
+                text: self.labels[index].clone(),
+                style: self.result_style.clone(),
+                measured_size: None,
+            });
+
+            self.register_result_handler(index);
+            ctx.register_hit_test(self.result_id(index), row_bounds, 1);
+        }
+    }
+}
+
+impl<T: ToString + Clone + 'static> Element for Launcher<T> {
+    fn layout(&mut self, ctx: &mut LayoutContext) -> NodeId {
+        // Initialize state if needed
+        if self.state.is_none() {
+            self.state = Some(new_entity(LauncherState::new()));
+        }
+
+        ctx.request_layout(Style {
+            size: Size {
+                width: percent(1.0),
+                height: percent(1.0),
+            },
+            ..Default::default()
+        })
+    }
+
+    fn paint(&mut self, bounds: Rect, ctx: &mut PaintContext) {
+        if !ctx.is_visible(&bounds) {
+            return;
+        }
+
+        let Some(state_entity) = self.state.clone() else {
+            return;
+        };
+        let state = self.get_state();
+
+        ctx.paint_quad(PaintQuad {
+            bounds,
+            fill: self.background,
+            corner_radii: Corners::all(self.corner_radius),
+            border_widths: Edges::zero(),
+            border_color: colors::TRANSPARENT,
+        });
+
+        let field = Rect::from_pos_size(bounds.pos, Vec2::new(bounds.size.x, self.field_height));
+        self.paint_field(field, ctx, &state);
+
+        // Divider between the field and the results
+        ctx.paint_quad(PaintQuad::filled(
+            Rect::from_pos_size(
+                Vec2::new(bounds.pos.x, field.pos.y + field.size.y),
+                Vec2::new(bounds.size.x, 1.0),
+            ),
+            colors::GRAY_200,
+        ));
+
+        let results_top = self.field_height + 1.0 + self.padding_v / 2.0;
+        let results = Rect::from_pos_size(
+            bounds.pos + Vec2::new(0.0, results_top),
+            Vec2::new(bounds.size.x, (bounds.size.y - results_top).max(0.0)),
+        );
+        self.paint_results(results, ctx, &state);
+
+        // The field keeps focus, so typing always goes to the query
+        self.register_field_handlers(state_entity);
+        ctx.register_focusable(self.element_id, field, 0);
+        if !get_element_state(self.element_id)
+            .unwrap_or_default()
+            .is_focused
+        {
+            request_focus(self.element_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_move_highlight_wraps() {
+        let mut state = LauncherState::new();
+        state.move_highlight(-1, 3);
+        assert_eq!(state.highlighted, 2);
+        state.move_highlight(1, 3);
+        assert_eq!(state.highlighted, 0);
+        state.highlighted = 7;
+        state.move_highlight(1, 3);
+        assert_eq!(state.highlighted, 0);
+        state.move_highlight(1, 0);
+        assert_eq!(state.highlighted, 0);
+    }
+}
@@ -5,17 +5,31 @@
 //! - Hover reveal action buttons
 //! - Empty state display
 //! - Loading state
+//! - Keyboard navigation and type-ahead while focused
+//! - Reordering by dragging rows or their grips
+//! - Sections with sticky headers and per-section actions
 //!
 //! Future features (require drag gesture support in interaction system):
 //! - Swipe-to-delete gesture
-//! - Item reordering via drag
 
 use crate::{
+    clock::frame_time,
     color::{colors, Color, ColorExt},
-    element::{Element, LayoutContext, PaintContext, text, Text},
+    element::{
+        Element, LayoutContext, PaintContext, ScrollAlignment, ScrollState, Text,
+        request_scroll_into_view, text,
+        reorder::{
+            REORDER_DRAG_Z, REORDER_PREVIEW_Z, ReorderCallback, ReorderState, drag_handler,
+            press_handler, release_handler,
+        },
+    },
     entity::{Entity, new_entity, read_entity, update_entity},
     geometry::{Corners, Edges, Rect},
-    interaction::{ElementId, EventHandlers, registry::register_element},
+    interaction::{
+        ElementId, EventHandlers,
+        registry::{get_element_state, register_element, request_focus},
+    },
+    layer::Key,
     render::PaintQuad,
     style::TextStyle,
 };
@@ -24,6 +38,15 @@ use std::collections::HashSet;
 use std::rc::Rc;
 use taffy::prelude::*;
 
+/// Focus ring color for the highlighted row
+const FOCUS_RING_COLOR: Color = colors::BLUE_400;
+/// Focus ring width
+const FOCUS_RING_WIDTH: f32 = 2.0;
+/// Seconds between keystrokes before type-ahead starts a new search
+const TYPEAHEAD_TIMEOUT: f32 = 1.0;
+/// Width of the drag grip at the start of reorderable rows
+const GRIP_WIDTH: f32 = 16.0;
+
 /// Selection mode for the list
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum SelectionMode {
@@ -45,6 +68,20 @@ pub struct ListState {
     pub hovered: Option<usize>,
     /// Whether the list is in loading state
     pub is_loading: bool,
+    /// Row highlighted for keyboard navigation
+    pub highlighted: Option<usize>,
+    /// Letters typed so far for type-ahead
+    typeahead: String,
+    /// Frame time of the last type-ahead keystroke
+    typeahead_time: f32,
+    /// Rows that fit in the visible area, for Page Up/Down
+    page_size: usize,
+    /// Row being dragged to a new position
+    reorder: ReorderState,
+    /// Flat index of each section's first row, empty for ungrouped lists
+    section_starts: Vec<usize>,
+    /// A row was clicked, so the list should take keyboard focus
+    focus_requested: bool,
 }
 
 impl ListState {
@@ -102,6 +139,102 @@ impl ListState {
     pub fn clear_selection(&mut self) {
         self.selected.clear();
     }
+
+    /// The section and row of the item at a flat `index`
+    ///
+    /// Rows of an ungrouped list are all in section 0.
+    pub fn index_path(&self, index: usize) -> IndexPath {
+        index_path(&self.section_starts, index)
+    }
+
+    /// Section and row of each selected item, in order
+    pub fn selected_index_paths(&self) -> Vec<IndexPath> {
+        let mut selected: Vec<usize> = self.selected.iter().copied().collect();
+        selected.sort_unstable();
+        selected.into_iter().map(|i| self.index_path(i)).collect()
+    }
+
+    /// Add a typed character to the type-ahead query and return the query
+    ///
+    /// The query starts over when keystrokes are more than
+    /// [`TYPEAHEAD_TIMEOUT`] apart.
+    fn push_typeahead(&mut self, c: char, now: f32) -> &str {
+        if now - self.typeahead_time > TYPEAHEAD_TIMEOUT {
+            self.typeahead.clear();
+        }
+        self.typeahead_time = now;
+        self.typeahead.extend(c.to_lowercase());
+        &self.typeahead
+    }
+}
+
+/// Position of an item in a grouped list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct IndexPath {
+    /// Index of the section
+    pub section: usize,
+    /// Index of the row within its section
+    pub row: usize,
+}
+
+/// The section and row of the item at a flat `index`, given the flat index
+/// of each section's first row
+fn index_path(section_starts: &[usize], index: usize) -> IndexPath {
+    // Empty sections share their start with the next section, so take the
+    // last section starting at or before the index
+    let section = section_starts
+        .partition_point(|&start| start <= index)
+        .saturating_sub(1);
+    let start = section_starts.get(section).copied().unwrap_or(0);
+    IndexPath {
+        section,
+        row: index - start,
+    }
+}
+
+/// Where to draw a sticky header so it stays at the top of the visible area
+/// while its section scrolls under it, and is pushed up by the next section
+fn sticky_header_top(
+    header_top: f32,
+    header_height: f32,
+    visible_top: f32,
+    section_bottom: f32,
+) -> f32 {
+    let pinned = header_top.max(visible_top);
+    pinned.min(section_bottom - header_height).max(header_top)
+}
+
+/// The enabled row closest to `target`, looking in the direction of travel
+/// first and then back the other way
+fn nearest_enabled(disabled: &[bool], target: usize, forward: bool) -> Option<usize> {
+    let target = target.min(disabled.len().checked_sub(1)?);
+    let enabled = |i: &usize| !disabled[*i];
+    if forward {
+        (target..disabled.len())
+            .find(enabled)
+            .or_else(|| (0..target).rev().find(enabled))
+    } else {
+        (0..=target)
+            .rev()
+            .find(enabled)
+            .or_else(|| (target + 1..disabled.len()).find(enabled))
+    }
+}
+
+/// The first enabled row at or after `start` whose title starts with
+/// `query`, wrapping around the end of the list
+///
+/// `query` is expected to be lowercase already.
+fn typeahead_match(
+    titles: &[String],
+    disabled: &[bool],
+    query: &str,
+    start: usize,
+) -> Option<usize> {
+    let count = titles.len();
+    (0..count)
+        .map(|offset| (start + offset) % count)
+        .find(|&i| !disabled[i] && titles[i].to_lowercase().starts_with(query))
 }
 
 /// Action button configuration for list items
@@ -168,15 +301,93 @@ impl ListItemData {
     }
 }
 
+/// A group of list items under a header
+pub struct ListSection {
+    /// Header text
+    pub title: String,
+    /// Items in the section
+    pub items: Vec<ListItemData>,
+    /// Buttons in the header, called with the section's index
+    pub actions: Vec<ListAction>,
+}
+
+impl ListSection {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            items: Vec::new(),
+            actions: Vec::new(),
+        }
+    }
+
+    /// Add items to the section
+    pub fn items<T: Into<ListItemData>>(mut self, items: impl IntoIterator<Item = T>) -> Self {
+        self.items.extend(items.into_iter().map(Into::into));
+        self
+    }
+
+    /// Add an item to the section
+    pub fn item(mut self, item: impl Into<ListItemData>) -> Self {
+        self.items.push(item.into());
+        self
+    }
+
+    /// Add a button to the section's header
+    pub fn action(mut self, action: ListAction) -> Self {
+        self.actions.push(action);
+        self
+    }
+}
+
+/// A section's header, and where its rows start in the flat item list
+struct SectionHeader {
+    title: String,
+    start: usize,
+    actions: Vec<ListAction>,
+}
+
+/// Called with the index of a clicked item, counting from the top of the list
+type ItemClickCallback = Rc<RefCell<Box<dyn FnMut(usize)>>>;
+
+/// Called with the section and row of a clicked item
+type RowClickCallback = Rc<RefCell<Box<dyn FnMut(IndexPath)>>>;
+
 /// Create a new list element
 pub fn list<T: Into<ListItemData>>(items: impl IntoIterator<Item = T>) -> List {
     List::new(items)
 }
 
+/// Create a list of items grouped into sections with headers
+pub fn grouped_list(sections: impl IntoIterator<Item = ListSection>) -> List {
+    List::grouped(sections)
+}
+
 /// A list element that renders items from data
+///
+/// While focused, Up/Down move a highlighted row, Home/End jump to the
+/// first and last rows, Page Up/Down move by a page, Enter activates the
+/// highlighted row, and typing letters jumps to the first row whose title
+/// starts with them. Inside a scroll container, the highlighted row is kept
+/// in view; give the list a [`scroll_state`](Self::scroll_state) to use a
+/// particular container's state instead of the nearest one.
+///
+/// Grouped lists built with [`grouped_list`] number their items across
+/// sections, so index-based callbacks and the selection count rows from the
+/// top of the list. [`on_row_click`](Self::on_row_click) and
+/// [`ListState::index_path`] give rows as an [`IndexPath`] instead.
 pub struct List {
-    /// Item data
+    /// Item data, across all sections
     items: Vec<ListItemData>,
+    /// Section headers, empty for ungrouped lists
+    sections: Vec<SectionHeader>,
+    /// Whether section headers stay in view while their section scrolls
+    sticky_headers: bool,
+    /// Section header height
+    header_height: f32,
+    /// Section header background color
+    header_background: Color,
+    /// Section header text style
+    header_style: TextStyle,
     /// Selection mode
     selection_mode: SelectionMode,
     /// Actions available on each item (shown on hover)
@@ -184,7 +395,13 @@ pub struct List {
     /// Callback when selection changes
     on_selection_change: Option<Rc<RefCell<Box<dyn FnMut(&HashSet<usize>)>>>>,
     /// Callback when item is clicked
-    on_item_click: Option<Rc<RefCell<Box<dyn FnMut(usize)>>>>,
+    on_item_click: Option<ItemClickCallback>,
+    /// Callback when item is clicked, with its section and row
+    on_row_click: Option<RowClickCallback>,
+    /// Callback when a row is dragged to a new position
+    on_move: Option<ReorderCallback>,
+    /// Whether rows are only dragged by their grips
+    drag_handles: bool,
     /// Item height
     item_height: f32,
     /// Gap between items
@@ -219,22 +436,41 @@ pub struct List {
     style: Style,
     /// Persistent state
     state: Option<Entity<ListState>>,
+    /// Element ID for focus and keyboard handling, derived from the state
+    /// entity unless set with a key
+    element_id: Option<ElementId>,
+    /// Scroll container to keep the highlighted row visible in
+    scroll_state: Option<Entity<ScrollState>>,
     /// Cached layout node ID
     node_id: Option<NodeId>,
     /// Child node IDs for items
     child_nodes: Vec<NodeId>,
     /// Rendered item elements
     item_elements: Vec<ListItemElement>,
+    /// Rendered section header elements
+    header_elements: Vec<ListHeaderElement>,
 }
 
 impl List {
     pub fn new<T: Into<ListItemData>>(items: impl IntoIterator<Item = T>) -> Self {
         Self {
             items: items.into_iter().map(Into::into).collect(),
+            sections: Vec::new(),
+            sticky_headers: false,
+            header_height: 28.0,
+            header_background: colors::GRAY_100,
+            header_style: TextStyle {
+                size: 12.0,
+                color: colors::GRAY_600,
+                ..Default::default()
+            },
             selection_mode: SelectionMode::None,
             actions: Vec::new(),
             on_selection_change: None,
             on_item_click: None,
+            on_row_click: None,
+            on_move: None,
+            drag_handles: false,
             item_height: 48.0,
             gap: 1.0,
             background: None,
@@ -264,12 +500,47 @@ impl List {
                 ..Style::default()
             },
             state: None,
+            element_id: None,
+            scroll_state: None,
             node_id: None,
             child_nodes: Vec::new(),
             item_elements: Vec::new(),
+            header_elements: Vec::new(),
         }
     }
 
+    /// Create a list of items grouped into sections with headers
+    pub fn grouped(sections: impl IntoIterator<Item = ListSection>) -> Self {
+        let mut list = Self::new(Vec::<ListItemData>::new());
+        for section in sections {
+            list.sections.push(SectionHeader {
+                title: section.title,
+                start: list.items.len(),
+                actions: section.actions,
+            });
+            list.items.extend(section.items);
+        }
+        list
+    }
+
+    /// Set a unique key for this list
+    pub fn with_key(mut self, key: impl AsRef<str>) -> Self {
+        self.element_id = Some(ElementId::stable(format!("list:{}", key.as_ref())));
+        self
+    }
+
+    /// Keep the highlighted row visible in the scroll container with this state
+    pub fn scroll_state(mut self, state: Entity<ScrollState>) -> Self {
+        self.scroll_state = Some(state);
+        self
+    }
+
+    /// Bind to a persistent state entity
+    pub fn state(mut self, state: Entity<ListState>) -> Self {
+        self.state = Some(state);
+        self
+    }
+
     /// Set the selection mode
     pub fn selection_mode(mut self, mode: SelectionMode) -> Self {
         self.selection_mode = mode;
@@ -312,6 +583,59 @@ impl List {
         self
     }
 
+    /// Set callback for item clicks, with the item's section and row
+    pub fn on_row_click<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(IndexPath) + 'static,
+    {
+        self.on_row_click = Some(Rc::new(RefCell::new(Box::new(handler))));
+        self
+    }
+
+    /// Keep each section's header at the top of the visible area while its
+    /// rows scroll under it
+    pub fn sticky_headers(mut self) -> Self {
+        self.sticky_headers = true;
+        self
+    }
+
+    /// Set section header height
+    pub fn header_height(mut self, height: f32) -> Self {
+        self.header_height = height;
+        self
+    }
+
+    /// Set section header background color
+    pub fn header_background(mut self, color: Color) -> Self {
+        self.header_background = color;
+        self
+    }
+
+    /// Set section header text style
+    pub fn header_style(mut self, style: TextStyle) -> Self {
+        self.header_style = style;
+        self
+    }
+
+    /// Let rows be dragged to new positions
+    ///
+    /// `on_move` gets `(from, to)` when a row is dropped somewhere new; the
+    /// app should move item `from` so it ends up at index `to`. A click that
+    /// ends a drag doesn't change the selection.
+    pub fn reorderable<F>(mut self, on_move: F) -> Self
+    where
+        F: FnMut(usize, usize) + 'static,
+    {
+        self.on_move = Some(Rc::new(RefCell::new(Box::new(on_move))));
+        self
+    }
+
+    /// Only start reordering from a grip drawn at the start of each row
+    pub fn drag_handles(mut self) -> Self {
+        self.drag_handles = true;
+        self
+    }
+
     /// Set item height
     pub fn item_height(mut self, height: f32) -> Self {
         self.item_height = height;
@@ -498,12 +822,30 @@ impl List {
     }
 }
 
+/// Element ID for a row, stable across frames so scrolling can find it
+fn row_id(list_id: ElementId, index: usize) -> ElementId {
+    ElementId::stable(format!("list-row:{}:{}", list_id.0, index))
+}
+
 impl Default for List {
     fn default() -> Self {
         Self::new(Vec::<ListItemData>::new())
     }
 }
 
+/// Element ID for a section header
+fn header_id(list_id: ElementId, section: usize) -> ElementId {
+    ElementId::stable(format!("list-section:{}:{}", list_id.0, section))
+}
+
+/// Internal element for rendering a section header
+struct ListHeaderElement {
+    section: usize,
+    title: Text,
+    title_node: Option<NodeId>,
+    node_id: Option<NodeId>,
+}
+
 /// Internal element for rendering a single list item
 struct ListItemElement {
     index: usize,
@@ -519,6 +861,7 @@ struct ListItemElement {
 impl ListItemElement {
     fn new(
         index: usize,
+        element_id: ElementId,
         data: &ListItemData,
         title_style: TextStyle,
         subtitle_style: TextStyle,
@@ -526,6 +869,7 @@ impl ListItemElement {
         selection_mode: SelectionMode,
         on_item_click: Option<Rc<RefCell<Box<dyn FnMut(usize)>>>>,
         on_selection_change: Option<Rc<RefCell<Box<dyn FnMut(&HashSet<usize>)>>>>,
+        reorder: Option<(ReorderCallback, Option<f32>)>,
     ) -> Self {
         let title = text(data.title.clone(), title_style);
         let subtitle = data.subtitle.as_ref().map(|s| text(s.clone(), subtitle_style));
@@ -540,9 +884,17 @@ impl ListItemElement {
         let item_index = index;
 
         handlers.borrow_mut().on_click = Some(Box::new(move |_button, _click_type, _pos, _local_pos, _modifiers| {
-            // Toggle selection
+            // The click ending a drag only drops the row
+            if update_entity(&state_for_click, |s| s.reorder.take_dropped()).unwrap_or(false) {
+                return;
+            }
+
+            // Toggle selection, and continue keyboard navigation from here.
+            // Rows aren't focusable themselves, so focus the list
             update_entity(&state_for_click, |s| {
                 s.toggle_selection(item_index, selection_mode);
+                s.highlighted = Some(item_index);
+                s.focus_requested = true;
             });
 
             // Fire selection change callback
@@ -577,6 +929,20 @@ impl ListItemElement {
             });
         }));
 
+        // Set up drag to reorder
+        if let Some((on_move, grip)) = reorder {
+            let reorder_state: fn(&mut ListState) -> &mut ReorderState = |s| &mut s.reorder;
+            let grip = grip.map(|width| (width, false));
+            handlers.borrow_mut().on_mouse_down = Some(Box::new(press_handler(
+                state.clone(),
+                reorder_state,
+                index,
+                grip,
+            )));
+            handlers.borrow_mut().on_mouse_up =
+                Some(Box::new(release_handler(state.clone(), reorder_state, on_move)));
+        }
+
         Self {
             index,
             title,
@@ -584,7 +950,7 @@ impl ListItemElement {
             title_node: None,
             subtitle_node: None,
             node_id: None,
-            element_id: ElementId::auto(),
+            element_id,
             handlers,
         }
     }
@@ -631,21 +997,77 @@ impl Element for List {
 
         // Create item elements
         self.item_elements.clear();
+        self.header_elements.clear();
         self.child_nodes.clear();
 
         // Get state entity for handlers (must exist after init above)
         let state = self.state.clone().unwrap();
+        let list_id = self.list_id(&state);
+        let grip = (self.on_move.is_some() && self.drag_handles)
+            .then_some(GRIP_WIDTH + self.item_padding);
+        let on_item_click = self.item_click_handler();
+
+        // Let the state map flat indices to sections
+        let section_starts: Vec<usize> = self.sections.iter().map(|s| s.start).collect();
+        if read_entity(&state, |s| s.section_starts != section_starts).unwrap_or(false) {
+            update_entity(&state, |s| s.section_starts = section_starts);
+        }
+
+        // Lay out section headers, placed before their first rows below
+        let mut header_nodes = Vec::with_capacity(self.sections.len());
+        for (section, header) in self.sections.iter().enumerate() {
+            let mut header_element = ListHeaderElement {
+                section,
+                title: text(header.title.clone(), self.header_style.clone()),
+                title_node: None,
+                node_id: None,
+            };
+            let title_node = header_element.title.layout(ctx);
+            header_element.title_node = Some(title_node);
+
+            let header_style = Style {
+                display: Display::Flex,
+                align_items: Some(AlignItems::Center),
+                padding: taffy::Rect {
+                    left: LengthPercentage::length(self.item_padding),
+                    right: LengthPercentage::length(self.item_padding),
+                    top: LengthPercentage::length(0.0),
+                    bottom: LengthPercentage::length(0.0),
+                },
+                min_size: Size {
+                    width: Dimension::percent(1.0),
+                    height: Dimension::length(self.header_height),
+                },
+                ..Style::default()
+            };
+            let header_node = ctx.request_layout_with_children(header_style, &[title_node]);
+            header_element.node_id = Some(header_node);
+            header_nodes.push(header_node);
+            self.header_elements.push(header_element);
+        }
+        let mut next_header = 0;
 
         for (index, item_data) in self.items.iter().enumerate() {
+            // Section headers go before their first row
+            while let Some(header) = self.sections.get(next_header) {
+                if header.start > index {
+                    break;
+                }
+                self.child_nodes.push(header_nodes[next_header]);
+                next_header += 1;
+            }
+
             let mut item_element = ListItemElement::new(
                 index,
+                row_id(list_id, index),
                 item_data,
                 self.title_style.clone(),
                 self.subtitle_style.clone(),
                 state.clone(),
                 self.selection_mode,
-                self.on_item_click.clone(),
+                on_item_click.clone(),
                 self.on_selection_change.clone(),
+                self.on_move.clone().map(|on_move| (on_move, grip)),
             );
 
             // Layout title
@@ -667,7 +1089,7 @@ impl Element for List {
                 flex_direction: FlexDirection::Column,
                 justify_content: Some(JustifyContent::Center),
                 padding: taffy::Rect {
-                    left: LengthPercentage::length(self.item_padding),
+                    left: LengthPercentage::length(grip.unwrap_or(self.item_padding)),
                     right: LengthPercentage::length(self.item_padding),
                     top: LengthPercentage::length(self.item_padding / 2.0),
                     bottom: LengthPercentage::length(self.item_padding / 2.0),
@@ -696,6 +1118,8 @@ impl Element for List {
             self.child_nodes.push(item_node);
             self.item_elements.push(item_element);
         }
+        // Headers of empty sections at the end
+        self.child_nodes.extend_from_slice(&header_nodes[next_header..]);
 
         // Update container style with gap
         let mut container_style = self.style.clone();
@@ -762,145 +1186,523 @@ impl Element for List {
         }
 
         // Get current state
-        let (selected, hovered) = self.state
+        let Some(state_entity) = self.state.clone() else {
+            return;
+        };
+        let (selected, hovered, highlighted, page_size) = read_entity(&state_entity, |state| {
+            (
+                state.selected.clone(),
+                state.hovered,
+                state.highlighted,
+                state.page_size,
+            )
+        })
+        .unwrap_or_default();
+        let list_id = self.list_id(&state_entity);
+        let is_focused = get_element_state(list_id).unwrap_or_default().is_focused;
+
+        // Remember how many rows fit on screen for Page Up/Down, which is
+        // less than the whole list when a scroll container clips it
+        let transform = ctx.draw_list.current_transform();
+        let visible_height = self
+            .scroll_state
             .as_ref()
-            .and_then(|s| read_entity(s, |state| (state.selected.clone(), state.hovered)))
-            .unwrap_or_default();
+            .and_then(|s| read_entity(s, |scroll| scroll.viewport_size.y))
+            .filter(|height| *height > 0.0)
+            .or_else(|| {
+                ctx.draw_list
+                    .current_clip()
+                    .and_then(|clip| clip.intersect(&transform.apply_rect(bounds)))
+                    .map(|visible| visible.size.y / transform.scale)
+            })
+            .unwrap_or(bounds.size.y);
+        let rows_per_page = ((visible_height + self.gap) / (self.item_height + self.gap))
+            .floor()
+            .max(1.0) as usize;
+        if rows_per_page != page_size {
+            update_entity(&state_entity, |s| s.page_size = rows_per_page);
+        }
 
-        // Paint items
-        for (item_element, &item_node) in self.item_elements.iter_mut().zip(&self.child_nodes) {
+        // Ease the gap for a dragged row towards where it would land
+        let mut row_bounds = Vec::with_capacity(self.item_elements.len());
+        for item_node in self.item_elements.iter().filter_map(|item| item.node_id) {
             let item_bounds = ctx.layout_engine.layout_bounds(item_node);
+            row_bounds.push(Rect::from_pos_size(bounds.pos + item_bounds.pos, item_bounds.size));
+        }
+        let mut reorder_moving = false;
+        if read_entity(&state_entity, |s| s.reorder.dragging().is_some()).unwrap_or(false) {
+            let rows: Vec<(f32, f32)> = row_bounds
+                .iter()
+                .map(|&row| {
+                    let row = transform.apply_rect(row);
+                    (row.pos.y, row.size.y)
+                })
+                .collect();
+            reorder_moving = update_entity(&state_entity, |s| {
+                s.reorder.step(&rows, false, frame_time())
+            })
+            .unwrap_or(false);
+        }
+        let reorder = read_entity(&state_entity, |s| s.reorder.clone()).unwrap_or_default();
+        let dragging = reorder.dragging();
+
+        // Paint items
+        let mut item_elements = std::mem::take(&mut self.item_elements);
+        for (item_element, &row) in item_elements.iter_mut().zip(&row_bounds) {
+            let index = item_element.index;
+            if dragging == Some(index) {
+                // Painted last, floating above the other rows
+                ctx.register_scroll_target(item_element.element_id, row);
+                continue;
+            }
+
+            // Slide aside to open a gap for the dragged row
+            let shift = reorder.offset(index) / transform.scale;
             let absolute_bounds = Rect::from_pos_size(
-                bounds.pos + item_bounds.pos,
-                item_bounds.size,
+                row.pos + glam::Vec2::new(0.0, shift),
+                row.size,
             );
 
             if !ctx.is_visible(&absolute_bounds) {
+                // Still let scroll containers find the row to reveal it
+                ctx.register_scroll_target(item_element.element_id, absolute_bounds);
                 continue;
             }
 
-            let index = item_element.index;
             let is_selected = selected.contains(&index);
-            let is_hovered = hovered == Some(index);
+            let is_hovered = dragging.is_none() && hovered == Some(index);
+            let is_highlighted = is_focused && highlighted == Some(index);
+            self.paint_item(item_element, absolute_bounds, is_selected, is_hovered, is_highlighted, ctx);
+        }
 
-            // Determine background color
-            let bg_color = if is_selected {
-                self.selected_background
-            } else if is_hovered {
-                self.hovered_background
-            } else {
-                self.item_background
-            };
+        // Headers go over the rows, which scroll under sticky ones
+        self.paint_headers(bounds, &row_bounds, list_id, ctx);
 
-            // Paint item background
+        // The dragged row follows the pointer
+        let preview = dragging.zip(reorder.preview_origin()).and_then(|(index, origin)| {
+            let item_element = item_elements.iter_mut().find(|item| item.index == index)?;
+            Some((item_element, origin))
+        });
+        if let Some((item_element, origin)) = preview {
+            let index = item_element.index;
+            let preview_bounds = Rect::from_pos_size(
+                transform.apply_inverse(origin),
+                row_bounds[index].size,
+            );
+            ctx.draw_list.push_z_index(REORDER_PREVIEW_Z);
+            self.paint_item(item_element, preview_bounds, selected.contains(&index), false, false, ctx);
+            ctx.paint_quad(PaintQuad {
+                bounds: preview_bounds,
+                fill: colors::TRANSPARENT,
+                corner_radii: Corners::all(self.item_corner_radius),
+                border_widths: Edges::all(1.0),
+                border_color: FOCUS_RING_COLOR,
+            });
+            ctx.draw_list.pop_z_index();
+        }
+        self.item_elements = item_elements;
+
+        if reorder.is_pressed() {
+            // Follow the pointer over the whole list while a row is pressed
+            let reorder_state: fn(&mut ListState) -> &mut ReorderState = |s| &mut s.reorder;
+            let handlers = EventHandlers::new()
+                .on_mouse_move(drag_handler(state_entity.clone(), reorder_state));
+            let drag_id = ElementId::stable(format!("list-drag:{}", list_id.0));
+            register_element(drag_id, Rc::new(RefCell::new(handlers)));
+            ctx.register_hit_test(drag_id, bounds, REORDER_DRAG_Z);
+
+            // Scroll when the pointer nears the edge of a clipping container
+            let screen_bounds = transform.apply_rect(bounds);
+            let visible = ctx
+                .draw_list
+                .current_clip()
+                .and_then(|clip| clip.intersect(&screen_bounds))
+                .unwrap_or(screen_bounds);
+            if let Some(next) = reorder.auto_scroll_target(visible, self.items.len(), false) {
+                request_scroll_into_view(row_id(list_id, next), ScrollAlignment::Nearest);
+                reorder_moving = true;
+            }
+        }
+        if reorder_moving {
+            ctx.request_animation_frame();
+        }
+
+        // Clicks land on the rows, which hand focus on to the list here
+        if read_entity(&state_entity, |s| s.focus_requested).unwrap_or(false) {
+            update_entity(&state_entity, |s| s.focus_requested = false);
+            request_focus(list_id);
+        }
+
+        // Register after the rows so clicks still reach them
+        self.register_key_handler(list_id, state_entity);
+        ctx.register_focusable(list_id, bounds, 0);
+    }
+}
+
+impl List {
+    /// Paint one row with its title, subtitle and hover actions, and
+    /// register it for interaction
+    fn paint_item(
+        &self,
+        item_element: &mut ListItemElement,
+        absolute_bounds: Rect,
+        is_selected: bool,
+        is_hovered: bool,
+        is_highlighted: bool,
+        ctx: &mut PaintContext,
+    ) {
+        let index = item_element.index;
+
+        // Determine background color
+        let bg_color = if is_selected {
+            self.selected_background
+        } else if is_hovered || is_highlighted {
+            self.hovered_background
+        } else {
+            self.item_background
+        };
+
+        // Paint item background
+        ctx.paint_quad(PaintQuad {
+            bounds: absolute_bounds,
+            fill: bg_color,
+            corner_radii: Corners::all(self.item_corner_radius),
+            border_widths: Edges::zero(),
+            border_color: colors::TRANSPARENT,
+        });
+
+        if is_highlighted {
             ctx.paint_quad(PaintQuad {
                 bounds: absolute_bounds,
-                fill: bg_color,
+                fill: colors::TRANSPARENT,
                 corner_radii: Corners::all(self.item_corner_radius),
+                border_widths: Edges::all(FOCUS_RING_WIDTH),
+                border_color: FOCUS_RING_COLOR,
+            });
+        }
+
+        // Paint title
+        if let Some(title_node) = item_element.title_node {
+            let title_bounds = ctx.layout_engine.layout_bounds(title_node);
+            let title_absolute = Rect::from_pos_size(
+                absolute_bounds.pos + title_bounds.pos,
+                title_bounds.size,
+            );
+            item_element.title.paint(title_absolute, ctx);
+        }
+
+        // Paint subtitle
+        if let (Some(subtitle), Some(subtitle_node)) =
+            (&mut item_element.subtitle, item_element.subtitle_node)
+        {
+            let subtitle_bounds = ctx.layout_engine.layout_bounds(subtitle_node);
+            let subtitle_absolute = Rect::from_pos_size(
+                absolute_bounds.pos + subtitle_bounds.pos,
+                subtitle_bounds.size,
+            );
+            subtitle.paint(subtitle_absolute, ctx);
+        }
+
+        // Paint action buttons on hover
+        if is_hovered && !self.actions.is_empty() {
+            let id = item_element.element_id;
+            self.paint_actions(&self.actions, absolute_bounds, id, index, 1, ctx);
+        }
+
+        if self.on_move.is_some() && self.drag_handles {
+            self.paint_grip(absolute_bounds, ctx);
+        }
+
+        // Register element for interaction and hit testing
+        register_element(item_element.element_id, item_element.handlers.clone());
+        ctx.register_hit_test(item_element.element_id, absolute_bounds, 0);
+    }
+
+    /// Paint action buttons at the right of `bounds`, hit tested at
+    /// `z_index` and called with `index` when clicked
+    fn paint_actions(
+        &self,
+        actions: &[ListAction],
+        bounds: Rect,
+        base_id: ElementId,
+        index: usize,
+        z_index: i32,
+        ctx: &mut PaintContext,
+    ) {
+        let action_button_height = 28.0;
+        let action_button_padding = 8.0;
+        let action_gap = 4.0;
+
+        // Calculate total width of all action buttons
+        let mut total_actions_width = 0.0;
+        for action in actions {
+            let approx_text_width = action.label.len() as f32 * 7.0;
+            total_actions_width += approx_text_width + action_button_padding * 2.0 + action_gap;
+        }
+        total_actions_width -= action_gap; // Remove last gap
+
+        // Position actions on the right side
+        let actions_start_x =
+            bounds.pos.x + bounds.size.x - total_actions_width - self.item_padding;
+        let actions_y = bounds.pos.y + (bounds.size.y - action_button_height) / 2.0;
+
+        let mut current_x = actions_start_x;
+        for (action_idx, action) in actions.iter().enumerate() {
+            let approx_text_width = action.label.len() as f32 * 7.0;
+            let button_width = approx_text_width + action_button_padding * 2.0;
+
+            let button_bounds = Rect::from_pos_size(
+                glam::Vec2::new(current_x, actions_y),
+                glam::Vec2::new(button_width, action_button_height),
+            );
+
+            // Paint button background
+            ctx.paint_quad(PaintQuad {
+                bounds: button_bounds,
+                fill: action.color,
+                corner_radii: Corners::all(4.0),
                 border_widths: Edges::zero(),
                 border_color: colors::TRANSPARENT,
             });
 
-            // Paint title
-            if let Some(title_node) = item_element.title_node {
-                let title_bounds = ctx.layout_engine.layout_bounds(title_node);
-                let title_absolute = Rect::from_pos_size(
-                    absolute_bounds.pos + title_bounds.pos,
-                    title_bounds.size,
+            // Paint button label
+            let text_style = TextStyle {
+                size: 12.0,
+                color: colors::WHITE,
+                ..Default::default()
+            };
+            ctx.paint_text(crate::render::PaintText {
+                position: glam::Vec2::new(
+                    button_bounds.pos.x + action_button_padding,
+                    button_bounds.pos.y + (action_button_height - text_style.size) / 2.0,
+                ),
+                text: action.label.clone(),
+                style: text_style,
+                measured_size: None,
+            });
+
+            // Create unique element ID for this action button
+            let action_id = ElementId::new(base_id.0.wrapping_add((action_idx + 1000) as u64));
+
+            // Create handler for action button
+            let action_handlers = Rc::new(RefCell::new(EventHandlers::new()));
+            let on_action = action.on_click.clone();
+            let item_idx = index;
+            action_handlers.borrow_mut().on_click = Some(Box::new(
+                move |_btn, _click_type, _pos, _local, _modifiers| {
+                    (on_action.borrow_mut())(item_idx);
+                },
+            ));
+
+            // Register action button for interaction (above what it sits on)
+            register_element(action_id, action_handlers);
+            ctx.register_hit_test(action_id, button_bounds, z_index);
+
+            current_x += button_width + action_gap;
+        }
+    }
+
+    /// Paint section headers, pinning sticky ones to the top of the visible
+    /// area until the next section pushes them out
+    fn paint_headers(
+        &mut self,
+        bounds: Rect,
+        row_bounds: &[Rect],
+        list_id: ElementId,
+        ctx: &mut PaintContext,
+    ) {
+        let transform = ctx.draw_list.current_transform();
+        let visible_top = ctx
+            .draw_list
+            .current_clip()
+            .filter(|_| self.sticky_headers)
+            .map(|clip| transform.apply_inverse(clip.pos).y);
+
+        let mut header_elements = std::mem::take(&mut self.header_elements);
+        for header_element in &mut header_elements {
+            let section = header_element.section;
+            let (Some(header), Some(node_id)) =
+                (self.sections.get(section), header_element.node_id)
+            else {
+                continue;
+            };
+            let layout = ctx.layout_engine.layout_bounds(node_id);
+            let mut header_bounds = Rect::from_pos_size(bounds.pos + layout.pos, layout.size);
+
+            if let Some(visible_top) = visible_top {
+                let end = self
+                    .sections
+                    .get(section + 1)
+                    .map_or(self.items.len(), |next| next.start);
+                let section_rows = row_bounds.get(header.start..end).unwrap_or_default();
+                let section_bottom = match section_rows.last() {
+                    Some(last_row) => last_row.pos.y + last_row.size.y,
+                    None => header_bounds.pos.y + header_bounds.size.y,
+                };
+                header_bounds.pos.y = sticky_header_top(
+                    header_bounds.pos.y,
+                    header_bounds.size.y,
+                    visible_top,
+                    section_bottom,
                 );
-                item_element.title.paint(title_absolute, ctx);
             }
 
-            // Paint subtitle
-            if let (Some(subtitle), Some(subtitle_node)) =
-                (&mut item_element.subtitle, item_element.subtitle_node)
-            {
-                let subtitle_bounds = ctx.layout_engine.layout_bounds(subtitle_node);
-                let subtitle_absolute = Rect::from_pos_size(
-                    absolute_bounds.pos + subtitle_bounds.pos,
-                    subtitle_bounds.size,
-                );
-                subtitle.paint(subtitle_absolute, ctx);
+            if !ctx.is_visible(&header_bounds) {
+                continue;
             }
 
-            // Paint action buttons on hover
-            if is_hovered && !self.actions.is_empty() {
-                let action_button_height = 28.0;
-                let action_button_padding = 8.0;
-                let action_gap = 4.0;
-
-                // Calculate total width of all action buttons
-                let mut total_actions_width = 0.0;
-                for action in &self.actions {
-                    let approx_text_width = action.label.len() as f32 * 7.0;
-                    total_actions_width += approx_text_width + action_button_padding * 2.0 + action_gap;
-                }
-                total_actions_width -= action_gap; // Remove last gap
+            ctx.paint_quad(PaintQuad::filled(header_bounds, self.header_background));
+            if let Some(title_node) = header_element.title_node {
+                let title_bounds = ctx.layout_engine.layout_bounds(title_node);
+                let title_absolute =
+                    Rect::from_pos_size(header_bounds.pos + title_bounds.pos, title_bounds.size);
+                header_element.title.paint(title_absolute, ctx);
+            }
 
-                // Position actions on right side of item
-                let actions_start_x = absolute_bounds.pos.x + absolute_bounds.size.x - total_actions_width - self.item_padding;
-                let actions_y = absolute_bounds.pos.y + (absolute_bounds.size.y - action_button_height) / 2.0;
+            let id = header_id(list_id, section);
+            self.paint_actions(&header.actions, header_bounds, id, section, 2, ctx);
+            // Keep clicks on a pinned header from reaching rows under it
+            ctx.register_hit_test(id, header_bounds, 1);
+        }
+        self.header_elements = header_elements;
+    }
 
-                let mut current_x = actions_start_x;
-                for (action_idx, action) in self.actions.iter().enumerate() {
-                    let approx_text_width = action.label.len() as f32 * 7.0;
-                    let button_width = approx_text_width + action_button_padding * 2.0;
+    /// Item click callback that also calls the index path handler
+    fn item_click_handler(&self) -> Option<ItemClickCallback> {
+        let Some(on_row_click) = self.on_row_click.clone() else {
+            return self.on_item_click.clone();
+        };
+        let on_item_click = self.on_item_click.clone();
+        let section_starts: Vec<usize> = self.sections.iter().map(|s| s.start).collect();
+        Some(Rc::new(RefCell::new(Box::new(move |index| {
+            if let Some(callback) = &on_item_click {
+                (callback.borrow_mut())(index);
+            }
+            (on_row_click.borrow_mut())(index_path(&section_starts, index));
+        }))))
+    }
 
-                    let button_bounds = Rect::from_pos_size(
-                        glam::Vec2::new(current_x, actions_y),
-                        glam::Vec2::new(button_width, action_button_height),
-                    );
+    /// Paint the dots marking where a row can be dragged from
+    fn paint_grip(&self, row: Rect, ctx: &mut PaintContext) {
+        let dot_size = 3.0;
+        let spacing = 5.0;
+        let center = glam::Vec2::new(
+            row.pos.x + (GRIP_WIDTH + self.item_padding) / 2.0,
+            row.pos.y + row.size.y / 2.0,
+        );
+        for column in [-0.5, 0.5] {
+            for line in [-1.0, 0.0, 1.0] {
+                let position = center + glam::Vec2::new(column, line) * spacing;
+                ctx.paint_quad(PaintQuad {
+                    bounds: Rect::from_pos_size(
+                        position - glam::Vec2::splat(dot_size / 2.0),
+                        glam::Vec2::splat(dot_size),
+                    ),
+                    fill: colors::GRAY_400,
+                    corner_radii: Corners::all(dot_size / 2.0),
+                    border_widths: Edges::zero(),
+                    border_color: colors::TRANSPARENT,
+                });
+            }
+        }
+    }
 
-                    // Paint button background
-                    ctx.paint_quad(PaintQuad {
-                        bounds: button_bounds,
-                        fill: action.color,
-                        corner_radii: Corners::all(4.0),
-                        border_widths: Edges::zero(),
-                        border_color: colors::TRANSPARENT,
-                    });
-
-                    // Paint button label
-                    let text_style = TextStyle {
-                        size: 12.0,
-                        color: colors::WHITE,
-                        ..Default::default()
-                    };
-                    ctx.paint_text(crate::render::PaintText {
-                        position: glam::Vec2::new(
-                            button_bounds.pos.x + action_button_padding,
-                            button_bounds.pos.y + (action_button_height - text_style.size) / 2.0,
-                        ),
-                        text: action.label.clone(),
-                        style: text_style,
-                        measured_size: None,
-                    });
-
-                    // Create unique element ID for this action button
-                    let action_id = ElementId::new(
-                        item_element.element_id.0.wrapping_add((action_idx + 1000) as u64)
-                    );
+    /// Element ID for the list itself, derived from the state entity unless
+    /// set with [`with_key`](Self::with_key)
+    fn list_id(&mut self, state: &Entity<ListState>) -> ElementId {
+        *self
+            .element_id
+            .get_or_insert_with(|| ElementId::stable(format!("list:{:?}", state.id())))
+    }
 
-                    // Create handler for action button
-                    let action_handlers = Rc::new(RefCell::new(EventHandlers::new()));
-                    let on_action = action.on_click.clone();
-                    let item_idx = index;
-                    action_handlers.borrow_mut().on_click = Some(Box::new(move |_btn, _click_type, _pos, _local, _modifiers| {
-                        (on_action.borrow_mut())(item_idx);
-                    }));
+    /// Register keyboard navigation for the focused list
+    fn register_key_handler(&self, list_id: ElementId, state_entity: Entity<ListState>) {
+        let titles: Vec<String> = self.items.iter().map(|item| item.title.clone()).collect();
+        let disabled: Vec<bool> = self.items.iter().map(|item| item.disabled).collect();
+        let last = disabled.len().saturating_sub(1);
+        let selection_mode = self.selection_mode;
+        let on_item_click = self.item_click_handler();
+        let on_selection_change = self.on_selection_change.clone();
+        let scroll_state = self.scroll_state.clone();
+
+        let handlers = EventHandlers::new().on_key_down(move |key, modifiers, character, _| {
+            let mut activated = None;
+            let mut moved_to = None;
+
+            update_entity(&state_entity, |s| {
+                let current = s.highlighted;
+                let target = match key {
+                    Key::Down => nearest_enabled(&disabled, current.map_or(0, |h| h + 1), true),
+                    Key::Up => nearest_enabled(
+                        &disabled,
+                        current.map_or(last, |h| h.saturating_sub(1)),
+                        false,
+                    ),
+                    Key::Home => nearest_enabled(&disabled, 0, true),
+                    Key::End => nearest_enabled(&disabled, last, false),
+                    Key::PageDown => {
+                        nearest_enabled(&disabled, current.map_or(0, |h| h + s.page_size), true)
+                    }
+                    Key::PageUp => nearest_enabled(
+                        &disabled,
+                        current.map_or(0, |h| h.saturating_sub(s.page_size)),
+                        false,
+                    ),
+                    Key::Return => {
+                        if let Some(index) = current.filter(|&h| disabled.get(h) == Some(&false)) {
+                            s.toggle_selection(index, selection_mode);
+                            activated = Some(index);
+                        }
+                        None
+                    }
+                    _ => match character {
+                        Some(c) if !modifiers.cmd && !modifiers.ctrl && c.is_alphanumeric() => {
+                            let query = s.push_typeahead(c, frame_time()).to_string();
+                            // Repeating one letter cycles through the rows
+                            // starting with it
+                            let first = query.chars().next().unwrap_or(c);
+                            let (query, start) = if query.chars().all(|q| q == first) {
+                                (&query[..first.len_utf8()], current.map_or(0, |h| h + 1))
+                            } else {
+                                (&query[..], current.unwrap_or(0))
+                            };
+                            typeahead_match(&titles, &disabled, query, start)
+                        }
+                        _ => None,
+                    },
+                };
+
+                if let Some(index) = target {
+                    s.highlighted = Some(index);
+                    moved_to = Some(index);
+                }
+            });
 
-                    // Register action button for interaction (higher z-index to be on top)
-                    register_element(action_id, action_handlers);
-                    ctx.register_hit_test(action_id, button_bounds, 1);
+            if let Some(index) = moved_to {
+                let row = row_id(list_id, index);
+                match &scroll_state {
+                    Some(scroll) => {
+                        update_entity(scroll, |s| {
+                            s.scroll_into_view(row, ScrollAlignment::Nearest, true)
+                        });
+                    }
+                    None => request_scroll_into_view(row, ScrollAlignment::Nearest),
+                }
+            }
 
-                    current_x += button_width + action_gap;
+            if let Some(index) = activated {
+                if let Some(callback) = &on_selection_change
+                    && let Some(selected) = read_entity(&state_entity, |s| s.selected.clone())
+                {
+                    (callback.borrow_mut())(&selected);
+                }
+                if let Some(callback) = &on_item_click {
+                    (callback.borrow_mut())(index);
                 }
             }
+        });
 
-            // Register element for interaction and hit testing
-            register_element(item_element.element_id, item_element.handlers.clone());
-            ctx.register_hit_test(item_element.element_id, absolute_bounds, 0);
-        }
+        register_element(list_id, Rc::new(RefCell::new(handlers)));
     }
 }
 
@@ -928,3 +1730,81 @@ impl From<(&str, &str)> for ListItemData {
         ListItemData::new(title).subtitle(subtitle)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn titles() -> Vec<String> {
+        ["Apple", "Apricot", "Banana", "Blueberry", "Cherry"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_nearest_enabled_skips_disabled_rows() {
+        let disabled = [false, true, true, false];
+        assert_eq!(nearest_enabled(&disabled, 1, true), Some(3));
+        assert_eq!(nearest_enabled(&disabled, 2, false), Some(0));
+        // Past the end clamps, and falls back the other way if needed
+        assert_eq!(nearest_enabled(&disabled, 10, true), Some(3));
+        assert_eq!(nearest_enabled(&[true, false, true], 2, true), Some(1));
+        assert_eq!(nearest_enabled(&[], 0, true), None);
+    }
+
+    #[test]
+    fn test_typeahead_match_wraps_and_ignores_case() {
+        let titles = titles();
+        let disabled = [false; 5];
+        assert_eq!(typeahead_match(&titles, &disabled, "b", 0), Some(2));
+        assert_eq!(typeahead_match(&titles, &disabled, "bl", 0), Some(3));
+        assert_eq!(typeahead_match(&titles, &disabled, "a", 3), Some(0));
+        assert_eq!(typeahead_match(&titles, &disabled, "kiwi", 0), None);
+
+        let disabled = [true, false, false, false, false];
+        assert_eq!(typeahead_match(&titles, &disabled, "ap", 0), Some(1));
+    }
+
+    #[test]
+    fn test_index_path_skips_empty_sections() {
+        // Sections of 2, 0 and 3 rows
+        let starts = [0, 2, 2];
+        assert_eq!(index_path(&starts, 1), IndexPath { section: 0, row: 1 });
+        assert_eq!(index_path(&starts, 2), IndexPath { section: 2, row: 0 });
+        assert_eq!(index_path(&starts, 4), IndexPath { section: 2, row: 2 });
+        // Ungrouped lists are one section
+        assert_eq!(index_path(&[], 3), IndexPath { section: 0, row: 3 });
+    }
+
+    #[test]
+    fn test_grouped_list_flattens_sections() {
+        let list = List::grouped([
+            ListSection::new("Fruit").items(["Apple", "Banana"]),
+            ListSection::new("Empty"),
+            ListSection::new("Nuts").item("Cashew"),
+        ]);
+        let titles: Vec<&str> = list.items.iter().map(|i| i.title.as_str()).collect();
+        assert_eq!(titles, vec!["Apple", "Banana", "Cashew"]);
+        let starts: Vec<usize> = list.sections.iter().map(|s| s.start).collect();
+        assert_eq!(starts, vec![0, 2, 2]);
+    }
+
+    #[test]
+    fn test_sticky_header_pins_until_pushed_out() {
+        // Header at 100, 20 tall, section rows ending at 300
+        assert_eq!(sticky_header_top(100.0, 20.0, 50.0, 300.0), 100.0);
+        assert_eq!(sticky_header_top(100.0, 20.0, 150.0, 300.0), 150.0);
+        assert_eq!(sticky_header_top(100.0, 20.0, 290.0, 300.0), 280.0);
+        // Never drawn above where it's laid out
+        assert_eq!(sticky_header_top(100.0, 20.0, 400.0, 110.0), 100.0);
+    }
+
+    #[test]
+    fn test_typeahead_query_resets_after_pause() {
+        let mut state = ListState::new();
+        assert_eq!(state.push_typeahead('B', 10.0), "b");
+        assert_eq!(state.push_typeahead('l', 10.5), "bl");
+        assert_eq!(state.push_typeahead('c', 12.0), "c");
+    }
+}
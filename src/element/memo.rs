@@ -0,0 +1,446 @@
+//! Memo wrapper - reuses a subtree's output while its inputs are unchanged
+
+use crate::{
+    element::{Element, LayoutContext},
+    entity::{EntityId, context::try_with_entity_store},
+    geometry::Rect,
+    layout_engine::TaffyLayoutEngine,
+    render::{DrawCommand, PaintContext},
+};
+use glam::Vec2;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use taffy::prelude::*;
+
+thread_local! {
+    static MEMO_CACHE: RefCell<MemoCache> = RefCell::new(MemoCache::default());
+}
+
+/// Wrap the subtree built by `build` so its output is reused across frames
+///
+/// While `key` hashes the same and no entity observed while building the
+/// subtree has changed, its cached size and draw commands are re-emitted
+/// without calling `build`, laying it out or painting it. Put anything else
+/// the subtree depends on, such as data read outside the entity store or
+/// the width it's laid out at, in the key.
+///
+/// Subtrees that register hit tests, use z-index groups, draw carets or
+/// overlays, capture images or animate are rebuilt every frame, since their
+/// draw commands alone don't reproduce them. Memoize static content such as
+/// long documents or charts.
+///
+/// Keys only need to be unique per call site: the caller's source location
+/// is part of the cache key, so `memo(row, ..)` in two places doesn't share
+/// output. A helper that calls `memo` should be `#[track_caller]` too if it's
+/// used from several places with the same keys.
+///
+/// ```ignore
+/// memo((document.revision, width), || render_document(&document))
+/// ```
+#[track_caller]
+pub fn memo<K: Hash, E: Element, F: FnOnce() -> E>(key: K, build: F) -> Memoized<E, F> {
+    let mut hasher = DefaultHasher::new();
+    std::panic::Location::caller().hash(&mut hasher);
+    key.hash(&mut hasher);
+    Memoized {
+        key: hasher.finish(),
+        build: Some(build),
+        child: None,
+        dependencies: Vec::new(),
+    }
+}
+
+/// An element wrapper that caches its subtree's layout and paint output
+pub struct Memoized<E, F> {
+    key: u64,
+    build: Option<F>,
+    /// The subtree, if it was built this frame
+    child: Option<E>,
+    /// Entities observed while building the subtree, with the versions read
+    dependencies: Vec<(EntityId, u64)>,
+}
+
+impl<E: Element, F: FnOnce() -> E> Memoized<E, F> {
+    /// Paint `child` at `bounds`, caching its output if its commands alone
+    /// reproduce it
+    fn paint_and_cache(&mut self, child: &mut E, bounds: Rect, ctx: &mut PaintContext) {
+        let transform = ctx.draw_list.current_transform();
+        let screen_bounds = transform.apply_rect(bounds);
+        let visible = visible_part(
+            screen_bounds,
+            *ctx.draw_list.viewport(),
+            ctx.draw_list.current_clip().copied(),
+        );
+        let mark = ctx.draw_list.mark();
+        let hit_targets = hit_target_count(ctx);
+
+        try_with_entity_store(|store| store.begin_recording());
+        child.paint(bounds, ctx);
+        let painted_dependencies =
+            try_with_entity_store(|store| store.end_recording()).unwrap_or_default();
+        for dependency in painted_dependencies {
+            if !self.dependencies.iter().any(|(id, _)| *id == dependency.0) {
+                self.dependencies.push(dependency);
+            }
+        }
+
+        let commands = ctx
+            .draw_list
+            .plain_commands_since(mark)
+            .filter(|_| hit_target_count(ctx) == hit_targets)
+            .map(<[DrawCommand]>::to_vec);
+        MEMO_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            match commands {
+                Some(commands) => {
+                    let entry = MemoEntry {
+                        size: bounds.size,
+                        scale_factor: ctx.scale_factor,
+                        dependencies: std::mem::take(&mut self.dependencies),
+                        screen_bounds,
+                        scale: transform.scale,
+                        visible,
+                        commands,
+                    };
+                    cache.entries.insert(self.key, entry);
+                }
+                None => {
+                    cache.entries.remove(&self.key);
+                }
+            }
+        });
+    }
+
+    /// Build the subtree and lay it out on its own at `bounds`, for when the
+    /// cached output can't be replayed there
+    fn paint_detached(&mut self, bounds: Rect, ctx: &mut PaintContext) {
+        let Some(build) = self.build.take() else {
+            return;
+        };
+
+        try_with_entity_store(|store| store.begin_recording());
+        let mut child = build();
+        let mut engine = TaffyLayoutEngine::new();
+        let node = child.layout(&mut LayoutContext {
+            engine: &mut engine,
+            text_system: &mut *ctx.text_system,
+            scale_factor: ctx.scale_factor,
+        });
+        let available_space = Size {
+            width: AvailableSpace::Definite(bounds.size.x),
+            height: AvailableSpace::Definite(bounds.size.y),
+        };
+        let computed =
+            engine.compute_layout(node, available_space, ctx.text_system, ctx.scale_factor);
+        self.dependencies =
+            try_with_entity_store(|store| store.end_recording()).unwrap_or_default();
+        if computed.is_err() {
+            return;
+        }
+
+        let child_bounds = Rect::from_pos_size(bounds.pos, engine.layout_bounds(node).size);
+        let mut detached_ctx = PaintContext {
+            draw_list: &mut *ctx.draw_list,
+            text_system: &mut *ctx.text_system,
+            layout_engine: &engine,
+            scale_factor: ctx.scale_factor,
+            parent_offset: ctx.parent_offset,
+            hit_test_builder: ctx.hit_test_builder.clone(),
+        };
+        self.paint_and_cache(&mut child, child_bounds, &mut detached_ctx);
+
+        // The new size is laid out from the next frame
+        if child_bounds.size != bounds.size {
+            ctx.request_animation_frame();
+        }
+    }
+}
+
+impl<E: Element, F: FnOnce() -> E> Element for Memoized<E, F> {
+    fn layout(&mut self, ctx: &mut LayoutContext) -> NodeId {
+        let cached = MEMO_CACHE.with(|cache| {
+            let cache = cache.borrow();
+            let entry = cache.entries.get(&self.key)?;
+            entry.is_fresh(ctx.scale_factor, entity_version).then(|| {
+                let ids: Vec<EntityId> = entry.dependencies.iter().map(|(id, _)| *id).collect();
+                (entry.size, ids)
+            })
+        });
+
+        if let Some((size, dependencies)) = cached {
+            // Keep observing the entities the output was built from, so
+            // changes to them still request a frame
+            try_with_entity_store(|store| {
+                for id in dependencies {
+                    store.observe_id(id);
+                }
+            });
+            return ctx.request_layout(Style {
+                size: Size {
+                    width: length(size.x),
+                    height: length(size.y),
+                },
+                flex_shrink: 0.0,
+                ..Default::default()
+            });
+        }
+
+        let Some(build) = self.build.take() else {
+            return ctx.request_layout(Style::default());
+        };
+        try_with_entity_store(|store| store.begin_recording());
+        let mut child = build();
+        let node = child.layout(ctx);
+        self.dependencies =
+            try_with_entity_store(|store| store.end_recording()).unwrap_or_default();
+        self.child = Some(child);
+        node
+    }
+
+    fn paint(&mut self, bounds: Rect, ctx: &mut PaintContext) {
+        MEMO_CACHE.with(|cache| cache.borrow_mut().painted.insert(self.key));
+
+        if let Some(mut child) = self.child.take() {
+            self.paint_and_cache(&mut child, bounds, ctx);
+            self.child = Some(child);
+            return;
+        }
+
+        let transform = ctx.draw_list.current_transform();
+        let screen_bounds = transform.apply_rect(bounds);
+        let visible = visible_part(
+            screen_bounds,
+            *ctx.draw_list.viewport(),
+            ctx.draw_list.current_clip().copied(),
+        );
+        let replayed = MEMO_CACHE.with(|cache| {
+            cache
+                .borrow()
+                .entries
+                .get(&self.key)
+                .and_then(|entry| entry.replay(screen_bounds, transform.scale, visible))
+        });
+        match replayed {
+            Some(commands) => ctx.draw_list.commands_mut().extend(commands),
+            None => self.paint_detached(bounds, ctx),
+        }
+    }
+}
+
+/// Drop cached output for memoized subtrees that weren't painted since the
+/// last call
+///
+/// Called by the layer manager once every layer has rendered.
+pub(crate) fn end_memo_frame() {
+    MEMO_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let painted = std::mem::take(&mut cache.painted);
+        cache.entries.retain(|key, _| painted.contains(key));
+    });
+}
+
+#[derive(Default)]
+struct MemoCache {
+    entries: HashMap<u64, MemoEntry>,
+    /// Keys painted since the cache was last trimmed
+    painted: HashSet<u64>,
+}
+
+/// A memoized subtree's output from the frame it was last built in
+struct MemoEntry {
+    /// The size the subtree was laid out at
+    size: Vec2,
+    scale_factor: f32,
+    /// Entities the subtree observed, with the versions it read
+    dependencies: Vec<(EntityId, u64)>,
+    /// Where the commands were painted, in screen coordinates
+    screen_bounds: Rect,
+    /// Scale of the transform they were painted through
+    scale: f32,
+    /// The part of `screen_bounds` that was visible, relative to its
+    /// origin; content outside it may have been culled
+    visible: Option<Rect>,
+    commands: Vec<DrawCommand>,
+}
+
+impl MemoEntry {
+    /// Whether the subtree would be built the same way now
+    fn is_fresh(&self, scale_factor: f32, version: impl Fn(EntityId) -> Option<u64>) -> bool {
+        self.scale_factor == scale_factor
+            && self
+                .dependencies
+                .iter()
+                .all(|&(id, read)| version(id) == Some(read))
+    }
+
+    /// The cached commands moved to `screen_bounds`, if they reproduce the
+    /// subtree painted there with `visible` showing
+    fn replay(
+        &self,
+        screen_bounds: Rect,
+        scale: f32,
+        visible: Option<Rect>,
+    ) -> Option<Vec<DrawCommand>> {
+        if self.scale != scale || self.screen_bounds.size != screen_bounds.size {
+            return None;
+        }
+        let fully_painted =
+            self.visible == Some(Rect::from_pos_size(Vec2::ZERO, screen_bounds.size));
+        if !fully_painted && self.visible != visible {
+            return None;
+        }
+
+        let offset = screen_bounds.pos - self.screen_bounds.pos;
+        let mut commands = self.commands.clone();
+        for command in &mut commands {
+            command.translate(offset);
+        }
+        Some(commands)
+    }
+}
+
+/// The part of `screen_bounds` inside the viewport and clip, relative to
+/// its origin
+fn visible_part(screen_bounds: Rect, viewport: Option<Rect>, clip: Option<Rect>) -> Option<Rect> {
+    let mut visible = screen_bounds;
+    for limit in [viewport, clip].into_iter().flatten() {
+        visible = visible.intersect(&limit)?;
+    }
+    Some(Rect::from_pos_size(
+        visible.pos - screen_bounds.pos,
+        visible.size,
+    ))
+}
+
+/// The version reads of an entity see, if there's a store and it's alive
+fn entity_version(id: EntityId) -> Option<u64> {
+    try_with_entity_store(|store| store.version(id)).flatten()
+}
+
+/// How many hit targets and scroll targets have been registered
+fn hit_target_count(ctx: &PaintContext) -> Option<usize> {
+    ctx.hit_test_builder.as_ref().map(|builder| {
+        let builder = builder.borrow();
+        builder.entries().len() + builder.scroll_targets().len()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        color::colors,
+        element::{column, container},
+        entity::EntityStore,
+        image::RgbaImage,
+        layer::{LayerManager, LayerOptions},
+        renderer::FrameInfo,
+        software_renderer::SoftwareRenderer,
+        text_system::TextSystem,
+    };
+
+    fn entry(visible: Option<Rect>) -> MemoEntry {
+        MemoEntry {
+            size: Vec2::new(100.0, 50.0),
+            scale_factor: 2.0,
+            dependencies: vec![(EntityId::new(0, 0), 3)],
+            screen_bounds: Rect::new(10.0, 10.0, 100.0, 50.0),
+            scale: 1.0,
+            visible,
+            commands: vec![DrawCommand::Rect {
+                rect: Rect::new(10.0, 10.0, 100.0, 50.0),
+                color: colors::WHITE,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_entry_freshness() {
+        let entry = entry(None);
+        assert!(entry.is_fresh(2.0, |_| Some(3)));
+        assert!(!entry.is_fresh(2.0, |_| Some(4)));
+        assert!(!entry.is_fresh(2.0, |_| None));
+        assert!(!entry.is_fresh(1.0, |_| Some(3)));
+    }
+
+    #[test]
+    fn test_replay_moves_commands() {
+        let full = Some(Rect::new(0.0, 0.0, 100.0, 50.0));
+        let entry = entry(full);
+        let moved = Rect::new(30.0, 40.0, 100.0, 50.0);
+        let commands = entry.replay(moved, 1.0, Some(Rect::new(0.0, 0.0, 20.0, 50.0)));
+        match commands.as_deref() {
+            Some([DrawCommand::Rect { rect, .. }]) => assert_eq!(*rect, moved),
+            _ => panic!("expected the cached rect"),
+        }
+
+        // A different size or scale can't reuse the commands
+        assert!(
+            entry
+                .replay(Rect::new(30.0, 40.0, 90.0, 50.0), 1.0, full)
+                .is_none()
+        );
+        assert!(entry.replay(moved, 2.0, full).is_none());
+    }
+
+    #[test]
+    fn test_partly_culled_output_replays_only_where_painted() {
+        let visible = Some(Rect::new(0.0, 0.0, 40.0, 50.0));
+        let entry = entry(visible);
+        let bounds = Rect::new(10.0, 10.0, 100.0, 50.0);
+        assert!(entry.replay(bounds, 1.0, visible).is_some());
+        assert!(
+            entry
+                .replay(bounds, 1.0, Some(Rect::new(0.0, 0.0, 60.0, 50.0)))
+                .is_none()
+        );
+
+        assert_eq!(
+            visible_part(bounds, Some(Rect::new(0.0, 0.0, 50.0, 500.0)), None),
+            visible
+        );
+        assert_eq!(
+            visible_part(bounds, None, Some(Rect::new(200.0, 0.0, 10.0, 10.0))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_call_sites_with_equal_keys_keep_separate_output() {
+        let mut layers = LayerManager::new();
+        layers.add_ui_layer(0, LayerOptions::default(), || {
+            Box::new(
+                column()
+                    .child(memo(0, || {
+                        container().size(10.0, 10.0).background(colors::RED)
+                    }))
+                    .child(memo(0, || {
+                        container().size(10.0, 10.0).background(colors::BLUE)
+                    })),
+            )
+        });
+
+        let mut renderer = SoftwareRenderer::new();
+        let mut text_system = TextSystem::headless();
+        let mut entity_store = EntityStore::new();
+        // The second frame replays the cached output
+        for _ in 0..2 {
+            let mut target = RgbaImage::new(1, 1);
+            layers.invalidate_all();
+            layers.render(
+                &mut renderer,
+                &mut target,
+                FrameInfo {
+                    size: Vec2::new(20.0, 20.0),
+                    scale_factor: 1.0,
+                    elapsed_time: 0.0,
+                },
+                &mut text_system,
+                &mut entity_store,
+            );
+            assert_eq!(target.pixel(5, 5), Some([255, 0, 0, 255]));
+            assert_eq!(target.pixel(5, 15), Some([0, 0, 255, 255]));
+        }
+    }
+}
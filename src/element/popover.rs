@@ -0,0 +1,137 @@
+//! Popover positioning shared by elements that open floating panels
+//!
+//! Computes where a floating panel (dropdown list, calendar, suggestions)
+//! should go relative to the element that opened it, flipping to the other
+//! side and shifting horizontally to stay inside the viewport.
+
+use crate::geometry::Rect;
+use glam::Vec2;
+
+/// Preferred side of the anchor to place a popover on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PopoverPlacement {
+    /// Below the anchor, flipping above if there is not enough room
+    #[default]
+    Below,
+    /// Above the anchor, flipping below if there is not enough room
+    Above,
+}
+
+/// Compute the bounds of a popover of `size` anchored to `anchor`
+///
+/// The popover is left-aligned with the anchor and separated from it by `gap`.
+/// When a viewport is given, the popover flips to the opposite side if it would
+/// overflow on the preferred side and the opposite side has more room, and is
+/// shifted horizontally to stay inside the viewport.
+pub fn place_popover(
+    anchor: Rect,
+    size: Vec2,
+    viewport: Option<Rect>,
+    placement: PopoverPlacement,
+    gap: f32,
+) -> Rect {
+    let below_y = anchor.pos.y + anchor.size.y + gap;
+    let above_y = anchor.pos.y - gap - size.y;
+
+    let Some(viewport) = viewport else {
+        let y = match placement {
+            PopoverPlacement::Below => below_y,
+            PopoverPlacement::Above => above_y,
+        };
+        return Rect::from_pos_size(Vec2::new(anchor.pos.x, y), size);
+    };
+
+    let viewport_max = viewport.pos + viewport.size;
+    let room_below = viewport_max.y - below_y;
+    let room_above = anchor.pos.y - gap - viewport.pos.y;
+
+    let y = match placement {
+        PopoverPlacement::Below if room_below < size.y && room_above > room_below => above_y,
+        PopoverPlacement::Below => below_y,
+        PopoverPlacement::Above if room_above < size.y && room_below > room_above => below_y,
+        PopoverPlacement::Above => above_y,
+    };
+
+    // Keep the popover horizontally inside the viewport where possible
+    let x = anchor
+        .pos
+        .x
+        .min(viewport_max.x - size.x)
+        .max(viewport.pos.x);
+
+    Rect::from_pos_size(Vec2::new(x, y), size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn viewport() -> Option<Rect> {
+        Some(Rect::new(0.0, 0.0, 400.0, 300.0))
+    }
+
+    #[test]
+    fn test_places_below_anchor() {
+        let anchor = Rect::new(10.0, 10.0, 100.0, 30.0);
+        let bounds = place_popover(
+            anchor,
+            Vec2::new(100.0, 80.0),
+            viewport(),
+            PopoverPlacement::Below,
+            2.0,
+        );
+        assert_eq!(bounds.pos, Vec2::new(10.0, 42.0));
+    }
+
+    #[test]
+    fn test_flips_above_when_no_room_below() {
+        let anchor = Rect::new(10.0, 250.0, 100.0, 30.0);
+        let bounds = place_popover(
+            anchor,
+            Vec2::new(100.0, 80.0),
+            viewport(),
+            PopoverPlacement::Below,
+            2.0,
+        );
+        assert_eq!(bounds.pos, Vec2::new(10.0, 168.0));
+    }
+
+    #[test]
+    fn test_stays_below_when_above_is_tighter() {
+        let anchor = Rect::new(10.0, 20.0, 100.0, 30.0);
+        let bounds = place_popover(
+            anchor,
+            Vec2::new(100.0, 400.0),
+            viewport(),
+            PopoverPlacement::Below,
+            2.0,
+        );
+        assert_eq!(bounds.pos.y, 52.0);
+    }
+
+    #[test]
+    fn test_shifts_inside_viewport_horizontally() {
+        let anchor = Rect::new(350.0, 10.0, 40.0, 30.0);
+        let bounds = place_popover(
+            anchor,
+            Vec2::new(120.0, 80.0),
+            viewport(),
+            PopoverPlacement::Below,
+            0.0,
+        );
+        assert_eq!(bounds.pos.x, 280.0);
+    }
+
+    #[test]
+    fn test_without_viewport_uses_preferred_side() {
+        let anchor = Rect::new(0.0, 100.0, 50.0, 20.0);
+        let bounds = place_popover(
+            anchor,
+            Vec2::new(50.0, 40.0),
+            None,
+            PopoverPlacement::Above,
+            4.0,
+        );
+        assert_eq!(bounds.pos, Vec2::new(0.0, 56.0));
+    }
+}
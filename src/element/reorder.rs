@@ -0,0 +1,346 @@
+//! Drag-to-reorder shared by lists and containers
+//!
+//! A pressed item becomes a drag once the pointer moves past the drag
+//! threshold. While dragging, the item floats under the pointer, the other
+//! items slide apart to open a gap where it would land, and pointing near
+//! the edge of a clipping scroll container scrolls it. Releasing reports
+//! `(from, to)`: the item at `from` should be removed and inserted at `to`,
+//! as with `Vec::remove` followed by `Vec::insert`.
+
+use crate::{
+    entity::{Entity, update_entity},
+    geometry::Rect,
+    interaction::DRAG_THRESHOLD,
+    layer::{Modifiers, MouseButton},
+};
+use glam::Vec2;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Distance from the visible edge within which dragging scrolls
+const AUTO_SCROLL_EDGE: f32 = 32.0;
+/// How quickly the gap opens and closes; offsets cover 1/e of the remaining
+/// distance every 1/GAP_RATE seconds
+const GAP_RATE: f32 = 18.0;
+/// Z-index the dragged item floats at
+pub(crate) const REORDER_PREVIEW_Z: i32 = 100;
+/// Hit test z-index of the target catching moves during a drag
+pub(crate) const REORDER_DRAG_Z: i32 = 200;
+
+/// Called with `(from, to)` when an item is dropped somewhere new
+pub(crate) type ReorderCallback = Rc<RefCell<Box<dyn FnMut(usize, usize)>>>;
+
+/// An item being pressed or dragged
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ReorderDrag {
+    from: usize,
+    start: Vec2,
+    pointer: Vec2,
+    /// Pointer position relative to the item's top-left when pressed
+    grab: Vec2,
+    /// Where the item would land if dropped now
+    to: usize,
+    /// Whether the pointer has moved far enough to start dragging
+    active: bool,
+}
+
+/// Drag-to-reorder state, persisted via the Entity system
+#[derive(Debug, Clone, Default)]
+pub struct ReorderState {
+    drag: Option<ReorderDrag>,
+    /// How far each item is shifted along the main axis to open the gap
+    offsets: Vec<f32>,
+    /// Frame time the offsets were last stepped at
+    last_step: Option<f32>,
+    /// A drag just ended, so the click that ends it should be ignored
+    dropped: bool,
+}
+
+impl ReorderState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Index of the item being dragged, once the pointer has moved far enough
+    pub fn dragging(&self) -> Option<usize> {
+        self.drag.filter(|drag| drag.active).map(|drag| drag.from)
+    }
+
+    /// Where the dragged item would land if dropped now
+    pub fn target(&self) -> Option<usize> {
+        self.drag.filter(|drag| drag.active).map(|drag| drag.to)
+    }
+
+    /// Whether an item is pressed, whether or not it's being dragged yet
+    pub(crate) fn is_pressed(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// Press item `index` with the pointer at `position`, with the item's
+    /// top-left at `origin`, both in screen coordinates
+    pub(crate) fn press(&mut self, index: usize, position: Vec2, origin: Vec2) {
+        self.drag = Some(ReorderDrag {
+            from: index,
+            start: position,
+            pointer: position,
+            grab: position - origin,
+            to: index,
+            active: false,
+        });
+        self.dropped = false;
+    }
+
+    /// Move the pointer during a press or drag
+    pub(crate) fn move_to(&mut self, position: Vec2) {
+        if let Some(drag) = &mut self.drag {
+            drag.pointer = position;
+            drag.active |= (position - drag.start).length() >= DRAG_THRESHOLD;
+        }
+    }
+
+    /// End the press, returning `(from, to)` if the item was dragged somewhere new
+    pub(crate) fn release(&mut self) -> Option<(usize, usize)> {
+        let drag = self.drag.take()?;
+        self.offsets.clear();
+        self.last_step = None;
+        if !drag.active {
+            return None;
+        }
+        self.dropped = true;
+        (drag.from != drag.to).then_some((drag.from, drag.to))
+    }
+
+    /// Check and reset whether a drag just ended
+    pub(crate) fn take_dropped(&mut self) -> bool {
+        std::mem::take(&mut self.dropped)
+    }
+
+    /// Top-left of the floating item, in screen coordinates
+    pub(crate) fn preview_origin(&self) -> Option<Vec2> {
+        self.drag
+            .filter(|drag| drag.active)
+            .map(|drag| drag.pointer - drag.grab)
+    }
+
+    /// How far item `index` is shifted along the main axis, in screen points
+    pub(crate) fn offset(&self, index: usize) -> f32 {
+        self.offsets.get(index).copied().unwrap_or(0.0)
+    }
+
+    /// Update the drop target and ease the gap towards it
+    ///
+    /// `items` are the start and length of each item along the main axis, in
+    /// screen coordinates and before any shifting. Returns whether the gap is
+    /// still moving and needs another frame.
+    pub(crate) fn step(&mut self, items: &[(f32, f32)], horizontal: bool, now: f32) -> bool {
+        let Some(drag) = self.drag.as_mut().filter(|drag| drag.active) else {
+            return false;
+        };
+        let Some(&(_, length)) = items.get(drag.from) else {
+            // The items changed under the drag
+            self.drag = None;
+            self.offsets.clear();
+            return false;
+        };
+
+        let leading = along(drag.pointer - drag.grab, horizontal);
+        drag.to = insertion_index(items, drag.from, leading + length / 2.0);
+        let extent = length + spacing(items);
+
+        let dt = self.last_step.map_or(0.0, |last| (now - last).max(0.0));
+        self.last_step = Some(now);
+        let blend = 1.0 - (-dt * GAP_RATE).exp();
+
+        self.offsets.resize(items.len(), 0.0);
+        let mut moving = false;
+        for (index, offset) in self.offsets.iter_mut().enumerate() {
+            let target = gap_offset(index, drag.from, drag.to, extent);
+            *offset += (target - *offset) * blend;
+            if (target - *offset).abs() < 0.5 {
+                *offset = target;
+            } else {
+                moving = true;
+            }
+        }
+        moving
+    }
+
+    /// The item to scroll into view while the pointer is near an edge of
+    /// `visible`, in screen coordinates
+    pub(crate) fn auto_scroll_target(
+        &self,
+        visible: Rect,
+        count: usize,
+        horizontal: bool,
+    ) -> Option<usize> {
+        let drag = self.drag.filter(|drag| drag.active)?;
+        let pointer = along(drag.pointer, horizontal);
+        let start = along(visible.pos, horizontal);
+        let end = start + along(visible.size, horizontal);
+        if pointer < start + AUTO_SCROLL_EDGE {
+            drag.to.checked_sub(1)
+        } else if pointer > end - AUTO_SCROLL_EDGE {
+            Some(drag.to + 1).filter(|&next| next < count)
+        } else {
+            None
+        }
+    }
+}
+
+/// The component of `v` along the main axis
+pub(crate) fn along(v: Vec2, horizontal: bool) -> f32 {
+    if horizontal { v.x } else { v.y }
+}
+
+/// A vector of length `amount` along the main axis
+pub(crate) fn axis_vec(amount: f32, horizontal: bool) -> Vec2 {
+    if horizontal {
+        Vec2::new(amount, 0.0)
+    } else {
+        Vec2::new(0.0, amount)
+    }
+}
+
+/// Where an item centered at `center` lands: the number of other items
+/// centered before it
+fn insertion_index(items: &[(f32, f32)], from: usize, center: f32) -> usize {
+    items
+        .iter()
+        .enumerate()
+        .filter(|&(index, &(start, length))| index != from && start + length / 2.0 < center)
+        .count()
+}
+
+/// Space between consecutive items
+fn spacing(items: &[(f32, f32)]) -> f32 {
+    match items {
+        [(first, length), (second, _), ..] => (second - first - length).max(0.0),
+        _ => 0.0,
+    }
+}
+
+/// How far item `index` moves to open a gap at `to` for the item from `from`
+fn gap_offset(index: usize, from: usize, to: usize, extent: f32) -> f32 {
+    if from < index && index <= to {
+        -extent
+    } else if to <= index && index < from {
+        extent
+    } else {
+        0.0
+    }
+}
+
+/// Mouse down handler pressing item `index`
+///
+/// With `grip`, only presses within that distance of the item's leading
+/// edge start a drag.
+pub(crate) fn press_handler<T: 'static>(
+    state: Entity<T>,
+    reorder: fn(&mut T) -> &mut ReorderState,
+    index: usize,
+    grip: Option<(f32, bool)>,
+) -> impl FnMut(MouseButton, Vec2, Vec2, Modifiers, u32) + 'static {
+    move |button, position, local_position, _, _| {
+        if button != MouseButton::Left {
+            return;
+        }
+        if grip.is_some_and(|(width, horizontal)| along(local_position, horizontal) > width) {
+            return;
+        }
+        update_entity(&state, |s| {
+            reorder(s).press(index, position, position - local_position)
+        });
+    }
+}
+
+/// Mouse up handler ending a press, calling `on_move` after a drag
+pub(crate) fn release_handler<T: 'static>(
+    state: Entity<T>,
+    reorder: fn(&mut T) -> &mut ReorderState,
+    on_move: ReorderCallback,
+) -> impl FnMut(MouseButton, Vec2, Vec2, Modifiers) + 'static {
+    move |button, _, _, _| {
+        if button != MouseButton::Left {
+            return;
+        }
+        if let Some((from, to)) = update_entity(&state, |s| reorder(s).release()).flatten() {
+            (on_move.borrow_mut())(from, to);
+        }
+    }
+}
+
+/// Mouse move handler following the pointer during a press
+pub(crate) fn drag_handler<T: 'static>(
+    state: Entity<T>,
+    reorder: fn(&mut T) -> &mut ReorderState,
+) -> impl FnMut(Vec2, Vec2) + 'static {
+    move |position, _| {
+        update_entity(&state, |s| reorder(s).move_to(position));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Four 40pt rows with 10pt between them
+    fn rows() -> Vec<(f32, f32)> {
+        (0..4).map(|i| (i as f32 * 50.0, 40.0)).collect()
+    }
+
+    #[test]
+    fn test_drag_down_opens_gap_below() {
+        let mut state = ReorderState::new();
+        state.press(0, Vec2::new(10.0, 20.0), Vec2::ZERO);
+        state.move_to(Vec2::new(10.0, 22.0));
+        // Still under the drag threshold
+        assert_eq!(state.dragging(), None);
+
+        // Center the row between rows 2 and 3
+        state.move_to(Vec2::new(10.0, 140.0));
+        state.step(&rows(), false, 0.0);
+        assert_eq!(state.target(), Some(2));
+        // Let the gap finish opening
+        while state.step(&rows(), false, 1.0) {}
+        assert_eq!(state.offset(1), -50.0);
+        assert_eq!(state.offset(2), -50.0);
+        assert_eq!(state.offset(3), 0.0);
+
+        assert_eq!(state.release(), Some((0, 2)));
+        assert!(state.take_dropped());
+        assert_eq!(state.offset(1), 0.0);
+    }
+
+    #[test]
+    fn test_drag_up_and_back_reports_nothing() {
+        let mut state = ReorderState::new();
+        state.press(3, Vec2::new(10.0, 170.0), Vec2::new(0.0, 150.0));
+        state.move_to(Vec2::new(10.0, 15.0));
+        state.step(&rows(), false, 0.0);
+        assert_eq!(state.target(), Some(0));
+
+        state.move_to(Vec2::new(10.0, 172.0));
+        state.step(&rows(), false, 0.1);
+        assert_eq!(state.target(), Some(3));
+        assert_eq!(state.release(), None);
+
+        // A press without a drag isn't a drop
+        state.press(1, Vec2::new(10.0, 60.0), Vec2::new(0.0, 50.0));
+        assert_eq!(state.release(), None);
+        assert!(!state.take_dropped());
+    }
+
+    #[test]
+    fn test_auto_scroll_near_edges() {
+        let mut state = ReorderState::new();
+        let visible = Rect::new(0.0, 0.0, 200.0, 100.0);
+        state.press(1, Vec2::new(10.0, 60.0), Vec2::new(0.0, 50.0));
+        state.move_to(Vec2::new(10.0, 95.0));
+        state.step(&rows(), false, 0.0);
+        assert_eq!(state.auto_scroll_target(visible, 4, false), Some(2));
+
+        state.move_to(Vec2::new(10.0, 50.0));
+        state.step(&rows(), false, 0.0);
+        assert_eq!(state.auto_scroll_target(visible, 4, false), None);
+    }
+}
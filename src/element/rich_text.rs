@@ -0,0 +1,445 @@
+//! Rich text element - a paragraph of styled spans with inline links
+
+use crate::{
+    color::{Color, colors},
+    element::{Element, LayoutContext, PaintContext},
+    geometry::Rect,
+    interaction::{
+        ElementId, EventHandlers,
+        registry::{get_element_state, register_element},
+    },
+    layer::{ClickType, Modifiers, MouseButton},
+    render::PaintText,
+    style::TextStyle,
+    text_system::{ShapedLine, ShapedText, TextConfig},
+};
+use glam::Vec2;
+use std::cell::RefCell;
+use std::ops::Range;
+use std::rc::Rc;
+use taffy::prelude::*;
+
+/// Create a rich text element; spans use `style` unless they override it
+pub fn rich_text(style: TextStyle) -> RichText {
+    RichText::new(style)
+}
+
+/// Create a plain span of text
+pub fn span(text: impl Into<String>) -> TextSpan {
+    TextSpan::new(text)
+}
+
+/// Create a link span, underlined on hover, that calls `handler` when clicked
+pub fn link<F>(text: impl Into<String>, mut handler: F) -> TextSpan
+where
+    F: FnMut() + 'static,
+{
+    span(text)
+        .color(colors::BLUE_500)
+        .hover_color(colors::BLUE_600)
+        .hover_underline()
+        .on_click(move |_, _, _, _, _| handler())
+}
+
+type ClickHandler = Box<dyn FnMut(MouseButton, ClickType, Vec2, Vec2, Modifiers)>;
+
+/// A run of text within a [`RichText`]
+///
+/// Spans share the paragraph's font and size, so they wrap together as one
+/// block of text; each can change its color and underline, and react to the
+/// mouse when given a click handler.
+pub struct TextSpan {
+    text: String,
+    color: Option<Color>,
+    underline: bool,
+    hover_color: Option<Color>,
+    hover_underline: bool,
+    on_click: Option<ClickHandler>,
+}
+
+impl TextSpan {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            color: None,
+            underline: false,
+            hover_color: None,
+            hover_underline: false,
+            on_click: None,
+        }
+    }
+
+    /// Set the text color
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Underline the span
+    pub fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    /// Set the text color while the mouse is over a clickable span
+    pub fn hover_color(mut self, color: Color) -> Self {
+        self.hover_color = Some(color);
+        self
+    }
+
+    /// Underline the span while the mouse is over it, if it's clickable
+    pub fn hover_underline(mut self) -> Self {
+        self.hover_underline = true;
+        self
+    }
+
+    /// Make the span clickable
+    /// Handler receives: (button, click_type, position, local_position, modifiers)
+    pub fn on_click<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(MouseButton, ClickType, Vec2, Vec2, Modifiers) + 'static,
+    {
+        self.on_click = Some(Box::new(handler));
+        self
+    }
+
+    /// The span's text
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+/// A wrapping paragraph made of [`TextSpan`]s
+pub struct RichText {
+    style: TextStyle,
+    spans: Vec<TextSpan>,
+    key: Option<String>,
+    /// Handlers for clickable spans, kept across paints of the same element
+    span_handlers: Vec<Option<Rc<RefCell<EventHandlers>>>>,
+}
+
+impl RichText {
+    pub fn new(style: TextStyle) -> Self {
+        Self {
+            style,
+            spans: Vec::new(),
+            key: None,
+            span_handlers: Vec::new(),
+        }
+    }
+
+    /// Set a unique key for this paragraph, used to identify its spans
+    ///
+    /// Defaults to the paragraph's text and position. Set a key to keep a
+    /// link's hover state while the paragraph moves, e.g. when scrolled.
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    /// Add a span
+    pub fn span(mut self, span: TextSpan) -> Self {
+        self.spans.push(span);
+        self
+    }
+
+    /// Add plain text with the paragraph's style
+    pub fn text(self, text: impl Into<String>) -> Self {
+        self.span(TextSpan::new(text))
+    }
+
+    /// The full text of all spans
+    pub fn content(&self) -> String {
+        self.spans.iter().map(|s| s.text.as_str()).collect()
+    }
+
+    fn span_id(&self, content: &str, origin: Vec2, index: usize) -> ElementId {
+        match &self.key {
+            Some(key) => ElementId::stable(format!("rich-text:{}:{}", key, index)),
+            // Paragraphs with the same text are told apart by where they are
+            None => ElementId::stable(format!(
+                "rich-text:{}@{},{}:{}",
+                content,
+                origin.x.round(),
+                origin.y.round(),
+                index
+            )),
+        }
+    }
+}
+
+impl Element for RichText {
+    fn layout(&mut self, ctx: &mut LayoutContext) -> NodeId {
+        ctx.request_text_layout(Style::default(), &self.content(), &self.style)
+    }
+
+    fn paint(&mut self, bounds: Rect, ctx: &mut PaintContext) {
+        if !ctx.is_visible(&bounds) {
+            return;
+        }
+
+        let content = self.content();
+        let config = TextConfig::from(&self.style);
+        // Layout rounds to whole pixels, so allow the same wrapping width
+        // the text was measured with
+        let Ok(shaped) = ctx.text_system.shape_text(
+            &content,
+            &config,
+            Some(bounds.size.x + 1.0),
+            ctx.scale_factor,
+        ) else {
+            return;
+        };
+
+        // Move click handlers into shared handlers once; they're reused if
+        // this element paints again
+        if self.span_handlers.len() != self.spans.len() {
+            self.span_handlers = self
+                .spans
+                .iter_mut()
+                .map(|span| {
+                    span.on_click.take().map(|handler| {
+                        let mut handlers = EventHandlers::new();
+                        handlers.on_click = Some(handler);
+                        Rc::new(RefCell::new(handlers))
+                    })
+                })
+                .collect();
+        }
+
+        // Resolve each span's color, underlining it and registering it for
+        // the mouse on each line it covers
+        let mut span_colors = Vec::with_capacity(self.spans.len());
+        let mut start = 0;
+        for (index, span) in self.spans.iter().enumerate() {
+            let range = start..start + span.text.len();
+            start = range.end;
+
+            let id = self.span_id(&content, bounds.pos, index);
+            let handlers = self.span_handlers[index].clone();
+            let clickable = handlers.is_some();
+            let hovered = match handlers {
+                Some(handlers) => {
+                    register_element(id, handlers);
+                    get_element_state(id).unwrap_or_default().is_hovered
+                }
+                None => false,
+            };
+
+            let color = hovered
+                .then_some(span.hover_color)
+                .flatten()
+                .or(span.color)
+                .unwrap_or(self.style.color);
+            let underline = span.underline || (hovered && span.hover_underline);
+
+            for (line, _, fragment) in span_fragments(&shaped, range.clone()) {
+                if underline {
+                    let rect = underline_rect(&shaped.lines[line], fragment);
+                    ctx.paint_solid_quad(
+                        Rect::from_pos_size(bounds.pos + rect.pos, rect.size),
+                        color,
+                    );
+                }
+                if clickable {
+                    let fragment = Rect::from_pos_size(bounds.pos + fragment.pos, fragment.size);
+                    ctx.register_hit_test(id, fragment, 0);
+                }
+            }
+            span_colors.push((range, color));
+        }
+
+        // Paint each line as a single run with the spans' colors as ranges,
+        // so kerning and ligatures carry across span boundaries
+        for line in &shaped.lines {
+            let Some((_, text_range, fragment)) = span_fragments(&shaped, line.text_range.clone())
+                .into_iter()
+                .next()
+            else {
+                continue;
+            };
+            let text = content[text_range.clone()].trim_end_matches('\n');
+            let text_range = text_range.start..text_range.start + text.len();
+            let style = line_color_ranges(&span_colors, text_range)
+                .into_iter()
+                .fold(self.style.clone(), |style, (range, color)| {
+                    style.color_range(range, color)
+                });
+            ctx.paint_text(PaintText {
+                position: bounds.pos + fragment.pos,
+                text: text.to_string(),
+                style,
+                measured_size: Some(fragment.size),
+            });
+        }
+    }
+}
+
+/// The parts of `spans` on a line covering `line` bytes, relative to the
+/// start of the line
+fn line_color_ranges(
+    spans: &[(Range<usize>, Color)],
+    line: Range<usize>,
+) -> Vec<(Range<usize>, Color)> {
+    spans
+        .iter()
+        .filter_map(|(range, color)| {
+            let start = range.start.max(line.start);
+            let end = range.end.min(line.end);
+            (start < end).then(|| (start - line.start..end - line.start, *color))
+        })
+        .collect()
+}
+
+/// Where to underline `fragment` of `line`, relative to the text origin
+fn underline_rect(line: &ShapedLine, fragment: Rect) -> Rect {
+    let metrics = &line.decoration;
+    Rect::new(
+        fragment.pos.x,
+        line.baseline - metrics.underline_offset,
+        fragment.size.x,
+        metrics.underline_size.max(1.0),
+    )
+}
+
+/// Split a span's byte range into one piece per line it covers
+///
+/// Returns the line index, the text range on that line and the area it
+/// covers, relative to the text origin.
+fn span_fragments(shaped: &ShapedText, range: Range<usize>) -> Vec<(usize, Range<usize>, Rect)> {
+    let mut fragments: Vec<(usize, Range<usize>, Rect)> = Vec::new();
+    let clusters = shaped
+        .clusters
+        .iter()
+        .filter(|c| c.text_range.start >= range.start && c.text_range.end <= range.end);
+
+    for cluster in clusters {
+        match fragments
+            .iter_mut()
+            .find(|(line, ..)| *line == cluster.line)
+        {
+            Some((_, text_range, bounds)) => {
+                text_range.start = text_range.start.min(cluster.text_range.start);
+                text_range.end = text_range.end.max(cluster.text_range.end);
+                let left = bounds.pos.x.min(cluster.bounds.pos.x);
+                let right = (bounds.pos.x + bounds.size.x)
+                    .max(cluster.bounds.pos.x + cluster.bounds.size.x);
+                bounds.pos.x = left;
+                bounds.size.x = right - left;
+            }
+            None => fragments.push((cluster.line, cluster.text_range.clone(), cluster.bounds)),
+        }
+    }
+    fragments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text_system::{DecorationMetrics, ShapedCluster};
+
+    /// "ab cd" wrapped after the space, 10px per character
+    fn shaped() -> ShapedText {
+        let cluster = |start: usize, x: f32, line: usize| ShapedCluster {
+            text_range: start..start + 1,
+            bounds: Rect::new(x, line as f32 * 20.0, 10.0, 20.0),
+            line,
+            is_rtl: false,
+        };
+        let line = |text_range: Range<usize>, top: f32| ShapedLine {
+            text_range,
+            top,
+            height: 20.0,
+            baseline: top + 15.0,
+            decoration: DecorationMetrics::default(),
+        };
+        ShapedText {
+            glyphs: vec![],
+            clusters: vec![
+                cluster(0, 0.0, 0),
+                cluster(1, 10.0, 0),
+                cluster(2, 20.0, 0),
+                cluster(3, 0.0, 1),
+                cluster(4, 10.0, 1),
+            ],
+            lines: vec![line(0..3, 0.0), line(3..5, 20.0)],
+            size: Vec2::new(30.0, 40.0),
+        }
+    }
+
+    #[test]
+    fn test_span_fragments_single_line() {
+        let fragments = span_fragments(&shaped(), 1..3);
+        assert_eq!(fragments.len(), 1);
+        assert_eq!(fragments[0].1, 1..3);
+        assert_eq!(fragments[0].2, Rect::new(10.0, 0.0, 20.0, 20.0));
+    }
+
+    #[test]
+    fn test_span_fragments_across_lines() {
+        let fragments = span_fragments(&shaped(), 1..5);
+        assert_eq!(fragments.len(), 2);
+        assert_eq!((fragments[0].0, fragments[0].1.clone()), (0, 1..3));
+        assert_eq!((fragments[1].0, fragments[1].1.clone()), (1, 3..5));
+        assert_eq!(fragments[1].2, Rect::new(0.0, 20.0, 20.0, 20.0));
+    }
+
+    #[test]
+    fn test_line_color_ranges() {
+        let spans = [
+            (0..4, colors::WHITE),
+            (4..12, colors::BLUE_500),
+            (12..13, colors::WHITE),
+        ];
+        // The link wraps after "the "; its second line starts at byte 8
+        assert_eq!(
+            line_color_ranges(&spans, 0..8),
+            vec![(0..4, colors::WHITE), (4..8, colors::BLUE_500)]
+        );
+        assert_eq!(
+            line_color_ranges(&spans, 8..13),
+            vec![(0..4, colors::BLUE_500), (4..5, colors::WHITE)]
+        );
+    }
+
+    #[test]
+    fn test_underline_rect_follows_line_metrics() {
+        let shaped = shaped();
+        let line = ShapedLine {
+            decoration: DecorationMetrics {
+                underline_offset: -2.0,
+                underline_size: 0.5,
+                ..DecorationMetrics::default()
+            },
+            ..shaped.lines[1].clone()
+        };
+        let (_, _, fragment) = span_fragments(&shaped, 3..5).remove(0);
+        assert_eq!(
+            underline_rect(&line, fragment),
+            Rect::new(0.0, 37.0, 20.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn test_span_ids_differ_by_position() {
+        let text = rich_text(TextStyle::default()).text("Same");
+        let a = text.span_id("Same", Vec2::new(0.0, 0.0), 0);
+        let b = text.span_id("Same", Vec2::new(0.0, 20.0), 0);
+        assert_ne!(a, b);
+
+        let keyed = rich_text(TextStyle::default()).with_key("intro");
+        assert_eq!(
+            keyed.span_id("Same", Vec2::ZERO, 0),
+            keyed.span_id("Same", Vec2::new(0.0, 20.0), 0)
+        );
+    }
+
+    #[test]
+    fn test_rich_text_content() {
+        let text = rich_text(TextStyle::default())
+            .text("See ")
+            .span(link("the docs", || {}))
+            .text(".");
+        assert_eq!(text.content(), "See the docs.");
+    }
+}
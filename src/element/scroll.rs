@@ -1,25 +1,198 @@
 //! Scrollable container element
+//!
+//! Scrolling follows the macOS feel: trackpad gestures drag the content
+//! directly and rubber-band past the edges, lifting the fingers hands off to
+//! momentum that decays on the frame clock, and overscroll springs back. The
+//! physics lives on [`ScrollState`] and is stepped each paint while moving.
+//!
+//! For data-driven content, [`ScrollContainer::on_reach_end`] pages in more
+//! items near the bottom and [`ScrollContainer::on_refresh`] adds
+//! pull-to-refresh.
+//!
+//! A [`ScrollHandle`] scrolls an element or region into view from outside
+//! the container. Keyboard focus changes and [`request_scroll_into_view`]
+//! reveal elements in whichever containers hold them, so the active control
+//! never ends up hidden off-screen.
+//!
+//! Nested containers share scrolling the way a page shares it with a
+//! carousel inside it: the inner container hands a gesture that would push
+//! past its edge, or runs along an axis it doesn't scroll, to the container
+//! beneath, and keeps the rest. Whichever takes a gesture keeps it until the
+//! fingers lift, locked to the axis it started along.
+//! [`ScrollContainer::axis_lock`] and [`ScrollContainer::scroll_chaining`]
+//! change this per container.
 
 use crate::{
-    color::{Color, ColorExt},
+    clock::frame_time,
+    color::{Color, ColorExt, colors},
     element::{Element, LayoutContext},
-    entity::{Entity, new_entity, read_entity, update_entity},
+    entity::{Entity, new_entity, observe, read_entity, update_entity},
     geometry::{Corners, Edges, Rect},
+    interaction::{
+        ElementId, EventHandlers,
+        registry::{can_pass_scroll, pass_scroll, register_element},
+    },
+    layer::ScrollPhase,
     layout_id::LayoutId,
     render::{PaintContext, PaintQuad},
 };
 use glam::Vec2;
+use std::cell::RefCell;
+use std::rc::Rc;
 use taffy::{Overflow, prelude::*};
 
+thread_local! {
+    /// Elements to reveal in whichever scroll containers hold them
+    static REVEAL_REQUESTS: RefCell<Vec<(ElementId, ScrollAlignment)>> =
+        const { RefCell::new(Vec::new()) };
+}
+
+/// Bring an element into view in every scroll container holding it, on the
+/// next paint
+///
+/// Unlike [`ScrollHandle::scroll_into_view`] this doesn't need to know which
+/// container the element is in. Keyboard focus changes request this for the
+/// newly focused element.
+pub fn request_scroll_into_view(id: impl Into<ElementId>, alignment: ScrollAlignment) {
+    let id = id.into();
+    REVEAL_REQUESTS.with(|requests| requests.borrow_mut().push((id, alignment)));
+}
+
+/// Drop reveal requests once a frame has painted
+pub(crate) fn clear_scroll_into_view_requests() {
+    REVEAL_REQUESTS.with(|requests| requests.borrow_mut().clear());
+}
+
+/// Stiffness of the spring used for spring-back and animated scrolls
+const SPRING_STIFFNESS: f32 = 170.0;
+/// Momentum velocity falls to 1/e after this many seconds
+const MOMENTUM_TIME_CONSTANT: f32 = 0.325;
+/// How strongly overscroll resists; smaller values resist more
+const RUBBER_BAND_COEFFICIENT: f32 = 0.55;
+/// Speed in points per second below which motion comes to rest
+const REST_VELOCITY: f32 = 5.0;
+/// How far back gesture deltas count towards the release velocity, in seconds
+const VELOCITY_WINDOW: f32 = 0.1;
+/// Longest physics step, so slow frames stay stable
+const MAX_STEP: f32 = 1.0 / 120.0;
+/// How far content must be pulled down, after resistance, to refresh
+const REFRESH_THRESHOLD: f32 = 64.0;
+/// Space held open above the content for the spinner while refreshing
+const REFRESH_INDICATOR_HEIGHT: f32 = 48.0;
+/// Number of dots in the refresh spinner
+const SPINNER_DOTS: usize = 8;
+/// Spinner speed in dots per second
+const SPINNER_SPEED: f32 = 12.0;
+
+/// Where a revealed element ends up in the viewport
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScrollAlignment {
+    /// Scroll as little as possible, leaving visible elements where they are
+    #[default]
+    Nearest,
+    /// Align with the top or left edge
+    Start,
+    /// Center in the viewport
+    Center,
+    /// Align with the bottom or right edge
+    End,
+}
+
+/// Directions a scroll container scrolls in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScrollAxes {
+    #[default]
+    Vertical,
+    Horizontal,
+    Both,
+}
+
+impl ScrollAxes {
+    /// Whether content scrolls left and right
+    pub fn horizontal(self) -> bool {
+        matches!(self, ScrollAxes::Horizontal | ScrollAxes::Both)
+    }
+
+    /// Whether content scrolls up and down
+    pub fn vertical(self) -> bool {
+        matches!(self, ScrollAxes::Vertical | ScrollAxes::Both)
+    }
+}
+
+/// What a scroll container does with scrolling it can't use
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScrollChaining {
+    /// Hand scrolling past an edge, or along an axis that doesn't scroll, to
+    /// the container beneath
+    #[default]
+    Edges,
+    /// Keep all scrolling, rubber-banding at the edges rather than scrolling
+    /// the container beneath
+    Contain,
+}
+
+/// Something to scroll into view
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScrollTarget {
+    /// An element painted inside the container, found by the ID it
+    /// registers for hit testing or focus
+    Element(ElementId),
+    /// A region in content coordinates, relative to the top-left of the
+    /// unscrolled content
+    Bounds(Rect),
+}
+
+impl From<ElementId> for ScrollTarget {
+    fn from(id: ElementId) -> Self {
+        ScrollTarget::Element(id)
+    }
+}
+
+impl From<Rect> for ScrollTarget {
+    fn from(bounds: Rect) -> Self {
+        ScrollTarget::Bounds(bounds)
+    }
+}
+
 /// State for a scroll container, persisted via the Entity system
 #[derive(Debug, Clone, Default)]
 pub struct ScrollState {
     /// Current scroll offset (positive = scrolled down/right)
+    ///
+    /// Goes past the scroll limits while rubber-banding.
     pub offset: Vec2,
     /// Content size from last frame (for scroll limit calculation)
     pub content_size: Vec2,
     /// Viewport size from last frame
     pub viewport_size: Vec2,
+    /// Scroll velocity in points per second while coasting or springing
+    pub velocity: Vec2,
+    /// Whether fingers are on the trackpad
+    gesture_active: bool,
+    /// Whether gestures move along their dominant axis only
+    axis_lock: bool,
+    /// Axis the current gesture is locked to
+    locked_axis: Option<usize>,
+    /// What happens to scrolling this container can't use
+    chaining: ScrollChaining,
+    /// Offset the gesture would reach without rubber-band resistance
+    gesture_offset: Vec2,
+    /// Recent gesture movement as (frame time, offset change)
+    gesture_samples: Vec<(f32, Vec2)>,
+    /// Destination of an animated scroll
+    target: Option<Vec2>,
+    /// Target to bring into view on the next paint, and whether to animate
+    pending_reveal: Option<(ScrollTarget, ScrollAlignment, bool)>,
+    /// Frame time of the last physics step
+    last_step: Option<f32>,
+    /// Whether the offset was within the reach-end threshold last frame
+    end_reached: bool,
+    /// Whether pulling down past the top can start a refresh
+    refresh_enabled: bool,
+    /// Whether a refresh is running, holding the spinner open
+    refreshing: bool,
+    /// Whether a released pull started a refresh the element hasn't reported yet
+    refresh_requested: bool,
 }
 
 impl ScrollState {
@@ -35,10 +208,435 @@ impl ScrollState {
         )
     }
 
+    /// Get the minimum scroll offset; negative while a refresh holds the spinner open
+    pub fn min_offset(&self) -> Vec2 {
+        if self.refreshing {
+            Vec2::new(0.0, -REFRESH_INDICATOR_HEIGHT)
+        } else {
+            Vec2::ZERO
+        }
+    }
+
     /// Clamp the current offset to valid bounds
     pub fn clamp_offset(&mut self) {
         let max = self.max_offset();
-        self.offset = self.offset.clamp(Vec2::ZERO, max);
+        self.offset = self.offset.clamp(self.min_offset(), max);
+    }
+
+    /// Check if the offset is past the scroll limits
+    pub fn is_overscrolled(&self) -> bool {
+        self.offset != self.offset.clamp(self.min_offset(), self.max_offset())
+    }
+
+    /// Check if the content is moving on its own, or showing the refresh
+    /// spinner, and needs more frames
+    pub fn is_animating(&self) -> bool {
+        !self.gesture_active
+            && (self.target.is_some()
+                || self.velocity != Vec2::ZERO
+                || self.is_overscrolled()
+                || self.refreshing)
+    }
+
+    /// Check if a pull-to-refresh is running
+    pub fn is_refreshing(&self) -> bool {
+        self.refreshing
+    }
+
+    /// Finish a pull-to-refresh, letting the content spring back up
+    pub fn end_refresh(&mut self) {
+        self.refreshing = false;
+        self.last_step = None;
+    }
+
+    /// How far the content is pulled towards starting a refresh, from 0.0 to 1.0
+    pub fn refresh_progress(&self) -> f32 {
+        if self.refreshing {
+            1.0
+        } else {
+            (-self.offset.y / REFRESH_THRESHOLD).clamp(0.0, 1.0)
+        }
+    }
+
+    /// Check if a scroll event would move this content, rather than
+    /// belonging to a container beneath
+    ///
+    /// A gesture this container already has is always its own. Otherwise
+    /// the delta, locked to its dominant axis, must point away from an edge
+    /// along an axis that scrolls, or towards a pull-to-refresh.
+    pub fn takes_scroll(&self, delta: Vec2, phase: ScrollPhase) -> bool {
+        match phase {
+            ScrollPhase::Ended | ScrollPhase::Momentum => self.gesture_active,
+            ScrollPhase::Changed if self.gesture_active => true,
+            _ if self.chaining == ScrollChaining::Contain => true,
+            _ => {
+                let change = -self.locked(delta);
+                let (min, max) = (self.min_offset(), self.max_offset());
+                (0..2).any(|axis| {
+                    let can_pull = axis == 1 && self.refresh_enabled;
+                    (change[axis] > 0.0 && self.offset[axis] < max[axis])
+                        || (change[axis] < 0.0 && (self.offset[axis] > min[axis] || can_pull))
+                })
+            }
+        }
+    }
+
+    /// Apply a scroll event at frame time `time`
+    ///
+    /// Mouse wheel deltas move the content directly. Trackpad gestures
+    /// rubber-band past the edges and coast with momentum once released; the
+    /// system's own momentum events are ignored in favor of that.
+    pub fn scroll_by(&mut self, delta: Vec2, phase: ScrollPhase, time: f32) {
+        match phase {
+            ScrollPhase::None => {
+                self.target = None;
+                self.velocity = Vec2::ZERO;
+                // Negative delta because scrolling down should increase offset
+                self.offset -= self.locked(delta);
+                self.clamp_offset();
+            }
+            ScrollPhase::Began | ScrollPhase::Changed => {
+                if phase == ScrollPhase::Began || !self.gesture_active {
+                    self.begin_gesture();
+                }
+                self.drag(-delta, time);
+            }
+            ScrollPhase::Ended => {
+                if self.gesture_active {
+                    self.gesture_active = false;
+                    self.locked_axis = None;
+                    self.velocity = self.release_velocity();
+                    self.last_step = Some(time);
+                    if self.refresh_enabled
+                        && !self.refreshing
+                        && self.offset.y <= -REFRESH_THRESHOLD
+                    {
+                        self.refreshing = true;
+                        self.refresh_requested = true;
+                    }
+                }
+            }
+            ScrollPhase::Momentum => {}
+        }
+    }
+
+    /// Scroll to `offset`, either easing there with a spring or jumping
+    pub fn scroll_to(&mut self, offset: Vec2, animated: bool) {
+        self.gesture_active = false;
+        self.pending_reveal = None;
+        if animated {
+            self.target = Some(offset.max(Vec2::ZERO));
+            self.last_step = None;
+        } else {
+            self.target = None;
+            self.velocity = Vec2::ZERO;
+            self.offset = offset.max(Vec2::ZERO);
+            // Sizes aren't known until the container has painted once
+            if self.viewport_size != Vec2::ZERO {
+                self.clamp_offset();
+            }
+        }
+    }
+
+    /// Scroll the minimum distance to bring an element into view on the next paint
+    ///
+    /// The element is found by the ID it registers for hit testing, so it
+    /// must be an interactive element painted inside the container.
+    pub fn scroll_to_element(&mut self, id: impl Into<ElementId>, animated: bool) {
+        self.scroll_into_view(id.into(), ScrollAlignment::Nearest, animated);
+    }
+
+    /// Bring an element or region into view on the next paint
+    pub fn scroll_into_view(
+        &mut self,
+        target: impl Into<ScrollTarget>,
+        alignment: ScrollAlignment,
+        animated: bool,
+    ) {
+        self.pending_reveal = Some((target.into(), alignment, animated));
+    }
+
+    /// Scroll so `bounds`, in content coordinates, sits in the viewport as
+    /// `alignment` asks, without going past the scroll limits
+    pub fn reveal(&mut self, bounds: Rect, alignment: ScrollAlignment, animated: bool) {
+        let mut target = self.offset;
+        for axis in 0..2 {
+            let viewport = self.viewport_size[axis];
+            let start = bounds.pos[axis];
+            let end = start + bounds.size[axis];
+            let (visible_start, visible_end) = (target[axis], target[axis] + viewport);
+            target[axis] = match alignment {
+                // Leave it alone if it's fully in view, or fills the view
+                ScrollAlignment::Nearest
+                    if (start >= visible_start && end <= visible_end)
+                        || (start <= visible_start && end >= visible_end) =>
+                {
+                    target[axis]
+                }
+                // Otherwise line up the nearer edge if it fits, the farther
+                // one if it doesn't
+                ScrollAlignment::Nearest
+                    if (start < visible_start) == (end - start <= viewport) =>
+                {
+                    start
+                }
+                ScrollAlignment::Nearest => end - viewport,
+                ScrollAlignment::Start => start,
+                ScrollAlignment::Center => (start + end - viewport) / 2.0,
+                ScrollAlignment::End => end - viewport,
+            };
+        }
+        let target = target.clamp(self.min_offset(), self.max_offset());
+        if target != self.offset {
+            self.scroll_to(target, animated);
+        }
+    }
+
+    /// Advance momentum, spring-back and animated scrolls to frame time `time`
+    ///
+    /// Returns whether the content is still moving.
+    pub fn step(&mut self, time: f32) -> bool {
+        let last = self.last_step.replace(time);
+        if !self.is_animating() {
+            return false;
+        }
+
+        let mut remaining = (time - last.unwrap_or(time)).clamp(0.0, 0.25);
+        while remaining > 0.0 {
+            let dt = remaining.min(MAX_STEP);
+            self.integrate(dt);
+            remaining -= dt;
+        }
+        self.settle();
+        self.is_animating()
+    }
+
+    /// Check if the offset is within `threshold` of the bottom
+    ///
+    /// Content shorter than the viewport is always at the end.
+    pub fn is_near_end(&self, threshold: f32) -> bool {
+        self.viewport_size.y > 0.0 && self.max_offset().y - self.offset.y <= threshold
+    }
+
+    fn begin_gesture(&mut self) {
+        self.gesture_active = true;
+        self.locked_axis = None;
+        self.target = None;
+        self.velocity = Vec2::ZERO;
+        self.gesture_samples.clear();
+
+        // Continue from what's on screen, even mid spring-back
+        let (min, max) = (self.min_offset(), self.max_offset());
+        for axis in 0..2 {
+            let limit = self.offset[axis].clamp(min[axis], max[axis]);
+            let overscroll = self.offset[axis] - limit;
+            self.gesture_offset[axis] =
+                limit + rubber_band_inverse(overscroll, self.viewport_size[axis]);
+        }
+    }
+
+    fn drag(&mut self, change: Vec2, time: f32) {
+        // The first movement picks the axis for the rest of the gesture
+        if self.axis_lock && self.locked_axis.is_none() && change != Vec2::ZERO {
+            self.locked_axis = Some(dominant_axis(change));
+        }
+        let change = self.locked(change);
+
+        let (min, max) = (self.min_offset(), self.max_offset());
+        for axis in 0..2 {
+            // Don't rubber-band along an axis that can't scroll, unless
+            // pulling down can refresh
+            let can_pull = axis == 1 && self.refresh_enabled;
+            if max[axis] <= 0.0 && self.offset[axis] == min[axis] && !can_pull {
+                continue;
+            }
+            self.gesture_offset[axis] += change[axis];
+            let limit = self.gesture_offset[axis].clamp(min[axis], max[axis]);
+            let overscroll = self.gesture_offset[axis] - limit;
+            self.offset[axis] = limit + rubber_band(overscroll, self.viewport_size[axis]);
+        }
+
+        self.gesture_samples.push((time, change));
+        self.gesture_samples
+            .retain(|(sample_time, _)| time - sample_time <= VELOCITY_WINDOW);
+    }
+
+    /// `delta` along the gesture's axis only, when gestures lock to one
+    fn locked(&self, delta: Vec2) -> Vec2 {
+        if !self.axis_lock {
+            return delta;
+        }
+        match self.locked_axis.unwrap_or_else(|| dominant_axis(delta)) {
+            0 => Vec2::new(delta.x, 0.0),
+            _ => Vec2::new(0.0, delta.y),
+        }
+    }
+
+    /// Average speed over the last few gesture samples
+    fn release_velocity(&mut self) -> Vec2 {
+        let samples = std::mem::take(&mut self.gesture_samples);
+        let (Some(first), Some(last)) = (samples.first(), samples.last()) else {
+            return Vec2::ZERO;
+        };
+        // Samples from a single frame share a time, so assume one frame elapsed
+        let span = (last.0 - first.0).max(1.0 / 60.0);
+        let distance: Vec2 = samples.iter().map(|(_, change)| *change).sum();
+        let velocity = distance / span;
+        if velocity.length() < REST_VELOCITY {
+            Vec2::ZERO
+        } else {
+            velocity
+        }
+    }
+
+    /// Where an axis is being pulled to: the animation target or the nearest
+    /// scroll limit when overscrolled
+    fn anchor(&self, axis: usize) -> Option<f32> {
+        let min = self.min_offset()[axis];
+        let max = self.max_offset()[axis];
+        match self.target {
+            Some(target) => Some(target[axis].clamp(min, max)),
+            None if self.offset[axis] < min => Some(min),
+            None if self.offset[axis] > max => Some(max),
+            None => None,
+        }
+    }
+
+    fn integrate(&mut self, dt: f32) {
+        for axis in 0..2 {
+            let velocity = self.velocity[axis];
+            self.velocity[axis] = match self.anchor(axis) {
+                // Critically damped, so it settles without oscillating
+                Some(anchor) => {
+                    let damping = 2.0 * SPRING_STIFFNESS.sqrt();
+                    let acceleration =
+                        SPRING_STIFFNESS * (anchor - self.offset[axis]) - damping * velocity;
+                    velocity + acceleration * dt
+                }
+                None => velocity * (-dt / MOMENTUM_TIME_CONSTANT).exp(),
+            };
+            self.offset[axis] += self.velocity[axis] * dt;
+        }
+    }
+
+    fn settle(&mut self) {
+        let mut at_rest = true;
+        for axis in 0..2 {
+            if self.velocity[axis].abs() >= REST_VELOCITY {
+                at_rest = false;
+                continue;
+            }
+            match self.anchor(axis) {
+                Some(anchor) if (self.offset[axis] - anchor).abs() < 0.5 => {
+                    self.offset[axis] = anchor;
+                    self.velocity[axis] = 0.0;
+                }
+                Some(_) => at_rest = false,
+                None => self.velocity[axis] = 0.0,
+            }
+        }
+        if at_rest {
+            self.target = None;
+        }
+    }
+}
+
+/// The axis `delta` mostly moves along, vertical when it's even
+fn dominant_axis(delta: Vec2) -> usize {
+    if delta.x.abs() > delta.y.abs() { 0 } else { 1 }
+}
+
+/// Resisted distance shown for `overscroll` points of pull past an edge
+///
+/// Approaches `dimension` asymptotically, so content can never be pulled
+/// further than the viewport.
+fn rubber_band(overscroll: f32, dimension: f32) -> f32 {
+    if dimension <= 0.0 {
+        return 0.0;
+    }
+    let pulled = overscroll.abs() * RUBBER_BAND_COEFFICIENT / dimension;
+    (1.0 - 1.0 / (pulled + 1.0)) * dimension * overscroll.signum()
+}
+
+/// Pull needed for [`rubber_band`] to show `shown` points of overscroll
+fn rubber_band_inverse(shown: f32, dimension: f32) -> f32 {
+    if dimension <= 0.0 {
+        return 0.0;
+    }
+    let fraction = (shown.abs() / dimension).min(0.99);
+    (1.0 / (1.0 - fraction) - 1.0) * dimension / RUBBER_BAND_COEFFICIENT * shown.signum()
+}
+
+/// A cloneable handle for scrolling a container from outside it
+///
+/// Build it from scroll state that lives across frames, such as a
+/// [`StateCell`](crate::entity::StateCell), pass it to the container with
+/// [`ScrollContainer::scroll_handle`], and keep a clone wherever the
+/// scrolling is triggered from. A handle made with [`ScrollHandle::new`]
+/// inside a render closure would start over from fresh state every frame.
+///
+/// ```ignore
+/// let scroll_state = StateCell::new();
+///
+/// layers.add_ui_layer(0, LayerOptions::default(), move || {
+///     // Created on the first frame and reused after that
+///     let handle = ScrollHandle::from(scroll_state.get_or_init(ScrollState::new));
+///     let jump = handle.clone();
+///     column()
+///         .child(button("Jump to row 42").on_click_simple(move || {
+///             jump.scroll_into_view(ElementId::stable("row:42"), ScrollAlignment::Center)
+///         }))
+///         .child(scroll().scroll_handle(&handle).child(rows()))
+/// });
+/// ```
+#[derive(Clone)]
+pub struct ScrollHandle {
+    state: Entity<ScrollState>,
+}
+
+impl ScrollHandle {
+    /// Create a handle with fresh scroll state
+    ///
+    /// Creates a new entity, so keep the handle rather than calling this
+    /// every frame.
+    pub fn new() -> Self {
+        Self {
+            state: new_entity(ScrollState::new()),
+        }
+    }
+
+    /// The state entity behind this handle
+    pub fn state(&self) -> Entity<ScrollState> {
+        self.state.clone()
+    }
+
+    /// Get the current scroll offset
+    pub fn offset(&self) -> Vec2 {
+        read_entity(&self.state, |s| s.offset).unwrap_or(Vec2::ZERO)
+    }
+
+    /// Scroll to an offset, animated with a spring or immediately
+    pub fn scroll_to(&self, offset: Vec2, animated: bool) {
+        update_entity(&self.state, |s| s.scroll_to(offset, animated));
+    }
+
+    /// Bring an element, or a region in content coordinates, into view on
+    /// the next paint, animating there
+    pub fn scroll_into_view(&self, target: impl Into<ScrollTarget>, alignment: ScrollAlignment) {
+        let target = target.into();
+        update_entity(&self.state, |s| s.scroll_into_view(target, alignment, true));
+    }
+}
+
+impl Default for ScrollHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<Entity<ScrollState>> for ScrollHandle {
+    fn from(state: Entity<ScrollState>) -> Self {
+        Self { state }
     }
 }
 
@@ -48,6 +646,10 @@ pub fn scroll() -> ScrollContainer {
 }
 
 /// A scrollable container element
+///
+/// Bind it to a persistent [`ScrollState`] with [`state`](Self::state) so the
+/// offset and momentum survive across frames, and use that entity to scroll
+/// programmatically.
 pub struct ScrollContainer {
     style: Style,
     background: Option<Color>,
@@ -57,9 +659,18 @@ pub struct ScrollContainer {
     scrollbar_color: Option<Color>,
     scrollbar_width: f32,
     show_scrollbar: bool,
+    axes: ScrollAxes,
+    axis_lock: bool,
+    chaining: ScrollChaining,
     children: Vec<Box<dyn Element>>,
     child_nodes: Vec<NodeId>,
     state: Option<Entity<ScrollState>>,
+    /// ID for receiving scroll events; derived from the state unless keyed
+    element_id: Option<ElementId>,
+    /// Distance from the bottom that counts as the end, and its handler
+    on_reach_end: Option<(f32, Box<dyn FnMut()>)>,
+    /// Pull-to-refresh handler
+    on_refresh: Option<Box<dyn FnMut()>>,
     /// Stable layout ID for caching across frames
     layout_id: Option<LayoutId>,
 }
@@ -81,13 +692,46 @@ impl ScrollContainer {
             scrollbar_color: Some(Color::rgba(0.5, 0.5, 0.5, 0.5)),
             scrollbar_width: 8.0,
             show_scrollbar: true,
+            axes: ScrollAxes::Vertical,
+            axis_lock: true,
+            chaining: ScrollChaining::Edges,
             children: Vec::new(),
             child_nodes: Vec::new(),
             state: None,
+            element_id: None,
+            on_reach_end: None,
+            on_refresh: None,
             layout_id: None,
         }
     }
 
+    /// Set a unique key for this scroll container
+    pub fn with_key(mut self, key: impl AsRef<str>) -> Self {
+        self.element_id = Some(ElementId::stable(format!("scroll:{}", key.as_ref())));
+        self
+    }
+
+    /// Replace the layout style, keeping the content clipped
+    pub(crate) fn with_style(mut self, style: Style) -> Self {
+        self.style = Style {
+            overflow: self.style.overflow,
+            ..style
+        };
+        self
+    }
+
+    /// Bind to a persistent state entity
+    pub fn state(mut self, state: Entity<ScrollState>) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Control this container through a [`ScrollHandle`]
+    pub fn scroll_handle(mut self, handle: &ScrollHandle) -> Self {
+        self.state = Some(handle.state());
+        self
+    }
+
     /// Set a stable layout ID for caching across frames.
     pub fn layout_id(mut self, id: impl Into<LayoutId>) -> Self {
         self.layout_id = Some(id.into());
@@ -179,6 +823,55 @@ impl ScrollContainer {
         self
     }
 
+    /// Set the directions the content scrolls in; vertical by default
+    pub fn axes(mut self, axes: ScrollAxes) -> Self {
+        self.axes = axes;
+        self
+    }
+
+    /// Lock each gesture to the axis it starts along (the default), or let
+    /// it move freely in both
+    ///
+    /// Only matters for containers that scroll both ways, and for deciding
+    /// whether a diagonal gesture belongs to this container or the one
+    /// beneath it.
+    pub fn axis_lock(mut self, lock: bool) -> Self {
+        self.axis_lock = lock;
+        self
+    }
+
+    /// Set what happens to scrolling this container can't use
+    pub fn scroll_chaining(mut self, chaining: ScrollChaining) -> Self {
+        self.chaining = chaining;
+        self
+    }
+
+    /// Call `handler` when scrolling comes within `threshold` points of the bottom
+    ///
+    /// Use this to page in more items. It fires once each time the end is
+    /// approached, and again after the content size changes if the end is
+    /// still in reach, so short pages keep loading until the viewport fills.
+    pub fn on_reach_end<F>(mut self, threshold: f32, handler: F) -> Self
+    where
+        F: FnMut() + 'static,
+    {
+        self.on_reach_end = Some((threshold, Box::new(handler)));
+        self
+    }
+
+    /// Enable pull-to-refresh: pulling the content down past the top and
+    /// releasing calls `handler` and shows a spinner
+    ///
+    /// The spinner stays until the refresh is finished with
+    /// [`ScrollState::end_refresh`] on the container's state.
+    pub fn on_refresh<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut() + 'static,
+    {
+        self.on_refresh = Some(Box::new(handler));
+        self
+    }
+
     /// Add a child element
     pub fn child(mut self, child: impl Element + 'static) -> Self {
         self.children.push(Box::new(child));
@@ -228,14 +921,26 @@ impl ScrollContainer {
     pub fn apply_scroll(&self, delta: Vec2) {
         if let Some(ref state) = self.state {
             update_entity(state, |s| {
-                // Negative delta because scrolling down should increase offset
-                s.offset.y -= delta.y;
-                s.offset.x -= delta.x;
-                s.clamp_offset();
+                s.scroll_by(delta, ScrollPhase::None, frame_time())
             });
         }
     }
 
+    /// Scroll to an offset, animated with a spring or immediately
+    pub fn scroll_to(&self, offset: Vec2, animated: bool) {
+        if let Some(ref state) = self.state {
+            update_entity(state, |s| s.scroll_to(offset, animated));
+        }
+    }
+
+    /// Scroll an interactive child into view on the next paint
+    pub fn scroll_to_element(&self, id: impl Into<ElementId>, animated: bool) {
+        let id = id.into();
+        if let Some(ref state) = self.state {
+            update_entity(state, |s| s.scroll_to_element(id, animated));
+        }
+    }
+
     /// Get the current scroll offset
     pub fn scroll_offset(&self) -> Vec2 {
         self.state
@@ -280,6 +985,8 @@ impl Element for ScrollContainer {
                 width: Dimension::percent(1.0),
                 height: Dimension::auto(),
             },
+            // Keep the content's own width when it can scroll sideways
+            flex_shrink: if self.axes.horizontal() { 0.0 } else { 1.0 },
             ..Style::default()
         };
 
@@ -305,15 +1012,26 @@ impl Element for ScrollContainer {
             });
         }
 
-        // Get scroll offset from state
-        let scroll_offset = self.state
-            .as_ref()
-            .and_then(|s| read_entity(s, |state| state.offset))
-            .unwrap_or(Vec2::ZERO);
+        let Some(state) = self.state.clone() else {
+            return;
+        };
+
+        // Step momentum and springs on the frame clock. Observing the state
+        // means each step's update requests the next frame until it settles.
+        if observe(&state, |s| s.is_animating()).unwrap_or(false) {
+            update_entity(&state, |s| s.step(frame_time()));
+        }
+        let scroll_offset = read_entity(&state, |s| s.offset).unwrap_or(Vec2::ZERO);
 
         // Push clip rect to confine children to this container's bounds
         ctx.draw_list.push_clip(bounds);
 
+        // Targets the children register come after this point
+        let first_target = ctx
+            .hit_test_builder
+            .as_ref()
+            .map_or(0, |builder| builder.borrow().scroll_targets().len());
+
         // Paint children with scroll offset applied
         for (child, &child_node) in self.children.iter_mut().zip(&self.child_nodes) {
             // Get child's layout bounds (relative to parent)
@@ -328,64 +1046,192 @@ impl Element for ScrollContainer {
             child.paint(child_absolute_bounds, ctx);
         }
 
+        // Show the refresh spinner in the space revealed above the content
+        if self.on_refresh.is_some() {
+            let progress = read_entity(&state, |s| s.refresh_progress()).unwrap_or(0.0);
+            let refreshing = read_entity(&state, |s| s.refreshing).unwrap_or(false);
+            if progress > 0.0 && scroll_offset.y < 0.0 {
+                let center = Vec2::new(
+                    bounds.pos.x + bounds.size.x / 2.0,
+                    bounds.pos.y + (-scroll_offset.y).min(REFRESH_INDICATOR_HEIGHT) / 2.0,
+                );
+                paint_spinner(center, progress, refreshing, ctx);
+            }
+        }
+
         // Pop clip rect
         ctx.draw_list.pop_clip();
 
-        // Calculate content size for scroll state
-        let content_height: f32 = self.child_nodes
+        // Calculate content size for scroll state, along the axes that scroll
+        let content_extent = self
+            .child_nodes
             .iter()
             .map(|&node| {
                 let child_bounds = ctx.layout_engine.layout_bounds(node);
-                child_bounds.pos.y + child_bounds.size.y
+                child_bounds.pos + child_bounds.size
             })
-            .fold(0.0f32, |a, b| a.max(b));
+            .fold(Vec2::ZERO, Vec2::max);
 
-        let content_size = Vec2::new(bounds.size.x, content_height);
+        let content_size = Vec2::new(
+            if self.axes.horizontal() {
+                content_extent.x
+            } else {
+                bounds.size.x
+            },
+            if self.axes.vertical() {
+                content_extent.y
+            } else {
+                bounds.size.y
+            },
+        );
 
-        // Update state with current sizes
-        if let Some(ref state) = self.state {
-            update_entity(state, |s| {
+        // Only write sizes when they change, so observers aren't notified every frame.
+        // Content that shrinks past the offset springs back rather than jumping.
+        let sizes_changed = read_entity(&state, |s| {
+            s.viewport_size != bounds.size || s.content_size != content_size
+        })
+        .unwrap_or(false);
+        if sizes_changed {
+            update_entity(&state, |s| {
                 s.viewport_size = bounds.size;
                 s.content_size = content_size;
-                s.clamp_offset();
+                // New content re-arms the reach-end handler
+                s.end_reached = false;
+            });
+        }
+
+        self.update_refresh(&state);
+        let config = (self.axis_lock, self.chaining);
+        if read_entity(&state, |s| (s.axis_lock, s.chaining)) != Some(config) {
+            update_entity(&state, |s| (s.axis_lock, s.chaining) = config);
+        }
+        if let Some((threshold, handler)) = &mut self.on_reach_end {
+            let (near, reached) =
+                read_entity(&state, |s| (s.is_near_end(*threshold), s.end_reached))
+                    .unwrap_or((false, false));
+            if near != reached {
+                update_entity(&state, |s| s.end_reached = near);
+                if near {
+                    handler();
+                }
+            }
+        }
+
+        // Bring a requested child into view, now that it has painted
+        if let Some((target, alignment, animated)) =
+            read_entity(&state, |s| s.pending_reveal).flatten()
+        {
+            let target = match target {
+                ScrollTarget::Element(id) => {
+                    content_bounds(id, first_target, bounds, scroll_offset, ctx)
+                }
+                ScrollTarget::Bounds(target) => Some(target),
+            };
+            update_entity(&state, |s| {
+                s.pending_reveal = None;
+                if let Some(target) = target {
+                    s.reveal(target, alignment, animated);
+                }
             });
         }
 
-        // Paint scrollbar if enabled and content overflows
-        if self.show_scrollbar && content_size.y > bounds.size.y {
-            self.paint_scrollbar(bounds, content_size, scroll_offset, ctx);
+        // Reveal children that took keyboard focus or asked to be shown
+        let requests = REVEAL_REQUESTS.with(|requests| requests.borrow().clone());
+        for (id, alignment) in requests {
+            if let Some(target) = content_bounds(id, first_target, bounds, scroll_offset, ctx) {
+                update_entity(&state, |s| s.reveal(target, alignment, true));
+            }
         }
+
+        // Paint scrollbars if enabled and content overflows
+        if self.show_scrollbar {
+            for axis in 0..2 {
+                if content_size[axis] > bounds.size[axis] {
+                    self.paint_scrollbar(axis, bounds, content_size, scroll_offset, ctx);
+                }
+            }
+        }
+
+        // Register after the children so they keep their own hit tests
+        let element_id = *self
+            .element_id
+            .get_or_insert_with(|| ElementId::stable(format!("scroll:{:?}", state.id())));
+        let handlers = EventHandlers::new().on_scroll_gesture(move |delta, phase, _, _| {
+            // Scrolling this container can't use goes to the one beneath, if any
+            let takes = read_entity(&state, |s| s.takes_scroll(delta, phase)).unwrap_or(false);
+            if takes || !can_pass_scroll() {
+                update_entity(&state, |s| s.scroll_by(delta, phase, frame_time()));
+            } else {
+                pass_scroll();
+            }
+        });
+        register_element(element_id, Rc::new(RefCell::new(handlers)));
+        ctx.register_hit_test(element_id, bounds, 0);
     }
 }
 
 impl ScrollContainer {
-    fn paint_scrollbar(&self, bounds: Rect, content_size: Vec2, scroll_offset: Vec2, ctx: &mut PaintContext) {
+    /// Sync pull-to-refresh with the state and report a refresh started by a gesture
+    fn update_refresh(&mut self, state: &Entity<ScrollState>) {
+        let enabled = self.on_refresh.is_some();
+        let (was_enabled, requested) =
+            read_entity(state, |s| (s.refresh_enabled, s.refresh_requested))
+                .unwrap_or((enabled, false));
+        if enabled != was_enabled || requested {
+            update_entity(state, |s| {
+                s.refresh_enabled = enabled;
+                s.refresh_requested = false;
+            });
+        }
+        if requested && let Some(handler) = &mut self.on_refresh {
+            handler();
+        }
+    }
+
+    /// Paint the scrollbar for `axis`: along the right edge for vertical
+    /// scrolling, the bottom edge for horizontal
+    fn paint_scrollbar(
+        &self,
+        axis: usize,
+        bounds: Rect,
+        content_size: Vec2,
+        scroll_offset: Vec2,
+        ctx: &mut PaintContext,
+    ) {
         let scrollbar_color = self.scrollbar_color.unwrap_or(Color::rgba(0.5, 0.5, 0.5, 0.5));
+        let across = 1 - axis;
 
-        // Calculate scrollbar track position (right side of container)
-        let track_x = bounds.pos.x + bounds.size.x - self.scrollbar_width - 2.0;
-        let track_y = bounds.pos.y + 2.0;
-        let track_height = bounds.size.y - 4.0;
+        // Calculate scrollbar track position along the far edge
+        let track_start = bounds.pos[axis] + 2.0;
+        let track_length = bounds.size[axis] - 4.0;
+        let track_offset = bounds.pos[across] + bounds.size[across] - self.scrollbar_width - 2.0;
 
         // Calculate thumb size based on viewport/content ratio
-        let visible_ratio = (bounds.size.y / content_size.y).min(1.0);
-        let thumb_height = (track_height * visible_ratio).max(20.0);
+        let visible_ratio = (bounds.size[axis] / content_size[axis]).min(1.0);
+        let thumb_length = (track_length * visible_ratio).max(20.0);
 
         // Calculate thumb position based on scroll offset
-        let max_scroll = (content_size.y - bounds.size.y).max(0.0);
+        let max_scroll = (content_size[axis] - bounds.size[axis]).max(0.0);
         let scroll_ratio = if max_scroll > 0.0 {
-            scroll_offset.y / max_scroll
+            (scroll_offset[axis] / max_scroll).clamp(0.0, 1.0)
         } else {
             0.0
         };
-        let thumb_y = track_y + (track_height - thumb_height) * scroll_ratio;
+        let thumb_start = track_start + (track_length - thumb_length) * scroll_ratio;
+
+        // Build a rect from its position and length along the track
+        let bar = |start: f32, length: f32| {
+            let (mut pos, mut size) = (Vec2::ZERO, Vec2::ZERO);
+            pos[axis] = start;
+            pos[across] = track_offset;
+            size[axis] = length;
+            size[across] = self.scrollbar_width;
+            Rect::from_pos_size(pos, size)
+        };
 
         // Paint scrollbar track (optional, subtle background)
         ctx.paint_quad(PaintQuad {
-            bounds: Rect::from_pos_size(
-                Vec2::new(track_x, track_y),
-                Vec2::new(self.scrollbar_width, track_height),
-            ),
+            bounds: bar(track_start, track_length),
             fill: Color::rgba(0.0, 0.0, 0.0, 0.1),
             corner_radii: Corners::all(self.scrollbar_width / 2.0),
             border_widths: Edges::zero(),
@@ -394,10 +1240,7 @@ impl ScrollContainer {
 
         // Paint scrollbar thumb
         ctx.paint_quad(PaintQuad {
-            bounds: Rect::from_pos_size(
-                Vec2::new(track_x, thumb_y),
-                Vec2::new(self.scrollbar_width, thumb_height),
-            ),
+            bounds: bar(thumb_start, thumb_length),
             fill: scrollbar_color,
             corner_radii: Corners::all(self.scrollbar_width / 2.0),
             border_widths: Edges::zero(),
@@ -405,3 +1248,293 @@ impl ScrollContainer {
         });
     }
 }
+
+/// Paint a ring of dots: `progress` of them while pulling, then spinning
+/// on the frame clock while refreshing
+fn paint_spinner(center: Vec2, progress: f32, refreshing: bool, ctx: &mut PaintContext) {
+    const RADIUS: f32 = 10.0;
+    const DOT_SIZE: f32 = 4.0;
+
+    let visible = (progress * SPINNER_DOTS as f32).ceil() as usize;
+    let head = (frame_time() * SPINNER_SPEED) as usize % SPINNER_DOTS;
+    for i in 0..visible {
+        let angle = i as f32 / SPINNER_DOTS as f32 * std::f32::consts::TAU;
+        let position = center + Vec2::new(angle.sin(), -angle.cos()) * RADIUS;
+
+        // Dots trailing the head fade out
+        let alpha = if refreshing {
+            let behind = (head + SPINNER_DOTS - i) % SPINNER_DOTS;
+            1.0 - behind as f32 / SPINNER_DOTS as f32 * 0.75
+        } else {
+            0.6
+        };
+        let mut color = colors::GRAY_500;
+        color.alpha *= alpha;
+
+        ctx.paint_quad(PaintQuad {
+            bounds: Rect::from_pos_size(
+                position - Vec2::splat(DOT_SIZE / 2.0),
+                Vec2::splat(DOT_SIZE),
+            ),
+            fill: color,
+            corner_radii: Corners::all(DOT_SIZE / 2.0),
+            border_widths: Edges::zero(),
+            border_color: colors::TRANSPARENT,
+        });
+    }
+}
+
+/// Content-space bounds of the child with `id`, looking only at scroll
+/// targets registered from `first_target` on
+fn content_bounds(
+    id: ElementId,
+    first_target: usize,
+    bounds: Rect,
+    scroll_offset: Vec2,
+    ctx: &PaintContext,
+) -> Option<Rect> {
+    // Scroll targets are in screen space
+    let transform = ctx.draw_list.current_transform();
+    let viewport = transform.apply_rect(bounds);
+    let element = ctx
+        .hit_test_builder
+        .as_ref()?
+        .borrow()
+        .scroll_targets()
+        .get(first_target..)?
+        .iter()
+        .rev()
+        .find(|(target_id, _)| *target_id == id)?
+        .1;
+
+    Some(Rect::from_pos_size(
+        (element.pos - viewport.pos) / transform.scale + scroll_offset,
+        element.size / transform.scale,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 100pt viewport over 500pt of content
+    fn state() -> ScrollState {
+        ScrollState {
+            content_size: Vec2::new(100.0, 500.0),
+            viewport_size: Vec2::new(100.0, 100.0),
+            ..ScrollState::new()
+        }
+    }
+
+    /// Step at 120fps until the content stops moving
+    fn run_until_rest(state: &mut ScrollState, mut time: f32) -> f32 {
+        while state.step(time) {
+            time += 1.0 / 120.0;
+            assert!(time < 10.0, "scroll never came to rest");
+        }
+        time
+    }
+
+    #[test]
+    fn test_rubber_band_resists_overscroll() {
+        let mut state = state();
+        state.scroll_by(Vec2::ZERO, ScrollPhase::Began, 0.0);
+        state.scroll_by(Vec2::new(0.0, 1000.0), ScrollPhase::Changed, 0.0);
+
+        // Pulled down past the top, but by less than the viewport
+        assert!(state.offset.y < 0.0);
+        assert!(state.offset.y > -100.0);
+        assert_eq!(state.offset.x, 0.0);
+    }
+
+    #[test]
+    fn test_overscroll_springs_back() {
+        let mut state = state();
+        state.scroll_by(Vec2::ZERO, ScrollPhase::Began, 0.0);
+        state.scroll_by(Vec2::new(0.0, 50.0), ScrollPhase::Changed, 0.0);
+        state.scroll_by(Vec2::ZERO, ScrollPhase::Ended, 0.0);
+        assert!(state.is_animating());
+
+        run_until_rest(&mut state, 0.0);
+        assert_eq!(state.offset.y, 0.0);
+        assert!(!state.is_animating());
+    }
+
+    #[test]
+    fn test_momentum_coasts_after_release() {
+        let mut state = state();
+        state.scroll_by(Vec2::ZERO, ScrollPhase::Began, 0.0);
+        for frame in 1..=5 {
+            state.scroll_by(
+                Vec2::new(0.0, -10.0),
+                ScrollPhase::Changed,
+                frame as f32 / 60.0,
+            );
+        }
+        let released = state.offset.y;
+        state.scroll_by(Vec2::ZERO, ScrollPhase::Ended, 5.0 / 60.0);
+        assert!(state.velocity.y > 0.0);
+
+        run_until_rest(&mut state, 5.0 / 60.0);
+        assert!(state.offset.y > released);
+        assert!(state.offset.y <= state.max_offset().y);
+    }
+
+    #[test]
+    fn test_animated_scroll_to_reaches_clamped_target() {
+        let mut state = state();
+        state.scroll_to(Vec2::new(0.0, 1000.0), true);
+        run_until_rest(&mut state, 0.0);
+        assert_eq!(state.offset, Vec2::new(0.0, 400.0));
+
+        state.scroll_to(Vec2::new(0.0, 150.0), false);
+        assert_eq!(state.offset.y, 150.0);
+        assert!(!state.is_animating());
+    }
+
+    #[test]
+    fn test_reveal_nearest_scrolls_least() {
+        let mut state = state();
+        let row = |y: f32| Rect::from_pos_size(Vec2::new(0.0, y), Vec2::new(100.0, 20.0));
+
+        // Already visible
+        state.reveal(row(50.0), ScrollAlignment::Nearest, false);
+        assert_eq!(state.offset.y, 0.0);
+
+        // Below the viewport lines up with the bottom edge
+        state.reveal(row(200.0), ScrollAlignment::Nearest, false);
+        assert_eq!(state.offset.y, 120.0);
+
+        // Above it lines up with the top edge
+        state.reveal(row(60.0), ScrollAlignment::Nearest, false);
+        assert_eq!(state.offset.y, 60.0);
+
+        // Something filling the viewport stays put
+        let tall = Rect::from_pos_size(Vec2::new(0.0, 0.0), Vec2::new(100.0, 300.0));
+        state.reveal(tall, ScrollAlignment::Nearest, false);
+        assert_eq!(state.offset.y, 60.0);
+    }
+
+    #[test]
+    fn test_reveal_alignment_is_clamped() {
+        let mut state = state();
+        let row = |y: f32| Rect::from_pos_size(Vec2::new(0.0, y), Vec2::new(100.0, 20.0));
+
+        state.reveal(row(200.0), ScrollAlignment::Center, false);
+        assert_eq!(state.offset.y, 160.0);
+        state.reveal(row(200.0), ScrollAlignment::Start, false);
+        assert_eq!(state.offset.y, 200.0);
+        state.reveal(row(200.0), ScrollAlignment::End, false);
+        assert_eq!(state.offset.y, 120.0);
+
+        // The last row can't be scrolled to the top
+        state.reveal(row(480.0), ScrollAlignment::Start, false);
+        assert_eq!(state.offset.y, 400.0);
+    }
+
+    #[test]
+    fn test_pull_to_refresh() {
+        let mut state = ScrollState {
+            refresh_enabled: true,
+            ..state()
+        };
+        state.scroll_by(Vec2::ZERO, ScrollPhase::Began, 0.0);
+        state.scroll_by(Vec2::new(0.0, 400.0), ScrollPhase::Changed, 0.0);
+        assert_eq!(state.refresh_progress(), 1.0);
+        state.scroll_by(Vec2::ZERO, ScrollPhase::Ended, 0.0);
+        assert!(state.is_refreshing());
+
+        // Springs to the spinner's space and holds there
+        let mut time = 0.0;
+        for _ in 0..240 {
+            time += 1.0 / 120.0;
+            state.step(time);
+        }
+        assert_eq!(state.offset.y, -REFRESH_INDICATOR_HEIGHT);
+
+        state.end_refresh();
+        run_until_rest(&mut state, time);
+        assert_eq!(state.offset.y, 0.0);
+    }
+
+    #[test]
+    fn test_short_pull_does_not_refresh() {
+        let mut state = ScrollState {
+            refresh_enabled: true,
+            ..state()
+        };
+        state.scroll_by(Vec2::ZERO, ScrollPhase::Began, 0.0);
+        state.scroll_by(Vec2::new(0.0, 20.0), ScrollPhase::Changed, 0.0);
+        state.scroll_by(Vec2::ZERO, ScrollPhase::Ended, 0.0);
+        assert!(!state.is_refreshing());
+    }
+
+    #[test]
+    fn test_takes_scroll_until_edge() {
+        let mut state = state();
+        let down = Vec2::new(0.0, -10.0);
+        let up = Vec2::new(0.0, 10.0);
+        assert!(state.takes_scroll(down, ScrollPhase::None));
+        assert!(!state.takes_scroll(up, ScrollPhase::None));
+        assert!(!state.takes_scroll(Vec2::new(-10.0, 0.0), ScrollPhase::None));
+
+        state.scroll_to(Vec2::new(0.0, 400.0), false);
+        assert!(!state.takes_scroll(down, ScrollPhase::Began));
+        assert!(state.takes_scroll(up, ScrollPhase::Began));
+
+        // A gesture it already has stays, even past the edge
+        state.scroll_by(up, ScrollPhase::Began, 0.0);
+        assert!(state.takes_scroll(down, ScrollPhase::Changed));
+
+        let contained = ScrollState {
+            chaining: ScrollChaining::Contain,
+            ..ScrollState::new()
+        };
+        assert!(contained.takes_scroll(up, ScrollPhase::None));
+    }
+
+    #[test]
+    fn test_gesture_locks_to_dominant_axis() {
+        let mut state = ScrollState {
+            content_size: Vec2::new(500.0, 500.0),
+            axis_lock: true,
+            ..state()
+        };
+        state.scroll_by(Vec2::ZERO, ScrollPhase::Began, 0.0);
+        state.scroll_by(Vec2::new(-10.0, -4.0), ScrollPhase::Changed, 0.0);
+        state.scroll_by(Vec2::new(-2.0, -10.0), ScrollPhase::Changed, 0.0);
+        assert_eq!(state.offset, Vec2::new(12.0, 0.0));
+
+        state.scroll_by(Vec2::ZERO, ScrollPhase::Ended, 0.0);
+        state.axis_lock = false;
+        state.scroll_by(Vec2::ZERO, ScrollPhase::Began, 1.0);
+        state.scroll_by(Vec2::new(-2.0, -10.0), ScrollPhase::Changed, 1.0);
+        assert_eq!(state.offset, Vec2::new(14.0, 10.0));
+    }
+
+    #[test]
+    fn test_diagonal_gesture_belongs_to_dominant_axis() {
+        // A carousel that only scrolls sideways
+        let state = ScrollState {
+            content_size: Vec2::new(500.0, 100.0),
+            axis_lock: true,
+            ..state()
+        };
+        assert!(state.takes_scroll(Vec2::new(-10.0, -2.0), ScrollPhase::Began));
+        assert!(!state.takes_scroll(Vec2::new(-2.0, -10.0), ScrollPhase::Began));
+
+        let free = ScrollState {
+            axis_lock: false,
+            ..state
+        };
+        assert!(free.takes_scroll(Vec2::new(-2.0, -10.0), ScrollPhase::Began));
+    }
+
+    #[test]
+    fn test_near_end() {
+        let mut state = state();
+        assert!(!state.is_near_end(50.0));
+        state.scroll_to(Vec2::new(0.0, 360.0), false);
+        assert!(state.is_near_end(50.0));
+    }
+}
@@ -0,0 +1,454 @@
+//! Rubber-band (marquee) selection over a group of selectable children
+//!
+//! Dragging across the background of a [`selection_area`] draws a marquee,
+//! and every [`selectable`] child it touches is selected. Holding Shift or
+//! Cmd when the drag starts adds to the current selection instead of
+//! replacing it; a click on the background clears it.
+//!
+//! ```ignore
+//! let files = new_entity(SelectionAreaState::new());
+//! selection_area(files.clone())
+//!     .container(|c| c.flex_row().flex_wrap().gap(8.0))
+//!     .child(selectable(0, file_icon("a.txt")))
+//!     .child(selectable(1, file_icon("b.txt")))
+//!     .on_select(|selected| println!("{selected:?}"))
+//! ```
+
+use crate::{
+    color::{Color, ColorExt, colors},
+    element::{Container, Element, LayoutContext, container},
+    entity::{Entity, read_entity, update_entity},
+    geometry::{Corners, Edges, Rect},
+    interaction::{DRAG_THRESHOLD, ElementId, EventHandlers, registry::register_element},
+    layer::MouseButton,
+    render::{PaintContext, PaintQuad},
+};
+use glam::Vec2;
+use std::cell::RefCell;
+use std::rc::Rc;
+use taffy::prelude::*;
+
+/// Hit test z-index of the target catching moves while a marquee is dragged
+const MARQUEE_DRAG_Z: i32 = 200;
+
+thread_local! {
+    /// Selection areas being painted, innermost last
+    static PAINTING_AREAS: RefCell<Vec<PaintingArea>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Drop selection areas left pushed by a paint that panicked
+pub(crate) fn clear_painting_areas() {
+    PAINTING_AREAS.with(|areas| areas.borrow_mut().clear());
+}
+
+/// What selectable children need from the area painting them
+struct PaintingArea {
+    selected: Vec<usize>,
+    highlight: Option<Color>,
+    /// Index and screen bounds of each selectable painted so far
+    items: Vec<(usize, Rect)>,
+}
+
+/// A marquee being dragged
+#[derive(Debug, Clone)]
+struct MarqueeDrag {
+    start: Vec2,
+    current: Vec2,
+    /// Selection kept from before the drag
+    base: Vec<usize>,
+    /// Whether the pointer has moved far enough to show the marquee
+    active: bool,
+}
+
+impl MarqueeDrag {
+    fn rect(&self) -> Rect {
+        let min = self.start.min(self.current);
+        let max = self.start.max(self.current);
+        Rect::from_pos_size(min, max - min)
+    }
+}
+
+/// Selection of a [`SelectionArea`], persisted via the Entity system
+#[derive(Debug, Clone, Default)]
+pub struct SelectionAreaState {
+    /// Selected indices, in ascending order
+    selected: Vec<usize>,
+    /// Selectable children and their screen bounds from the last frame
+    items: Vec<(usize, Rect)>,
+    drag: Option<MarqueeDrag>,
+}
+
+impl SelectionAreaState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Selected indices, in ascending order
+    pub fn selected(&self) -> &[usize] {
+        &self.selected
+    }
+
+    /// Whether the child with `index` is selected
+    pub fn is_selected(&self, index: usize) -> bool {
+        self.selected.binary_search(&index).is_ok()
+    }
+
+    /// Replace the selection
+    pub fn set_selected(&mut self, selected: impl IntoIterator<Item = usize>) {
+        self.selected = selected.into_iter().collect();
+        self.selected.sort_unstable();
+        self.selected.dedup();
+    }
+
+    /// Deselect everything
+    pub fn clear(&mut self) {
+        self.selected.clear();
+    }
+
+    /// The marquee in screen coordinates, while one is being dragged
+    pub fn marquee(&self) -> Option<Rect> {
+        self.drag
+            .as_ref()
+            .filter(|drag| drag.active)
+            .map(MarqueeDrag::rect)
+    }
+
+    /// Start a marquee at `position`, returning whether the selection changed
+    ///
+    /// With `extend`, children the marquee touches are added to the current
+    /// selection; otherwise it starts empty.
+    fn begin(&mut self, position: Vec2, extend: bool) -> bool {
+        self.drag = Some(MarqueeDrag {
+            start: position,
+            current: position,
+            base: if extend {
+                self.selected.clone()
+            } else {
+                Vec::new()
+            },
+            active: false,
+        });
+        self.update_selection()
+    }
+
+    /// Move the marquee's corner, returning whether the selection changed
+    fn drag_to(&mut self, position: Vec2) -> bool {
+        let Some(drag) = &mut self.drag else {
+            return false;
+        };
+        drag.current = position;
+        drag.active |= (position - drag.start).length() >= DRAG_THRESHOLD;
+        self.update_selection()
+    }
+
+    /// Finish the marquee, keeping the selection
+    fn end(&mut self) {
+        self.drag = None;
+    }
+
+    fn update_selection(&mut self) -> bool {
+        let Some(drag) = &self.drag else {
+            return false;
+        };
+        let mut selected = drag.base.clone();
+        if drag.active {
+            let marquee = drag.rect();
+            selected.extend(
+                self.items
+                    .iter()
+                    .filter(|(_, bounds)| marquee.intersect(bounds).is_some())
+                    .map(|(index, _)| *index),
+            );
+        }
+        selected.sort_unstable();
+        selected.dedup();
+        if selected == self.selected {
+            return false;
+        }
+        self.selected = selected;
+        true
+    }
+}
+
+/// Called with the selected children's indices whenever the selection changes
+type SelectCallback = Rc<RefCell<Box<dyn FnMut(&[usize])>>>;
+
+/// Create a selection area bound to a persistent selection state
+pub fn selection_area(state: Entity<SelectionAreaState>) -> SelectionArea {
+    SelectionArea::new(state)
+}
+
+/// A container whose [`selectable`] children can be picked with a marquee
+///
+/// The marquee starts on the area's background, so children that handle
+/// mouse downs themselves, like buttons, keep working. It's drawn in the
+/// layer's overlay pass, above anything painted after the area.
+pub struct SelectionArea {
+    state: Entity<SelectionAreaState>,
+    element_id: ElementId,
+    content: Container,
+    marquee_color: Color,
+    highlight: Option<Color>,
+    on_select: Option<SelectCallback>,
+}
+
+impl SelectionArea {
+    pub fn new(state: Entity<SelectionAreaState>) -> Self {
+        Self {
+            element_id: ElementId::stable(format!("selection-area:{:?}", state.id())),
+            state,
+            content: container(),
+            marquee_color: colors::BLUE_500,
+            highlight: Some(colors::BLUE_500.with_alpha(0.2)),
+            on_select: None,
+        }
+    }
+
+    /// Set a unique key for this selection area
+    pub fn with_key(mut self, key: impl AsRef<str>) -> Self {
+        self.element_id = ElementId::stable(format!("selection-area:{}", key.as_ref()));
+        self
+    }
+
+    /// Style the container holding the children
+    pub fn container(mut self, style: impl FnOnce(Container) -> Container) -> Self {
+        self.content = style(self.content);
+        self
+    }
+
+    /// Add a child
+    pub fn child(mut self, child: impl Element + 'static) -> Self {
+        self.content = self.content.child(child);
+        self
+    }
+
+    /// Set the marquee's border color; its fill is a faint version of it
+    pub fn marquee_color(mut self, color: Color) -> Self {
+        self.marquee_color = color;
+        self
+    }
+
+    /// Set the color drawn over selected children, or `None` to leave
+    /// showing the selection to the children
+    pub fn highlight(mut self, color: Option<Color>) -> Self {
+        self.highlight = color;
+        self
+    }
+
+    /// Call `handler` with the selected indices whenever they change
+    pub fn on_select<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(&[usize]) + 'static,
+    {
+        self.on_select = Some(Rc::new(RefCell::new(Box::new(handler))));
+        self
+    }
+
+    fn handlers(&self) -> EventHandlers {
+        let down_state = self.state.clone();
+        let move_state = self.state.clone();
+        let up_state = self.state.clone();
+        let down_select = self.on_select.clone();
+        let move_select = self.on_select.clone();
+
+        EventHandlers::new()
+            .on_mouse_down(move |button, position, _, modifiers, _| {
+                if button != MouseButton::Left {
+                    return;
+                }
+                let extend = modifiers.shift || modifiers.cmd;
+                let changed =
+                    update_entity(&down_state, |s| s.begin(position, extend)).unwrap_or(false);
+                if changed {
+                    notify(&down_state, &down_select);
+                }
+            })
+            .on_mouse_move(move |position, _| {
+                let changed = update_entity(&move_state, |s| s.drag_to(position)).unwrap_or(false);
+                if changed {
+                    notify(&move_state, &move_select);
+                }
+            })
+            .on_mouse_up(move |button, _, _, _| {
+                if button == MouseButton::Left {
+                    update_entity(&up_state, |s| s.end());
+                }
+            })
+    }
+}
+
+/// Pass the current selection to the area's callback, if it has one
+fn notify(
+    state: &Entity<SelectionAreaState>,
+    on_select: &Option<SelectCallback>,
+) {
+    let Some(on_select) = on_select else {
+        return;
+    };
+    if let Some(selected) = read_entity(state, |s| s.selected.clone()) {
+        (on_select.borrow_mut())(&selected);
+    }
+}
+
+impl Element for SelectionArea {
+    fn layout(&mut self, ctx: &mut LayoutContext) -> NodeId {
+        self.content.layout(ctx)
+    }
+
+    fn paint(&mut self, bounds: Rect, ctx: &mut PaintContext) {
+        if !ctx.is_visible(&bounds) {
+            return;
+        }
+
+        let (selected, marquee, previous_items) = read_entity(&self.state, |s| {
+            (s.selected.clone(), s.marquee(), s.items.clone())
+        })
+        .unwrap_or_default();
+        let depth = PAINTING_AREAS.with(|areas| {
+            let mut areas = areas.borrow_mut();
+            areas.push(PaintingArea {
+                selected,
+                highlight: self.highlight,
+                items: Vec::new(),
+            });
+            areas.len() - 1
+        });
+        self.content.paint(bounds, ctx);
+        let items = PAINTING_AREAS.with(|areas| {
+            let mut areas = areas.borrow_mut();
+            let items = areas
+                .get_mut(depth)
+                .map(|area| std::mem::take(&mut area.items));
+            areas.truncate(depth);
+            items.unwrap_or_default()
+        });
+
+        // Only write the items when they move, so observers aren't notified every frame
+        if items != previous_items {
+            update_entity(&self.state, |s| s.items = items);
+        }
+
+        // Register after the children so they win hit tests over the background
+        register_element(self.element_id, Rc::new(RefCell::new(self.handlers())));
+        ctx.register_hit_test(self.element_id, bounds, 0);
+        if read_entity(&self.state, |s| s.drag.is_some()).unwrap_or(false) {
+            // Keep receiving moves while the pointer is over the children
+            let drag_id = ElementId::stable(format!("{:?}:drag", self.element_id));
+            let handlers = EventHandlers::new().on_mouse_move({
+                let state = self.state.clone();
+                let on_select = self.on_select.clone();
+                move |position, _| {
+                    let changed = update_entity(&state, |s| s.drag_to(position)).unwrap_or(false);
+                    if changed {
+                        notify(&state, &on_select);
+                    }
+                }
+            });
+            register_element(drag_id, Rc::new(RefCell::new(handlers)));
+            ctx.register_hit_test(drag_id, bounds, MARQUEE_DRAG_Z);
+        }
+
+        if let Some(marquee) = marquee {
+            // The marquee is kept in screen coordinates
+            let transform = ctx.draw_list.current_transform();
+            let marquee = Rect::from_pos_size(
+                transform.apply_inverse(marquee.pos),
+                marquee.size / transform.scale,
+            );
+            ctx.paint_overlay_quad(PaintQuad {
+                bounds: marquee,
+                corner_radii: Corners::zero(),
+                fill: self.marquee_color.with_alpha(0.15),
+                border_widths: Edges::all(1.0),
+                border_color: self.marquee_color,
+            });
+        }
+    }
+}
+
+/// Make `child` selectable by the enclosing [`selection_area`] as `index`
+pub fn selectable(index: usize, child: impl Element + 'static) -> Selectable {
+    Selectable {
+        index,
+        child: Box::new(child),
+    }
+}
+
+/// A child of a [`SelectionArea`] that the marquee can select
+///
+/// Outside a selection area it paints its child unchanged.
+pub struct Selectable {
+    index: usize,
+    child: Box<dyn Element>,
+}
+
+impl Element for Selectable {
+    fn layout(&mut self, ctx: &mut LayoutContext) -> NodeId {
+        self.child.layout(ctx)
+    }
+
+    fn paint(&mut self, bounds: Rect, ctx: &mut PaintContext) {
+        self.child.paint(bounds, ctx);
+
+        let screen_bounds = ctx.draw_list.current_transform().apply_rect(bounds);
+        let highlight = PAINTING_AREAS.with(|areas| {
+            let mut areas = areas.borrow_mut();
+            let area = areas.last_mut()?;
+            area.items.push((self.index, screen_bounds));
+            let selected = area.selected.binary_search(&self.index).is_ok();
+            area.highlight.filter(|_| selected)
+        });
+        if let Some(color) = highlight {
+            ctx.paint_solid_quad(bounds, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> SelectionAreaState {
+        SelectionAreaState {
+            items: vec![
+                (0, Rect::new(0.0, 0.0, 50.0, 50.0)),
+                (1, Rect::new(60.0, 0.0, 50.0, 50.0)),
+                (2, Rect::new(0.0, 60.0, 50.0, 50.0)),
+            ],
+            ..SelectionAreaState::new()
+        }
+    }
+
+    #[test]
+    fn test_marquee_selects_intersecting_items() {
+        let mut state = state();
+        state.begin(Vec2::new(-10.0, -10.0), false);
+        // Too short a drag to start the marquee
+        assert!(!state.drag_to(Vec2::new(-8.0, -8.0)));
+        assert_eq!(state.marquee(), None);
+
+        assert!(state.drag_to(Vec2::new(70.0, 20.0)));
+        assert_eq!(state.selected(), &[0, 1]);
+        // Shrinking the marquee deselects again
+        assert!(state.drag_to(Vec2::new(20.0, 20.0)));
+        assert_eq!(state.selected(), &[0]);
+
+        state.end();
+        assert_eq!(state.marquee(), None);
+        assert!(state.is_selected(0));
+    }
+
+    #[test]
+    fn test_extend_keeps_selection() {
+        let mut state = state();
+        state.set_selected([2]);
+        state.begin(Vec2::new(55.0, -5.0), true);
+        state.drag_to(Vec2::new(120.0, 20.0));
+        assert_eq!(state.selected(), &[1, 2]);
+
+        // A plain click on the background clears the selection
+        state.end();
+        assert!(state.begin(Vec2::new(200.0, 200.0), false));
+        assert!(state.selected().is_empty());
+    }
+}
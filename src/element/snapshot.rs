@@ -0,0 +1,50 @@
+//! Snapshot wrapper - captures a subtree to an image
+
+use crate::{
+    element::{Element, LayoutContext},
+    geometry::Rect,
+    image::ImageHandle,
+    render::PaintContext,
+};
+use taffy::prelude::*;
+
+/// Wrap an element so its painted output is captured into `handle`
+///
+/// The capture is taken whenever the handle is pending: once for a new
+/// handle, and again after each call to [`ImageHandle::request`].
+pub fn snapshot<E: Element>(child: E, handle: ImageHandle) -> Snapshot<E> {
+    Snapshot { child, handle }
+}
+
+/// An element wrapper that renders its child to an offscreen image
+pub struct Snapshot<E: Element> {
+    child: E,
+    handle: ImageHandle,
+}
+
+impl<E: Element> Element for Snapshot<E> {
+    fn layout(&mut self, ctx: &mut LayoutContext) -> NodeId {
+        self.child.layout(ctx)
+    }
+
+    fn paint(&mut self, bounds: Rect, ctx: &mut PaintContext) {
+        if !self.handle.is_pending() {
+            self.child.paint(bounds, ctx);
+            return;
+        }
+
+        let start = ctx.draw_list.current_pos();
+        self.child.paint(bounds, ctx);
+        ctx.capture_since(start, bounds, &self.handle);
+    }
+}
+
+/// Trait for elements that can be captured with `.snapshot(handle)`
+pub trait Snapshottable: Element + Sized {
+    /// Capture this element's painted output into `handle`
+    fn snapshot(self, handle: ImageHandle) -> Snapshot<Self> {
+        snapshot(self, handle)
+    }
+}
+
+impl<T: Element> Snapshottable for T {}
@@ -0,0 +1,192 @@
+//! Tag element - a rounded chip of text with an optional dismiss button
+
+use crate::{
+    color::{Color, ColorExt, colors},
+    element::{Element, LayoutContext, PaintContext},
+    geometry::Rect,
+    interaction::{
+        ElementId, EventHandlers,
+        registry::{get_element_state, register_element},
+    },
+    render::PaintText,
+    style::{ElementStyle, LineHeight, TextStyle},
+    text_system::TextConfig,
+};
+use glam::Vec2;
+use std::cell::RefCell;
+use std::rc::Rc;
+use taffy::prelude::*;
+
+/// Horizontal space between the tag's edge and its contents
+const TAG_PADDING_H: f32 = 8.0;
+/// Vertical space between the tag's edge and its text
+const TAG_PADDING_V: f32 = 3.0;
+/// Space between the text and the dismiss button
+const DISMISS_GAP: f32 = 4.0;
+/// Size of the dismiss button
+const DISMISS_SIZE: f32 = 14.0;
+
+/// Create a tag showing `text`
+pub fn tag(text: impl Into<String>) -> Tag {
+    Tag::new(text)
+}
+
+/// A small rounded label, dismissable when given an [`on_dismiss`](Tag::on_dismiss) handler
+pub struct Tag {
+    text: String,
+    text_style: TextStyle,
+    background: Color,
+    key: Option<String>,
+    /// Handlers for the dismiss button, if there is one
+    dismiss_handlers: Option<Rc<RefCell<EventHandlers>>>,
+}
+
+impl Tag {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            text_style: TextStyle {
+                size: 12.0,
+                color: colors::GRAY_800,
+                ..Default::default()
+            },
+            background: colors::GRAY_200,
+            key: None,
+            dismiss_handlers: None,
+        }
+    }
+
+    /// Set a unique key for this tag, used to identify its dismiss button
+    ///
+    /// Defaults to the tag's text.
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    /// Set the background color
+    pub fn background(mut self, color: Color) -> Self {
+        self.background = color;
+        self
+    }
+
+    /// Set the text color
+    pub fn text_color(mut self, color: Color) -> Self {
+        self.text_style.color = color;
+        self
+    }
+
+    /// Set the text style
+    pub fn text_style(mut self, style: TextStyle) -> Self {
+        self.text_style = style;
+        self
+    }
+
+    /// Show a dismiss button that calls `handler` when clicked
+    pub fn on_dismiss<F>(mut self, mut handler: F) -> Self
+    where
+        F: FnMut() + 'static,
+    {
+        let mut handlers = EventHandlers::new();
+        handlers.on_click = Some(Box::new(move |_, _, _, _, _| handler()));
+        self.dismiss_handlers = Some(Rc::new(RefCell::new(handlers)));
+        self
+    }
+
+    fn dismiss_id(&self) -> ElementId {
+        let key = self.key.as_deref().unwrap_or(&self.text);
+        ElementId::stable(format!("tag:{}:dismiss", key))
+    }
+
+    fn paint_dismiss(&self, bounds: Rect, ctx: &mut PaintContext) {
+        let Some(handlers) = self.dismiss_handlers.clone() else {
+            return;
+        };
+
+        let button = Rect::from_pos_size(
+            Vec2::new(
+                bounds.pos.x + bounds.size.x - TAG_PADDING_H / 2.0 - DISMISS_SIZE,
+                bounds.pos.y + (bounds.size.y - DISMISS_SIZE) / 2.0,
+            ),
+            Vec2::splat(DISMISS_SIZE),
+        );
+
+        let id = self.dismiss_id();
+        register_element(id, handlers);
+        if get_element_state(id).unwrap_or_default().is_hovered {
+            ctx.draw_list.add_frame(
+                button,
+                ElementStyle::new()
+                    .with_background(self.text_style.color.with_alpha(0.15))
+                    .with_corner_radius(DISMISS_SIZE / 2.0),
+            );
+        }
+
+        let style = TextStyle {
+            size: DISMISS_SIZE,
+            line_height: LineHeight::Relative(1.0),
+            ..self.text_style.clone()
+        };
+        let mark_size = measure(ctx, "×", &style);
+        ctx.paint_text(PaintText {
+            position: button.pos + (button.size - mark_size) / 2.0,
+            text: "×".to_string(),
+            style,
+            measured_size: Some(mark_size),
+        });
+
+        ctx.register_hit_test(id, button, 0);
+    }
+}
+
+impl Element for Tag {
+    fn layout(&mut self, ctx: &mut LayoutContext) -> NodeId {
+        // Leave room on the right for the dismiss button
+        let right = match self.dismiss_handlers {
+            Some(_) => TAG_PADDING_H / 2.0 + DISMISS_GAP + DISMISS_SIZE,
+            None => TAG_PADDING_H,
+        };
+        let style = Style {
+            padding: taffy::Rect {
+                left: LengthPercentage::length(TAG_PADDING_H),
+                right: LengthPercentage::length(right),
+                top: LengthPercentage::length(TAG_PADDING_V),
+                bottom: LengthPercentage::length(TAG_PADDING_V),
+            },
+            flex_shrink: 0.0,
+            ..Default::default()
+        };
+        ctx.request_text_layout(style, &self.text, &self.text_style)
+    }
+
+    fn paint(&mut self, bounds: Rect, ctx: &mut PaintContext) {
+        if !ctx.is_visible(&bounds) {
+            return;
+        }
+
+        ctx.draw_list.add_frame(
+            bounds,
+            ElementStyle::new()
+                .with_background(self.background)
+                .with_corner_radius(bounds.size.y / 2.0),
+        );
+
+        let text_size = measure(ctx, &self.text, &self.text_style);
+        ctx.paint_text(PaintText {
+            position: Vec2::new(
+                bounds.pos.x + TAG_PADDING_H,
+                bounds.pos.y + (bounds.size.y - text_size.y) / 2.0,
+            ),
+            text: self.text.clone(),
+            style: self.text_style.clone(),
+            measured_size: Some(text_size),
+        });
+
+        self.paint_dismiss(bounds, ctx);
+    }
+}
+
+fn measure(ctx: &mut PaintContext, text: &str, style: &TextStyle) -> Vec2 {
+    ctx.text_system
+        .measure_text(text, &TextConfig::from(style), None, ctx.scale_factor)
+}
@@ -1,10 +1,27 @@
 use crate::{
+    color::{ColorExt, colors},
     element::{Element, LayoutContext, PaintContext},
     geometry::Rect,
+    interaction::{
+        ElementId, EventHandlers, current_style_state, register_element,
+        text_selection::{
+            SelectionPoint, extend_text_drag, register_selectable_text, selected_range,
+            start_text_drag, text_selection_enabled,
+        },
+    },
+    layer::MouseButton,
+    layout_engine::ElementData,
     layout_id::LayoutId,
     render::PaintText,
-    style::TextStyle,
+    style::{StyleStates, TextStyle},
+    text_effect::GlyphEffect,
+    text_system::{TextConfig, TextSystem},
 };
+
+pub use crate::text_system::{Truncation, truncate_text};
+use glam::Vec2;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
 use taffy::prelude::*;
 
 /// Create a new text element
@@ -16,6 +33,12 @@ pub fn text(content: impl Into<String>, style: TextStyle) -> Text {
 pub struct Text {
     content: String,
     style: TextStyle,
+    truncation: Truncation,
+    states: StyleStates,
+    /// Per-glyph animation applied when painting
+    glyph_effect: Option<GlyphEffect>,
+    /// Shared flag updated with the truncated state on each paint
+    truncated_flag: Option<Rc<Cell<bool>>>,
     node_id: Option<NodeId>,
     /// Stable layout ID for caching across frames
     layout_id: Option<LayoutId>,
@@ -26,6 +49,10 @@ impl Text {
         Self {
             content: content.into(),
             style,
+            truncation: Truncation::None,
+            states: StyleStates::default(),
+            glyph_effect: None,
+            truncated_flag: None,
             node_id: None,
             layout_id: None,
         }
@@ -36,11 +63,64 @@ impl Text {
         self.layout_id = Some(id.into());
         self
     }
+
+    /// Keep the text on one line, shortening it with an ellipsis when it
+    /// doesn't fit the available width
+    pub fn truncate(mut self, truncation: Truncation) -> Self {
+        self.truncation = truncation;
+        self
+    }
+
+    /// Set the text color per interaction state
+    ///
+    /// The state comes from the nearest enclosing interactive element.
+    pub fn states(mut self, states: StyleStates) -> Self {
+        self.states = states;
+        self
+    }
+
+    /// Place each glyph with `effect` when painting, e.g. a wave or a
+    /// typewriter reveal
+    pub fn glyph_effect(mut self, effect: GlyphEffect) -> Self {
+        self.glyph_effect = Some(effect);
+        self
+    }
+
+    /// Report whether the text was truncated into `flag` each time it paints,
+    /// e.g. to show the full string in a tooltip only when it's cut off
+    pub fn report_truncated(mut self, flag: Rc<Cell<bool>>) -> Self {
+        self.truncated_flag = Some(flag);
+        self
+    }
+
+    /// The full, untruncated text
+    pub fn content(&self) -> &str {
+        &self.content
+    }
 }
 
 impl Element for Text {
     fn layout(&mut self, ctx: &mut LayoutContext) -> NodeId {
-        let node_id = if let Some(ref layout_id) = self.layout_id {
+        let node_id = if self.truncation != Truncation::None {
+            // Measure as a single line, but allow shrinking below that width
+            let style = Style {
+                min_size: Size {
+                    width: Dimension::length(0.0),
+                    height: Dimension::auto(),
+                },
+                ..Style::default()
+            };
+            let data = ElementData {
+                text: Some((self.content.clone(), self.style.clone())),
+                background: None,
+                no_wrap: true,
+            };
+            if let Some(ref layout_id) = self.layout_id {
+                ctx.request_layout_with_data_cached(layout_id, style, data, &[], &[])
+            } else {
+                ctx.request_layout_with_data(style, data, &[])
+            }
+        } else if let Some(ref layout_id) = self.layout_id {
             // Use cached layout
             ctx.request_text_layout_cached(layout_id, Style::default(), &self.content, &self.style)
         } else {
@@ -56,11 +136,130 @@ impl Element for Text {
             return;
         }
 
-        ctx.paint_text(PaintText {
+        let truncated = if self.truncation != Truncation::None {
+            // Layout rounds to whole pixels, so allow the natural width to
+            // exceed the bounds slightly before truncating
+            ctx.text_system
+                .measure_truncated(
+                    &self.content,
+                    &TextConfig::from(&self.style),
+                    bounds.size.x + 1.0,
+                    self.truncation,
+                    ctx.scale_factor,
+                )
+                .truncated
+        } else {
+            None
+        };
+
+        if let Some(flag) = &self.truncated_flag {
+            flag.set(truncated.is_some());
+        }
+
+        let state = current_style_state();
+        let color = self.states.resolve(state).text_color;
+        let color = self
+            .states
+            .apply_opacity(color.unwrap_or(self.style.color), state);
+
+        let content = truncated.unwrap_or_else(|| self.content.clone());
+        if text_selection_enabled() {
+            self.paint_selectable(&content, bounds, ctx);
+        }
+
+        let text = PaintText {
             position: bounds.pos,
-            text: self.content.clone(),
-            style: self.style.clone(),
+            text: content,
+            style: TextStyle {
+                color,
+                ..self.style.clone()
+            },
             measured_size: Some(bounds.size),
-        });
+        };
+        match &self.glyph_effect {
+            Some(effect) => ctx.paint_text_with_effect(text, effect),
+            None => ctx.paint_text(text),
+        }
+    }
+}
+
+impl Text {
+    /// Make the text selectable by dragging, highlighting the selected part
+    fn paint_selectable(&self, content: &str, bounds: Rect, ctx: &mut PaintContext) {
+        let config = TextConfig::from(&self.style);
+        let Ok(shaped) = ctx
+            .text_system
+            .shape_text(content, &config, None, ctx.scale_factor)
+        else {
+            return;
+        };
+
+        let index = register_selectable_text(content);
+        if let Some(range) = selected_range(index, content.len()) {
+            for rect in TextSystem::selection_rects(&shaped, range) {
+                ctx.paint_solid_quad(
+                    Rect::from_pos_size(bounds.pos + rect.pos, rect.size),
+                    colors::BLUE_500.with_alpha(0.3),
+                );
+            }
+        }
+
+        let shaped = Rc::new(shaped);
+        let point_at = move |local: Vec2| SelectionPoint {
+            text: index,
+            offset: TextSystem::hit_test_text(&shaped, local).0,
+        };
+        let press_point = point_at.clone();
+        let handlers = EventHandlers::new()
+            .on_mouse_down(move |button, _, local, _, _| {
+                if button == MouseButton::Left {
+                    start_text_drag(press_point(local));
+                }
+            })
+            .on_mouse_move(move |_, local| extend_text_drag(point_at(local)));
+
+        let id = ElementId::stable(format!("selectable-text:{}", index));
+        register_element(id, Rc::new(RefCell::new(handlers)));
+        ctx.register_hit_test(id, bounds, 0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every character is 10 wide
+    fn measure(s: &str) -> f32 {
+        s.chars().count() as f32 * 10.0
+    }
+
+    #[test]
+    fn test_truncate_fits() {
+        assert_eq!(
+            truncate_text("hello", 50.0, Truncation::Ellipsis, measure),
+            None
+        );
+        assert_eq!(
+            truncate_text("hello", 10.0, Truncation::None, measure),
+            None
+        );
+    }
+
+    #[test]
+    fn test_truncate_end() {
+        let result = truncate_text("hello world", 60.0, Truncation::Ellipsis, measure);
+        assert_eq!(result.as_deref(), Some("hello…"));
+    }
+
+    #[test]
+    fn test_truncate_middle() {
+        let result = truncate_text("/usr/local/bin/tool", 90.0, Truncation::Middle, measure);
+        assert_eq!(result.as_deref(), Some("/usr…tool"));
+    }
+
+    #[test]
+    fn test_truncate_to_ellipsis_only() {
+        let result = truncate_text("hello", 5.0, Truncation::Ellipsis, measure);
+        assert_eq!(result.as_deref(), Some("…"));
     }
 }
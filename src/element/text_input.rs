@@ -1,17 +1,20 @@
 //! Text input element with cursor, selection, and keyboard handling
 
 use crate::{
+    caret::{CaretMovement, caret_config},
+    clock::frame_time,
     color::{Color, ColorExt, colors},
     element::{Element, LayoutContext},
-    entity::{Entity, read_entity, update_entity},
+    entity::{Entity, read_entity, try_with_entity_store, update_entity},
     geometry::{Corners, Edges, Rect},
     interaction::{
         ElementId, Interactable, InteractiveElement,
         registry::get_element_state,
     },
-    layer::Key,
+    layer::{Key, MouseButton},
     render::{PaintContext, PaintQuad, PaintText},
     style::TextStyle,
+    text_system::{CaretAffinity, CaretRects, CharIndex, ShapedText, TextSystem},
 };
 use glam::Vec2;
 use std::cell::RefCell;
@@ -27,10 +30,58 @@ pub struct TextInputState {
     pub cursor: usize,
     /// Selection start (if selecting)
     pub selection_start: Option<usize>,
-    /// Whether cursor is visible (for blinking)
-    pub cursor_visible: bool,
-    /// Frame counter for cursor blinking
-    pub blink_counter: u32,
+    /// Which side of a direction boundary the cursor is drawn on
+    pub affinity: CaretAffinity,
+    /// Frame time of the last edit or caret movement, which holds the caret
+    /// solid while typing
+    pub last_activity: f32,
+    /// Error from the input's validator, if the current text is invalid
+    pub validation_error: Option<String>,
+    /// Granularity and initial range of an in-progress mouse selection
+    mouse_selection: Option<(SelectionGranularity, usize, usize)>,
+}
+
+/// The unit a mouse selection grows by while dragging
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SelectionGranularity {
+    /// Single click: select between characters
+    #[default]
+    Character,
+    /// Double click: select whole words
+    Word,
+    /// Triple click: select whole lines
+    Line,
+}
+
+impl SelectionGranularity {
+    /// Granularity for a mouse down with the given click count
+    pub fn from_click_count(count: u32) -> Self {
+        match count {
+            0 | 1 => SelectionGranularity::Character,
+            2 => SelectionGranularity::Word,
+            _ => SelectionGranularity::Line,
+        }
+    }
+}
+
+/// Character classes that words are split on
+#[derive(PartialEq)]
+enum CharClass {
+    Word,
+    Whitespace,
+    Punctuation,
+}
+
+impl CharClass {
+    fn of(c: char) -> Self {
+        if c.is_alphanumeric() || c == '_' {
+            CharClass::Word
+        } else if c.is_whitespace() {
+            CharClass::Whitespace
+        } else {
+            CharClass::Punctuation
+        }
+    }
 }
 
 impl Default for TextInputState {
@@ -39,8 +90,10 @@ impl Default for TextInputState {
             text: String::new(),
             cursor: 0,
             selection_start: None,
-            cursor_visible: true,
-            blink_counter: 0,
+            affinity: CaretAffinity::default(),
+            last_activity: 0.0,
+            validation_error: None,
+            mouse_selection: None,
         }
     }
 }
@@ -92,6 +145,31 @@ impl TextInputState {
         self.delete_selection();
         self.text.insert_str(self.cursor, s);
         self.cursor += s.len();
+        self.affinity = CaretAffinity::Upstream;
+    }
+
+    /// Insert a typed character if `mask` and `max_length` allow it
+    ///
+    /// Any selection is replaced, so it doesn't count towards the length and
+    /// the mask is checked against the text without it. Returns whether the
+    /// character was inserted.
+    pub fn type_char(&mut self, c: char, mask: &InputMask, max_length: Option<usize>) -> bool {
+        let (text_after_delete, insert_at) = match self.selection_range() {
+            Some((start, end)) => {
+                let mut text = self.text.clone();
+                text.replace_range(start..end, "");
+                (text, start)
+            }
+            None => (self.text.clone(), self.cursor),
+        };
+        let within_length = max_length.is_none_or(|max| text_after_delete.chars().count() < max);
+
+        if within_length && mask.allows(&text_after_delete, insert_at, c) {
+            self.insert(&c.to_string());
+            true
+        } else {
+            false
+        }
     }
 
     /// Delete character before cursor (backspace)
@@ -128,10 +206,12 @@ impl TextInputState {
             if let Some((start, _)) = self.selection_range() {
                 self.cursor = start;
                 self.selection_start = None;
+                self.affinity = CaretAffinity::Downstream;
                 return;
             }
         }
 
+        self.affinity = CaretAffinity::Downstream;
         if self.cursor > 0 {
             if extend_selection && self.selection_start.is_none() {
                 self.selection_start = Some(self.cursor);
@@ -155,10 +235,12 @@ impl TextInputState {
             if let Some((_, end)) = self.selection_range() {
                 self.cursor = end;
                 self.selection_start = None;
+                self.affinity = CaretAffinity::Upstream;
                 return;
             }
         }
 
+        self.affinity = CaretAffinity::Upstream;
         if self.cursor < self.text.len() {
             if extend_selection && self.selection_start.is_none() {
                 self.selection_start = Some(self.cursor);
@@ -175,6 +257,49 @@ impl TextInputState {
         }
     }
 
+    /// Move cursor one character left or right on screen
+    ///
+    /// `shaped` is the text as laid out. In right-to-left text this moves
+    /// the cursor backward through the string for Right and forward for
+    /// Left, and at a boundary between directions it jumps to wherever the
+    /// next character is drawn.
+    pub fn move_visually(&mut self, shaped: &ShapedText, right: bool, extend_selection: bool) {
+        if !extend_selection {
+            // Collapse a selection to whichever end is further that way
+            if let Some((start, end)) = self.selection_range() {
+                let x = |index: usize, affinity| {
+                    TextSystem::caret_rects(shaped, CharIndex(index), affinity)
+                        .primary
+                        .pos
+                        .x
+                };
+                let start_x = x(start, CaretAffinity::Downstream);
+                let end_x = x(end, CaretAffinity::Upstream);
+                (self.cursor, self.affinity) = if (end_x > start_x) == right {
+                    (end, CaretAffinity::Upstream)
+                } else {
+                    (start, CaretAffinity::Downstream)
+                };
+                self.selection_start = None;
+                return;
+            }
+        }
+
+        let Some((index, affinity)) =
+            TextSystem::move_visually(shaped, CharIndex(self.cursor), self.affinity, right)
+        else {
+            return;
+        };
+        if extend_selection && self.selection_start.is_none() {
+            self.selection_start = Some(self.cursor);
+        }
+        self.cursor = index.0;
+        self.affinity = affinity;
+        if !extend_selection {
+            self.selection_start = None;
+        }
+    }
+
     /// Move cursor to start
     pub fn move_to_start(&mut self, extend_selection: bool) {
         if extend_selection && self.selection_start.is_none() {
@@ -197,6 +322,185 @@ impl TextInputState {
         }
     }
 
+    /// Move cursor to the start of the previous word (Option+Left)
+    pub fn move_word_left(&mut self, extend_selection: bool) {
+        let from = self.collapse_for_move(extend_selection, true);
+        self.cursor = self.previous_word_start(from);
+    }
+
+    /// Move cursor to the end of the next word (Option+Right)
+    pub fn move_word_right(&mut self, extend_selection: bool) {
+        let from = self.collapse_for_move(extend_selection, false);
+        self.cursor = self.next_word_end(from);
+    }
+
+    /// Move by word for Option+Left/Right, or to the start or end in a
+    /// password, whose word boundaries would reveal the hidden text
+    pub fn move_word(&mut self, right: bool, extend_selection: bool, password: bool) {
+        match (right, password) {
+            (false, false) => self.move_word_left(extend_selection),
+            (true, false) => self.move_word_right(extend_selection),
+            (false, true) => self.move_to_start(extend_selection),
+            (true, true) => self.move_to_end(extend_selection),
+        }
+    }
+
+    /// Prepare the selection for a cursor move and return where it starts
+    ///
+    /// Without `extend_selection` a selection collapses to the side the
+    /// cursor is moving towards.
+    fn collapse_for_move(&mut self, extend_selection: bool, backward: bool) -> usize {
+        if extend_selection {
+            if self.selection_start.is_none() {
+                self.selection_start = Some(self.cursor);
+            }
+            return self.cursor;
+        }
+        let from = match self.selection_range() {
+            Some((start, _)) if backward => start,
+            Some((_, end)) => end,
+            None => self.cursor,
+        };
+        self.selection_start = None;
+        from
+    }
+
+    /// Byte offset of the start of the word before `index`
+    fn previous_word_start(&self, index: usize) -> usize {
+        let is_word = |(_, c): &(usize, char)| CharClass::of(*c) == CharClass::Word;
+        let mut chars = self.text[..index].char_indices().rev().peekable();
+        while chars.next_if(|c| !is_word(c)).is_some() {}
+        let mut start = chars.peek().map_or(0, |(i, _)| *i);
+        while let Some((i, _)) = chars.next_if(is_word) {
+            start = i;
+        }
+        start
+    }
+
+    /// Byte offset of the end of the word after `index`
+    fn next_word_end(&self, index: usize) -> usize {
+        let is_word = |(_, c): &(usize, char)| CharClass::of(*c) == CharClass::Word;
+        let mut chars = self.text[index..].char_indices().peekable();
+        while chars.next_if(|c| !is_word(c)).is_some() {}
+        while chars.next_if(is_word).is_some() {}
+        chars.peek().map_or(self.text.len(), |(i, _)| index + i)
+    }
+
+    /// Byte range of the word (or run of whitespace) at `index`
+    ///
+    /// An index just past the end of a word selects that word, so double
+    /// clicking the right half of its last character still selects it.
+    pub fn word_range_at(&self, index: usize) -> (usize, usize) {
+        let index = index.min(self.text.len());
+        let after = self.text[index..].chars().next();
+        let before = self.text[..index].chars().next_back();
+        let class = match (before.map(CharClass::of), after.map(CharClass::of)) {
+            (Some(CharClass::Word), Some(class)) if class != CharClass::Word => CharClass::Word,
+            (_, Some(class)) => class,
+            (Some(class), None) => class,
+            (None, None) => return (0, 0),
+        };
+
+        // Punctuation is selected one character at a time
+        if class == CharClass::Punctuation {
+            return match after {
+                Some(c) if CharClass::of(c) == CharClass::Punctuation => {
+                    (index, index + c.len_utf8())
+                }
+                _ => (index - before.map_or(0, char::len_utf8), index),
+            };
+        }
+
+        let start = self.text[..index]
+            .char_indices()
+            .rev()
+            .take_while(|(_, c)| CharClass::of(*c) == class)
+            .last()
+            .map_or(index, |(i, _)| i);
+        let end = self.text[index..]
+            .char_indices()
+            .find(|(_, c)| CharClass::of(*c) != class)
+            .map_or(self.text.len(), |(i, _)| index + i);
+        (start, end)
+    }
+
+    /// Byte range of the line at `index`, excluding its line break
+    pub fn line_range_at(&self, index: usize) -> (usize, usize) {
+        let index = index.min(self.text.len());
+        let start = self.text[..index].rfind('\n').map_or(0, |i| i + 1);
+        let end = self.text[index..]
+            .find('\n')
+            .map_or(self.text.len(), |i| index + i);
+        (start, end)
+    }
+
+    fn range_at(&self, index: usize, granularity: SelectionGranularity) -> (usize, usize) {
+        match granularity {
+            SelectionGranularity::Character => (index, index),
+            SelectionGranularity::Word => self.word_range_at(index),
+            SelectionGranularity::Line => self.line_range_at(index),
+        }
+    }
+
+    /// Start a mouse selection at `index`
+    ///
+    /// Word and line granularity select the word or line under the mouse.
+    /// With `extend`, the current selection grows to `index` instead, as
+    /// with Shift+click.
+    pub fn start_mouse_selection(
+        &mut self,
+        index: usize,
+        granularity: SelectionGranularity,
+        extend: bool,
+    ) {
+        let index = index.min(self.text.len());
+        let anchor = if extend {
+            let anchor = self.selection_start.unwrap_or(self.cursor);
+            (anchor, anchor)
+        } else {
+            self.range_at(index, granularity)
+        };
+        self.mouse_selection = Some((granularity, anchor.0, anchor.1));
+        self.selection_start = None;
+        self.cursor = anchor.1;
+        self.update_mouse_selection(index);
+    }
+
+    /// Extend an in-progress mouse selection to `index`
+    pub fn update_mouse_selection(&mut self, index: usize) {
+        let Some((granularity, anchor_start, anchor_end)) = self.mouse_selection else {
+            return;
+        };
+        let (start, end) = self.range_at(index.min(self.text.len()), granularity);
+        let (anchor, cursor) = if start < anchor_start {
+            (anchor_end, start)
+        } else {
+            (anchor_start, end.max(anchor_end))
+        };
+        self.cursor = cursor;
+        self.selection_start = (anchor != cursor).then_some(anchor);
+    }
+
+    /// Finish the mouse selection, keeping what was selected
+    pub fn end_mouse_selection(&mut self) {
+        self.mouse_selection = None;
+    }
+
+    /// Check if a mouse selection is in progress
+    pub fn is_mouse_selecting(&self) -> bool {
+        self.mouse_selection.is_some()
+    }
+
+    /// Number of characters in the text
+    pub fn char_count(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    /// Check if the current text passed validation
+    pub fn is_valid(&self) -> bool {
+        self.validation_error.is_none()
+    }
+
     /// Select all text
     pub fn select_all(&mut self) {
         self.selection_start = Some(0);
@@ -207,6 +511,163 @@ impl TextInputState {
     pub fn clear_selection(&mut self) {
         self.selection_start = None;
     }
+
+    /// Replace the text, keeping the cursor and selection next to the same
+    /// unchanged text where possible
+    ///
+    /// A cursor before the changed part stays put, one after it keeps its
+    /// distance from the end, and one inside it moves to the end of the new
+    /// text there.
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        let text = text.into();
+        if text == self.text {
+            return;
+        }
+        let old = std::mem::replace(&mut self.text, text);
+        self.cursor = remap_index(&old, &self.text, self.cursor);
+        self.selection_start = self
+            .selection_start
+            .map(|start| remap_index(&old, &self.text, start))
+            .filter(|&start| start != self.cursor);
+        self.mouse_selection = None;
+    }
+}
+
+/// Map a byte offset in `old` to the matching offset in `new`
+fn remap_index(old: &str, new: &str, index: usize) -> usize {
+    let prefix: usize = old
+        .chars()
+        .zip(new.chars())
+        .take_while(|(a, b)| a == b)
+        .map(|(c, _)| c.len_utf8())
+        .sum();
+    if index <= prefix && index < old.len() {
+        return index;
+    }
+
+    let suffix: usize = old[prefix..]
+        .chars()
+        .rev()
+        .zip(new[prefix..].chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(c, _)| c.len_utf8())
+        .sum();
+    let from_end = old.len() - index.min(old.len());
+    new.len() - from_end.min(suffix)
+}
+
+/// Character used to render text in password mode
+const PASSWORD_BULLET: char = '\u{2022}';
+
+/// Restricts which characters can be typed into a text input
+#[derive(Clone, Default)]
+pub enum InputMask {
+    /// Any character is accepted
+    #[default]
+    Any,
+    /// Only ASCII digits
+    Numeric,
+    /// Digits with an optional leading minus sign and a single decimal point
+    Decimal,
+    /// Only ASCII letters and digits
+    Alphanumeric,
+    /// Custom predicate deciding whether a character is accepted
+    Custom(Rc<dyn Fn(char) -> bool>),
+}
+
+impl InputMask {
+    /// Check if `c` can be inserted at `cursor` into `text`
+    ///
+    /// `text` should already have any selection removed.
+    pub fn allows(&self, text: &str, cursor: usize, c: char) -> bool {
+        match self {
+            InputMask::Any => true,
+            InputMask::Numeric => c.is_ascii_digit(),
+            InputMask::Decimal => {
+                c.is_ascii_digit()
+                    || (c == '.' && !text.contains('.'))
+                    || (c == '-' && cursor == 0 && !text.starts_with('-'))
+            }
+            InputMask::Alphanumeric => c.is_ascii_alphanumeric(),
+            InputMask::Custom(predicate) => predicate(c),
+        }
+    }
+}
+
+impl std::fmt::Debug for InputMask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputMask::Any => write!(f, "Any"),
+            InputMask::Numeric => write!(f, "Numeric"),
+            InputMask::Decimal => write!(f, "Decimal"),
+            InputMask::Alphanumeric => write!(f, "Alphanumeric"),
+            InputMask::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+/// Validation callback: returns an error message when the text is invalid
+///
+/// Regex validation is left to callers; see [`TextInput::validator`].
+pub type TextValidator = Rc<dyn Fn(&str) -> Result<(), String>>;
+
+/// Run `validator` on `text`, returning its error message if it fails
+fn validation_error(validator: Option<&TextValidator>, text: &str) -> Option<String> {
+    validator.and_then(|validator| validator(text).err())
+}
+
+/// Replace `text` with one bullet per character, mapping the cursor and
+/// selection byte offsets into the bulleted text
+fn mask_password(
+    text: &str,
+    cursor: usize,
+    selection_start: Option<usize>,
+) -> (String, usize, Option<usize>) {
+    let to_masked =
+        |index: usize| text[..index.min(text.len())].chars().count() * PASSWORD_BULLET.len_utf8();
+    (
+        PASSWORD_BULLET.to_string().repeat(text.chars().count()),
+        to_masked(cursor),
+        selection_start.map(to_masked),
+    )
+}
+
+/// The text as last painted, used to map mouse positions to text offsets
+struct TextHitTarget {
+    shaped: ShapedText,
+    /// Screen position of the text origin
+    origin: Vec2,
+    /// Whether the shaped text is password bullets
+    masked: bool,
+}
+
+impl TextHitTarget {
+    /// Byte offset in `text` closest to the screen position `point`
+    fn index_at(&self, text: &str, point: Vec2) -> usize {
+        let index = TextSystem::hit_test_text(&self.shaped, point - self.origin).0;
+        if !self.masked {
+            return index.min(text.len());
+        }
+        // Each bullet stands for one character of the real text
+        let chars = index / PASSWORD_BULLET.len_utf8();
+        text.char_indices()
+            .nth(chars)
+            .map_or(text.len(), |(i, _)| i)
+    }
+}
+
+/// Clamp an auto-growing input's width, preferring `min` if the bounds cross
+fn clamp_width(width: f32, min: f32, max: f32) -> f32 {
+    width.min(max).max(min)
+}
+
+/// Two-way link between an input's text and a `String` inside another entity
+#[derive(Clone)]
+struct TextBinding {
+    /// Read the bound field, observing its entity
+    read: Rc<dyn Fn() -> Option<String>>,
+    /// Write typed text to the bound field
+    write: Rc<dyn Fn(&str)>,
 }
 
 /// Create a new text input element
@@ -222,6 +683,12 @@ pub struct TextInput {
     element_id: ElementId,
     /// Width of the input (None = fit to content/parent)
     width: Option<f32>,
+    /// Narrowest the input gets
+    min_width: f32,
+    /// Widest an auto-growing input gets
+    max_width: f32,
+    /// Whether the width tracks the text
+    auto_grow: bool,
     /// Height of the input
     height: f32,
     /// Text style
@@ -244,16 +711,30 @@ pub struct TextInput {
     padding_h: f32,
     /// Vertical padding
     padding_v: f32,
-    /// Cursor color
-    cursor_color: Color,
+    /// Cursor color; `None` uses the configured caret color
+    cursor_color: Option<Color>,
     /// Selection background color
     selection_color: Color,
     /// Whether the input is disabled
     disabled: bool,
+    /// Characters accepted when typing
+    input_mask: InputMask,
+    /// Maximum number of characters
+    max_length: Option<usize>,
+    /// Validation callback run whenever the text changes
+    validator: Option<TextValidator>,
+    /// Border color when the text fails validation
+    invalid_border_color: Color,
+    /// Whether to render the text as bullets
+    password: bool,
     /// On change callback (called when text changes)
     on_change: Option<Rc<RefCell<Box<dyn FnMut(&str)>>>>,
     /// On submit callback (called on Enter key)
     on_submit: Option<Rc<RefCell<Box<dyn FnMut(&str)>>>>,
+    /// Entity field the text mirrors
+    binding: Option<TextBinding>,
+    /// Text layout from the last paint, shared with the mouse handlers
+    hit_target: Rc<RefCell<Option<TextHitTarget>>>,
     /// Cached layout node
     node_id: Option<NodeId>,
 }
@@ -264,6 +745,9 @@ impl TextInput {
             state,
             element_id: ElementId::auto(),
             width: None,
+            min_width: 100.0,
+            max_width: f32::INFINITY,
+            auto_grow: false,
             height: 36.0,
             text_style: TextStyle {
                 color: colors::BLACK,
@@ -279,11 +763,18 @@ impl TextInput {
             corner_radius: 4.0,
             padding_h: 12.0,
             padding_v: 8.0,
-            cursor_color: colors::BLACK,
+            cursor_color: None,
             selection_color: colors::BLUE_500.with_alpha(0.3),
             disabled: false,
+            input_mask: InputMask::Any,
+            max_length: None,
+            validator: None,
+            invalid_border_color: colors::RED_500,
+            password: false,
             on_change: None,
             on_submit: None,
+            binding: None,
+            hit_target: Rc::new(RefCell::new(None)),
             node_id: None,
         }
     }
@@ -300,6 +791,27 @@ impl TextInput {
         self
     }
 
+    /// Set the narrowest the input gets
+    pub fn min_width(mut self, width: f32) -> Self {
+        self.min_width = width;
+        self
+    }
+
+    /// Set the widest an auto-growing input gets
+    pub fn max_width(mut self, width: f32) -> Self {
+        self.max_width = width;
+        self
+    }
+
+    /// Size the input to fit its text, between the min and max widths
+    ///
+    /// The width follows the text as it's typed, or the placeholder while
+    /// empty, e.g. for tags and inline renaming. Overrides a fixed `width`.
+    pub fn auto_grow(mut self) -> Self {
+        self.auto_grow = true;
+        self
+    }
+
     /// Set the height
     pub fn height(mut self, height: f32) -> Self {
         self.height = height;
@@ -401,7 +913,7 @@ impl TextInput {
 
     /// Set cursor color
     pub fn cursor_color(mut self, color: Color) -> Self {
-        self.cursor_color = color;
+        self.cursor_color = Some(color);
         self
     }
 
@@ -417,6 +929,74 @@ impl TextInput {
         self
     }
 
+    /// Restrict which characters can be typed
+    pub fn input_mask(mut self, mask: InputMask) -> Self {
+        self.input_mask = mask;
+        self
+    }
+
+    /// Only accept ASCII digits
+    pub fn numeric(self) -> Self {
+        self.input_mask(InputMask::Numeric)
+    }
+
+    /// Limit the text to a maximum number of characters
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    /// Set a validator that runs whenever the text changes
+    ///
+    /// Return `Err(message)` to mark the input invalid. The error is stored in
+    /// `TextInputState::validation_error` and the border switches to the
+    /// invalid color.
+    ///
+    /// There's no built-in pattern matching, since sol-ui doesn't depend on a
+    /// regex engine. To validate against a regex, compile it with the crate of
+    /// your choice and check it here.
+    ///
+    /// # Example
+    /// ```ignore
+    /// text_input(email)
+    ///     .validator(|text| {
+    ///         if text.contains('@') {
+    ///             Ok(())
+    ///         } else {
+    ///             Err("Enter a valid email address".to_string())
+    ///         }
+    ///     })
+    ///
+    /// let zip = regex::Regex::new(r"^\d{5}$").unwrap();
+    /// text_input(postcode)
+    ///     .validator(move |text| {
+    ///         if zip.is_match(text) {
+    ///             Ok(())
+    ///         } else {
+    ///             Err("Enter a 5 digit ZIP code".to_string())
+    ///         }
+    ///     })
+    /// ```
+    pub fn validator<F>(mut self, validator: F) -> Self
+    where
+        F: Fn(&str) -> Result<(), String> + 'static,
+    {
+        self.validator = Some(Rc::new(validator));
+        self
+    }
+
+    /// Set border color used when validation fails
+    pub fn invalid_border_color(mut self, color: Color) -> Self {
+        self.invalid_border_color = color;
+        self
+    }
+
+    /// Render the text as bullets while keeping the real value in the state
+    pub fn password(mut self, password: bool) -> Self {
+        self.password = password;
+        self
+    }
+
     /// Set the on_change callback
     pub fn on_change<F>(mut self, handler: F) -> Self
     where
@@ -435,23 +1015,94 @@ impl TextInput {
         self
     }
 
+    /// Mirror a `String` field of an entity
+    ///
+    /// Typing writes the new text to the field, and changes made to the
+    /// field elsewhere show up in the input, with the cursor kept next to
+    /// the text around it. `field` is also used to read the field, so it
+    /// shouldn't change anything.
+    ///
+    /// # Example
+    /// ```ignore
+    /// text_input(input_state).bind_text(&form, |s| &mut s.name)
+    /// ```
+    pub fn bind_text<T: 'static>(
+        mut self,
+        entity: &Entity<T>,
+        field: impl Fn(&mut T) -> &mut String + 'static,
+    ) -> Self {
+        let field = Rc::new(field);
+        let read = {
+            let (entity, field) = (entity.clone(), field.clone());
+            move || {
+                try_with_entity_store(|store| store.observe_mut(&entity, |s| field(s).clone()))
+                    .flatten()
+            }
+        };
+        let write = {
+            let entity = entity.clone();
+            move |text: &str| {
+                update_entity(&entity, |s| {
+                    let value = field(s);
+                    value.clear();
+                    value.push_str(text);
+                });
+            }
+        };
+        self.binding = Some(TextBinding {
+            read: Rc::new(read),
+            write: Rc::new(write),
+        });
+        self
+    }
+
     /// Get the element ID
     pub fn element_id(&self) -> ElementId {
         self.element_id
     }
+
+    /// Width that fits the text and a caret after it
+    fn auto_grow_width(&self, ctx: &mut LayoutContext) -> f32 {
+        let text = read_entity(&self.state, |s| s.text.clone()).unwrap_or_default();
+        let text = if text.is_empty() {
+            self.placeholder.clone().unwrap_or_default()
+        } else if self.password {
+            PASSWORD_BULLET.to_string().repeat(text.chars().count())
+        } else {
+            text
+        };
+        let text_width = ctx.measure_text(&text, &self.text_style, None).x;
+        let content_width = text_width + caret_config().width + self.padding_h * 2.0;
+        clamp_width(content_width, self.min_width, self.max_width)
+    }
+
+    /// Pull in changes made to the bound field since the last paint
+    fn sync_binding(&self) {
+        let Some(value) = self.binding.as_ref().and_then(|binding| (binding.read)()) else {
+            return;
+        };
+        if read_entity(&self.state, |s| s.text != value).unwrap_or(false) {
+            update_entity(&self.state, |s| s.set_text(value));
+        }
+    }
 }
 
 impl Element for TextInput {
     fn layout(&mut self, ctx: &mut LayoutContext) -> NodeId {
+        let width = if self.auto_grow {
+            // Measure the bound text, not last frame's
+            self.sync_binding();
+            Some(self.auto_grow_width(ctx))
+        } else {
+            self.width
+        };
         let style = Style {
             size: Size {
-                width: self.width
-                    .map(Dimension::length)
-                    .unwrap_or(Dimension::auto()),
+                width: width.map(Dimension::length).unwrap_or(Dimension::auto()),
                 height: Dimension::length(self.height),
             },
             min_size: Size {
-                width: Dimension::length(100.0), // Minimum width
+                width: Dimension::length(self.min_width),
                 height: Dimension::auto(),
             },
             padding: taffy::Rect {
@@ -473,22 +1124,54 @@ impl Element for TextInput {
             return;
         }
 
+        self.sync_binding();
+
         // Get interaction state
         let interaction_state = get_element_state(self.element_id).unwrap_or_default();
         let is_focused = interaction_state.is_focused;
 
         // Read current state from entity
-        let (text, cursor, selection_start, cursor_visible) = read_entity(&self.state, |s| {
-            (s.text.clone(), s.cursor, s.selection_start, s.cursor_visible)
-        }).unwrap_or_default();
+        let (text, cursor, selection_start, affinity, last_activity, stored_error) =
+            read_entity(&self.state, |s| {
+                (
+                    s.text.clone(),
+                    s.cursor,
+                    s.selection_start,
+                    s.affinity,
+                    s.last_activity,
+                    s.validation_error.clone(),
+                )
+            })
+            .unwrap_or_default();
+
+        // Keep the stored validation result in sync (covers initial text)
+        let validation_error = validation_error(self.validator.as_ref(), &text);
+        if validation_error != stored_error {
+            update_entity(&self.state, |s| {
+                s.validation_error = validation_error.clone();
+            });
+        }
+        let is_invalid = validation_error.is_some();
 
-        // Determine border color based on focus
-        let current_border_color = if is_focused && !self.disabled {
+        // Determine border color based on validity and focus
+        let current_border_color = if is_invalid && !self.disabled {
+            self.invalid_border_color
+        } else if is_focused && !self.disabled {
             self.focus_border_color
         } else {
             self.border_color
         };
 
+        // In password mode, paint and measure bullets instead of the real text
+        let masked_text;
+        let (text, cursor, selection_start) = if self.password {
+            let (masked, cursor, selection_start) = mask_password(&text, cursor, selection_start);
+            masked_text = masked;
+            (masked_text.as_str(), cursor, selection_start)
+        } else {
+            (text.as_str(), cursor, selection_start)
+        };
+
         // Paint background
         ctx.paint_quad(PaintQuad {
             bounds,
@@ -508,18 +1191,16 @@ impl Element for TextInput {
         let display_text = if text.is_empty() {
             self.placeholder.as_deref().unwrap_or("")
         } else {
-            &text
+            text
         };
         let is_placeholder = text.is_empty() && self.placeholder.is_some();
 
-        // Measure text for cursor positioning
-        let text_config = crate::text_system::TextConfig {
-            font_stack: parley::FontStack::from(self.text_style.font_family),
-            size: self.text_style.size,
-            weight: self.text_style.weight,
-            color: self.text_style.color.clone(),
-            line_height: self.text_style.line_height,
-        };
+        // Lay out the text for selection and caret positions
+        let text_config = crate::text_system::TextConfig::from(&self.text_style);
+        let shaped = ctx
+            .text_system
+            .shape_text(text, &text_config, None, ctx.scale_factor)
+            .ok();
 
         // Paint selection highlight if present
         if !is_placeholder && selection_start.is_some() {
@@ -534,22 +1215,19 @@ impl Element for TextInput {
             };
 
             if sel_start != sel_end {
-                // Measure text up to selection start and end
-                let start_text = &text[..sel_start];
-                let end_text = &text[..sel_end];
-
-                let start_width = if start_text.is_empty() {
-                    0.0
-                } else {
-                    ctx.text_system.measure_text(start_text, &text_config, None, ctx.scale_factor).x
-                };
-                let end_width = ctx.text_system.measure_text(end_text, &text_config, None, ctx.scale_factor).x;
-
-                let selection_rect = Rect::from_pos_size(
-                    Vec2::new(text_area.pos.x + start_width, text_area.pos.y),
-                    Vec2::new(end_width - start_width, text_area.size.y),
-                );
-                ctx.paint_quad(PaintQuad::filled(selection_rect, self.selection_color));
+                // Mixed-direction text can put the selection in several
+                // separate pieces
+                let pieces = shaped
+                    .as_ref()
+                    .map(|shaped| TextSystem::selection_rects(shaped, sel_start..sel_end))
+                    .unwrap_or_default();
+                for piece in pieces {
+                    let selection_rect = Rect::from_pos_size(
+                        Vec2::new(text_area.pos.x + piece.pos.x, text_area.pos.y),
+                        Vec2::new(piece.size.x, text_area.size.y),
+                    );
+                    ctx.paint_quad(PaintQuad::filled(selection_rect, self.selection_color));
+                }
             }
         }
 
@@ -571,6 +1249,13 @@ impl Element for TextInput {
         );
         let text_y = text_area.pos.y + (text_area.size.y - text_size.y) / 2.0;
 
+        // Keep the shaped text so clicks can be mapped to offsets
+        *self.hit_target.borrow_mut() = shaped.map(|shaped| TextHitTarget {
+            shaped,
+            origin: Vec2::new(text_area.pos.x, text_y),
+            masked: self.password,
+        });
+
         ctx.paint_text(PaintText {
             position: Vec2::new(text_area.pos.x, text_y),
             text: display_text.to_string(),
@@ -581,44 +1266,47 @@ impl Element for TextInput {
             measured_size: Some(text_size),
         });
 
-        // Paint cursor if focused and visible
-        if is_focused && cursor_visible && !self.disabled && !is_placeholder {
-            let text_before_cursor = &text[..cursor.min(text.len())];
-            let cursor_x = if text_before_cursor.is_empty() {
-                0.0
-            } else {
-                ctx.text_system.measure_text(text_before_cursor, &text_config, None, ctx.scale_factor).x
+        // Paint the caret; it blinks without the layer repainting
+        if is_focused && !self.disabled {
+            let hit_target = self.hit_target.borrow();
+            let caret = hit_target
+                .as_ref()
+                .map(|target| TextSystem::caret_rects(&target.shaped, CharIndex(cursor), affinity));
+            let width = caret_config().width;
+            let top = text_area.pos.y + 2.0;
+            let height = text_area.size.y - 4.0;
+            let cursor_rect = |x: f32, y: f32, height: f32| {
+                Rect::from_pos_size(Vec2::new(text_area.pos.x + x, y), Vec2::new(width, height))
             };
 
-            let cursor_rect = Rect::from_pos_size(
-                Vec2::new(text_area.pos.x + cursor_x, text_area.pos.y + 2.0),
-                Vec2::new(2.0, text_area.size.y - 4.0),
-            );
-            ctx.paint_quad(PaintQuad::filled(cursor_rect, self.cursor_color));
-        } else if is_focused && !self.disabled && is_placeholder {
-            // Show cursor at start when empty
-            let cursor_rect = Rect::from_pos_size(
-                Vec2::new(text_area.pos.x, text_area.pos.y + 2.0),
-                Vec2::new(2.0, text_area.size.y - 4.0),
-            );
-            ctx.paint_quad(PaintQuad::filled(cursor_rect, self.cursor_color));
-        }
-
-        // Update cursor blink
-        if is_focused {
-            update_entity(&self.state, |s| {
-                s.blink_counter += 1;
-                if s.blink_counter >= 30 {
-                    s.cursor_visible = !s.cursor_visible;
-                    s.blink_counter = 0;
+            match caret {
+                // Split caret where text directions meet, with typed text
+                // going at the top half
+                Some(CaretRects {
+                    primary,
+                    secondary: Some(secondary),
+                }) => {
+                    let half = height / 2.0;
+                    ctx.paint_caret(
+                        cursor_rect(primary.pos.x, top, half),
+                        self.cursor_color,
+                        last_activity,
+                    );
+                    ctx.paint_caret(
+                        cursor_rect(secondary.pos.x, top + half, half),
+                        self.cursor_color,
+                        last_activity,
+                    );
                 }
-            });
-        } else {
-            // Reset cursor visibility when not focused
-            update_entity(&self.state, |s| {
-                s.cursor_visible = true;
-                s.blink_counter = 0;
-            });
+                caret => {
+                    let x = caret.map_or(0.0, |caret| caret.primary.pos.x);
+                    ctx.paint_caret(
+                        cursor_rect(x, top, height),
+                        self.cursor_color,
+                        last_activity,
+                    );
+                }
+            }
         }
 
         // Register for hit testing
@@ -644,11 +1332,19 @@ impl InteractiveTextInput {
         let state = input.state.clone();
         let element_id = input.element_id;
         let disabled = input.disabled;
+        let input_mask = input.input_mask.clone();
+        let max_length = input.max_length;
+        let validator = input.validator.clone();
         let on_change = input.on_change.clone();
         let on_submit = input.on_submit.clone();
+        let binding = input.binding.clone();
+        let input_is_password = input.password;
         let focus_border_color = input.focus_border_color;
+        let corner_radius = input.corner_radius;
+        let hit_target = input.hit_target.clone();
 
         let state_for_keys = state.clone();
+        let hit_target_for_keys = hit_target.clone();
         let on_change_for_keys = on_change.clone();
         let on_submit_for_keys = on_submit.clone();
 
@@ -656,6 +1352,7 @@ impl InteractiveTextInput {
             .interactive()
             .with_id(element_id)
             .focusable_with_overlay(focus_border_color.with_alpha(0.1))
+            .focus_ring_radius(corner_radius)
             .hover_overlay(colors::BLACK.with_alpha(0.02));
 
         if !disabled {
@@ -664,9 +1361,8 @@ impl InteractiveTextInput {
                     let mut text_changed = false;
 
                     update_entity(&state_for_keys, |s| {
-                        // Reset cursor blink on any key
-                        s.cursor_visible = true;
-                        s.blink_counter = 0;
+                        // Hold the caret solid while typing
+                        s.last_activity = frame_time();
 
                         match key {
                             Key::Backspace => {
@@ -677,11 +1373,28 @@ impl InteractiveTextInput {
                                 s.delete();
                                 text_changed = true;
                             }
-                            Key::Left => {
-                                s.move_left(modifiers.shift);
+                            Key::Left if modifiers.cmd => {
+                                s.move_to_start(modifiers.shift);
+                            }
+                            Key::Right if modifiers.cmd => {
+                                s.move_to_end(modifiers.shift);
+                            }
+                            Key::Left | Key::Right if modifiers.alt => {
+                                s.move_word(key == Key::Right, modifiers.shift, input_is_password);
                             }
-                            Key::Right => {
-                                s.move_right(modifiers.shift);
+                            Key::Left | Key::Right => {
+                                let right = key == Key::Right;
+                                let target = hit_target_for_keys.borrow();
+                                // Bullets stand in for password text, so its
+                                // layout can't be used
+                                let visual = caret_config().movement == CaretMovement::Visual;
+                                match target.as_ref().filter(|t| visual && !t.masked) {
+                                    Some(target) => {
+                                        s.move_visually(&target.shaped, right, modifiers.shift)
+                                    }
+                                    None if right => s.move_right(modifiers.shift),
+                                    None => s.move_left(modifiers.shift),
+                                }
                             }
                             Key::Home => {
                                 s.move_to_start(modifiers.shift);
@@ -699,18 +1412,24 @@ impl InteractiveTextInput {
                                 // Handle character input
                                 if let Some(c) = character {
                                     if !modifiers.cmd && !modifiers.ctrl {
-                                        s.insert(&c.to_string());
-                                        text_changed = true;
+                                        text_changed = s.type_char(c, &input_mask, max_length);
                                     }
                                 }
                             }
                         }
+
+                        if text_changed {
+                            s.validation_error = validation_error(validator.as_ref(), &s.text);
+                        }
                     });
 
-                    // Call on_change if text was modified
+                    // Update the bound field and call on_change if text was modified
                     if text_changed {
-                        if let Some(handler) = &on_change_for_keys {
-                            if let Some(text) = read_entity(&state_for_keys, |s| s.text.clone()) {
+                        if let Some(text) = read_entity(&state_for_keys, |s| s.text.clone()) {
+                            if let Some(binding) = &binding {
+                                (binding.write)(&text);
+                            }
+                            if let Some(handler) = &on_change_for_keys {
                                 (handler.borrow_mut())(&text);
                             }
                         }
@@ -725,12 +1444,69 @@ impl InteractiveTextInput {
                         }
                     }
                 })
+                .on_mouse_down({
+                    let state = state.clone();
+                    let hit_target = hit_target.clone();
+                    move |button, position, _, modifiers, click_count| {
+                        if button != MouseButton::Left {
+                            return;
+                        }
+                        let Some(text) = read_entity(&state, |s| s.text.clone()) else {
+                            return;
+                        };
+                        let index = hit_target
+                            .borrow()
+                            .as_ref()
+                            .map_or(0, |target| target.index_at(&text, position));
+                        // Words in a password aren't revealed by double-clicking
+                        let granularity = match SelectionGranularity::from_click_count(click_count)
+                        {
+                            SelectionGranularity::Word if input_is_password => {
+                                SelectionGranularity::Line
+                            }
+                            granularity => granularity,
+                        };
+                        update_entity(&state, |s| {
+                            s.last_activity = frame_time();
+                            s.start_mouse_selection(index, granularity, modifiers.shift);
+                        });
+                    }
+                })
+                .on_mouse_move({
+                    let state = state.clone();
+                    let hit_target = hit_target.clone();
+                    move |position, _| {
+                        let Some((text, true)) =
+                            read_entity(&state, |s| (s.text.clone(), s.is_mouse_selecting()))
+                        else {
+                            return;
+                        };
+                        let Some(index) = hit_target
+                            .borrow()
+                            .as_ref()
+                            .map(|target| target.index_at(&text, position))
+                        else {
+                            return;
+                        };
+                        update_entity(&state, |s| {
+                            s.last_activity = frame_time();
+                            s.update_mouse_selection(index);
+                        });
+                    }
+                })
+                .on_mouse_up({
+                    let state = state.clone();
+                    move |button, _, _, _| {
+                        if button == MouseButton::Left {
+                            update_entity(&state, |s| s.end_mouse_selection());
+                        }
+                    }
+                })
                 .on_focus_in({
                     let state = state.clone();
                     move || {
                         update_entity(&state, |s| {
-                            s.cursor_visible = true;
-                            s.blink_counter = 0;
+                            s.last_activity = frame_time();
                         });
                     }
                 });
@@ -768,3 +1544,221 @@ impl TextInputInteractable for TextInput {
         InteractiveTextInput::new(self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clamp_width() {
+        assert_eq!(clamp_width(40.0, 60.0, 200.0), 60.0);
+        assert_eq!(clamp_width(120.0, 60.0, 200.0), 120.0);
+        assert_eq!(clamp_width(500.0, 60.0, 200.0), 200.0);
+        assert_eq!(clamp_width(500.0, 60.0, f32::INFINITY), 500.0);
+        assert_eq!(clamp_width(500.0, 300.0, 200.0), 300.0);
+    }
+
+    #[test]
+    fn test_input_masks() {
+        assert!(InputMask::Numeric.allows("12", 2, '3'));
+        assert!(!InputMask::Numeric.allows("12", 2, 'a'));
+        assert!(InputMask::Alphanumeric.allows("", 0, 'Z'));
+        assert!(!InputMask::Alphanumeric.allows("", 0, '-'));
+
+        // One decimal point and a minus sign only at the start
+        assert!(InputMask::Decimal.allows("12", 2, '.'));
+        assert!(!InputMask::Decimal.allows("1.2", 3, '.'));
+        assert!(InputMask::Decimal.allows("12", 0, '-'));
+        assert!(!InputMask::Decimal.allows("12", 1, '-'));
+        assert!(!InputMask::Decimal.allows("-12", 0, '-'));
+
+        let vowels = InputMask::Custom(Rc::new(|c| "aeiou".contains(c)));
+        assert!(vowels.allows("", 0, 'e'));
+        assert!(!vowels.allows("", 0, 'x'));
+    }
+
+    #[test]
+    fn test_type_char_respects_mask_and_max_length() {
+        let mut state = TextInputState::with_text("12");
+        assert!(!state.type_char('a', &InputMask::Numeric, None));
+        assert!(state.type_char('3', &InputMask::Numeric, Some(3)));
+        assert!(!state.type_char('4', &InputMask::Numeric, Some(3)));
+        assert_eq!(state.text, "123");
+
+        // The selection is replaced, so it doesn't count towards the length
+        state.select_all();
+        assert!(state.type_char('9', &InputMask::Numeric, Some(3)));
+        assert_eq!(state.text, "9");
+
+        // The mask sees the text without the selected decimal point
+        let mut state = TextInputState::with_text("1.5");
+        state.selection_start = Some(1);
+        state.cursor = 2;
+        assert!(state.type_char('.', &InputMask::Decimal, None));
+        assert_eq!(state.text, "1.5");
+    }
+
+    #[test]
+    fn test_validation_error() {
+        let validator: TextValidator = Rc::new(|text| {
+            if text.contains('@') {
+                Ok(())
+            } else {
+                Err("Enter a valid email address".to_string())
+            }
+        });
+        assert_eq!(validation_error(Some(&validator), "a@b.c"), None);
+        assert_eq!(
+            validation_error(Some(&validator), "abc").as_deref(),
+            Some("Enter a valid email address")
+        );
+        assert_eq!(validation_error(None, "abc"), None);
+    }
+
+    #[test]
+    fn test_mask_password() {
+        let bullet = PASSWORD_BULLET.len_utf8();
+        let (masked, cursor, selection_start) = mask_password("héllo", "hé".len(), Some(0));
+        assert_eq!(masked, "\u{2022}".repeat(5));
+        assert_eq!(cursor, 2 * bullet);
+        assert_eq!(selection_start, Some(0));
+
+        let (masked, cursor, _) = mask_password("", 0, None);
+        assert!(masked.is_empty());
+        assert_eq!(cursor, 0);
+    }
+
+    #[test]
+    fn test_word_range_at() {
+        let state = TextInputState::with_text("hello, big world");
+        assert_eq!(state.word_range_at(2), (0, 5));
+        // Just past a word still selects it
+        assert_eq!(state.word_range_at(5), (0, 5));
+        assert_eq!(state.word_range_at(6), (6, 7));
+        assert_eq!(state.word_range_at(16), (11, 16));
+    }
+
+    #[test]
+    fn test_move_by_word() {
+        let mut state = TextInputState::with_text("one two  three");
+        state.move_word_left(false);
+        assert_eq!(state.cursor, 9);
+        state.move_word_left(true);
+        assert_eq!(state.cursor, 4);
+        assert_eq!(state.selected_text(), Some("two  "));
+
+        state.move_to_start(false);
+        state.move_word_right(false);
+        assert_eq!(state.cursor, 3);
+        assert_eq!(state.selection_start, None);
+        state.move_word_right(false);
+        assert_eq!(state.cursor, 7);
+    }
+
+    #[test]
+    fn test_move_by_word_in_password_goes_to_the_ends() {
+        let mut state = TextInputState::with_text("one two three");
+        state.cursor = 5;
+        state.move_word(false, false, true);
+        assert_eq!(state.cursor, 0);
+        state.move_word(true, true, true);
+        assert_eq!(state.cursor, 13);
+        assert_eq!(state.selected_text(), Some("one two three"));
+
+        // Plain text still moves a word at a time
+        state.cursor = 5;
+        state.selection_start = None;
+        state.move_word(true, false, false);
+        assert_eq!(state.cursor, 7);
+    }
+
+    #[test]
+    fn test_mouse_selection_by_word() {
+        let mut state = TextInputState::with_text("one two three");
+        state.start_mouse_selection(5, SelectionGranularity::Word, false);
+        assert_eq!(state.selected_text(), Some("two"));
+
+        // Dragging left keeps the original word selected
+        state.update_mouse_selection(1);
+        assert_eq!(state.selected_text(), Some("one two"));
+        state.update_mouse_selection(10);
+        assert_eq!(state.selected_text(), Some("two three"));
+        state.end_mouse_selection();
+        assert!(!state.is_mouse_selecting());
+    }
+
+    #[test]
+    fn test_mouse_selection_line_and_shift_click() {
+        let mut state = TextInputState::with_text("first\nsecond");
+        state.start_mouse_selection(8, SelectionGranularity::Line, false);
+        assert_eq!(state.selected_text(), Some("second"));
+
+        state.start_mouse_selection(2, SelectionGranularity::Character, false);
+        assert_eq!(state.selection_start, None);
+        state.start_mouse_selection(9, SelectionGranularity::Character, true);
+        assert_eq!(state.selected_text(), Some("rst\nsec"));
+    }
+
+    #[test]
+    fn test_set_text_keeps_cursor() {
+        // Cursor after the change keeps its distance from the end
+        let mut state = TextInputState::with_text("hello world");
+        state.cursor = 8;
+        state.set_text("hi world");
+        assert_eq!(state.cursor, 5);
+
+        // Cursor before the change stays put
+        state.cursor = 1;
+        state.set_text("hi there world");
+        assert_eq!(state.cursor, 1);
+
+        // Cursor at the end stays at the end, including for new text
+        let mut state = TextInputState::default();
+        state.set_text("héllo");
+        assert_eq!(state.cursor, "héllo".len());
+        state.set_text("");
+        assert_eq!(state.cursor, 0);
+    }
+
+    #[test]
+    fn test_move_visually_through_mixed_directions() {
+        use crate::text_system::{DecorationMetrics, ShapedCluster, ShapedLine};
+
+        // "ab" then the right-to-left "אב", drawn as "ab" then "בא"
+        let cluster = |text_range: std::ops::Range<usize>, x: f32, is_rtl: bool| ShapedCluster {
+            text_range,
+            bounds: Rect::new(x, 0.0, 10.0, 20.0),
+            line: 0,
+            is_rtl,
+        };
+        let shaped = ShapedText {
+            glyphs: vec![],
+            clusters: vec![
+                cluster(0..1, 0.0, false),
+                cluster(1..2, 10.0, false),
+                cluster(4..6, 20.0, true),
+                cluster(2..4, 30.0, true),
+            ],
+            lines: vec![ShapedLine {
+                text_range: 0..6,
+                top: 0.0,
+                height: 20.0,
+                baseline: 15.0,
+                decoration: DecorationMetrics::default(),
+            }],
+            size: Vec2::new(40.0, 20.0),
+        };
+
+        // The end of the text is drawn between "b" and "ב"
+        let mut state = TextInputState::with_text("abאב");
+        state.move_visually(&shaped, false, false);
+        assert_eq!(state.cursor, 1);
+        state.move_visually(&shaped, true, false);
+        state.move_visually(&shaped, true, true);
+        assert_eq!((state.selection_start, state.cursor), (Some(2), 4));
+
+        // Collapsing goes to the end of the selection further right on screen
+        state.move_visually(&shaped, true, false);
+        assert_eq!(state.cursor, 2);
+    }
+}
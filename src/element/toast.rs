@@ -184,13 +184,7 @@ impl Element for Toast {
         };
         let text_size = ctx.text_system.measure_text(
             &self.message,
-            &crate::text_system::TextConfig {
-                font_stack: parley::FontStack::from("system-ui"),
-                size: text_style.size,
-                weight: parley::FontWeight::NORMAL,
-                color: text_style.color.clone(),
-                line_height: 1.2,
-            },
+            &crate::text_system::TextConfig::from(&text_style),
             Some(300.0), // Max width for text
             ctx.scale_factor,
         );
@@ -161,13 +161,7 @@ impl Element for Tooltip {
         };
         let text_size = ctx.text_system.measure_text(
             &self.text,
-            &crate::text_system::TextConfig {
-                font_stack: parley::FontStack::from("system-ui"),
-                size: text_style.size,
-                weight: parley::FontWeight::NORMAL,
-                color: text_style.color.clone(),
-                line_height: 1.2,
-            },
+            &crate::text_system::TextConfig::from(&text_style),
             Some(200.0), // Max width
             ctx.scale_factor,
         );
@@ -2,8 +2,10 @@
 //!
 //! This module provides thread-local access to the EntityStore during rendering.
 //! The store is set at the beginning of a render frame and cleared at the end.
+//! Use [`enter_entity_store`] to make a store current for a scope, or
+//! [`entity_store_handle`] to reach the store from outside the render pass.
 
-use super::{Entity, EntityStore};
+use super::{Entity, EntityStore, EntityStoreHandle};
 use std::cell::RefCell;
 
 thread_local! {
@@ -33,6 +35,35 @@ pub fn clear_entity_store() {
     });
 }
 
+/// Make `store` the current entity store while `f` runs
+///
+/// The previous store (if any) is restored afterwards, even if `f` panics,
+/// so scopes can nest.
+pub fn enter_entity_store<R>(store: &mut EntityStore, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<*mut EntityStore>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            ENTITY_STORE.with(|cell| *cell.borrow_mut() = self.0);
+        }
+    }
+
+    let previous = ENTITY_STORE.with(|cell| cell.borrow_mut().replace(store as *mut EntityStore));
+    let _restore = Restore(previous);
+    f()
+}
+
+/// Get a thread-safe handle to the current entity store
+///
+/// The handle can be cloned into closures and background threads to queue
+/// updates, which are applied on the UI thread before the next frame.
+///
+/// # Panics
+/// Panics if called outside of a render context.
+pub fn entity_store_handle() -> EntityStoreHandle {
+    with_entity_store(|store| store.handle())
+}
+
 /// Execute a closure with access to the current entity store
 ///
 /// # Panics
@@ -192,6 +223,21 @@ mod tests {
         clear_entity_store();
     }
 
+    #[test]
+    fn test_enter_restores_previous_store() {
+        let mut outer = EntityStore::new();
+        let mut inner = EntityStore::new();
+        inner.create(1u32);
+
+        enter_entity_store(&mut outer, || {
+            enter_entity_store(&mut inner, || {
+                assert_eq!(with_entity_store(|s| s.len()), 1);
+            });
+            assert_eq!(with_entity_store(|s| s.len()), 0);
+        });
+        assert!(!has_entity_store());
+    }
+
     #[test]
     #[should_panic(expected = "with_entity_store called outside render context")]
     fn test_with_entity_store_panics_outside_context() {
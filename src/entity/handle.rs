@@ -0,0 +1,140 @@
+//! Thread-safe handles for updating entities from outside the render pass
+//!
+//! The entity store lives on the UI thread and is normally reached through
+//! the thread-local set while rendering. An [`EntityStoreHandle`] can be
+//! cloned into any closure, including ones running on background threads.
+//! Updates made through it are queued and applied on the UI thread before
+//! the next frame, marking entities dirty like any other update so observing
+//! UI re-renders.
+//!
+//! Handles refer to entities through [`WeakEntity`], which can be sent
+//! between threads but doesn't keep the state alive.
+//!
+//! ```ignore
+//! let handle = entity_store_handle();
+//! let results = results.downgrade();
+//! std::thread::spawn(move || {
+//!     let rows = load_rows();
+//!     handle.update(&results, move |r| r.rows = rows);
+//! });
+//! ```
+
+use super::{EntityStore, WeakEntity};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+
+/// An update waiting to be applied on the UI thread
+pub(crate) type QueuedUpdate = Box<dyn FnOnce(&mut EntityStore) + Send>;
+
+/// State shared between a store and its handles
+#[derive(Default)]
+pub(crate) struct HandleShared {
+    queue: Mutex<Vec<QueuedUpdate>>,
+    /// Wakes the UI thread's event loop when an update is queued
+    waker: OnceLock<fn()>,
+}
+
+impl HandleShared {
+    /// Take the updates queued so far, oldest first
+    pub(crate) fn take_queued(&self) -> Vec<QueuedUpdate> {
+        std::mem::take(&mut *self.queue.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Set how to wake the UI thread when an update is queued
+    #[cfg(target_os = "macos")]
+    pub(crate) fn set_waker(&self, waker: fn()) {
+        let _ = self.waker.set(waker);
+    }
+}
+
+/// A cloneable, thread-safe handle to an [`EntityStore`]
+///
+/// Get one with [`EntityStore::handle`] or
+/// [`entity_store_handle`](super::entity_store_handle). Reads still happen
+/// on the UI thread; the handle only queues writes.
+#[derive(Clone)]
+pub struct EntityStoreHandle {
+    shared: Arc<HandleShared>,
+}
+
+impl EntityStoreHandle {
+    pub(crate) fn new(shared: Arc<HandleShared>) -> Self {
+        Self { shared }
+    }
+
+    /// Queue an update to an entity, applied on the UI thread before the
+    /// next frame
+    ///
+    /// Does nothing if the entity is gone by then.
+    pub fn update<T: 'static>(
+        &self,
+        entity: &WeakEntity<T>,
+        f: impl FnOnce(&mut T) + Send + 'static,
+    ) {
+        let id = entity.id();
+        self.queue(move |store| {
+            store.update_by_id(id, f);
+        });
+    }
+
+    /// Queue a closure to run with the whole store on the UI thread before
+    /// the next frame, e.g. to update several entities together
+    pub fn queue(&self, f: impl FnOnce(&mut EntityStore) + Send + 'static) {
+        self.shared
+            .queue
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(Box::new(f));
+        if let Some(wake) = self.shared.waker.get() {
+            wake();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entity::EntityStore;
+
+    #[derive(Debug, PartialEq)]
+    struct Counter {
+        value: u32,
+    }
+
+    #[test]
+    fn test_update_from_another_thread() {
+        let mut store = EntityStore::new();
+        let counter = store.create(Counter { value: 1 });
+        store.observe(&counter, |c| c.value);
+
+        let handle = store.handle();
+        let weak = counter.downgrade();
+        std::thread::spawn(move || {
+            handle.update(&weak, |c| c.value += 1);
+            handle.update(&weak, |c| c.value *= 10);
+        })
+        .join()
+        .unwrap();
+
+        // Nothing changes until the UI thread applies the queue
+        assert_eq!(store.read(&counter, |c| c.value), Some(1));
+        assert_eq!(store.apply_queued_updates(), 2);
+        assert_eq!(store.read(&counter, |c| c.value), Some(20));
+        assert!(store.needs_render());
+    }
+
+    #[test]
+    fn test_update_stale_entity_is_ignored() {
+        let mut store = EntityStore::new();
+        let counter = store.create(Counter { value: 1 });
+        let weak = counter.downgrade();
+
+        // Free the slot and reuse it for another entity
+        store.decrement_ref(counter.id());
+        store.cleanup();
+        std::mem::forget(counter);
+        let other = store.create(Counter { value: 5 });
+
+        store.handle().update(&weak, |c| c.value = 0);
+        assert_eq!(store.apply_queued_updates(), 1);
+        assert_eq!(store.read(&other, |c| c.value), Some(5));
+    }
+}
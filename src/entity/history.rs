@@ -0,0 +1,297 @@
+//! Recording entity mutations for time-travel debugging
+//!
+//! With history enabled on the [`EntityStore`](super::EntityStore), every
+//! `update` to an entity of a type registered with [`EntityHistory::track`]
+//! is recorded in a ring buffer, with before and after snapshots, so the
+//! store can step backwards and forwards through the changes and the debug
+//! panel can show what changed. Updates to other types aren't recorded, so
+//! incidental state like hover doesn't crowd out the history or drop what
+//! was stepped over.
+//!
+//! ```ignore
+//! store.enable_history(500);
+//! if let Some(history) = store.history_mut() {
+//!     history.track::<TodoList>();
+//! }
+//!
+//! // Later, to undo the last mutation
+//! store.step_back();
+//! ```
+
+use super::EntityId;
+use std::any::{Any, TypeId};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// One recorded call to `update`
+pub struct EntityMutation {
+    /// The entity that was updated
+    pub entity: EntityId,
+    /// Rust type name of the entity's state
+    pub type_name: &'static str,
+    /// When the update happened, since recording started
+    pub timestamp: Duration,
+    /// Changed lines of the state's pretty `Debug` output
+    pub diff: String,
+    /// State before and after the update
+    snapshots: Option<(Box<dyn Any>, Box<dyn Any>)>,
+}
+
+impl EntityMutation {
+    /// Whether stepping over this mutation restores the entity's state
+    pub fn can_restore(&self) -> bool {
+        self.snapshots.is_some()
+    }
+
+    /// The type name without its module path
+    pub fn short_type_name(&self) -> String {
+        let name = self.type_name;
+        let (path, generics) = name.split_at(name.find('<').unwrap_or(name.len()));
+        format!("{}{}", path.rsplit("::").next().unwrap_or(path), generics)
+    }
+}
+
+impl fmt::Debug for EntityMutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EntityMutation")
+            .field("entity", &self.entity)
+            .field("type_name", &self.type_name)
+            .field("timestamp", &self.timestamp)
+            .field("diff", &self.diff)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Type-erased operations on a tracked state type
+struct Snapshotter {
+    clone: fn(&dyn Any) -> Option<Box<dyn Any>>,
+    restore: fn(&mut dyn Any, &dyn Any),
+    describe: fn(&dyn Any) -> String,
+}
+
+impl Snapshotter {
+    fn new<T: Clone + fmt::Debug + 'static>() -> Self {
+        Self {
+            clone: |value| {
+                let value = value.downcast_ref::<T>()?;
+                Some(Box::new(value.clone()))
+            },
+            restore: |target, snapshot| {
+                if let (Some(target), Some(snapshot)) =
+                    (target.downcast_mut::<T>(), snapshot.downcast_ref::<T>())
+                {
+                    *target = snapshot.clone();
+                }
+            },
+            describe: |value| {
+                value
+                    .downcast_ref::<T>()
+                    .map(|value| format!("{value:#?}"))
+                    .unwrap_or_default()
+            },
+        }
+    }
+}
+
+/// Ring buffer of recent entity mutations, with a cursor for time travel
+pub struct EntityHistory {
+    entries: VecDeque<EntityMutation>,
+    capacity: usize,
+    /// Number of entries currently applied; less than the length while
+    /// stepped back
+    position: usize,
+    started: Instant,
+    snapshotters: HashMap<TypeId, Snapshotter>,
+}
+
+impl EntityHistory {
+    /// Create a history keeping the last `capacity` mutations
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity.min(1024)),
+            capacity: capacity.max(1),
+            position: 0,
+            started: Instant::now(),
+            snapshotters: HashMap::new(),
+        }
+    }
+
+    /// Keep snapshots and diffs for entities of type `T`
+    ///
+    /// Updates that leave a tracked entity's `Debug` output unchanged aren't
+    /// recorded.
+    pub fn track<T: Clone + fmt::Debug + 'static>(&mut self) {
+        self.snapshotters
+            .insert(TypeId::of::<T>(), Snapshotter::new::<T>());
+    }
+
+    /// Recorded mutations, oldest first
+    pub fn entries(&self) -> impl Iterator<Item = &EntityMutation> {
+        self.entries.iter()
+    }
+
+    /// Number of mutations currently applied
+    ///
+    /// Equal to [`len`](Self::len) unless the store has stepped back.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Whether the store has stepped back from the latest state
+    pub fn is_time_travelling(&self) -> bool {
+        self.position < self.entries.len()
+    }
+
+    /// Number of recorded mutations
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether nothing has been recorded
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Forget all recorded mutations, keeping tracked types
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.position = 0;
+    }
+
+    /// Snapshot a value before it's updated, if its type is tracked
+    pub(crate) fn snapshot(&self, value: &dyn Any) -> Option<Box<dyn Any>> {
+        let snapshotter = self.snapshotters.get(&value.type_id())?;
+        (snapshotter.clone)(value)
+    }
+
+    /// Record an update to `entity`, given the snapshot taken before it,
+    /// if its type is tracked
+    ///
+    /// Mutations that were stepped over are dropped, like redo history after
+    /// a new edit.
+    pub(crate) fn record(
+        &mut self,
+        entity: EntityId,
+        type_name: &'static str,
+        before: Option<Box<dyn Any>>,
+        value: &dyn Any,
+    ) {
+        // Untracked types aren't recorded
+        let (Some(before), Some(snapshotter)) = (before, self.snapshotters.get(&value.type_id()))
+        else {
+            return;
+        };
+        let Some(diff) = debug_diff(
+            &(snapshotter.describe)(before.as_ref()),
+            &(snapshotter.describe)(value),
+        ) else {
+            return;
+        };
+        let snapshots = (snapshotter.clone)(value).map(|after| (before, after));
+
+        self.entries.truncate(self.position);
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(EntityMutation {
+            entity,
+            type_name,
+            timestamp: self.started.elapsed(),
+            diff,
+            snapshots,
+        });
+        self.position = self.entries.len();
+    }
+
+    /// Move the cursor back one mutation, returning the entity and the state
+    /// to restore it to
+    pub(crate) fn step_back(&mut self) -> Option<(EntityId, Option<Restore<'_>>)> {
+        self.position = self.position.checked_sub(1)?;
+        Some(self.restore(self.position, false))
+    }
+
+    /// Move the cursor forward one mutation, returning the entity and the
+    /// state to restore it to
+    pub(crate) fn step_forward(&mut self) -> Option<(EntityId, Option<Restore<'_>>)> {
+        if self.position >= self.entries.len() {
+            return None;
+        }
+        self.position += 1;
+        Some(self.restore(self.position - 1, true))
+    }
+
+    fn restore(&self, index: usize, after: bool) -> (EntityId, Option<Restore<'_>>) {
+        let entry = &self.entries[index];
+        let restore = entry.snapshots.as_ref().and_then(|(before, after_state)| {
+            let snapshot = if after { after_state } else { before };
+            let snapshotter = self.snapshotters.get(&snapshot.as_ref().type_id())?;
+            Some(Restore {
+                snapshot: snapshot.as_ref(),
+                apply: snapshotter.restore,
+            })
+        });
+        (entry.entity, restore)
+    }
+}
+
+/// A snapshot to write back into an entity
+pub(crate) struct Restore<'a> {
+    snapshot: &'a dyn Any,
+    apply: fn(&mut dyn Any, &dyn Any),
+}
+
+impl Restore<'_> {
+    /// Overwrite `target` with the snapshot
+    pub(crate) fn apply(&self, target: &mut dyn Any) {
+        (self.apply)(target, self.snapshot);
+    }
+}
+
+/// Lines that differ between two `Debug` dumps, as `- ` and `+ ` lines
+///
+/// Only the changed middle is kept: lines shared at the start and end are
+/// dropped. Returns `None` if the dumps are the same.
+fn debug_diff(before: &str, after: &str) -> Option<String> {
+    if before == after {
+        return None;
+    }
+    let before: Vec<&str> = before.lines().collect();
+    let after: Vec<&str> = after.lines().collect();
+    let prefix = before
+        .iter()
+        .zip(&after)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = before[prefix..]
+        .iter()
+        .rev()
+        .zip(after[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let removed = &before[prefix..before.len() - suffix];
+    let added = &after[prefix..after.len() - suffix];
+    let lines: Vec<String> = removed
+        .iter()
+        .map(|line| format!("- {}", line.trim()))
+        .chain(added.iter().map(|line| format!("+ {}", line.trim())))
+        .collect();
+    Some(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_diff_keeps_changed_lines() {
+        let before = "State {\n    count: 1,\n    name: \"a\",\n}";
+        let after = "State {\n    count: 2,\n    name: \"a\",\n}";
+        assert_eq!(
+            debug_diff(before, after).as_deref(),
+            Some("- count: 1,\n+ count: 2,")
+        );
+        assert_eq!(debug_diff(before, before), None);
+    }
+}
@@ -12,21 +12,33 @@
 //! - When `update_entity` mutates observed state, the UI automatically re-renders
 //! - Updates within a frame are batched to prevent excessive re-renders
 //!
+//! ## Frame-Consistent Reads
+//!
+//! Types registered with [`EntityStore::double_buffer`] are read as they were
+//! at frame start for the whole of layout and paint, so an update made while
+//! painting doesn't leave earlier and later elements showing different
+//! states. The update shows in the next frame. Set
+//! [`EntityReadMode::Immediate`] to always read the latest state.
+//!
 //! See the `subscription` module for details.
 
 pub mod context;
 pub mod derived;
+pub mod handle;
+pub mod history;
 pub mod state_cell;
 pub mod store;
 pub mod subscription;
 
 pub use context::{
-    clear_entity_store, new_entity, observe, read_entity, set_entity_store, update_entity,
-    with_entity_store,
+    clear_entity_store, enter_entity_store, entity_store_handle, new_entity, observe, read_entity,
+    set_entity_store, try_with_entity_store, update_entity, with_entity_store,
 };
 pub use derived::{derive, derive_from, derive_from2, Memo};
+pub use handle::EntityStoreHandle;
+pub use history::{EntityHistory, EntityMutation};
 pub use state_cell::StateCell;
-pub use store::EntityStore;
+pub use store::{EntityReadMode, EntityStore};
 pub use subscription::SubscriptionManager;
 
 use std::marker::PhantomData;
@@ -130,6 +142,17 @@ impl<T: 'static> Entity<T> {
     pub fn update<R>(&self, f: impl FnOnce(&mut T) -> R) -> Option<R> {
         update_entity(self, f)
     }
+
+    /// Get a weak reference to this entity
+    ///
+    /// Weak references can be sent to other threads and used with an
+    /// [`EntityStoreHandle`], but don't keep the state alive.
+    pub fn downgrade(&self) -> WeakEntity<T> {
+        WeakEntity {
+            id: self.id,
+            _marker: PhantomData,
+        }
+    }
 }
 
 impl<T: 'static> Clone for Entity<T> {
@@ -160,6 +183,31 @@ impl<T: 'static> Drop for Entity<T> {
 unsafe impl<T: Send> Send for Entity<T> {}
 unsafe impl<T: Sync> Sync for Entity<T> {}
 
+/// Non-owning reference to an entity
+///
+/// Created with [`Entity::downgrade`]. Unlike `Entity<T>` it doesn't touch
+/// the store's reference counts, so it can be freely copied and moved to
+/// other threads. Updates through a stale weak reference are ignored.
+pub struct WeakEntity<T: 'static> {
+    id: EntityId,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: 'static> WeakEntity<T> {
+    /// Get the entity's ID
+    pub fn id(&self) -> EntityId {
+        self.id
+    }
+}
+
+impl<T: 'static> Clone for WeakEntity<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: 'static> Copy for WeakEntity<T> {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,7 +1,32 @@
 //! Entity storage implementation
 
-use super::{Entity, EntityId, subscription::SubscriptionManager};
-use std::any::Any;
+use super::{
+    Entity, EntityId,
+    handle::{EntityStoreHandle, HandleShared},
+    history::{EntityHistory, Restore},
+    subscription::SubscriptionManager,
+};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// How reads see updates made while a frame is being laid out and painted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EntityReadMode {
+    /// Reads of double-buffered types see their state as of frame start
+    ///
+    /// Updates still apply straight away, but elements reading the entity
+    /// later in the same frame see the old state until the frame ends, so
+    /// one frame never shows two versions of it. Only types registered with
+    /// [`EntityStore::double_buffer`] are affected.
+    #[default]
+    FrameConsistent,
+    /// Reads always see the latest state, even partway through a frame
+    Immediate,
+}
+
+/// Clones type-erased state of a known type
+type CloneFn = fn(&dyn Any) -> Option<Box<dyn Any>>;
 
 /// A slot in the entity store
 struct EntitySlot {
@@ -11,6 +36,12 @@ struct EntitySlot {
     generation: u32,
     /// Reference count
     ref_count: u32,
+    /// State at the start of the frame, kept while the frame updates `data`
+    frame_start: Option<Box<dyn Any>>,
+    /// Bumped on every update, so caches can tell whether the state changed
+    version: u64,
+    /// `version` as of the frame-start copy
+    frame_start_version: u64,
 }
 
 impl EntitySlot {
@@ -19,6 +50,23 @@ impl EntitySlot {
             data: None,
             generation: 0,
             ref_count: 0,
+            frame_start: None,
+            version: 0,
+            frame_start_version: 0,
+        }
+    }
+
+    /// The state reads should see: the frame-start copy if there is one
+    fn readable(&self) -> Option<&dyn Any> {
+        self.frame_start.as_deref().or(self.data.as_deref())
+    }
+
+    /// The version of the state reads see
+    fn readable_version(&self) -> u64 {
+        if self.frame_start.is_some() {
+            self.frame_start_version
+        } else {
+            self.version
         }
     }
 
@@ -44,6 +92,20 @@ pub struct EntityStore {
     pending_cleanup: Vec<u32>,
     /// Subscription manager for tracking observations and dirty state
     subscriptions: SubscriptionManager,
+    /// Update queue shared with this store's handles
+    handle_shared: Arc<HandleShared>,
+    /// Recorded mutations, when time-travel debugging is enabled
+    history: Option<EntityHistory>,
+    /// How reads during a frame see updates made in it
+    read_mode: EntityReadMode,
+    /// Whether a frame is being laid out and painted
+    in_frame: bool,
+    /// Clone functions for double-buffered types
+    double_buffered: HashMap<TypeId, CloneFn>,
+    /// Slots holding a frame-start copy this frame
+    frame_copies: Vec<u32>,
+    /// Entities observed by each recording in progress, innermost last
+    recordings: Vec<Vec<(EntityId, u64)>>,
 }
 
 impl EntityStore {
@@ -54,6 +116,13 @@ impl EntityStore {
             free_list: Vec::new(),
             pending_cleanup: Vec::new(),
             subscriptions: SubscriptionManager::new(),
+            handle_shared: Arc::default(),
+            history: None,
+            read_mode: EntityReadMode::default(),
+            in_frame: false,
+            double_buffered: HashMap::new(),
+            frame_copies: Vec::new(),
+            recordings: Vec::new(),
         }
     }
 
@@ -78,6 +147,26 @@ impl EntityStore {
             return None;
         }
 
+        let value = slot.readable()?.downcast_ref::<T>()?;
+        Some(f(value))
+    }
+
+    /// Read the latest entity state, even if it changed earlier this frame
+    ///
+    /// Like `read`, but skips the frame-start copy kept for double-buffered
+    /// types. For the element that made an update and needs its result.
+    pub fn read_latest<T: 'static, R>(
+        &self,
+        entity: &Entity<T>,
+        f: impl FnOnce(&T) -> R,
+    ) -> Option<R> {
+        let id = entity.id();
+        let slot = self.slots.get(id.index() as usize)?;
+
+        if !slot.is_valid(id.generation()) {
+            return None;
+        }
+
         let data = slot.data.as_ref()?;
         let value = data.downcast_ref::<T>()?;
         Some(f(value))
@@ -92,7 +181,15 @@ impl EntityStore {
         entity: &Entity<T>,
         f: impl FnOnce(&mut T) -> R,
     ) -> Option<R> {
-        let id = entity.id();
+        self.update_by_id(entity.id(), f)
+    }
+
+    /// Update entity state by id, for callers that don't hold an [`Entity`]
+    pub(crate) fn update_by_id<T: 'static, R>(
+        &mut self,
+        id: EntityId,
+        f: impl FnOnce(&mut T) -> R,
+    ) -> Option<R> {
         let slot = self.slots.get_mut(id.index() as usize)?;
 
         if !slot.is_valid(id.generation()) {
@@ -100,12 +197,31 @@ impl EntityStore {
         }
 
         let data = slot.data.as_mut()?;
+        let needs_copy = self.in_frame && slot.frame_start.is_none();
+        if let Some(clone) = self
+            .double_buffered
+            .get(&TypeId::of::<T>())
+            .filter(|_| needs_copy)
+        {
+            slot.frame_start = clone(&**data);
+            slot.frame_start_version = slot.version;
+            self.frame_copies.push(id.index());
+        }
+        slot.version += 1;
         let value = data.downcast_mut::<T>()?;
+        let before = self
+            .history
+            .as_ref()
+            .and_then(|history| history.snapshot(&*value));
 
         // Mark this entity as dirty for the subscription system
         self.subscriptions.mark_dirty(id);
 
-        Some(f(value))
+        let result = f(value);
+        if let Some(history) = &mut self.history {
+            history.record(id, std::any::type_name::<T>(), before, &*value);
+        }
+        Some(result)
     }
 
     /// Observe entity state (read with subscription tracking)
@@ -123,12 +239,183 @@ impl EntityStore {
 
         // Register this entity as observed
         self.subscriptions.observe(id);
+        record_observed(&mut self.recordings, id, slot.readable_version());
 
-        let data = slot.data.as_ref()?;
-        let value = data.downcast_ref::<T>()?;
+        let value = slot.readable()?.downcast_ref::<T>()?;
         Some(f(value))
     }
 
+    /// Register an entity as observed for the current frame without reading it
+    ///
+    /// For caches that reuse output built from the entity's state.
+    pub(crate) fn observe_id(&mut self, id: EntityId) {
+        let Some(version) = self.version(id) else {
+            return;
+        };
+        self.subscriptions.observe(id);
+        record_observed(&mut self.recordings, id, version);
+    }
+
+    /// How many times the entity has been updated, as seen by reads
+    ///
+    /// Changes whenever the state reads see changes, so a cache built from
+    /// it can tell whether it's stale. `None` for a dead entity.
+    pub fn version(&self, id: EntityId) -> Option<u64> {
+        let slot = self.slots.get(id.index() as usize)?;
+        if !slot.is_valid(id.generation()) {
+            return None;
+        }
+        Some(slot.readable_version())
+    }
+
+    /// Start collecting the entities observed until the matching
+    /// [`end_recording`](Self::end_recording)
+    ///
+    /// Recordings nest: entities observed in an inner recording also count
+    /// for the enclosing ones.
+    pub(crate) fn begin_recording(&mut self) {
+        self.recordings.push(Vec::new());
+    }
+
+    /// Stop the innermost recording and return the entities it observed,
+    /// with the version of each that was read
+    pub(crate) fn end_recording(&mut self) -> Vec<(EntityId, u64)> {
+        let recorded = self.recordings.pop().unwrap_or_default();
+        for &(id, version) in &recorded {
+            record_observed(&mut self.recordings, id, version);
+        }
+        recorded
+    }
+
+    /// Observe entity state through a mutable reference, without marking it dirty
+    ///
+    /// For reading through accessors shaped like `|s| &mut s.field` that are
+    /// also used to write. `f` must not change the state, since observers
+    /// wouldn't be told about it.
+    pub(crate) fn observe_mut<T: 'static, R>(
+        &mut self,
+        entity: &Entity<T>,
+        f: impl FnOnce(&mut T) -> R,
+    ) -> Option<R> {
+        let id = entity.id();
+        let slot = self.slots.get_mut(id.index() as usize)?;
+
+        if !slot.is_valid(id.generation()) {
+            return None;
+        }
+
+        self.subscriptions.observe(id);
+        record_observed(&mut self.recordings, id, slot.readable_version());
+
+        let data = slot.frame_start.as_mut().or(slot.data.as_mut())?;
+        let value = data.downcast_mut::<T>()?;
+        Some(f(value))
+    }
+
+    /// Get a handle for queueing updates to this store from other closures
+    /// or threads
+    pub fn handle(&self) -> EntityStoreHandle {
+        EntityStoreHandle::new(self.handle_shared.clone())
+    }
+
+    /// Apply updates queued through this store's handles
+    ///
+    /// Called by the app once per frame, before handling input. Returns the
+    /// number of updates applied.
+    pub fn apply_queued_updates(&mut self) -> usize {
+        let updates = self.handle_shared.take_queued();
+        let count = updates.len();
+        for update in updates {
+            update(self);
+        }
+        count
+    }
+
+    /// Set how handles wake the UI thread after queueing an update
+    #[cfg(target_os = "macos")]
+    pub(crate) fn set_waker(&self, waker: fn()) {
+        self.handle_shared.set_waker(waker);
+    }
+
+    /// Keep reads of `T` consistent within a frame
+    ///
+    /// The first update to a `T` entity while a frame is being built clones
+    /// its state, and reads for the rest of the frame see that copy. Has no
+    /// effect in [`EntityReadMode::Immediate`].
+    pub fn double_buffer<T: Clone + 'static>(&mut self) {
+        self.double_buffered.insert(TypeId::of::<T>(), |value| {
+            let value = value.downcast_ref::<T>()?;
+            Some(Box::new(value.clone()))
+        });
+    }
+
+    /// Set how reads see updates made during a frame
+    pub fn set_read_mode(&mut self, mode: EntityReadMode) {
+        self.read_mode = mode;
+    }
+
+    /// How reads see updates made during a frame
+    pub fn read_mode(&self) -> EntityReadMode {
+        self.read_mode
+    }
+
+    /// Mark the start of a frame's layout and paint
+    ///
+    /// Called by the layer manager before building layers. Frame-start
+    /// copies are dropped again by [`cleanup`](Self::cleanup).
+    pub fn begin_frame(&mut self) {
+        self.in_frame = self.read_mode == EntityReadMode::FrameConsistent;
+    }
+
+    /// Start recording mutations, keeping the last `capacity`
+    ///
+    /// Replaces any history already recorded.
+    pub fn enable_history(&mut self, capacity: usize) {
+        self.history = Some(EntityHistory::new(capacity));
+    }
+
+    /// Stop recording mutations and drop the recorded history
+    pub fn disable_history(&mut self) {
+        self.history = None;
+    }
+
+    /// Recorded mutations, if history is enabled
+    pub fn history(&self) -> Option<&EntityHistory> {
+        self.history.as_ref()
+    }
+
+    /// Recorded mutations, for registering tracked types
+    pub fn history_mut(&mut self) -> Option<&mut EntityHistory> {
+        self.history.as_mut()
+    }
+
+    /// Undo the most recent applied mutation
+    ///
+    /// Restores the entity to its state before the mutation. Returns
+    /// `false` if there's nothing to step back over.
+    pub fn step_back(&mut self) -> bool {
+        let Some((id, restore)) = self.history.as_mut().and_then(|h| h.step_back()) else {
+            return false;
+        };
+        if let Some(restore) = restore {
+            restore_slot(&mut self.slots, &mut self.subscriptions, id, restore);
+        }
+        true
+    }
+
+    /// Reapply the next mutation after stepping back
+    ///
+    /// Returns `false` if the history is already at the latest state.
+    pub fn step_forward(&mut self) -> bool {
+        let Some((id, restore)) = self.history.as_mut().and_then(|h| h.step_forward()) else {
+            return false;
+        };
+        if let Some(restore) = restore {
+            restore_slot(&mut self.slots, &mut self.subscriptions, id, restore);
+        }
+        true
+    }
+
     /// Increment reference count for an entity
     pub(crate) fn increment_ref(&mut self, id: EntityId) {
         if let Some(slot) = self.slots.get_mut(id.index() as usize) {
@@ -158,6 +445,16 @@ impl EntityStore {
     ///
     /// Returns `true` if any observed entity was mutated and a re-render is needed.
     pub fn cleanup(&mut self) -> bool {
+        // Updates made during the frame become visible to reads
+        self.in_frame = false;
+        // Recordings left open by a layer that panicked mid-frame
+        self.recordings.clear();
+        for index in self.frame_copies.drain(..) {
+            if let Some(slot) = self.slots.get_mut(index as usize) {
+                slot.frame_start = None;
+            }
+        }
+
         for index in self.pending_cleanup.drain(..) {
             if let Some(slot) = self.slots.get_mut(index as usize) {
                 // Only clean up if still at zero refs (could have been re-referenced)
@@ -218,6 +515,37 @@ impl EntityStore {
     }
 }
 
+/// Add an observed entity to the innermost recording, keeping the version
+/// first seen
+fn record_observed(recordings: &mut [Vec<(EntityId, u64)>], id: EntityId, version: u64) {
+    if let Some(recording) = recordings
+        .last_mut()
+        .filter(|recording| !recording.iter().any(|(seen, _)| *seen == id))
+    {
+        recording.push((id, version));
+    }
+}
+
+/// Write a history snapshot back into an entity, if it's still alive
+fn restore_slot(
+    slots: &mut [EntitySlot],
+    subscriptions: &mut SubscriptionManager,
+    id: EntityId,
+    restore: Restore<'_>,
+) {
+    let Some(slot) = slots.get_mut(id.index() as usize) else {
+        return;
+    };
+    if !slot.is_valid(id.generation()) {
+        return;
+    }
+    if let Some(data) = slot.data.as_mut() {
+        restore.apply(data.as_mut());
+        slot.version += 1;
+        subscriptions.mark_dirty(id);
+    }
+}
+
 impl Default for EntityStore {
     fn default() -> Self {
         Self::new()
@@ -228,7 +556,7 @@ impl Default for EntityStore {
 mod tests {
     use super::*;
 
-    #[derive(Debug, PartialEq)]
+    #[derive(Debug, Clone, PartialEq)]
     struct TestState {
         value: i32,
     }
@@ -408,4 +736,99 @@ mod tests {
         let needs_render = store.cleanup();
         assert!(needs_render);
     }
+
+    #[test]
+    fn test_history_steps_back_and_forward() {
+        let mut store = EntityStore::new();
+        store.enable_history(8);
+        store.history_mut().unwrap().track::<TestState>();
+        let entity = store.create(TestState { value: 0 });
+
+        store.update(&entity, |s| s.value = 1);
+        store.update(&entity, |s| s.value = 2);
+        // Updates that don't change a tracked entity aren't recorded
+        store.update(&entity, |_| {});
+        assert_eq!(store.history().unwrap().len(), 2);
+
+        assert!(store.step_back());
+        assert_eq!(store.read(&entity, |s| s.value), Some(1));
+        assert!(store.step_back());
+        assert_eq!(store.read(&entity, |s| s.value), Some(0));
+        assert!(!store.step_back());
+
+        assert!(store.step_forward());
+        assert_eq!(store.read(&entity, |s| s.value), Some(1));
+
+        // Untracked updates aren't recorded, so they keep the history that
+        // was stepped over
+        let other = store.create(String::new());
+        store.update(&other, |s| s.push('a'));
+        assert_eq!(store.history().unwrap().len(), 2);
+        assert!(store.history().unwrap().is_time_travelling());
+
+        // A new tracked mutation drops it
+        store.update(&entity, |s| s.value = 5);
+        assert_eq!(store.history().unwrap().len(), 2);
+        assert!(!store.step_forward());
+    }
+
+    #[test]
+    fn test_frame_consistent_reads() {
+        let mut store = EntityStore::new();
+        store.double_buffer::<TestState>();
+        let entity = store.create(TestState { value: 0 });
+
+        store.begin_frame();
+        store.observe(&entity, |s| s.value);
+        store.update(&entity, |s| s.value = 1);
+        store.update(&entity, |s| s.value += 1);
+        // Later reads in the frame see the frame-start state
+        assert_eq!(store.read(&entity, |s| s.value), Some(0));
+        assert_eq!(store.read_latest(&entity, |s| s.value), Some(2));
+
+        // The updates show from the next frame, which they request
+        assert!(store.cleanup());
+        assert_eq!(store.read(&entity, |s| s.value), Some(2));
+
+        store.set_read_mode(EntityReadMode::Immediate);
+        store.begin_frame();
+        store.update(&entity, |s| s.value = 3);
+        assert_eq!(store.read(&entity, |s| s.value), Some(3));
+    }
+
+    #[test]
+    fn test_version_follows_reads() {
+        let mut store = EntityStore::new();
+        store.double_buffer::<TestState>();
+        let entity = store.create(TestState { value: 0 });
+        let id = entity.id();
+        assert_eq!(store.version(id), Some(0));
+
+        store.update(&entity, |s| s.value = 1);
+        assert_eq!(store.version(id), Some(1));
+
+        // Mid-frame updates aren't visible to reads until the frame ends
+        store.begin_frame();
+        store.update(&entity, |s| s.value = 2);
+        assert_eq!(store.version(id), Some(1));
+        store.cleanup();
+        assert_eq!(store.version(id), Some(2));
+    }
+
+    #[test]
+    fn test_nested_recordings() {
+        let mut store = EntityStore::new();
+        let a = store.create(TestState { value: 0 });
+        let b = store.create(TestState { value: 0 });
+        store.update(&b, |s| s.value = 1);
+
+        store.begin_recording();
+        store.observe(&a, |s| s.value);
+        store.begin_recording();
+        store.observe(&b, |s| s.value);
+        store.observe(&b, |s| s.value);
+        assert_eq!(store.end_recording(), vec![(b.id(), 1)]);
+        assert_eq!(store.end_recording(), vec![(a.id(), 0), (b.id(), 1)]);
+        assert!(store.end_recording().is_empty());
+    }
 }
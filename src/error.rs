@@ -0,0 +1,220 @@
+//! Crate-wide error type
+//!
+//! Renderer, text system, image, and input recording failures are reported as [`SolError`] so that
+//! applications can match on the kind of failure and decide how to recover.
+//!
+//! ```ignore
+//! use sol_ui::error::{SolError, SolResultExt};
+//!
+//! let text_system = match TextSystem::new(&device) {
+//!     Ok(system) => system,
+//!     // Fall back to a smaller atlas on memory-constrained devices
+//!     Err(e) if e.is_atlas_full() => TextSystem::with_atlas_size(&device, 1024, 1024)?,
+//!     Err(e) => return Err(e.context("Failed to create text system")),
+//! };
+//! ```
+
+use std::fmt;
+
+/// Errors produced by the renderer, text system, images, and input recording
+#[derive(Debug)]
+pub enum SolError {
+    /// Metal shader source failed to compile
+    ShaderCompilation(String),
+    /// A function was not found in a compiled shader library
+    MissingShaderFunction { name: String, message: String },
+    /// Creating a render pipeline state failed
+    PipelineCreation {
+        pipeline: &'static str,
+        message: String,
+    },
+    /// Font data could not be loaded or parsed
+    FontLoading(String),
+    /// The glyph atlas has no room left for a glyph of the requested size
+    AtlasFull {
+        atlas_width: u32,
+        atlas_height: u32,
+        glyph_width: u32,
+        glyph_height: u32,
+    },
+    /// A glyph could not be rasterized
+    GlyphMissing { glyph_id: u16 },
+    /// An image could not be decoded or encoded
+    Image(String),
+    /// Reading or writing a file failed
+    Io(std::io::Error),
+    /// Serializing or deserializing data failed
+    Serialization(serde_json::Error),
+    /// A translation file could not be parsed
+    Translation { line: usize, message: String },
+    /// Frame graph passes and textures don't form a valid graph
+    FrameGraph(String),
+    /// Additional context wrapped around an underlying error
+    Context {
+        context: String,
+        source: Box<SolError>,
+    },
+}
+
+impl SolError {
+    /// Wrap this error with additional context
+    pub fn context(self, context: impl Into<String>) -> Self {
+        SolError::Context {
+            context: context.into(),
+            source: Box::new(self),
+        }
+    }
+
+    /// Get the innermost error, skipping any context layers
+    pub fn root_cause(&self) -> &SolError {
+        let mut error = self;
+        while let SolError::Context { source, .. } = error {
+            error = source;
+        }
+        error
+    }
+
+    /// Check if the root cause is a full glyph atlas
+    pub fn is_atlas_full(&self) -> bool {
+        matches!(self.root_cause(), SolError::AtlasFull { .. })
+    }
+}
+
+impl fmt::Display for SolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolError::ShaderCompilation(e) => write!(f, "Failed to compile shaders: {}", e),
+            SolError::MissingShaderFunction { name, message } => {
+                write!(f, "Failed to find {} function: {}", name, message)
+            }
+            SolError::PipelineCreation { pipeline, message } => {
+                write!(
+                    f,
+                    "Failed to create {} pipeline state: {}",
+                    pipeline, message
+                )
+            }
+            SolError::FontLoading(e) => write!(f, "Failed to load font: {}", e),
+            SolError::AtlasFull {
+                atlas_width,
+                atlas_height,
+                glyph_width,
+                glyph_height,
+            } => write!(
+                f,
+                "Atlas is full ({}x{} atlas, {}x{} glyph)",
+                atlas_width, atlas_height, glyph_width, glyph_height
+            ),
+            SolError::GlyphMissing { glyph_id } => {
+                write!(f, "Failed to render glyph {}", glyph_id)
+            }
+            SolError::Image(e) => write!(f, "Image error: {}", e),
+            SolError::Io(e) => write!(f, "I/O error: {}", e),
+            SolError::Serialization(e) => write!(f, "Serialization error: {}", e),
+            SolError::Translation { line, message } => {
+                write!(f, "Translation error on line {}: {}", line, message)
+            }
+            SolError::FrameGraph(e) => write!(f, "Invalid frame graph: {}", e),
+            SolError::Context { context, .. } => write!(f, "{}", context),
+        }
+    }
+}
+
+impl std::error::Error for SolError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SolError::Io(e) => Some(e),
+            SolError::Serialization(e) => Some(e),
+            SolError::Context { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for SolError {
+    fn from(e: std::io::Error) -> Self {
+        SolError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for SolError {
+    fn from(e: serde_json::Error) -> Self {
+        SolError::Serialization(e)
+    }
+}
+
+/// Result type for fallible sol-ui operations
+pub type SolResult<T> = Result<T, SolError>;
+
+/// Extension trait for adding context to a [`SolResult`]
+pub trait SolResultExt<T> {
+    /// Wrap the error with a context message
+    fn context(self, context: impl Into<String>) -> SolResult<T>;
+
+    /// Wrap the error with a lazily built context message
+    fn with_context<F, S>(self, f: F) -> SolResult<T>
+    where
+        F: FnOnce() -> S,
+        S: Into<String>;
+}
+
+impl<T> SolResultExt<T> for SolResult<T> {
+    fn context(self, context: impl Into<String>) -> SolResult<T> {
+        self.map_err(|e| e.context(context))
+    }
+
+    fn with_context<F, S>(self, f: F) -> SolResult<T>
+    where
+        F: FnOnce() -> S,
+        S: Into<String>,
+    {
+        self.map_err(|e| e.context(f()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    fn atlas_full() -> SolError {
+        SolError::AtlasFull {
+            atlas_width: 2048,
+            atlas_height: 2048,
+            glyph_width: 64,
+            glyph_height: 64,
+        }
+    }
+
+    #[test]
+    fn test_root_cause_skips_context() {
+        let error = atlas_full()
+            .context("shaping text")
+            .context("rendering layer");
+        assert!(matches!(error.root_cause(), SolError::AtlasFull { .. }));
+        assert!(error.is_atlas_full());
+    }
+
+    #[test]
+    fn test_source_chain() {
+        let error = atlas_full().context("shaping text");
+        assert_eq!(error.to_string(), "shaping text");
+
+        let source = error.source().expect("context should have a source");
+        assert_eq!(
+            source.to_string(),
+            "Atlas is full (2048x2048 atlas, 64x64 glyph)"
+        );
+        assert!(source.source().is_none());
+    }
+
+    #[test]
+    fn test_result_context() {
+        let result: SolResult<()> = Err(SolError::GlyphMissing { glyph_id: 7 });
+        let error = result
+            .with_context(|| format!("glyph run {}", 3))
+            .unwrap_err();
+        assert_eq!(error.to_string(), "glyph run 3");
+        assert!(!error.is_atlas_full());
+    }
+}
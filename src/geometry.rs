@@ -118,11 +118,93 @@ impl Rect {
         Rect::from_pos_size(self.pos - expansion, self.size + expansion * 2.0)
     }
 
+    /// Expand the rectangle outwards by a different amount on each edge
+    pub fn expand_edges(&self, edges: Edges) -> Rect {
+        Rect::new(
+            self.pos.x - edges.left,
+            self.pos.y - edges.top,
+            self.size.x + edges.horizontal(),
+            self.size.y + edges.vertical(),
+        )
+    }
+
     /// Contract the rectangle by the given amount in all directions
     pub fn contract(&self, amount: f32) -> Rect {
         let new_size = (self.size - Vec2::splat(amount * 2.0)).max(Vec2::ZERO);
         Rect::from_pos_size(self.pos + Vec2::splat(amount), new_size)
     }
+
+    /// Move each edge to the nearest device pixel boundary
+    ///
+    /// A side thinner than a device pixel, such as a hairline, keeps one
+    /// device pixel so it doesn't vanish.
+    pub fn snap_to_pixels(&self, scale_factor: f32) -> Rect {
+        let snap = |start: f32, size: f32| {
+            let first = (start * scale_factor).round();
+            let mut last = ((start + size) * scale_factor).round();
+            if size > 0.0 && last <= first {
+                last = first + 1.0;
+            }
+            (first / scale_factor, (last - first) / scale_factor)
+        };
+        let (x, width) = snap(self.pos.x, self.size.x);
+        let (y, height) = snap(self.pos.y, self.size.y);
+        Rect::new(x, y, width, height)
+    }
+}
+
+/// A uniform scale followed by a translation
+///
+/// Maps a point `p` to `p * scale + offset`. Used for zoomable content, where
+/// children paint in their own coordinate space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub offset: Vec2,
+    pub scale: f32,
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Self {
+        offset: Vec2::ZERO,
+        scale: 1.0,
+    };
+
+    pub fn new(offset: Vec2, scale: f32) -> Self {
+        Self { offset, scale }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::IDENTITY
+    }
+
+    /// Map a point into the outer coordinate space
+    pub fn apply(&self, point: Vec2) -> Vec2 {
+        point * self.scale + self.offset
+    }
+
+    /// Map a rectangle into the outer coordinate space
+    pub fn apply_rect(&self, rect: Rect) -> Rect {
+        Rect::from_pos_size(self.apply(rect.pos), rect.size * self.scale)
+    }
+
+    /// Map a point from the outer coordinate space back into this one
+    pub fn apply_inverse(&self, point: Vec2) -> Vec2 {
+        (point - self.offset) / self.scale
+    }
+
+    /// Combine with a transform applied first, e.g. a nested child's
+    pub fn then(&self, inner: Transform) -> Transform {
+        Transform {
+            offset: self.apply(inner.offset),
+            scale: self.scale * inner.scale,
+        }
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
 }
 
 #[cfg(test)]
@@ -165,6 +247,33 @@ mod tests {
         assert_eq!(rect.visibility_ratio_in(&offscreen), 0.0);
     }
 
+    #[test]
+    fn test_transform_round_trip() {
+        let outer = Transform::new(Vec2::new(10.0, 20.0), 2.0);
+        let inner = Transform::new(Vec2::new(5.0, 0.0), 0.5);
+        let combined = outer.then(inner);
+
+        let point = Vec2::new(4.0, 8.0);
+        assert_eq!(combined.apply(point), outer.apply(inner.apply(point)));
+        assert_eq!(combined.apply_inverse(combined.apply(point)), point);
+
+        let rect = outer.apply_rect(Rect::new(1.0, 1.0, 10.0, 5.0));
+        assert_eq!(rect, Rect::new(12.0, 22.0, 20.0, 10.0));
+    }
+
+    #[test]
+    fn test_rect_snap_to_pixels() {
+        // Edges land on half points at 2x
+        let rect = Rect::new(10.3, 4.9, 20.1, 0.5).snap_to_pixels(2.0);
+        assert_eq!(rect, Rect::new(10.5, 5.0, 20.0, 0.5));
+
+        // A hairline keeps one device pixel
+        let hairline = Rect::new(0.0, 10.0, 100.0, 0.1).snap_to_pixels(2.0);
+        assert_eq!(hairline, Rect::new(0.0, 10.0, 100.0, 0.5));
+        let hairline = Rect::new(3.2, 0.0, 0.2, 10.0).snap_to_pixels(1.0);
+        assert_eq!(hairline, Rect::new(3.0, 0.0, 1.0, 10.0));
+    }
+
     #[test]
     fn test_rect_expand_contract() {
         let rect = Rect::new(10.0, 10.0, 100.0, 100.0);
@@ -264,4 +373,14 @@ impl Edges {
     pub fn size(&self) -> Vec2 {
         Vec2::new(self.horizontal(), self.vertical())
     }
+
+    /// Apply `f` to each edge
+    pub fn map(self, f: impl Fn(f32) -> f32) -> Self {
+        Self {
+            top: f(self.top),
+            right: f(self.right),
+            bottom: f(self.bottom),
+            left: f(self.left),
+        }
+    }
 }
@@ -0,0 +1,634 @@
+//! Localized strings and locale-aware formatting
+//!
+//! Translations are added per locale to the thread's catalog, either from
+//! Fluent-style `key = value` files or from flat JSON objects, and looked up
+//! with [`tr!`](crate::tr) while building elements. Switching locale with
+//! [`set_locale`] requests a full frame, so trees built with `tr!` pick up the
+//! new strings without any extra bookkeeping.
+//!
+//! ```ignore
+//! use sol_ui::{i18n::{self, Locale, Translations}, tr};
+//!
+//! i18n::add_translations("de", Translations::parse("greeting = Hallo, { $name }!")?);
+//! i18n::set_locale(Locale::new("de-DE"));
+//!
+//! text(tr!("greeting", name = "Ada"), style)
+//! text(i18n::format_number(1234.5, 2), style) // "1.234,50"
+//! ```
+
+use crate::{
+    element::CalendarDate,
+    error::{SolError, SolResult, SolResultExt},
+};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+thread_local! {
+    static CATALOG: RefCell<Catalog> = RefCell::new(Catalog::new());
+    static LOCALE_CHANGED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// A language tag such as `en-US`, `de` or `zh-Hant-TW`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Locale {
+    tag: String,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self::new("en-US")
+    }
+}
+
+impl Locale {
+    /// Create a locale from a language tag
+    ///
+    /// POSIX-style names like `de_DE.UTF-8` are accepted and normalized to
+    /// `de-DE`.
+    pub fn new(tag: impl AsRef<str>) -> Self {
+        let tag = tag.as_ref();
+        let tag = tag.split(['.', '@']).next().unwrap_or(tag);
+        let subtags: Vec<String> = tag
+            .split(['-', '_'])
+            .filter(|s| !s.is_empty())
+            .enumerate()
+            .map(|(i, subtag)| match (i, subtag.len()) {
+                (0, _) => subtag.to_ascii_lowercase(),
+                // Region, e.g. "US"
+                (_, 2) => subtag.to_ascii_uppercase(),
+                // Script, e.g. "Hant"
+                (_, 4) => {
+                    let first_len = subtag.chars().next().map_or(0, char::len_utf8);
+                    let (first, rest) = subtag.split_at(first_len);
+                    first.to_ascii_uppercase() + &rest.to_ascii_lowercase()
+                }
+                _ => subtag.to_string(),
+            })
+            .collect();
+
+        if subtags.is_empty() {
+            return Self::default();
+        }
+        Self {
+            tag: subtags.join("-"),
+        }
+    }
+
+    /// The user's preferred locale
+    ///
+    /// Reads the `AppleLocale` user default on macOS, then the `LANG`
+    /// environment variable, and falls back to `en-US`.
+    pub fn from_system() -> Self {
+        #[cfg(target_os = "macos")]
+        {
+            use crate::platform::mac::user_default_string;
+
+            if let Some(tag) = user_default_string("AppleLocale") {
+                return Self::new(tag);
+            }
+        }
+
+        match std::env::var("LANG") {
+            Ok(lang) if !lang.is_empty() && lang != "C" && lang != "POSIX" => Self::new(lang),
+            _ => Self::default(),
+        }
+    }
+
+    /// The full language tag
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    /// The language subtag, e.g. `en` for `en-US`
+    pub fn language(&self) -> &str {
+        self.tag.split('-').next().unwrap_or(&self.tag)
+    }
+
+    /// The region subtag, e.g. `US` for `en-US`, if there is one
+    pub fn region(&self) -> Option<&str> {
+        self.tag
+            .split('-')
+            .skip(1)
+            .find(|s| s.len() == 2 || (s.len() == 3 && s.chars().all(|c| c.is_ascii_digit())))
+    }
+
+    /// Decimal and grouping separators for numbers
+    fn number_separators(&self) -> (char, char) {
+        match (self.language(), self.region()) {
+            ("de" | "it", Some("CH" | "LI")) => ('.', '\u{2019}'),
+            ("fr", _) => (',', '\u{202F}'),
+            (
+                "de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" | "el" | "ro" | "hr" | "sl"
+                | "sr" | "vi",
+                _,
+            ) => (',', '.'),
+            (
+                "ru" | "pl" | "uk" | "cs" | "sk" | "sv" | "nb" | "no" | "fi" | "hu" | "bg" | "lt"
+                | "lv" | "et",
+                _,
+            ) => (',', '\u{00A0}'),
+            _ => ('.', ','),
+        }
+    }
+
+    /// Short numeric date pattern: `y` year, `M`/`MM` month, `d`/`dd` day
+    fn date_pattern(&self) -> &'static str {
+        match (self.language(), self.region()) {
+            ("en", None | Some("US" | "PH")) => "M/d/y",
+            ("en", Some("CA")) | ("sv" | "lt", _) => "y-MM-dd",
+            ("ja" | "zh", _) => "y/MM/dd",
+            ("ko" | "hu", _) => "y. MM. dd.",
+            ("fi", _) => "d.M.y",
+            ("nl", _) => "dd-MM-y",
+            ("de" | "ru" | "pl" | "uk" | "tr" | "nb" | "no" | "da" | "cs" | "sk", _) => "dd.MM.y",
+            _ => "dd/MM/y",
+        }
+    }
+
+    /// Format a number with this locale's separators and a fixed number of
+    /// decimal places
+    pub fn format_number(&self, value: f64, decimals: usize) -> String {
+        if !value.is_finite() {
+            return value.to_string();
+        }
+
+        let (decimal, group) = self.number_separators();
+        let digits = format!("{:.*}", decimals, value.abs());
+        let (integer, fraction) = digits.split_once('.').unwrap_or((digits.as_str(), ""));
+
+        let mut result = String::new();
+        // Skip the sign when the value rounds to zero
+        if value < 0.0 && digits.bytes().any(|b| matches!(b, b'1'..=b'9')) {
+            result.push('-');
+        }
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                result.push(group);
+            }
+            result.push(digit);
+        }
+        if !fraction.is_empty() {
+            result.push(decimal);
+            result.push_str(fraction);
+        }
+        result
+    }
+
+    /// Format a date in this locale's short numeric style, e.g. `3/5/2024`
+    /// for `en-US` or `05.03.2024` for `de`
+    pub fn format_date(&self, date: CalendarDate) -> String {
+        let mut result = String::new();
+        let mut chars = self.date_pattern().chars().peekable();
+        while let Some(c) = chars.next() {
+            let padded = matches!(c, 'M' | 'd') && chars.next_if_eq(&c).is_some();
+            let value = match c {
+                'y' => date.year() as u32,
+                'M' => date.month(),
+                'd' => date.day(),
+                _ => {
+                    result.push(c);
+                    continue;
+                }
+            };
+            if padded {
+                result.push_str(&format!("{:02}", value));
+            } else {
+                result.push_str(&value.to_string());
+            }
+        }
+        result
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.tag)
+    }
+}
+
+impl From<&str> for Locale {
+    fn from(tag: &str) -> Self {
+        Self::new(tag)
+    }
+}
+
+impl From<String> for Locale {
+    fn from(tag: String) -> Self {
+        Self::new(tag)
+    }
+}
+
+/// A value substituted into a translated message
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrArg {
+    Text(String),
+    /// Formatted with the current locale's separators
+    Number(f64),
+}
+
+impl From<&str> for TrArg {
+    fn from(value: &str) -> Self {
+        TrArg::Text(value.to_string())
+    }
+}
+
+impl From<String> for TrArg {
+    fn from(value: String) -> Self {
+        TrArg::Text(value)
+    }
+}
+
+impl From<&String> for TrArg {
+    fn from(value: &String) -> Self {
+        TrArg::Text(value.clone())
+    }
+}
+
+macro_rules! impl_number_arg {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for TrArg {
+                fn from(value: $ty) -> Self {
+                    TrArg::Number(value as f64)
+                }
+            }
+        )*
+    };
+}
+
+impl_number_arg!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32);
+
+impl From<f64> for TrArg {
+    fn from(value: f64) -> Self {
+        TrArg::Number(value)
+    }
+}
+
+impl TrArg {
+    fn format(&self, locale: &Locale) -> String {
+        match self {
+            TrArg::Text(text) => text.clone(),
+            TrArg::Number(value) => {
+                // Show as many decimals as the value needs, up to six
+                let shortest = format!("{}", (value * 1e6).round() / 1e6);
+                let decimals = shortest.split_once('.').map_or(0, |(_, f)| f.len());
+                locale.format_number(*value, decimals)
+            }
+        }
+    }
+}
+
+/// A table of translated messages for one locale
+#[derive(Debug, Clone, Default)]
+pub struct Translations {
+    messages: HashMap<String, String>,
+}
+
+impl Translations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse Fluent-style messages
+    ///
+    /// Supports the subset of Fluent syntax needed for simple messages:
+    /// `key = value` lines, `#` comments, indented continuation lines, and
+    /// `{ $name }` placeholders. Selectors and terms aren't supported.
+    pub fn parse(source: &str) -> SolResult<Self> {
+        let mut translations = Self::new();
+        let mut current: Option<String> = None;
+
+        for (index, line) in source.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.starts_with('#') {
+                current = None;
+                continue;
+            }
+
+            // Indented lines continue the previous message
+            if line.starts_with([' ', '\t']) {
+                if trimmed.is_empty() {
+                    continue;
+                }
+                let Some(message) = current
+                    .as_ref()
+                    .and_then(|key| translations.messages.get_mut(key))
+                else {
+                    return Err(SolError::Translation {
+                        line: index + 1,
+                        message: "Continuation line without a message".to_string(),
+                    });
+                };
+                if !message.is_empty() {
+                    message.push('\n');
+                }
+                message.push_str(trimmed);
+                continue;
+            }
+
+            if trimmed.is_empty() {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(SolError::Translation {
+                    line: index + 1,
+                    message: format!("Expected `key = value`, found `{}`", trimmed),
+                });
+            };
+            let key = key.trim();
+            if key.is_empty()
+                || !key
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+            {
+                return Err(SolError::Translation {
+                    line: index + 1,
+                    message: format!("Invalid message key `{}`", key),
+                });
+            }
+            translations
+                .messages
+                .insert(key.to_string(), value.trim().to_string());
+            current = Some(key.to_string());
+        }
+
+        Ok(translations)
+    }
+
+    /// Parse a flat JSON object of keys to messages
+    pub fn from_json(source: &str) -> SolResult<Self> {
+        Ok(Self {
+            messages: serde_json::from_str(source)?,
+        })
+    }
+
+    /// Load translations from a file, as JSON if it has a `.json` extension
+    /// and as Fluent-style messages otherwise
+    pub fn load(path: impl AsRef<Path>) -> SolResult<Self> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)?;
+        let translations = if path.extension().is_some_and(|ext| ext == "json") {
+            Self::from_json(&source)
+        } else {
+            Self::parse(&source)
+        };
+        translations.with_context(|| format!("Failed to load {}", path.display()))
+    }
+
+    /// Add a message
+    pub fn with(mut self, key: impl Into<String>, message: impl Into<String>) -> Self {
+        self.insert(key, message);
+        self
+    }
+
+    /// Add or replace a message
+    pub fn insert(&mut self, key: impl Into<String>, message: impl Into<String>) {
+        self.messages.insert(key.into(), message.into());
+    }
+
+    /// Get the message for a key
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.messages.get(key).map(String::as_str)
+    }
+
+    /// Number of messages
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Check if there are no messages
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+}
+
+/// Substitute `{ $name }` placeholders in a message
+///
+/// Quoted literals like `{ "{" }` are inserted as-is. Placeholders without a
+/// matching argument are left in place so missing arguments are visible.
+pub fn format_message(message: &str, args: &[(&str, TrArg)], locale: &Locale) -> String {
+    let mut result = String::with_capacity(message.len());
+    let mut rest = message;
+
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}') else {
+            rest = &rest[start..];
+            break;
+        };
+        let placeholder = &rest[start..start + end + 1];
+        let expression = placeholder[1..placeholder.len() - 1].trim();
+
+        if let Some(name) = expression.strip_prefix('$') {
+            match args.iter().find(|(arg, _)| *arg == name) {
+                Some((_, value)) => result.push_str(&value.format(locale)),
+                None => result.push_str(placeholder),
+            }
+        } else if let Some(literal) = expression
+            .strip_prefix('"')
+            .and_then(|e| e.strip_suffix('"'))
+        {
+            result.push_str(literal);
+        } else {
+            result.push_str(placeholder);
+        }
+        rest = &rest[start + end + 1..];
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// All translations and the active locale for this thread
+struct Catalog {
+    locale: Locale,
+    fallback: Locale,
+    tables: HashMap<Locale, Translations>,
+}
+
+impl Catalog {
+    fn new() -> Self {
+        Self {
+            locale: Locale::from_system(),
+            fallback: Locale::new("en"),
+            tables: HashMap::new(),
+        }
+    }
+
+    /// Find a message, trying the locale, its language, then the fallback
+    fn lookup(&self, key: &str) -> Option<&str> {
+        [&self.locale, &self.fallback]
+            .into_iter()
+            .flat_map(|locale| [locale.clone(), Locale::new(locale.language())])
+            .find_map(|locale| self.tables.get(&locale)?.get(key))
+    }
+}
+
+/// Get the active locale
+pub fn locale() -> Locale {
+    CATALOG.with(|c| c.borrow().locale.clone())
+}
+
+/// Switch the active locale and re-render with the new strings
+pub fn set_locale(locale: impl Into<Locale>) {
+    let locale = locale.into();
+    let changed = CATALOG.with(|c| {
+        let mut catalog = c.borrow_mut();
+        let changed = catalog.locale != locale;
+        catalog.locale = locale;
+        changed
+    });
+    if changed {
+        LOCALE_CHANGED.with(|f| f.set(true));
+    }
+}
+
+/// Set the locale whose messages are used when the active locale lacks a key
+///
+/// Defaults to `en`.
+pub fn set_fallback_locale(locale: impl Into<Locale>) {
+    CATALOG.with(|c| c.borrow_mut().fallback = locale.into());
+}
+
+/// Add messages for a locale, replacing any existing messages with the same keys
+///
+/// Messages for a bare language like `de` are used by every `de-*` locale
+/// that doesn't have its own.
+pub fn add_translations(locale: impl Into<Locale>, translations: Translations) {
+    CATALOG.with(|c| {
+        c.borrow_mut()
+            .tables
+            .entry(locale.into())
+            .or_default()
+            .messages
+            .extend(translations.messages);
+    });
+    LOCALE_CHANGED.with(|f| f.set(true));
+}
+
+/// Look up and format a message for the active locale
+///
+/// Falls back to the key itself when no locale has the message. Usually
+/// called through [`tr!`](crate::tr).
+pub fn translate(key: &str, args: &[(&str, TrArg)]) -> String {
+    CATALOG.with(|c| {
+        let catalog = c.borrow();
+        let message = catalog.lookup(key).unwrap_or(key);
+        format_message(message, args, &catalog.locale)
+    })
+}
+
+/// Format a number for the active locale
+pub fn format_number(value: f64, decimals: usize) -> String {
+    CATALOG.with(|c| c.borrow().locale.format_number(value, decimals))
+}
+
+/// Format a date for the active locale
+pub fn format_date(date: CalendarDate) -> String {
+    CATALOG.with(|c| c.borrow().locale.format_date(date))
+}
+
+/// Check and reset whether the locale or translations changed since last frame
+pub(crate) fn take_locale_change() -> bool {
+    LOCALE_CHANGED.with(|f| f.replace(false))
+}
+
+/// Translate a message for the active locale
+///
+/// ```ignore
+/// text(tr!("menu.quit"), style)
+/// text(tr!("inbox.unread", count = 3, name = user.name), style)
+/// ```
+#[macro_export]
+macro_rules! tr {
+    ($key:expr $(,)?) => {
+        $crate::i18n::translate($key, &[])
+    };
+    ($key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::translate(
+            $key,
+            &[$((stringify!($name), $crate::i18n::TrArg::from($value))),+],
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locale_normalization() {
+        let locale = Locale::new("zh_hant_tw.UTF-8");
+        assert_eq!(locale.tag(), "zh-Hant-TW");
+        assert_eq!(locale.language(), "zh");
+        assert_eq!(locale.region(), Some("TW"));
+        assert_eq!(Locale::new("").tag(), "en-US");
+        // Non-ASCII subtags are kept rather than split mid-character
+        assert_eq!(Locale::new("en-éab").tag(), "en-éab");
+    }
+
+    #[test]
+    fn test_parse_messages() {
+        let source = "\
+# Greetings
+hello = Hello, { $name }!
+about =
+    Line one
+    Line two
+";
+        let translations = Translations::parse(source).unwrap();
+        assert_eq!(translations.get("hello"), Some("Hello, { $name }!"));
+        assert_eq!(translations.get("about"), Some("Line one\nLine two"));
+        assert!(Translations::parse("no separator").is_err());
+    }
+
+    #[test]
+    fn test_format_message() {
+        let en = Locale::new("en-US");
+        let args = [("name", TrArg::from("Ada")), ("count", TrArg::from(1200))];
+        assert_eq!(
+            format_message("{ $name } has { $count } {\"{\"}items}", &args, &en),
+            "Ada has 1,200 {items}"
+        );
+        assert_eq!(format_message("Hi { $who }", &args, &en), "Hi { $who }");
+    }
+
+    #[test]
+    fn test_translate_fallback() {
+        add_translations(
+            "en",
+            Translations::new()
+                .with("save", "Save")
+                .with("quit", "Quit"),
+        );
+        add_translations("de", Translations::new().with("save", "Speichern"));
+        set_locale("de-AT");
+        assert!(take_locale_change());
+
+        assert_eq!(tr!("save"), "Speichern");
+        assert_eq!(tr!("quit"), "Quit");
+        assert_eq!(tr!("missing"), "missing");
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(
+            Locale::new("en-US").format_number(-1234567.891, 2),
+            "-1,234,567.89"
+        );
+        assert_eq!(Locale::new("de-DE").format_number(1234.5, 1), "1.234,5");
+        assert_eq!(Locale::new("fr").format_number(1000.0, 0), "1\u{202F}000");
+        assert_eq!(Locale::new("en").format_number(-0.001, 2), "0.00");
+    }
+
+    #[test]
+    fn test_format_date() {
+        let date = CalendarDate::from_ymd_opt(2024, 3, 5).unwrap();
+        assert_eq!(Locale::new("en-US").format_date(date), "3/5/2024");
+        assert_eq!(Locale::new("en-GB").format_date(date), "05/03/2024");
+        assert_eq!(Locale::new("de").format_date(date), "05.03.2024");
+        assert_eq!(Locale::new("ja-JP").format_date(date), "2024/03/05");
+    }
+}
@@ -0,0 +1,237 @@
+//! In-memory RGBA images
+//!
+//! [`RgbaImage`] is the pixel format exchanged with the clipboard and other
+//! platform APIs: tightly packed, non-premultiplied 8-bit RGBA rows.
+//! [`ImageHandle`] receives images that are produced later, such as element
+//! captures that are rendered at the end of a frame.
+
+use crate::error::{SolError, SolResult};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// An 8-bit, non-premultiplied RGBA image
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgbaImage {
+    width: u32,
+    height: u32,
+    /// Row-major pixel data, 4 bytes per pixel
+    data: Vec<u8>,
+}
+
+impl RgbaImage {
+    /// Create a transparent image
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            data: vec![0; width as usize * height as usize * 4],
+        }
+    }
+
+    /// Create an image from raw RGBA bytes
+    ///
+    /// Returns `None` if `data` is not exactly `width * height * 4` bytes.
+    pub fn from_raw(width: u32, height: u32, data: Vec<u8>) -> Option<Self> {
+        (data.len() == width as usize * height as usize * 4).then_some(Self {
+            width,
+            height,
+            data,
+        })
+    }
+
+    /// Decode a PNG image
+    pub fn from_png(bytes: &[u8]) -> SolResult<Self> {
+        let pixmap = tiny_skia::Pixmap::decode_png(bytes)
+            .map_err(|e| SolError::Image(format!("Failed to decode PNG: {}", e)))?;
+
+        let data = pixmap
+            .pixels()
+            .iter()
+            .flat_map(|p| {
+                let c = p.demultiply();
+                [c.red(), c.green(), c.blue(), c.alpha()]
+            })
+            .collect();
+
+        Ok(Self {
+            width: pixmap.width(),
+            height: pixmap.height(),
+            data,
+        })
+    }
+
+    /// Encode the image as PNG
+    pub fn to_png(&self) -> SolResult<Vec<u8>> {
+        let mut pixmap = tiny_skia::Pixmap::new(self.width, self.height)
+            .ok_or_else(|| SolError::Image("Cannot encode an empty image".to_string()))?;
+
+        for (dst, src) in pixmap
+            .pixels_mut()
+            .iter_mut()
+            .zip(self.data.chunks_exact(4))
+        {
+            *dst = tiny_skia::ColorU8::from_rgba(src[0], src[1], src[2], src[3]).premultiply();
+        }
+
+        pixmap
+            .encode_png()
+            .map_err(|e| SolError::Image(format!("Failed to encode PNG: {}", e)))
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Raw RGBA bytes
+    pub fn as_raw(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Consume the image and return its raw RGBA bytes
+    pub fn into_raw(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Get the RGBA value of a pixel
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        Some([
+            self.data[i],
+            self.data[i + 1],
+            self.data[i + 2],
+            self.data[i + 3],
+        ])
+    }
+
+    /// Set the RGBA value of a pixel; out-of-bounds writes are ignored
+    pub fn set_pixel(&mut self, x: u32, y: u32, rgba: [u8; 4]) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        self.data[i..i + 4].copy_from_slice(&rgba);
+    }
+}
+
+/// A shared slot for an image that becomes available after rendering
+///
+/// Clones share the same slot. A new handle starts out pending; once an image
+/// is delivered it stays available until [`request`](Self::request) asks for
+/// a fresh capture.
+#[derive(Debug, Clone)]
+pub struct ImageHandle {
+    inner: Rc<RefCell<ImageHandleState>>,
+}
+
+#[derive(Debug)]
+struct ImageHandleState {
+    image: Option<RgbaImage>,
+    pending: bool,
+}
+
+impl ImageHandle {
+    pub fn new() -> Self {
+        Self {
+            inner: Rc::new(RefCell::new(ImageHandleState {
+                image: None,
+                pending: true,
+            })),
+        }
+    }
+
+    /// Get a copy of the most recently delivered image
+    pub fn image(&self) -> Option<RgbaImage> {
+        self.inner.borrow().image.clone()
+    }
+
+    /// Check if an image has been delivered
+    pub fn is_ready(&self) -> bool {
+        self.inner.borrow().image.is_some()
+    }
+
+    /// Check if a new image has been requested but not delivered yet
+    pub fn is_pending(&self) -> bool {
+        self.inner.borrow().pending
+    }
+
+    /// Ask for a fresh image; the previous one stays available until then
+    pub fn request(&self) {
+        self.inner.borrow_mut().pending = true;
+    }
+
+    /// Deliver an image, completing any pending request
+    pub fn set(&self, image: RgbaImage) {
+        let mut state = self.inner.borrow_mut();
+        state.image = Some(image);
+        state.pending = false;
+    }
+}
+
+impl Default for ImageHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_raw_checks_length() {
+        assert!(RgbaImage::from_raw(2, 2, vec![0; 16]).is_some());
+        assert!(RgbaImage::from_raw(2, 2, vec![0; 15]).is_none());
+    }
+
+    #[test]
+    fn test_pixel_access() {
+        let mut image = RgbaImage::new(3, 2);
+        image.set_pixel(2, 1, [1, 2, 3, 4]);
+        image.set_pixel(3, 0, [9, 9, 9, 9]);
+        assert_eq!(image.pixel(2, 1), Some([1, 2, 3, 4]));
+        assert_eq!(image.pixel(0, 0), Some([0, 0, 0, 0]));
+        assert_eq!(image.pixel(3, 0), None);
+    }
+
+    #[test]
+    fn test_png_round_trip() {
+        let mut image = RgbaImage::new(2, 1);
+        image.set_pixel(0, 0, [255, 0, 0, 255]);
+        image.set_pixel(1, 0, [0, 128, 255, 255]);
+
+        let png = image.to_png().unwrap();
+        let decoded = RgbaImage::from_png(&png).unwrap();
+        assert_eq!(decoded, image);
+    }
+
+    #[test]
+    fn test_image_handle_shares_delivery() {
+        let handle = ImageHandle::new();
+        let clone = handle.clone();
+        assert!(handle.is_pending());
+        assert!(!handle.is_ready());
+
+        clone.set(RgbaImage::new(1, 1));
+        assert!(handle.is_ready());
+        assert!(!handle.is_pending());
+
+        handle.request();
+        assert!(clone.is_pending());
+        assert!(clone.image().is_some());
+    }
+
+    #[test]
+    fn test_invalid_png() {
+        assert!(matches!(
+            RgbaImage::from_png(b"not a png"),
+            Err(SolError::Image(_))
+        ));
+    }
+}
@@ -0,0 +1,216 @@
+//! Coalescing of high-frequency pointer motion
+//!
+//! A fast mouse or trackpad can report several moves between frames. The
+//! platform layer collapses each run of them into the latest position, and
+//! each run of pointer-lock deltas into their sum, before they reach the
+//! interaction system, so hover and drag handling runs once per run rather
+//! than once per sample.
+//!
+//! Elements that need every sample, such as drawing tools, read the
+//! positions a move stands for with [`pointer_samples`] from their
+//! `on_mouse_move` handler, or turn coalescing off with
+//! [`set_pointer_coalescing`].
+
+use crate::layer::InputEvent;
+use glam::Vec2;
+use std::cell::{Cell, RefCell};
+use std::time::Instant;
+
+thread_local! {
+    static COALESCING: Cell<bool> = const { Cell::new(true) };
+    /// Positions the event being handled stands for, oldest first
+    static POINTER_SAMPLES: RefCell<Vec<Vec2>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Whether runs of pointer motion are collapsed before they're handled
+pub fn pointer_coalescing() -> bool {
+    COALESCING.with(|coalescing| coalescing.get())
+}
+
+/// Collapse runs of pointer motion before they're handled, or deliver
+/// every sample as its own event
+///
+/// On by default.
+pub fn set_pointer_coalescing(enabled: bool) {
+    COALESCING.with(|coalescing| coalescing.set(enabled));
+}
+
+/// Every pointer position the move being handled stands for, oldest first
+///
+/// Ends with the move's own position. Empty while handling anything other
+/// than a `MouseMove`.
+pub fn pointer_samples() -> Vec<Vec2> {
+    POINTER_SAMPLES.with(|samples| samples.borrow().clone())
+}
+
+/// Set the samples returned by [`pointer_samples`] for the event about to
+/// be handled
+pub(crate) fn set_pointer_samples(samples: Vec<Vec2>) {
+    POINTER_SAMPLES.with(|current| *current.borrow_mut() = samples);
+}
+
+/// An input event left after coalescing
+#[derive(Debug, Clone)]
+pub struct CoalescedEvent {
+    pub event: InputEvent,
+    /// When the platform received the newest event this one replaces
+    pub timestamp: Instant,
+    /// Pointer positions of the moves this one replaces, oldest first
+    pub samples: Vec<Vec2>,
+}
+
+impl CoalescedEvent {
+    /// An event that replaces only itself
+    pub fn new(event: InputEvent, timestamp: Instant) -> Self {
+        let samples = match event {
+            InputEvent::MouseMove { position } => vec![position],
+            _ => Vec::new(),
+        };
+        Self {
+            event,
+            timestamp,
+            samples,
+        }
+    }
+}
+
+/// Collapse runs of pointer motion in `events`, keeping everything else in
+/// order
+///
+/// Consecutive `MouseMove`s become the last of them, with every position in
+/// the run as samples, and only the last `TabletMove` among them is kept.
+/// Consecutive `MouseDelta`s become one with their deltas summed. With
+/// coalescing off, every event is kept.
+pub fn coalesce_pointer_motion(
+    events: impl IntoIterator<Item = (InputEvent, Instant)>,
+) -> Vec<CoalescedEvent> {
+    let coalescing = pointer_coalescing();
+    let mut coalesced: Vec<CoalescedEvent> = Vec::new();
+    for (event, timestamp) in events {
+        if !coalescing {
+            coalesced.push(CoalescedEvent::new(event, timestamp));
+            continue;
+        }
+
+        match event {
+            InputEvent::MouseMove { position } => {
+                // A tablet event after the previous move belonged to it
+                let tablet_after_move = matches!(
+                    coalesced.as_slice(),
+                    [.., previous, last]
+                        if matches!(previous.event, InputEvent::MouseMove { .. })
+                            && matches!(last.event, InputEvent::TabletMove { .. })
+                );
+                if tablet_after_move {
+                    coalesced.pop();
+                }
+                match coalesced.last_mut() {
+                    Some(CoalescedEvent {
+                        event: InputEvent::MouseMove { position: latest },
+                        timestamp: latest_timestamp,
+                        samples,
+                    }) => {
+                        *latest = position;
+                        *latest_timestamp = timestamp;
+                        samples.push(position);
+                    }
+                    _ => coalesced.push(CoalescedEvent::new(event, timestamp)),
+                }
+            }
+            InputEvent::MouseDelta { delta } => match coalesced.last_mut() {
+                Some(CoalescedEvent {
+                    event: InputEvent::MouseDelta { delta: total },
+                    timestamp: latest_timestamp,
+                    ..
+                }) => {
+                    *total += delta;
+                    *latest_timestamp = timestamp;
+                }
+                _ => coalesced.push(CoalescedEvent::new(event, timestamp)),
+            },
+            _ => coalesced.push(CoalescedEvent::new(event, timestamp)),
+        }
+    }
+    coalesced
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::{MouseButton, TabletData};
+
+    fn moved(x: f32) -> InputEvent {
+        InputEvent::MouseMove {
+            position: Vec2::new(x, 0.0),
+        }
+    }
+
+    fn tablet(pressure: f32) -> InputEvent {
+        InputEvent::TabletMove {
+            position: Vec2::ZERO,
+            tablet: TabletData {
+                pressure,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn coalesce(events: Vec<InputEvent>) -> Vec<CoalescedEvent> {
+        let now = Instant::now();
+        coalesce_pointer_motion(events.into_iter().map(|event| (event, now)))
+    }
+
+    #[test]
+    fn test_moves_collapse_to_latest_position() {
+        let down = InputEvent::MouseDown {
+            position: Vec2::new(2.0, 0.0),
+            button: MouseButton::Left,
+            click_count: 1,
+        };
+        let events = coalesce(vec![moved(1.0), moved(2.0), down, moved(3.0), moved(4.0)]);
+
+        assert_eq!(events.len(), 3);
+        assert!(matches!(events[0].event, InputEvent::MouseMove { position } if position.x == 2.0));
+        assert_eq!(
+            events[0].samples,
+            vec![Vec2::new(1.0, 0.0), Vec2::new(2.0, 0.0)]
+        );
+        assert!(matches!(events[1].event, InputEvent::MouseDown { .. }));
+        assert!(events[1].samples.is_empty());
+        assert!(matches!(events[2].event, InputEvent::MouseMove { position } if position.x == 4.0));
+    }
+
+    #[test]
+    fn test_deltas_accumulate() {
+        let delta = |x| InputEvent::MouseDelta {
+            delta: Vec2::new(x, 1.0),
+        };
+        let events = coalesce(vec![delta(1.0), delta(2.0), delta(3.0)]);
+
+        assert_eq!(events.len(), 1);
+        assert!(
+            matches!(events[0].event, InputEvent::MouseDelta { delta } if delta == Vec2::new(6.0, 3.0))
+        );
+    }
+
+    #[test]
+    fn test_keeps_latest_tablet_data() {
+        let events = coalesce(vec![moved(1.0), tablet(0.2), moved(2.0), tablet(0.8)]);
+
+        assert_eq!(events.len(), 2);
+        assert!(matches!(events[0].event, InputEvent::MouseMove { position } if position.x == 2.0));
+        assert!(
+            matches!(events[1].event, InputEvent::TabletMove { tablet, .. } if tablet.pressure == 0.8)
+        );
+    }
+
+    #[test]
+    fn test_coalescing_can_be_turned_off() {
+        set_pointer_coalescing(false);
+        let events = coalesce(vec![moved(1.0), moved(2.0)]);
+        set_pointer_coalescing(true);
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].samples, vec![Vec2::new(2.0, 0.0)]);
+    }
+}
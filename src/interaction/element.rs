@@ -4,13 +4,16 @@ use super::ElementId;
 use crate::{
     color::Color,
     element::{Element, LayoutContext},
-    geometry::Rect,
+    geometry::{Corners, Edges, Rect},
     interaction::{
         events::EventHandlers,
-        registry::{get_element_state, register_element},
+        lifecycle::{LifecycleHandlers, register_lifecycle},
+        registry::{get_element_state, pop_style_state, push_style_state, register_element},
+        window_chrome::{WindowChrome, WindowEdge, register_window_chrome},
     },
     layer::{Key, Modifiers},
     render::{PaintContext, PaintQuad},
+    style::{Outline, StyleState},
 };
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -27,17 +30,33 @@ pub struct InteractiveElement<E: Element> {
     /// Event handlers
     handlers: Rc<RefCell<EventHandlers>>,
 
+    /// Lifecycle handlers, if any are set
+    lifecycle: Option<Rc<RefCell<LifecycleHandlers>>>,
+
     /// Visual feedback options
     hover_overlay: Option<Color>,
     press_overlay: Option<Color>,
     focus_overlay: Option<Color>,
 
+    /// Ring drawn outside the element while it has keyboard focus
+    focus_ring: Option<Outline>,
+    focus_ring_radius: f32,
+
     /// Whether this element is interactive
     enabled: bool,
 
     /// Whether this element can receive keyboard focus
     focusable: bool,
 
+    /// Window move or resize started by mouse downs on this element
+    window_chrome: Option<WindowChrome>,
+
+    /// Whether this element and its children are left out of hit testing
+    hit_test_disabled: bool,
+
+    /// How far the interactive area extends past each edge
+    hit_slop: Edges,
+
     /// Z-index offset for this element
     z_index: i32,
 
@@ -56,11 +75,17 @@ impl<E: Element> InteractiveElement<E> {
             element,
             id: ElementId::auto(),
             handlers: Rc::new(RefCell::new(EventHandlers::new())),
+            lifecycle: None,
             hover_overlay: None,
             press_overlay: None,
             focus_overlay: None,
+            focus_ring: Some(Outline::focus_ring()),
+            focus_ring_radius: 0.0,
             enabled: true,
             focusable: false,
+            window_chrome: None,
+            hit_test_disabled: false,
+            hit_slop: Edges::zero(),
             z_index: 0,
             node_id: None,
         }
@@ -99,6 +124,26 @@ impl<E: Element> InteractiveElement<E> {
         self
     }
 
+    /// Set the ring drawn outside the element while it has keyboard focus
+    ///
+    /// Focusable elements draw [`Outline::focus_ring`] by default.
+    pub fn focus_ring(mut self, outline: Outline) -> Self {
+        self.focus_ring = Some(outline);
+        self
+    }
+
+    /// Round the focus ring to follow the element's corner radius
+    pub fn focus_ring_radius(mut self, radius: f32) -> Self {
+        self.focus_ring_radius = radius;
+        self
+    }
+
+    /// Don't draw a focus ring, for elements that show focus another way
+    pub fn no_focus_ring(mut self) -> Self {
+        self.focus_ring = None;
+        self
+    }
+
     /// Set both hover and press overlays
     pub fn with_overlays(mut self, hover: Color, press: Color) -> Self {
         self.hover_overlay = Some(hover);
@@ -125,7 +170,29 @@ impl<E: Element> InteractiveElement<E> {
         self
     }
 
+    /// Leave this element and everything inside it out of hit testing
+    ///
+    /// For purely decorative content, such as watermarks or an FPS counter
+    /// drawn over the UI: clicks and hovers go to whatever is beneath it.
+    pub fn hit_test_disabled(mut self) -> Self {
+        self.hit_test_disabled = true;
+        self
+    }
+
+    /// Extend the area that responds to the mouse past the element's edges
+    ///
+    /// For small controls such as close buttons. The element paints at its
+    /// own size, and local positions in mouse events are measured from the
+    /// corner of the extended area.
+    pub fn hit_slop(mut self, edges: Edges) -> Self {
+        self.hit_slop = edges;
+        self
+    }
+
     /// Set the z-index offset for this element
+    ///
+    /// Affects both hit testing and paint order: the element draws above
+    /// siblings with a lower z-index.
     pub fn z_index(mut self, z_index: i32) -> Self {
         self.z_index = z_index;
         self
@@ -149,6 +216,40 @@ impl<E: Element> InteractiveElement<E> {
         self
     }
 
+    /// Set the right click handler (for context menus)
+    /// Handler receives: (position, local_position, modifiers)
+    pub fn on_right_click<F>(self, handler: F) -> Self
+    where
+        F: FnMut(glam::Vec2, glam::Vec2, crate::layer::Modifiers) + 'static,
+    {
+        self.handlers.borrow_mut().on_right_click = Some(Box::new(handler));
+        self
+    }
+
+    /// Set the middle click handler
+    /// Handler receives: (position, local_position, modifiers)
+    pub fn on_middle_click<F>(self, handler: F) -> Self
+    where
+        F: FnMut(glam::Vec2, glam::Vec2, crate::layer::Modifiers) + 'static,
+    {
+        self.handlers.borrow_mut().on_middle_click = Some(Box::new(handler));
+        self
+    }
+
+    /// Add a handler for clicks with `button`, such as the back and forward
+    /// side buttons
+    /// Handler receives: (position, local_position, modifiers)
+    pub fn on_mouse_button<F>(self, button: crate::layer::MouseButton, handler: F) -> Self
+    where
+        F: FnMut(glam::Vec2, glam::Vec2, crate::layer::Modifiers) + 'static,
+    {
+        self.handlers
+            .borrow_mut()
+            .on_mouse_button
+            .push((button, Box::new(handler)));
+        self
+    }
+
     /// Set the mouse enter handler
     pub fn on_mouse_enter<F>(self, handler: F) -> Self
     where
@@ -167,6 +268,32 @@ impl<E: Element> InteractiveElement<E> {
         self
     }
 
+    /// Set the hover start handler, called once the mouse has rested on the
+    /// element for `delay`
+    ///
+    /// Use this instead of `on_mouse_enter` for tooltips and previews so they
+    /// don't flash when the cursor merely passes over the element.
+    pub fn on_hover_start_after<F>(self, delay: std::time::Duration, handler: F) -> Self
+    where
+        F: FnMut() + 'static,
+    {
+        {
+            let mut handlers = self.handlers.borrow_mut();
+            handlers.hover_delay = Some(delay);
+            handlers.on_hover_start = Some(Box::new(handler));
+        }
+        self
+    }
+
+    /// Set the hover end handler, called when the mouse leaves after hover start
+    pub fn on_hover_end<F>(self, handler: F) -> Self
+    where
+        F: FnMut() + 'static,
+    {
+        self.handlers.borrow_mut().on_hover_end = Some(Box::new(handler));
+        self
+    }
+
     /// Set the mouse move handler
     pub fn on_mouse_move<F>(self, handler: F) -> Self
     where
@@ -203,6 +330,49 @@ impl<E: Element> InteractiveElement<E> {
         self
     }
 
+    /// Set the handler for mouse motion while the pointer is locked to
+    /// this element
+    /// Handler receives: (delta)
+    pub fn on_mouse_delta<F>(self, handler: F) -> Self
+    where
+        F: FnMut(glam::Vec2) + 'static,
+    {
+        self.handlers.borrow_mut().on_mouse_delta = Some(Box::new(handler));
+        self
+    }
+
+    /// Set the stylus handler for pressure, tilt and barrel button data
+    /// Handler receives: (tablet, position, local_position)
+    pub fn on_tablet_move<F>(self, handler: F) -> Self
+    where
+        F: FnMut(crate::layer::TabletData, glam::Vec2, glam::Vec2) + 'static,
+    {
+        self.handlers.borrow_mut().on_tablet_move = Some(Box::new(handler));
+        self
+    }
+
+    /// Lock the pointer while this element is pressed
+    ///
+    /// The cursor hides and stays put until the button is released, and
+    /// mouse motion goes to [`on_mouse_delta`](Self::on_mouse_delta) instead
+    /// of moving over other elements.
+    pub fn pointer_lock(self) -> Self {
+        self.handlers.borrow_mut().lock_pointer = true;
+        self
+    }
+
+    /// Play trackpad feedback when the element is clicked, like native
+    /// toggles and snapping controls on Force Touch trackpads
+    ///
+    /// Call [`haptics::perform`](crate::platform::haptics::perform) directly
+    /// for feedback that isn't tied to a click, like a drag snapping into
+    /// place.
+    #[cfg(target_os = "macos")]
+    pub fn haptic_on_click(self, pattern: crate::platform::haptics::Pattern) -> Self {
+        self.handlers.borrow_mut().haptic_on_click = Some(pattern);
+        self
+    }
+
     // --- Keyboard handlers ---
 
     /// Set the key down handler (element must be focusable)
@@ -243,6 +413,65 @@ impl<E: Element> InteractiveElement<E> {
         self
     }
 
+    // --- Lifecycle handlers ---
+
+    fn lifecycle_handlers(&mut self) -> std::cell::RefMut<'_, LifecycleHandlers> {
+        self.lifecycle
+            .get_or_insert_with(|| Rc::new(RefCell::new(LifecycleHandlers::new())))
+            .borrow_mut()
+    }
+
+    /// Set the handler called on the first frame this element is painted
+    ///
+    /// Lifecycle is tracked by ID, so give the element a stable one with
+    /// `.with_key()` or `.with_id()`. Elements skipped while painting, such
+    /// as those culled outside a scroll view, count as disappeared.
+    pub fn on_appear<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut() + 'static,
+    {
+        self.lifecycle_handlers().on_appear = Some(Box::new(handler));
+        self
+    }
+
+    /// Set the handler called on the first frame this element is no longer painted
+    pub fn on_disappear<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut() + 'static,
+    {
+        self.lifecycle_handlers().on_disappear = Some(Box::new(handler));
+        self
+    }
+
+    /// Set the handler called with the element's bounds when they change,
+    /// including when it first appears
+    pub fn on_bounds_change<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(Rect) + 'static,
+    {
+        self.lifecycle_handlers().on_bounds_change = Some(Box::new(handler));
+        self
+    }
+
+    // --- Window chrome ---
+
+    /// Move the window when this element is dragged, like a titlebar
+    ///
+    /// For windows with a hidden titlebar. Double-clicking zooms or minimizes
+    /// the window per the user's system setting. Child elements with their
+    /// own hit areas, such as buttons, still receive their clicks.
+    pub fn window_drag_region(mut self) -> Self {
+        self.window_chrome = Some(WindowChrome::Drag);
+        self
+    }
+
+    /// Resize the window when this element is dragged, as if it were the
+    /// given edge or corner of the window frame
+    pub fn window_resize_edge(mut self, edge: WindowEdge) -> Self {
+        self.window_chrome = Some(WindowChrome::Resize(edge));
+        self
+    }
+
     /// Get the element's ID
     pub fn element_id(&self) -> ElementId {
         self.id
@@ -263,12 +492,39 @@ impl<E: Element> Element for InteractiveElement<E> {
     }
 
     fn paint(&mut self, bounds: Rect, ctx: &mut PaintContext) {
-        // First, paint the wrapped element
+        // Nothing inside a disabled element is hit tested
+        let suppressed_hit_test = self
+            .hit_test_disabled
+            .then(|| ctx.hit_test_builder.clone())
+            .flatten();
+        if let Some(builder) = &suppressed_hit_test {
+            builder.borrow_mut().push_disabled();
+        }
+
+        if self.z_index != 0 {
+            ctx.draw_list.push_z_index(self.z_index);
+        }
+
+        // First, paint the wrapped element, styled for this element's state
+        let style_state = StyleState::from_interaction(
+            &get_element_state(self.id).unwrap_or_default(),
+            !self.enabled,
+        );
+        push_style_state(style_state);
         self.element.paint(bounds, ctx);
+        pop_style_state();
+
+        // Lifecycle is tracked whether or not the element is enabled
+        if let Some(lifecycle) = &self.lifecycle {
+            register_lifecycle(self.id, bounds, lifecycle.clone());
+        }
 
         // Register this element with the current registry
         if self.enabled {
             register_element(self.id, self.handlers.clone());
+            if let Some(chrome) = self.window_chrome {
+                register_window_chrome(self.id, chrome);
+            }
         }
 
         // Get current interaction state from registry
@@ -291,16 +547,30 @@ impl<E: Element> Element for InteractiveElement<E> {
                 // Paint overlay on top of the element
                 ctx.paint_quad(PaintQuad::filled(bounds, color));
             }
+
+            let focused = self.focusable && state.is_focused;
+            if let Some(ring) = self.focus_ring.filter(|_| focused) {
+                ctx.paint_outline(bounds, Corners::all(self.focus_ring_radius), ring);
+            }
+        }
+
+        if self.z_index != 0 {
+            ctx.draw_list.pop_z_index();
         }
 
         // Register for hit testing (focusable elements get focus on click)
         if self.enabled {
+            let hit_bounds = bounds.expand_edges(self.hit_slop);
             if self.focusable {
-                ctx.register_focusable(self.id, bounds, self.z_index);
+                ctx.register_focusable(self.id, hit_bounds, self.z_index);
             } else {
-                ctx.register_hit_test(self.id, bounds, self.z_index);
+                ctx.register_hit_test(self.id, hit_bounds, self.z_index);
             }
         }
+
+        if let Some(builder) = &suppressed_hit_test {
+            builder.borrow_mut().pop_disabled();
+        }
     }
 }
 
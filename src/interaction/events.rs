@@ -1,8 +1,14 @@
 //! Interaction event types and state
 
-use super::{DragDropEvent, ElementId, ShortcutId};
-use crate::layer::{ClickType, Key, Modifiers, MouseButton};
+use super::{
+    DragDropEvent, ElementId, ShortcutId,
+    pointer_lock::{lock_pointer, pointer_lock_owner, unlock_pointer},
+};
+use crate::layer::{ClickType, Key, Modifiers, MouseButton, ScrollPhase, TabletData};
+#[cfg(target_os = "macos")]
+use crate::platform::haptics;
 use glam::Vec2;
+use std::time::Duration;
 
 /// Events generated by the interaction system
 #[derive(Debug, Clone)]
@@ -15,6 +21,12 @@ pub enum InteractionEvent {
     /// Mouse left an element
     MouseLeave { element_id: ElementId },
 
+    /// Mouse rested on an element for its hover delay
+    HoverStart { element_id: ElementId },
+
+    /// Mouse left an element that had received `HoverStart`
+    HoverEnd { element_id: ElementId },
+
     /// Mouse moved over an element
     MouseMove {
         element_id: ElementId,
@@ -81,6 +93,29 @@ pub enum InteractionEvent {
     ScrollWheel {
         element_id: ElementId,
         delta: Vec2,
+        phase: ScrollPhase,
+        position: Vec2,
+        local_position: Vec2,
+        modifiers: Modifiers,
+    },
+
+    /// Trackpad pinch on an element
+    Magnify {
+        element_id: ElementId,
+        /// Relative scale change (0.1 = 10% larger)
+        magnification: f32,
+        position: Vec2,
+        local_position: Vec2,
+    },
+
+    /// Mouse moved while the pointer is locked to an element
+    MouseDelta { element_id: ElementId, delta: Vec2 },
+
+    /// Stylus data from a graphics tablet over an element, or while it's
+    /// pressed
+    TabletMove {
+        element_id: ElementId,
+        tablet: TabletData,
         position: Vec2,
         local_position: Vec2,
     },
@@ -129,6 +164,34 @@ pub enum InteractionEvent {
     DragDrop(DragDropEvent),
 }
 
+impl InteractionEvent {
+    /// The element the event is aimed at, if any
+    pub fn element_id(&self) -> Option<ElementId> {
+        match self {
+            InteractionEvent::MouseEnter { element_id }
+            | InteractionEvent::MouseLeave { element_id }
+            | InteractionEvent::HoverStart { element_id }
+            | InteractionEvent::HoverEnd { element_id }
+            | InteractionEvent::MouseMove { element_id, .. }
+            | InteractionEvent::MouseDown { element_id, .. }
+            | InteractionEvent::MouseUp { element_id, .. }
+            | InteractionEvent::Click { element_id, .. }
+            | InteractionEvent::DoubleClick { element_id, .. }
+            | InteractionEvent::TripleClick { element_id, .. }
+            | InteractionEvent::RightClick { element_id, .. }
+            | InteractionEvent::ScrollWheel { element_id, .. }
+            | InteractionEvent::Magnify { element_id, .. }
+            | InteractionEvent::MouseDelta { element_id, .. }
+            | InteractionEvent::TabletMove { element_id, .. }
+            | InteractionEvent::KeyDown { element_id, .. }
+            | InteractionEvent::KeyUp { element_id, .. }
+            | InteractionEvent::FocusIn { element_id }
+            | InteractionEvent::FocusOut { element_id } => Some(*element_id),
+            InteractionEvent::ShortcutTriggered { .. } | InteractionEvent::DragDrop(_) => None,
+        }
+    }
+}
+
 /// Current interaction state of an element
 #[derive(Debug, Clone, Default)]
 pub struct InteractionState {
@@ -157,6 +220,12 @@ pub trait InteractionHandler {
     /// Called when mouse leaves the element
     fn on_mouse_leave(&mut self) {}
 
+    /// Called when the mouse has rested on the element for its hover delay
+    fn on_hover_start(&mut self) {}
+
+    /// Called when the mouse leaves the element after `on_hover_start`
+    fn on_hover_end(&mut self) {}
+
     /// Called when mouse moves over the element
     fn on_mouse_move(&mut self, _position: Vec2, _local_position: Vec2) {}
 
@@ -215,6 +284,9 @@ pub trait InteractionHandler {
     /// Called when element is right-clicked (context menu trigger)
     fn on_right_click(&mut self, _position: Vec2, _local_position: Vec2, _modifiers: Modifiers) {}
 
+    /// Called when element is middle-clicked
+    fn on_middle_click(&mut self, _position: Vec2, _local_position: Vec2, _modifiers: Modifiers) {}
+
     // Keyboard handlers
     /// Called when a key is pressed while element has focus
     fn on_key_down(
@@ -237,12 +309,34 @@ pub trait InteractionHandler {
     fn on_focus_out(&mut self) {}
 }
 
+/// Handler for a click with a particular button: (position, local_position, modifiers)
+pub type ButtonClickHandler = Box<dyn FnMut(Vec2, Vec2, Modifiers)>;
+
+/// Scroll handler: (delta, position, local_position)
+pub type ScrollHandler = Box<dyn FnMut(Vec2, Vec2, Vec2)>;
+
+/// Scroll handler that also sees modifiers: (delta, position, local_position, modifiers)
+pub type ScrollWithModifiersHandler = Box<dyn FnMut(Vec2, Vec2, Vec2, Modifiers)>;
+
+/// Scroll handler with the gesture phase: (delta, phase, position, modifiers)
+pub type ScrollGestureHandler = Box<dyn FnMut(Vec2, ScrollPhase, Vec2, Modifiers)>;
+
+/// Pinch handler: (magnification, position, local_position)
+pub type MagnifyHandler = Box<dyn FnMut(f32, Vec2, Vec2)>;
+
+/// Stylus handler: (tablet, position, local_position)
+pub type TabletHandler = Box<dyn FnMut(TabletData, Vec2, Vec2)>;
+
 /// Event handler closures for interactive elements
 pub struct EventHandlers {
     // Mouse handlers
     pub on_mouse_enter: Option<Box<dyn FnMut()>>,
     pub on_mouse_leave: Option<Box<dyn FnMut()>>,
     pub on_mouse_move: Option<Box<dyn FnMut(Vec2, Vec2)>>,
+    /// How long the mouse must rest on the element before `on_hover_start`
+    pub hover_delay: Option<Duration>,
+    pub on_hover_start: Option<Box<dyn FnMut()>>,
+    pub on_hover_end: Option<Box<dyn FnMut()>>,
     /// Handler for mouse down: (button, position, local_position, modifiers, click_count)
     pub on_mouse_down: Option<Box<dyn FnMut(MouseButton, Vec2, Vec2, Modifiers, u32)>>,
     /// Handler for mouse up: (button, position, local_position, modifiers)
@@ -254,8 +348,28 @@ pub struct EventHandlers {
     /// Handler for triple click: (button, position, local_position, modifiers)
     pub on_triple_click: Option<Box<dyn FnMut(MouseButton, Vec2, Vec2, Modifiers)>>,
     /// Handler for right click: (position, local_position, modifiers)
-    pub on_right_click: Option<Box<dyn FnMut(Vec2, Vec2, Modifiers)>>,
-    pub on_scroll: Option<Box<dyn FnMut(Vec2, Vec2, Vec2)>>,
+    pub on_right_click: Option<ButtonClickHandler>,
+    /// Handler for middle click: (position, local_position, modifiers)
+    pub on_middle_click: Option<ButtonClickHandler>,
+    /// Handlers for clicks with specific buttons: (position, local_position, modifiers)
+    pub on_mouse_button: Vec<(MouseButton, ButtonClickHandler)>,
+    /// Handler for scroll: (delta, position, local_position)
+    pub on_scroll: Option<ScrollHandler>,
+    /// Handler for scroll: (delta, position, local_position, modifiers)
+    pub on_scroll_with_modifiers: Option<ScrollWithModifiersHandler>,
+    /// Handler for scroll with its gesture phase: (delta, phase, position, modifiers)
+    pub on_scroll_gesture: Option<ScrollGestureHandler>,
+    /// Handler for pinch: (magnification, position, local_position)
+    pub on_magnify: Option<MagnifyHandler>,
+    /// Handler for mouse motion while the pointer is locked: (delta)
+    pub on_mouse_delta: Option<Box<dyn FnMut(Vec2)>>,
+    /// Handler for stylus input: (tablet, position, local_position)
+    pub on_tablet_move: Option<TabletHandler>,
+    /// Whether pressing the element locks the pointer until it's released
+    pub lock_pointer: bool,
+    /// Trackpad feedback played when the element is clicked
+    #[cfg(target_os = "macos")]
+    pub haptic_on_click: Option<haptics::Pattern>,
     // Keyboard handlers
     pub on_key_down: Option<Box<dyn FnMut(Key, Modifiers, Option<char>, bool)>>,
     pub on_key_up: Option<Box<dyn FnMut(Key, Modifiers)>>,
@@ -270,13 +384,26 @@ impl EventHandlers {
             on_mouse_enter: None,
             on_mouse_leave: None,
             on_mouse_move: None,
+            hover_delay: None,
+            on_hover_start: None,
+            on_hover_end: None,
             on_mouse_down: None,
             on_mouse_up: None,
             on_click: None,
             on_double_click: None,
             on_triple_click: None,
             on_right_click: None,
+            on_middle_click: None,
+            on_mouse_button: Vec::new(),
             on_scroll: None,
+            on_scroll_with_modifiers: None,
+            on_scroll_gesture: None,
+            on_magnify: None,
+            on_mouse_delta: None,
+            on_tablet_move: None,
+            lock_pointer: false,
+            #[cfg(target_os = "macos")]
+            haptic_on_click: None,
             on_key_down: None,
             on_key_up: None,
             on_focus_in: None,
@@ -311,6 +438,26 @@ impl EventHandlers {
         self
     }
 
+    /// Set the hover start handler, called once the mouse has rested on the
+    /// element for `delay`
+    pub fn on_hover_start_after<F>(mut self, delay: Duration, handler: F) -> Self
+    where
+        F: FnMut() + 'static,
+    {
+        self.hover_delay = Some(delay);
+        self.on_hover_start = Some(Box::new(handler));
+        self
+    }
+
+    /// Set the hover end handler, called when the mouse leaves after hover start
+    pub fn on_hover_end<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut() + 'static,
+    {
+        self.on_hover_end = Some(Box::new(handler));
+        self
+    }
+
     /// Set the mouse down handler
     /// Handler receives: (button, position, local_position, modifiers, click_count)
     pub fn on_mouse_down<F>(mut self, handler: F) -> Self
@@ -341,6 +488,13 @@ impl EventHandlers {
         self
     }
 
+    /// Play trackpad feedback when the element is clicked
+    #[cfg(target_os = "macos")]
+    pub fn haptic_on_click(mut self, pattern: haptics::Pattern) -> Self {
+        self.haptic_on_click = Some(pattern);
+        self
+    }
+
     /// Set the double click handler
     /// Handler receives: (button, position, local_position, modifiers)
     pub fn on_double_click<F>(mut self, handler: F) -> Self
@@ -371,7 +525,29 @@ impl EventHandlers {
         self
     }
 
+    /// Set the middle click handler
+    /// Handler receives: (position, local_position, modifiers)
+    pub fn on_middle_click<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(Vec2, Vec2, Modifiers) + 'static,
+    {
+        self.on_middle_click = Some(Box::new(handler));
+        self
+    }
+
+    /// Add a handler for clicks with `button`, such as
+    /// [`MouseButton::Back`] for navigation
+    /// Handler receives: (position, local_position, modifiers)
+    pub fn on_mouse_button<F>(mut self, button: MouseButton, handler: F) -> Self
+    where
+        F: FnMut(Vec2, Vec2, Modifiers) + 'static,
+    {
+        self.on_mouse_button.push((button, Box::new(handler)));
+        self
+    }
+
     /// Set the scroll handler
+    /// Handler receives: (delta, position, local_position)
     pub fn on_scroll<F>(mut self, handler: F) -> Self
     where
         F: FnMut(Vec2, Vec2, Vec2) + 'static,
@@ -380,6 +556,59 @@ impl EventHandlers {
         self
     }
 
+    /// Set the scroll handler for elements that react to held modifiers,
+    /// e.g. zooming on Cmd+scroll
+    /// Handler receives: (delta, position, local_position, modifiers)
+    pub fn on_scroll_with_modifiers<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(Vec2, Vec2, Vec2, Modifiers) + 'static,
+    {
+        self.on_scroll_with_modifiers = Some(Box::new(handler));
+        self
+    }
+
+    /// Set the scroll handler for elements that follow trackpad gestures,
+    /// e.g. to run their own momentum after the fingers lift
+    /// Handler receives: (delta, phase, position, modifiers)
+    pub fn on_scroll_gesture<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(Vec2, ScrollPhase, Vec2, Modifiers) + 'static,
+    {
+        self.on_scroll_gesture = Some(Box::new(handler));
+        self
+    }
+
+    /// Set the trackpad pinch handler
+    /// Handler receives: (magnification, position, local_position)
+    pub fn on_magnify<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(f32, Vec2, Vec2) + 'static,
+    {
+        self.on_magnify = Some(Box::new(handler));
+        self
+    }
+
+    /// Set the handler for mouse motion while the pointer is locked to the
+    /// element
+    /// Handler receives: (delta)
+    pub fn on_mouse_delta<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(Vec2) + 'static,
+    {
+        self.on_mouse_delta = Some(Box::new(handler));
+        self
+    }
+
+    /// Set the stylus handler for pressure, tilt and barrel button data
+    /// Handler receives: (tablet, position, local_position)
+    pub fn on_tablet_move<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(TabletData, Vec2, Vec2) + 'static,
+    {
+        self.on_tablet_move = Some(Box::new(handler));
+        self
+    }
+
     /// Set the key down handler
     pub fn on_key_down<F>(mut self, handler: F) -> Self
     where
@@ -429,6 +658,16 @@ impl EventHandlers {
                     handler();
                 }
             }
+            InteractionEvent::HoverStart { .. } => {
+                if let Some(handler) = &mut self.on_hover_start {
+                    handler();
+                }
+            }
+            InteractionEvent::HoverEnd { .. } => {
+                if let Some(handler) = &mut self.on_hover_end {
+                    handler();
+                }
+            }
             InteractionEvent::MouseMove {
                 position,
                 local_position,
@@ -439,24 +678,30 @@ impl EventHandlers {
                 }
             }
             InteractionEvent::MouseDown {
+                element_id,
                 button,
                 position,
                 local_position,
                 modifiers,
                 click_count,
-                ..
             } => {
+                if self.lock_pointer {
+                    lock_pointer(*element_id);
+                }
                 if let Some(handler) = &mut self.on_mouse_down {
                     handler(*button, *position, *local_position, *modifiers, *click_count);
                 }
             }
             InteractionEvent::MouseUp {
+                element_id,
                 button,
                 position,
                 local_position,
                 modifiers,
-                ..
             } => {
+                if self.lock_pointer && pointer_lock_owner() == Some(*element_id) {
+                    unlock_pointer();
+                }
                 if let Some(handler) = &mut self.on_mouse_up {
                     handler(*button, *position, *local_position, *modifiers);
                 }
@@ -469,9 +714,23 @@ impl EventHandlers {
                 modifiers,
                 ..
             } => {
+                #[cfg(target_os = "macos")]
+                if let Some(pattern) = self.haptic_on_click {
+                    haptics::perform(pattern);
+                }
                 if let Some(handler) = &mut self.on_click {
                     handler(*button, *click_type, *position, *local_position, *modifiers);
                 }
+                if *button == MouseButton::Middle
+                    && let Some(handler) = &mut self.on_middle_click
+                {
+                    handler(*position, *local_position, *modifiers);
+                }
+                for (handler_button, handler) in &mut self.on_mouse_button {
+                    if handler_button == button {
+                        handler(*position, *local_position, *modifiers);
+                    }
+                }
             }
             InteractionEvent::DoubleClick {
                 button,
@@ -507,13 +766,46 @@ impl EventHandlers {
             }
             InteractionEvent::ScrollWheel {
                 delta,
+                phase,
                 position,
                 local_position,
+                modifiers,
                 ..
             } => {
                 if let Some(handler) = &mut self.on_scroll {
                     handler(*delta, *position, *local_position);
                 }
+                if let Some(handler) = &mut self.on_scroll_with_modifiers {
+                    handler(*delta, *position, *local_position, *modifiers);
+                }
+                if let Some(handler) = &mut self.on_scroll_gesture {
+                    handler(*delta, *phase, *position, *modifiers);
+                }
+            }
+            InteractionEvent::Magnify {
+                magnification,
+                position,
+                local_position,
+                ..
+            } => {
+                if let Some(handler) = &mut self.on_magnify {
+                    handler(*magnification, *position, *local_position);
+                }
+            }
+            InteractionEvent::MouseDelta { delta, .. } => {
+                if let Some(handler) = &mut self.on_mouse_delta {
+                    handler(*delta);
+                }
+            }
+            InteractionEvent::TabletMove {
+                tablet,
+                position,
+                local_position,
+                ..
+            } => {
+                if let Some(handler) = &mut self.on_tablet_move {
+                    handler(*tablet, *position, *local_position);
+                }
             }
             InteractionEvent::KeyDown {
                 key,
@@ -556,3 +848,18 @@ impl Default for EventHandlers {
         Self::new()
     }
 }
+
+#[cfg(all(test, target_os = "macos"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_haptic_on_click() {
+        assert_eq!(EventHandlers::new().haptic_on_click, None);
+        let handlers = EventHandlers::new().haptic_on_click(haptics::Pattern::LevelChange);
+        assert_eq!(
+            handlers.haptic_on_click,
+            Some(haptics::Pattern::LevelChange)
+        );
+    }
+}
@@ -2,6 +2,38 @@
 
 use super::ElementId;
 use crate::geometry::Rect;
+use glam::Vec2;
+use std::cell::Cell;
+
+thread_local! {
+    static MIN_TARGET_SIZE: Cell<f32> = const { Cell::new(0.0) };
+}
+
+/// Smallest hit target, in points, for elements on this thread
+pub fn min_hit_target_size() -> f32 {
+    MIN_TARGET_SIZE.with(|size| size.get())
+}
+
+/// Grow hit targets narrower or shorter than `size` points around their
+/// centers, e.g. 24.0 so small close buttons are easy to click
+///
+/// Applies as elements register for hit testing; painting is unchanged. The
+/// default of 0.0 keeps targets at their painted size.
+pub fn set_min_hit_target_size(size: f32) {
+    MIN_TARGET_SIZE.with(|min| min.set(size.max(0.0)));
+}
+
+/// `bounds` grown around its center to at least `min_size` on each side
+///
+/// Empty bounds stay empty, so elements with nothing painted don't become
+/// clickable.
+pub(crate) fn grow_to_min_size(bounds: Rect, min_size: f32) -> Rect {
+    if bounds.size.x <= 0.0 || bounds.size.y <= 0.0 {
+        return bounds;
+    }
+    let growth = ((Vec2::splat(min_size) - bounds.size) / 2.0).max(Vec2::ZERO);
+    bounds.expand_by(growth)
+}
 
 /// Entry in the hit test list
 #[derive(Debug, Clone)]
@@ -20,6 +52,10 @@ pub struct HitTestEntry {
 
     /// Whether this element can receive keyboard focus
     pub focusable: bool,
+
+    /// How many screen points one of the element's own units covers, e.g.
+    /// 2.0 inside a canvas zoomed to 200%
+    pub scale: f32,
 }
 
 impl HitTestEntry {
@@ -30,6 +66,7 @@ impl HitTestEntry {
             z_index,
             layer_index,
             focusable: false,
+            scale: 1.0,
         }
     }
 
@@ -37,6 +74,17 @@ impl HitTestEntry {
         self.focusable = focusable;
         self
     }
+
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// A screen position relative to the element's top-left corner, in the
+    /// element's own units
+    pub fn local_position(&self, position: Vec2) -> Vec2 {
+        (position - self.bounds.pos) / self.scale
+    }
 }
 
 /// Result of a hit test
@@ -60,6 +108,11 @@ pub struct HitTestBuilder {
     entries: Vec<HitTestEntry>,
     current_z_base: i32,
     layer_index: usize,
+    /// Depth of nested subtrees whose entries are dropped
+    disabled_depth: usize,
+    /// Unclipped screen bounds of painted elements, in paint order, for
+    /// scrolling them into view
+    scroll_targets: Vec<(ElementId, Rect)>,
 }
 
 impl HitTestBuilder {
@@ -69,6 +122,8 @@ impl HitTestBuilder {
             entries: Vec::new(),
             current_z_base: z_base,
             layer_index,
+            disabled_depth: 0,
+            scroll_targets: Vec::new(),
         }
     }
 
@@ -78,32 +133,55 @@ impl HitTestBuilder {
             entries: Vec::new(),
             current_z_base: 0,
             layer_index: 0,
+            disabled_depth: 0,
+            scroll_targets: Vec::new(),
         }
     }
 
     /// Add a hit test entry
     pub fn add_entry(&mut self, element_id: ElementId, bounds: Rect, relative_z: i32) {
-        let entry = HitTestEntry::new(
-            element_id,
-            bounds,
-            self.current_z_base + relative_z,
-            self.layer_index,
-        );
-        self.entries.push(entry);
+        self.add_scaled_entry(element_id, bounds, relative_z, 1.0, false);
     }
 
     /// Add a focusable hit test entry
     pub fn add_focusable_entry(&mut self, element_id: ElementId, bounds: Rect, relative_z: i32) {
+        self.add_scaled_entry(element_id, bounds, relative_z, 1.0, true);
+    }
+
+    /// Add an entry for an element painted under a scaling transform, so its
+    /// handlers get local positions in its own units
+    pub fn add_scaled_entry(
+        &mut self,
+        element_id: ElementId,
+        bounds: Rect,
+        relative_z: i32,
+        scale: f32,
+        focusable: bool,
+    ) {
+        if self.is_disabled() {
+            return;
+        }
         let entry = HitTestEntry::new(
             element_id,
             bounds,
             self.current_z_base + relative_z,
             self.layer_index,
         )
-        .with_focusable(true);
+        .with_focusable(focusable)
+        .with_scale(scale);
         self.entries.push(entry);
     }
 
+    /// Record where an element is, even if it's clipped out of view
+    pub fn add_scroll_target(&mut self, element_id: ElementId, bounds: Rect) {
+        self.scroll_targets.push((element_id, bounds));
+    }
+
+    /// Elements recorded with `add_scroll_target`, in paint order
+    pub fn scroll_targets(&self) -> &[(ElementId, Rect)] {
+        &self.scroll_targets
+    }
+
     /// Push a new z-index context (for nested elements)
     pub fn push_z_context(&mut self, z_offset: i32) {
         self.current_z_base += z_offset;
@@ -114,6 +192,24 @@ impl HitTestBuilder {
         self.current_z_base -= z_offset;
     }
 
+    /// Stop adding entries until the matching `pop_disabled`
+    ///
+    /// Used for decorative subtrees that must never catch the mouse, so
+    /// events fall through to whatever is beneath them.
+    pub fn push_disabled(&mut self) {
+        self.disabled_depth += 1;
+    }
+
+    /// End a subtree started with `push_disabled`
+    pub fn pop_disabled(&mut self) {
+        self.disabled_depth = self.disabled_depth.saturating_sub(1);
+    }
+
+    /// Check if entries are currently being dropped
+    pub fn is_disabled(&self) -> bool {
+        self.disabled_depth > 0
+    }
+
     /// Build the final sorted hit test list
     pub fn build(&mut self) -> Vec<HitTestEntry> {
         // Sort by z-index in descending order (highest z-index first)
@@ -134,6 +230,7 @@ impl HitTestBuilder {
     /// Clear all entries
     pub fn clear(&mut self) {
         self.entries.clear();
+        self.scroll_targets.clear();
     }
 }
 
@@ -152,3 +249,60 @@ pub trait HitTestable {
         0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_subtree_is_skipped() {
+        let mut builder = HitTestBuilder::default_for_testing();
+        let bounds = Rect::new(0.0, 0.0, 10.0, 10.0);
+
+        builder.add_entry(ElementId::new(1), bounds, 0);
+        builder.push_disabled();
+        builder.add_entry(ElementId::new(2), bounds, 0);
+        builder.push_disabled();
+        builder.add_focusable_entry(ElementId::new(3), bounds, 0);
+        builder.pop_disabled();
+        builder.add_entry(ElementId::new(4), bounds, 0);
+        builder.pop_disabled();
+        builder.add_entry(ElementId::new(5), bounds, 0);
+
+        let ids: Vec<_> = builder.build().iter().map(|e| e.element_id).collect();
+        assert_eq!(ids, vec![ElementId::new(1), ElementId::new(5)]);
+    }
+
+    #[test]
+    fn test_scaled_entry_local_position() {
+        let mut builder = HitTestBuilder::default_for_testing();
+        builder.add_scaled_entry(
+            ElementId::new(1),
+            Rect::new(100.0, 50.0, 40.0, 40.0),
+            0,
+            2.0,
+            false,
+        );
+        builder.add_entry(ElementId::new(2), Rect::new(100.0, 50.0, 40.0, 40.0), 0);
+
+        let entries = builder.build();
+        let position = Vec2::new(120.0, 70.0);
+        assert_eq!(entries[0].local_position(position), Vec2::new(10.0, 10.0));
+        assert_eq!(entries[1].local_position(position), Vec2::new(20.0, 20.0));
+    }
+
+    #[test]
+    fn test_grow_to_min_size() {
+        let small = Rect::new(10.0, 10.0, 12.0, 30.0);
+        assert_eq!(
+            grow_to_min_size(small, 24.0),
+            Rect::new(4.0, 10.0, 24.0, 30.0)
+        );
+
+        let large = Rect::new(0.0, 0.0, 40.0, 40.0);
+        assert_eq!(grow_to_min_size(large, 24.0), large);
+
+        let empty = Rect::new(5.0, 5.0, 0.0, 10.0);
+        assert_eq!(grow_to_min_size(empty, 24.0), empty);
+    }
+}
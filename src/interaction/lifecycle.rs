@@ -0,0 +1,239 @@
+//! Element lifecycle callbacks: appear, disappear and bounds changes
+//!
+//! Elements are rebuilt every frame, so lifecycle is tracked by [`ElementId`]:
+//! an element appears on the first frame it paints with an ID, disappears on
+//! the first full frame it doesn't, and its bounds change whenever they differ
+//! from the previous frame. Elements need a stable ID (e.g. from `with_key`)
+//! for this to be meaningful.
+
+use super::ElementId;
+use crate::geometry::Rect;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Callbacks for an element's lifecycle
+#[derive(Default)]
+pub struct LifecycleHandlers {
+    /// Called on the first frame the element is painted
+    pub on_appear: Option<Box<dyn FnMut()>>,
+    /// Called on the first frame the element is no longer painted
+    pub on_disappear: Option<Box<dyn FnMut()>>,
+    /// Called with the new bounds when they change, including on appear
+    pub on_bounds_change: Option<Box<dyn FnMut(Rect)>>,
+}
+
+impl LifecycleHandlers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the appear handler
+    pub fn on_appear<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut() + 'static,
+    {
+        self.on_appear = Some(Box::new(handler));
+        self
+    }
+
+    /// Set the disappear handler
+    pub fn on_disappear<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut() + 'static,
+    {
+        self.on_disappear = Some(Box::new(handler));
+        self
+    }
+
+    /// Set the bounds change handler
+    pub fn on_bounds_change<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(Rect) + 'static,
+    {
+        self.on_bounds_change = Some(Box::new(handler));
+        self
+    }
+
+    /// Check if any handler is set
+    pub fn is_empty(&self) -> bool {
+        self.on_appear.is_none() && self.on_disappear.is_none() && self.on_bounds_change.is_none()
+    }
+}
+
+/// A lifecycle change found at the end of a frame
+enum LifecycleEvent {
+    Appear,
+    Disappear,
+    BoundsChange(Rect),
+}
+
+type Tracked = (Rect, Rc<RefCell<LifecycleHandlers>>);
+
+/// Tracks which elements were painted last frame to find lifecycle changes
+#[derive(Default)]
+pub struct LifecycleTracker {
+    previous: HashMap<ElementId, Tracked>,
+    current: HashMap<ElementId, Tracked>,
+}
+
+impl LifecycleTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that an element painted at `bounds` this frame
+    pub fn register(
+        &mut self,
+        id: ElementId,
+        bounds: Rect,
+        handlers: Rc<RefCell<LifecycleHandlers>>,
+    ) {
+        self.current.insert(id, (bounds, handlers));
+    }
+
+    /// Compare this frame's elements with the last frame's and call handlers
+    ///
+    /// Disappear handlers run first, using the handlers from the element's
+    /// last frame, then appear and bounds change handlers.
+    pub fn finish_frame(&mut self) {
+        let mut events = Vec::new();
+        for (id, (_, handlers)) in &self.previous {
+            if !self.current.contains_key(id) {
+                events.push((handlers.clone(), LifecycleEvent::Disappear));
+            }
+        }
+        for (id, (bounds, handlers)) in &self.current {
+            match self.previous.get(id) {
+                None => {
+                    events.push((handlers.clone(), LifecycleEvent::Appear));
+                    events.push((handlers.clone(), LifecycleEvent::BoundsChange(*bounds)));
+                }
+                Some((previous, _)) if previous != bounds => {
+                    events.push((handlers.clone(), LifecycleEvent::BoundsChange(*bounds)));
+                }
+                Some(_) => {}
+            }
+        }
+        self.previous = std::mem::take(&mut self.current);
+
+        for (handlers, event) in events {
+            let mut handlers = handlers.borrow_mut();
+            match event {
+                LifecycleEvent::Appear => {
+                    if let Some(handler) = handlers.on_appear.as_mut() {
+                        handler();
+                    }
+                }
+                LifecycleEvent::Disappear => {
+                    if let Some(handler) = handlers.on_disappear.as_mut() {
+                        handler();
+                    }
+                }
+                LifecycleEvent::BoundsChange(bounds) => {
+                    if let Some(handler) = handlers.on_bounds_change.as_mut() {
+                        handler(bounds);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Check if an element was painted last frame
+    pub fn is_mounted(&self, id: ElementId) -> bool {
+        self.previous.contains_key(&id)
+    }
+}
+
+thread_local! {
+    /// Thread-local lifecycle tracker for the current paint phase
+    static CURRENT_TRACKER: RefCell<Option<Rc<RefCell<LifecycleTracker>>>> = const { RefCell::new(None) };
+}
+
+/// Set the current lifecycle tracker for this thread
+pub fn set_current_lifecycle(tracker: Rc<RefCell<LifecycleTracker>>) {
+    CURRENT_TRACKER.with(|t| {
+        *t.borrow_mut() = Some(tracker);
+    });
+}
+
+/// Clear the current lifecycle tracker
+pub fn clear_current_lifecycle() {
+    CURRENT_TRACKER.with(|t| {
+        *t.borrow_mut() = None;
+    });
+}
+
+/// Record an element's bounds with the current lifecycle tracker
+pub fn register_lifecycle(id: ElementId, bounds: Rect, handlers: Rc<RefCell<LifecycleHandlers>>) {
+    CURRENT_TRACKER.with(|t| {
+        if let Some(tracker) = t.borrow().as_ref() {
+            tracker.borrow_mut().register(id, bounds, handlers);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[derive(Default)]
+    struct Counts {
+        appear: Cell<u32>,
+        disappear: Cell<u32>,
+        bounds: RefCell<Vec<Rect>>,
+    }
+
+    fn handlers(counts: &Rc<Counts>) -> Rc<RefCell<LifecycleHandlers>> {
+        let (a, d, b) = (counts.clone(), counts.clone(), counts.clone());
+        Rc::new(RefCell::new(
+            LifecycleHandlers::new()
+                .on_appear(move || a.appear.set(a.appear.get() + 1))
+                .on_disappear(move || d.disappear.set(d.disappear.get() + 1))
+                .on_bounds_change(move |bounds| b.bounds.borrow_mut().push(bounds)),
+        ))
+    }
+
+    #[test]
+    fn test_lifecycle_appear_and_disappear() {
+        let counts = Rc::new(Counts::default());
+        let id = ElementId::stable("lifecycle:test");
+        let bounds = Rect::new(0.0, 0.0, 10.0, 10.0);
+        let mut tracker = LifecycleTracker::new();
+
+        tracker.register(id, bounds, handlers(&counts));
+        tracker.finish_frame();
+        assert_eq!(counts.appear.get(), 1);
+        assert_eq!(*counts.bounds.borrow(), vec![bounds]);
+        assert!(tracker.is_mounted(id));
+
+        // Still painted with the same bounds: nothing to report
+        tracker.register(id, bounds, handlers(&counts));
+        tracker.finish_frame();
+        assert_eq!(counts.appear.get(), 1);
+        assert_eq!(counts.bounds.borrow().len(), 1);
+
+        tracker.finish_frame();
+        assert_eq!(counts.disappear.get(), 1);
+        assert!(!tracker.is_mounted(id));
+    }
+
+    #[test]
+    fn test_lifecycle_bounds_change() {
+        let counts = Rc::new(Counts::default());
+        let id = ElementId::stable("lifecycle:resize");
+        let mut tracker = LifecycleTracker::new();
+
+        tracker.register(id, Rect::new(0.0, 0.0, 10.0, 10.0), handlers(&counts));
+        tracker.finish_frame();
+        tracker.register(id, Rect::new(0.0, 0.0, 20.0, 10.0), handlers(&counts));
+        tracker.finish_frame();
+
+        assert_eq!(counts.appear.get(), 1);
+        assert_eq!(
+            counts.bounds.borrow().last(),
+            Some(&Rect::new(0.0, 0.0, 20.0, 10.0))
+        );
+    }
+}
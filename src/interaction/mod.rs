@@ -1,31 +1,54 @@
 //! Interaction system for handling mouse and keyboard events with z-order based hit testing
 
 use crate::{
+    clock::frame_time,
     geometry::Point,
-    layer::{ClickType, InputEvent, Key, Modifiers, MouseButton},
+    layer::{ClickType, InputEvent, Key, Modifiers, MouseButton, ScrollPhase, TabletData},
 };
 use glam::Vec2;
 use std::collections::HashMap;
+use std::time::Duration;
 
+pub mod coalesce;
 pub mod drag_drop;
 pub mod element;
 pub mod events;
 pub mod hit_test;
+pub mod lifecycle;
+pub mod observer;
+pub mod pointer_lock;
 pub mod registry;
 pub mod shortcuts;
+pub mod text_selection;
+pub mod window_chrome;
 
+pub use coalesce::{pointer_coalescing, pointer_samples, set_pointer_coalescing};
 pub use drag_drop::{
     DragConfig, DragData, DragDropEvent, DragState, DropResult, DropZone, DropZoneRegistry,
     Draggable, DropTarget, DRAG_THRESHOLD,
 };
 pub use element::{Interactable, InteractiveElement};
 pub use events::{EventHandlers, InteractionEvent, InteractionState};
-pub use hit_test::{HitTestBuilder, HitTestEntry, HitTestResult};
-pub use registry::{ElementRegistry, get_element_state, register_element};
+pub use hit_test::{
+    HitTestBuilder, HitTestEntry, HitTestResult, min_hit_target_size, set_min_hit_target_size,
+};
+pub use lifecycle::{LifecycleHandlers, LifecycleTracker};
+pub use observer::{
+    ElementInfo, InteractionObserver, ObserverId, add_interaction_observer,
+    remove_interaction_observer,
+};
+pub use pointer_lock::{lock_pointer, pointer_lock_owner, unlock_pointer};
+pub use registry::{
+    ElementRegistry, current_style_state, get_element_state, register_element, request_focus,
+};
 pub use shortcuts::{
     Shortcut, ShortcutConflict, ShortcutId, ShortcutInfo, ShortcutMatch, ShortcutModifiers,
     ShortcutRegistry, ShortcutScope,
 };
+pub use text_selection::{
+    clear_text_selection, selected_text, set_text_selection_enabled, text_selection_enabled,
+};
+pub use window_chrome::{WindowChrome, WindowEdge};
 
 /// Manages interaction state across the entire UI
 pub struct InteractionSystem {
@@ -77,6 +100,18 @@ pub struct InteractionSystem {
 
     /// Click count from the last mouse down event (for double/triple click detection)
     last_click_count: u32,
+
+    /// Hover delays of elements with hover start handlers (from the last paint)
+    hover_delays: HashMap<ElementId, Duration>,
+
+    /// Frame clock time the mouse started hovering the current hovered element
+    hover_started: Option<f32>,
+
+    /// Element that has received `HoverStart` and is still hovered
+    hover_intent_element: Option<ElementId>,
+
+    /// Hover events produced outside `handle_input`, delivered on the next poll
+    deferred_hover_events: Vec<InteractionEvent>,
 }
 
 impl InteractionSystem {
@@ -98,6 +133,10 @@ impl InteractionSystem {
             press_start_position: None,
             drop_zones: DropZoneRegistry::new(),
             last_click_count: 1,
+            hover_delays: HashMap::new(),
+            hover_started: None,
+            hover_intent_element: None,
+            deferred_hover_events: Vec::new(),
         }
     }
 
@@ -302,11 +341,14 @@ impl InteractionSystem {
             } => {
                 self.mouse_position = *position;
                 self.last_click_count = *click_count;
+                // A press on selectable text starts a new selection
+                text_selection::clear_text_selection();
                 events.extend(self.handle_mouse_down(*position, *button, *click_count));
             }
 
             InputEvent::MouseUp { position, button } => {
                 self.mouse_position = *position;
+                text_selection::end_text_drag();
                 events.extend(self.handle_mouse_up(*position, *button));
             }
 
@@ -334,9 +376,34 @@ impl InteractionSystem {
                 self.current_modifiers = *modifiers;
             }
 
-            InputEvent::ScrollWheel { position, delta } => {
+            InputEvent::ScrollWheel {
+                position,
+                delta,
+                phase,
+            } => {
                 self.mouse_position = *position;
-                events.extend(self.handle_scroll_wheel(*position, *delta));
+                events.extend(self.handle_scroll_wheel(*position, *delta, *phase));
+            }
+
+            InputEvent::Magnify {
+                position,
+                magnification,
+            } => {
+                self.mouse_position = *position;
+                events.extend(self.handle_magnify(*position, *magnification));
+            }
+
+            InputEvent::MouseDelta { delta } => {
+                if let Some(element_id) = pointer_lock_owner() {
+                    events.push(InteractionEvent::MouseDelta {
+                        element_id,
+                        delta: *delta,
+                    });
+                }
+            }
+
+            InputEvent::TabletMove { position, tablet } => {
+                events.extend(self.handle_tablet_move(*position, *tablet));
             }
 
             // Window events are handled at the app level, not the interaction system
@@ -389,6 +456,15 @@ impl InteractionSystem {
             }
         }
 
+        // Cmd+C copies selected static text when nothing has focus
+        if key == Key::C
+            && modifiers.cmd
+            && self.focused_element.is_none()
+            && text_selection::copy_text_selection()
+        {
+            return events;
+        }
+
         // Route keyboard event to focused element
         if let Some(element_id) = self.focused_element {
             events.push(InteractionEvent::KeyDown {
@@ -429,6 +505,8 @@ impl InteractionSystem {
 
         // Handle hover changes
         if new_hovered != self.hovered_element {
+            events.extend(self.end_hover_intent());
+
             // Mouse leave on previous element
             if let Some(prev_id) = self.hovered_element {
                 if let Some(state) = self.element_states.get_mut(&prev_id) {
@@ -450,6 +528,7 @@ impl InteractionSystem {
             }
 
             self.hovered_element = new_hovered;
+            self.hover_started = new_hovered.map(|_| frame_time());
         }
 
         // Send move event to hovered element
@@ -650,6 +729,9 @@ impl InteractionSystem {
     fn handle_mouse_leave(&mut self) -> Vec<InteractionEvent> {
         let mut events = Vec::new();
 
+        events.extend(self.end_hover_intent());
+        self.hover_started = None;
+
         // Clear hover state
         if let Some(hovered_id) = self.hovered_element.take() {
             if let Some(state) = self.element_states.get_mut(&hovered_id) {
@@ -667,7 +749,12 @@ impl InteractionSystem {
     }
 
     /// Handle scroll wheel events
-    fn handle_scroll_wheel(&mut self, position: Vec2, delta: Vec2) -> Vec<InteractionEvent> {
+    fn handle_scroll_wheel(
+        &mut self,
+        position: Vec2,
+        delta: Vec2,
+        phase: ScrollPhase,
+    ) -> Vec<InteractionEvent> {
         let mut events = Vec::new();
 
         // Find what's under the mouse and send scroll event to it
@@ -675,6 +762,25 @@ impl InteractionSystem {
             events.push(InteractionEvent::ScrollWheel {
                 element_id: hit.element_id,
                 delta,
+                phase,
+                position,
+                local_position: hit.local_position,
+                modifiers: self.current_modifiers,
+            });
+        }
+
+        events
+    }
+
+    /// Handle trackpad pinch events
+    fn handle_magnify(&mut self, position: Vec2, magnification: f32) -> Vec<InteractionEvent> {
+        let mut events = Vec::new();
+
+        // Pinches go to the element under the mouse, like scrolling
+        if let Some(hit) = self.hit_test(position) {
+            events.push(InteractionEvent::Magnify {
+                element_id: hit.element_id,
+                magnification,
                 position,
                 local_position: hit.local_position,
             });
@@ -683,9 +789,95 @@ impl InteractionSystem {
         events
     }
 
+    /// Handle stylus data from a graphics tablet
+    fn handle_tablet_move(&mut self, position: Vec2, tablet: TabletData) -> Vec<InteractionEvent> {
+        let mut events = Vec::new();
+
+        // Strokes stay with the pressed element when the pen leaves it
+        let target = match self.pressed_element {
+            Some((element_id, _)) => self
+                .hit_test_entry(element_id)
+                .map(|entry| (element_id, entry.local_position(position))),
+            None => self
+                .hit_test(position)
+                .map(|hit| (hit.element_id, hit.local_position)),
+        };
+        if let Some((element_id, local_position)) = target {
+            events.push(InteractionEvent::TabletMove {
+                element_id,
+                tablet,
+                position,
+                local_position,
+            });
+        }
+
+        events
+    }
+
     /// Update hover state based on current mouse position
     fn update_hover_state(&mut self) {
-        let _ = self.handle_mouse_move(self.mouse_position);
+        // Keep hover ends so hover start handlers always see a matching end
+        let events = self.handle_mouse_move(self.mouse_position);
+        self.deferred_hover_events.extend(
+            events
+                .into_iter()
+                .filter(|e| matches!(e, InteractionEvent::HoverEnd { .. })),
+        );
+    }
+
+    /// End the current hover intent, if any
+    fn end_hover_intent(&mut self) -> Option<InteractionEvent> {
+        self.hover_intent_element
+            .take()
+            .map(|element_id| InteractionEvent::HoverEnd { element_id })
+    }
+
+    /// Set the hover delays for elements with hover start handlers
+    ///
+    /// Called after paint with the delays collected by the element registry.
+    pub fn set_hover_delays(&mut self, delays: HashMap<ElementId, Duration>) {
+        self.hover_delays = delays;
+    }
+
+    /// Check hover delays against `now` and return due hover events
+    ///
+    /// `now` is frame clock seconds, as from [`frame_time`]. Emits
+    /// `HoverStart` once the hovered element has been under the mouse for its
+    /// hover delay, along with any hover ends caused by layout changes.
+    pub fn update_hover_intent(&mut self, now: f32) -> Vec<InteractionEvent> {
+        let mut events = std::mem::take(&mut self.deferred_hover_events);
+
+        if let (Some(element_id), Some(due)) = (self.hovered_element, self.next_hover_deadline())
+            && now >= due
+        {
+            self.hover_intent_element = Some(element_id);
+            events.push(InteractionEvent::HoverStart { element_id });
+        }
+
+        events
+    }
+
+    /// Frame clock time the hovered element's hover start becomes due, if
+    /// it's waiting on one
+    pub fn next_hover_deadline(&self) -> Option<f32> {
+        let element_id = self.hovered_element?;
+        if self.hover_intent_element == Some(element_id) {
+            return None;
+        }
+        let delay = self.hover_delays.get(&element_id)?;
+        Some(self.hover_started? + delay.as_secs_f32())
+    }
+
+    /// Check if hover events are waiting to be delivered by `update_hover_intent`
+    pub fn has_pending_hover_events(&self) -> bool {
+        !self.deferred_hover_events.is_empty() || self.next_hover_deadline().is_some()
+    }
+
+    /// The hit test entry for an element from the last paint
+    pub fn hit_test_entry(&self, element_id: ElementId) -> Option<&HitTestEntry> {
+        self.last_hit_test
+            .iter()
+            .find(|entry| entry.element_id == element_id)
     }
 
     /// Perform hit testing at the given position
@@ -693,7 +885,7 @@ impl InteractionSystem {
         // Hit test entries are sorted by z-order (highest first)
         for entry in &self.last_hit_test {
             if entry.bounds.contains(Point::from(position)) {
-                let local_position = position - entry.bounds.pos;
+                let local_position = entry.local_position(position);
                 return Some(HitTestResult {
                     element_id: entry.element_id,
                     bounds: entry.bounds,
@@ -705,6 +897,24 @@ impl InteractionSystem {
         None
     }
 
+    /// Every element from the last paint whose hit area contains
+    /// `position`, topmost first
+    pub fn elements_at(&self, position: Vec2) -> Vec<ElementId> {
+        self.last_hit_test
+            .iter()
+            .filter(|entry| entry.bounds.contains(Point::from(position)))
+            .map(|entry| entry.element_id)
+            .collect()
+    }
+
+    /// Check if a pointer event at `position` belongs to this system's
+    /// elements: it lands on one, or an element is pressed or being dragged
+    pub fn captures_pointer(&self, position: Vec2) -> bool {
+        self.pressed_element.is_some()
+            || self.current_drag.is_some()
+            || self.hit_test(position).is_some()
+    }
+
     /// Get the current interaction state for an element
     pub fn get_state(&self, element_id: ElementId) -> Option<&InteractionState> {
         self.element_states.get(&element_id)
@@ -723,6 +933,10 @@ impl InteractionSystem {
         self.press_start_position = None;
         self.drop_zones.clear();
         self.last_click_count = 1;
+        self.hover_delays.clear();
+        self.hover_started = None;
+        self.hover_intent_element = None;
+        self.deferred_hover_events.clear();
     }
 
     /// Get current modifier state
@@ -971,6 +1185,28 @@ mod tests {
         assert!(system.focused_element().is_none());
     }
 
+    #[test]
+    fn test_elements_at() {
+        let mut system = create_test_system();
+        let panel = Rect::new(0.0, 0.0, 200.0, 200.0);
+        let button = Rect::new(10.0, 10.0, 100.0, 50.0);
+        system.update_hit_test(create_hit_entries(&[(2, button, 1), (1, panel, 0)]));
+
+        assert_eq!(
+            system.elements_at(Vec2::new(50.0, 30.0)),
+            vec![ElementId::new(2), ElementId::new(1)]
+        );
+        assert_eq!(
+            system.elements_at(Vec2::new(150.0, 150.0)),
+            vec![ElementId::new(1)]
+        );
+        assert!(system.elements_at(Vec2::new(300.0, 30.0)).is_empty());
+        assert_eq!(
+            system.hit_test_entry(ElementId::new(2)).map(|e| e.bounds),
+            Some(button)
+        );
+    }
+
     #[test]
     fn test_mouse_enter_leave() {
         let mut system = create_test_system();
@@ -1046,6 +1282,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_button_specific_click_handlers() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let mut system = create_test_system();
+        let bounds = Rect::new(0.0, 0.0, 50.0, 50.0);
+        system.update_hit_test(create_hit_entries(&[(1, bounds, 0)]));
+
+        let clicks = Rc::new(RefCell::new(Vec::new()));
+        let (middle, back) = (clicks.clone(), clicks.clone());
+        let mut handlers = EventHandlers::new()
+            .on_middle_click(move |_, _, _| middle.borrow_mut().push("middle"))
+            .on_mouse_button(MouseButton::Back, move |_, _, _| {
+                back.borrow_mut().push("back")
+            });
+
+        let buttons = [MouseButton::Left, MouseButton::Middle, MouseButton::Back];
+        for button in buttons {
+            let position = Vec2::new(10.0, 10.0);
+            system.handle_input(&InputEvent::MouseDown {
+                position,
+                button,
+                click_count: 1,
+            });
+            for event in system.handle_input(&InputEvent::MouseUp { position, button }) {
+                handlers.handle_event(&event);
+            }
+        }
+        assert_eq!(*clicks.borrow(), vec!["middle", "back"]);
+    }
+
     #[test]
     fn test_no_click_when_released_outside() {
         let mut system = create_test_system();
@@ -1079,6 +1347,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_captures_pointer_while_pressed() {
+        let mut system = create_test_system();
+        let button = Rect::new(10.0, 10.0, 100.0, 50.0);
+        system.update_hit_test(create_hit_entries(&[(1, button, 0)]));
+
+        assert!(system.captures_pointer(Vec2::new(50.0, 30.0)));
+        assert!(!system.captures_pointer(Vec2::new(200.0, 200.0)));
+
+        // A press keeps the pointer until it is released, wherever it goes
+        system.handle_input(&InputEvent::MouseDown {
+            position: Vec2::new(50.0, 30.0),
+            button: MouseButton::Left,
+            click_count: 1,
+        });
+        assert!(system.captures_pointer(Vec2::new(200.0, 200.0)));
+
+        system.handle_input(&InputEvent::MouseUp {
+            position: Vec2::new(200.0, 200.0),
+            button: MouseButton::Left,
+        });
+        assert!(!system.captures_pointer(Vec2::new(200.0, 200.0)));
+    }
+
     #[test]
     fn test_z_order_hit_testing() {
         let mut system = create_test_system();
@@ -1223,6 +1515,7 @@ mod tests {
         let events = system.handle_input(&InputEvent::ScrollWheel {
             position: Vec2::new(100.0, 100.0),
             delta: Vec2::new(0.0, -10.0),
+            phase: ScrollPhase::None,
         });
 
         assert!(
@@ -1253,6 +1546,33 @@ mod tests {
             .any(|e| matches!(e, InteractionEvent::MouseLeave { element_id } if element_id.0 == 1)));
     }
 
+    #[test]
+    fn test_tablet_move_follows_pressed_element() {
+        let mut system = create_test_system();
+        let canvas = Rect::new(10.0, 10.0, 100.0, 50.0);
+        let tablet = TabletData {
+            pressure: 0.5,
+            ..Default::default()
+        };
+
+        system.update_hit_test(create_hit_entries(&[(1, canvas, 0)]));
+        system.handle_input(&InputEvent::MouseDown {
+            position: Vec2::new(50.0, 30.0),
+            button: MouseButton::Left,
+            click_count: 1,
+        });
+
+        // The pen leaves the canvas mid-stroke
+        let events = system.handle_input(&InputEvent::TabletMove {
+            position: Vec2::new(150.0, 30.0),
+            tablet,
+        });
+
+        assert!(events.iter().any(|e| matches!(e,
+            InteractionEvent::TabletMove { element_id, tablet, local_position, .. }
+                if element_id.0 == 1 && tablet.pressure == 0.5 && local_position.x == 140.0)));
+    }
+
     #[test]
     fn test_clear_resets_all_state() {
         let mut system = create_test_system();
@@ -1271,6 +1591,87 @@ mod tests {
         assert!(system.get_state(ElementId::new(1)).is_none());
     }
 
+    #[test]
+    fn test_hover_intent_after_delay() {
+        let mut system = create_test_system();
+        let button = Rect::new(10.0, 10.0, 100.0, 50.0);
+        let delay = Duration::from_millis(300);
+
+        system.update_hit_test(create_hit_entries(&[(1, button, 0)]));
+        system.set_hover_delays(HashMap::from([(ElementId::new(1), delay)]));
+        system.handle_input(&InputEvent::MouseMove {
+            position: Vec2::new(50.0, 30.0),
+        });
+
+        // Not due yet
+        assert!(system.update_hover_intent(0.1).is_empty());
+        assert!(system.has_pending_hover_events());
+
+        let later = delay.as_secs_f32();
+        let events = system.update_hover_intent(later);
+        assert!(matches!(
+            events.as_slice(),
+            [InteractionEvent::HoverStart { element_id }] if element_id.0 == 1
+        ));
+
+        // Fires only once per hover
+        assert!(system.update_hover_intent(later).is_empty());
+        assert!(!system.has_pending_hover_events());
+
+        let events = system.handle_input(&InputEvent::MouseMove {
+            position: Vec2::new(200.0, 200.0),
+        });
+        assert!(
+            events.iter().any(
+                |e| matches!(e, InteractionEvent::HoverEnd { element_id } if element_id.0 == 1)
+            )
+        );
+    }
+
+    #[test]
+    fn test_hover_intent_follows_frame_clock() {
+        let mut system = create_test_system();
+        let button = Rect::new(10.0, 10.0, 100.0, 50.0);
+
+        system.update_hit_test(create_hit_entries(&[(1, button, 0)]));
+        system.set_hover_delays(HashMap::from([(
+            ElementId::new(1),
+            Duration::from_millis(300),
+        )]));
+        crate::clock::set_frame_time(5.0);
+        system.handle_input(&InputEvent::MouseMove {
+            position: Vec2::new(50.0, 30.0),
+        });
+
+        assert!((system.next_hover_deadline().unwrap() - 5.3).abs() < 1e-5);
+        assert!(system.update_hover_intent(5.2).is_empty());
+        assert_eq!(system.update_hover_intent(5.31).len(), 1);
+    }
+
+    #[test]
+    fn test_no_hover_intent_when_passing_over() {
+        let mut system = create_test_system();
+        let button = Rect::new(10.0, 10.0, 100.0, 50.0);
+
+        system.update_hit_test(create_hit_entries(&[(1, button, 0)]));
+        system.set_hover_delays(HashMap::from([(
+            ElementId::new(1),
+            Duration::from_millis(300),
+        )]));
+        system.handle_input(&InputEvent::MouseMove {
+            position: Vec2::new(50.0, 30.0),
+        });
+        let events = system.handle_input(&InputEvent::MouseLeave);
+
+        // No hover start happened, so no hover end either
+        assert!(
+            !events
+                .iter()
+                .any(|e| matches!(e, InteractionEvent::HoverEnd { .. }))
+        );
+        assert!(!system.has_pending_hover_events());
+    }
+
     #[test]
     fn test_element_id_equality() {
         let id1 = ElementId::new(42);
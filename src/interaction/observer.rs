@@ -0,0 +1,147 @@
+//! App-wide observers of interaction events
+//!
+//! Observers see every [`InteractionEvent`] after it's dispatched to its
+//! element, along with what's known about that element, so analytics,
+//! session recording and test assertions don't need to touch individual
+//! handlers.
+//!
+//! ```ignore
+//! let clicks = Rc::new(Cell::new(0));
+//! let counter = clicks.clone();
+//! add_interaction_observer(move |event: &InteractionEvent, _: Option<&ElementInfo>| {
+//!     if matches!(event, InteractionEvent::Click { .. }) {
+//!         counter.set(counter.get() + 1);
+//!     }
+//! });
+//! ```
+
+use super::{ElementId, InteractionEvent, InteractionState};
+use crate::geometry::Rect;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+/// What's known about the element an event was aimed at
+#[derive(Debug, Clone)]
+pub struct ElementInfo {
+    /// The element's ID
+    pub id: ElementId,
+    /// Screen bounds from the last paint, if the element can be hit
+    pub bounds: Option<Rect>,
+    /// The element's z-index within its layer
+    pub z_index: i32,
+    /// Whether the element can take keyboard focus
+    pub focusable: bool,
+    /// Z-index of the layer the element is in
+    pub layer_z_index: i32,
+    /// The element's interaction state after the event
+    pub state: InteractionState,
+    /// Whether the element had handlers to receive the event
+    pub handled: bool,
+}
+
+/// Receives every interaction event in the app
+///
+/// Closures taking the event and element info implement this trait.
+pub trait InteractionObserver {
+    /// Called after `event` is dispatched; `element` is `None` for events
+    /// without a target element, such as shortcuts
+    fn on_event(&mut self, event: &InteractionEvent, element: Option<&ElementInfo>);
+}
+
+impl<F> InteractionObserver for F
+where
+    F: FnMut(&InteractionEvent, Option<&ElementInfo>),
+{
+    fn on_event(&mut self, event: &InteractionEvent, element: Option<&ElementInfo>) {
+        self(event, element)
+    }
+}
+
+/// Handle for removing an observer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObserverId(u64);
+
+type SharedObserver = Rc<RefCell<dyn InteractionObserver>>;
+
+thread_local! {
+    static OBSERVERS: RefCell<Vec<(ObserverId, SharedObserver)>> = const { RefCell::new(Vec::new()) };
+    static NEXT_OBSERVER_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Start sending interaction events to `observer`
+pub fn add_interaction_observer(observer: impl InteractionObserver + 'static) -> ObserverId {
+    let id = ObserverId(NEXT_OBSERVER_ID.with(|next| next.replace(next.get() + 1)));
+    OBSERVERS.with(|observers| {
+        observers
+            .borrow_mut()
+            .push((id, Rc::new(RefCell::new(observer))));
+    });
+    id
+}
+
+/// Stop sending events to an observer
+pub fn remove_interaction_observer(id: ObserverId) {
+    OBSERVERS.with(|observers| observers.borrow_mut().retain(|(other, _)| *other != id));
+}
+
+/// Whether any observers are registered, to skip gathering element info
+pub(crate) fn has_interaction_observers() -> bool {
+    OBSERVERS.with(|observers| !observers.borrow().is_empty())
+}
+
+/// Send an event to every observer
+pub(crate) fn notify_interaction_observers(
+    event: &InteractionEvent,
+    element: Option<&ElementInfo>,
+) {
+    // Observers may add or remove observers while handling the event
+    let observers: Vec<SharedObserver> = OBSERVERS.with(|observers| {
+        observers
+            .borrow()
+            .iter()
+            .map(|(_, observer)| observer.clone())
+            .collect()
+    });
+    for observer in observers {
+        // An observer that triggers events from inside its own callback
+        // doesn't see them
+        if let Ok(mut observer) = observer.try_borrow_mut() {
+            observer.on_event(event, element);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_observers_receive_events_until_removed() {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let log = seen.clone();
+        let id = add_interaction_observer(
+            move |event: &InteractionEvent, element: Option<&ElementInfo>| {
+                log.borrow_mut()
+                    .push((event.element_id(), element.map(|e| e.handled)));
+            },
+        );
+        assert!(has_interaction_observers());
+
+        let element_id = ElementId::new(7);
+        let info = ElementInfo {
+            id: element_id,
+            bounds: None,
+            z_index: 0,
+            focusable: false,
+            layer_z_index: 0,
+            state: InteractionState::default(),
+            handled: true,
+        };
+        notify_interaction_observers(&InteractionEvent::FocusIn { element_id }, Some(&info));
+        remove_interaction_observer(id);
+        notify_interaction_observers(&InteractionEvent::FocusOut { element_id }, Some(&info));
+
+        assert_eq!(*seen.borrow(), vec![(Some(element_id), Some(true))]);
+        assert!(!has_interaction_observers());
+    }
+}
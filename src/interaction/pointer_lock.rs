@@ -0,0 +1,88 @@
+//! Pointer lock for elements that need raw mouse motion
+//!
+//! While the pointer is locked the cursor is hidden and stays where it is,
+//! and mouse motion reaches the locking element's `on_mouse_delta` handler
+//! as relative deltas instead of moves. 3D viewports use it to orbit without
+//! the cursor running into the edge of the screen.
+//!
+//! Elements lock the pointer while they're pressed with
+//! [`pointer_lock`](super::InteractiveElement::pointer_lock), or for as long
+//! as they need with [`lock_pointer`] and [`unlock_pointer`]. The platform
+//! layer applies a change before handling the next event, and only while
+//! the window is key.
+
+use super::ElementId;
+use std::cell::Cell;
+
+thread_local! {
+    /// Element receiving mouse motion as deltas
+    static POINTER_LOCK: Cell<Option<ElementId>> = const { Cell::new(None) };
+}
+
+/// Lock the pointer, sending mouse motion to `element_id` as deltas
+pub fn lock_pointer(element_id: ElementId) {
+    POINTER_LOCK.with(|lock| lock.set(Some(element_id)));
+}
+
+/// Release the pointer lock, if any
+pub fn unlock_pointer() {
+    POINTER_LOCK.with(|lock| lock.set(None));
+}
+
+/// The element the pointer is locked to, if any
+pub fn pointer_lock_owner() -> Option<ElementId> {
+    POINTER_LOCK.with(|lock| lock.get())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interaction::{EventHandlers, InteractionEvent, InteractionSystem};
+    use crate::layer::{InputEvent, Modifiers, MouseButton};
+    use glam::Vec2;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_lock_while_pressed_routes_deltas() {
+        let viewport = ElementId::stable("pointer-lock:viewport");
+        let deltas = Rc::new(Cell::new(Vec2::ZERO));
+        let total = deltas.clone();
+        let mut handlers = EventHandlers::new().on_mouse_delta(move |delta| {
+            total.set(total.get() + delta);
+        });
+        handlers.lock_pointer = true;
+
+        let mut system = InteractionSystem::new();
+        let move_by = |system: &mut InteractionSystem, delta| {
+            system.handle_input(&InputEvent::MouseDelta { delta })
+        };
+        // Nothing holds the lock yet
+        assert!(move_by(&mut system, Vec2::new(3.0, 0.0)).is_empty());
+
+        handlers.handle_event(&InteractionEvent::MouseDown {
+            element_id: viewport,
+            button: MouseButton::Left,
+            position: Vec2::ZERO,
+            local_position: Vec2::ZERO,
+            modifiers: Modifiers::new(),
+            click_count: 1,
+        });
+        assert_eq!(pointer_lock_owner(), Some(viewport));
+        for event in move_by(&mut system, Vec2::new(3.0, -2.0))
+            .iter()
+            .chain(&move_by(&mut system, Vec2::new(1.0, 0.0)))
+        {
+            handlers.handle_event(event);
+        }
+        assert_eq!(deltas.get(), Vec2::new(4.0, -2.0));
+
+        handlers.handle_event(&InteractionEvent::MouseUp {
+            element_id: viewport,
+            button: MouseButton::Left,
+            position: Vec2::ZERO,
+            local_position: Vec2::ZERO,
+            modifiers: Modifiers::new(),
+        });
+        assert_eq!(pointer_lock_owner(), None);
+    }
+}
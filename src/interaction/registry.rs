@@ -1,9 +1,12 @@
 //! Registry for interactive elements to enable event routing
 
 use super::{ElementId, EventHandlers, InteractionEvent, InteractionState};
-use std::cell::RefCell;
+use crate::{layer::ScrollPhase, style::StyleState};
+use glam::Vec2;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::rc::Rc;
+use std::time::Duration;
 
 /// Registry for interactive elements in a UI layer
 pub struct ElementRegistry {
@@ -15,6 +18,24 @@ pub struct ElementRegistry {
 
     /// List of focusable elements in tab order
     focusable_elements: Vec<ElementId>,
+
+    /// Element asking for keyboard focus this frame
+    focus_request: Option<ElementId>,
+
+    /// Which element the current trackpad gesture belongs to
+    scroll_latch: ScrollLatch,
+}
+
+/// Progress of picking an element for a trackpad gesture
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum ScrollLatch {
+    /// No gesture, so each scroll event finds its own element
+    #[default]
+    None,
+    /// A gesture began but hasn't moved yet
+    Pending,
+    /// The element that took the gesture's first movement
+    Element(ElementId),
 }
 
 impl ElementRegistry {
@@ -24,6 +45,8 @@ impl ElementRegistry {
             handlers: HashMap::new(),
             states: HashMap::new(),
             focusable_elements: Vec::new(),
+            focus_request: None,
+            scroll_latch: ScrollLatch::None,
         }
     }
 
@@ -39,6 +62,16 @@ impl ElementRegistry {
         &self.focusable_elements
     }
 
+    /// Ask for `id` to be focused once the frame has painted
+    pub fn request_focus(&mut self, id: ElementId) {
+        self.focus_request = Some(id);
+    }
+
+    /// Take the element that asked for focus this frame, if any
+    pub fn take_focus_request(&mut self) -> Option<ElementId> {
+        self.focus_request.take()
+    }
+
     /// Register an element's event handlers
     pub fn register(&mut self, id: ElementId, handlers: Rc<RefCell<EventHandlers>>) {
         self.handlers.insert(id, handlers);
@@ -51,6 +84,21 @@ impl ElementRegistry {
         self.states.remove(&id);
     }
 
+    /// Get the hover delays of elements with a hover start handler
+    pub fn hover_delays(&self) -> HashMap<ElementId, Duration> {
+        self.handlers
+            .iter()
+            .filter_map(|(id, handlers)| {
+                let handlers = handlers.borrow();
+                handlers
+                    .on_hover_start
+                    .as_ref()
+                    .and(handlers.hover_delay)
+                    .map(|delay| (*id, delay))
+            })
+            .collect()
+    }
+
     /// Get the interaction state for an element
     pub fn get_state(&self, id: ElementId) -> Option<&InteractionState> {
         self.states.get(&id)
@@ -65,30 +113,10 @@ impl ElementRegistry {
 
     /// Dispatch an event to the appropriate element
     pub fn dispatch_event(&mut self, event: &InteractionEvent) -> bool {
-        // ShortcutTriggered events are handled at the application level, not dispatched to elements
-        let element_id = match event {
-            InteractionEvent::MouseEnter { element_id }
-            | InteractionEvent::MouseLeave { element_id }
-            | InteractionEvent::MouseMove { element_id, .. }
-            | InteractionEvent::MouseDown { element_id, .. }
-            | InteractionEvent::MouseUp { element_id, .. }
-            | InteractionEvent::Click { element_id, .. }
-            | InteractionEvent::DoubleClick { element_id, .. }
-            | InteractionEvent::TripleClick { element_id, .. }
-            | InteractionEvent::RightClick { element_id, .. }
-            | InteractionEvent::ScrollWheel { element_id, .. }
-            | InteractionEvent::KeyDown { element_id, .. }
-            | InteractionEvent::KeyUp { element_id, .. }
-            | InteractionEvent::FocusIn { element_id }
-            | InteractionEvent::FocusOut { element_id } => *element_id,
-            InteractionEvent::ShortcutTriggered { .. } => {
-                // Shortcut events aren't dispatched to specific elements
-                return true;
-            }
-            InteractionEvent::DragDrop(_) => {
-                // Drag and drop events aren't dispatched to specific elements
-                return true;
-            }
+        // Shortcut and drag and drop events are handled at the application
+        // level, not dispatched to elements
+        let Some(element_id) = event.element_id() else {
+            return true;
         };
 
         // Update states based on event type
@@ -135,11 +163,102 @@ impl ElementRegistry {
         }
     }
 
+    /// Dispatch a scroll event to the first element under the pointer that
+    /// takes it
+    ///
+    /// `candidates` are the elements under the pointer, topmost first, and
+    /// `local_position` gives the event's position relative to one of them.
+    /// Elements without scroll handlers are skipped, and a handler can hand
+    /// the event on to the next one with [`pass_scroll`]. Once an element
+    /// takes a trackpad gesture's first movement, the rest of the gesture and
+    /// its momentum go to it alone, with the first movement delivered as
+    /// [`ScrollPhase::Began`].
+    ///
+    /// Returns the event as delivered to the element that took it.
+    pub fn dispatch_scroll(
+        &mut self,
+        event: &InteractionEvent,
+        candidates: &[ElementId],
+        local_position: impl Fn(ElementId) -> Vec2,
+    ) -> Option<InteractionEvent> {
+        let InteractionEvent::ScrollWheel {
+            delta,
+            phase,
+            position,
+            modifiers,
+            ..
+        } = *event
+        else {
+            return None;
+        };
+        let retarget = |element_id, phase| InteractionEvent::ScrollWheel {
+            element_id,
+            delta,
+            phase,
+            position,
+            local_position: local_position(element_id),
+            modifiers,
+        };
+
+        match phase {
+            ScrollPhase::None => self.scroll_latch = ScrollLatch::None,
+            ScrollPhase::Began => self.scroll_latch = ScrollLatch::Pending,
+            _ => {}
+        }
+        let moved = delta != Vec2::ZERO;
+        let phase = match self.scroll_latch {
+            ScrollLatch::Element(id) => {
+                let event = retarget(id, phase);
+                return self.deliver_scroll(id, &event, false).then_some(event);
+            }
+            // Wait for movement, so the direction can decide who takes it
+            ScrollLatch::Pending if !moved => return None,
+            ScrollLatch::Pending if phase == ScrollPhase::Changed => ScrollPhase::Began,
+            _ => phase,
+        };
+
+        let scrollable: Vec<ElementId> = candidates
+            .iter()
+            .copied()
+            .filter(|id| {
+                self.handlers.get(id).is_some_and(|handlers| {
+                    let handlers = handlers.borrow();
+                    handlers.on_scroll.is_some()
+                        || handlers.on_scroll_with_modifiers.is_some()
+                        || handlers.on_scroll_gesture.is_some()
+                })
+            })
+            .collect();
+        for (index, &id) in scrollable.iter().enumerate() {
+            let event = retarget(id, phase);
+            if self.deliver_scroll(id, &event, index + 1 < scrollable.len()) {
+                if moved && matches!(phase, ScrollPhase::Began | ScrollPhase::Changed) {
+                    self.scroll_latch = ScrollLatch::Element(id);
+                }
+                return Some(event);
+            }
+        }
+        None
+    }
+
+    /// Call an element's scroll handlers, returning whether it kept the event
+    fn deliver_scroll(&self, id: ElementId, event: &InteractionEvent, can_pass: bool) -> bool {
+        let Some(handlers) = self.handlers.get(&id) else {
+            return false;
+        };
+        CAN_PASS_SCROLL.with(|can| can.set(can_pass));
+        SCROLL_PASSED.with(|passed| passed.set(false));
+        handlers.borrow_mut().handle_event(event);
+        CAN_PASS_SCROLL.with(|can| can.set(false));
+        !SCROLL_PASSED.with(|passed| passed.take())
+    }
+
     /// Clear all registrations
     pub fn clear(&mut self) {
         self.handlers.clear();
         self.states.clear();
         self.focusable_elements.clear();
+        self.focus_request = None;
     }
 
     /// Check if an element is registered
@@ -167,6 +286,12 @@ impl Default for ElementRegistry {
 thread_local! {
     /// Thread-local registry for the current frame
     static CURRENT_REGISTRY: RefCell<Option<Rc<RefCell<ElementRegistry>>>> = RefCell::new(None);
+    /// Style states of the interactive elements being painted, innermost last
+    static STYLE_STATE_STACK: RefCell<Vec<StyleState>> = const { RefCell::new(Vec::new()) };
+    /// Whether the scroll event being handled has another element to go to
+    static CAN_PASS_SCROLL: Cell<bool> = const { Cell::new(false) };
+    /// Whether the scroll handler being called handed its event on
+    static SCROLL_PASSED: Cell<bool> = const { Cell::new(false) };
 }
 
 /// Set the current element registry for this thread
@@ -209,3 +334,180 @@ pub fn register_focusable(id: ElementId) {
         }
     });
 }
+
+/// Give `id` keyboard focus once the current frame has painted
+///
+/// For elements that take focus as soon as they appear, like a launcher's
+/// search field. The last request in a frame wins.
+pub fn request_focus(id: ElementId) {
+    CURRENT_REGISTRY.with(|r| {
+        if let Some(registry) = r.borrow().as_ref() {
+            registry.borrow_mut().request_focus(id);
+        }
+    });
+}
+
+/// Hand the scroll event being handled on to the next scrollable element
+/// under the pointer, instead of keeping it
+///
+/// Call this from an `on_scroll` or `on_scroll_gesture` handler that has no
+/// use for the event, e.g. a scroll container already at its edge. It has no
+/// effect when [`can_pass_scroll`] is false.
+pub fn pass_scroll() {
+    if can_pass_scroll() {
+        SCROLL_PASSED.with(|passed| passed.set(true));
+    }
+}
+
+/// Whether the scroll event being handled could go on to another element
+///
+/// False for the bottommost scrollable element under the pointer, and for
+/// the element a trackpad gesture is latched to, which get the event
+/// whether they want it or not.
+pub fn can_pass_scroll() -> bool {
+    CAN_PASS_SCROLL.with(|can| can.get())
+}
+
+/// Make `state` the style state for elements painted inside an interactive
+/// element
+///
+/// Disabled is inherited, so everything inside a disabled element is styled
+/// as disabled.
+pub fn push_style_state(mut state: StyleState) {
+    STYLE_STATE_STACK.with(|s| {
+        let mut stack = s.borrow_mut();
+        state.disabled |= stack.last().is_some_and(|parent| parent.disabled);
+        stack.push(state);
+    });
+}
+
+/// Restore the style state from before the last [`push_style_state`]
+pub fn pop_style_state() {
+    STYLE_STATE_STACK.with(|s| {
+        s.borrow_mut().pop();
+    });
+}
+
+/// Drop style states left pushed by a paint that panicked
+pub(crate) fn clear_style_states() {
+    STYLE_STATE_STACK.with(|s| s.borrow_mut().clear());
+}
+
+/// The style state of the innermost interactive element being painted
+///
+/// Outside any interactive element this is the default, idle state.
+pub fn current_style_state() -> StyleState {
+    STYLE_STATE_STACK.with(|s| s.borrow().last().copied().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::Modifiers;
+
+    fn scroll_event(delta: Vec2, phase: ScrollPhase) -> InteractionEvent {
+        InteractionEvent::ScrollWheel {
+            element_id: ElementId::new(1),
+            delta,
+            phase,
+            position: Vec2::new(10.0, 10.0),
+            local_position: Vec2::new(10.0, 10.0),
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    /// Register a scrollable element that passes events on while `passing`
+    /// is set, recording the phases it keeps
+    fn scrollable(
+        registry: &mut ElementRegistry,
+        id: u64,
+        passing: Rc<Cell<bool>>,
+    ) -> Rc<RefCell<Vec<ScrollPhase>>> {
+        let kept = Rc::new(RefCell::new(Vec::new()));
+        let record = kept.clone();
+        let handlers = EventHandlers::new().on_scroll_gesture(move |_, phase, _, _| {
+            if passing.get() && can_pass_scroll() {
+                pass_scroll();
+            } else {
+                record.borrow_mut().push(phase);
+            }
+        });
+        registry.register(ElementId::new(id), Rc::new(RefCell::new(handlers)));
+        kept
+    }
+
+    fn dispatch(
+        registry: &mut ElementRegistry,
+        delta: Vec2,
+        phase: ScrollPhase,
+    ) -> Option<ElementId> {
+        let candidates = [1, 2, 3].map(ElementId::new);
+        registry
+            .dispatch_scroll(&scroll_event(delta, phase), &candidates, |_| Vec2::ZERO)
+            .and_then(|event| event.element_id())
+    }
+
+    #[test]
+    fn test_scroll_passes_to_parent() {
+        let mut registry = ElementRegistry::new();
+        // Element 1 is a button without scroll handlers
+        registry.register(
+            ElementId::new(1),
+            Rc::new(RefCell::new(EventHandlers::new())),
+        );
+        let inner_passing = Rc::new(Cell::new(true));
+        let inner = scrollable(&mut registry, 2, inner_passing.clone());
+        let outer = scrollable(&mut registry, 3, Rc::new(Cell::new(true)));
+
+        let wheel = Vec2::new(0.0, -10.0);
+        assert_eq!(
+            dispatch(&mut registry, wheel, ScrollPhase::None),
+            Some(ElementId::new(3))
+        );
+        // The last element can't pass, so it keeps the event
+        assert_eq!(*outer.borrow(), vec![ScrollPhase::None]);
+
+        inner_passing.set(false);
+        assert_eq!(
+            dispatch(&mut registry, wheel, ScrollPhase::None),
+            Some(ElementId::new(2))
+        );
+        assert_eq!(*inner.borrow(), vec![ScrollPhase::None]);
+    }
+
+    #[test]
+    fn test_gesture_latches_to_first_taker() {
+        let mut registry = ElementRegistry::new();
+        let inner_passing = Rc::new(Cell::new(false));
+        let inner = scrollable(&mut registry, 2, inner_passing.clone());
+        let outer = scrollable(&mut registry, 3, Rc::new(Cell::new(true)));
+
+        // Held until the gesture moves
+        assert_eq!(
+            dispatch(&mut registry, Vec2::ZERO, ScrollPhase::Began),
+            None
+        );
+        let delta = Vec2::new(0.0, -10.0);
+        assert_eq!(
+            dispatch(&mut registry, delta, ScrollPhase::Changed),
+            Some(ElementId::new(2))
+        );
+
+        // Stays with the inner element even once it would pass
+        inner_passing.set(true);
+        dispatch(&mut registry, delta, ScrollPhase::Changed);
+        dispatch(&mut registry, Vec2::ZERO, ScrollPhase::Ended);
+        assert_eq!(
+            *inner.borrow(),
+            vec![ScrollPhase::Began, ScrollPhase::Changed, ScrollPhase::Ended]
+        );
+        assert!(outer.borrow().is_empty());
+
+        // The next gesture is arbitrated afresh
+        dispatch(&mut registry, Vec2::ZERO, ScrollPhase::Began);
+        assert_eq!(
+            dispatch(&mut registry, delta, ScrollPhase::Changed),
+            Some(ElementId::new(3))
+        );
+    }
+}
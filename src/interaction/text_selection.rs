@@ -0,0 +1,196 @@
+//! App-wide selection of static text
+//!
+//! In text selection mode, `text()` elements can be selected by dragging
+//! across them, like labels in a web view, and Cmd+C copies the selection
+//! when no element has keyboard focus. A drag can span several texts; they
+//! are ordered by when they paint, and copied with a line break between
+//! each.
+//!
+//! Turn the mode on with [`set_text_selection_enabled`]. While it's on,
+//! static texts catch mouse presses instead of the elements under them.
+
+use std::cell::RefCell;
+use std::ops::Range;
+
+thread_local! {
+    static TEXT_SELECTION: RefCell<TextSelection> = RefCell::new(TextSelection::default());
+}
+
+/// A position in the selectable texts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct SelectionPoint {
+    /// Index of the text in paint order
+    pub text: usize,
+    /// Byte offset into the text
+    pub offset: usize,
+}
+
+#[derive(Debug, Default)]
+struct TextSelection {
+    enabled: bool,
+    /// Where the selection started
+    anchor: Option<SelectionPoint>,
+    /// Where the selection ends, following the mouse while dragging
+    focus: Option<SelectionPoint>,
+    dragging: bool,
+    /// Content of each selectable text, in paint order
+    texts: Vec<String>,
+    /// Number of texts registered so far this frame
+    painted: usize,
+}
+
+impl TextSelection {
+    /// Start and end of the selection, in order
+    fn bounds(&self) -> Option<(SelectionPoint, SelectionPoint)> {
+        let (anchor, focus) = self.anchor.zip(self.focus)?;
+        Some((anchor.min(focus), anchor.max(focus)))
+    }
+
+    fn range(&self, text: usize, len: usize) -> Option<Range<usize>> {
+        let (start, end) = self.bounds()?;
+        if text < start.text || text > end.text {
+            return None;
+        }
+        let from = if text == start.text { start.offset } else { 0 };
+        let to = if text == end.text { end.offset } else { len };
+        (from < to).then_some(from..to.min(len))
+    }
+
+    fn selected_text(&self) -> Option<String> {
+        let pieces: Vec<&str> = self
+            .texts
+            .iter()
+            .enumerate()
+            .filter_map(|(index, text)| {
+                let range = self.range(index, text.len())?;
+                text.get(range)
+            })
+            .collect();
+        (!pieces.is_empty()).then(|| pieces.join("\n"))
+    }
+}
+
+/// Turn app-wide text selection on or off
+///
+/// Turning it off clears the selection.
+pub fn set_text_selection_enabled(enabled: bool) {
+    TEXT_SELECTION.with(|s| {
+        let mut selection = s.borrow_mut();
+        selection.enabled = enabled;
+        if !enabled {
+            selection.anchor = None;
+            selection.focus = None;
+            selection.dragging = false;
+        }
+    });
+}
+
+/// Whether static text can be selected
+pub fn text_selection_enabled() -> bool {
+    TEXT_SELECTION.with(|s| s.borrow().enabled)
+}
+
+/// The selected text, with a line break between texts
+pub fn selected_text() -> Option<String> {
+    TEXT_SELECTION.with(|s| s.borrow().selected_text())
+}
+
+/// Deselect all static text
+pub fn clear_text_selection() {
+    TEXT_SELECTION.with(|s| {
+        let mut selection = s.borrow_mut();
+        selection.anchor = None;
+        selection.focus = None;
+    });
+}
+
+/// Copy the selected text to the clipboard, returning whether there was any
+/// and the platform has a clipboard to copy it to
+pub(crate) fn copy_text_selection() -> bool {
+    #[cfg(target_os = "macos")]
+    return selected_text().is_some_and(|text| crate::platform::Clipboard::copy(&text));
+    #[cfg(not(target_os = "macos"))]
+    false
+}
+
+/// Forget which texts were painted, before a full frame paints them again
+pub(crate) fn begin_text_selection_frame() {
+    TEXT_SELECTION.with(|s| {
+        let mut selection = s.borrow_mut();
+        let painted = selection.painted;
+        selection.texts.truncate(painted);
+        selection.painted = 0;
+    });
+}
+
+/// Register a text painted this frame, returning its index in paint order
+pub(crate) fn register_selectable_text(content: &str) -> usize {
+    TEXT_SELECTION.with(|s| {
+        let mut selection = s.borrow_mut();
+        let index = selection.painted;
+        selection.painted += 1;
+        match selection.texts.get_mut(index) {
+            Some(text) if *text != *content => *text = content.to_string(),
+            Some(_) => {}
+            None => selection.texts.push(content.to_string()),
+        }
+        index
+    })
+}
+
+/// The selected byte range of the text at `index`, if any
+pub(crate) fn selected_range(index: usize, len: usize) -> Option<Range<usize>> {
+    TEXT_SELECTION.with(|s| s.borrow().range(index, len))
+}
+
+/// Start selecting at `point`
+pub(crate) fn start_text_drag(point: SelectionPoint) {
+    TEXT_SELECTION.with(|s| {
+        let mut selection = s.borrow_mut();
+        selection.anchor = Some(point);
+        selection.focus = Some(point);
+        selection.dragging = true;
+    });
+}
+
+/// Move the end of the selection to `point` while dragging
+pub(crate) fn extend_text_drag(point: SelectionPoint) {
+    TEXT_SELECTION.with(|s| {
+        let mut selection = s.borrow_mut();
+        if selection.dragging {
+            selection.focus = Some(point);
+        }
+    });
+}
+
+/// Stop following the mouse, keeping the selection
+pub(crate) fn end_text_drag() {
+    TEXT_SELECTION.with(|s| s.borrow_mut().dragging = false);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selection_across_texts() {
+        set_text_selection_enabled(true);
+        begin_text_selection_frame();
+        for text in ["first", "second", "third"] {
+            register_selectable_text(text);
+        }
+
+        // Drag backwards from the middle of "third" into "first"
+        start_text_drag(SelectionPoint { text: 2, offset: 2 });
+        extend_text_drag(SelectionPoint { text: 0, offset: 3 });
+        end_text_drag();
+        extend_text_drag(SelectionPoint { text: 1, offset: 0 });
+
+        assert_eq!(selected_range(0, 5), Some(3..5));
+        assert_eq!(selected_range(1, 6), Some(0..6));
+        assert_eq!(selected_text().as_deref(), Some("st\nsecond\nth"));
+
+        set_text_selection_enabled(false);
+        assert_eq!(selected_text(), None);
+    }
+}
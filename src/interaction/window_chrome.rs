@@ -0,0 +1,196 @@
+//! Window drag and resize regions for custom window chrome
+//!
+//! A window with a hidden titlebar loses the areas the system uses to move
+//! and resize it. Elements marked with
+//! [`window_drag_region`](super::InteractiveElement::window_drag_region) or
+//! [`window_resize_edge`](super::InteractiveElement::window_resize_edge) stand
+//! in for them: after each full frame the UI layers publish where those
+//! elements are, and the platform layer checks mouse downs against that map
+//! before they reach the app, starting a native window move or resize.
+//!
+//! Regions take part in hit testing like any other element, so a button
+//! inside a custom titlebar still gets its clicks.
+
+use super::{ElementId, HitTestEntry};
+use crate::geometry::{Point, Rect};
+use glam::Vec2;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// An edge or corner of the window that can be dragged to resize it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WindowEdge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl WindowEdge {
+    fn moves_left(self) -> bool {
+        matches!(
+            self,
+            WindowEdge::Left | WindowEdge::TopLeft | WindowEdge::BottomLeft
+        )
+    }
+
+    fn moves_right(self) -> bool {
+        matches!(
+            self,
+            WindowEdge::Right | WindowEdge::TopRight | WindowEdge::BottomRight
+        )
+    }
+
+    fn moves_top(self) -> bool {
+        matches!(
+            self,
+            WindowEdge::Top | WindowEdge::TopLeft | WindowEdge::TopRight
+        )
+    }
+
+    fn moves_bottom(self) -> bool {
+        matches!(
+            self,
+            WindowEdge::Bottom | WindowEdge::BottomLeft | WindowEdge::BottomRight
+        )
+    }
+
+    /// Resize `frame` by dragging this edge by `delta`
+    ///
+    /// Coordinates have y increasing downwards. The opposite edges stay in
+    /// place, and the frame doesn't shrink below `min_size`.
+    pub fn resize(self, frame: Rect, delta: Vec2, min_size: Vec2) -> Rect {
+        let (mut left, mut top) = (frame.pos.x, frame.pos.y);
+        let (mut right, mut bottom) = (left + frame.size.x, top + frame.size.y);
+
+        if self.moves_left() {
+            left = (left + delta.x).min(right - min_size.x);
+        }
+        if self.moves_right() {
+            right = (right + delta.x).max(left + min_size.x);
+        }
+        if self.moves_top() {
+            top = (top + delta.y).min(bottom - min_size.y);
+        }
+        if self.moves_bottom() {
+            bottom = (bottom + delta.y).max(top + min_size.y);
+        }
+        Rect::new(left, top, right - left, bottom - top)
+    }
+}
+
+/// What a mouse down on a chrome element does to the window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WindowChrome {
+    /// Move the window, like its titlebar
+    Drag,
+    /// Resize the window from an edge or corner
+    Resize(WindowEdge),
+}
+
+/// A published layer's hit areas in hit test order, with the chrome of each
+type LayerChrome = Vec<(Rect, Option<WindowChrome>)>;
+
+thread_local! {
+    /// Chrome elements registered while painting the current layer
+    static CURRENT_CHROME: RefCell<HashMap<ElementId, WindowChrome>> = RefCell::new(HashMap::new());
+    /// Hit areas of each input layer from the last full frame, bottom layer
+    /// first, each in hit test order
+    static CHROME_MAP: RefCell<Vec<LayerChrome>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Mark an element painted this frame as window chrome
+pub fn register_window_chrome(id: ElementId, chrome: WindowChrome) {
+    CURRENT_CHROME.with(|c| {
+        c.borrow_mut().insert(id, chrome);
+    });
+}
+
+/// Start a new chrome map for a full frame
+pub(crate) fn begin_chrome_frame() {
+    CHROME_MAP.with(|m| m.borrow_mut().clear());
+}
+
+/// Publish a painted layer's hit test entries along with its chrome elements
+///
+/// Layers must be published from bottom to top. Every entry is kept, not
+/// just chrome, so ordinary elements can shadow regions below them.
+pub(crate) fn publish_layer_chrome(entries: &[HitTestEntry]) {
+    let chrome = CURRENT_CHROME.with(|c| std::mem::take(&mut *c.borrow_mut()));
+    let layer = entries
+        .iter()
+        .map(|entry| (entry.bounds, chrome.get(&entry.element_id).copied()))
+        .collect();
+    CHROME_MAP.with(|m| m.borrow_mut().push(layer));
+}
+
+/// Drop chrome registered by a layer that won't be published
+pub(crate) fn discard_layer_chrome() {
+    CURRENT_CHROME.with(|c| c.borrow_mut().clear());
+}
+
+/// Find the window chrome at `position`, in window content coordinates
+///
+/// Returns `None` if the topmost element there isn't chrome.
+pub fn window_chrome_at(position: Vec2) -> Option<WindowChrome> {
+    CHROME_MAP.with(|m| {
+        m.borrow().iter().rev().find_map(|layer| {
+            layer
+                .iter()
+                .find(|(bounds, _)| bounds.contains(Point::from(position)))
+                .map(|(_, chrome)| *chrome)
+        })
+    })?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resize_from_corner() {
+        let frame = Rect::new(100.0, 100.0, 400.0, 300.0);
+        let min_size = Vec2::new(200.0, 150.0);
+
+        let resized = WindowEdge::TopLeft.resize(frame, Vec2::new(-50.0, 20.0), min_size);
+        assert_eq!(resized, Rect::new(50.0, 120.0, 450.0, 280.0));
+
+        // Shrinking stops at the minimum size with the opposite edges fixed
+        let resized = WindowEdge::BottomRight.resize(frame, Vec2::new(-500.0, -500.0), min_size);
+        assert_eq!(resized, Rect::new(100.0, 100.0, 200.0, 150.0));
+        let resized = WindowEdge::Left.resize(frame, Vec2::new(500.0, 0.0), min_size);
+        assert_eq!(resized, Rect::new(300.0, 100.0, 200.0, 300.0));
+    }
+
+    #[test]
+    fn test_chrome_shadowed_by_elements_above() {
+        let titlebar = ElementId::stable("chrome:titlebar");
+        let close = ElementId::stable("chrome:close");
+
+        begin_chrome_frame();
+        register_window_chrome(titlebar, WindowChrome::Drag);
+        // Hit test order: the close button inside the titlebar comes first
+        publish_layer_chrome(&[
+            HitTestEntry::new(close, Rect::new(8.0, 8.0, 12.0, 12.0), 0, 0),
+            HitTestEntry::new(titlebar, Rect::new(0.0, 0.0, 800.0, 28.0), 0, 0),
+        ]);
+
+        assert_eq!(
+            window_chrome_at(Vec2::new(400.0, 10.0)),
+            Some(WindowChrome::Drag)
+        );
+        assert_eq!(window_chrome_at(Vec2::new(12.0, 12.0)), None);
+        assert_eq!(window_chrome_at(Vec2::new(400.0, 100.0)), None);
+
+        // An empty spot in a layer above doesn't hide the region
+        publish_layer_chrome(&[]);
+        assert_eq!(
+            window_chrome_at(Vec2::new(400.0, 10.0)),
+            Some(WindowChrome::Drag)
+        );
+    }
+}
@@ -1,19 +1,53 @@
+#[cfg(target_os = "macos")]
+use crate::platform::mac::metal_renderer::{self, MetalRenderer, MetalTarget};
 use crate::{
-    element::{Element, LayoutContext},
+    caret::schedule_blink,
+    clock::set_frame_time,
+    color::{Color, colors},
+    debug::{DebugConsole, begin_layer_paint_profile, end_paint_profile_frame},
+    element::{
+        Element, LayoutContext, ScrollAlignment, clear_measure_context, clear_painting_areas,
+        clear_scroll_into_view_requests, end_memo_frame, request_scroll_into_view,
+        set_measure_context,
+    },
     entity::{EntityStore, clear_entity_store, set_entity_store},
+    error::SolResult,
+    geometry::Rect,
+    i18n::take_locale_change,
+    image::RgbaImage,
     interaction::{
-        InteractionSystem,
+        ElementId, InteractionEvent, InteractionSystem,
         hit_test::HitTestBuilder,
-        registry::{ElementRegistry, clear_current_registry, set_current_registry},
+        lifecycle::{LifecycleTracker, clear_current_lifecycle, set_current_lifecycle},
+        observer::{ElementInfo, has_interaction_observers, notify_interaction_observers},
+        registry::{
+            ElementRegistry, clear_current_registry, clear_style_states, set_current_registry,
+        },
+        text_selection::begin_text_selection_frame,
+        window_chrome::{begin_chrome_frame, discard_layer_chrome, publish_layer_chrome},
     },
     layout_engine::TaffyLayoutEngine,
-    platform::mac::metal_renderer::MetalRenderer,
-    render::{DrawList, PaintContext},
+    post_process::PostProcess,
+    render::{DrawCommand, DrawList, PaintContext},
+    renderer::{Composite, FrameInfo, Renderer},
+    style::TextStyle,
+    text_system::TextSystem,
+    vibrancy::begin_material_frame,
 };
 use glam::Vec2;
+#[cfg(target_os = "macos")]
 use metal::CommandBufferRef;
+use serde::{Deserialize, Serialize};
 use std::any::Any;
-use tracing::{debug, info, info_span};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::rc::Rc;
+use std::time::Duration;
+#[cfg(target_os = "macos")]
+use tracing::warn;
+use tracing::{debug, error, info, info_span};
 
 /// Options for configuring a layer
 #[derive(Debug, Clone)]
@@ -22,12 +56,22 @@ pub struct LayerOptions {
     pub z_index: i32,
     /// Whether this layer receives input events
     pub receives_input: bool,
+    /// Whether mouse events that miss this layer's elements reach the
+    /// layers below
+    pub input_passthrough: bool,
     /// Blend mode for compositing
     pub blend_mode: BlendMode,
-    /// Whether to clear before rendering
+    /// Whether to clear everything below before rendering, rather than
+    /// drawing over it
     pub clear: bool,
-    /// Clear color (if clearing is enabled
-    pub clear_color: metal::MTLClearColor,
+    /// Clear color (if clearing is enabled)
+    pub clear_color: Color,
+    /// Initial opacity of the whole layer, from 0.0 to 1.0
+    pub opacity: f32,
+    /// Whether rects, frames and borders are snapped to device pixels
+    pub snap_to_pixels: bool,
+    /// Shader passes run over the layer's content, in order
+    pub post_processes: Vec<Rc<dyn PostProcess>>,
 }
 
 impl Default for LayerOptions {
@@ -35,9 +79,13 @@ impl Default for LayerOptions {
         Self {
             z_index: 0,
             receives_input: false,
+            input_passthrough: false,
             blend_mode: BlendMode::Alpha,
             clear: false,
-            clear_color: metal::MTLClearColor::new(0.0, 0.0, 0.0, 0.0),
+            clear_color: colors::TRANSPARENT,
+            opacity: 1.0,
+            snap_to_pixels: true,
+            post_processes: Vec::new(),
         }
     }
 }
@@ -57,6 +105,17 @@ impl LayerOptions {
         self
     }
 
+    /// Let mouse events that don't land on one of this layer's elements
+    /// through to the layers below
+    ///
+    /// Without it, an input layer takes every mouse event, even over empty
+    /// space. Useful for overlays that cover the window but only have a few
+    /// interactive parts, such as a toolbar floating over a canvas.
+    pub fn input_passthrough(mut self, passthrough: bool) -> Self {
+        self.input_passthrough = passthrough;
+        self
+    }
+
     /// Set the blend mode
     pub fn with_blend_mode(mut self, mode: BlendMode) -> Self {
         self.blend_mode = mode;
@@ -71,11 +130,161 @@ impl LayerOptions {
 
     /// Set the clear color
     pub fn with_clear_color(mut self, r: f64, g: f64, b: f64, a: f64) -> Self {
-        self.clear_color = metal::MTLClearColor::new(r, g, b, a);
+        self.clear_color = Color::new(r as f32, g as f32, b as f32, a as f32);
+        self
+    }
+
+    /// Clear everything below to `color` before drawing this layer
+    ///
+    /// A transparent color gives the layer a see-through background over
+    /// whatever the window composites beneath it, e.g. a vibrancy view.
+    pub fn clear_color(mut self, color: Color) -> Self {
+        self.clear = true;
+        self.clear_color = color;
+        self
+    }
+
+    /// Set whether this layer draws over the layers below it
+    ///
+    /// On by default for every layer but the first. Turning it off clears
+    /// the layers below to the clear color first; turning it back on keeps
+    /// them even if a clear color was set.
+    pub fn preserve_below(mut self, preserve: bool) -> Self {
+        self.clear = !preserve;
+        self
+    }
+
+    /// Color to clear to before drawing this layer, or `None` to draw over
+    /// the layers below
+    ///
+    /// The first layer always clears, to the window background unless it
+    /// has a clear color of its own, since the drawable starts out undefined.
+    pub(crate) fn clear_to(&self, is_first_layer: bool) -> Option<Color> {
+        if self.clear {
+            Some(self.clear_color)
+        } else if is_first_layer {
+            Some(colors::GRAY_100)
+        } else {
+            None
+        }
+    }
+
+    /// Set whether rects, frames and borders snap to device pixels
+    ///
+    /// On by default, which keeps 1px lines and edges crisp at fractional
+    /// positions. Turn it off for content that moves or scales smoothly in
+    /// sub-pixel steps, where snapping would make it jitter.
+    pub fn with_snap_to_pixels(mut self, snap: bool) -> Self {
+        self.snap_to_pixels = snap;
+        self
+    }
+
+    /// Set the initial opacity of the layer's content
+    ///
+    /// Change it later through the layer's [`LayerHandle`].
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Run a post-process pass over the layer's content, after any added
+    /// before it
+    ///
+    /// Keep a clone of the `Rc` to adjust the pass while the app runs. Only
+    /// UI layers apply passes; raw layers draw straight to the window.
+    pub fn with_post_process(mut self, pass: Rc<dyn PostProcess>) -> Self {
+        self.post_processes.push(pass);
         self
     }
 }
 
+/// Handle for changing a layer's opacity while the app runs
+///
+/// Get one from [`LayerManager::layer_handle`]. Clones control the same
+/// layer. Opacity is applied when the layer is composited, so elements keep
+/// their own colors; overlapping content within the layer is faded per draw
+/// rather than as a flattened group.
+#[derive(Debug, Clone)]
+pub struct LayerHandle {
+    inner: Rc<RefCell<LayerHandleState>>,
+}
+
+#[derive(Debug)]
+struct LayerHandleState {
+    opacity: f32,
+    fade: Option<Fade>,
+}
+
+#[derive(Debug)]
+struct Fade {
+    from: f32,
+    to: f32,
+    duration: f32,
+    /// Frame time the fade started, set on the first frame that renders it
+    start: Option<f32>,
+}
+
+impl LayerHandle {
+    fn new(opacity: f32) -> Self {
+        Self {
+            inner: Rc::new(RefCell::new(LayerHandleState {
+                opacity,
+                fade: None,
+            })),
+        }
+    }
+
+    /// Current opacity of the layer
+    pub fn opacity(&self) -> f32 {
+        self.inner.borrow().opacity
+    }
+
+    /// Set the opacity immediately, cancelling any fade
+    pub fn set_opacity(&self, opacity: f32) {
+        let mut state = self.inner.borrow_mut();
+        state.opacity = opacity.clamp(0.0, 1.0);
+        state.fade = None;
+    }
+
+    /// Animate from the current opacity to `opacity` over `duration`
+    pub fn fade_to(&self, opacity: f32, duration: Duration) {
+        let mut state = self.inner.borrow_mut();
+        state.fade = Some(Fade {
+            from: state.opacity,
+            to: opacity.clamp(0.0, 1.0),
+            duration: duration.as_secs_f32(),
+            start: None,
+        });
+    }
+
+    /// Check if a fade is in progress
+    pub fn is_fading(&self) -> bool {
+        self.inner.borrow().fade.is_some()
+    }
+
+    /// Advance any fade to the frame clock `time` and return the opacity
+    fn update(&self, time: f32) -> f32 {
+        let mut state = self.inner.borrow_mut();
+        let Some(fade) = state.fade.as_mut() else {
+            return state.opacity;
+        };
+
+        let start = *fade.start.get_or_insert(time);
+        let t = if fade.duration > 0.0 {
+            ((time - start) / fade.duration).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        let eased = t * t * (3.0 - 2.0 * t);
+        let opacity = fade.from + (fade.to - fade.from) * eased;
+        if t >= 1.0 {
+            state.fade = None;
+        }
+        state.opacity = opacity;
+        opacity
+    }
+}
+
 /// Blend modes for layer compositing
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BlendMode {
@@ -103,20 +312,38 @@ pub trait Layer: Any {
     /// Get layer options
     fn options(&self) -> &LayerOptions;
 
-    /// Render this layer
+    /// Render this layer into `frame`
     fn render(
         &mut self,
-        renderer: &mut MetalRenderer,
-        command_buffer: &CommandBufferRef,
-        drawable: &metal::MetalDrawableRef,
-        size: Vec2,
-        scale_factor: f32,
-        text_system: &mut crate::text_system::TextSystem,
+        frame: &mut dyn LayerFrame,
+        text_system: &mut TextSystem,
         is_first_layer: bool,
         animation_frame_requested: &mut bool,
         elapsed_time: f32,
     );
 
+    /// Render a frame in which only time-based overlays, such as blinking
+    /// carets, have changed
+    ///
+    /// Layers that cache their content can redraw it without rebuilding. The
+    /// default renders the layer normally.
+    fn render_overlays(
+        &mut self,
+        frame: &mut dyn LayerFrame,
+        text_system: &mut TextSystem,
+        is_first_layer: bool,
+        animation_frame_requested: &mut bool,
+        elapsed_time: f32,
+    ) {
+        self.render(
+            frame,
+            text_system,
+            is_first_layer,
+            animation_frame_requested,
+            elapsed_time,
+        );
+    }
+
     /// Handle input events
     fn handle_input(&mut self, _event: &InputEvent) -> bool {
         false
@@ -129,15 +356,142 @@ pub trait Layer: Any {
     fn invalidate(&mut self) {
         // Default implementation does nothing
     }
+
+    /// Get a handle for changing this layer's opacity, if it supports one
+    fn layer_handle(&self) -> Option<LayerHandle> {
+        None
+    }
+
+    /// Where an element was painted last frame, in window coordinates
+    fn element_bounds(&self, _element_id: ElementId) -> Option<Rect> {
+        None
+    }
+
+    /// Elements whose hit area contained `position` last frame, topmost first
+    fn hit_test(&self, _position: Vec2) -> Vec<ElementId> {
+        Vec::new()
+    }
+}
+
+/// The renderer and target a layer draws into this frame
+///
+/// Layers render through this rather than a particular [`Renderer`], so the
+/// same layers can be drawn into a window by the Metal renderer or into an
+/// image by the [software renderer](crate::software_renderer::SoftwareRenderer).
+pub trait LayerFrame {
+    /// Size of the target in points
+    fn size(&self) -> Vec2;
+
+    /// Device pixels per point
+    fn scale_factor(&self) -> f32;
+
+    /// Render a draw list into the target
+    fn draw(&mut self, draw_list: &DrawList, composite: Composite, text_system: &mut TextSystem);
+
+    /// Render a draw list into the target through post-process passes
+    fn draw_post_processed(
+        &mut self,
+        draw_list: &DrawList,
+        composite: Composite,
+        passes: &[Rc<dyn PostProcess>],
+        time: f32,
+        text_system: &mut TextSystem,
+    );
+
+    /// Render `bounds` of a draw list into an image
+    fn capture(
+        &mut self,
+        draw_list: &DrawList,
+        bounds: Rect,
+        text_system: &mut TextSystem,
+    ) -> SolResult<RgbaImage>;
+
+    /// The Metal renderer and drawable, if the frame is rendered with Metal
+    #[cfg(target_os = "macos")]
+    fn metal(&mut self) -> Option<(&mut MetalRenderer, MetalTarget<'_>)>;
+}
+
+/// A [`LayerFrame`] rendering into `target` with `renderer`
+struct RendererFrame<'a, 'b, R: Renderer> {
+    renderer: &'a mut R,
+    target: &'a mut R::Target<'b>,
+    size: Vec2,
+    scale_factor: f32,
+}
+
+impl<R: Renderer> LayerFrame for RendererFrame<'_, '_, R> {
+    fn size(&self) -> Vec2 {
+        self.size
+    }
+
+    fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+
+    fn draw(&mut self, draw_list: &DrawList, composite: Composite, text_system: &mut TextSystem) {
+        self.renderer.render_draw_list(
+            draw_list,
+            self.target,
+            composite,
+            self.size,
+            self.scale_factor,
+            text_system,
+        );
+    }
+
+    fn draw_post_processed(
+        &mut self,
+        draw_list: &DrawList,
+        composite: Composite,
+        passes: &[Rc<dyn PostProcess>],
+        time: f32,
+        text_system: &mut TextSystem,
+    ) {
+        let frame = FrameInfo {
+            size: self.size,
+            scale_factor: self.scale_factor,
+            elapsed_time: time,
+        };
+        self.renderer.render_post_processed(
+            draw_list,
+            self.target,
+            composite,
+            passes,
+            frame,
+            text_system,
+        );
+    }
+
+    fn capture(
+        &mut self,
+        draw_list: &DrawList,
+        bounds: Rect,
+        text_system: &mut TextSystem,
+    ) -> SolResult<RgbaImage> {
+        self.renderer
+            .capture(draw_list, bounds, self.scale_factor, text_system)
+    }
+
+    #[cfg(target_os = "macos")]
+    fn metal(&mut self) -> Option<(&mut MetalRenderer, MetalTarget<'_>)> {
+        self.renderer.metal(self.target)
+    }
 }
 
+#[cfg(target_os = "macos")]
 /// A raw layer with direct shader access
+///
+/// Raw layers encode Metal commands, so they are skipped when the app
+/// renders with another backend.
 pub struct RawLayer<F> {
     z_index: i32,
     options: LayerOptions,
     render_fn: F,
+    /// Whether the layer has logged that it can't render with this backend
+    warned_unsupported: bool,
 }
 
+#[cfg(target_os = "macos")]
 impl<F> RawLayer<F>
 where
     F: for<'a> FnMut(&mut RawLayerContext<'a>) + Any,
@@ -147,10 +501,12 @@ where
             z_index,
             options,
             render_fn,
+            warned_unsupported: false,
         }
     }
 }
 
+#[cfg(target_os = "macos")]
 /// Context provided to raw layer render functions
 pub struct RawLayerContext<'a> {
     pub renderer: &'a mut MetalRenderer,
@@ -161,6 +517,7 @@ pub struct RawLayerContext<'a> {
     animation_frame_requested: &'a mut bool,
 }
 
+#[cfg(target_os = "macos")]
 impl<'a> RawLayerContext<'a> {
     /// Request that another frame be rendered immediately after this one
     pub fn request_animation_frame(&mut self) {
@@ -191,6 +548,7 @@ impl<'a> RawLayerContext<'a> {
     }
 }
 
+#[cfg(target_os = "macos")]
 impl<F> Layer for RawLayer<F>
 where
     F: for<'a> FnMut(&mut RawLayerContext<'a>) + Any,
@@ -205,22 +563,29 @@ where
 
     fn render(
         &mut self,
-        renderer: &mut MetalRenderer,
-        command_buffer: &CommandBufferRef,
-        drawable: &metal::MetalDrawableRef,
-        size: Vec2,
-        _scale_factor: f32,
-        _text_system: &mut crate::text_system::TextSystem,
+        frame: &mut dyn LayerFrame,
+        _text_system: &mut TextSystem,
         _is_first_layer: bool,
         animation_frame_requested: &mut bool,
         elapsed_time: f32,
     ) {
         let _raw_render_span = info_span!("raw_layer_render").entered();
 
+        let size = frame.size();
+        let Some((renderer, target)) = frame.metal() else {
+            if !self.warned_unsupported {
+                warn!(
+                    "Skipping raw layer (z-index {}): it needs the Metal renderer",
+                    self.z_index
+                );
+                self.warned_unsupported = true;
+            }
+            return;
+        };
         let mut ctx = RawLayerContext {
             renderer,
-            command_buffer,
-            drawable,
+            command_buffer: target.command_buffer,
+            drawable: target.drawable,
             size,
             time: elapsed_time,
             animation_frame_requested,
@@ -238,6 +603,219 @@ where
     }
 }
 
+#[cfg(target_os = "macos")]
+/// A texture rendered outside sol-ui, shared between the host and an
+/// [`ExternalLayer`]
+///
+/// Clones refer to the same slot. The host sets the texture it renders its
+/// scene into, and the layer draws whatever is set each frame. Rendering on
+/// another command queue must finish before the window's frame is committed.
+#[derive(Clone, Default)]
+pub struct ExternalTexture {
+    texture: Rc<RefCell<Option<metal::Texture>>>,
+}
+
+#[cfg(target_os = "macos")]
+impl ExternalTexture {
+    /// Create an empty slot
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Show `texture` from the next frame on, stretched over the window
+    pub fn set(&self, texture: metal::Texture) {
+        *self.texture.borrow_mut() = Some(texture);
+    }
+
+    /// Stop showing a texture
+    pub fn clear(&self) {
+        self.texture.borrow_mut().take();
+    }
+
+    /// The texture currently shown
+    pub fn get(&self) -> Option<metal::Texture> {
+        self.texture.borrow().clone()
+    }
+}
+
+#[cfg(target_os = "macos")]
+/// Context provided to external layer render callbacks
+pub struct ExternalLayerContext<'a> {
+    pub command_buffer: &'a CommandBufferRef,
+    pub drawable: &'a metal::MetalDrawableRef,
+    pub size: Vec2,
+    pub scale_factor: f32,
+    pub time: f32,
+    /// Load action and clear color the layer's first render pass should use
+    pub load_action: (metal::MTLLoadAction, metal::MTLClearColor),
+    animation_frame_requested: &'a mut bool,
+}
+
+#[cfg(target_os = "macos")]
+impl<'a> ExternalLayerContext<'a> {
+    /// Request that another frame be rendered immediately after this one
+    pub fn request_animation_frame(&mut self) {
+        *self.animation_frame_requested = true;
+    }
+}
+
+#[cfg(target_os = "macos")]
+enum ExternalSource {
+    Texture(ExternalTexture),
+    Render(Box<dyn for<'a> FnMut(&mut ExternalLayerContext<'a>)>),
+}
+
+#[cfg(target_os = "macos")]
+/// A layer showing content rendered outside sol-ui, such as a game
+/// engine's 3D scene, for compositing UI layers over it
+///
+/// The content is Metal textures and commands, so with another backend the
+/// layer only clears, if it's set to.
+///
+/// Put it below the UI and give the UI layers
+/// [`input_passthrough`](LayerOptions::input_passthrough), so mouse events
+/// that miss the UI reach [`on_input`](Self::on_input).
+///
+/// ```ignore
+/// let scene = ExternalTexture::new();
+/// layers.add_external_layer(
+///     0,
+///     LayerOptions::default(),
+///     ExternalLayer::from_texture(&scene).on_input(|event| camera.handle(event)),
+/// );
+/// layers.add_ui_layer(1, LayerOptions::default().with_input().input_passthrough(true), hud);
+/// ```
+pub struct ExternalLayer {
+    options: LayerOptions,
+    source: ExternalSource,
+    on_input: Option<Box<dyn FnMut(&InputEvent) -> bool>>,
+    /// Whether the layer has logged that it can't render with this backend
+    warned_unsupported: bool,
+}
+
+#[cfg(target_os = "macos")]
+impl ExternalLayer {
+    /// Show a texture the host renders into
+    pub fn from_texture(texture: &ExternalTexture) -> Self {
+        Self {
+            options: LayerOptions::default(),
+            source: ExternalSource::Texture(texture.clone()),
+            on_input: None,
+            warned_unsupported: false,
+        }
+    }
+
+    /// Let the host encode its own rendering into the frame's command
+    /// buffer each frame
+    pub fn from_render<F>(render_fn: F) -> Self
+    where
+        F: for<'a> FnMut(&mut ExternalLayerContext<'a>) + 'static,
+    {
+        Self {
+            options: LayerOptions::default(),
+            source: ExternalSource::Render(Box::new(render_fn)),
+            on_input: None,
+            warned_unsupported: false,
+        }
+    }
+
+    /// Forward input events that the layers above don't consume to the
+    /// host, which returns whether it consumed them
+    ///
+    /// This turns on input for the layer.
+    pub fn on_input(mut self, handler: impl FnMut(&InputEvent) -> bool + 'static) -> Self {
+        self.on_input = Some(Box::new(handler));
+        self
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl Layer for ExternalLayer {
+    fn z_index(&self) -> i32 {
+        self.options.z_index
+    }
+
+    fn options(&self) -> &LayerOptions {
+        &self.options
+    }
+
+    fn render(
+        &mut self,
+        frame: &mut dyn LayerFrame,
+        text_system: &mut TextSystem,
+        is_first_layer: bool,
+        animation_frame_requested: &mut bool,
+        elapsed_time: f32,
+    ) {
+        let _external_render_span = info_span!("external_layer_render").entered();
+        let clear = self.options.clear_to(is_first_layer);
+        let cleared = Composite {
+            clear,
+            opacity: 1.0,
+        };
+        let (size, scale_factor) = (frame.size(), frame.scale_factor());
+
+        let Some((renderer, mut target)) = frame.metal() else {
+            if !self.warned_unsupported {
+                warn!(
+                    "Skipping external layer (z-index {}): it needs the Metal renderer",
+                    self.options.z_index
+                );
+                self.warned_unsupported = true;
+            }
+            // The layers above may still expect the drawable cleared
+            if clear.is_some() {
+                frame.draw(&DrawList::new(), cleared, text_system);
+            }
+            return;
+        };
+        let (load_action, clear_color) = metal_renderer::load_action(clear);
+
+        match &mut self.source {
+            ExternalSource::Texture(texture) => match texture.get() {
+                Some(texture) => renderer.composite_texture(
+                    target.command_buffer,
+                    target.drawable,
+                    &texture,
+                    load_action,
+                    clear_color,
+                    self.options.opacity,
+                ),
+                // Nothing to show yet, but the drawable may still need clearing
+                None if clear.is_some() => renderer.render_draw_list(
+                    &DrawList::new(),
+                    &mut target,
+                    cleared,
+                    size,
+                    scale_factor,
+                    text_system,
+                ),
+                None => {}
+            },
+            ExternalSource::Render(render_fn) => {
+                let mut ctx = ExternalLayerContext {
+                    command_buffer: target.command_buffer,
+                    drawable: target.drawable,
+                    size,
+                    scale_factor,
+                    time: elapsed_time,
+                    load_action: (load_action, clear_color),
+                    animation_frame_requested,
+                };
+                render_fn(&mut ctx);
+            }
+        }
+    }
+
+    fn handle_input(&mut self, event: &InputEvent) -> bool {
+        self.on_input.as_mut().is_some_and(|handler| handler(event))
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
 /// A UI layer that uses Taffy for layout
 pub struct UiLayer<F> {
     options: LayerOptions,
@@ -246,10 +824,25 @@ pub struct UiLayer<F> {
     root_element: Option<Box<dyn Element>>,
     interaction_system: InteractionSystem,
     element_registry: std::rc::Rc<std::cell::RefCell<ElementRegistry>>,
+    /// Elements painted last frame, for lifecycle callbacks
+    lifecycle: Rc<RefCell<LifecycleTracker>>,
     /// Tracks if the layer needs to rebuild its element tree
     needs_rebuild: bool,
     /// Last viewport size used for layout
     last_size: Option<Vec2>,
+    /// Content of the last full render, redrawn on overlay-only frames
+    cached_frame: Option<CachedFrame>,
+    /// Unclipped window bounds of the elements painted last frame
+    element_bounds: HashMap<ElementId, Rect>,
+    /// Opacity control shared with the app
+    handle: LayerHandle,
+}
+
+/// A painted layer kept for redrawing with fresh overlays
+struct CachedFrame {
+    draw_list: DrawList,
+    overlays: Vec<DrawCommand>,
+    size: Vec2,
 }
 
 impl<F> UiLayer<F>
@@ -259,15 +852,131 @@ where
     /// Create a new Taffy UI layer
     pub fn new(options: LayerOptions, render_fn: F) -> Self {
         Self {
+            handle: LayerHandle::new(options.opacity),
             options,
             render_fn,
             layout_engine: TaffyLayoutEngine::new(),
             root_element: None,
             interaction_system: InteractionSystem::new(),
             element_registry: std::rc::Rc::new(std::cell::RefCell::new(ElementRegistry::new())),
+            lifecycle: Rc::new(RefCell::new(LifecycleTracker::new())),
             needs_rebuild: true, // Always rebuild on first frame
             last_size: None,
+            cached_frame: None,
+            element_bounds: HashMap::new(),
+        }
+    }
+
+    /// Draw the cached content with this frame's carets and overlays
+    fn present_cached_frame(
+        &mut self,
+        frame: &mut dyn LayerFrame,
+        text_system: &mut TextSystem,
+        is_first_layer: bool,
+        animation_frame_requested: &mut bool,
+        elapsed_time: f32,
+    ) {
+        let Some(cached) = self.cached_frame.as_mut() else {
+            return;
+        };
+
+        let opacity = self.handle.update(elapsed_time);
+        if self.handle.is_fading() {
+            *animation_frame_requested = true;
+        }
+        // A hidden layer only needs to draw if it has to clear the drawable
+        if opacity <= 0.0 && !is_first_layer && !self.options.clear {
+            return;
+        }
+
+        // Blink carets in place, and wake for the next blink
+        if let Some(at) = cached.draw_list.update_carets(elapsed_time) {
+            schedule_blink(at);
         }
+
+        // Overlay pass: append overlays, then drop them again after rendering
+        let content_len = cached.draw_list.commands().len();
+        cached
+            .draw_list
+            .commands_mut()
+            .extend(cached.overlays.iter().cloned());
+
+        // Determine what to clear to
+        let composite = Composite {
+            clear: self.options.clear_to(is_first_layer),
+            opacity,
+        };
+
+        let passes = &self.options.post_processes;
+        if passes
+            .iter()
+            .any(|pass| pass.is_active() && pass.is_animated())
+        {
+            *animation_frame_requested = true;
+        }
+
+        // Render to screen
+        frame.draw_post_processed(
+            &cached.draw_list,
+            composite,
+            passes,
+            elapsed_time,
+            text_system,
+        );
+
+        cached.draw_list.commands_mut().truncate(content_len);
+    }
+
+    /// Send an event to its element, then to any interaction observers
+    ///
+    /// Scroll events go to the first element under the pointer that takes
+    /// them, so nested scroll containers can hand scrolling they can't use
+    /// to the one beneath.
+    fn dispatch_event(&self, event: &InteractionEvent) -> bool {
+        let (event, handled) = match event {
+            InteractionEvent::ScrollWheel { position, .. } => {
+                let position = *position;
+                let taken = self.element_registry.borrow_mut().dispatch_scroll(
+                    event,
+                    &self.interaction_system.elements_at(position),
+                    |id| {
+                        self.interaction_system
+                            .hit_test_entry(id)
+                            .map_or(Vec2::ZERO, |entry| entry.local_position(position))
+                    },
+                );
+                match taken {
+                    Some(taken) => (Cow::Owned(taken), true),
+                    None => (Cow::Borrowed(event), false),
+                }
+            }
+            _ => (
+                Cow::Borrowed(event),
+                self.element_registry.borrow_mut().dispatch_event(event),
+            ),
+        };
+        let event = event.as_ref();
+        if has_interaction_observers() {
+            let element = event.element_id().map(|id| {
+                let entry = self.interaction_system.hit_test_entry(id);
+                ElementInfo {
+                    id,
+                    bounds: entry.map(|entry| entry.bounds),
+                    z_index: entry.map_or(0, |entry| entry.z_index),
+                    focusable: entry.is_some_and(|entry| entry.focusable),
+                    layer_z_index: self.options.z_index,
+                    state: self
+                        .element_registry
+                        .borrow()
+                        .get_state(id)
+                        .cloned()
+                        .unwrap_or_default(),
+                    handled,
+                }
+            });
+            notify_interaction_observers(event, element.as_ref());
+        }
+        handled
     }
 }
 
@@ -285,17 +994,21 @@ where
 
     fn render(
         &mut self,
-        renderer: &mut MetalRenderer,
-        command_buffer: &CommandBufferRef,
-        drawable: &metal::MetalDrawableRef,
-        size: Vec2,
-        scale_factor: f32,
-        text_system: &mut crate::text_system::TextSystem,
+        frame: &mut dyn LayerFrame,
+        text_system: &mut TextSystem,
         is_first_layer: bool,
-        _animation_frame_requested: &mut bool,
-        _elapsed_time: f32,
+        animation_frame_requested: &mut bool,
+        elapsed_time: f32,
     ) {
         let _render_span = info_span!("taffy_ui_layer_render").entered();
+        let (size, scale_factor) = (frame.size(), frame.scale_factor());
+
+        // Deliver hover starts that came due since the last frame, while the
+        // previous frame's handlers are still registered
+        let hover_events = self.interaction_system.update_hover_intent(elapsed_time);
+        for event in &hover_events {
+            self.dispatch_event(event);
+        }
 
         // Track if size changed (useful for debugging and future optimizations)
         let size_changed = self.last_size != Some(size);
@@ -312,8 +1025,10 @@ where
         // Begin new frame - prepares cache but doesn't clear retained nodes
         self.layout_engine.begin_frame();
 
-        // Create root element
+        // Create root element; it can measure elements offscreen as it builds
+        set_measure_context(text_system, scale_factor);
         self.root_element = Some((self.render_fn)());
+        clear_measure_context();
 
         // Phase 1: Layout
         let layout_start = std::time::Instant::now();
@@ -346,10 +1061,12 @@ where
         // Phase 2: Paint
         let mut draw_list =
             DrawList::with_viewport(crate::geometry::Rect::from_pos_size(Vec2::ZERO, size));
+        draw_list.set_snap_to_pixels(self.options.snap_to_pixels.then_some(scale_factor));
 
         // Clear and set the current element registry for this paint phase
         self.element_registry.borrow_mut().clear();
         set_current_registry(self.element_registry.clone());
+        set_current_lifecycle(self.lifecycle.clone());
 
         // Create hit test builder for this layer
         let hit_test_builder = std::rc::Rc::new(std::cell::RefCell::new(HitTestBuilder::new(
@@ -367,41 +1084,115 @@ where
 
         // Paint the root element (which will recursively paint children)
         let root_bounds = self.layout_engine.layout_bounds(root_node);
+        begin_layer_paint_profile(self.options.z_index);
         self.root_element
             .as_mut()
             .unwrap()
             .paint(root_bounds, &mut paint_ctx);
 
+        // Apply element z-indices to paint order
+        draw_list.sort_by_z_index();
+
         // Update hit test results in interaction system
         let hit_test_entries = hit_test_builder.borrow_mut().build();
+        self.element_bounds.clear();
+        for &(id, bounds) in hit_test_builder.borrow().scroll_targets() {
+            self.element_bounds.entry(id).or_insert(bounds);
+        }
+        if self.options.receives_input {
+            publish_layer_chrome(&hit_test_entries);
+        } else {
+            discard_layer_chrome();
+        }
         self.interaction_system.update_hit_test(hit_test_entries);
+        self.interaction_system
+            .set_hover_delays(self.element_registry.borrow().hover_delays());
+
+        // Focus an element that asked for it while painting
+        let focus_request = self.element_registry.borrow_mut().take_focus_request();
+        if let Some(id) = focus_request {
+            let events = self.interaction_system.set_focus(Some(id));
+            if !events.is_empty() {
+                for event in &events {
+                    self.dispatch_event(event);
+                }
+                // Paint the focus change
+                *animation_frame_requested = true;
+            }
+        }
+
+        // Keep frames coming until a pending hover start is delivered, or
+        // while painted elements are animating
+        if self.interaction_system.has_pending_hover_events()
+            || draw_list.take_animation_frame_request()
+        {
+            *animation_frame_requested = true;
+        }
 
         // Clear the current registry after painting
         clear_current_registry();
+        clear_current_lifecycle();
 
-        // Determine load action and clear color
-        let (load_action, clear_color) = if is_first_layer {
-            (
-                metal::MTLLoadAction::Clear,
-                metal::MTLClearColor::new(0.95, 0.95, 0.95, 1.0),
-            )
-        } else {
-            (
-                metal::MTLLoadAction::Load,
-                metal::MTLClearColor::new(0.0, 0.0, 0.0, 0.0),
-            )
-        };
+        // Call lifecycle handlers now that the whole tree has painted
+        self.lifecycle.borrow_mut().finish_frame();
 
-        // Render to screen
-        renderer.render_draw_list(
-            &draw_list,
-            command_buffer,
-            drawable,
-            (size.x, size.y),
-            scale_factor,
+        // Render any captures requested during paint
+        for request in draw_list.take_captures() {
+            let source = request.commands.as_ref().unwrap_or(&draw_list);
+            match frame.capture(source, request.bounds, text_system) {
+                Ok(image) => request.handle.set(image),
+                Err(e) => error!("Failed to capture region {:?}: {}", request.bounds, e),
+            }
+        }
+
+        // Keep the content so overlay-only frames can skip layout and paint
+        let overlays = draw_list.take_overlays();
+        self.cached_frame = Some(CachedFrame {
+            draw_list,
+            overlays,
+            size,
+        });
+        self.present_cached_frame(
+            frame,
+            text_system,
+            is_first_layer,
+            animation_frame_requested,
+            elapsed_time,
+        );
+    }
+
+    fn render_overlays(
+        &mut self,
+        frame: &mut dyn LayerFrame,
+        text_system: &mut TextSystem,
+        is_first_layer: bool,
+        animation_frame_requested: &mut bool,
+        elapsed_time: f32,
+    ) {
+        let size = frame.size();
+        let cache_valid = !self.needs_rebuild
+            && self
+                .cached_frame
+                .as_ref()
+                .is_some_and(|cached| cached.size == size);
+        if !cache_valid {
+            self.render(
+                frame,
+                text_system,
+                is_first_layer,
+                animation_frame_requested,
+                elapsed_time,
+            );
+            return;
+        }
+
+        let _render_span = info_span!("taffy_ui_layer_render_overlays").entered();
+        self.present_cached_frame(
+            frame,
             text_system,
-            load_action,
-            clear_color,
+            is_first_layer,
+            animation_frame_requested,
+            elapsed_time,
         );
     }
 
@@ -410,13 +1201,34 @@ where
             return false;
         }
 
+        // Pass-through layers only keep mouse events aimed at their elements
+        let passes_through = self.options.input_passthrough
+            && event
+                .pointer_position()
+                .is_some_and(|position| !self.interaction_system.captures_pointer(position));
+
         // Process the event through the interaction system
         let interaction_events = self.interaction_system.handle_input(event);
 
+        // Keep keyboard focus visible inside scroll containers
+        if matches!(event, InputEvent::KeyDown { .. }) {
+            for event in &interaction_events {
+                if let InteractionEvent::FocusIn { element_id } = event {
+                    request_scroll_into_view(*element_id, ScrollAlignment::Nearest);
+                }
+            }
+        }
+        if passes_through {
+            for event in &interaction_events {
+                self.dispatch_event(event);
+            }
+            return false;
+        }
+
         // Dispatch events to registered elements
         let mut handled = false;
         for event in &interaction_events {
-            if self.element_registry.borrow_mut().dispatch_event(event) {
+            if self.dispatch_event(event) {
                 handled = true;
             }
         }
@@ -432,19 +1244,103 @@ where
     fn invalidate(&mut self) {
         self.needs_rebuild = true;
     }
+
+    fn layer_handle(&self) -> Option<LayerHandle> {
+        Some(self.handle.clone())
+    }
+
+    fn element_bounds(&self, element_id: ElementId) -> Option<Rect> {
+        self.element_bounds.get(&element_id).copied()
+    }
+
+    fn hit_test(&self, position: Vec2) -> Vec<ElementId> {
+        self.interaction_system.elements_at(position)
+    }
 }
 
+/// Phase in which a layer panicked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayerErrorPhase {
+    /// The panic happened while the layer was rendering a frame
+    Render,
+    /// The panic happened while the layer was handling an input event
+    Input,
+}
+
+/// A panic caught at a layer boundary
+#[derive(Debug, Clone)]
+pub struct LayerError {
+    /// Index of the layer in render order
+    pub layer_index: usize,
+    /// Z-index of the layer
+    pub z_index: i32,
+    /// Phase the panic occurred in
+    pub phase: LayerErrorPhase,
+    /// Panic message, if one could be extracted from the payload
+    pub message: String,
+}
+
+/// Callback type for handling layer panics
+pub type LayerErrorHandler = Box<dyn FnMut(&LayerError)>;
+
+/// Maximum number of entries kept in the layer manager's error console
+const ERROR_CONSOLE_CAPACITY: usize = 50;
+
 /// Manages all layers and handles rendering order
+///
+/// Each layer's `render` and `handle_input` call runs inside a panic boundary.
+/// A panicking layer is replaced by an error placeholder for that frame while
+/// the remaining layers keep rendering and receiving input.
 pub struct LayerManager {
     pub layers: Vec<(i32, Box<dyn Layer>)>,
+    /// Last error per layer index, cleared once the layer renders successfully
+    layer_errors: HashMap<usize, LayerError>,
+    /// Console that collects caught layer panics
+    console: DebugConsole,
+    /// Optional app-level hook invoked when a layer panics
+    error_handler: Option<LayerErrorHandler>,
 }
 
 impl LayerManager {
     pub fn new() -> Self {
-        Self { layers: Vec::new() }
+        Self {
+            layers: Vec::new(),
+            layer_errors: HashMap::new(),
+            console: DebugConsole::new(ERROR_CONSOLE_CAPACITY),
+            error_handler: None,
+        }
+    }
+
+    /// Set a handler that is called whenever a layer panics
+    ///
+    /// The handler is invoked once per distinct failure; a layer that keeps
+    /// panicking with the same message every frame only reports it once.
+    pub fn set_error_handler(&mut self, handler: LayerErrorHandler) {
+        self.error_handler = Some(handler);
+    }
+
+    /// Get the console that collects caught layer panics
+    pub fn console(&self) -> &DebugConsole {
+        &self.console
+    }
+
+    /// Get mutable access to the error console
+    pub fn console_mut(&mut self) -> &mut DebugConsole {
+        &mut self.console
+    }
+
+    /// Get the most recent error for a layer, if it is currently failing
+    pub fn layer_error(&self, layer_index: usize) -> Option<&LayerError> {
+        self.layer_errors.get(&layer_index)
+    }
+
+    /// Check if any layer is currently failing
+    pub fn has_errors(&self) -> bool {
+        !self.layer_errors.is_empty()
     }
 
     /// Add a raw layer
+    #[cfg(target_os = "macos")]
     pub fn add_raw_layer<F>(&mut self, z_index: i32, options: LayerOptions, render_fn: F)
     where
         F: for<'a> FnMut(&mut RawLayerContext<'a>) + Any + 'static,
@@ -462,17 +1358,35 @@ impl LayerManager {
         self.add_layer(Box::new(layer));
     }
 
+    /// Add a layer showing content rendered outside sol-ui
+    #[cfg(target_os = "macos")]
+    pub fn add_external_layer(
+        &mut self,
+        z_index: i32,
+        options: LayerOptions,
+        mut layer: ExternalLayer,
+    ) {
+        layer.options = options.with_z_index(z_index);
+        if layer.on_input.is_some() {
+            layer.options.receives_input = true;
+        }
+        self.add_layer(Box::new(layer));
+    }
+
     /// Add a layer and maintain z-order
     fn add_layer(&mut self, layer: Box<dyn Layer>) {
         let z_index = layer.z_index();
         self.layers.push((z_index, layer));
         // Sort by z-index (ascending, so higher values render on top)
         self.layers.sort_by_key(|(z, _)| *z);
+        // Indices may have shifted, so stored errors no longer line up
+        self.layer_errors.clear();
     }
 
     /// Clear all layers
     pub fn clear(&mut self) {
         self.layers.clear();
+        self.layer_errors.clear();
     }
 
     /// Invalidate all layers, forcing them to rebuild their cached data
@@ -483,6 +1397,40 @@ impl LayerManager {
         }
     }
 
+    /// Get the handle for the layer at `z_index`, e.g. to fade it in or out
+    ///
+    /// Only UI layers have handles.
+    pub fn layer_handle(&self, z_index: i32) -> Option<LayerHandle> {
+        self.layers
+            .iter()
+            .find(|(_, l)| l.z_index() == z_index)
+            .and_then(|(_, l)| l.layer_handle())
+    }
+
+    /// Where an element was painted last frame, in window coordinates
+    ///
+    /// Covers elements that are hit-testable, focusable or registered as
+    /// scroll targets, including the parts clipped out of view. Searches
+    /// the topmost layer first.
+    pub fn element_bounds(&self, element_id: ElementId) -> Option<Rect> {
+        self.layers
+            .iter()
+            .rev()
+            .find_map(|(_, layer)| layer.element_bounds(element_id))
+    }
+
+    /// Elements under `position` last frame, topmost first
+    ///
+    /// Includes every element whose hit area contains the point, not just
+    /// the one that would receive a click.
+    pub fn hit_test(&self, position: Vec2) -> Vec<ElementId> {
+        self.layers
+            .iter()
+            .rev()
+            .flat_map(|(_, layer)| layer.hit_test(position))
+            .collect()
+    }
+
     /// Invalidate a specific layer by z-index
     pub fn invalidate_layer(&mut self, z_index: i32) {
         if let Some((_, layer)) = self.layers.iter_mut().find(|(_, l)| l.z_index() == z_index) {
@@ -491,42 +1439,145 @@ impl LayerManager {
         }
     }
 
-    /// Render all layers
-    pub fn render(
+    /// Render all layers into `target`
+    pub fn render<R: Renderer>(
+        &mut self,
+        renderer: &mut R,
+        target: &mut R::Target<'_>,
+        frame: FrameInfo,
+        text_system: &mut TextSystem,
+        entity_store: &mut EntityStore,
+    ) -> bool {
+        let mut layer_frame = RendererFrame {
+            renderer,
+            target,
+            size: frame.size,
+            scale_factor: frame.scale_factor,
+        };
+        self.render_layers(
+            &mut layer_frame,
+            text_system,
+            entity_store,
+            frame.elapsed_time,
+            false,
+        )
+    }
+
+    /// Render a frame where only time-based overlays such as carets changed
+    ///
+    /// Layers with cached content redraw it instead of rebuilding.
+    pub fn render_overlays<R: Renderer>(
         &mut self,
-        renderer: &mut MetalRenderer,
-        command_buffer: &CommandBufferRef,
-        drawable: &metal::MetalDrawableRef,
-        size: Vec2,
-        text_system: &mut crate::text_system::TextSystem,
+        renderer: &mut R,
+        target: &mut R::Target<'_>,
+        frame: FrameInfo,
+        text_system: &mut TextSystem,
+        entity_store: &mut EntityStore,
+    ) -> bool {
+        let mut layer_frame = RendererFrame {
+            renderer,
+            target,
+            size: frame.size,
+            scale_factor: frame.scale_factor,
+        };
+        self.render_layers(
+            &mut layer_frame,
+            text_system,
+            entity_store,
+            frame.elapsed_time,
+            true,
+        )
+    }
+
+    fn render_layers(
+        &mut self,
+        frame: &mut dyn LayerFrame,
+        text_system: &mut TextSystem,
         entity_store: &mut EntityStore,
-        scale_factor: f32,
         elapsed_time: f32,
+        overlays_only: bool,
     ) -> bool {
         let _render_all_span =
             info_span!("layer_manager_render_all", layer_count = self.layers.len()).entered();
         debug!("Rendering {} layers", self.layers.len());
 
-        // Set thread-local entity store for this render frame
+        // Set thread-local entity store and frame clock for this render frame
+        entity_store.begin_frame();
         set_entity_store(entity_store);
+        set_frame_time(elapsed_time);
 
         let mut animation_frame_requested = false;
 
-        for (i, (_, layer)) in self.layers.iter_mut().enumerate() {
-            let _layer_span =
-                info_span!("render_layer", layer_index = i, z_index = layer.z_index()).entered();
+        // Layers republish their window chrome and materials as they render
+        if !overlays_only {
+            begin_chrome_frame();
+            begin_material_frame();
+            begin_text_selection_frame();
+        }
+
+        for i in 0..self.layers.len() {
+            let (_, layer) = &mut self.layers[i];
+            let z_index = layer.z_index();
+            let _layer_span = info_span!("render_layer", layer_index = i, z_index).entered();
             let is_first_layer = i == 0;
-            layer.render(
-                renderer,
-                command_buffer,
-                drawable,
-                size,
-                scale_factor,
-                text_system,
-                is_first_layer,
-                &mut animation_frame_requested,
-                elapsed_time,
-            );
+            let result = catch_unwind(AssertUnwindSafe(|| {
+                if overlays_only {
+                    layer.render_overlays(
+                        frame,
+                        text_system,
+                        is_first_layer,
+                        &mut animation_frame_requested,
+                        elapsed_time,
+                    );
+                } else {
+                    layer.render(
+                        frame,
+                        text_system,
+                        is_first_layer,
+                        &mut animation_frame_requested,
+                        elapsed_time,
+                    );
+                }
+            }));
+
+            match result {
+                Ok(()) => {
+                    self.layer_errors.remove(&i);
+                }
+                Err(payload) => {
+                    // The layer may have panicked mid-build or mid-paint with
+                    // its measure context or registry installed
+                    clear_measure_context();
+                    clear_current_registry();
+                    clear_current_lifecycle();
+                    clear_style_states();
+                    clear_painting_areas();
+                    discard_layer_chrome();
+
+                    let layer_error = LayerError {
+                        layer_index: i,
+                        z_index,
+                        phase: LayerErrorPhase::Render,
+                        message: panic_message(payload.as_ref()),
+                    };
+                    Self::render_error_placeholder(
+                        &layer_error,
+                        frame,
+                        text_system,
+                        is_first_layer,
+                    );
+                    self.report_error(layer_error);
+                }
+            }
+        }
+
+        // Scroll containers have acted on this frame's reveal requests,
+        // memoized subtrees that weren't painted can be dropped, and every
+        // element's paint has been profiled
+        if !overlays_only {
+            clear_scroll_into_view_requests();
+            end_memo_frame();
+            end_paint_profile_frame();
         }
 
         // Clear thread-local and cleanup entities at frame boundary
@@ -534,24 +1585,138 @@ impl LayerManager {
         clear_entity_store();
         let needs_reactive_render = entity_store.cleanup();
 
-        // Request animation frame if explicitly requested OR if reactive state changed
-        animation_frame_requested || needs_reactive_render
+        // Request animation frame if explicitly requested, if reactive state changed,
+        // or if the locale switched during this frame
+        animation_frame_requested || needs_reactive_render || take_locale_change()
     }
 
     /// Handle input, starting from the topmost layer that accepts input
     pub fn handle_input(&mut self, event: &InputEvent) -> bool {
         // Iterate in reverse order (topmost layers first)
-        for (_, layer) in self.layers.iter_mut().rev() {
-            if layer.options().receives_input && layer.handle_input(event) {
-                return true; // Event was consumed
+        for i in (0..self.layers.len()).rev() {
+            let (_, layer) = &mut self.layers[i];
+            if !layer.options().receives_input {
+                continue;
+            }
+
+            let z_index = layer.z_index();
+            match catch_unwind(AssertUnwindSafe(|| layer.handle_input(event))) {
+                Ok(true) => return true, // Event was consumed
+                Ok(false) => {}
+                Err(payload) => {
+                    self.report_error(LayerError {
+                        layer_index: i,
+                        z_index,
+                        phase: LayerErrorPhase::Input,
+                        message: panic_message(payload.as_ref()),
+                    });
+                }
             }
         }
         false
     }
+
+    /// Log a caught layer panic and notify the app's error handler
+    fn report_error(&mut self, layer_error: LayerError) {
+        let is_repeat = self
+            .layer_errors
+            .get(&layer_error.layer_index)
+            .is_some_and(|previous| {
+                previous.phase == layer_error.phase && previous.message == layer_error.message
+            });
+
+        if !is_repeat {
+            error!(
+                "Layer {} (z-index {}) panicked during {:?}: {}",
+                layer_error.layer_index,
+                layer_error.z_index,
+                layer_error.phase,
+                layer_error.message
+            );
+            self.console.error(format!(
+                "Layer {} panicked during {:?}: {}",
+                layer_error.layer_index, layer_error.phase, layer_error.message
+            ));
+            if let Some(ref mut handler) = self.error_handler {
+                handler(&layer_error);
+            }
+        }
+
+        self.layer_errors
+            .insert(layer_error.layer_index, layer_error);
+    }
+
+    /// Paint a visible placeholder in place of a layer that failed to render
+    fn render_error_placeholder(
+        layer_error: &LayerError,
+        frame: &mut dyn LayerFrame,
+        text_system: &mut TextSystem,
+        is_first_layer: bool,
+    ) {
+        let size = frame.size();
+        let viewport = crate::geometry::Rect::from_pos_size(Vec2::ZERO, size);
+        let mut draw_list = DrawList::with_viewport(viewport);
+
+        let banner_height = 48.0_f32.min(size.y);
+        draw_list.add_rect(
+            crate::geometry::Rect::from_pos_size(Vec2::ZERO, Vec2::new(size.x, banner_height)),
+            colors::RED_600,
+        );
+        draw_list.add_text(
+            Vec2::new(12.0, 8.0),
+            format!(
+                "Layer {} (z-index {}) panicked: {}",
+                layer_error.layer_index, layer_error.z_index, layer_error.message
+            ),
+            TextStyle {
+                size: 14.0,
+                color: colors::WHITE,
+                ..Default::default()
+            },
+            None,
+        );
+        draw_list.add_text(
+            Vec2::new(12.0, 28.0),
+            "Other layers are still running. See the log for details.",
+            TextStyle {
+                size: 11.0,
+                color: colors::WHITE,
+                ..Default::default()
+            },
+            None,
+        );
+
+        // A first layer that panicked never cleared the drawable
+        let composite = Composite {
+            clear: LayerOptions::default().clear_to(is_first_layer),
+            opacity: 1.0,
+        };
+
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            frame.draw(&draw_list, composite, text_system);
+        }));
+        if result.is_err() {
+            error!(
+                "Failed to render error placeholder for layer {}",
+                layer_error.layer_index
+            );
+        }
+    }
+}
+
+/// Extract a readable message from a panic payload
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        (*message).to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
 }
 
 /// Input events from the platform layer
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum InputEvent {
     // Window events
     /// Window was resized - metal layer drawable size already updated
@@ -568,7 +1733,21 @@ pub enum InputEvent {
     MouseUp { position: Vec2, button: MouseButton },
     MouseLeave,
     /// Scroll wheel event (positive delta = scroll up/left, negative = scroll down/right)
-    ScrollWheel { position: Vec2, delta: Vec2 },
+    ScrollWheel {
+        position: Vec2,
+        delta: Vec2,
+        /// Where the event falls in a trackpad gesture
+        #[serde(default)]
+        phase: ScrollPhase,
+    },
+    /// Trackpad pinch (magnification is the relative change, 0.1 = 10% larger)
+    Magnify { position: Vec2, magnification: f32 },
+    /// Relative mouse motion while the pointer is locked, sent instead of
+    /// `MouseMove` (positive y = down)
+    MouseDelta { delta: Vec2 },
+    /// Stylus data from a graphics tablet, sent after the mouse event it
+    /// belongs to
+    TabletMove { position: Vec2, tablet: TabletData },
 
     // Keyboard events
     KeyDown {
@@ -609,11 +1788,104 @@ pub enum InputEvent {
     WindowCloseRequested,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+impl InputEvent {
+    /// Check if this is a window-level event rather than mouse or keyboard input
+    pub fn is_window_event(&self) -> bool {
+        matches!(
+            self,
+            InputEvent::WindowResize { .. }
+                | InputEvent::WindowFocused
+                | InputEvent::WindowBlurred
+                | InputEvent::WindowResized { .. }
+                | InputEvent::WindowMoved { .. }
+                | InputEvent::WindowMinimized
+                | InputEvent::WindowRestored
+                | InputEvent::WindowEnteredFullscreen
+                | InputEvent::WindowExitedFullscreen
+                | InputEvent::WindowCloseRequested
+        )
+    }
+
+    /// The mouse position of a pointer event, in window coordinates
+    pub fn pointer_position(&self) -> Option<Vec2> {
+        match self {
+            InputEvent::MouseMove { position }
+            | InputEvent::MouseDown { position, .. }
+            | InputEvent::MouseUp { position, .. }
+            | InputEvent::ScrollWheel { position, .. }
+            | InputEvent::Magnify { position, .. }
+            | InputEvent::TabletMove { position, .. } => Some(*position),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MouseButton {
     Left,
     Right,
     Middle,
+    /// Button 4, the side button mice use for navigating back
+    Back,
+    /// Button 5, the side button mice use for navigating forward
+    Forward,
+    /// Any further button, by its zero-based number
+    Other(u8),
+}
+
+impl MouseButton {
+    /// The button with a zero-based number, as in `NSEvent.buttonNumber`
+    pub fn from_number(number: u8) -> Self {
+        match number {
+            0 => MouseButton::Left,
+            1 => MouseButton::Right,
+            2 => MouseButton::Middle,
+            3 => MouseButton::Back,
+            4 => MouseButton::Forward,
+            n => MouseButton::Other(n),
+        }
+    }
+
+    /// The button's zero-based number
+    pub fn number(self) -> u8 {
+        match self {
+            MouseButton::Left => 0,
+            MouseButton::Right => 1,
+            MouseButton::Middle => 2,
+            MouseButton::Back => 3,
+            MouseButton::Forward => 4,
+            MouseButton::Other(n) => n,
+        }
+    }
+}
+
+/// Where a scroll event falls in a trackpad gesture
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ScrollPhase {
+    /// Not part of a gesture, e.g. a mouse wheel notch
+    #[default]
+    None,
+    /// Fingers touched the trackpad
+    Began,
+    /// Fingers moved on the trackpad
+    Changed,
+    /// Fingers lifted; the delta is usually zero
+    Ended,
+    /// Inertia generated by the system after the fingers lifted
+    Momentum,
+}
+
+/// Stylus state reported by a graphics tablet
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct TabletData {
+    /// Tip pressure from 0.0 to 1.0
+    pub pressure: f32,
+    /// Pen tilt from -1.0 to 1.0 on each axis
+    pub tilt: Vec2,
+    /// Barrel rotation in degrees
+    pub rotation: f32,
+    /// Whether a barrel button is held
+    pub barrel_button: bool,
 }
 
 /// Type of click event based on rapid successive clicks
@@ -650,7 +1922,7 @@ impl ClickType {
 }
 
 /// Modifier key state
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub struct Modifiers {
     pub shift: bool,
     pub ctrl: bool,
@@ -681,7 +1953,7 @@ impl Modifiers {
 }
 
 /// Virtual key codes matching macOS key codes
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Key {
     // Letters
     A, B, C, D, E, F, G, H, I, J, K, L, M,
@@ -861,3 +2133,336 @@ impl Key {
 }
 
 // Re-export commonly used types
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        element::{column, container},
+        software_renderer::SoftwareRenderer,
+    };
+    use std::cell::Cell;
+
+    /// Render `layers` 100 points square with the software renderer
+    fn render_headless(layers: &mut LayerManager) -> RgbaImage {
+        let mut target = RgbaImage::new(1, 1);
+        layers.render(
+            &mut SoftwareRenderer::new(),
+            &mut target,
+            FrameInfo {
+                size: Vec2::new(100.0, 100.0),
+                scale_factor: 1.0,
+                elapsed_time: 0.0,
+            },
+            &mut TextSystem::headless(),
+            &mut EntityStore::new(),
+        );
+        target
+    }
+
+    /// A layer that panics on input
+    struct PanickingLayer {
+        options: LayerOptions,
+    }
+
+    impl Layer for PanickingLayer {
+        fn z_index(&self) -> i32 {
+            self.options.z_index
+        }
+
+        fn options(&self) -> &LayerOptions {
+            &self.options
+        }
+
+        fn render(
+            &mut self,
+            _frame: &mut dyn LayerFrame,
+            _text_system: &mut TextSystem,
+            _is_first_layer: bool,
+            _animation_frame_requested: &mut bool,
+            _elapsed_time: f32,
+        ) {
+        }
+
+        fn handle_input(&mut self, _event: &InputEvent) -> bool {
+            panic!("bad input");
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+    }
+
+    #[test]
+    fn test_render_panic_shows_placeholder_and_reports_once() {
+        let reports = Rc::new(Cell::new(0));
+        let failing = Rc::new(Cell::new(true));
+        let mut layers = LayerManager::new();
+        layers.set_error_handler(Box::new({
+            let reports = reports.clone();
+            move |error| {
+                assert_eq!(error.phase, LayerErrorPhase::Render);
+                reports.set(reports.get() + 1);
+            }
+        }));
+        layers.add_ui_layer(0, LayerOptions::default(), {
+            let failing = failing.clone();
+            move || {
+                if failing.get() {
+                    panic!("boom");
+                }
+                Box::new(container().size(100.0, 100.0).background(colors::BLUE))
+            }
+        });
+        layers.add_ui_layer(1, LayerOptions::default(), || {
+            Box::new(
+                column()
+                    .child(container().size(10.0, 60.0))
+                    .child(container().size(10.0, 10.0).background(colors::RED)),
+            )
+        });
+
+        let image = render_headless(&mut layers);
+        assert_eq!(reports.get(), 1);
+        assert_eq!(
+            layers.layer_error(0).map(|e| e.message.as_str()),
+            Some("boom")
+        );
+        assert_eq!(layers.console().len(), 1);
+        // The placeholder banner replaces the failed layer
+        let [r, g, b, _] = image.pixel(50, 10).unwrap();
+        assert!(r > 150 && g < 100 && b < 100);
+        // Layers above it still render
+        assert_eq!(image.pixel(5, 65), Some([255, 0, 0, 255]));
+
+        // The same panic again isn't reported twice
+        layers.invalidate_all();
+        render_headless(&mut layers);
+        assert_eq!(reports.get(), 1);
+
+        // A layer that renders again is no longer failing
+        failing.set(false);
+        layers.invalidate_all();
+        let image = render_headless(&mut layers);
+        assert!(!layers.has_errors());
+        assert_eq!(image.pixel(50, 10), Some([0, 0, 255, 255]));
+    }
+
+    #[test]
+    fn test_render_panic_drops_pushed_style_states() {
+        use crate::{
+            interaction::registry::{current_style_state, push_style_state},
+            style::StyleState,
+        };
+
+        let mut layers = LayerManager::new();
+        layers.add_ui_layer(0, LayerOptions::default(), || {
+            push_style_state(StyleState {
+                disabled: true,
+                ..Default::default()
+            });
+            panic!("boom inside a disabled element");
+        });
+        render_headless(&mut layers);
+
+        assert!(layers.has_errors());
+        assert_eq!(current_style_state(), StyleState::default());
+    }
+
+    #[test]
+    fn test_input_panic_is_reported_and_not_consumed() {
+        let mut layers = LayerManager::new();
+        layers.add_layer(Box::new(PanickingLayer {
+            options: LayerOptions::default().with_input(),
+        }));
+
+        let consumed = layers.handle_input(&InputEvent::MouseMove {
+            position: Vec2::ZERO,
+        });
+        assert!(!consumed);
+        let error = layers.layer_error(0).unwrap();
+        assert_eq!(error.phase, LayerErrorPhase::Input);
+        assert_eq!(error.message, "bad input");
+    }
+
+    #[test]
+    fn test_panic_message() {
+        assert_eq!(panic_message(&"static"), "static");
+        assert_eq!(panic_message(&"owned".to_string()), "owned");
+        assert_eq!(panic_message(&42), "unknown panic");
+    }
+
+    #[test]
+    fn test_layer_fade_eases_to_target() {
+        let handle = LayerHandle::new(0.0);
+        handle.fade_to(1.0, Duration::from_millis(200));
+
+        // The fade starts on the first frame that renders it
+        assert_eq!(handle.update(5.0), 0.0);
+        let mid = handle.update(5.1);
+        assert!((mid - 0.5).abs() < 1e-4);
+        assert!(handle.is_fading());
+
+        assert_eq!(handle.update(5.3), 1.0);
+        assert!(!handle.is_fading());
+    }
+
+    #[test]
+    fn test_set_opacity_cancels_fade() {
+        let handle = LayerHandle::new(1.0);
+        handle.fade_to(0.0, Duration::from_secs(1));
+        handle.update(0.0);
+        handle.set_opacity(0.25);
+        assert!(!handle.is_fading());
+        assert_eq!(handle.update(0.5), 0.25);
+    }
+
+    #[test]
+    fn test_layer_clear_to() {
+        let overlay = LayerOptions::default();
+        assert_eq!(overlay.clear_to(false), None);
+        assert_eq!(overlay.clear_to(true), Some(colors::GRAY_100));
+
+        let cleared = LayerOptions::default().clear_color(colors::TRANSPARENT);
+        assert_eq!(cleared.clear_to(false), Some(colors::TRANSPARENT));
+
+        let preserved = cleared.preserve_below(true);
+        assert_eq!(preserved.clear_to(false), None);
+    }
+
+    #[test]
+    fn test_layers_render_with_the_software_renderer() {
+        let mut layers = LayerManager::new();
+        layers.add_ui_layer(0, LayerOptions::default(), || {
+            Box::new(container().size(2.0, 4.0).background(colors::RED))
+        });
+
+        let mut renderer = SoftwareRenderer::new();
+        let mut target = RgbaImage::new(1, 1);
+        let mut text_system = TextSystem::headless();
+        let mut entity_store = EntityStore::new();
+        layers.render(
+            &mut renderer,
+            &mut target,
+            FrameInfo {
+                size: Vec2::new(4.0, 4.0),
+                scale_factor: 1.0,
+                elapsed_time: 0.0,
+            },
+            &mut text_system,
+            &mut entity_store,
+        );
+
+        assert_eq!((target.width(), target.height()), (4, 4));
+        assert_eq!(target.pixel(1, 1), Some([255, 0, 0, 255]));
+        // The first layer clears to the window background
+        assert_eq!(target.pixel(3, 1), Some([242, 242, 242, 255]));
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn test_external_layer_on_input_enables_input() {
+        let scene = ExternalTexture::new();
+        let mut layers = LayerManager::new();
+        layers.add_external_layer(
+            0,
+            LayerOptions::default(),
+            ExternalLayer::from_texture(&scene),
+        );
+        layers.add_external_layer(
+            1,
+            LayerOptions::default(),
+            ExternalLayer::from_texture(&scene).on_input(|_| true),
+        );
+
+        assert!(!layers.layers[0].1.options().receives_input);
+        assert!(layers.layers[1].1.options().receives_input);
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn test_unconsumed_input_reaches_external_layer() {
+        let received = Rc::new(Cell::new(0));
+        let host_consumes = Rc::new(Cell::new(false));
+        let mut layers = LayerManager::new();
+        layers.add_external_layer(
+            0,
+            LayerOptions::default(),
+            ExternalLayer::from_texture(&ExternalTexture::new()).on_input({
+                let received = received.clone();
+                let host_consumes = host_consumes.clone();
+                move |_| {
+                    received.set(received.get() + 1);
+                    host_consumes.get()
+                }
+            }),
+        );
+        layers.add_ui_layer(
+            1,
+            LayerOptions::default().with_input().input_passthrough(true),
+            || Box::new(crate::element::button("Menu").with_id("menu")),
+        );
+        render_headless(&mut layers);
+
+        let mouse_down = |x, y| InputEvent::MouseDown {
+            position: Vec2::new(x, y),
+            button: MouseButton::Left,
+            click_count: 1,
+        };
+
+        // A click that misses the UI goes to the host, which decides
+        // whether it was consumed
+        assert!(!layers.handle_input(&mouse_down(90.0, 90.0)));
+        assert_eq!(received.get(), 1);
+        host_consumes.set(true);
+        assert!(layers.handle_input(&mouse_down(90.0, 90.0)));
+        assert_eq!(received.get(), 2);
+
+        // A click on the button stays with the UI
+        assert!(layers.handle_input(&mouse_down(5.0, 5.0)));
+        assert_eq!(received.get(), 2);
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn test_external_layer_without_metal_only_clears_when_asked() {
+        let scene = ExternalTexture::new();
+        let red_below = |layers: &mut LayerManager| {
+            layers.add_ui_layer(0, LayerOptions::default(), || {
+                Box::new(container().size(100.0, 100.0).background(colors::RED))
+            });
+        };
+
+        // As the first layer it clears to the window background
+        let mut layers = LayerManager::new();
+        layers.add_external_layer(
+            0,
+            LayerOptions::default(),
+            ExternalLayer::from_texture(&scene),
+        );
+        let image = render_headless(&mut layers);
+        assert_eq!(image.pixel(50, 50), Some([242, 242, 242, 255]));
+
+        // Over other layers it leaves them alone
+        let mut layers = LayerManager::new();
+        red_below(&mut layers);
+        layers.add_external_layer(
+            1,
+            LayerOptions::default(),
+            ExternalLayer::from_texture(&scene),
+        );
+        let image = render_headless(&mut layers);
+        assert_eq!(image.pixel(50, 50), Some([255, 0, 0, 255]));
+
+        // Unless it has a clear color
+        let mut layers = LayerManager::new();
+        red_below(&mut layers);
+        layers.add_external_layer(
+            1,
+            LayerOptions::default().clear_color(colors::BLUE),
+            ExternalLayer::from_texture(&scene),
+        );
+        let image = render_headless(&mut layers);
+        assert_eq!(image.pixel(50, 50), Some([0, 0, 255, 255]));
+    }
+}
@@ -13,6 +13,8 @@ use tracing::{debug, info_span};
 pub struct ElementData {
     pub text: Option<(String, crate::style::TextStyle)>,
     pub background: Option<crate::color::Color>,
+    /// Measure text as a single line instead of wrapping to the available width
+    pub no_wrap: bool,
 }
 
 /// Cached node information for retained-mode layout
@@ -144,6 +146,7 @@ fn hash_data(data: &ElementData) -> u64 {
     if let Some(bg) = &data.background {
         format!("{:?}", bg).hash(&mut hasher);
     }
+    data.no_wrap.hash(&mut hasher);
     hasher.finish()
 }
 
@@ -151,6 +154,8 @@ fn hash_data(data: &ElementData) -> u64 {
 pub struct TaffyLayoutEngine {
     taffy: TaffyTree<ElementData>,
     cache: LayoutCache,
+    /// Root of the most recently computed layout
+    root: Option<NodeId>,
 }
 
 impl TaffyLayoutEngine {
@@ -159,6 +164,7 @@ impl TaffyLayoutEngine {
         TaffyLayoutEngine {
             taffy: TaffyTree::new(),
             cache: LayoutCache::new(),
+            root: None,
         }
     }
 
@@ -179,6 +185,7 @@ impl TaffyLayoutEngine {
     pub fn clear(&mut self) {
         self.taffy.clear();
         self.cache = LayoutCache::new();
+        self.root = None;
     }
 
     /// Request layout for a node with a stable ID (cached/retained mode)
@@ -330,6 +337,7 @@ impl TaffyLayoutEngine {
     ) -> Result<(), taffy::TaffyError> {
         let _compute_span = info_span!("compute_layout").entered();
 
+        self.root = Some(root);
         self.taffy.compute_layout_with_measure(
             root,
             available_space,
@@ -354,6 +362,22 @@ impl TaffyLayoutEngine {
         )
     }
 
+    /// Get the root of the most recently computed layout
+    pub fn root(&self) -> Option<NodeId> {
+        self.root
+    }
+
+    /// Get the style a node was laid out with
+    pub fn style(&self, id: NodeId) -> Option<&Style> {
+        self.taffy.style(id).ok()
+    }
+
+    /// Get the full computed layout for a node, including resolved padding,
+    /// border and margin
+    pub fn layout(&self, id: NodeId) -> Option<&taffy::Layout> {
+        self.taffy.layout(id).ok()
+    }
+
     /// Get the element data for a node
     pub fn get_node_context(&self, id: NodeId) -> Option<&ElementData> {
         self.taffy.get_node_context(id)
@@ -397,18 +421,16 @@ fn measure_element(
 
     if let Some(data) = node_data {
         if let Some((content, style)) = &data.text {
-            let max_width = match available_space.width {
-                AvailableSpace::Definite(w) => Some(w),
-                _ => known_dimensions.width,
+            let max_width = if data.no_wrap {
+                None
+            } else {
+                match available_space.width {
+                    AvailableSpace::Definite(w) => Some(w),
+                    _ => known_dimensions.width,
+                }
             };
 
-            let text_config = crate::text_system::TextConfig {
-                font_stack: parley::FontStack::from(style.font_family),
-                size: style.size,
-                weight: style.weight,
-                color: style.color.clone(),
-                line_height: style.line_height,
-            };
+            let text_config = crate::text_system::TextConfig::from(style);
 
             let measured_size =
                 text_system.measure_text(content, &text_config, max_width, scale_factor);
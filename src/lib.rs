@@ -2,22 +2,37 @@
 #![allow(unexpected_cfgs, deprecated)]
 
 pub mod app;
+pub mod caret;
+pub mod clock;
 pub mod color;
 pub mod debug;
+pub mod declarative;
+pub mod docking;
 pub mod element;
 pub mod entity;
+pub mod error;
 pub mod geometry;
+pub mod i18n;
+pub mod image;
 pub mod interaction;
 pub mod layer;
 pub mod layout_engine;
 pub mod layout_id;
+pub mod notifier;
 pub mod platform;
+pub mod post_process;
+pub mod quality;
+pub mod recording;
 pub mod render;
+pub mod renderer;
+pub mod software_renderer;
 pub mod storage;
 pub mod style;
 pub mod task;
+pub mod text_effect;
 pub mod text_system;
 pub mod undo;
+pub mod vibrancy;
 
 /// Test utilities for layout, interaction, and render testing
 #[cfg(any(test, feature = "testing"))]
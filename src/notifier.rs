@@ -0,0 +1,146 @@
+//! Scheduling frames from other threads
+//!
+//! The render loop sleeps until input arrives or something asks for a frame.
+//! A [`Notifier`] can be cloned into background threads to wake it, e.g.
+//! after changing data the UI reads outside the entity store, and to mark
+//! layers dirty so they rebuild on that frame.
+//!
+//! Entity updates queued through an
+//! [`EntityStoreHandle`](crate::entity::EntityStoreHandle) wake the loop
+//! themselves.
+//!
+//! ```ignore
+//! let notifier = notifier();
+//! std::thread::spawn(move || {
+//!     *shared.lock().unwrap() = load_rows();
+//!     notifier.invalidate_layer(0);
+//! });
+//! ```
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+
+thread_local! {
+    static NOTIFIER: Notifier = Notifier::new();
+}
+
+/// Get a notifier for the render loop running on this thread
+///
+/// Call this on the UI thread, then move the notifier to other threads.
+pub fn notifier() -> Notifier {
+    NOTIFIER.with(Notifier::clone)
+}
+
+/// A cloneable, thread-safe handle for waking the render loop
+#[derive(Clone, Default)]
+pub struct Notifier {
+    shared: Arc<NotifierShared>,
+}
+
+#[derive(Default)]
+struct NotifierShared {
+    frame_requested: AtomicBool,
+    invalidate_all: AtomicBool,
+    /// Z-indexes of layers to invalidate
+    invalidated_layers: Mutex<Vec<i32>>,
+    /// Wakes the UI thread's event loop
+    waker: OnceLock<fn()>,
+}
+
+/// Requests made through a [`Notifier`] since the render loop last checked
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Notification {
+    /// Whether every layer should be invalidated
+    pub invalidate_all: bool,
+    /// Z-indexes of layers to invalidate
+    pub invalidated_layers: Vec<i32>,
+}
+
+impl Notifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how to wake the UI thread's event loop
+    #[cfg(target_os = "macos")]
+    pub(crate) fn set_waker(&self, waker: fn()) {
+        let _ = self.shared.waker.set(waker);
+    }
+
+    /// Schedule a frame
+    pub fn notify(&self) {
+        self.shared.frame_requested.store(true, Ordering::Release);
+        if let Some(wake) = self.shared.waker.get() {
+            wake();
+        }
+    }
+
+    /// Invalidate the layer at `z_index` and schedule a frame
+    pub fn invalidate_layer(&self, z_index: i32) {
+        self.shared
+            .invalidated_layers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(z_index);
+        self.notify();
+    }
+
+    /// Invalidate every layer and schedule a frame
+    pub fn invalidate_all(&self) {
+        self.shared.invalidate_all.store(true, Ordering::Release);
+        self.notify();
+    }
+
+    /// Take the requests made since the last call, if any
+    pub(crate) fn take(&self) -> Option<Notification> {
+        if !self.shared.frame_requested.swap(false, Ordering::AcqRel) {
+            return None;
+        }
+        let invalidated_layers = std::mem::take(
+            &mut *self
+                .shared
+                .invalidated_layers
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner),
+        );
+        Some(Notification {
+            invalidate_all: self.shared.invalidate_all.swap(false, Ordering::AcqRel),
+            invalidated_layers,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notifications_from_another_thread() {
+        let notifier = Notifier::new();
+        assert_eq!(notifier.take(), None);
+
+        let sender = notifier.clone();
+        std::thread::spawn(move || {
+            sender.invalidate_layer(2);
+            sender.notify();
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(
+            notifier.take(),
+            Some(Notification {
+                invalidate_all: false,
+                invalidated_layers: vec![2],
+            })
+        );
+        assert_eq!(notifier.take(), None);
+    }
+
+    #[test]
+    fn test_notifier_is_shared_per_thread() {
+        notifier().invalidate_all();
+        let notification = notifier().take().unwrap();
+        assert!(notification.invalidate_all);
+    }
+}
@@ -8,5 +8,11 @@ pub mod mac;
 #[cfg(target_os = "macos")]
 pub use mac::{
     create_app_menu, create_standard_menu_bar, Clipboard, KeyModifiers, KeyboardShortcut, Menu,
-    MenuBar, MenuItem, MenuItemBuilder, MenuModifiers, Window,
+    MenuBar, MenuItem, MenuItemBuilder, MenuModifiers, Window, WindowKind,
+};
+
+#[cfg(target_os = "macos")]
+pub use mac::{
+    Display, DisplayId, GlobalHotkey, accessibility_access, display, displays, global_hotkey,
+    haptics, sample_screen_color, set_cursor_position,
 };
@@ -1,11 +1,27 @@
+mod app_delegate;
 mod clipboard;
+mod color_sampler;
+mod cursor;
+mod defaults;
+mod display;
+mod global_hotkey;
+pub mod haptics;
+mod image_atlas;
 mod menu;
+mod time_zone;
 pub(crate) mod metal_renderer;
 mod window;
 
+pub use app_delegate::{AppLifecycleEvent, install_app_delegate, take_lifecycle_events};
 pub use clipboard::Clipboard;
+pub use color_sampler::sample_screen_color;
+pub use cursor::set_cursor_position;
+pub use defaults::{user_default_f64, user_default_string};
+pub use display::{Display, DisplayId, display, displays};
+pub use global_hotkey::{GlobalHotkey, accessibility_access, global_hotkey};
 pub use menu::{
     create_app_menu, create_standard_menu_bar, show_context_menu, show_context_menu_at_cursor,
     KeyModifiers, KeyboardShortcut, Menu, MenuBar, MenuItem, MenuItemBuilder, MenuModifiers,
 };
-pub use window::Window;
+pub use time_zone::local_utc_offset;
+pub use window::{Window, WindowKind, wake_event_loop};
@@ -0,0 +1,150 @@
+//! `NSApplication` delegate that reports app lifecycle events
+//!
+//! The app pumps its own event loop instead of calling `[NSApp run]`, so a
+//! quit request can't be allowed to reach `exit()` directly:
+//! `applicationShouldTerminate:` cancels it and queues
+//! [`AppLifecycleEvent::TerminateRequested`], and the app runs its shutdown
+//! hooks before leaving the loop normally.
+
+use cocoa::base::{YES, id};
+use objc::{
+    class,
+    declare::ClassDecl,
+    msg_send,
+    runtime::{BOOL, Class, Object, Sel},
+    sel, sel_impl,
+};
+use std::{cell::RefCell, ptr};
+
+/// A lifecycle event reported by the application delegate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppLifecycleEvent {
+    /// The app finished launching
+    Launched,
+    /// The dock icon was clicked while the app was already running
+    Reopen { has_visible_windows: bool },
+    /// The user asked to quit, e.g. with Cmd+Q or by closing the window
+    TerminateRequested,
+    /// A display was connected, disconnected, rearranged or changed
+    /// resolution
+    DisplaysChanged,
+}
+
+static mut APP_DELEGATE_CLASS: *const Class = ptr::null();
+
+thread_local! {
+    static LIFECYCLE_EVENTS: RefCell<Vec<AppLifecycleEvent>> = const { RefCell::new(Vec::new()) };
+}
+
+fn push_event(event: AppLifecycleEvent) {
+    LIFECYCLE_EVENTS.with(|events| events.borrow_mut().push(event));
+}
+
+/// Install the lifecycle delegate on the shared application
+///
+/// Call before `finishLaunching` so the launch notification is reported.
+pub fn install_app_delegate() {
+    unsafe {
+        if APP_DELEGATE_CLASS.is_null() {
+            create_app_delegate_class();
+        }
+        // The application holds its delegate weakly, so this one is never released
+        let delegate: id = msg_send![APP_DELEGATE_CLASS, new];
+        let app: id = msg_send![class!(NSApplication), sharedApplication];
+        let _: () = msg_send![app, setDelegate: delegate];
+    }
+}
+
+/// Take the lifecycle events reported since the last call
+pub fn take_lifecycle_events() -> Vec<AppLifecycleEvent> {
+    LIFECYCLE_EVENTS.with(|events| std::mem::take(&mut *events.borrow_mut()))
+}
+
+unsafe fn create_app_delegate_class() {
+    let superclass = class!(NSObject);
+    let mut decl = ClassDecl::new("ToyUIAppDelegate", superclass).unwrap();
+
+    // applicationDidFinishLaunching:
+    extern "C" fn did_finish_launching(_: &Object, _: Sel, _: *mut Object) {
+        push_event(AppLifecycleEvent::Launched);
+    }
+
+    unsafe {
+        decl.add_method(
+            sel!(applicationDidFinishLaunching:),
+            did_finish_launching as extern "C" fn(&Object, Sel, *mut Object),
+        );
+    }
+
+    // applicationShouldHandleReopen:hasVisibleWindows: - dock icon clicked
+    extern "C" fn should_handle_reopen(_: &Object, _: Sel, _: *mut Object, visible: BOOL) -> BOOL {
+        push_event(AppLifecycleEvent::Reopen {
+            has_visible_windows: visible == YES,
+        });
+        // Let AppKit restore minimized windows as usual
+        YES
+    }
+
+    unsafe {
+        decl.add_method(
+            sel!(applicationShouldHandleReopen:hasVisibleWindows:),
+            should_handle_reopen as extern "C" fn(&Object, Sel, *mut Object, BOOL) -> BOOL,
+        );
+    }
+
+    // applicationShouldTerminate: - defer to the app's main loop
+    extern "C" fn should_terminate(_: &Object, _: Sel, _: *mut Object) -> u64 {
+        push_event(AppLifecycleEvent::TerminateRequested);
+        0 // NSTerminateCancel
+    }
+
+    unsafe {
+        decl.add_method(
+            sel!(applicationShouldTerminate:),
+            should_terminate as extern "C" fn(&Object, Sel, *mut Object) -> u64,
+        );
+    }
+
+    // applicationDidChangeScreenParameters: - display configuration changed
+    extern "C" fn did_change_screen_parameters(_: &Object, _: Sel, _: *mut Object) {
+        push_event(AppLifecycleEvent::DisplaysChanged);
+        // Notifications don't end the wait for the next event
+        super::wake_event_loop();
+    }
+
+    unsafe {
+        decl.add_method(
+            sel!(applicationDidChangeScreenParameters:),
+            did_change_screen_parameters as extern "C" fn(&Object, Sel, *mut Object),
+        );
+    }
+
+    unsafe {
+        APP_DELEGATE_CLASS = decl.register();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lifecycle_events_are_taken_in_order() {
+        push_event(AppLifecycleEvent::Launched);
+        push_event(AppLifecycleEvent::Reopen {
+            has_visible_windows: false,
+        });
+        push_event(AppLifecycleEvent::TerminateRequested);
+        assert_eq!(
+            take_lifecycle_events(),
+            vec![
+                AppLifecycleEvent::Launched,
+                AppLifecycleEvent::Reopen {
+                    has_visible_windows: false
+                },
+                AppLifecycleEvent::TerminateRequested,
+            ]
+        );
+        assert!(take_lifecycle_events().is_empty());
+    }
+}
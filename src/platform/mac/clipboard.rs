@@ -13,15 +13,33 @@
 //! if let Some(text) = Clipboard::paste() {
 //!     println!("Pasted: {}", text);
 //! }
+//!
+//! // Paste a screenshot
+//! if let Some(image) = Clipboard::get_image() {
+//!     canvas.insert_image(image);
+//! }
+//!
+//! // Custom typed data, identified by a uniform type identifier
+//! Clipboard::set_data("com.example.shapes", &serialized_shapes);
 //! ```
 //!
 //! The standard Edit menu (Cmd+C/V/X) is shown by `Menu::edit_menu()`.
 //! Text input elements should call these methods when handling those shortcuts.
 
+use crate::image::RgbaImage;
 use cocoa::base::{id, nil};
 use cocoa::foundation::NSString;
 use objc::{class, msg_send, sel, sel_impl};
 
+/// Pasteboard type for PNG data (`NSPasteboardTypePNG`)
+const PNG_TYPE: &str = "public.png";
+
+/// Pasteboard type for TIFF data (`NSPasteboardTypeTIFF`)
+const TIFF_TYPE: &str = "public.tiff";
+
+/// `NSBitmapImageFileTypePNG`
+const BITMAP_FILE_TYPE_PNG: u64 = 4;
+
 /// Clipboard access for copy/paste operations
 pub struct Clipboard;
 
@@ -74,6 +92,116 @@ impl Clipboard {
         }
     }
 
+    /// Copy an image to the system clipboard
+    ///
+    /// The image is written as both PNG and TIFF so that it can be pasted
+    /// into apps that only read one of the two.
+    pub fn set_image(image: &RgbaImage) -> bool {
+        let Ok(png) = image.to_png() else {
+            return false;
+        };
+
+        unsafe {
+            let pasteboard: id = msg_send![class!(NSPasteboard), generalPasteboard];
+            let _: i64 = msg_send![pasteboard, clearContents];
+
+            let png_data = ns_data(&png);
+            let mut result: bool =
+                msg_send![pasteboard, setData: png_data forType: ns_string(PNG_TYPE)];
+
+            let image_rep: id = msg_send![class!(NSBitmapImageRep), imageRepWithData: png_data];
+            if image_rep != nil {
+                let tiff_data: id = msg_send![image_rep, TIFFRepresentation];
+                if tiff_data != nil {
+                    let tiff_result: bool =
+                        msg_send![pasteboard, setData: tiff_data forType: ns_string(TIFF_TYPE)];
+                    result |= tiff_result;
+                }
+            }
+            result
+        }
+    }
+
+    /// Paste an image from the system clipboard
+    ///
+    /// PNG data is preferred; TIFF data (e.g. screenshots) is converted.
+    pub fn get_image() -> Option<RgbaImage> {
+        if let Some(png) = Self::get_data(PNG_TYPE) {
+            if let Ok(image) = RgbaImage::from_png(&png) {
+                return Some(image);
+            }
+        }
+
+        unsafe {
+            let pasteboard: id = msg_send![class!(NSPasteboard), generalPasteboard];
+            let tiff_data: id = msg_send![pasteboard, dataForType: ns_string(TIFF_TYPE)];
+            if tiff_data == nil {
+                return None;
+            }
+
+            let image_rep: id = msg_send![class!(NSBitmapImageRep), imageRepWithData: tiff_data];
+            if image_rep == nil {
+                return None;
+            }
+            let properties: id = msg_send![class!(NSDictionary), dictionary];
+            let png_data: id = msg_send![
+                image_rep,
+                representationUsingType: BITMAP_FILE_TYPE_PNG
+                properties: properties
+            ];
+            if png_data == nil {
+                return None;
+            }
+
+            RgbaImage::from_png(&data_to_vec(png_data)).ok()
+        }
+    }
+
+    /// Check if the clipboard contains an image
+    pub fn has_image() -> bool {
+        Self::has_type(PNG_TYPE) || Self::has_type(TIFF_TYPE)
+    }
+
+    /// Copy custom typed data to the system clipboard
+    ///
+    /// `data_type` is a uniform type identifier such as `"com.example.shapes"`.
+    pub fn set_data(data_type: &str, data: &[u8]) -> bool {
+        unsafe {
+            let pasteboard: id = msg_send![class!(NSPasteboard), generalPasteboard];
+            let _: i64 = msg_send![pasteboard, clearContents];
+
+            let result: bool =
+                msg_send![pasteboard, setData: ns_data(data) forType: ns_string(data_type)];
+            result
+        }
+    }
+
+    /// Paste custom typed data from the system clipboard
+    pub fn get_data(data_type: &str) -> Option<Vec<u8>> {
+        unsafe {
+            let pasteboard: id = msg_send![class!(NSPasteboard), generalPasteboard];
+            let data: id = msg_send![pasteboard, dataForType: ns_string(data_type)];
+
+            if data == nil {
+                return None;
+            }
+            Some(data_to_vec(data))
+        }
+    }
+
+    /// Check if the clipboard contains data of the given type
+    pub fn has_type(data_type: &str) -> bool {
+        unsafe {
+            let pasteboard: id = msg_send![class!(NSPasteboard), generalPasteboard];
+            let types: id = msg_send![pasteboard, types];
+            if types == nil {
+                return false;
+            }
+            let contains: bool = msg_send![types, containsObject: ns_string(data_type)];
+            contains
+        }
+    }
+
     /// Clear the clipboard contents
     pub fn clear() {
         unsafe {
@@ -83,6 +211,37 @@ impl Clipboard {
     }
 }
 
+/// Create an autoreleased NSString
+unsafe fn ns_string(s: &str) -> id {
+    unsafe {
+        let string: id = NSString::alloc(nil).init_str(s);
+        msg_send![string, autorelease]
+    }
+}
+
+/// Create an NSData copying the given bytes
+unsafe fn ns_data(bytes: &[u8]) -> id {
+    unsafe {
+        msg_send![
+            class!(NSData),
+            dataWithBytes: bytes.as_ptr() as *const std::ffi::c_void
+            length: bytes.len() as u64
+        ]
+    }
+}
+
+/// Copy the contents of an NSData into a Vec
+unsafe fn data_to_vec(data: id) -> Vec<u8> {
+    unsafe {
+        let length: u64 = msg_send![data, length];
+        let bytes: *const u8 = msg_send![data, bytes];
+        if bytes.is_null() || length == 0 {
+            return Vec::new();
+        }
+        std::slice::from_raw_parts(bytes, length as usize).to_vec()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -94,4 +253,22 @@ mod tests {
         assert!(Clipboard::has_text());
         assert_eq!(Clipboard::paste(), Some(test_text.to_string()));
     }
+
+    #[test]
+    fn test_clipboard_image_roundtrip() {
+        let mut image = RgbaImage::new(4, 3);
+        image.set_pixel(1, 2, [10, 20, 30, 255]);
+
+        assert!(Clipboard::set_image(&image));
+        assert!(Clipboard::has_image());
+        assert_eq!(Clipboard::get_image(), Some(image));
+    }
+
+    #[test]
+    fn test_clipboard_custom_data() {
+        let data_type = "dev.sol-ui.test-data";
+        assert!(Clipboard::set_data(data_type, &[1, 2, 3]));
+        assert!(Clipboard::has_type(data_type));
+        assert_eq!(Clipboard::get_data(data_type), Some(vec![1, 2, 3]));
+    }
 }
@@ -0,0 +1,60 @@
+//! Picking a color from anywhere on screen
+
+use crate::color::{Color, ColorExt};
+use block::ConcreteBlock;
+use cocoa::base::{id, nil};
+use objc::{class, msg_send, runtime::Class, sel, sel_impl};
+use std::cell::RefCell;
+
+/// Let the user pick a color from anywhere on screen with the system
+/// magnifier, then call `on_sample` with it, or with `None` if they cancel
+///
+/// Uses `NSColorSampler`, which asks for screen recording permission itself
+/// the first time it's needed; if the user declines, sampling is cancelled.
+/// Returns straight away: the sampler runs alongside the app's event loop
+/// and `on_sample` is called on the main thread once the user is done,
+/// outside of any frame, so update entities through an
+/// [`EntityStoreHandle`](crate::entity::EntityStoreHandle). On macOS before
+/// 10.15, `on_sample` is called with `None` immediately.
+pub fn sample_screen_color(on_sample: impl FnOnce(Option<Color>) + 'static) {
+    let Some(sampler_class) = Class::get("NSColorSampler") else {
+        on_sample(None);
+        return;
+    };
+
+    // The block may be called more than once in principle; only the first
+    // call counts
+    let on_sample = RefCell::new(Some(on_sample));
+    let handler = ConcreteBlock::new(move |color: id| {
+        if let Some(on_sample) = on_sample.borrow_mut().take() {
+            on_sample(unsafe { to_srgb(color) });
+        }
+    })
+    .copy();
+
+    unsafe {
+        let sampler: id = msg_send![sampler_class, new];
+        let _: () = msg_send![sampler, showSamplerWithSelectionHandler: &*handler];
+        // The sampler keeps itself alive while it's showing
+        let _: () = msg_send![sampler, release];
+    }
+}
+
+/// Convert an `NSColor` to sRGB components, or `None` for nil
+unsafe fn to_srgb(color: id) -> Option<Color> {
+    if color == nil {
+        return None;
+    }
+    unsafe {
+        let srgb_space: id = msg_send![class!(NSColorSpace), sRGBColorSpace];
+        let color: id = msg_send![color, colorUsingColorSpace: srgb_space];
+        if color == nil {
+            return None;
+        }
+        let r: f64 = msg_send![color, redComponent];
+        let g: f64 = msg_send![color, greenComponent];
+        let b: f64 = msg_send![color, blueComponent];
+        let a: f64 = msg_send![color, alphaComponent];
+        Some(Color::rgba(r as f32, g as f32, b as f32, a as f32))
+    }
+}
@@ -0,0 +1,54 @@
+//! Moving and pinning the mouse cursor
+
+use core_graphics::geometry::CGPoint;
+use glam::Vec2;
+use objc::{class, msg_send, sel, sel_impl};
+use std::cell::Cell;
+
+#[link(name = "CoreGraphics", kind = "framework")]
+unsafe extern "C" {
+    fn CGWarpMouseCursorPosition(new_cursor_position: CGPoint) -> i32;
+    fn CGAssociateMouseAndMouseCursorPosition(connected: u32) -> i32;
+}
+
+thread_local! {
+    /// Whether the cursor is hidden and detached from mouse motion
+    static CURSOR_PINNED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Move the cursor to `position`, in points from the top-left of the main
+/// display
+///
+/// No mouse move event is sent for the jump. See
+/// [`Window::set_cursor_position`](super::Window::set_cursor_position) to
+/// place it relative to the window's content.
+pub fn set_cursor_position(position: Vec2) {
+    unsafe {
+        CGWarpMouseCursorPosition(CGPoint::new(position.x as f64, position.y as f64));
+        // Warping pauses mouse events briefly; reconnecting the cursor
+        // resumes them straight away
+        if !is_cursor_pinned() {
+            CGAssociateMouseAndMouseCursorPosition(1);
+        }
+    }
+}
+
+/// Hide the cursor and stop it following the mouse, or undo that
+pub(crate) fn set_cursor_pinned(pinned: bool) {
+    if CURSOR_PINNED.with(|p| p.replace(pinned)) == pinned {
+        return;
+    }
+    unsafe {
+        if pinned {
+            let _: () = msg_send![class!(NSCursor), hide];
+        } else {
+            let _: () = msg_send![class!(NSCursor), unhide];
+        }
+        CGAssociateMouseAndMouseCursorPosition(!pinned as u32);
+    }
+}
+
+/// Whether the cursor is pinned for a pointer lock
+pub(crate) fn is_cursor_pinned() -> bool {
+    CURSOR_PINNED.with(|p| p.get())
+}
@@ -0,0 +1,46 @@
+//! Access to the user's `NSUserDefaults`
+
+use cocoa::base::{YES, id, nil};
+use cocoa::foundation::NSString;
+use objc::{class, msg_send, runtime::BOOL, sel, sel_impl};
+
+/// Read a numeric user default, or `None` if it isn't set
+pub fn user_default_f64(key: &str) -> Option<f64> {
+    unsafe {
+        let defaults: id = msg_send![class!(NSUserDefaults), standardUserDefaults];
+        let key = NSString::alloc(nil).init_str(key);
+        let value: id = msg_send![defaults, objectForKey: key];
+        let _: () = msg_send![key, release];
+
+        if value == nil {
+            return None;
+        }
+        let responds: BOOL = msg_send![value, respondsToSelector: sel!(doubleValue)];
+        if responds != YES {
+            return None;
+        }
+        Some(msg_send![value, doubleValue])
+    }
+}
+
+/// Read a string user default, or `None` if it isn't set
+pub fn user_default_string(key: &str) -> Option<String> {
+    unsafe {
+        let defaults: id = msg_send![class!(NSUserDefaults), standardUserDefaults];
+        let key = NSString::alloc(nil).init_str(key);
+        let value: id = msg_send![defaults, stringForKey: key];
+        let _: () = msg_send![key, release];
+
+        if value == nil {
+            return None;
+        }
+        let bytes: *const i8 = msg_send![value, UTF8String];
+        if bytes.is_null() {
+            return None;
+        }
+        std::ffi::CStr::from_ptr(bytes)
+            .to_str()
+            .ok()
+            .map(|s| s.to_string())
+    }
+}
@@ -0,0 +1,197 @@
+//! Enumerating the connected displays
+
+use crate::geometry::Rect;
+use cocoa::base::{NO, id, nil};
+use cocoa::foundation::{NSRect, NSString};
+use glam::Vec2;
+use objc::{class, msg_send, runtime::BOOL, sel, sel_impl};
+use std::ffi::CStr;
+
+/// Identifies a display while it stays connected (`CGDirectDisplayID`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DisplayId(pub u32);
+
+/// A connected display
+///
+/// Frames are in screen coordinates like
+/// [`Window::frame`](super::Window::frame): points with the origin at the
+/// bottom-left of the main display and y going up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Display {
+    pub id: DisplayId,
+    /// Localized name, like "Built-in Retina Display"
+    pub name: String,
+    /// The whole display
+    pub frame: Rect,
+    /// The part of the display not covered by the menu bar and Dock
+    pub visible_frame: Rect,
+    /// Pixels per point
+    pub scale_factor: f32,
+    /// Refresh rate in Hz, or 0 if unknown
+    pub refresh_rate: f32,
+    /// Whether this is the display with the menu bar
+    pub is_main: bool,
+}
+
+impl Display {
+    /// Where a window of `size` would be centered in the visible frame
+    pub fn centered_origin(&self, size: Vec2) -> Vec2 {
+        self.visible_frame.pos + (self.visible_frame.size - size) / 2.0
+    }
+}
+
+#[link(name = "CoreGraphics", kind = "framework")]
+unsafe extern "C" {
+    fn CGMainDisplayID() -> u32;
+    fn CGDisplayCopyDisplayMode(display: u32) -> *mut std::ffi::c_void;
+    fn CGDisplayModeGetRefreshRate(mode: *mut std::ffi::c_void) -> f64;
+    fn CGDisplayModeRelease(mode: *mut std::ffi::c_void);
+}
+
+/// The connected displays, with the main display first
+pub fn displays() -> Vec<Display> {
+    unsafe {
+        let screens: id = msg_send![class!(NSScreen), screens];
+        let count: usize = msg_send![screens, count];
+        let main_id = CGMainDisplayID();
+        let mut displays: Vec<Display> = (0..count)
+            .map(|i| {
+                let screen: id = msg_send![screens, objectAtIndex: i];
+                display_from_screen(screen, main_id)
+            })
+            .collect();
+        displays.sort_by_key(|display| !display.is_main);
+        displays
+    }
+}
+
+/// The display with `id`, if it's still connected
+pub fn display(id: DisplayId) -> Option<Display> {
+    displays().into_iter().find(|display| display.id == id)
+}
+
+/// The `NSScreen` for `id`, if it's still connected
+pub(crate) unsafe fn screen_for_display(display: DisplayId) -> Option<id> {
+    unsafe {
+        let screens: id = msg_send![class!(NSScreen), screens];
+        let count: usize = msg_send![screens, count];
+        (0..count)
+            .map(|i| -> id { msg_send![screens, objectAtIndex: i] })
+            .find(|&screen| display_id(screen) == display)
+    }
+}
+
+/// The display `screen` is on
+pub(crate) unsafe fn display_id(screen: id) -> DisplayId {
+    unsafe {
+        let description: id = msg_send![screen, deviceDescription];
+        let key = NSString::alloc(nil).init_str("NSScreenNumber");
+        let number: id = msg_send![description, objectForKey: key];
+        let _: () = msg_send![key, release];
+        if number == nil {
+            return DisplayId(0);
+        }
+        let value: u32 = msg_send![number, unsignedIntValue];
+        DisplayId(value)
+    }
+}
+
+unsafe fn display_from_screen(screen: id, main_id: u32) -> Display {
+    unsafe {
+        let display = display_id(screen);
+        let frame: NSRect = msg_send![screen, frame];
+        let visible_frame: NSRect = msg_send![screen, visibleFrame];
+        let scale_factor: f64 = msg_send![screen, backingScaleFactor];
+        Display {
+            id: display,
+            name: localized_name(screen),
+            frame: to_rect(frame),
+            visible_frame: to_rect(visible_frame),
+            scale_factor: scale_factor as f32,
+            refresh_rate: refresh_rate(screen, display),
+            is_main: display.0 == main_id,
+        }
+    }
+}
+
+unsafe fn localized_name(screen: id) -> String {
+    unsafe {
+        let responds: BOOL = msg_send![screen, respondsToSelector: sel!(localizedName)];
+        if responds == NO {
+            return String::new();
+        }
+        let name: id = msg_send![screen, localizedName];
+        if name == nil {
+            return String::new();
+        }
+        let utf8: *const std::ffi::c_char = msg_send![name, UTF8String];
+        CStr::from_ptr(utf8).to_string_lossy().into_owned()
+    }
+}
+
+unsafe fn refresh_rate(screen: id, display: DisplayId) -> f32 {
+    unsafe {
+        // macOS 12 reports variable refresh rate displays correctly here
+        let responds: BOOL = msg_send![screen, respondsToSelector: sel!(maximumFramesPerSecond)];
+        if responds != NO {
+            let fps: isize = msg_send![screen, maximumFramesPerSecond];
+            if fps > 0 {
+                return fps as f32;
+            }
+        }
+        let mode = CGDisplayCopyDisplayMode(display.0);
+        if mode.is_null() {
+            return 0.0;
+        }
+        let rate = CGDisplayModeGetRefreshRate(mode);
+        CGDisplayModeRelease(mode);
+        rate as f32
+    }
+}
+
+fn to_rect(rect: NSRect) -> Rect {
+    Rect::from_pos_size(
+        Vec2::new(rect.origin.x as f32, rect.origin.y as f32),
+        Vec2::new(rect.size.width as f32, rect.size.height as f32),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cocoa::foundation::{NSPoint, NSSize};
+
+    fn display(visible_frame: Rect) -> Display {
+        Display {
+            id: DisplayId(1),
+            name: String::new(),
+            frame: visible_frame,
+            visible_frame,
+            scale_factor: 2.0,
+            refresh_rate: 60.0,
+            is_main: true,
+        }
+    }
+
+    #[test]
+    fn test_to_rect_keeps_screen_coordinates() {
+        let rect = to_rect(NSRect::new(
+            NSPoint::new(-1440.0, 25.0),
+            NSSize::new(1440.0, 875.0),
+        ));
+        assert_eq!(rect.pos, Vec2::new(-1440.0, 25.0));
+        assert_eq!(rect.size, Vec2::new(1440.0, 875.0));
+    }
+
+    #[test]
+    fn test_centered_origin_uses_visible_frame() {
+        let display = display(Rect::from_pos_size(
+            Vec2::new(0.0, 80.0),
+            Vec2::new(1920.0, 1000.0),
+        ));
+        assert_eq!(
+            display.centered_origin(Vec2::new(800.0, 600.0)),
+            Vec2::new(560.0, 280.0)
+        );
+    }
+}
@@ -0,0 +1,193 @@
+//! System-wide keyboard shortcuts
+//!
+//! Lets launcher and utility apps be summoned while other applications are
+//! focused. Key presses in other apps are watched with an `NSEvent` global
+//! monitor, which macOS only delivers once the user has granted the app
+//! accessibility access; see [`accessibility_access`].
+
+use crate::interaction::Shortcut;
+use crate::layer::{Key, Modifiers};
+use block::ConcreteBlock;
+use cocoa::base::{id, nil};
+use core_foundation::{
+    base::TCFType,
+    boolean::CFBoolean,
+    dictionary::{CFDictionary, CFDictionaryRef},
+    string::{CFString, CFStringRef},
+};
+use objc::{class, msg_send, sel, sel_impl};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[link(name = "ApplicationServices", kind = "framework")]
+unsafe extern "C" {
+    static kAXTrustedCheckOptionPrompt: CFStringRef;
+    fn AXIsProcessTrustedWithOptions(options: CFDictionaryRef) -> bool;
+}
+
+/// `NSEventMaskKeyDown`
+const KEY_DOWN_MASK: u64 = 1 << 10;
+
+/// Whether the app may watch key presses in other apps
+///
+/// With `prompt`, macOS asks the user to grant access in System Settings
+/// if they haven't yet. Access takes effect for hotkeys registered after
+/// it's granted, so apps typically check again when they're next
+/// activated.
+pub fn accessibility_access(prompt: bool) -> bool {
+    unsafe {
+        let key = CFString::wrap_under_get_rule(kAXTrustedCheckOptionPrompt);
+        let value = if prompt {
+            CFBoolean::true_value()
+        } else {
+            CFBoolean::false_value()
+        };
+        let options = CFDictionary::from_CFType_pairs(&[(key, value)]);
+        AXIsProcessTrustedWithOptions(options.as_concrete_TypeRef())
+    }
+}
+
+/// A registered system-wide shortcut
+///
+/// The shortcut stays active until this is dropped.
+#[must_use = "the hotkey is unregistered when dropped"]
+pub struct GlobalHotkey {
+    global_monitor: id,
+    local_monitor: id,
+}
+
+impl Drop for GlobalHotkey {
+    fn drop(&mut self) {
+        unsafe {
+            for monitor in [self.global_monitor, self.local_monitor] {
+                if monitor != nil {
+                    let _: () = msg_send![class!(NSEvent), removeMonitor: monitor];
+                    let _: () = msg_send![monitor, release];
+                }
+            }
+        }
+    }
+}
+
+/// Call `callback` whenever `chord` is pressed, in any app
+///
+/// Prompts for accessibility access the first time, since presses in other
+/// apps aren't delivered without it; presses while this app is active are
+/// caught either way, and don't reach the app's own key handlers.
+/// `callback` runs on the main thread outside of any frame, so update
+/// entities through an [`EntityStoreHandle`](crate::entity::EntityStoreHandle).
+///
+/// # Example
+/// ```ignore
+/// let _hotkey = global_hotkey(
+///     Shortcut::new(Key::Space, ShortcutModifiers::alt()),
+///     move || window.focus(),
+/// );
+/// ```
+pub fn global_hotkey(chord: Shortcut, callback: impl FnMut() + 'static) -> GlobalHotkey {
+    accessibility_access(true);
+
+    let callback: Rc<RefCell<dyn FnMut()>> = Rc::new(RefCell::new(callback));
+    let fire = move |event: id| -> bool {
+        if !unsafe { matches_chord(event, &chord) } {
+            return false;
+        }
+        (callback.borrow_mut())();
+        // The app may be waiting for its own events; let it see the change
+        super::wake_event_loop();
+        true
+    };
+
+    let global_fire = fire.clone();
+    let global_handler = ConcreteBlock::new(move |event: id| {
+        global_fire(event);
+    })
+    .copy();
+    let local_handler = ConcreteBlock::new(move |event: id| -> id {
+        if fire(event) { nil } else { event }
+    })
+    .copy();
+
+    unsafe {
+        let global_monitor: id = msg_send![
+            class!(NSEvent),
+            addGlobalMonitorForEventsMatchingMask: KEY_DOWN_MASK
+            handler: &*global_handler
+        ];
+        let local_monitor: id = msg_send![
+            class!(NSEvent),
+            addLocalMonitorForEventsMatchingMask: KEY_DOWN_MASK
+            handler: &*local_handler
+        ];
+        // Monitors are returned autoreleased and must live until removed
+        if global_monitor != nil {
+            let _: id = msg_send![global_monitor, retain];
+        }
+        let _: id = msg_send![local_monitor, retain];
+        GlobalHotkey {
+            global_monitor,
+            local_monitor,
+        }
+    }
+}
+
+/// Whether a key down `event` is `chord`, ignoring key repeats
+unsafe fn matches_chord(event: id, chord: &Shortcut) -> bool {
+    unsafe {
+        let is_repeat: bool = msg_send![event, isARepeat];
+        let key_code: u16 = msg_send![event, keyCode];
+        let flags: u64 = msg_send![event, modifierFlags];
+        chord_pressed(chord, key_code, flags, is_repeat)
+    }
+}
+
+/// Whether a key down with `key_code` and `NSEventModifierFlags` `flags` is
+/// `chord`
+fn chord_pressed(chord: &Shortcut, key_code: u16, flags: u64, is_repeat: bool) -> bool {
+    if is_repeat {
+        return false;
+    }
+    let modifiers = Modifiers {
+        shift: flags & (1 << 17) != 0,
+        ctrl: flags & (1 << 18) != 0,
+        alt: flags & (1 << 19) != 0,
+        cmd: flags & (1 << 20) != 0,
+        caps_lock: false,
+    };
+    chord.matches(Key::from_keycode(key_code), &modifiers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interaction::ShortcutModifiers;
+
+    /// `kVK_Space`
+    const SPACE: u16 = 0x31;
+    /// `NSEventModifierFlagOption`
+    const OPTION: u64 = 1 << 19;
+
+    #[test]
+    fn test_chord_pressed_matches_key_and_modifiers() {
+        let chord = Shortcut::new(Key::Space, ShortcutModifiers::alt());
+        assert!(chord_pressed(&chord, SPACE, OPTION, false));
+        // kVK_ANSI_A
+        assert!(!chord_pressed(&chord, 0x00, OPTION, false));
+        assert!(!chord_pressed(&chord, SPACE, 0, false));
+    }
+
+    #[test]
+    fn test_chord_pressed_needs_exact_modifiers() {
+        let chord = Shortcut::new(Key::Space, ShortcutModifiers::alt());
+        // NSEventModifierFlagCommand
+        assert!(!chord_pressed(&chord, SPACE, OPTION | 1 << 20, false));
+        // Caps lock (NSEventModifierFlagCapsLock) doesn't change the chord
+        assert!(chord_pressed(&chord, SPACE, OPTION | 1 << 16, false));
+    }
+
+    #[test]
+    fn test_chord_pressed_ignores_key_repeats() {
+        let chord = Shortcut::new(Key::Space, ShortcutModifiers::alt());
+        assert!(!chord_pressed(&chord, SPACE, OPTION, true));
+    }
+}
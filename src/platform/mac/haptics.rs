@@ -0,0 +1,63 @@
+//! Force Touch trackpad feedback
+//!
+//! Wraps `NSHapticFeedbackManager`. The trackpad only plays feedback while
+//! the user is touching it, and not at all on other pointing devices, so
+//! it's safe to call these freely from interaction handlers.
+
+use cocoa::base::{id, nil};
+use objc::{class, msg_send, sel, sel_impl};
+
+/// Kind of feedback to play, matching `NSHapticFeedbackPattern`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Pattern {
+    /// General feedback with no particular meaning
+    #[default]
+    Generic,
+    /// Something snapped into alignment, like a dragged item reaching a guide
+    Alignment,
+    /// A value crossed a step, like a toggle flipping or a slider detent
+    LevelChange,
+}
+
+impl Pattern {
+    fn raw(self) -> isize {
+        match self {
+            Pattern::Generic => 0,
+            Pattern::Alignment => 1,
+            Pattern::LevelChange => 2,
+        }
+    }
+}
+
+/// Play `pattern` on the trackpad
+///
+/// The feedback is timed to the next time the window's contents reach the
+/// screen, so it lines up with what the user sees.
+pub fn perform(pattern: Pattern) {
+    // NSHapticFeedbackPerformanceTimeDrawCompleted
+    const DRAW_COMPLETED: usize = 2;
+    unsafe {
+        let performer: id = msg_send![class!(NSHapticFeedbackManager), defaultPerformer];
+        if performer == nil {
+            return;
+        }
+        let _: () = msg_send![
+            performer,
+            performFeedbackPattern: pattern.raw()
+            performanceTime: DRAW_COMPLETED
+        ];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patterns_match_appkit_values() {
+        assert_eq!(Pattern::default(), Pattern::Generic);
+        assert_eq!(Pattern::Generic.raw(), 0);
+        assert_eq!(Pattern::Alignment.raw(), 1);
+        assert_eq!(Pattern::LevelChange.raw(), 2);
+    }
+}
@@ -0,0 +1,499 @@
+//! RGBA atlas for small images
+//!
+//! Icons and other small images are packed into one shared texture, like
+//! glyphs in the glyph atlas, so a run of them draws with a single texture
+//! bind. Unlike glyphs, images come and go, so space is freed when an image
+//! stops being drawn and the atlas is repacked once too much of it is lost
+//! to holes.
+//!
+//! Repacking writes a new texture at the start of a frame, so draws already
+//! encoded against the old layout keep their texture until they finish.
+
+use crate::image::RgbaImage;
+use metal::{Device, MTLPixelFormat, MTLRegion, MTLStorageMode, MTLTextureUsage, Texture};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::debug;
+
+/// Width and height of the atlas texture
+const ATLAS_SIZE: u32 = 1024;
+
+/// Largest image side, in pixels, packed into the atlas; bigger images get
+/// their own texture
+pub(crate) const ATLAS_MAX_IMAGE_SIZE: u32 = 128;
+
+/// Border around each image, filled with copies of its edge pixels so
+/// bilinear filtering at the edges doesn't blend in neighboring images
+const ATLAS_PADDING: u32 = 1;
+
+/// Fraction of the claimed space lost to holes before the atlas is repacked
+const REPACK_FRAGMENTATION: f32 = 0.5;
+
+/// A rectangle of the atlas, in pixels
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct AtlasRegion {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+/// A run of pixels along a shelf, either holding an image or free
+#[derive(Debug)]
+struct Slot {
+    x: u32,
+    width: u32,
+    used: bool,
+}
+
+/// A row of the atlas holding images no taller than it
+#[derive(Debug)]
+struct Shelf {
+    y: u32,
+    height: u32,
+    /// Slots from left to right, ending at the shelf's used width
+    slots: Vec<Slot>,
+}
+
+impl Shelf {
+    fn end(&self) -> u32 {
+        self.slots.last().map_or(0, |slot| slot.x + slot.width)
+    }
+}
+
+/// Shelf packing with freeing, in atlas pixels
+#[derive(Debug)]
+struct AtlasAllocator {
+    width: u32,
+    height: u32,
+    shelves: Vec<Shelf>,
+}
+
+impl AtlasAllocator {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            shelves: Vec::new(),
+        }
+    }
+
+    /// Reserve a `width` by `height` region, or `None` if there's no room
+    fn allocate(&mut self, width: u32, height: u32) -> Option<AtlasRegion> {
+        if width == 0 || height == 0 || width > self.width || height > self.height {
+            return None;
+        }
+
+        // Shelves much taller than the image waste the rest of their height
+        let fits = |shelf: &Shelf| shelf.height >= height && shelf.height <= height * 2;
+
+        // Reuse the tightest free slot first
+        let reusable = self
+            .shelves
+            .iter()
+            .enumerate()
+            .filter(|(_, shelf)| fits(shelf))
+            .flat_map(|(shelf_index, shelf)| {
+                shelf
+                    .slots
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, slot)| !slot.used && slot.width >= width)
+                    .map(move |(slot_index, slot)| {
+                        let waste = (shelf.height - height) * width + (slot.width - width) * height;
+                        (waste, shelf_index, slot_index)
+                    })
+            })
+            .min_by_key(|(waste, ..)| *waste);
+        if let Some((_, shelf_index, slot_index)) = reusable {
+            let shelf = &mut self.shelves[shelf_index];
+            let slot = &mut shelf.slots[slot_index];
+            let remaining = slot.width - width;
+            slot.width = width;
+            slot.used = true;
+            let x = slot.x;
+            if remaining > 0 {
+                shelf.slots.insert(
+                    slot_index + 1,
+                    Slot {
+                        x: x + width,
+                        width: remaining,
+                        used: false,
+                    },
+                );
+            }
+            return Some(AtlasRegion {
+                x,
+                y: shelf.y,
+                width,
+                height,
+            });
+        }
+
+        // Then the end of the shortest shelf with room
+        let atlas_width = self.width;
+        let shelf = self
+            .shelves
+            .iter_mut()
+            .filter(|shelf| fits(shelf) && shelf.end() + width <= atlas_width)
+            .min_by_key(|shelf| shelf.height);
+        if let Some(shelf) = shelf {
+            let x = shelf.end();
+            shelf.slots.push(Slot {
+                x,
+                width,
+                used: true,
+            });
+            return Some(AtlasRegion {
+                x,
+                y: shelf.y,
+                width,
+                height,
+            });
+        }
+
+        // Then a new shelf below the others
+        let y = self
+            .shelves
+            .last()
+            .map_or(0, |shelf| shelf.y + shelf.height);
+        if y + height > self.height {
+            return None;
+        }
+        self.shelves.push(Shelf {
+            y,
+            height,
+            slots: vec![Slot {
+                x: 0,
+                width,
+                used: true,
+            }],
+        });
+        Some(AtlasRegion {
+            x: 0,
+            y,
+            width,
+            height,
+        })
+    }
+
+    /// Release a region returned by `allocate`
+    fn free(&mut self, region: AtlasRegion) {
+        let Some(shelf) = self.shelves.iter_mut().find(|shelf| shelf.y == region.y) else {
+            return;
+        };
+        let Some(index) = shelf.slots.iter().position(|slot| slot.x == region.x) else {
+            return;
+        };
+        shelf.slots[index].used = false;
+
+        // Merge with free neighbors
+        if index + 1 < shelf.slots.len() && !shelf.slots[index + 1].used {
+            let next = shelf.slots.remove(index + 1);
+            shelf.slots[index].width += next.width;
+        }
+        if index > 0 && !shelf.slots[index - 1].used {
+            let slot = shelf.slots.remove(index);
+            shelf.slots[index - 1].width += slot.width;
+        }
+        // Free space at the end of a shelf is just unclaimed
+        if shelf.slots.last().is_some_and(|slot| !slot.used) {
+            shelf.slots.pop();
+        }
+
+        // Drop empty shelves at the bottom so their rows can be reshaped
+        while self
+            .shelves
+            .last()
+            .is_some_and(|shelf| shelf.slots.is_empty())
+        {
+            self.shelves.pop();
+        }
+    }
+
+    /// Pixels held by allocated regions
+    fn used_pixels(&self) -> u64 {
+        self.shelves
+            .iter()
+            .flat_map(|shelf| {
+                shelf
+                    .slots
+                    .iter()
+                    .filter(|slot| slot.used)
+                    .map(|slot| slot.width as u64 * shelf.height as u64)
+            })
+            .sum()
+    }
+
+    /// Pixels claimed by shelves, whether in use or not
+    fn claimed_pixels(&self) -> u64 {
+        self.shelves
+            .iter()
+            .map(|shelf| shelf.end() as u64 * shelf.height as u64)
+            .sum()
+    }
+
+    /// Fraction of the claimed space not in use
+    fn fragmentation(&self) -> f32 {
+        let claimed = self.claimed_pixels();
+        if claimed == 0 {
+            return 0.0;
+        }
+        1.0 - self.used_pixels() as f32 / claimed as f32
+    }
+}
+
+/// `image` with its edge pixels repeated `ATLAS_PADDING` times on each side
+fn padded_pixels(image: &RgbaImage) -> Vec<u8> {
+    let (width, height) = (image.width(), image.height());
+    let padded_width = width + ATLAS_PADDING * 2;
+    let padded_height = height + ATLAS_PADDING * 2;
+    let source = image.as_raw();
+
+    let mut pixels = Vec::with_capacity(padded_width as usize * padded_height as usize * 4);
+    for y in 0..padded_height {
+        let source_y = y.saturating_sub(ATLAS_PADDING).min(height - 1);
+        for x in 0..padded_width {
+            let source_x = x.saturating_sub(ATLAS_PADDING).min(width - 1);
+            let offset = (source_y as usize * width as usize + source_x as usize) * 4;
+            pixels.extend_from_slice(&source[offset..offset + 4]);
+        }
+    }
+    pixels
+}
+
+/// An image packed into the atlas and the last frame it was drawn in
+///
+/// Images are keyed by address, so the image is held to keep that address
+/// from being reused while it's in the atlas, and to re-upload it when
+/// repacking.
+struct AtlasEntry {
+    image: Arc<RgbaImage>,
+    /// Padded region holding the image
+    region: AtlasRegion,
+    last_used: u64,
+}
+
+/// A shared RGBA texture holding small images
+pub(crate) struct ImageAtlas {
+    device: Device,
+    texture: Texture,
+    allocator: AtlasAllocator,
+    entries: HashMap<usize, AtlasEntry>,
+    /// An image didn't fit because of holes; repack at the next frame
+    repack_pending: bool,
+}
+
+impl ImageAtlas {
+    pub(crate) fn new(device: &Device) -> Self {
+        Self {
+            device: device.clone(),
+            texture: new_atlas_texture(device),
+            allocator: AtlasAllocator::new(ATLAS_SIZE, ATLAS_SIZE),
+            entries: HashMap::new(),
+            repack_pending: false,
+        }
+    }
+
+    /// Whether `image` is small enough to pack into the atlas
+    pub(crate) fn accepts(image: &RgbaImage) -> bool {
+        let (width, height) = (image.width(), image.height());
+        width > 0 && height > 0 && width.max(height) <= ATLAS_MAX_IMAGE_SIZE
+    }
+
+    /// Make sure `image` is in the atlas, uploading it if needed
+    ///
+    /// Returns `false` if there's no room, in which case the image needs a
+    /// texture of its own. If the atlas is fragmented it's repacked at the
+    /// start of the next frame, making room for the image then.
+    pub(crate) fn prepare(&mut self, image: &Arc<RgbaImage>, frame: u64) -> bool {
+        let key = Arc::as_ptr(image) as usize;
+        if let Some(entry) = self.entries.get_mut(&key) {
+            entry.last_used = frame;
+            return true;
+        }
+
+        let width = image.width() + ATLAS_PADDING * 2;
+        let height = image.height() + ATLAS_PADDING * 2;
+        let Some(region) = self.allocator.allocate(width, height) else {
+            self.repack_pending |= self.allocator.fragmentation() > 0.0;
+            return false;
+        };
+
+        self.upload(image, region);
+        self.entries.insert(
+            key,
+            AtlasEntry {
+                image: image.clone(),
+                region,
+                last_used: frame,
+            },
+        );
+        true
+    }
+
+    /// Texture coordinates of an image in the atlas
+    pub(crate) fn uv_bounds(&self, key: usize) -> Option<([f32; 2], [f32; 2])> {
+        let entry = self.entries.get(&key)?;
+        let size = ATLAS_SIZE as f32;
+        let region = entry.region;
+        let min = [
+            (region.x + ATLAS_PADDING) as f32 / size,
+            (region.y + ATLAS_PADDING) as f32 / size,
+        ];
+        let max = [
+            (region.x + region.width - ATLAS_PADDING) as f32 / size,
+            (region.y + region.height - ATLAS_PADDING) as f32 / size,
+        ];
+        Some((min, max))
+    }
+
+    /// Drop images not drawn for more than `max_age` frames, repacking if
+    /// that leaves the atlas too fragmented
+    ///
+    /// Call at the start of a frame, before any images are drawn.
+    pub(crate) fn evict_unused(&mut self, frame: u64, max_age: u64) {
+        let allocator = &mut self.allocator;
+        self.entries.retain(|_, entry| {
+            let keep = frame - entry.last_used <= max_age;
+            if !keep {
+                allocator.free(entry.region);
+            }
+            keep
+        });
+
+        if self.repack_pending || self.allocator.fragmentation() > REPACK_FRAGMENTATION {
+            self.repack();
+        }
+    }
+
+    pub(crate) fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    /// GPU memory used by the atlas texture, four bytes per pixel
+    pub(crate) fn bytes(&self) -> u64 {
+        ATLAS_SIZE as u64 * ATLAS_SIZE as u64 * 4
+    }
+
+    /// Pack every image again from scratch into a new texture, tallest first
+    fn repack(&mut self) {
+        debug!(
+            "Repacking image atlas: {} images, {:.0}% fragmented",
+            self.entries.len(),
+            self.allocator.fragmentation() * 100.0
+        );
+        self.repack_pending = false;
+        self.allocator = AtlasAllocator::new(ATLAS_SIZE, ATLAS_SIZE);
+        self.texture = new_atlas_texture(&self.device);
+
+        let mut keys: Vec<usize> = self.entries.keys().copied().collect();
+        keys.sort_by_key(|key| std::cmp::Reverse(self.entries[key].region.height));
+        for key in keys {
+            let entry = &self.entries[&key];
+            let (width, height) = (entry.region.width, entry.region.height);
+            match self.allocator.allocate(width, height) {
+                Some(region) => {
+                    let image = entry.image.clone();
+                    self.upload(&image, region);
+                    if let Some(entry) = self.entries.get_mut(&key) {
+                        entry.region = region;
+                    }
+                }
+                // Packing tallest first never needs more room than before,
+                // but drop the image rather than draw garbage if it does
+                None => {
+                    self.entries.remove(&key);
+                }
+            }
+        }
+    }
+
+    fn upload(&self, image: &RgbaImage, region: AtlasRegion) {
+        let pixels = padded_pixels(image);
+        self.texture.replace_region(
+            MTLRegion::new_2d(
+                region.x as u64,
+                region.y as u64,
+                region.width as u64,
+                region.height as u64,
+            ),
+            0,
+            pixels.as_ptr() as *const _,
+            region.width as u64 * 4,
+        );
+    }
+}
+
+fn new_atlas_texture(device: &Device) -> Texture {
+    let descriptor = metal::TextureDescriptor::new();
+    descriptor.set_pixel_format(MTLPixelFormat::RGBA8Unorm);
+    descriptor.set_width(ATLAS_SIZE as u64);
+    descriptor.set_height(ATLAS_SIZE as u64);
+    descriptor.set_usage(MTLTextureUsage::ShaderRead);
+    descriptor.set_storage_mode(MTLStorageMode::Managed);
+    device.new_texture(&descriptor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allocate_packs_shelves() {
+        let mut allocator = AtlasAllocator::new(64, 64);
+        let a = allocator.allocate(30, 10).unwrap();
+        let b = allocator.allocate(30, 10).unwrap();
+        let c = allocator.allocate(30, 10).unwrap();
+
+        assert_eq!((a.x, a.y), (0, 0));
+        assert_eq!((b.x, b.y), (30, 0));
+        // No room left on the first shelf
+        assert_eq!((c.x, c.y), (0, 10));
+        assert!(allocator.allocate(65, 1).is_none());
+    }
+
+    #[test]
+    fn test_free_reuses_space() {
+        let mut allocator = AtlasAllocator::new(64, 64);
+        let a = allocator.allocate(20, 10).unwrap();
+        let _b = allocator.allocate(20, 10).unwrap();
+
+        allocator.free(a);
+        assert!(allocator.fragmentation() > 0.0);
+
+        // A narrower image of the same height goes in the hole
+        let c = allocator.allocate(12, 10).unwrap();
+        assert_eq!((c.x, c.y), (0, 0));
+        let d = allocator.allocate(8, 10).unwrap();
+        assert_eq!((d.x, d.y), (12, 0));
+        assert_eq!(allocator.fragmentation(), 0.0);
+    }
+
+    #[test]
+    fn test_free_trims_empty_shelves() {
+        let mut allocator = AtlasAllocator::new(64, 64);
+        let a = allocator.allocate(20, 10).unwrap();
+        let b = allocator.allocate(20, 30).unwrap();
+
+        allocator.free(b);
+        allocator.free(a);
+        assert_eq!(allocator.claimed_pixels(), 0);
+
+        // The freed rows can take a differently sized shelf
+        let c = allocator.allocate(64, 64).unwrap();
+        assert_eq!((c.x, c.y), (0, 0));
+    }
+
+    #[test]
+    fn test_padded_pixels_repeats_edges() {
+        let mut image = RgbaImage::new(2, 1);
+        image.set_pixel(0, 0, [1, 1, 1, 1]);
+        image.set_pixel(1, 0, [2, 2, 2, 2]);
+
+        let pixels = padded_pixels(&image);
+        // 4x3 pixels, every row a copy of the image row with its edges repeated
+        let row = [1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2, 2];
+        assert_eq!(pixels, row.repeat(3));
+    }
+}
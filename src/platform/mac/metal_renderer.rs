@@ -1,19 +1,40 @@
+use super::image_atlas::ImageAtlas;
 use crate::{
-    color::Color,
-    geometry::Rect,
-    render::{DrawCommand, DrawList},
-    style::{ElementStyle, Fill},
-    text_system::{ShapedText, TextSystem},
+    color::{
+        Color, ColorExt,
+        colors::{BLACK, WHITE},
+    },
+    debug::RendererStats,
+    error::{SolError, SolResult},
+    geometry::{Corners, Rect},
+    image::RgbaImage,
+    post_process::{PostProcess, PostProcessShader},
+    render::{
+        CustomDrawContext, DrawCommand, DrawList,
+        frame_graph::{CompiledFrameGraph, FrameGraph, TextureDesc, TextureFormat},
+    },
+    renderer::{Composite, FrameInfo, Renderer},
+    style::{
+        CornerStyle, ElementStyle, Fill, GradientKind, GradientSpace, MAX_GRADIENT_STOPS, Shadow,
+        TextStyle,
+    },
+    text_effect::GlyphTransform,
+    text_system::{ShapedGlyph, ShapedText, TextSystem},
 };
 use glam::Vec2;
 use metal::{
-    CommandBufferRef, CommandQueue, Device, Library, MTLLoadAction, MTLPrimitiveType,
-    MTLScissorRect, MTLStoreAction, RenderPassDescriptor, RenderPipelineDescriptor,
-    RenderPipelineState, VertexDescriptor,
+    CommandBufferRef, CommandQueue, ComputePipelineState, Device, Function, Library, MTLLoadAction,
+    MTLOrigin, MTLPixelFormat, MTLPrimitiveType, MTLRegion, MTLScissorRect, MTLSize,
+    MTLStorageMode, MTLStoreAction, MTLTextureUsage, MTLViewport, RenderPassDescriptor,
+    RenderPipelineDescriptor, RenderPipelineState, SamplerState, Texture, TextureDescriptor,
+    TextureRef, VertexDescriptor,
 };
+use std::collections::HashMap;
 use std::mem;
+use std::rc::Rc;
+use std::sync::Arc;
 use std::time::Instant;
-use tracing::{debug, info, info_span};
+use tracing::{debug, error, info, info_span};
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
@@ -28,52 +49,529 @@ pub struct Vertex {
 struct FrameUniforms {
     center: [f32; 2],
     half_size: [f32; 2],
-    radii: [f32; 4], // top_left, top_right, bottom_right, bottom_left
-    border_width: f32,
-    fill_type: u32,      // 0 = solid, 1 = linear gradient, 2 = radial gradient
-    gradient_angle: f32, // For linear gradient
-    _padding: f32,       // Padding to align to 16 bytes
-    color1: [f32; 4],    // Solid color or gradient start/center
-    color2: [f32; 4],    // Gradient end/edge (unused for solid)
-    border_color: [f32; 4],
+    radii: [f32; 4],         // top_left, top_right, bottom_right, bottom_left
+    border_widths: [f32; 4], // top, right, bottom, left
+    fill: GradientUniforms,
+    border_colors: [[f32; 4]; 4], // top, right, bottom, left
     shadow_offset: [f32; 2],
     shadow_blur: f32,
-    _padding2: f32,
+    corner_style: u32, // 0 = circular, 1 = continuous
     shadow_color: [f32; 4],
+    antialias: f32, // 1 = smooth edges over a pixel, 0 = hard edges
+    _padding: [f32; 3],
+}
+
+/// A [`Fill`] as the shaders evaluate it
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct GradientUniforms {
+    kind: u32,  // 0 = solid, 1 = linear, 2 = radial, 3 = conic gradient
+    space: u32, // 0 = sRGB, 1 = OKLCH
+    stop_count: u32,
+    angle: f32,         // For linear and conic gradients
+    center: [f32; 2],   // For conic gradient, as a fraction of the bounds
+    _padding: [f32; 2], // Padding to align to 16 bytes
+    positions: [f32; MAX_GRADIENT_STOPS],
+    /// sRGBA, or lightness, chroma, hue in radians and alpha for OKLCH
+    colors: [[f32; 4]; MAX_GRADIENT_STOPS],
+}
+
+impl GradientUniforms {
+    fn new(fill: &Fill) -> Self {
+        let mut uniforms = Self {
+            kind: 0,
+            space: 0,
+            stop_count: 1,
+            angle: 0.0,
+            center: [0.5, 0.5],
+            _padding: [0.0; 2],
+            positions: [0.0; MAX_GRADIENT_STOPS],
+            colors: [[0.0; 4]; MAX_GRADIENT_STOPS],
+        };
+        let gradient = match fill {
+            Fill::Solid(color) => {
+                uniforms.colors[0] = [color.red, color.green, color.blue, color.alpha];
+                return uniforms;
+            }
+            Fill::Gradient(gradient) => gradient,
+        };
+        uniforms.kind = match gradient.kind {
+            GradientKind::Linear { angle } => {
+                uniforms.angle = angle;
+                1
+            }
+            GradientKind::Radial => 2,
+            GradientKind::Conic { center, angle } => {
+                uniforms.angle = angle;
+                uniforms.center = [center.x, center.y];
+                3
+            }
+        };
+        uniforms.space = match gradient.space {
+            GradientSpace::Srgb => 0,
+            GradientSpace::Oklch => 1,
+        };
+        let stops = gradient.sorted_stops();
+        uniforms.stop_count = stops.len() as u32;
+        for (i, stop) in stops.iter().enumerate() {
+            let color = stop.color;
+            uniforms.positions[i] = stop.position;
+            uniforms.colors[i] = match gradient.space {
+                GradientSpace::Srgb => [color.red, color.green, color.blue, color.alpha],
+                GradientSpace::Oklch => {
+                    let (l, c, h) = color.to_oklch();
+                    [l, c, h.to_radians(), color.alpha]
+                }
+            };
+        }
+        uniforms
+    }
+}
+
+/// Gradient for text filled with a [`Fill`], across the text's bounds
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct TextGradientUniforms {
+    /// Center of the text's bounds, in physical pixels
+    center: [f32; 2],
+    /// Half size of the text's bounds, in physical pixels
+    half_size: [f32; 2],
+    fill: GradientUniforms,
+    /// Whether the glyphs are drawn from distance fields
+    distance_field: u32,
+    _padding: [u32; 3],
+}
+
+impl TextGradientUniforms {
+    /// Uniforms for `fill` across `bounds` in points
+    fn new(fill: &Fill, bounds: Rect, scale_factor: f32, distance_field: bool) -> Self {
+        let center = (bounds.pos + bounds.size / 2.0) * scale_factor;
+        let half_size = bounds.size * (scale_factor / 2.0);
+        Self {
+            center: [center.x, center.y],
+            half_size: [half_size.x, half_size.y],
+            fill: GradientUniforms::new(fill),
+            distance_field: u32::from(distance_field),
+            _padding: [0; 3],
+        }
+    }
+}
+
+/// Values applied to every draw in a layer's render pass
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct LayerUniforms {
+    opacity: f32,
+    /// Whether fragments are clipped to the rounded rect below
+    has_rounded_clip: u32,
+    _padding: [f32; 2],
+    /// Center and half size of the rounded clip, in physical pixels
+    clip_bounds: [f32; 4],
+    /// Rounded clip radii: top_left, top_right, bottom_right, bottom_left
+    clip_radii: [f32; 4],
+}
+
+impl LayerUniforms {
+    /// Uniforms for a pass at `opacity`, inside a rounded clip in points
+    fn new(opacity: f32, rounded_clip: Option<(Rect, Corners)>, scale_factor: f32) -> Self {
+        let mut uniforms = Self {
+            opacity: opacity.clamp(0.0, 1.0),
+            has_rounded_clip: 0,
+            _padding: [0.0; 2],
+            clip_bounds: [0.0; 4],
+            clip_radii: [0.0; 4],
+        };
+        if let Some((rect, radii)) = rounded_clip {
+            let half_size = rect.size * scale_factor / 2.0;
+            let center = rect.pos * scale_factor + half_size;
+            uniforms.has_rounded_clip = 1;
+            uniforms.clip_bounds = [center.x, center.y, half_size.x, half_size.y];
+            uniforms.clip_radii = [
+                radii.top_left * scale_factor,
+                radii.top_right * scale_factor,
+                radii.bottom_right * scale_factor,
+                radii.bottom_left * scale_factor,
+            ];
+        }
+        uniforms
+    }
+}
+
+/// Shape of a shadow mask, in physical pixels
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct ShadowMaskUniforms {
+    half_size: [f32; 2],
+    padding: [f32; 2],
+    radii: [f32; 4],
+    corner_style: u32, // 0 = circular, 1 = continuous
+    _padding: [u32; 3],
+}
+
+/// One pass of the separable Gaussian blur
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct BlurParams {
+    direction: [i32; 2],
+    sigma: f32,
+    radius: i32,
+}
+
+/// Matches `PostProcessUniforms` in [`POST_PROCESS_PRELUDE`]
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct PostProcessUniforms {
+    resolution: [f32; 2],
+    time: f32,
+    scale_factor: f32,
+    params: [f32; 4],
+}
+
+/// A compiled post-process pass
+#[derive(Clone)]
+enum PostProcessPipeline {
+    Fragment(RenderPipelineState),
+    Compute(ComputePipelineState),
+}
+
+/// What a pass in a post-processed layer's frame graph does
+enum PostProcessStep {
+    /// Draw the layer's draw list
+    Content,
+    /// Run a post-process pass with its parameters
+    Pass(PostProcessPipeline, [f32; 4]),
+    /// Composite the result onto the drawable
+    Composite,
+}
+
+/// Declarations every post-process pass is compiled with, and the
+/// fullscreen triangle fragment passes are drawn with
+const POST_PROCESS_PRELUDE: &str = r#"
+    #include <metal_stdlib>
+    using namespace metal;
+
+    struct PostProcessUniforms {
+        float2 resolution;
+        float time;
+        float scale_factor;
+        float4 params;
+    };
+
+    struct PostProcessVertex {
+        float4 position [[position]];
+        float2 uv;
+    };
+
+    vertex PostProcessVertex post_process_vertex(uint vid [[vertex_id]]) {
+        float2 positions[3] = {
+            float2(-1.0, -1.0),
+            float2( 3.0, -1.0),
+            float2(-1.0,  3.0)
+        };
+
+        PostProcessVertex out;
+        out.position = float4(positions[vid], 0.0, 1.0);
+        out.uv = (positions[vid] + 1.0) * 0.5;
+        out.uv.y = 1.0 - out.uv.y;
+        return out;
+    }
+"#;
+
+/// Calls a fragment pass's `post_process` function for every pixel
+const POST_PROCESS_FRAGMENT: &str = r#"
+    fragment float4 post_process_fragment(PostProcessVertex in [[stage_in]],
+                                          texture2d<float> source [[texture(0)]],
+                                          constant PostProcessUniforms &uniforms [[buffer(0)]]) {
+        constexpr sampler source_sampler(filter::linear, address::clamp_to_edge);
+        return post_process(source, source_sampler, in.uv, uniforms);
+    }
+"#;
+
+/// Draws a post-processed layer or external texture onto the drawable at
+/// the layer's opacity
+const COMPOSITE_SHADER: &str = r#"
+    fragment float4 composite_fragment(PostProcessVertex in [[stage_in]],
+                                       texture2d<float> source [[texture(0)]],
+                                       constant float &opacity [[buffer(0)]]) {
+        constexpr sampler source_sampler(filter::linear, address::clamp_to_edge);
+        return source.sample(source_sampler, in.uv) * opacity;
+    }
+"#;
+
+/// Draws a blurred region back onto its target, masked to rounded corners
+/// given as a layer's rounded clip
+const BLUR_COMPOSITE_SHADER: &str = r#"
+    struct LayerUniforms {
+        float opacity;
+        uint has_rounded_clip;
+        float2 _padding;
+        float4 clip_bounds;
+        float4 clip_radii;
+    };
+
+    float sdRoundedRect(float2 p, float2 half_size, float4 radii) {
+        float radius = p.x > 0.0 ?
+            (p.y > 0.0 ? radii.z : radii.y) :
+            (p.y > 0.0 ? radii.w : radii.x);
+
+        float2 q = abs(p) - half_size + radius;
+        return min(max(q.x, q.y), 0.0) + length(max(q, 0.0)) - radius;
+    }
+
+    fragment float4 blur_composite_fragment(PostProcessVertex in [[stage_in]],
+                                            texture2d<float> source [[texture(0)]],
+                                            constant LayerUniforms &layer [[buffer(0)]]) {
+        constexpr sampler source_sampler(filter::linear, address::clamp_to_edge);
+        float4 color = source.sample(source_sampler, in.uv) * layer.opacity;
+        if (layer.has_rounded_clip != 0) {
+            float2 p = in.position.xy - layer.clip_bounds.xy;
+            float d = sdRoundedRect(p, layer.clip_bounds.zw, layer.clip_radii);
+            color *= 1.0 - smoothstep(-0.5, 0.5, d);
+        }
+        return color;
+    }
+"#;
+
+/// Largest standard deviation, in pixels, of element and backdrop blurs;
+/// larger blurs are clamped to keep the kernel affordable
+const MAX_BLUR_SIGMA: f32 = 64.0;
+
+/// Default blur radius, in points, above which shadows are drawn from a
+/// blurred mask instead of the SDF approximation
+pub(crate) const DEFAULT_BLURRED_SHADOW_THRESHOLD: f32 = 16.0;
+
+/// Frames a cached shadow mask is kept without being drawn
+const SHADOW_CACHE_MAX_AGE: u64 = 120;
+
+/// Largest shadow mask side, in pixels; bigger shadows fall back to the SDF
+const MAX_SHADOW_MASK_SIZE: u32 = 4096;
+
+/// Identifies a blurred shadow mask: the shape and blur in physical pixels
+///
+/// Color and offset aren't part of the key, so frames of the same size
+/// share a mask however their shadows are tinted or placed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct ShadowKey {
+    width: u32,
+    height: u32,
+    radii: [u32; 4],
+    corner_style: u32,
+    blur: u32,
+}
+
+impl ShadowKey {
+    /// The key for a frame's shadow, if its blur is above `threshold`
+    fn for_frame(
+        rect: &Rect,
+        style: &ElementStyle,
+        scale_factor: f32,
+        threshold: Option<f32>,
+    ) -> Option<Self> {
+        let threshold = threshold?;
+        let shadow = style.shadow.as_ref()?;
+        if shadow.color.alpha <= 0.0 || shadow.blur <= threshold {
+            return None;
+        }
+        let px = |value: f32| (value * scale_factor).round().max(0.0) as u32;
+        let radii = &style.corner_radii;
+        let key = Self {
+            width: px(rect.size.x),
+            height: px(rect.size.y),
+            radii: [
+                px(radii.top_left),
+                px(radii.top_right),
+                px(radii.bottom_right),
+                px(radii.bottom_left),
+            ],
+            corner_style: corner_style_index(style.corner_style),
+            blur: px(shadow.blur),
+        };
+        let (width, height) = key.mask_size();
+        (key.width > 0 && key.height > 0 && width.max(height) <= MAX_SHADOW_MASK_SIZE)
+            .then_some(key)
+    }
+
+    /// Size of the mask texture: the shape padded by the blur on each side
+    fn mask_size(&self) -> (u32, u32) {
+        (self.width + self.blur * 2, self.height + self.blur * 2)
+    }
+
+    /// GPU memory used by the mask, one byte per pixel
+    fn mask_bytes(&self) -> u64 {
+        let (width, height) = self.mask_size();
+        width as u64 * height as u64
+    }
+}
+
+/// A blurred shadow mask and the last frame it was drawn in
+struct CachedShadow {
+    texture: Texture,
+    last_used: u64,
+}
+
+/// Frames an image texture is kept without being drawn
+const IMAGE_CACHE_MAX_AGE: u64 = 120;
+
+/// An uploaded image and the last frame it was drawn in
+///
+/// Images are keyed by address, so the image is held to keep that address
+/// from being reused by another image while its texture is cached.
+struct CachedImage {
+    image: Arc<RgbaImage>,
+    texture: Texture,
+    last_used: u64,
+}
+
+impl CachedImage {
+    /// GPU memory used by the texture, four bytes per pixel
+    fn bytes(&self) -> u64 {
+        self.image.width() as u64 * self.image.height() as u64 * 4
+    }
 }
 
 pub struct MetalRenderer {
     device: Device,
     pipeline_state: Option<RenderPipelineState>,
     text_pipeline_state: Option<RenderPipelineState>,
+    /// Text pipeline for glyphs drawn from distance fields
+    text_distance_field_pipeline_state: Option<RenderPipelineState>,
+    /// Text pipeline that fills glyphs with a gradient
+    text_gradient_pipeline_state: Option<RenderPipelineState>,
     frame_pipeline_state: Option<RenderPipelineState>,
+    /// Solid pipeline that overwrites instead of blending, for cutouts
+    cutout_pipeline_state: Option<RenderPipelineState>,
+    /// Renders a shape's coverage into a shadow mask
+    shadow_mask_pipeline_state: Option<RenderPipelineState>,
+    /// One pass of the separable Gaussian blur for shadow masks
+    blur_pipeline_state: Option<ComputePipelineState>,
+    /// The same blur over color, for element and backdrop blurs
+    color_blur_pipeline_state: Option<ComputePipelineState>,
+    /// Draws RGBA images
+    image_pipeline_state: Option<RenderPipelineState>,
+    /// Bilinear sampler shared by text, image and shadow mask draws
+    linear_sampler: SamplerState,
+    /// Blurred shadow masks by shape, reused while they keep being drawn
+    shadow_cache: HashMap<ShadowKey, CachedShadow>,
+    /// Shadows blurred more than this are drawn from a blurred mask
+    blurred_shadow_threshold: Option<f32>,
+    /// Whether frame shadows are drawn at all
+    shadows_enabled: bool,
+    /// Whether frame edges are antialiased
+    antialiasing: bool,
+    /// Whether a backdrop blur this frame read from its target
+    reads_target: bool,
+    /// Image textures by image address, reused while they keep being drawn
+    image_cache: HashMap<usize, CachedImage>,
+    /// Small images packed into one texture, created on first use
+    image_atlas: Option<ImageAtlas>,
+    /// Frames started since the renderer was created
+    frame_count: u64,
+    /// Queue for offscreen captures, created on first use
+    capture_queue: Option<CommandQueue>,
+    /// Post-process passes by key, or `None` for ones that failed to compile
+    post_process_pipelines: HashMap<String, Option<PostProcessPipeline>>,
+    /// Draws post-processed layers onto the drawable, created on first use
+    composite_pipeline_state: Option<RenderPipelineState>,
+    /// Drawable-sized textures for the frame graph's transient textures
+    post_process_targets: Vec<Texture>,
+    /// Frame graph of the last post-processed layer, for debugging
+    last_frame_graph: Option<CompiledFrameGraph<PostProcessStep>>,
+    /// Draws blurred regions back onto their target, created on first use
+    blur_composite_pipeline_state: Option<RenderPipelineState>,
+    /// Pairs of textures blurs run between, by size in pixels
+    blur_targets: HashMap<(u64, u64), Vec<[Texture; 2]>>,
+    /// Pairs of each size handed out this frame, reset by `begin_frame`
+    blur_targets_used: HashMap<(u64, u64), usize>,
+    /// Per-frame allocation counts, reset by `begin_frame`
+    frame_stats: RendererStats,
 }
 
 impl MetalRenderer {
     pub fn new(device: Device) -> Self {
+        let sampler_descriptor = metal::SamplerDescriptor::new();
+        sampler_descriptor.set_min_filter(metal::MTLSamplerMinMagFilter::Linear);
+        sampler_descriptor.set_mag_filter(metal::MTLSamplerMinMagFilter::Linear);
+        let linear_sampler = device.new_sampler(&sampler_descriptor);
         Self {
             device,
             pipeline_state: None,
             text_pipeline_state: None,
+            text_distance_field_pipeline_state: None,
+            text_gradient_pipeline_state: None,
             frame_pipeline_state: None,
+            cutout_pipeline_state: None,
+            shadow_mask_pipeline_state: None,
+            blur_pipeline_state: None,
+            color_blur_pipeline_state: None,
+            image_pipeline_state: None,
+            linear_sampler,
+            shadow_cache: HashMap::new(),
+            blurred_shadow_threshold: Some(DEFAULT_BLURRED_SHADOW_THRESHOLD),
+            shadows_enabled: true,
+            antialiasing: true,
+            reads_target: false,
+            image_cache: HashMap::new(),
+            image_atlas: None,
+            frame_count: 0,
+            capture_queue: None,
+            post_process_pipelines: HashMap::new(),
+            composite_pipeline_state: None,
+            post_process_targets: Vec::new(),
+            last_frame_graph: None,
+            blur_composite_pipeline_state: None,
+            blur_targets: HashMap::new(),
+            blur_targets_used: HashMap::new(),
+            frame_stats: RendererStats::default(),
         }
     }
 
-    pub fn initialize(&mut self) -> Result<(), String> {
-        // Create shader library
-        let start = Instant::now();
-        let library = self.compile_shaders()?;
-        info!("Shaders compiled in {:?}", start.elapsed());
+    /// Set the blur radius, in points, above which shadows are Gaussian
+    /// blurred on the GPU
+    ///
+    /// The SDF approximation used for smaller shadows gets inaccurate and
+    /// costly per pixel as the blur grows. Blurred shadows are rendered once
+    /// per frame size and cached, so they're cheap while sizes stay the same.
+    /// `None` draws every shadow with the SDF approximation.
+    pub fn set_blurred_shadow_threshold(&mut self, threshold: Option<f32>) {
+        self.blurred_shadow_threshold = threshold;
+        self.shadow_cache.clear();
+    }
 
-        // Create pipeline states
-        self.pipeline_state = Some(self.create_pipeline_state(&library)?);
-        self.text_pipeline_state = Some(self.create_text_pipeline_state(&library)?);
-        self.frame_pipeline_state = Some(self.create_frame_pipeline_state(&library)?);
+    /// Draw or skip frame shadows
+    ///
+    /// Turned off by the quality governor when frames run over budget.
+    pub fn set_shadows_enabled(&mut self, enabled: bool) {
+        self.shadows_enabled = enabled;
+        if !enabled {
+            self.shadow_cache.clear();
+        }
+    }
 
-        Ok(())
+    /// Smooth frame edges over a pixel, or draw them hard
+    ///
+    /// Turned off by the quality governor when frames run over budget.
+    pub fn set_antialiasing(&mut self, enabled: bool) {
+        self.antialiasing = enabled;
+    }
+
+    /// Whether this frame drew a backdrop blur, which copies from the
+    /// drawable and so needs it to be more than framebuffer-only
+    ///
+    /// Blurs into a framebuffer-only drawable are skipped, so the drawable
+    /// can stay framebuffer-only, and fast, until one is needed.
+    pub fn needs_readable_drawable(&self) -> bool {
+        self.reads_target
+    }
+
+    /// The frame graph of the last post-processed layer in Graphviz DOT
+    /// format, for seeing how its passes were ordered and textures shared
+    pub fn last_frame_graph(&self) -> Option<String> {
+        self.last_frame_graph
+            .as_ref()
+            .map(CompiledFrameGraph::to_dot)
     }
 
-    fn compile_shaders(&self) -> Result<Library, String> {
+    fn compile_shaders(&self) -> SolResult<Library> {
         let shader_source = r#"
             #include <metal_stdlib>
             using namespace metal;
@@ -98,8 +596,61 @@ impl MetalRenderer {
                 return out;
             }
 
-            fragment float4 fragment_main(VertexOut in [[stage_in]]) {
-                return in.color;
+            // Per-layer compositing values, shared by every fragment shader
+            struct LayerUniforms {
+                float opacity;
+                uint has_rounded_clip;
+                float2 _padding;
+                float4 clip_bounds; // center, half size in pixels
+                float4 clip_radii;
+            };
+
+            float sdRoundedRect(float2 p, float2 half_size, float4 radii) {
+                // Select the appropriate radius based on quadrant
+                float radius = p.x > 0.0 ?
+                    (p.y > 0.0 ? radii.z : radii.y) :
+                    (p.y > 0.0 ? radii.w : radii.x);
+
+                float2 q = abs(p) - half_size + radius;
+                return min(max(q.x, q.y), 0.0) + length(max(q, 0.0)) - radius;
+            }
+
+            // Continuous corners, like Apple's: the curve starts further from
+            // the corner and eases into the edges, approximated by a
+            // superellipse of degree 5
+            float sdContinuousRect(float2 p, float2 half_size, float4 radii) {
+                float radius = p.x > 0.0 ?
+                    (p.y > 0.0 ? radii.z : radii.y) :
+                    (p.y > 0.0 ? radii.w : radii.x);
+                float extent = min(radius * 1.528, min(half_size.x, half_size.y));
+
+                float2 q = abs(p) - half_size + extent;
+                float2 m = max(q, 0.0) / max(extent, 0.0001);
+                float corner = pow(pow(m.x, 5.0) + pow(m.y, 5.0), 0.2) * extent;
+                return min(max(q.x, q.y), 0.0) + corner - extent;
+            }
+
+            // Distance to a frame's edge, for corner_style 0 = circular,
+            // 1 = continuous
+            float sdFrame(float2 p, float2 half_size, float4 radii, uint corner_style) {
+                return corner_style == 1 ?
+                    sdContinuousRect(p, half_size, radii) :
+                    sdRoundedRect(p, half_size, radii);
+            }
+
+            // Layer opacity times the coverage of the rounded clip, if any
+            float layer_alpha(float4 position, constant LayerUniforms& layer) {
+                if (layer.has_rounded_clip == 0) {
+                    return layer.opacity;
+                }
+                float2 p = position.xy - layer.clip_bounds.xy;
+                float d = sdRoundedRect(p, layer.clip_bounds.zw, layer.clip_radii);
+                return layer.opacity * (1.0 - smoothstep(-0.5, 0.5, d));
+            }
+
+            fragment float4 fragment_main(VertexOut in [[stage_in]],
+                                          constant LayerUniforms& layer [[buffer(1)]]) {
+                return float4(in.color.rgb, in.color.a * layer_alpha(in.position, layer));
             }
 
             // Text rendering shaders
@@ -113,9 +664,137 @@ impl MetalRenderer {
 
             fragment float4 text_fragment_main(VertexOut in [[stage_in]],
                                                texture2d<float> glyph_texture [[texture(0)]],
-                                               sampler glyph_sampler [[sampler(0)]]) {
+                                               sampler glyph_sampler [[sampler(0)]],
+                                               constant LayerUniforms& layer [[buffer(1)]]) {
                 float alpha = glyph_texture.sample(glyph_sampler, in.tex_coord).r;
-                return float4(in.color.rgb, in.color.a * alpha);
+                return float4(in.color.rgb, in.color.a * alpha * layer_alpha(in.position, layer));
+            }
+
+            // Coverage from a glyph distance field, antialiased over about a
+            // pixel at any scale
+            float distance_field_alpha(float distance) {
+                float width = max(fwidth(distance) * 0.5, 0.0001);
+                return smoothstep(0.5 - width, 0.5 + width, distance);
+            }
+
+            fragment float4 text_distance_field_fragment_main(VertexOut in [[stage_in]],
+                                                              texture2d<float> glyph_texture [[texture(0)]],
+                                                              sampler glyph_sampler [[sampler(0)]],
+                                                              constant LayerUniforms& layer [[buffer(1)]]) {
+                float alpha = distance_field_alpha(glyph_texture.sample(glyph_sampler, in.tex_coord).r);
+                return float4(in.color.rgb, in.color.a * alpha * layer_alpha(in.position, layer));
+            }
+
+            // Fills shared by frames and gradient text, with up to 8 stops
+            struct GradientUniforms {
+                uint kind; // 0 = solid, 1 = linear, 2 = radial, 3 = conic gradient
+                uint space; // 0 = sRGB, 1 = OKLCH
+                uint stop_count;
+                float angle;
+                float2 center; // conic center, as a fraction of the bounds
+                float2 _padding;
+                float4 positions[2]; // 8 stop positions, packed
+                float4 colors[8]; // sRGBA, or OKLCH with hue in radians and alpha
+            };
+
+            float3 oklch_to_srgb(float3 lch) {
+                float a = lch.y * cos(lch.z);
+                float b = lch.y * sin(lch.z);
+                float l_ = lch.x + 0.3963377774 * a + 0.2158037573 * b;
+                float m_ = lch.x - 0.1055613458 * a - 0.0638541728 * b;
+                float s_ = lch.x - 0.0894841775 * a - 1.2914855480 * b;
+                float3 lms = float3(l_ * l_ * l_, m_ * m_ * m_, s_ * s_ * s_);
+                float3 linear_rgb = saturate(float3(
+                    4.0767416621 * lms.x - 3.3077115913 * lms.y + 0.2309699292 * lms.z,
+                    -1.2684380046 * lms.x + 2.6097574011 * lms.y - 0.3413193965 * lms.z,
+                    -0.0041960863 * lms.x - 0.7034186147 * lms.y + 1.7076147010 * lms.z));
+                return select(1.055 * pow(linear_rgb, 1.0 / 2.4) - 0.055,
+                              linear_rgb * 12.92,
+                              linear_rgb <= 0.0031308);
+            }
+
+            // Blend two stops in the gradient's color space, returning sRGBA
+            float4 blend_stops(uint space, float4 from, float4 to, float t) {
+                if (space == 0) {
+                    return mix(from, to, t);
+                }
+                // Grays take the other stop's hue so the blend doesn't sweep
+                // through unrelated hues
+                float h0 = from.y < 0.0001 ? to.z : from.z;
+                float h1 = to.y < 0.0001 ? from.z : to.z;
+                float dh = h1 - h0;
+                dh -= 6.2831853 * round(dh / 6.2831853);
+                float3 lch = float3(mix(from.x, to.x, t), mix(from.y, to.y, t), h0 + dh * t);
+                return float4(oklch_to_srgb(lch), mix(from.w, to.w, t));
+            }
+
+            float4 gradient_color(constant GradientUniforms& g, float t) {
+                if (g.stop_count == 0) {
+                    return float4(0.0);
+                }
+                if (g.kind == 0 || t <= g.positions[0][0]) {
+                    return blend_stops(g.space, g.colors[0], g.colors[0], 0.0);
+                }
+                for (uint i = 1; i < g.stop_count; i++) {
+                    float from = g.positions[(i - 1) / 4][(i - 1) % 4];
+                    float to = g.positions[i / 4][i % 4];
+                    if (t <= to) {
+                        return blend_stops(g.space, g.colors[i - 1], g.colors[i], (t - from) / max(to - from, 0.00001));
+                    }
+                }
+                uint last = g.stop_count - 1;
+                return blend_stops(g.space, g.colors[last], g.colors[last], 0.0);
+            }
+
+            // How far along the gradient `p`, relative to the center of the
+            // bounds, falls
+            float gradient_position(constant GradientUniforms& g, float2 p, float2 half_size) {
+                if (g.kind == 1) {
+                    float2 gradient_dir = float2(cos(g.angle), sin(g.angle));
+                    float t = dot(p, gradient_dir) / dot(half_size * 2.0, abs(gradient_dir));
+                    return (t + 1.0) * 0.5;
+                }
+                if (g.kind == 2) {
+                    return length(p) / max(length(half_size), 0.0001);
+                }
+                if (g.kind == 3) {
+                    // Clockwise on screen, since y goes down
+                    float2 q = p - (g.center - 0.5) * half_size * 2.0;
+                    return fract((atan2(q.y, q.x) - g.angle) / 6.2831853);
+                }
+                return 0.0;
+            }
+
+            // Text filled with a gradient across its bounds, sampled by
+            // fragment position so it runs continuously across glyphs
+            struct TextGradientUniforms {
+                float2 center; // in pixels
+                float2 half_size;
+                GradientUniforms fill;
+                uint distance_field; // whether the glyphs are distance fields
+            };
+
+            fragment float4 text_gradient_fragment_main(VertexOut in [[stage_in]],
+                                                        texture2d<float> glyph_texture [[texture(0)]],
+                                                        sampler glyph_sampler [[sampler(0)]],
+                                                        constant TextGradientUniforms& gradient [[buffer(0)]],
+                                                        constant LayerUniforms& layer [[buffer(1)]]) {
+                float alpha = glyph_texture.sample(glyph_sampler, in.tex_coord).r;
+                if (gradient.distance_field != 0) {
+                    alpha = distance_field_alpha(alpha);
+                }
+                float2 p = in.position.xy - gradient.center;
+                float4 color = gradient_color(gradient.fill, gradient_position(gradient.fill, p, gradient.half_size));
+                return float4(color.rgb, color.a * in.color.a * alpha * layer_alpha(in.position, layer));
+            }
+
+            // Image rendering, sharing the text vertex shader
+            fragment float4 image_fragment_main(VertexOut in [[stage_in]],
+                                                texture2d<float> image_texture [[texture(0)]],
+                                                sampler image_sampler [[sampler(0)]],
+                                                constant LayerUniforms& layer [[buffer(1)]]) {
+                float4 color = image_texture.sample(image_sampler, in.tex_coord);
+                return float4(color.rgb, color.a * in.color.a * layer_alpha(in.position, layer));
             }
 
             // SDF Frame rendering shaders
@@ -123,29 +802,17 @@ impl MetalRenderer {
                 float2 center;
                 float2 half_size;
                 float4 radii; // top_left, top_right, bottom_right, bottom_left
-                float border_width;
-                uint fill_type; // 0 = solid, 1 = linear gradient, 2 = radial gradient
-                float gradient_angle;
-                float _padding;
-                float4 color1; // Solid color or gradient start/center
-                float4 color2; // Gradient end/edge
-                float4 border_color;
+                float4 border_widths; // top, right, bottom, left
+                GradientUniforms fill;
+                float4 border_colors[4]; // top, right, bottom, left
                 float2 shadow_offset;
                 float shadow_blur;
-                float _padding2;
+                uint corner_style; // 0 = circular, 1 = continuous
                 float4 shadow_color;
+                float antialias; // 1 = smooth edges over a pixel, 0 = hard edges
+                float _padding[3];
             };
 
-            float sdRoundedRect(float2 p, float2 half_size, float4 radii) {
-                // Select the appropriate radius based on quadrant
-                float radius = p.x > 0.0 ?
-                    (p.y > 0.0 ? radii.z : radii.y) :
-                    (p.y > 0.0 ? radii.w : radii.x);
-
-                float2 q = abs(p) - half_size + radius;
-                return min(max(q.x, q.y), 0.0) + length(max(q, 0.0)) - radius;
-            }
-
             vertex VertexOut frame_vertex_main(Vertex in [[stage_in]]) {
                 VertexOut out;
                 out.position = float4(in.position, 0.0, 1.0);
@@ -155,7 +822,8 @@ impl MetalRenderer {
             }
 
             fragment float4 frame_fragment_main(VertexOut in [[stage_in]],
-                                              constant FrameUniforms& uniforms [[buffer(0)]]) {
+                                              constant FrameUniforms& uniforms [[buffer(0)]],
+                                              constant LayerUniforms& layer [[buffer(1)]]) {
                 // Convert from texture coordinates to local space coordinates
                 // tex_coord can be outside 0-1 range due to shadow expansion
                 // Map (0,0)-(1,1) to (-half_size, +half_size) in frame space
@@ -166,7 +834,7 @@ impl MetalRenderer {
                 float shadow_alpha = 0.0;
                 if (uniforms.shadow_color.a > 0.0) {
                     float2 shadow_p = p - uniforms.shadow_offset;
-                    float shadow_d = sdRoundedRect(shadow_p, uniforms.half_size, uniforms.radii);
+                    float shadow_d = sdFrame(shadow_p, uniforms.half_size, uniforms.radii, uniforms.corner_style);
 
                     // Handle both hard and soft shadows
                     if (uniforms.shadow_blur > 0.0) {
@@ -178,33 +846,46 @@ impl MetalRenderer {
                     }
                 }
 
-                float d = sdRoundedRect(p, uniforms.half_size, uniforms.radii);
+                float d = sdFrame(p, uniforms.half_size, uniforms.radii, uniforms.corner_style);
 
                 // Anti-aliasing
-                float aa = fwidth(d) * 0.5;
+                float aa = max(fwidth(d) * 0.5 * uniforms.antialias, 0.0001);
 
                 // Fill mask
                 float fill_mask = 1.0 - smoothstep(-aa, aa, d);
 
-                // Calculate fill color based on fill type
-                float4 fill_color = uniforms.color1;
-                if (uniforms.fill_type == 1) { // Linear gradient
-                    // Calculate gradient coordinate
-                    float2 gradient_dir = float2(cos(uniforms.gradient_angle), sin(uniforms.gradient_angle));
-                    float t = dot(p, gradient_dir) / dot(uniforms.half_size * 2.0, abs(gradient_dir));
-                    t = (t + 1.0) * 0.5; // Normalize to 0-1
-                    fill_color = mix(uniforms.color1, uniforms.color2, t);
-                } else if (uniforms.fill_type == 2) { // Radial gradient
-                    float t = length(p) / length(uniforms.half_size);
-                    fill_color = mix(uniforms.color1, uniforms.color2, smoothstep(0.0, 1.0, t));
-                }
+                float4 fill_color = gradient_color(uniforms.fill, gradient_position(uniforms.fill, p, uniforms.half_size));
 
-                // Border mask (only if border width > 0)
+                // Border mask (only if any border width > 0)
                 float4 color = fill_color;
-                if (uniforms.border_width > 0.0) {
-                    float border_inner = d + uniforms.border_width;
-                    float border_mask = smoothstep(-aa, aa, border_inner) * fill_mask;
-                    color = mix(fill_color, uniforms.border_color, border_mask);
+                float4 widths = uniforms.border_widths;
+                if (any(widths > 0.0)) {
+                    // The inside of the border is the frame inset by each
+                    // edge's width, with the radii shrunk to match
+                    float2 inner_offset = float2(widths.w - widths.y, widths.x - widths.z) * 0.5;
+                    float2 inner_half_size = max(uniforms.half_size - float2(widths.w + widths.y, widths.x + widths.z) * 0.5, 0.0);
+                    float4 inner_radii = max(uniforms.radii - float4(max(widths.w, widths.x),
+                                                                     max(widths.y, widths.x),
+                                                                     max(widths.y, widths.z),
+                                                                     max(widths.w, widths.z)), 0.0);
+                    float inner_d = sdFrame(p - inner_offset, inner_half_size, inner_radii, uniforms.corner_style);
+                    float border_mask = smoothstep(-aa, aa, inner_d) * fill_mask;
+
+                    // Each point takes the color of the edge it's deepest
+                    // into relative to that edge's width, so corners split
+                    // along the diagonal between the outer and inner corner
+                    float2 h = uniforms.half_size;
+                    float t_top = (p.y + h.y) / max(widths.x, 0.0001);
+                    float t_right = (h.x - p.x) / max(widths.y, 0.0001);
+                    float t_bottom = (h.y - p.y) / max(widths.z, 0.0001);
+                    float t_left = (p.x + h.x) / max(widths.w, 0.0001);
+                    float4 vertical_color = t_top < t_bottom ? uniforms.border_colors[0] : uniforms.border_colors[2];
+                    float4 horizontal_color = t_left < t_right ? uniforms.border_colors[3] : uniforms.border_colors[1];
+                    float split = min(t_left, t_right) - min(t_top, t_bottom);
+                    float split_aa = max(fwidth(split), 0.0001);
+                    float4 border_color = mix(horizontal_color, vertical_color, smoothstep(-split_aa, split_aa, split));
+
+                    color = mix(fill_color, border_color, border_mask);
                 }
 
                 // Apply fill mask to color
@@ -215,24 +896,94 @@ impl MetalRenderer {
                 float3 final_rgb = uniforms.shadow_color.rgb * shadow_alpha * (1.0 - color.a) + color.rgb * color.a;
                 float final_alpha = shadow_alpha * (1.0 - color.a) + color.a;
 
-                return float4(final_rgb, final_alpha);
+                return float4(final_rgb, final_alpha * layer_alpha(in.position, layer));
+            }
+
+            // Blurred shadows: a shape's coverage is rendered into a mask once
+            // per size, blurred with two separable Gaussian passes, and drawn
+            // tinted with the text pipeline
+            struct ShadowMaskUniforms {
+                float2 half_size;
+                float2 padding;
+                float4 radii;
+                uint corner_style; // 0 = circular, 1 = continuous
+                uint _padding[3];
+            };
+
+            fragment float4 shadow_mask_fragment(VertexOut in [[stage_in]],
+                                                 constant ShadowMaskUniforms& uniforms [[buffer(0)]]) {
+                // tex_coord spans the padded mask, in pixels around the shape's center
+                float2 p = (in.tex_coord - float2(0.5, 0.5)) * (uniforms.half_size + uniforms.padding) * 2.0;
+                float d = sdFrame(p, uniforms.half_size, uniforms.radii, uniforms.corner_style);
+                return float4(1.0 - smoothstep(-0.5, 0.5, d));
+            }
+
+            struct BlurParams {
+                int2 direction;
+                float sigma;
+                int radius;
+            };
+
+            kernel void gaussian_blur(texture2d<float, access::read> source [[texture(0)]],
+                                      texture2d<float, access::write> destination [[texture(1)]],
+                                      constant BlurParams& params [[buffer(0)]],
+                                      uint2 gid [[thread_position_in_grid]]) {
+                if (gid.x >= destination.get_width() || gid.y >= destination.get_height()) {
+                    return;
+                }
+
+                int2 last = int2(source.get_width(), source.get_height()) - 1;
+                float sum = 0.0;
+                float total = 0.0;
+                for (int i = -params.radius; i <= params.radius; i++) {
+                    int2 coord = clamp(int2(gid) + params.direction * i, int2(0), last);
+                    float weight = exp(-float(i * i) / (2.0 * params.sigma * params.sigma));
+                    sum += source.read(uint2(coord)).r * weight;
+                    total += weight;
+                }
+                destination.write(float4(sum / total), gid);
+            }
+
+            // The same blur over all four channels, for rendered content
+            kernel void gaussian_blur_color(texture2d<float, access::read> source [[texture(0)]],
+                                            texture2d<float, access::write> destination [[texture(1)]],
+                                            constant BlurParams& params [[buffer(0)]],
+                                            uint2 gid [[thread_position_in_grid]]) {
+                if (gid.x >= destination.get_width() || gid.y >= destination.get_height()) {
+                    return;
+                }
+
+                int2 last = int2(source.get_width(), source.get_height()) - 1;
+                float4 sum = float4(0.0);
+                float total = 0.0;
+                for (int i = -params.radius; i <= params.radius; i++) {
+                    int2 coord = clamp(int2(gid) + params.direction * i, int2(0), last);
+                    float weight = exp(-float(i * i) / (2.0 * params.sigma * params.sigma));
+                    sum += source.read(uint2(coord)) * weight;
+                    total += weight;
+                }
+                destination.write(sum / total, gid);
             }
         "#;
 
         let options = metal::CompileOptions::new();
         self.device
             .new_library_with_source(shader_source, &options)
-            .map_err(|e| format!("Failed to compile shaders: {}", e))
+            .map_err(SolError::ShaderCompilation)
     }
 
-    fn create_pipeline_state(&self, library: &Library) -> Result<RenderPipelineState, String> {
-        let vertex_function = library
-            .get_function("vertex_main", None)
-            .map_err(|e| format!("Failed to find vertex_main function: {}", e))?;
+    /// Create the solid pipeline, which replaces the target's pixels instead
+    /// of blending over them when `blending` is off
+    /// Pipeline for solid geometry, blended over what's drawn, or for
+    /// cutouts when `erase` is set, which clear what's drawn by their alpha
+    fn create_pipeline_state(
+        &self,
+        library: &Library,
+        erase: bool,
+    ) -> SolResult<RenderPipelineState> {
+        let vertex_function = get_shader_function(library, "vertex_main")?;
 
-        let fragment_function = library
-            .get_function("fragment_main", None)
-            .map_err(|e| format!("Failed to find fragment_main function: {}", e))?;
+        let fragment_function = get_shader_function(library, "fragment_main")?;
 
         let vertex_descriptor = VertexDescriptor::new();
 
@@ -270,24 +1021,33 @@ impl MetalRenderer {
             .unwrap();
         attachment.set_pixel_format(metal::MTLPixelFormat::BGRA8Unorm);
         attachment.set_blending_enabled(true);
-        attachment.set_source_rgb_blend_factor(metal::MTLBlendFactor::SourceAlpha);
+        let source_factor = if erase {
+            metal::MTLBlendFactor::Zero
+        } else {
+            metal::MTLBlendFactor::SourceAlpha
+        };
+        attachment.set_source_rgb_blend_factor(source_factor);
         attachment.set_destination_rgb_blend_factor(metal::MTLBlendFactor::OneMinusSourceAlpha);
-        attachment.set_source_alpha_blend_factor(metal::MTLBlendFactor::SourceAlpha);
+        attachment.set_source_alpha_blend_factor(source_factor);
         attachment.set_destination_alpha_blend_factor(metal::MTLBlendFactor::OneMinusSourceAlpha);
 
         self.device
             .new_render_pipeline_state(&pipeline_descriptor)
-            .map_err(|e| format!("Failed to create pipeline state: {}", e))
+            .map_err(|message| SolError::PipelineCreation {
+                pipeline: if erase { "cutout" } else { "solid" },
+                message,
+            })
     }
 
-    fn create_text_pipeline_state(&self, library: &Library) -> Result<RenderPipelineState, String> {
-        let vertex_function = library
-            .get_function("text_vertex_main", None)
-            .map_err(|e| format!("Failed to find text_vertex_main function: {}", e))?;
+    fn create_text_pipeline_state(
+        &self,
+        library: &Library,
+        fragment: &str,
+        pipeline: &'static str,
+    ) -> SolResult<RenderPipelineState> {
+        let vertex_function = get_shader_function(library, "text_vertex_main")?;
 
-        let fragment_function = library
-            .get_function("text_fragment_main", None)
-            .map_err(|e| format!("Failed to find text_fragment_main function: {}", e))?;
+        let fragment_function = get_shader_function(library, fragment)?;
 
         let vertex_descriptor = VertexDescriptor::new();
 
@@ -329,20 +1089,13 @@ impl MetalRenderer {
 
         self.device
             .new_render_pipeline_state(&pipeline_descriptor)
-            .map_err(|e| format!("Failed to create text pipeline state: {}", e))
+            .map_err(|message| SolError::PipelineCreation { pipeline, message })
     }
 
-    fn create_frame_pipeline_state(
-        &self,
-        library: &Library,
-    ) -> Result<RenderPipelineState, String> {
-        let vertex_function = library
-            .get_function("frame_vertex_main", None)
-            .map_err(|e| format!("Failed to find frame_vertex_main function: {}", e))?;
+    fn create_frame_pipeline_state(&self, library: &Library) -> SolResult<RenderPipelineState> {
+        let vertex_function = get_shader_function(library, "frame_vertex_main")?;
 
-        let fragment_function = library
-            .get_function("frame_fragment_main", None)
-            .map_err(|e| format!("Failed to find frame_fragment_main function: {}", e))?;
+        let fragment_function = get_shader_function(library, "frame_fragment_main")?;
 
         let vertex_descriptor = VertexDescriptor::new();
 
@@ -384,79 +1137,427 @@ impl MetalRenderer {
 
         self.device
             .new_render_pipeline_state(&pipeline_descriptor)
-            .map_err(|e| format!("Failed to create frame pipeline state: {}", e))
+            .map_err(|message| SolError::PipelineCreation {
+                pipeline: "frame",
+                message,
+            })
     }
 
-    /// Convert text to vertices using shaped glyphs
-    fn text_to_vertices(
-        &self,
-        position: glam::Vec2,
-        shaped_text: &ShapedText,
-        color: &Color,
-        text_system: &TextSystem,
-        screen_size: (f32, f32),
-        scale_factor: f32,
-    ) -> Vec<Vertex> {
-        let mut vertices = Vec::new();
-        let color_array = [color.red, color.green, color.blue, color.alpha];
-
-        for glyph in &shaped_text.glyphs {
-            if let Some(info) = text_system.glyph_info(glyph.font_id, glyph.glyph_id, glyph.size) {
-                // Calculate glyph position in screen space
-                // glyph.position is the baseline position from the shaper
-                // info.bearing_y is the distance from baseline to top of glyph
-                let glyph_x = position.x + glyph.position.x + info.left as f32;
-                let glyph_y = position.y + glyph.position.y - info.top as f32;
+    /// Pipeline for RGBA images, drawn with the text vertex shader
+    fn create_image_pipeline_state(&self, library: &Library) -> SolResult<RenderPipelineState> {
+        let vertex_function = get_shader_function(library, "text_vertex_main")?;
 
-                // Convert to NDC
-                // Note: glyph positions are in logical pixels, screen_size is in logical pixels
-                let physical_width = screen_size.0 * scale_factor;
-                let physical_height = screen_size.1 * scale_factor;
-                let x1 = (glyph_x * scale_factor / physical_width) * 2.0 - 1.0;
-                let y1 = 1.0 - (glyph_y * scale_factor / physical_height) * 2.0;
-                let x2 =
-                    ((glyph_x + info.width as f32) * scale_factor / physical_width) * 2.0 - 1.0;
-                let y2 =
-                    1.0 - ((glyph_y + info.height as f32) * scale_factor / physical_height) * 2.0;
+        let fragment_function = get_shader_function(library, "image_fragment_main")?;
 
-                // Create two triangles for the glyph quad
-                vertices.extend_from_slice(&[
-                    Vertex {
-                        position: [x1, y1],
-                        color: color_array,
-                        tex_coord: [info.uv_min.0, info.uv_min.1],
-                    },
-                    Vertex {
-                        position: [x2, y1],
-                        color: color_array,
-                        tex_coord: [info.uv_max.0, info.uv_min.1],
-                    },
-                    Vertex {
-                        position: [x1, y2],
-                        color: color_array,
-                        tex_coord: [info.uv_min.0, info.uv_max.1],
-                    },
-                    Vertex {
-                        position: [x2, y1],
-                        color: color_array,
-                        tex_coord: [info.uv_max.0, info.uv_min.1],
-                    },
-                    Vertex {
-                        position: [x2, y2],
-                        color: color_array,
-                        tex_coord: [info.uv_max.0, info.uv_max.1],
-                    },
-                    Vertex {
-                        position: [x1, y2],
-                        color: color_array,
-                        tex_coord: [info.uv_min.0, info.uv_max.1],
-                    },
-                ]);
-            }
-        }
+        let vertex_descriptor = VertexDescriptor::new();
 
-        vertices
-    }
+        // Same vertex descriptor as the solid and text pipelines
+        let position_attr = vertex_descriptor.attributes().object_at(0).unwrap();
+        position_attr.set_format(metal::MTLVertexFormat::Float2);
+        position_attr.set_offset(0);
+        position_attr.set_buffer_index(0);
+
+        let color_attr = vertex_descriptor.attributes().object_at(1).unwrap();
+        color_attr.set_format(metal::MTLVertexFormat::Float4);
+        color_attr.set_offset(8);
+        color_attr.set_buffer_index(0);
+
+        let tex_coord_attr = vertex_descriptor.attributes().object_at(2).unwrap();
+        tex_coord_attr.set_format(metal::MTLVertexFormat::Float2);
+        tex_coord_attr.set_offset(24);
+        tex_coord_attr.set_buffer_index(0);
+
+        let layout = vertex_descriptor.layouts().object_at(0).unwrap();
+        layout.set_stride(32);
+        layout.set_step_function(metal::MTLVertexStepFunction::PerVertex);
+
+        let pipeline_descriptor = RenderPipelineDescriptor::new();
+        pipeline_descriptor.set_vertex_function(Some(&vertex_function));
+        pipeline_descriptor.set_fragment_function(Some(&fragment_function));
+        pipeline_descriptor.set_vertex_descriptor(Some(vertex_descriptor));
+
+        let attachment = pipeline_descriptor
+            .color_attachments()
+            .object_at(0)
+            .unwrap();
+        attachment.set_pixel_format(metal::MTLPixelFormat::BGRA8Unorm);
+        attachment.set_blending_enabled(true);
+        attachment.set_source_rgb_blend_factor(metal::MTLBlendFactor::SourceAlpha);
+        attachment.set_destination_rgb_blend_factor(metal::MTLBlendFactor::OneMinusSourceAlpha);
+        attachment.set_source_alpha_blend_factor(metal::MTLBlendFactor::SourceAlpha);
+        attachment.set_destination_alpha_blend_factor(metal::MTLBlendFactor::OneMinusSourceAlpha);
+
+        self.device
+            .new_render_pipeline_state(&pipeline_descriptor)
+            .map_err(|message| SolError::PipelineCreation {
+                pipeline: "image",
+                message,
+            })
+    }
+
+    fn create_shadow_mask_pipeline_state(
+        &self,
+        library: &Library,
+    ) -> SolResult<RenderPipelineState> {
+        let vertex_function = get_shader_function(library, "vertex_main")?;
+
+        let fragment_function = get_shader_function(library, "shadow_mask_fragment")?;
+
+        let vertex_descriptor = VertexDescriptor::new();
+
+        // Same vertex descriptor as other pipelines
+        let position_attr = vertex_descriptor.attributes().object_at(0).unwrap();
+        position_attr.set_format(metal::MTLVertexFormat::Float2);
+        position_attr.set_offset(0);
+        position_attr.set_buffer_index(0);
+
+        let color_attr = vertex_descriptor.attributes().object_at(1).unwrap();
+        color_attr.set_format(metal::MTLVertexFormat::Float4);
+        color_attr.set_offset(8);
+        color_attr.set_buffer_index(0);
+
+        let tex_coord_attr = vertex_descriptor.attributes().object_at(2).unwrap();
+        tex_coord_attr.set_format(metal::MTLVertexFormat::Float2);
+        tex_coord_attr.set_offset(24);
+        tex_coord_attr.set_buffer_index(0);
+
+        let layout = vertex_descriptor.layouts().object_at(0).unwrap();
+        layout.set_stride(32);
+        layout.set_step_function(metal::MTLVertexStepFunction::PerVertex);
+
+        let pipeline_descriptor = RenderPipelineDescriptor::new();
+        pipeline_descriptor.set_vertex_function(Some(&vertex_function));
+        pipeline_descriptor.set_fragment_function(Some(&fragment_function));
+        pipeline_descriptor.set_vertex_descriptor(Some(vertex_descriptor));
+
+        // Masks are single-channel coverage, written without blending
+        let attachment = pipeline_descriptor
+            .color_attachments()
+            .object_at(0)
+            .unwrap();
+        attachment.set_pixel_format(MTLPixelFormat::R8Unorm);
+
+        self.device
+            .new_render_pipeline_state(&pipeline_descriptor)
+            .map_err(|message| SolError::PipelineCreation {
+                pipeline: "shadow mask",
+                message,
+            })
+    }
+
+    /// Create a blur pass over a mask's red channel, or over all four
+    /// channels with `color`
+    fn create_blur_pipeline_state(
+        &self,
+        library: &Library,
+        color: bool,
+    ) -> SolResult<ComputePipelineState> {
+        let (function, pipeline) = if color {
+            ("gaussian_blur_color", "color blur")
+        } else {
+            ("gaussian_blur", "blur")
+        };
+        let function = get_shader_function(library, function)?;
+
+        self.device
+            .new_compute_pipeline_state_with_function(&function)
+            .map_err(|message| SolError::PipelineCreation { pipeline, message })
+    }
+
+    /// Upload the images in a draw list that don't have a texture yet
+    ///
+    /// Small images are packed into the image atlas; the rest, and any that
+    /// don't fit, get a texture each.
+    fn prepare_images(&mut self, draw_list: &DrawList) {
+        for command in draw_list.commands() {
+            let DrawCommand::Image { image, .. } = command else {
+                continue;
+            };
+            if ImageAtlas::accepts(image) {
+                let device = &self.device;
+                let atlas = self
+                    .image_atlas
+                    .get_or_insert_with(|| ImageAtlas::new(device));
+                if atlas.prepare(image, self.frame_count) {
+                    continue;
+                }
+            }
+
+            let key = Arc::as_ptr(image) as usize;
+            if let Some(cached) = self.image_cache.get_mut(&key) {
+                cached.last_used = self.frame_count;
+                continue;
+            }
+            let (width, height) = (image.width() as u64, image.height() as u64);
+            if width == 0 || height == 0 {
+                continue;
+            }
+
+            let descriptor = TextureDescriptor::new();
+            descriptor.set_pixel_format(MTLPixelFormat::RGBA8Unorm);
+            descriptor.set_width(width);
+            descriptor.set_height(height);
+            descriptor.set_usage(MTLTextureUsage::ShaderRead);
+            descriptor.set_storage_mode(MTLStorageMode::Managed);
+            let texture = self.device.new_texture(&descriptor);
+            texture.replace_region(
+                MTLRegion::new_2d(0, 0, width, height),
+                0,
+                image.as_raw().as_ptr() as *const _,
+                width * 4,
+            );
+
+            self.image_cache.insert(
+                key,
+                CachedImage {
+                    image: image.clone(),
+                    texture,
+                    last_used: self.frame_count,
+                },
+            );
+        }
+    }
+
+    /// Render and cache blurred masks for the large shadows in a draw list
+    ///
+    /// Must run before the list's render pass starts, since the blur is
+    /// encoded into the same command buffer.
+    fn prepare_blurred_shadows(
+        &mut self,
+        draw_list: &DrawList,
+        command_buffer: &CommandBufferRef,
+        scale_factor: f32,
+    ) {
+        if !self.shadows_enabled {
+            return;
+        }
+        for command in draw_list.commands() {
+            let DrawCommand::Frame { rect, style } = command else {
+                continue;
+            };
+            let Some(key) =
+                ShadowKey::for_frame(rect, style, scale_factor, self.blurred_shadow_threshold)
+            else {
+                continue;
+            };
+
+            if let Some(cached) = self.shadow_cache.get_mut(&key) {
+                cached.last_used = self.frame_count;
+            } else if let Some(texture) = self.render_shadow_mask(&key, command_buffer) {
+                self.shadow_cache.insert(
+                    key,
+                    CachedShadow {
+                        texture,
+                        last_used: self.frame_count,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Encode a shape's coverage mask followed by a horizontal and a vertical
+    /// blur pass, returning the blurred mask
+    fn render_shadow_mask(
+        &mut self,
+        key: &ShadowKey,
+        command_buffer: &CommandBufferRef,
+    ) -> Option<Texture> {
+        let _mask_span = info_span!("metal_render_shadow_mask").entered();
+        let mask_pipeline_state = self.shadow_mask_pipeline_state.clone()?;
+        let blur_pipeline_state = self.blur_pipeline_state.clone()?;
+
+        let (width, height) = key.mask_size();
+        let descriptor = TextureDescriptor::new();
+        descriptor.set_pixel_format(MTLPixelFormat::R8Unorm);
+        descriptor.set_width(width as u64);
+        descriptor.set_height(height as u64);
+        descriptor.set_usage(
+            MTLTextureUsage::RenderTarget
+                | MTLTextureUsage::ShaderRead
+                | MTLTextureUsage::ShaderWrite,
+        );
+        descriptor.set_storage_mode(MTLStorageMode::Private);
+        let mask = self.device.new_texture(&descriptor);
+        let scratch = self.device.new_texture(&descriptor);
+        self.frame_stats.offscreen_texture_bytes += key.mask_bytes() * 2;
+        self.frame_stats.draw_calls += 1;
+
+        // Coverage of the unblurred shape, centered in the padded mask
+        let render_pass_descriptor = RenderPassDescriptor::new();
+        let color_attachment = render_pass_descriptor
+            .color_attachments()
+            .object_at(0)
+            .unwrap();
+        color_attachment.set_texture(Some(&mask));
+        color_attachment.set_load_action(MTLLoadAction::Clear);
+        color_attachment.set_clear_color(metal::MTLClearColor::new(0.0, 0.0, 0.0, 0.0));
+        color_attachment.set_store_action(MTLStoreAction::Store);
+
+        let quad = self.rect_to_vertices(
+            &Rect::new(0.0, 0.0, 1.0, 1.0),
+            Color::new(1.0, 1.0, 1.0, 1.0),
+            (1.0, 1.0),
+            1.0,
+        );
+        let vertex_buffer = self.device.new_buffer_with_data(
+            quad.as_ptr() as *const _,
+            mem::size_of_val(&quad) as u64,
+            metal::MTLResourceOptions::CPUCacheModeDefaultCache,
+        );
+        let uniforms = ShadowMaskUniforms {
+            half_size: [key.width as f32 / 2.0, key.height as f32 / 2.0],
+            padding: [key.blur as f32, key.blur as f32],
+            radii: key.radii.map(|radius| radius as f32),
+            corner_style: key.corner_style,
+            _padding: [0; 3],
+        };
+        self.frame_stats.vertex_buffer_bytes += vertex_buffer.length();
+
+        let encoder = command_buffer.new_render_command_encoder(&render_pass_descriptor);
+        encoder.set_render_pipeline_state(&mask_pipeline_state);
+        encoder.set_vertex_buffer(0, Some(&vertex_buffer), 0);
+        encoder.set_fragment_bytes(
+            0,
+            mem::size_of::<ShadowMaskUniforms>() as u64,
+            &uniforms as *const _ as *const _,
+        );
+        encoder.draw_primitives(MTLPrimitiveType::Triangle, 0, quad.len() as u64);
+        encoder.end_encoding();
+
+        // The SDF shadow fades over twice the blur radius, so a Gaussian with
+        // sigma of half the radius covers about the same distance
+        let sigma = (key.blur as f32 / 2.0).max(0.5);
+        let radius = (sigma * 3.0).ceil() as i32;
+        let threads = MTLSize::new(16, 16, 1);
+        let groups = MTLSize::new(width.div_ceil(16) as u64, height.div_ceil(16) as u64, 1);
+
+        let encoder = command_buffer.new_compute_command_encoder();
+        encoder.set_compute_pipeline_state(&blur_pipeline_state);
+        for (direction, source, destination) in
+            [([1, 0], &mask, &scratch), ([0, 1], &scratch, &mask)]
+        {
+            let params = BlurParams {
+                direction,
+                sigma,
+                radius,
+            };
+            encoder.set_texture(0, Some(source));
+            encoder.set_texture(1, Some(destination));
+            encoder.set_bytes(
+                0,
+                mem::size_of::<BlurParams>() as u64,
+                &params as *const _ as *const _,
+            );
+            encoder.dispatch_thread_groups(groups, threads);
+        }
+        encoder.end_encoding();
+
+        Some(mask)
+    }
+
+    /// Convert text to vertices using shaped glyphs
+    ///
+    /// `transforms` moves, scales and fades glyphs by their index in the
+    /// shaped text.
+    fn text_to_vertices(
+        &self,
+        position: glam::Vec2,
+        shaped_text: &ShapedText,
+        color: &Color,
+        transforms: Option<&[GlyphTransform]>,
+        text_system: &TextSystem,
+        screen_size: (f32, f32),
+        scale_factor: f32,
+    ) -> Vec<Vertex> {
+        let mut vertices = Vec::new();
+
+        for (index, glyph) in shaped_text.glyphs.iter().enumerate() {
+            let transform = transforms
+                .and_then(|transforms| transforms.get(index))
+                .copied()
+                .unwrap_or(GlyphTransform::IDENTITY);
+            if transform.opacity <= 0.0 || transform.scale <= 0.0 {
+                continue;
+            }
+            let color = glyph.color.as_ref().unwrap_or(color);
+            let color_array = [
+                color.red,
+                color.green,
+                color.blue,
+                color.alpha * transform.opacity,
+            ];
+
+            if let Some(info) =
+                text_system.glyph_info(glyph.font_id, glyph.glyph_id, glyph.size, glyph.scale)
+            {
+                // Calculate glyph position in screen space
+                // glyph.position is the logical baseline position from the shaper,
+                // while the bitmap offsets and size are in device pixels, or
+                // in distance field pixels
+                let bitmap_scale = match glyph.distance_field_scale {
+                    Some(scale) => 1.0 / scale,
+                    None => glyph.scale as f32 / 100.0,
+                };
+                let glyph_x = position.x + glyph.position.x + info.left as f32 / bitmap_scale;
+                let glyph_y = position.y + glyph.position.y - info.top as f32 / bitmap_scale;
+                let glyph_width = info.width as f32 / bitmap_scale;
+                let glyph_height = info.height as f32 / bitmap_scale;
+
+                // Scale around the glyph's center, then move it
+                let glyph_x =
+                    glyph_x + glyph_width * (1.0 - transform.scale) / 2.0 + transform.offset.x;
+                let glyph_y =
+                    glyph_y + glyph_height * (1.0 - transform.scale) / 2.0 + transform.offset.y;
+                let glyph_width = glyph_width * transform.scale;
+                let glyph_height = glyph_height * transform.scale;
+
+                // Convert to NDC
+                // Note: glyph positions are in logical pixels, screen_size is in logical pixels
+                let physical_width = screen_size.0 * scale_factor;
+                let physical_height = screen_size.1 * scale_factor;
+                let x1 = (glyph_x * scale_factor / physical_width) * 2.0 - 1.0;
+                let y1 = 1.0 - (glyph_y * scale_factor / physical_height) * 2.0;
+                let x2 = ((glyph_x + glyph_width) * scale_factor / physical_width) * 2.0 - 1.0;
+                let y2 = 1.0 - ((glyph_y + glyph_height) * scale_factor / physical_height) * 2.0;
+
+                // Create two triangles for the glyph quad
+                vertices.extend_from_slice(&[
+                    Vertex {
+                        position: [x1, y1],
+                        color: color_array,
+                        tex_coord: [info.uv_min.0, info.uv_min.1],
+                    },
+                    Vertex {
+                        position: [x2, y1],
+                        color: color_array,
+                        tex_coord: [info.uv_max.0, info.uv_min.1],
+                    },
+                    Vertex {
+                        position: [x1, y2],
+                        color: color_array,
+                        tex_coord: [info.uv_min.0, info.uv_max.1],
+                    },
+                    Vertex {
+                        position: [x2, y1],
+                        color: color_array,
+                        tex_coord: [info.uv_max.0, info.uv_min.1],
+                    },
+                    Vertex {
+                        position: [x2, y2],
+                        color: color_array,
+                        tex_coord: [info.uv_max.0, info.uv_max.1],
+                    },
+                    Vertex {
+                        position: [x1, y2],
+                        color: color_array,
+                        tex_coord: [info.uv_min.0, info.uv_max.1],
+                    },
+                ]);
+            }
+        }
+
+        vertices
+    }
 
     /// Convert a rect to 6 vertices (two triangles)
     fn rect_to_vertices(
@@ -514,6 +1615,8 @@ impl MetalRenderer {
     }
 
     /// Render draw commands to an existing render encoder
+    ///
+    /// `opacity` scales the alpha of everything drawn, for layer fades.
     fn render_draw_list_with_encoder(
         &mut self,
         draw_list: &DrawList,
@@ -521,9 +1624,13 @@ impl MetalRenderer {
         screen_size: (f32, f32),
         scale_factor: f32,
         text_system: &mut TextSystem,
+        opacity: f32,
     ) {
         let _encoder_span = info_span!("render_with_encoder").entered();
 
+        // Upload new images before the pipelines are borrowed for drawing
+        self.prepare_images(draw_list);
+
         // Get pipeline states
         let Some(pipeline_state) = &self.pipeline_state else {
             eprintln!("Pipeline state not initialized");
@@ -537,6 +1644,23 @@ impl MetalRenderer {
             eprintln!("Frame pipeline state not initialized");
             return;
         };
+        let Some(image_pipeline_state) = &self.image_pipeline_state else {
+            eprintln!("Image pipeline state not initialized");
+            return;
+        };
+        let Some(cutout_pipeline_state) = &self.cutout_pipeline_state else {
+            eprintln!("Cutout pipeline state not initialized");
+            return;
+        };
+        let Some(text_distance_field_pipeline_state) = &self.text_distance_field_pipeline_state
+        else {
+            eprintln!("Text distance field pipeline state not initialized");
+            return;
+        };
+        let Some(text_gradient_pipeline_state) = &self.text_gradient_pipeline_state else {
+            eprintln!("Text gradient pipeline state not initialized");
+            return;
+        };
 
         // Calculate physical dimensions for scissor rects
         let physical_width = (screen_size.0 * scale_factor) as u64;
@@ -551,13 +1675,29 @@ impl MetalRenderer {
         };
         encoder.set_scissor_rect(full_screen_scissor);
 
-        // Track clip stack for nested clips
-        let mut clip_stack: Vec<Rect> = Vec::new();
+        // Layer uniforms stay bound for every pipeline used in this pass, and
+        // are updated when a rounded clip starts or ends
+        let set_layer_uniforms = |rounded_clip: Option<(Rect, Corners)>| {
+            let layer_uniforms = LayerUniforms::new(opacity, rounded_clip, scale_factor);
+            encoder.set_fragment_bytes(
+                1,
+                mem::size_of::<LayerUniforms>() as u64,
+                &layer_uniforms as *const _ as *const _,
+            );
+        };
+        set_layer_uniforms(None);
+
+        // Track clip stack for nested clips, with the rounded clip each one
+        // is inside of
+        let mut clip_stack: Vec<(Rect, Option<(Rect, Corners)>)> = Vec::new();
 
         // Accumulators for batching within same clip region
         let mut solid_vertices: Vec<Vertex> = Vec::new();
         let mut text_vertices: Vec<Vertex> = Vec::new();
+        let mut distance_field_text_vertices: Vec<Vertex> = Vec::new();
         let mut frames: Vec<(Rect, ElementStyle)> = Vec::new();
+        let mut images: Vec<([Vertex; 6], usize)> = Vec::new();
+        let mut stats = RendererStats::default();
 
         // Helper to convert logical rect to physical scissor rect
         let to_scissor_rect = |rect: &Rect, scale: f32, ph: u64| -> MTLScissorRect {
@@ -579,17 +1719,31 @@ impl MetalRenderer {
         };
 
         // Helper closure to flush accumulated geometry
+        //
+        // Solid rects, frames and images are drawn in that order, with text
+        // on top of them all.
         let flush_batches = |encoder: &metal::RenderCommandEncoderRef,
                              device: &Device,
                              solid_vertices: &mut Vec<Vertex>,
                              text_vertices: &mut Vec<Vertex>,
+                             distance_field_text_vertices: &mut Vec<Vertex>,
                              frames: &mut Vec<(Rect, ElementStyle)>,
+                             images: &mut Vec<([Vertex; 6], usize)>,
                              pipeline_state: &RenderPipelineState,
                              text_pipeline_state: &RenderPipelineState,
+                             text_distance_field_pipeline_state: &RenderPipelineState,
                              frame_pipeline_state: &RenderPipelineState,
+                             image_pipeline_state: &RenderPipelineState,
+                             linear_sampler: &metal::SamplerStateRef,
+                             shadow_cache: &HashMap<ShadowKey, CachedShadow>,
+                             image_cache: &HashMap<usize, CachedImage>,
+                             image_atlas: Option<&ImageAtlas>,
+                             shadow_threshold: Option<f32>,
+                             antialias: bool,
                              text_system: &mut TextSystem,
                              screen_size: (f32, f32),
-                             scale_factor: f32| {
+                             scale_factor: f32,
+                             stats: &mut RendererStats| {
             // Draw solid geometry
             if !solid_vertices.is_empty() {
                 let buffer = device.new_buffer_with_data(
@@ -597,6 +1751,8 @@ impl MetalRenderer {
                     (solid_vertices.len() * mem::size_of::<Vertex>()) as u64,
                     metal::MTLResourceOptions::CPUCacheModeDefaultCache,
                 );
+                stats.vertex_buffer_bytes += buffer.length();
+                stats.draw_calls += 1;
                 encoder.set_render_pipeline_state(pipeline_state);
                 encoder.set_vertex_buffer(0, Some(&buffer), 0);
                 encoder.draw_primitives(
@@ -607,39 +1763,49 @@ impl MetalRenderer {
                 solid_vertices.clear();
             }
 
-            // Draw text geometry
-            if !text_vertices.is_empty() {
-                let buffer = device.new_buffer_with_data(
-                    text_vertices.as_ptr() as *const _,
-                    (text_vertices.len() * mem::size_of::<Vertex>()) as u64,
-                    metal::MTLResourceOptions::CPUCacheModeDefaultCache,
-                );
-                let texture = text_system.atlas_texture();
-                encoder.set_render_pipeline_state(text_pipeline_state);
-                encoder.set_vertex_buffer(0, Some(&buffer), 0);
-                encoder.set_fragment_texture(0, Some(texture));
-
-                let sampler_descriptor = metal::SamplerDescriptor::new();
-                sampler_descriptor.set_min_filter(metal::MTLSamplerMinMagFilter::Linear);
-                sampler_descriptor.set_mag_filter(metal::MTLSamplerMinMagFilter::Linear);
-                let sampler_state = device.new_sampler(&sampler_descriptor);
-                encoder.set_fragment_sampler_state(0, Some(&sampler_state));
-
-                encoder.draw_primitives(
-                    MTLPrimitiveType::Triangle,
-                    0,
-                    text_vertices.len() as u64,
-                );
-                text_vertices.clear();
-            }
-
             // Draw frames
             if !frames.is_empty() {
                 encoder.set_render_pipeline_state(frame_pipeline_state);
 
-                for (rect, style) in frames.drain(..) {
-                    let (vertices, uniforms) =
-                        frame_to_vertices_static(&rect, &style, screen_size, scale_factor);
+                for (rect, mut style) in frames.drain(..) {
+                    // Large shadows are drawn from their blurred mask, under a
+                    // frame drawn without a shadow
+                    let blurred_shadow =
+                        ShadowKey::for_frame(&rect, &style, scale_factor, shadow_threshold)
+                            .and_then(|key| shadow_cache.get(&key))
+                            .zip(style.shadow);
+                    if let Some((cached, shadow)) = blurred_shadow {
+                        style.shadow = None;
+                        let vertices =
+                            blurred_shadow_to_vertices(&rect, &shadow, screen_size, scale_factor);
+                        let buffer = device.new_buffer_with_data(
+                            vertices.as_ptr() as *const _,
+                            (vertices.len() * mem::size_of::<Vertex>()) as u64,
+                            metal::MTLResourceOptions::CPUCacheModeDefaultCache,
+                        );
+                        stats.vertex_buffer_bytes += buffer.length();
+                        stats.draw_calls += 1;
+
+                        // The mask is coverage, tinted like a glyph
+                        encoder.set_render_pipeline_state(text_pipeline_state);
+                        encoder.set_vertex_buffer(0, Some(&buffer), 0);
+                        encoder.set_fragment_texture(0, Some(&cached.texture));
+                        encoder.set_fragment_sampler_state(0, Some(linear_sampler));
+                        encoder.draw_primitives(
+                            MTLPrimitiveType::Triangle,
+                            0,
+                            vertices.len() as u64,
+                        );
+                        encoder.set_render_pipeline_state(frame_pipeline_state);
+                    }
+
+                    let (vertices, uniforms) = frame_to_vertices_static(
+                        &rect,
+                        &style,
+                        antialias,
+                        screen_size,
+                        scale_factor,
+                    );
                     let vertex_buffer = device.new_buffer_with_data(
                         vertices.as_ptr() as *const _,
                         (vertices.len() * mem::size_of::<Vertex>()) as u64,
@@ -650,90 +1816,284 @@ impl MetalRenderer {
                         mem::size_of::<FrameUniforms>() as u64,
                         metal::MTLResourceOptions::CPUCacheModeDefaultCache,
                     );
+                    stats.vertex_buffer_bytes += vertex_buffer.length() + uniforms_buffer.length();
+                    stats.draw_calls += 1;
 
                     encoder.set_vertex_buffer(0, Some(&vertex_buffer), 0);
                     encoder.set_fragment_buffer(0, Some(&uniforms_buffer), 0);
                     encoder.draw_primitives(MTLPrimitiveType::Triangle, 0, vertices.len() as u64);
                 }
             }
+
+            // Draw images in order, batching runs of atlas images into one
+            // draw and giving other images a draw each
+            if !images.is_empty() {
+                encoder.set_render_pipeline_state(image_pipeline_state);
+                encoder.set_fragment_sampler_state(0, Some(linear_sampler));
+
+                let mut draw_images = |vertices: &[Vertex], texture: &metal::TextureRef| {
+                    let buffer = device.new_buffer_with_data(
+                        vertices.as_ptr() as *const _,
+                        (vertices.len() * mem::size_of::<Vertex>()) as u64,
+                        metal::MTLResourceOptions::CPUCacheModeDefaultCache,
+                    );
+                    stats.vertex_buffer_bytes += buffer.length();
+                    stats.draw_calls += 1;
+                    encoder.set_vertex_buffer(0, Some(&buffer), 0);
+                    encoder.set_fragment_texture(0, Some(texture));
+                    encoder.draw_primitives(MTLPrimitiveType::Triangle, 0, vertices.len() as u64);
+                };
+
+                let mut atlas_vertices: Vec<Vertex> = Vec::new();
+                for (mut vertices, key) in images.drain(..) {
+                    let atlas_uv = image_atlas.and_then(|atlas| atlas.uv_bounds(key));
+                    if let Some((uv_min, uv_max)) = atlas_uv {
+                        for vertex in &mut vertices {
+                            let [u, v] = vertex.tex_coord;
+                            vertex.tex_coord = [
+                                uv_min[0] + u * (uv_max[0] - uv_min[0]),
+                                uv_min[1] + v * (uv_max[1] - uv_min[1]),
+                            ];
+                        }
+                        atlas_vertices.extend_from_slice(&vertices);
+                        continue;
+                    }
+
+                    let Some(cached) = image_cache.get(&key) else {
+                        continue;
+                    };
+                    // Keep paint order: atlas images queued so far go first
+                    if let Some(atlas) = image_atlas {
+                        if !atlas_vertices.is_empty() {
+                            draw_images(&atlas_vertices, atlas.texture());
+                            atlas_vertices.clear();
+                        }
+                    }
+                    draw_images(&vertices, &cached.texture);
+                }
+                if let Some(atlas) = image_atlas {
+                    if !atlas_vertices.is_empty() {
+                        draw_images(&atlas_vertices, atlas.texture());
+                    }
+                }
+            }
+
+            // Draw text geometry, bitmap glyphs then distance field glyphs
+            let text_batches = [
+                (text_vertices, text_pipeline_state),
+                (
+                    distance_field_text_vertices,
+                    text_distance_field_pipeline_state,
+                ),
+            ];
+            for (vertices, text_pipeline_state) in text_batches {
+                if vertices.is_empty() {
+                    continue;
+                }
+                let buffer = device.new_buffer_with_data(
+                    vertices.as_ptr() as *const _,
+                    (vertices.len() * mem::size_of::<Vertex>()) as u64,
+                    metal::MTLResourceOptions::CPUCacheModeDefaultCache,
+                );
+                stats.vertex_buffer_bytes += buffer.length();
+                stats.draw_calls += 1;
+                let texture = text_system.atlas_texture();
+                encoder.set_render_pipeline_state(text_pipeline_state);
+                encoder.set_vertex_buffer(0, Some(&buffer), 0);
+                encoder.set_fragment_texture(0, texture);
+                encoder.set_fragment_sampler_state(0, Some(linear_sampler));
+
+                encoder.draw_primitives(MTLPrimitiveType::Triangle, 0, vertices.len() as u64);
+                vertices.clear();
+            }
         };
 
         // Process commands in order
         for command in draw_list.commands() {
+            // Flush pending geometry before changing scissor, and before
+            // anything that must draw over batched shapes drawn later in a
+            // flush
+            let needs_flush = match command {
+                DrawCommand::PushClip { .. }
+                | DrawCommand::PushRoundedClip { .. }
+                | DrawCommand::PopClip
+                | DrawCommand::Custom { .. }
+                | DrawCommand::Cutout { .. } => true,
+                DrawCommand::Rect { .. } => !frames.is_empty() || !images.is_empty(),
+                DrawCommand::Frame { .. } => !images.is_empty(),
+                // Gradient text is drawn on its own with its gradient bound,
+                // and decorations are batched with rects
+                DrawCommand::Text { style, .. } => {
+                    gradient_fill(style).is_some()
+                        || (!style.decorations.is_empty()
+                            && (!frames.is_empty() || !images.is_empty()))
+                }
+                DrawCommand::Image { .. }
+                | DrawCommand::BackdropBlur { .. }
+                | DrawCommand::PushBlur { .. }
+                | DrawCommand::PopBlur => false,
+            };
+            if needs_flush {
+                flush_batches(
+                    encoder,
+                    &self.device,
+                    &mut solid_vertices,
+                    &mut text_vertices,
+                    &mut distance_field_text_vertices,
+                    &mut frames,
+                    &mut images,
+                    pipeline_state,
+                    text_pipeline_state,
+                    text_distance_field_pipeline_state,
+                    frame_pipeline_state,
+                    image_pipeline_state,
+                    &self.linear_sampler,
+                    &self.shadow_cache,
+                    &self.image_cache,
+                    self.image_atlas.as_ref(),
+                    self.blurred_shadow_threshold,
+                    self.antialiasing,
+                    text_system,
+                    screen_size,
+                    scale_factor,
+                    &mut stats,
+                );
+            }
+
             match command {
                 DrawCommand::Rect { rect, color } => {
                     let vertices = self.rect_to_vertices(rect, *color, screen_size, scale_factor);
                     solid_vertices.extend_from_slice(&vertices);
                 }
                 DrawCommand::Frame { rect, style } => {
-                    frames.push((*rect, style.clone()));
+                    let mut style = style.clone();
+                    if !self.shadows_enabled {
+                        style.shadow = None;
+                    }
+                    frames.push((*rect, style));
+                }
+                DrawCommand::Image { rect, image } => {
+                    let vertices = self.rect_to_vertices(rect, WHITE, screen_size, scale_factor);
+                    images.push((vertices, Arc::as_ptr(image) as usize));
                 }
                 DrawCommand::Text {
                     position,
                     text,
                     style,
+                    glyph_transforms,
                 } => {
-                    let text_config = crate::text_system::TextConfig {
-                        font_stack: parley::FontStack::from("system-ui"),
-                        size: style.size,
-                        color: style.color.clone(),
-                        weight: parley::FontWeight::NORMAL,
-                        line_height: 1.2,
+                    // The whole style applies, so text draws in the family,
+                    // weight and line height it was measured with rather
+                    // than always in regular system-ui at 1.2 line height
+                    let mut text_config = crate::text_system::TextConfig::from(style);
+                    // A gradient covers the whole text, color ranges included
+                    if gradient_fill(style).is_some() {
+                        text_config.color_ranges.clear();
+                    }
+                    let Ok(shaped) = text_system.shape_text(text, &text_config, None, scale_factor)
+                    else {
+                        continue;
                     };
-                    if let Ok(shaped) = text_system.shape_text(text, &text_config, None, scale_factor)
-                    {
+                    for decoration in &style.decorations {
+                        let color = decoration.color.unwrap_or(style.color);
+                        for rect in TextSystem::decoration_rects(&shaped, decoration) {
+                            let rect = Rect::from_pos_size(*position + rect.pos, rect.size);
+                            let vertices =
+                                self.rect_to_vertices(&rect, color, screen_size, scale_factor);
+                            solid_vertices.extend_from_slice(&vertices);
+                        }
+                    }
+                    let distance_field = shaped
+                        .glyphs
+                        .first()
+                        .is_some_and(ShapedGlyph::is_distance_field);
+                    let Some(fill) = gradient_fill(style) else {
+                        let color = match &style.fill {
+                            Some(Fill::Solid(color)) => *color,
+                            _ => style.color,
+                        };
                         let vertices = self.text_to_vertices(
                             *position,
                             &shaped,
-                            &style.color,
+                            &color,
+                            glyph_transforms.as_deref(),
                             text_system,
                             screen_size,
                             scale_factor,
                         );
-                        text_vertices.extend_from_slice(&vertices);
-                    }
-                }
-                DrawCommand::PushClip { rect } => {
-                    // Flush pending geometry before changing scissor
-                    flush_batches(
-                        encoder,
-                        &self.device,
-                        &mut solid_vertices,
-                        &mut text_vertices,
-                        &mut frames,
-                        pipeline_state,
-                        text_pipeline_state,
-                        frame_pipeline_state,
+                        if distance_field {
+                            distance_field_text_vertices.extend_from_slice(&vertices);
+                        } else {
+                            text_vertices.extend_from_slice(&vertices);
+                        }
+                        continue;
+                    };
+
+                    // Drawn right away, since the gradient is per draw
+                    let vertices = self.text_to_vertices(
+                        *position,
+                        &shaped,
+                        &WHITE,
+                        glyph_transforms.as_deref(),
                         text_system,
                         screen_size,
                         scale_factor,
                     );
+                    if vertices.is_empty() {
+                        continue;
+                    }
+                    let bounds = Rect::from_pos_size(*position, shaped.size);
+                    let uniforms =
+                        TextGradientUniforms::new(fill, bounds, scale_factor, distance_field);
+                    let vertex_buffer = self.device.new_buffer_with_data(
+                        vertices.as_ptr() as *const _,
+                        (vertices.len() * mem::size_of::<Vertex>()) as u64,
+                        metal::MTLResourceOptions::CPUCacheModeDefaultCache,
+                    );
+                    let uniforms_buffer = self.device.new_buffer_with_data(
+                        &uniforms as *const _ as *const _,
+                        mem::size_of::<TextGradientUniforms>() as u64,
+                        metal::MTLResourceOptions::CPUCacheModeDefaultCache,
+                    );
+                    stats.vertex_buffer_bytes += vertex_buffer.length() + uniforms_buffer.length();
+                    stats.draw_calls += 1;
 
-                    // Push new clip rect (already intersected with parent in DrawList)
-                    clip_stack.push(*rect);
+                    encoder.set_render_pipeline_state(text_gradient_pipeline_state);
+                    encoder.set_vertex_buffer(0, Some(&vertex_buffer), 0);
+                    encoder.set_fragment_buffer(0, Some(&uniforms_buffer), 0);
+                    encoder.set_fragment_texture(0, text_system.atlas_texture());
+                    encoder.set_fragment_sampler_state(0, Some(&self.linear_sampler));
+                    encoder.draw_primitives(MTLPrimitiveType::Triangle, 0, vertices.len() as u64);
+                }
+                DrawCommand::PushClip { rect } => {
+                    // Push new clip rect (already intersected with parent in
+                    // DrawList), staying inside any rounded clip
+                    let rounded_clip = clip_stack.last().and_then(|(_, rounded)| *rounded);
+                    clip_stack.push((*rect, rounded_clip));
                     let scissor = to_scissor_rect(rect, scale_factor, physical_height);
                     encoder.set_scissor_rect(scissor);
                     debug!("PushClip: {:?} -> scissor {:?}", rect, scissor);
                 }
+                DrawCommand::PushRoundedClip { rect, corner_radii } => {
+                    // Scissor to the shape's bounds, and round the corners in
+                    // the fragment shaders
+                    let clip_rect = match clip_stack.last() {
+                        Some((parent, _)) => parent
+                            .intersect(rect)
+                            .unwrap_or(Rect::new(rect.pos.x, rect.pos.y, 0.0, 0.0)),
+                        None => *rect,
+                    };
+                    clip_stack.push((clip_rect, Some((*rect, *corner_radii))));
+                    let scissor = to_scissor_rect(&clip_rect, scale_factor, physical_height);
+                    encoder.set_scissor_rect(scissor);
+                    set_layer_uniforms(Some((*rect, *corner_radii)));
+                    debug!("PushRoundedClip: {:?} -> scissor {:?}", rect, scissor);
+                }
                 DrawCommand::PopClip => {
-                    // Flush pending geometry before changing scissor
-                    flush_batches(
-                        encoder,
-                        &self.device,
-                        &mut solid_vertices,
-                        &mut text_vertices,
-                        &mut frames,
-                        pipeline_state,
-                        text_pipeline_state,
-                        frame_pipeline_state,
-                        text_system,
-                        screen_size,
-                        scale_factor,
-                    );
-
                     // Pop clip rect and restore previous scissor
-                    clip_stack.pop();
-                    if let Some(rect) = clip_stack.last() {
+                    let popped = clip_stack.pop();
+                    let rounded_clip = clip_stack.last().and_then(|(_, rounded)| *rounded);
+                    if let Some((rect, _)) = clip_stack.last() {
                         let scissor = to_scissor_rect(rect, scale_factor, physical_height);
                         encoder.set_scissor_rect(scissor);
                         debug!("PopClip: restored {:?} -> scissor {:?}", rect, scissor);
@@ -741,7 +2101,68 @@ impl MetalRenderer {
                         encoder.set_scissor_rect(full_screen_scissor);
                         debug!("PopClip: restored full screen scissor");
                     }
+                    if popped.and_then(|(_, rounded)| rounded) != rounded_clip {
+                        set_layer_uniforms(rounded_clip);
+                    }
+                }
+                DrawCommand::Custom { rect, draw } => {
+                    let ctx = CustomDrawContext {
+                        device: &self.device,
+                        bounds: *rect,
+                        screen_size: Vec2::new(screen_size.0, screen_size.1),
+                        scale_factor,
+                        opacity,
+                        pixel_format: MTLPixelFormat::BGRA8Unorm,
+                    };
+                    draw.draw(encoder, &ctx);
+                    stats.draw_calls += 1;
+
+                    // Put back the state the callback may have changed
+                    let (scissor, rounded_clip) = match clip_stack.last() {
+                        Some((rect, rounded)) => (
+                            to_scissor_rect(rect, scale_factor, physical_height),
+                            *rounded,
+                        ),
+                        None => (full_screen_scissor, None),
+                    };
+                    encoder.set_scissor_rect(scissor);
+                    set_layer_uniforms(rounded_clip);
+                }
+                DrawCommand::Cutout {
+                    rect,
+                    corner_radius,
+                } => {
+                    // Drawn right away, since later shapes must blend over
+                    // the hole. The cutout pipeline erases by coverage, and
+                    // a rounded clip of the hole's own shape rounds it
+                    let vertices = self.rect_to_vertices(rect, BLACK, screen_size, scale_factor);
+                    let buffer = self.device.new_buffer_with_data(
+                        vertices.as_ptr() as *const _,
+                        mem::size_of_val(&vertices) as u64,
+                        metal::MTLResourceOptions::CPUCacheModeDefaultCache,
+                    );
+                    stats.vertex_buffer_bytes += buffer.length();
+                    stats.draw_calls += 1;
+                    let hole = LayerUniforms::new(
+                        1.0,
+                        Some((*rect, Corners::all(*corner_radius))),
+                        scale_factor,
+                    );
+                    encoder.set_fragment_bytes(
+                        1,
+                        mem::size_of::<LayerUniforms>() as u64,
+                        &hole as *const _ as *const _,
+                    );
+                    encoder.set_render_pipeline_state(cutout_pipeline_state);
+                    encoder.set_vertex_buffer(0, Some(&buffer), 0);
+                    encoder.draw_primitives(MTLPrimitiveType::Triangle, 0, vertices.len() as u64);
+                    set_layer_uniforms(clip_stack.last().and_then(|(_, rounded)| *rounded));
                 }
+                // Blurs split the list into separate passes before it gets
+                // here; see `encode_blurred_commands`
+                DrawCommand::BackdropBlur { .. }
+                | DrawCommand::PushBlur { .. }
+                | DrawCommand::PopBlur => {}
             }
         }
 
@@ -751,14 +2172,28 @@ impl MetalRenderer {
             &self.device,
             &mut solid_vertices,
             &mut text_vertices,
+            &mut distance_field_text_vertices,
             &mut frames,
+            &mut images,
             pipeline_state,
             text_pipeline_state,
+            text_distance_field_pipeline_state,
             frame_pipeline_state,
+            image_pipeline_state,
+            &self.linear_sampler,
+            &self.shadow_cache,
+            &self.image_cache,
+            self.image_atlas.as_ref(),
+            self.blurred_shadow_threshold,
+            self.antialiasing,
             text_system,
             screen_size,
             scale_factor,
+            &mut stats,
         );
+
+        self.frame_stats.vertex_buffer_bytes += stats.vertex_buffer_bytes;
+        self.frame_stats.draw_calls += stats.draw_calls;
     }
 
     /// Legacy render method for backwards compatibility
@@ -788,6 +2223,9 @@ impl MetalRenderer {
         color_attachment.set_clear_color(clear_color);
         color_attachment.set_store_action(MTLStoreAction::Store);
 
+        // Blur large shadows before the render pass starts
+        self.prepare_blurred_shadows(draw_list, command_buffer, scale_factor);
+
         // Create render encoder
         let encoder = command_buffer.new_render_command_encoder(&render_pass_descriptor);
 
@@ -798,6 +2236,7 @@ impl MetalRenderer {
             screen_size,
             scale_factor,
             text_system,
+            1.0,
         );
 
         // End encoding
@@ -808,24 +2247,513 @@ impl MetalRenderer {
         command_buffer.commit();
     }
 
-    /// Render a draw list for the layer system
-    pub fn render_draw_list(
+    /// Encode the render passes drawing a draw list into `target`
+    ///
+    /// Lists without blurs are drawn in a single pass.
+    fn encode_draw_list(
+        &mut self,
+        draw_list: &DrawList,
+        command_buffer: &CommandBufferRef,
+        target: &TextureRef,
+        screen_size: (f32, f32),
+        scale_factor: f32,
+        text_system: &mut TextSystem,
+        load_action: metal::MTLLoadAction,
+        clear_color: metal::MTLClearColor,
+        opacity: f32,
+    ) {
+        // Blur large shadows before the render pass starts
+        self.prepare_blurred_shadows(draw_list, command_buffer, scale_factor);
+
+        if draw_list.has_blurs() {
+            self.encode_blurred_commands(
+                draw_list.commands(),
+                command_buffer,
+                target,
+                screen_size,
+                scale_factor,
+                text_system,
+                load_action,
+                clear_color,
+                opacity,
+            );
+        } else {
+            self.encode_render_pass(
+                draw_list,
+                command_buffer,
+                target,
+                screen_size,
+                scale_factor,
+                text_system,
+                load_action,
+                clear_color,
+                opacity,
+            );
+        }
+    }
+
+    /// Encode one render pass drawing a draw list into `target`
+    fn encode_render_pass(
+        &mut self,
+        draw_list: &DrawList,
+        command_buffer: &CommandBufferRef,
+        target: &TextureRef,
+        screen_size: (f32, f32),
+        scale_factor: f32,
+        text_system: &mut TextSystem,
+        load_action: metal::MTLLoadAction,
+        clear_color: metal::MTLClearColor,
+        opacity: f32,
+    ) {
+        // Create render pass descriptor
+        let render_pass_descriptor = RenderPassDescriptor::new();
+        let color_attachment = render_pass_descriptor
+            .color_attachments()
+            .object_at(0)
+            .unwrap();
+        color_attachment.set_texture(Some(target));
+        color_attachment.set_load_action(load_action);
+        color_attachment.set_clear_color(clear_color);
+        color_attachment.set_store_action(MTLStoreAction::Store);
+
+        // Create render encoder
+        let encoder = command_buffer.new_render_command_encoder(&render_pass_descriptor);
+
+        // Render the draw list
+        self.render_draw_list_with_encoder(
+            draw_list,
+            encoder,
+            screen_size,
+            scale_factor,
+            text_system,
+            opacity,
+        );
+
+        // End encoding
+        encoder.end_encoding();
+    }
+
+    /// Encode commands containing blurs, ending the render pass at each blur
+    ///
+    /// Each blur reads what earlier passes drew, so the commands before it
+    /// are drawn first, the blur is encoded, and a new pass picks up where
+    /// the last left off with the clips still in effect pushed again.
+    fn encode_blurred_commands(
+        &mut self,
+        commands: &[DrawCommand],
+        command_buffer: &CommandBufferRef,
+        target: &TextureRef,
+        screen_size: (f32, f32),
+        scale_factor: f32,
+        text_system: &mut TextSystem,
+        mut load_action: metal::MTLLoadAction,
+        clear_color: metal::MTLClearColor,
+        opacity: f32,
+    ) {
+        // Clip commands in effect at the start of the next pass
+        let mut clips: Vec<DrawCommand> = Vec::new();
+        let mut start = 0;
+        loop {
+            let end = commands[start..]
+                .iter()
+                .position(|command| {
+                    matches!(
+                        command,
+                        DrawCommand::BackdropBlur { .. } | DrawCommand::PushBlur { .. }
+                    )
+                })
+                .map_or(commands.len(), |offset| start + offset);
+
+            // Draw everything up to the blur
+            let segment = &commands[start..end];
+            if !segment.is_empty() || !matches!(load_action, MTLLoadAction::Load) {
+                let pass = DrawList::from_commands(clips.iter().chain(segment).cloned().collect());
+                self.encode_render_pass(
+                    &pass,
+                    command_buffer,
+                    target,
+                    screen_size,
+                    scale_factor,
+                    text_system,
+                    load_action,
+                    clear_color,
+                    opacity,
+                );
+                load_action = MTLLoadAction::Load;
+            }
+            for command in segment {
+                match command {
+                    DrawCommand::PushClip { .. } | DrawCommand::PushRoundedClip { .. } => {
+                        clips.push(command.clone());
+                    }
+                    DrawCommand::PopClip => {
+                        clips.pop();
+                    }
+                    _ => {}
+                }
+            }
+
+            match commands.get(end) {
+                Some(DrawCommand::BackdropBlur {
+                    rect,
+                    radius,
+                    corner_radii,
+                }) => {
+                    self.encode_backdrop_blur(
+                        command_buffer,
+                        target,
+                        *rect,
+                        *radius,
+                        *corner_radii,
+                        &clips,
+                        scale_factor,
+                        opacity,
+                    );
+                    start = end + 1;
+                }
+                Some(DrawCommand::PushBlur { rect, radius }) => {
+                    let pop = matching_pop_blur(commands, end + 1);
+                    self.encode_content_blur(
+                        &commands[end + 1..pop],
+                        command_buffer,
+                        target,
+                        *rect,
+                        *radius,
+                        &clips,
+                        scale_factor,
+                        text_system,
+                        opacity,
+                    );
+                    start = (pop + 1).min(commands.len());
+                }
+                _ => break,
+            }
+        }
+    }
+
+    /// Blur what's been drawn into `target` under `rect`, in place
+    ///
+    /// The region is copied out, blurred and drawn back masked to the
+    /// rect's corners, or to the rounded clip it's inside of.
+    fn encode_backdrop_blur(
+        &mut self,
+        command_buffer: &CommandBufferRef,
+        target: &TextureRef,
+        rect: Rect,
+        radius: f32,
+        corner_radii: Corners,
+        clips: &[DrawCommand],
+        scale_factor: f32,
+        opacity: f32,
+    ) {
+        let (clip, rounded_clip) = clip_state(clips);
+        let visible = match clip {
+            Some(clip) => clip.intersect(&rect),
+            None => Some(rect),
+        };
+        let (sigma, kernel_radius) = blur_kernel(radius, scale_factor);
+        let (Some(visible), Some(region)) = (
+            visible,
+            blur_region(&rect, kernel_radius, scale_factor, target),
+        ) else {
+            return;
+        };
+
+        self.reads_target = true;
+        if target.framebuffer_only() {
+            return;
+        }
+        let [backdrop, scratch] = self.blur_targets(region.size.width, region.size.height);
+        let blit = command_buffer.new_blit_command_encoder();
+        blit.copy_from_texture(
+            target,
+            0,
+            0,
+            region.origin,
+            region.size,
+            &backdrop,
+            0,
+            0,
+            MTLOrigin { x: 0, y: 0, z: 0 },
+        );
+        blit.end_encoding();
+
+        self.encode_color_blur(command_buffer, &backdrop, &scratch, sigma, kernel_radius);
+        let mask = if corner_radii == Corners::zero() {
+            rounded_clip
+        } else {
+            Some((rect, corner_radii))
+        };
+        self.composite_blurred(
+            command_buffer,
+            target,
+            &backdrop,
+            region,
+            visible,
+            mask,
+            opacity,
+            scale_factor,
+        );
+    }
+
+    /// Draw `commands` offscreen, then blur them onto `target`
+    ///
+    /// `rect` bounds the content; it's drawn with the clips in effect so the
+    /// blur spreads from what would have been visible.
+    fn encode_content_blur(
+        &mut self,
+        commands: &[DrawCommand],
+        command_buffer: &CommandBufferRef,
+        target: &TextureRef,
+        rect: Rect,
+        radius: f32,
+        clips: &[DrawCommand],
+        scale_factor: f32,
+        text_system: &mut TextSystem,
+        opacity: f32,
+    ) {
+        let (sigma, kernel_radius) = blur_kernel(radius, scale_factor);
+        let Some(region) = blur_region(&rect, kernel_radius, scale_factor, target) else {
+            return;
+        };
+        let origin = Vec2::new(region.origin.x as f32, region.origin.y as f32) / scale_factor;
+        let size = Vec2::new(region.size.width as f32, region.size.height as f32) / scale_factor;
+        let (clip, rounded_clip) = clip_state(clips);
+        let bounds = Rect::from_pos_size(origin, size);
+        let Some(visible) = clip.map_or(Some(bounds), |clip| clip.intersect(&bounds)) else {
+            return;
+        };
+
+        // Draw the content with the region's origin at the texture origin
+        let [content, scratch] = self.blur_targets(region.size.width, region.size.height);
+        let mut local: Vec<DrawCommand> = clips.iter().chain(commands).cloned().collect();
+        for command in &mut local {
+            command.translate(-origin);
+        }
+        self.encode_draw_list(
+            &DrawList::from_commands(local),
+            command_buffer,
+            &content,
+            (size.x, size.y),
+            scale_factor,
+            text_system,
+            MTLLoadAction::Clear,
+            metal::MTLClearColor::new(0.0, 0.0, 0.0, 0.0),
+            1.0,
+        );
+
+        self.encode_color_blur(command_buffer, &content, &scratch, sigma, kernel_radius);
+        self.composite_blurred(
+            command_buffer,
+            target,
+            &content,
+            region,
+            visible,
+            rounded_clip,
+            opacity,
+            scale_factor,
+        );
+    }
+
+    /// Encode a horizontal and a vertical color blur pass over `texture`,
+    /// using `scratch` in between
+    fn encode_color_blur(
+        &mut self,
+        command_buffer: &CommandBufferRef,
+        texture: &TextureRef,
+        scratch: &TextureRef,
+        sigma: f32,
+        kernel_radius: u32,
+    ) {
+        let Some(blur_pipeline_state) = self.color_blur_pipeline_state.clone() else {
+            return;
+        };
+
+        let threads = MTLSize::new(16, 16, 1);
+        let groups = MTLSize::new(
+            texture.width().div_ceil(16),
+            texture.height().div_ceil(16),
+            1,
+        );
+        let encoder = command_buffer.new_compute_command_encoder();
+        encoder.set_compute_pipeline_state(&blur_pipeline_state);
+        for (direction, source, destination) in
+            [([1, 0], texture, scratch), ([0, 1], scratch, texture)]
+        {
+            let params = BlurParams {
+                direction,
+                sigma,
+                radius: kernel_radius as i32,
+            };
+            encoder.set_texture(0, Some(source));
+            encoder.set_texture(1, Some(destination));
+            encoder.set_bytes(
+                0,
+                mem::size_of::<BlurParams>() as u64,
+                &params as *const _ as *const _,
+            );
+            encoder.dispatch_thread_groups(groups, threads);
+        }
+        encoder.end_encoding();
+    }
+
+    /// Draw a blurred texture over `region` of `target`, scissored to
+    /// `visible` and masked to a rounded rect, both in points
+    fn composite_blurred(
+        &mut self,
+        command_buffer: &CommandBufferRef,
+        target: &TextureRef,
+        texture: &TextureRef,
+        region: MTLRegion,
+        visible: Rect,
+        mask: Option<(Rect, Corners)>,
+        opacity: f32,
+        scale_factor: f32,
+    ) {
+        let Some(pipeline_state) = self.blur_composite_pipeline_state() else {
+            return;
+        };
+
+        let render_pass_descriptor = RenderPassDescriptor::new();
+        let color_attachment = render_pass_descriptor
+            .color_attachments()
+            .object_at(0)
+            .unwrap();
+        color_attachment.set_texture(Some(target));
+        color_attachment.set_load_action(MTLLoadAction::Load);
+        color_attachment.set_store_action(MTLStoreAction::Store);
+
+        let x = ((visible.pos.x * scale_factor).max(0.0) as u64).min(target.width());
+        let y = ((visible.pos.y * scale_factor).max(0.0) as u64).min(target.height());
+        let right =
+            (((visible.pos.x + visible.size.x) * scale_factor).max(0.0) as u64).min(target.width());
+        let bottom = (((visible.pos.y + visible.size.y) * scale_factor).max(0.0) as u64)
+            .min(target.height());
+        let uniforms = LayerUniforms::new(opacity, mask, scale_factor);
+
+        let encoder = command_buffer.new_render_command_encoder(&render_pass_descriptor);
+        encoder.set_render_pipeline_state(&pipeline_state);
+        encoder.set_viewport(MTLViewport {
+            originX: region.origin.x as f64,
+            originY: region.origin.y as f64,
+            width: region.size.width as f64,
+            height: region.size.height as f64,
+            znear: 0.0,
+            zfar: 1.0,
+        });
+        encoder.set_scissor_rect(MTLScissorRect {
+            x,
+            y,
+            width: right.saturating_sub(x),
+            height: bottom.saturating_sub(y),
+        });
+        encoder.set_fragment_texture(0, Some(texture));
+        encoder.set_fragment_bytes(
+            0,
+            mem::size_of::<LayerUniforms>() as u64,
+            &uniforms as *const _ as *const _,
+        );
+        encoder.draw_primitives(MTLPrimitiveType::Triangle, 0, 3);
+        encoder.end_encoding();
+        self.frame_stats.draw_calls += 1;
+    }
+
+    /// A pair of textures for blurring a region this size, reused across
+    /// frames while blurs of the same size keep being drawn
+    fn blur_targets(&mut self, width: u64, height: u64) -> [Texture; 2] {
+        let used = self.blur_targets_used.entry((width, height)).or_insert(0);
+        let targets = self.blur_targets.entry((width, height)).or_default();
+        if *used == targets.len() {
+            let descriptor = TextureDescriptor::new();
+            descriptor.set_pixel_format(MTLPixelFormat::BGRA8Unorm);
+            descriptor.set_width(width);
+            descriptor.set_height(height);
+            descriptor.set_usage(
+                MTLTextureUsage::RenderTarget
+                    | MTLTextureUsage::ShaderRead
+                    | MTLTextureUsage::ShaderWrite,
+            );
+            descriptor.set_storage_mode(MTLStorageMode::Private);
+            targets.push([
+                self.device.new_texture(&descriptor),
+                self.device.new_texture(&descriptor),
+            ]);
+            self.frame_stats.offscreen_texture_bytes += width * height * 4 * 2;
+        }
+        let pair = targets[*used].clone();
+        *used += 1;
+        pair
+    }
+
+    /// Run a post-process pass from `source` into `destination`
+    fn encode_post_process_pass(
+        &mut self,
+        command_buffer: &CommandBufferRef,
+        pipeline: &PostProcessPipeline,
+        uniforms: &PostProcessUniforms,
+        source: &TextureRef,
+        destination: &TextureRef,
+    ) {
+        match pipeline {
+            PostProcessPipeline::Fragment(pipeline_state) => {
+                let render_pass_descriptor = RenderPassDescriptor::new();
+                let color_attachment = render_pass_descriptor
+                    .color_attachments()
+                    .object_at(0)
+                    .unwrap();
+                color_attachment.set_texture(Some(destination));
+                color_attachment.set_load_action(MTLLoadAction::DontCare);
+                color_attachment.set_store_action(MTLStoreAction::Store);
+
+                let encoder = command_buffer.new_render_command_encoder(&render_pass_descriptor);
+                encoder.set_render_pipeline_state(pipeline_state);
+                encoder.set_fragment_texture(0, Some(source));
+                encoder.set_fragment_bytes(
+                    0,
+                    mem::size_of::<PostProcessUniforms>() as u64,
+                    uniforms as *const _ as *const _,
+                );
+                encoder.draw_primitives(MTLPrimitiveType::Triangle, 0, 3);
+                encoder.end_encoding();
+            }
+            PostProcessPipeline::Compute(pipeline_state) => {
+                let (width, height) = (destination.width(), destination.height());
+                let threads = MTLSize::new(16, 16, 1);
+                let groups = MTLSize::new(width.div_ceil(16), height.div_ceil(16), 1);
+
+                let encoder = command_buffer.new_compute_command_encoder();
+                encoder.set_compute_pipeline_state(pipeline_state);
+                encoder.set_texture(0, Some(source));
+                encoder.set_texture(1, Some(destination));
+                encoder.set_bytes(
+                    0,
+                    mem::size_of::<PostProcessUniforms>() as u64,
+                    uniforms as *const _ as *const _,
+                );
+                encoder.dispatch_thread_groups(groups, threads);
+                encoder.end_encoding();
+            }
+        }
+        self.frame_stats.draw_calls += 1;
+    }
+
+    /// Draw a texture stretched over the whole drawable at `opacity`
+    ///
+    /// Colors in the texture are taken to have premultiplied alpha, as
+    /// opaque content trivially does.
+    pub fn composite_texture(
         &mut self,
-        draw_list: &DrawList,
         command_buffer: &CommandBufferRef,
         drawable: &metal::MetalDrawableRef,
-        screen_size: (f32, f32),
-        scale_factor: f32,
-        text_system: &mut TextSystem,
+        texture: &TextureRef,
         load_action: metal::MTLLoadAction,
         clear_color: metal::MTLClearColor,
+        opacity: f32,
     ) {
-        let _render_span = info_span!(
-            "metal_render_draw_list",
-            commands = draw_list.commands().len()
-        )
-        .entered();
-        // Create render pass descriptor
+        let Some(composite_pipeline_state) = self.composite_pipeline_state() else {
+            return;
+        };
+
         let render_pass_descriptor = RenderPassDescriptor::new();
         let color_attachment = render_pass_descriptor
             .color_attachments()
@@ -836,20 +2764,204 @@ impl MetalRenderer {
         color_attachment.set_clear_color(clear_color);
         color_attachment.set_store_action(MTLStoreAction::Store);
 
-        // Create render encoder
         let encoder = command_buffer.new_render_command_encoder(&render_pass_descriptor);
-
-        // Render the draw list
-        self.render_draw_list_with_encoder(
-            draw_list,
-            encoder,
-            screen_size,
-            scale_factor,
-            text_system,
+        encoder.set_render_pipeline_state(&composite_pipeline_state);
+        encoder.set_fragment_texture(0, Some(texture));
+        encoder.set_fragment_bytes(
+            0,
+            mem::size_of::<f32>() as u64,
+            &opacity as *const _ as *const _,
         );
-
-        // End encoding
+        encoder.draw_primitives(MTLPrimitiveType::Triangle, 0, 3);
         encoder.end_encoding();
+        self.frame_stats.draw_calls += 1;
+    }
+
+    /// At least `count` offscreen textures for post-process passes to run
+    /// between, recreated when the drawable's size changes
+    fn post_process_targets(&mut self, width: u64, height: u64, count: usize) -> Vec<Texture> {
+        let fits = self
+            .post_process_targets
+            .first()
+            .is_some_and(|target| target.width() == width && target.height() == height);
+        if !fits {
+            self.post_process_targets.clear();
+        }
+        if self.post_process_targets.len() < count {
+            let descriptor = TextureDescriptor::new();
+            descriptor.set_pixel_format(MTLPixelFormat::BGRA8Unorm);
+            descriptor.set_width(width);
+            descriptor.set_height(height);
+            descriptor.set_usage(
+                MTLTextureUsage::RenderTarget
+                    | MTLTextureUsage::ShaderRead
+                    | MTLTextureUsage::ShaderWrite,
+            );
+            descriptor.set_storage_mode(MTLStorageMode::Private);
+            let missing = count - self.post_process_targets.len();
+            self.post_process_targets
+                .extend((0..missing).map(|_| self.device.new_texture(&descriptor)));
+            self.frame_stats.offscreen_texture_bytes += width * height * 4 * missing as u64;
+        }
+        self.post_process_targets.clone()
+    }
+
+    /// The compiled pipeline for a post-process pass, compiling it on first
+    /// use
+    ///
+    /// A pass that fails to compile is logged once and skipped from then on.
+    fn post_process_pipeline(&mut self, pass: &dyn PostProcess) -> Option<PostProcessPipeline> {
+        if let Some(pipeline) = self.post_process_pipelines.get(pass.key()) {
+            return pipeline.clone();
+        }
+
+        let start = Instant::now();
+        let pipeline = match self.compile_post_process(pass.shader()) {
+            Ok(pipeline) => {
+                info!(
+                    "Post-process pass {} compiled in {:?}",
+                    pass.key(),
+                    start.elapsed()
+                );
+                Some(pipeline)
+            }
+            Err(e) => {
+                error!("Failed to compile post-process pass {}: {}", pass.key(), e);
+                None
+            }
+        };
+        self.frame_stats.pipelines_compiled += 1;
+        self.post_process_pipelines
+            .insert(pass.key().to_string(), pipeline.clone());
+        pipeline
+    }
+
+    fn compile_post_process(&self, shader: PostProcessShader) -> SolResult<PostProcessPipeline> {
+        let options = metal::CompileOptions::new();
+        match shader {
+            PostProcessShader::Fragment(source) => {
+                let source = format!("{POST_PROCESS_PRELUDE}\n{source}\n{POST_PROCESS_FRAGMENT}");
+                let library = self
+                    .device
+                    .new_library_with_source(&source, &options)
+                    .map_err(SolError::ShaderCompilation)?;
+                self.create_fullscreen_pipeline_state(
+                    &library,
+                    "post_process_fragment",
+                    false,
+                    "post-process",
+                )
+                .map(PostProcessPipeline::Fragment)
+            }
+            PostProcessShader::Compute(source) => {
+                let source = format!("{POST_PROCESS_PRELUDE}\n{source}");
+                let library = self
+                    .device
+                    .new_library_with_source(&source, &options)
+                    .map_err(SolError::ShaderCompilation)?;
+                let function = get_shader_function(&library, "post_process")?;
+                self.device
+                    .new_compute_pipeline_state_with_function(&function)
+                    .map(PostProcessPipeline::Compute)
+                    .map_err(|message| SolError::PipelineCreation {
+                        pipeline: "post-process",
+                        message,
+                    })
+            }
+        }
+    }
+
+    /// The pipeline compositing post-processed layers, compiled on first use
+    fn composite_pipeline_state(&mut self) -> Option<RenderPipelineState> {
+        if self.composite_pipeline_state.is_none() {
+            let options = metal::CompileOptions::new();
+            let source = format!("{POST_PROCESS_PRELUDE}\n{COMPOSITE_SHADER}");
+            let pipeline_state = self
+                .device
+                .new_library_with_source(&source, &options)
+                .map_err(SolError::ShaderCompilation)
+                .and_then(|library| {
+                    self.create_fullscreen_pipeline_state(
+                        &library,
+                        "composite_fragment",
+                        true,
+                        "composite",
+                    )
+                });
+            match pipeline_state {
+                Ok(pipeline_state) => {
+                    self.frame_stats.pipelines_compiled += 1;
+                    self.composite_pipeline_state = Some(pipeline_state);
+                }
+                Err(e) => error!("Failed to create composite pipeline: {}", e),
+            }
+        }
+        self.composite_pipeline_state.clone()
+    }
+
+    /// The pipeline drawing blurred regions back, compiled on first use
+    fn blur_composite_pipeline_state(&mut self) -> Option<RenderPipelineState> {
+        if self.blur_composite_pipeline_state.is_none() {
+            let options = metal::CompileOptions::new();
+            let source = format!("{POST_PROCESS_PRELUDE}\n{BLUR_COMPOSITE_SHADER}");
+            let pipeline_state = self
+                .device
+                .new_library_with_source(&source, &options)
+                .map_err(SolError::ShaderCompilation)
+                .and_then(|library| {
+                    self.create_fullscreen_pipeline_state(
+                        &library,
+                        "blur_composite_fragment",
+                        true,
+                        "blur composite",
+                    )
+                });
+            match pipeline_state {
+                Ok(pipeline_state) => {
+                    self.frame_stats.pipelines_compiled += 1;
+                    self.blur_composite_pipeline_state = Some(pipeline_state);
+                }
+                Err(e) => error!("Failed to create blur composite pipeline: {}", e),
+            }
+        }
+        self.blur_composite_pipeline_state.clone()
+    }
+
+    /// Create a pipeline drawing `fragment` over a fullscreen triangle
+    ///
+    /// With `blend`, premultiplied colors are composited over the target;
+    /// without it they replace it.
+    fn create_fullscreen_pipeline_state(
+        &self,
+        library: &Library,
+        fragment: &str,
+        blend: bool,
+        pipeline: &'static str,
+    ) -> SolResult<RenderPipelineState> {
+        let vertex_function = get_shader_function(library, "post_process_vertex")?;
+        let fragment_function = get_shader_function(library, fragment)?;
+
+        let pipeline_descriptor = RenderPipelineDescriptor::new();
+        pipeline_descriptor.set_vertex_function(Some(&vertex_function));
+        pipeline_descriptor.set_fragment_function(Some(&fragment_function));
+
+        let attachment = pipeline_descriptor
+            .color_attachments()
+            .object_at(0)
+            .unwrap();
+        attachment.set_pixel_format(metal::MTLPixelFormat::BGRA8Unorm);
+        if blend {
+            attachment.set_blending_enabled(true);
+            attachment.set_source_rgb_blend_factor(metal::MTLBlendFactor::One);
+            attachment.set_destination_rgb_blend_factor(metal::MTLBlendFactor::OneMinusSourceAlpha);
+            attachment.set_source_alpha_blend_factor(metal::MTLBlendFactor::One);
+            attachment
+                .set_destination_alpha_blend_factor(metal::MTLBlendFactor::OneMinusSourceAlpha);
+        }
+
+        self.device
+            .new_render_pipeline_state(&pipeline_descriptor)
+            .map_err(|message| SolError::PipelineCreation { pipeline, message })
     }
 
     /// Draw a fullscreen quad with a custom fragment shader
@@ -940,6 +3052,7 @@ impl MetalRenderer {
                 return;
             }
         };
+        self.frame_stats.pipelines_compiled += 1;
 
         // Create uniforms
         #[repr(C)]
@@ -960,6 +3073,8 @@ impl MetalRenderer {
             std::mem::size_of::<Uniforms>() as u64,
             metal::MTLResourceOptions::CPUCacheModeDefaultCache,
         );
+        self.frame_stats.vertex_buffer_bytes += uniforms_buffer.length();
+        self.frame_stats.draw_calls += 1;
 
         // Create render pass descriptor
         let render_pass_descriptor = RenderPassDescriptor::new();
@@ -982,10 +3097,520 @@ impl MetalRenderer {
     }
 }
 
+/// A drawable for [`MetalRenderer`] to render into, and the command buffer
+/// encoding the frame
+#[derive(Clone, Copy)]
+pub struct MetalTarget<'a> {
+    pub command_buffer: &'a CommandBufferRef,
+    pub drawable: &'a metal::MetalDrawableRef,
+}
+
+/// Load action and clear color for a render pass that clears to `clear`, or
+/// keeps the target's contents
+pub(crate) fn load_action(clear: Option<Color>) -> (MTLLoadAction, metal::MTLClearColor) {
+    match clear {
+        Some(color) => (
+            MTLLoadAction::Clear,
+            metal::MTLClearColor::new(
+                color.red as f64,
+                color.green as f64,
+                color.blue as f64,
+                color.alpha as f64,
+            ),
+        ),
+        None => (
+            MTLLoadAction::Load,
+            metal::MTLClearColor::new(0.0, 0.0, 0.0, 0.0),
+        ),
+    }
+}
+
+impl Renderer for MetalRenderer {
+    type Target<'a> = MetalTarget<'a>;
+
+    fn initialize(&mut self) -> SolResult<()> {
+        // Create shader library
+        let start = Instant::now();
+        let library = self.compile_shaders()?;
+        info!("Shaders compiled in {:?}", start.elapsed());
+
+        // Create pipeline states
+        self.pipeline_state = Some(self.create_pipeline_state(&library, false)?);
+        self.cutout_pipeline_state = Some(self.create_pipeline_state(&library, true)?);
+        self.text_pipeline_state =
+            Some(self.create_text_pipeline_state(&library, "text_fragment_main", "text")?);
+        self.text_distance_field_pipeline_state = Some(self.create_text_pipeline_state(
+            &library,
+            "text_distance_field_fragment_main",
+            "text distance field",
+        )?);
+        self.text_gradient_pipeline_state = Some(self.create_text_pipeline_state(
+            &library,
+            "text_gradient_fragment_main",
+            "text gradient",
+        )?);
+        self.frame_pipeline_state = Some(self.create_frame_pipeline_state(&library)?);
+        self.shadow_mask_pipeline_state = Some(self.create_shadow_mask_pipeline_state(&library)?);
+        self.blur_pipeline_state = Some(self.create_blur_pipeline_state(&library, false)?);
+        self.color_blur_pipeline_state = Some(self.create_blur_pipeline_state(&library, true)?);
+        self.image_pipeline_state = Some(self.create_image_pipeline_state(&library)?);
+
+        Ok(())
+    }
+
+    /// Reset the per-frame statistics; call before rendering each frame
+    ///
+    /// Also drops shadow masks and image textures that haven't been drawn
+    /// for a while, and blur textures the last frame didn't need.
+    fn begin_frame(&mut self) {
+        self.frame_stats = RendererStats::default();
+        self.reads_target = false;
+        self.frame_count += 1;
+        let frame_count = self.frame_count;
+        self.shadow_cache
+            .retain(|_, shadow| frame_count - shadow.last_used <= SHADOW_CACHE_MAX_AGE);
+        self.image_cache
+            .retain(|_, image| frame_count - image.last_used <= IMAGE_CACHE_MAX_AGE);
+        if let Some(atlas) = &mut self.image_atlas {
+            atlas.evict_unused(frame_count, IMAGE_CACHE_MAX_AGE);
+        }
+        let used = mem::take(&mut self.blur_targets_used);
+        self.blur_targets.retain(|size, targets| {
+            targets.truncate(used.get(size).copied().unwrap_or(0));
+            !targets.is_empty()
+        });
+    }
+
+    /// Render a draw list for the layer system
+    ///
+    /// Everything in the list is drawn at the composite's opacity; the clear
+    /// color is not.
+    fn render_draw_list(
+        &mut self,
+        draw_list: &DrawList,
+        target: &mut MetalTarget<'_>,
+        composite: Composite,
+        screen_size: Vec2,
+        scale_factor: f32,
+        text_system: &mut TextSystem,
+    ) {
+        let _render_span = info_span!(
+            "metal_render_draw_list",
+            commands = draw_list.commands().len()
+        )
+        .entered();
+        let (load_action, clear_color) = load_action(composite.clear);
+        self.encode_draw_list(
+            draw_list,
+            target.command_buffer,
+            target.drawable.texture(),
+            (screen_size.x, screen_size.y),
+            scale_factor,
+            text_system,
+            load_action,
+            clear_color,
+            composite.opacity,
+        );
+    }
+
+    /// Render a draw list through a layer's post-process passes
+    ///
+    /// The list is drawn into an offscreen texture, each active pass reads
+    /// the previous pass's output, and the result is composited onto the
+    /// drawable at the composite's opacity, so the layer fades as one
+    /// flattened image. With no active passes the list is drawn straight to
+    /// the drawable, as by [`render_draw_list`](Self::render_draw_list).
+    fn render_post_processed(
+        &mut self,
+        draw_list: &DrawList,
+        target: &mut MetalTarget<'_>,
+        composite: Composite,
+        passes: &[Rc<dyn PostProcess>],
+        frame: FrameInfo,
+        text_system: &mut TextSystem,
+    ) {
+        let FrameInfo {
+            size: screen_size,
+            scale_factor,
+            elapsed_time: time,
+        } = frame;
+        let passes: Vec<(PostProcessPipeline, [f32; 4])> = passes
+            .iter()
+            .filter(|pass| pass.is_active())
+            .filter_map(|pass| Some((self.post_process_pipeline(pass.as_ref())?, pass.params())))
+            .collect();
+        if passes.is_empty() || self.composite_pipeline_state().is_none() {
+            self.render_draw_list(
+                draw_list,
+                target,
+                composite,
+                screen_size,
+                scale_factor,
+                text_system,
+            );
+            return;
+        }
+        let MetalTarget {
+            command_buffer,
+            drawable,
+        } = *target;
+        let (load_action, clear_color) = load_action(composite.clear);
+        let _post_process_span =
+            info_span!("metal_render_post_processed", passes = passes.len()).entered();
+
+        let (width, height) = (drawable.texture().width(), drawable.texture().height());
+        let desc = TextureDesc::new(width, height, TextureFormat::Bgra8);
+
+        // Each pass reads the previous pass's output; the graph lets outputs
+        // that are no longer needed share textures
+        let mut graph = FrameGraph::new();
+        let output = graph.import_texture("drawable", desc);
+        let mut current = graph.create_texture("content", desc);
+        graph.add_pass("content", &[], &[current], PostProcessStep::Content);
+        for (index, (pipeline, params)) in passes.into_iter().enumerate() {
+            let destination = graph.create_texture(format!("pass {index} output"), desc);
+            graph.add_pass(
+                format!("pass {index}"),
+                &[current],
+                &[destination],
+                PostProcessStep::Pass(pipeline, params),
+            );
+            current = destination;
+        }
+        let composite_step = PostProcessStep::Composite;
+        graph.add_pass("composite", &[current], &[output], composite_step);
+        let graph = match graph.compile() {
+            Ok(graph) => graph,
+            Err(e) => {
+                error!("Skipping post-processed layer: {}", e);
+                return;
+            }
+        };
+
+        let targets = self.post_process_targets(width, height, graph.transient_textures().len());
+        let texture = |id| {
+            let slot = graph.slot(id).expect("post-process textures are transient");
+            &targets[slot]
+        };
+        for pass in graph.passes() {
+            match pass.data {
+                PostProcessStep::Content => {
+                    // Draw the content at full opacity; the layer's opacity
+                    // is applied when compositing. Blending over transparent
+                    // black leaves colors premultiplied, which the passes and
+                    // the composite expect.
+                    self.encode_draw_list(
+                        draw_list,
+                        command_buffer,
+                        texture(pass.writes[0]),
+                        (screen_size.x, screen_size.y),
+                        scale_factor,
+                        text_system,
+                        MTLLoadAction::Clear,
+                        metal::MTLClearColor::new(0.0, 0.0, 0.0, 0.0),
+                        1.0,
+                    );
+                }
+                PostProcessStep::Pass(pipeline, params) => {
+                    let uniforms = PostProcessUniforms {
+                        resolution: [width as f32, height as f32],
+                        time,
+                        scale_factor,
+                        params: *params,
+                    };
+                    self.encode_post_process_pass(
+                        command_buffer,
+                        pipeline,
+                        &uniforms,
+                        texture(pass.reads[0]),
+                        texture(pass.writes[0]),
+                    );
+                }
+                PostProcessStep::Composite => {
+                    // Composite the result over the layers below
+                    self.composite_texture(
+                        command_buffer,
+                        drawable,
+                        texture(pass.reads[0]),
+                        load_action,
+                        clear_color,
+                        composite.opacity,
+                    );
+                }
+            }
+        }
+        self.last_frame_graph = Some(graph);
+    }
+
+    /// Render `bounds` of a draw list into an offscreen texture and read back the pixels
+    ///
+    /// This waits for the GPU to finish, so it is meant for occasional captures
+    /// (drag previews, sharing, visual tests) rather than every frame.
+    fn capture(
+        &mut self,
+        draw_list: &DrawList,
+        bounds: Rect,
+        scale_factor: f32,
+        text_system: &mut TextSystem,
+    ) -> SolResult<RgbaImage> {
+        let _capture_span = info_span!("metal_capture_draw_list").entered();
+
+        let width = (bounds.size.x * scale_factor).round() as u64;
+        let height = (bounds.size.y * scale_factor).round() as u64;
+        if width == 0 || height == 0 {
+            return Err(SolError::Image(
+                "Cannot capture an empty region".to_string(),
+            ));
+        }
+
+        let descriptor = TextureDescriptor::new();
+        descriptor.set_pixel_format(MTLPixelFormat::BGRA8Unorm);
+        descriptor.set_width(width);
+        descriptor.set_height(height);
+        descriptor.set_usage(MTLTextureUsage::RenderTarget | MTLTextureUsage::ShaderRead);
+        descriptor.set_storage_mode(MTLStorageMode::Managed);
+        let texture = self.device.new_texture(&descriptor);
+        self.frame_stats.offscreen_texture_bytes += width * height * 4;
+
+        let command_queue = self
+            .capture_queue
+            .get_or_insert_with(|| self.device.new_command_queue())
+            .clone();
+        let command_buffer = command_queue.new_command_buffer();
+
+        // Render with the capture region's origin at the texture origin
+        let local = draw_list.translated(-bounds.pos);
+        self.encode_draw_list(
+            &local,
+            command_buffer,
+            &texture,
+            (bounds.size.x, bounds.size.y),
+            scale_factor,
+            text_system,
+            MTLLoadAction::Clear,
+            metal::MTLClearColor::new(0.0, 0.0, 0.0, 0.0),
+            1.0,
+        );
+
+        // Make the rendered pixels visible to the CPU
+        let blit = command_buffer.new_blit_command_encoder();
+        blit.synchronize_resource(&texture);
+        blit.end_encoding();
+
+        command_buffer.commit();
+        command_buffer.wait_until_completed();
+
+        let bytes_per_row = width * 4;
+        let mut pixels = vec![0u8; (bytes_per_row * height) as usize];
+        texture.get_bytes(
+            pixels.as_mut_ptr() as *mut std::ffi::c_void,
+            bytes_per_row,
+            MTLRegion::new_2d(0, 0, width, height),
+            0,
+        );
+
+        // BGRA -> RGBA
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+
+        RgbaImage::from_raw(width as u32, height as u32, pixels)
+            .ok_or_else(|| SolError::Image("Captured texture has an unexpected size".to_string()))
+    }
+
+    /// GPU resource usage for the frame rendered since `begin_frame`
+    fn stats(&self, text_system: &TextSystem) -> RendererStats {
+        let atlas = text_system.glyph_atlas();
+        let image_bytes: u64 = self.image_cache.values().map(CachedImage::bytes).sum();
+        let atlas_bytes = self.image_atlas.as_ref().map_or(0, ImageAtlas::bytes);
+        RendererStats {
+            atlas_pages: atlas.page_count(),
+            atlas_glyphs: atlas.glyph_count(),
+            atlas_used_pixels: atlas.used_pixels(),
+            atlas_total_pixels: atlas.total_pixels(),
+            // R8 texture, one byte per pixel
+            atlas_bytes: atlas.total_pixels(),
+            pipeline_count: [
+                &self.pipeline_state,
+                &self.text_pipeline_state,
+                &self.text_distance_field_pipeline_state,
+                &self.text_gradient_pipeline_state,
+                &self.frame_pipeline_state,
+                &self.cutout_pipeline_state,
+                &self.shadow_mask_pipeline_state,
+                &self.image_pipeline_state,
+            ]
+            .iter()
+            .filter(|p| p.is_some())
+            .count()
+                + usize::from(self.blur_pipeline_state.is_some())
+                + usize::from(self.color_blur_pipeline_state.is_some()),
+            shadow_cache_bytes: self.shadow_cache.keys().map(ShadowKey::mask_bytes).sum(),
+            image_cache_bytes: image_bytes + atlas_bytes,
+            ..self.frame_stats.clone()
+        }
+    }
+
+    fn metal<'a>(
+        &'a mut self,
+        target: &'a MetalTarget<'_>,
+    ) -> Option<(&'a mut MetalRenderer, MetalTarget<'a>)> {
+        Some((self, *target))
+    }
+}
+
+/// How the shaders identify a [`CornerStyle`]
+fn corner_style_index(style: CornerStyle) -> u32 {
+    match style {
+        CornerStyle::Circular => 0,
+        CornerStyle::Continuous => 1,
+    }
+}
+
+/// The gradient to fill text with, if its fill isn't a plain color
+fn gradient_fill(style: &TextStyle) -> Option<&Fill> {
+    style
+        .fill
+        .as_ref()
+        .filter(|fill| !matches!(fill, Fill::Solid(_)))
+}
+
+/// Index of the `PopBlur` ending the blur whose content starts at `start`,
+/// or the end of the list if it's never popped
+fn matching_pop_blur(commands: &[DrawCommand], start: usize) -> usize {
+    let mut depth = 0;
+    for (index, command) in commands.iter().enumerate().skip(start) {
+        match command {
+            DrawCommand::PushBlur { .. } => depth += 1,
+            DrawCommand::PopBlur if depth == 0 => return index,
+            DrawCommand::PopBlur => depth -= 1,
+            _ => {}
+        }
+    }
+    commands.len()
+}
+
+/// The clip rect and rounded clip in effect after pushing `clips`, as the
+/// render pass tracks them
+fn clip_state(clips: &[DrawCommand]) -> (Option<Rect>, Option<(Rect, Corners)>) {
+    let mut state: (Option<Rect>, Option<(Rect, Corners)>) = (None, None);
+    for command in clips {
+        match command {
+            DrawCommand::PushClip { rect } => state.0 = Some(*rect),
+            DrawCommand::PushRoundedClip { rect, corner_radii } => {
+                let clip = match state.0 {
+                    Some(parent) => parent
+                        .intersect(rect)
+                        .unwrap_or(Rect::new(rect.pos.x, rect.pos.y, 0.0, 0.0)),
+                    None => *rect,
+                };
+                state = (Some(clip), Some((*rect, *corner_radii)));
+            }
+            _ => {}
+        }
+    }
+    state
+}
+
+/// Gaussian standard deviation and kernel radius, in pixels, for a blur of
+/// `radius` points
+fn blur_kernel(radius: f32, scale_factor: f32) -> (f32, u32) {
+    let sigma = (radius * scale_factor).clamp(0.5, MAX_BLUR_SIGMA);
+    (sigma, (sigma * 3.0).ceil() as u32)
+}
+
+/// Pixels of `target` a blur over `rect` reads and writes: the rect grown
+/// by the kernel radius, within the target
+fn blur_region(
+    rect: &Rect,
+    kernel_radius: u32,
+    scale_factor: f32,
+    target: &TextureRef,
+) -> Option<MTLRegion> {
+    let padding = kernel_radius as f32;
+    let left = ((rect.pos.x * scale_factor).floor() - padding).max(0.0) as u64;
+    let top = ((rect.pos.y * scale_factor).floor() - padding).max(0.0) as u64;
+    let right = (((rect.pos.x + rect.size.x) * scale_factor).ceil() + padding).max(0.0) as u64;
+    let bottom = (((rect.pos.y + rect.size.y) * scale_factor).ceil() + padding).max(0.0) as u64;
+    let right = right.min(target.width());
+    let bottom = bottom.min(target.height());
+    if right <= left || bottom <= top {
+        return None;
+    }
+    Some(MTLRegion::new_2d(left, top, right - left, bottom - top))
+}
+
+/// Look up a function in a compiled shader library
+fn get_shader_function(library: &Library, name: &str) -> SolResult<Function> {
+    library
+        .get_function(name, None)
+        .map_err(|message| SolError::MissingShaderFunction {
+            name: name.to_string(),
+            message,
+        })
+}
+
+/// Quad for a blurred shadow mask: the frame's rect moved by the shadow
+/// offset and padded by the blur radius, tinted with the shadow color
+fn blurred_shadow_to_vertices(
+    rect: &Rect,
+    shadow: &Shadow,
+    screen_size: (f32, f32),
+    scale_factor: f32,
+) -> [Vertex; 6] {
+    let physical_width = screen_size.0 * scale_factor;
+    let physical_height = screen_size.1 * scale_factor;
+    let left = rect.pos.x + shadow.offset.x - shadow.blur;
+    let top = rect.pos.y + shadow.offset.y - shadow.blur;
+    let right = rect.pos.x + rect.size.x + shadow.offset.x + shadow.blur;
+    let bottom = rect.pos.y + rect.size.y + shadow.offset.y + shadow.blur;
+    let x1 = (left * scale_factor / physical_width) * 2.0 - 1.0;
+    let y1 = 1.0 - (top * scale_factor / physical_height) * 2.0;
+    let x2 = (right * scale_factor / physical_width) * 2.0 - 1.0;
+    let y2 = 1.0 - (bottom * scale_factor / physical_height) * 2.0;
+
+    let color = &shadow.color;
+    let color_array = [color.red, color.green, color.blue, color.alpha];
+
+    [
+        Vertex {
+            position: [x1, y1],
+            color: color_array,
+            tex_coord: [0.0, 0.0],
+        },
+        Vertex {
+            position: [x2, y1],
+            color: color_array,
+            tex_coord: [1.0, 0.0],
+        },
+        Vertex {
+            position: [x1, y2],
+            color: color_array,
+            tex_coord: [0.0, 1.0],
+        },
+        Vertex {
+            position: [x2, y1],
+            color: color_array,
+            tex_coord: [1.0, 0.0],
+        },
+        Vertex {
+            position: [x2, y2],
+            color: color_array,
+            tex_coord: [1.0, 1.0],
+        },
+        Vertex {
+            position: [x1, y2],
+            color: color_array,
+            tex_coord: [0.0, 1.0],
+        },
+    ]
+}
+
 /// Static helper function for frame_to_vertices (used in closures)
 fn frame_to_vertices_static(
     rect: &Rect,
     style: &ElementStyle,
+    antialias: bool,
     screen_size: (f32, f32),
     scale_factor: f32,
 ) -> ([Vertex; 6], FrameUniforms) {
@@ -1066,36 +3691,20 @@ fn frame_to_vertices_static(
             style.corner_radii.bottom_right,
             style.corner_radii.bottom_left,
         ],
-        border_width: style.border_width,
-        fill_type: match &style.fill {
-            Fill::Solid(_) => 0,
-            Fill::LinearGradient { .. } => 1,
-            Fill::RadialGradient { .. } => 2,
-        },
-        gradient_angle: if let Fill::LinearGradient { angle, .. } = &style.fill {
-            *angle
-        } else {
-            0.0
-        },
-        _padding: 0.0,
-        color1: match &style.fill {
-            Fill::Solid(color) => [color.red, color.green, color.blue, color.alpha],
-            Fill::LinearGradient { start, .. } => [start.red, start.green, start.blue, start.alpha],
-            Fill::RadialGradient { center, .. } => {
-                [center.red, center.green, center.blue, center.alpha]
-            }
-        },
-        color2: match &style.fill {
-            Fill::Solid(color) => [color.red, color.green, color.blue, color.alpha],
-            Fill::LinearGradient { end, .. } => [end.red, end.green, end.blue, end.alpha],
-            Fill::RadialGradient { edge, .. } => [edge.red, edge.green, edge.blue, edge.alpha],
-        },
-        border_color: [
-            style.border_color.red,
-            style.border_color.green,
-            style.border_color.blue,
-            style.border_color.alpha,
+        border_widths: [
+            style.border_widths.top,
+            style.border_widths.right,
+            style.border_widths.bottom,
+            style.border_widths.left,
         ],
+        fill: GradientUniforms::new(&style.fill),
+        border_colors: [
+            style.border_colors.top,
+            style.border_colors.right,
+            style.border_colors.bottom,
+            style.border_colors.left,
+        ]
+        .map(|color| [color.red, color.green, color.blue, color.alpha]),
         shadow_offset: if let Some(shadow) = &style.shadow {
             [shadow.offset.x, shadow.offset.y]
         } else {
@@ -1106,7 +3715,7 @@ fn frame_to_vertices_static(
         } else {
             0.0
         },
-        _padding2: 0.0,
+        corner_style: corner_style_index(style.corner_style),
         shadow_color: if let Some(shadow) = &style.shadow {
             [
                 shadow.color.red,
@@ -1117,7 +3726,122 @@ fn frame_to_vertices_static(
         } else {
             [0.0, 0.0, 0.0, 0.0]
         },
+        antialias: if antialias { 1.0 } else { 0.0 },
+        _padding: [0.0; 3],
     };
 
     (vertices, uniforms)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::colors::TRANSPARENT;
+    use glam::Vec2;
+
+    #[test]
+    fn test_corner_style_index_matches_shaders() {
+        // sdFrame treats 0 as circular and 1 as continuous
+        assert_eq!(corner_style_index(CornerStyle::Circular), 0);
+        assert_eq!(corner_style_index(CornerStyle::Continuous), 1);
+        assert_eq!(corner_style_index(CornerStyle::default()), 0);
+    }
+
+    fn shadowed(blur: f32) -> ElementStyle {
+        ElementStyle::new().with_corner_radius(8.0).with_shadow(
+            Vec2::new(0.0, 4.0),
+            blur,
+            BLACK.with_alpha(0.5),
+        )
+    }
+
+    #[test]
+    fn test_shadow_key_in_physical_pixels() {
+        let rect = Rect::new(10.0, 10.0, 100.0, 50.0);
+        let key = ShadowKey::for_frame(&rect, &shadowed(20.0), 2.0, Some(8.0)).unwrap();
+        assert_eq!((key.width, key.height), (200, 100));
+        assert_eq!(key.radii, [16; 4]);
+        assert_eq!(key.blur, 40);
+        assert_eq!(key.mask_size(), (280, 180));
+        assert_eq!(key.mask_bytes(), 280 * 180);
+    }
+
+    #[test]
+    fn test_shadow_key_ignores_position_and_offset() {
+        let a = ShadowKey::for_frame(
+            &Rect::new(0.0, 0.0, 100.0, 50.0),
+            &shadowed(20.0),
+            1.0,
+            Some(8.0),
+        );
+        let moved = ElementStyle::new().with_corner_radius(8.0).with_shadow(
+            Vec2::new(12.0, -3.0),
+            20.0,
+            BLACK,
+        );
+        let b = ShadowKey::for_frame(&Rect::new(300.0, 40.0, 100.0, 50.0), &moved, 1.0, Some(8.0));
+        assert!(a.is_some());
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_shadow_key_skips_small_and_hidden_shadows() {
+        let rect = Rect::new(0.0, 0.0, 100.0, 50.0);
+        // At or under the threshold, the SDF shadow is used
+        assert_eq!(
+            ShadowKey::for_frame(&rect, &shadowed(8.0), 1.0, Some(8.0)),
+            None
+        );
+        // Blurred shadows turned off
+        assert_eq!(
+            ShadowKey::for_frame(&rect, &shadowed(20.0), 1.0, None),
+            None
+        );
+        // Transparent shadows and frames without one
+        let clear = ElementStyle::new().with_shadow(Vec2::ZERO, 20.0, TRANSPARENT);
+        assert_eq!(ShadowKey::for_frame(&rect, &clear, 1.0, Some(8.0)), None);
+        assert_eq!(
+            ShadowKey::for_frame(&rect, &ElementStyle::new(), 1.0, Some(8.0)),
+            None
+        );
+        // Empty frames and masks too large for a texture
+        let empty = Rect::new(0.0, 0.0, 0.0, 50.0);
+        assert_eq!(
+            ShadowKey::for_frame(&empty, &shadowed(20.0), 1.0, Some(8.0)),
+            None
+        );
+        let huge = Rect::new(0.0, 0.0, 4090.0, 50.0);
+        assert_eq!(
+            ShadowKey::for_frame(&huge, &shadowed(20.0), 1.0, Some(8.0)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_gradient_fill_skips_solid_fills() {
+        let style = TextStyle::default();
+        assert!(gradient_fill(&style).is_none());
+        assert!(gradient_fill(&style.clone().with_fill(Fill::Solid(BLACK))).is_none());
+
+        let gradient = style.with_linear_gradient(BLACK, WHITE, 90.0);
+        assert_eq!(
+            gradient_fill(&gradient),
+            Some(&Fill::linear_gradient(BLACK, WHITE, 90.0))
+        );
+    }
+
+    #[test]
+    fn test_text_gradient_uniforms_span_bounds_in_pixels() {
+        let fill = Fill::linear_gradient(BLACK, WHITE, 0.0);
+        let bounds = Rect::new(10.0, 20.0, 100.0, 40.0);
+        let uniforms = TextGradientUniforms::new(&fill, bounds, 2.0, true);
+        assert_eq!(uniforms.center, [120.0, 80.0]);
+        assert_eq!(uniforms.half_size, [100.0, 40.0]);
+        assert_eq!(uniforms.distance_field, 1);
+        assert_eq!(uniforms.fill.kind, 1);
+        assert_eq!(uniforms.fill.stop_count, 2);
+
+        let bitmap = TextGradientUniforms::new(&fill, bounds, 1.0, false);
+        assert_eq!(bitmap.distance_field, 0);
+    }
+}
@@ -0,0 +1,15 @@
+//! The system time zone, through `CFTimeZone`
+
+use core_foundation::date::CFDate;
+use core_foundation::timezone::CFTimeZone;
+
+/// Seconds between the Unix epoch and Core Foundation's reference date,
+/// 2001-01-01 00:00 UTC
+const UNIX_TO_CF_ABSOLUTE_TIME: f64 = 978_307_200.0;
+
+/// Seconds the system time zone is ahead of UTC at `secs` since the Unix
+/// epoch, including daylight saving time
+pub fn local_utc_offset(secs: i64) -> i64 {
+    let date = CFDate::new(secs as f64 - UNIX_TO_CF_ABSOLUTE_TIME);
+    CFTimeZone::system().seconds_from_gmt(date) as i64
+}
@@ -4,7 +4,18 @@ use cocoa::{
 };
 use core_graphics::geometry::CGSize;
 
-use crate::layer::{InputEvent, Key, Modifiers, MouseButton};
+use super::cursor::{is_cursor_pinned, set_cursor_pinned, set_cursor_position};
+use super::display::{Display, DisplayId, display_id, screen_for_display};
+use crate::{
+    geometry::Rect,
+    interaction::{
+        coalesce::{CoalescedEvent, coalesce_pointer_motion},
+        pointer_lock::pointer_lock_owner,
+        window_chrome::{WindowChrome, WindowEdge, window_chrome_at},
+    },
+    layer::{InputEvent, Key, Modifiers, MouseButton, ScrollPhase, TabletData},
+    vibrancy::MaterialRegion,
+};
 use glam::Vec2;
 use metal::MetalLayer;
 use objc::{
@@ -14,13 +25,133 @@ use objc::{
     runtime::{BOOL, Class, Object, Sel},
     sel, sel_impl,
 };
-use std::{cell::RefCell, ffi::c_void, ptr, sync::Arc};
+use std::{
+    cell::{Cell, RefCell},
+    ffi::c_void,
+    ptr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 unsafe fn ns_string(string: &str) -> id {
     let str: id = unsafe { NSString::alloc(nil).init_str(string) };
     unsafe { msg_send![str, autorelease] }
 }
 
+/// Size of a window's content view, which includes the titlebar area when
+/// the titlebar is hidden
+unsafe fn content_size(window: *mut Object) -> NSSize {
+    let content_view: *mut Object = unsafe { msg_send![window, contentView] };
+    let bounds: NSRect = unsafe { msg_send![content_view, bounds] };
+    bounds.size
+}
+
+/// Rect in the Metal view's coordinates for a material region
+///
+/// Content coordinates have a top-left origin, but the view isn't flipped,
+/// so the rect is measured up from the bottom of the content.
+fn material_view_rect(bounds: Rect, content_height: f64) -> NSRect {
+    NSRect::new(
+        NSPoint::new(
+            bounds.pos.x as f64,
+            content_height - (bounds.pos.y + bounds.size.y) as f64,
+        ),
+        NSSize::new(bounds.size.x as f64, bounds.size.y as f64),
+    )
+}
+
+/// NSWindowCollectionBehaviorCanJoinAllSpaces
+const COLLECTION_CAN_JOIN_ALL_SPACES: u64 = 1 << 0;
+/// NSWindowCollectionBehaviorFullScreenPrimary
+const COLLECTION_FULL_SCREEN_PRIMARY: u64 = 1 << 7;
+/// NSWindowCollectionBehaviorFullScreenAuxiliary
+const COLLECTION_FULL_SCREEN_AUXILIARY: u64 = 1 << 8;
+
+/// The `NSWindowStyleMask` a window of `kind` opens with
+fn style_mask(kind: WindowKind) -> u64 {
+    match kind {
+        WindowKind::Normal => 15, // Titled | Closable | Miniaturizable | Resizable
+        // Titled | Closable | Resizable | NonactivatingPanel
+        WindowKind::Panel => 1 | 2 | 8 | 1 << 7,
+    }
+}
+
+/// A window's collection behavior with the flags for `kind` added to the
+/// `existing` ones
+fn collection_behavior(existing: u64, kind: WindowKind) -> u64 {
+    existing
+        | match kind {
+            // Fullscreen gets its own space, like the green window button
+            WindowKind::Normal => COLLECTION_FULL_SCREEN_PRIMARY,
+            // Panels show on every space, including over fullscreen apps
+            WindowKind::Panel => COLLECTION_CAN_JOIN_ALL_SPACES | COLLECTION_FULL_SCREEN_AUXILIARY,
+        }
+}
+
+/// The scale a window renders at: `forced` if set, otherwise the display's
+/// `backing_scale`
+fn resolve_scale_factor(forced: Option<f32>, backing_scale: f64) -> f32 {
+    forced.unwrap_or(backing_scale as f32)
+}
+
+/// Convert an `NSEvent` timestamp (seconds since boot) to an `Instant`
+fn event_timestamp(event: *mut Object) -> Instant {
+    let now = Instant::now();
+    let (timestamp, uptime): (f64, f64) = unsafe {
+        let process_info: id = msg_send![class!(NSProcessInfo), processInfo];
+        (
+            msg_send![event, timestamp],
+            msg_send![process_info, systemUptime],
+        )
+    };
+    let age = Duration::from_secs_f64((uptime - timestamp).max(0.0));
+    now.checked_sub(age).unwrap_or(now)
+}
+
+/// The button a mouse down or up `NSEvent` is for
+fn mouse_button(event: *mut Object) -> MouseButton {
+    let number: i64 = unsafe { msg_send![event, buttonNumber] };
+    MouseButton::from_number(number.clamp(0, u8::MAX as i64) as u8)
+}
+
+/// How long to wait for an event when none are pending
+#[derive(Clone, Copy)]
+enum EventWait {
+    UntilEvent,
+    None,
+    Until(Instant),
+}
+
+/// Wake the main thread's event loop if it's waiting for events
+///
+/// Safe to call from any thread. Posts an empty application-defined event,
+/// which the loop picks up and ignores.
+pub fn wake_event_loop() {
+    unsafe {
+        // Background threads have no pool of their own, so drain this one
+        // before returning to release the event
+        let pool = NSAutoreleasePool::new(nil);
+        // NSEventTypeApplicationDefined = 15
+        let event: id = msg_send![
+            class!(NSEvent),
+            otherEventWithType: 15u64
+            location: NSPoint::new(0.0, 0.0)
+            modifierFlags: 0u64
+            timestamp: 0.0f64
+            windowNumber: 0i64
+            context: nil
+            subtype: 0i16
+            data1: 0i64
+            data2: 0i64
+        ];
+        if !event.is_null() {
+            let app = NSApplication::shared();
+            let _: () = msg_send![app, postEvent: event atStart: NO];
+        }
+        let _: () = msg_send![pool, drain];
+    }
+}
+
 #[allow(dead_code)] // This is a false positive
 #[repr(C)]
 pub struct NSWindow {
@@ -44,11 +175,30 @@ static mut VIEW_CLASS: *const Class = ptr::null();
 
 thread_local! {
     static PENDING_EVENTS: RefCell<Vec<InputEvent>> = RefCell::new(Vec::new());
+    /// Platform timestamps for input events, keyed by index into `PENDING_EVENTS`
+    static PENDING_EVENT_TIMES: RefCell<Vec<(usize, Instant)>> = RefCell::new(Vec::new());
     static CURRENT_MODIFIERS: RefCell<Modifiers> = RefCell::new(Modifiers::new());
     /// When true, window close is intercepted and WindowCloseRequested event is emitted
     static CLOSE_CONFIRMATION_ENABLED: RefCell<bool> = RefCell::new(false);
     /// Set to true to allow window close to proceed (used after user confirms)
     static CLOSE_CONFIRMED: RefCell<bool> = RefCell::new(false);
+    /// Set when the window's backing scale or display changed, which resets
+    /// the layer's contents scale
+    static BACKING_CHANGED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// How a window behaves alongside other windows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WindowKind {
+    /// A regular document or app window
+    #[default]
+    Normal,
+    /// A floating utility panel, like a palette, HUD or launcher
+    ///
+    /// Clicking the panel gives it keyboard focus without activating the
+    /// app, so the app the user was working in stays frontmost. Panels
+    /// float above normal windows and follow the user across spaces.
+    Panel,
 }
 
 #[allow(dead_code)] // dead ns_view is a false positive
@@ -56,16 +206,38 @@ pub struct Window {
     ns_window: *mut Object,
     ns_view: *mut Object,
     metal_layer: MetalLayer,
+    /// Visual effect views behind the Metal view, in paint order
+    material_views: RefCell<Vec<(MaterialRegion, *mut Object)>>,
+    /// Content height the material views were placed for
+    material_height: Cell<f64>,
+    /// Radius the window's corners are clipped to, or 0 for the system's
+    corner_radius: Cell<f32>,
+    /// Scale rendered at instead of the display's, if forced
+    forced_scale_factor: Cell<Option<f32>>,
 }
 
 impl Window {
     pub fn new(width: f64, height: f64, title: &str, device: &metal::Device) -> Arc<Self> {
+        Self::with_kind(width, height, title, device, WindowKind::Normal)
+    }
+
+    /// Create a window of the given kind
+    pub fn with_kind(
+        width: f64,
+        height: f64,
+        title: &str,
+        device: &metal::Device,
+        kind: WindowKind,
+    ) -> Arc<Self> {
         unsafe { ensure_classes_initialized() };
 
         let _pool = unsafe { NSAutoreleasePool::new(nil) };
 
         // Create window
-        let ns_window: *mut Object = unsafe { msg_send![class!(NSWindow), alloc] };
+        let ns_window: *mut Object = match kind {
+            WindowKind::Normal => unsafe { msg_send![class!(NSWindow), alloc] },
+            WindowKind::Panel => unsafe { msg_send![class!(NSPanel), alloc] },
+        };
         let content_rect = NSRect::new(
             NSPoint::new(100.0, 100.0),
             NSSize {
@@ -73,7 +245,7 @@ impl Window {
                 height: height,
             },
         );
-        let style_mask: u64 = 15; // Titled | Closable | Miniaturizable | Resizable
+        let style_mask = style_mask(kind);
         let backing_store: u64 = 2; // Buffered
 
         let ns_window: *mut Object = unsafe {
@@ -90,6 +262,16 @@ impl Window {
         let title = unsafe { ns_string(title) };
         let _: () = unsafe { msg_send![ns_window, setTitle: title] };
 
+        let existing: u64 = unsafe { msg_send![ns_window, collectionBehavior] };
+        let collection_behavior = collection_behavior(existing, kind);
+        let _: () = unsafe { msg_send![ns_window, setCollectionBehavior: collection_behavior] };
+        if kind == WindowKind::Panel {
+            unsafe {
+                let _: () = msg_send![ns_window, setFloatingPanel: YES];
+                let _: () = msg_send![ns_window, setHidesOnDeactivate: NO];
+            }
+        }
+
         // Create delegate
         let delegate: *mut Object = unsafe { msg_send![WINDOW_DELEGATE_CLASS, new] };
         let _: () = unsafe { msg_send![ns_window, setDelegate: delegate] };
@@ -109,6 +291,7 @@ impl Window {
 
         layer.set_opaque(true);
         layer.set_presents_with_transaction(false);
+        // Made readable while backdrop blurs need to copy from it
         layer.set_framebuffer_only(true);
         layer.set_drawable_size(CGSize::new(width * scale_factor, height * scale_factor));
         let _: () = unsafe { msg_send![layer.as_ref(), setFrame: content_rect] };
@@ -138,6 +321,10 @@ impl Window {
             ns_window,
             ns_view,
             metal_layer: layer,
+            material_views: RefCell::new(Vec::new()),
+            material_height: Cell::new(0.0),
+            corner_radius: Cell::new(0.0),
+            forced_scale_factor: Cell::new(None),
         })
     }
 
@@ -146,61 +333,276 @@ impl Window {
     }
 
     pub fn size(&self) -> (f32, f32) {
-        let frame: NSRect = unsafe { msg_send![self.ns_window, contentLayoutRect] };
-        (frame.size.width as f32, frame.size.height as f32)
+        let size = unsafe { content_size(self.ns_window) };
+        (size.width as f32, size.height as f32)
+    }
+
+    /// Hide the titlebar and extend the content under it
+    ///
+    /// The window buttons stay visible on top of the content. Mark elements
+    /// with `window_drag_region` so the window can still be moved.
+    pub fn set_titlebar_hidden(&self, hidden: bool) {
+        // NSWindowStyleMaskFullSizeContentView
+        const FULL_SIZE_CONTENT_VIEW: u64 = 1 << 15;
+        unsafe {
+            let style_mask: u64 = msg_send![self.ns_window, styleMask];
+            let style_mask = if hidden {
+                style_mask | FULL_SIZE_CONTENT_VIEW
+            } else {
+                style_mask & !FULL_SIZE_CONTENT_VIEW
+            };
+            let _: () = msg_send![self.ns_window, setStyleMask: style_mask];
+            let _: () = msg_send![
+                self.ns_window,
+                setTitlebarAppearsTransparent: if hidden { YES } else { NO }
+            ];
+            // NSWindowTitleHidden = 1, NSWindowTitleVisible = 0
+            let _: () = msg_send![self.ns_window, setTitleVisibility: hidden as i64];
+        }
+    }
+
+    /// Hide the close, minimize and zoom buttons
+    ///
+    /// With a hidden titlebar, this leaves the window without any system
+    /// chrome, for HUDs and launchers.
+    pub fn set_window_buttons_hidden(&self, hidden: bool) {
+        // NSWindowCloseButton, NSWindowMiniaturizeButton, NSWindowZoomButton
+        for kind in 0u64..3 {
+            unsafe {
+                let button: *mut Object = msg_send![self.ns_window, standardWindowButton: kind];
+                if !button.is_null() {
+                    let _: () = msg_send![button, setHidden: if hidden { YES } else { NO }];
+                }
+            }
+        }
+    }
+
+    /// Show or hide the window's drop shadow
+    ///
+    /// Transparent HUD-style windows often look better without one.
+    pub fn set_has_shadow(&self, has_shadow: bool) {
+        let has_shadow = if has_shadow { YES } else { NO };
+        let _: () = unsafe { msg_send![self.ns_window, setHasShadow: has_shadow] };
+    }
+
+    /// Whether the window has a drop shadow
+    pub fn has_shadow(&self) -> bool {
+        let has_shadow: BOOL = unsafe { msg_send![self.ns_window, hasShadow] };
+        has_shadow == YES
+    }
+
+    /// Clip the window to rounded corners of `radius` points, or 0 to use
+    /// the system's corners
+    ///
+    /// Use with a hidden titlebar. The window becomes transparent outside
+    /// the corners and its shadow follows the rounded shape.
+    pub fn set_corner_radius(&self, radius: f32) {
+        let radius = radius.max(0.0);
+        self.corner_radius.set(radius);
+        let custom = radius > 0.0;
+        unsafe {
+            let _: () = msg_send![self.ns_window, setOpaque: if custom { NO } else { YES }];
+            let background: id = if custom {
+                msg_send![class!(NSColor), clearColor]
+            } else {
+                msg_send![class!(NSColor), windowBackgroundColor]
+            };
+            let _: () = msg_send![self.ns_window, setBackgroundColor: background];
+
+            let layer: *mut Object = msg_send![self.ns_view, layer];
+            if !layer.is_null() {
+                let _: () = msg_send![layer, setCornerRadius: radius as f64];
+                let _: () = msg_send![layer, setMasksToBounds: if custom { YES } else { NO }];
+            }
+            // Recompute the shadow from the new shape
+            let _: () = msg_send![self.ns_window, invalidateShadow];
+        }
+        self.update_layer_opacity();
+    }
+
+    /// Radius the window's corners are clipped to, or 0 for the system's
+    pub fn corner_radius(&self) -> f32 {
+        self.corner_radius.get()
+    }
+
+    /// Transparent pixels only show what's behind the Metal view when its
+    /// layer isn't opaque
+    fn update_layer_opacity(&self) {
+        let opaque = self.material_views.borrow().is_empty() && self.corner_radius.get() == 0.0;
+        self.metal_layer.set_opaque(opaque);
+    }
+
+    /// Show system materials behind regions of the content
+    ///
+    /// Each region gets an `NSVisualEffectView` below the Metal view, which
+    /// shows through where the UI is transparent. Views are reused from frame
+    /// to frame and only touched when their region changes.
+    pub fn set_material_regions(&self, regions: &[MaterialRegion]) {
+        let mut views = self.material_views.borrow_mut();
+        // Frames are measured from the bottom, so a change in height moves
+        // every view even if the regions stay put
+        let height = unsafe { content_size(self.ns_window).height };
+        let moved = height != self.material_height.replace(height);
+        if !moved && views.iter().map(|(region, _)| region).eq(regions) {
+            return;
+        }
+
+        unsafe {
+            let superview: *mut Object = msg_send![self.ns_view, superview];
+            if superview.is_null() {
+                return;
+            }
+
+            while views.len() > regions.len() {
+                let Some((_, view)) = views.pop() else { break };
+                let _: () = msg_send![view, removeFromSuperview];
+            }
+
+            for (i, region) in regions.iter().enumerate() {
+                let view = match views.get(i) {
+                    Some((current, _)) if !moved && current == region => continue,
+                    Some((_, view)) => *view,
+                    None => {
+                        let view: *mut Object = msg_send![class!(NSVisualEffectView), new];
+                        // NSVisualEffectBlendingModeBehindWindow = 0,
+                        // NSVisualEffectStateActive = 1
+                        let _: () = msg_send![view, setBlendingMode: 0i64];
+                        let _: () = msg_send![view, setState: 1i64];
+                        let _: () = msg_send![view, setWantsLayer: YES];
+                        // Later regions sit closer to the Metal view, so
+                        // they cover earlier ones. NSWindowBelow = -1
+                        let _: () = msg_send![
+                            superview,
+                            addSubview: view
+                            positioned: -1i64
+                            relativeTo: self.ns_view
+                        ];
+                        // The superview keeps the view alive
+                        let _: () = msg_send![view, release];
+                        views.push((*region, view));
+                        view
+                    }
+                };
+
+                let rect = material_view_rect(region.bounds, height);
+                let frame: NSRect = msg_send![self.ns_view, convertRect: rect toView: superview];
+                let _: () = msg_send![view, setFrame: frame];
+                let _: () = msg_send![view, setMaterial: region.material.ns_material()];
+                let layer: *mut Object = msg_send![view, layer];
+                if !layer.is_null() {
+                    let _: () = msg_send![layer, setCornerRadius: region.corner_radius as f64];
+                    let _: () = msg_send![layer, setMasksToBounds: YES];
+                }
+                views[i].0 = *region;
+            }
+        }
+
+        drop(views);
+        self.update_layer_opacity();
     }
 
     pub fn handle_events(&self) -> bool {
-        self.handle_events_internal(true)
+        self.handle_events_internal(EventWait::UntilEvent)
     }
 
     pub fn handle_events_non_blocking(&self) -> bool {
-        self.handle_events_internal(false)
+        self.handle_events_internal(EventWait::None)
+    }
+
+    /// Handle pending events, waiting until `deadline` for one if there are
+    /// none
+    pub fn handle_events_until(&self, deadline: Instant) -> bool {
+        self.handle_events_internal(EventWait::Until(deadline))
     }
 
     pub fn get_pending_input_events(&self) -> Vec<InputEvent> {
-        PENDING_EVENTS.with(|events| {
-            let mut events_ref = events.borrow_mut();
-            let result = events_ref.clone();
-            events_ref.clear();
-            result
-        })
+        self.get_pending_input_events_with_timestamps()
+            .into_iter()
+            .map(|(event, _)| event)
+            .collect()
+    }
+
+    /// Take pending events with runs of pointer motion collapsed
+    ///
+    /// See [`coalesce_pointer_motion`].
+    pub fn take_coalesced_input_events(&self) -> Vec<CoalescedEvent> {
+        coalesce_pointer_motion(self.get_pending_input_events_with_timestamps())
+    }
+
+    /// Take pending events along with when the platform received them
+    ///
+    /// Mouse and keyboard events carry the timestamp of their `NSEvent`; window
+    /// events, which have no such timestamp, get the time they are taken.
+    pub fn get_pending_input_events_with_timestamps(&self) -> Vec<(InputEvent, Instant)> {
+        let now = Instant::now();
+        let mut times = PENDING_EVENT_TIMES.with(|times| std::mem::take(&mut *times.borrow_mut()));
+        times.reverse();
+
+        let events = PENDING_EVENTS.with(|events| std::mem::take(&mut *events.borrow_mut()));
+        events
+            .into_iter()
+            .enumerate()
+            .map(|(index, event)| {
+                let timestamp = match times.last() {
+                    Some(&(i, timestamp)) if i == index => {
+                        times.pop();
+                        timestamp
+                    }
+                    _ => now,
+                };
+                (event, timestamp)
+            })
+            .collect()
     }
 
-    fn handle_events_internal(&self, blocking: bool) -> bool {
+    fn handle_events_internal(&self, wait: EventWait) -> bool {
         let app = unsafe { NSApplication::shared() };
-
-        loop {
-            let event: *mut Object = unsafe {
-                if blocking {
-                    msg_send![
-                        app,
-                        nextEventMatchingMask: !0
-                        untilDate: nil
-                        inMode: ns_string("kCFRunLoopDefaultMode")
-                        dequeue: YES
-                    ]
-                } else {
-                    // Non-blocking: return immediately if no events
+        let distant_past = || -> *mut Object { unsafe { msg_send![class!(NSDate), distantPast] } };
+        let mut until: *mut Object = match wait {
+            EventWait::UntilEvent => nil,
+            // Non-blocking: return immediately if no events
+            EventWait::None => distant_past(),
+            EventWait::Until(deadline) => {
+                let seconds = deadline.saturating_duration_since(Instant::now());
+                unsafe {
                     msg_send![
-                        app,
-                        nextEventMatchingMask: !0
-                        untilDate: {
-                            let past: *mut Object = msg_send![class!(NSDate), distantPast];
-                            past
-                        }
-                        inMode: ns_string("kCFRunLoopDefaultMode")
-                        dequeue: YES
+                        class!(NSDate),
+                        dateWithTimeIntervalSinceNow: seconds.as_secs_f64()
                     ]
                 }
+            }
+        };
+
+        loop {
+            self.sync_pointer_lock();
+
+            let event: *mut Object = unsafe {
+                msg_send![
+                    app,
+                    nextEventMatchingMask: !0
+                    untilDate: until
+                    inMode: ns_string("kCFRunLoopDefaultMode")
+                    dequeue: YES
+                ]
             };
 
             if event.is_null() {
                 break;
             }
+            // Only wait for the first event; take the rest as they are
+            if matches!(wait, EventWait::Until(_)) {
+                until = distant_past();
+            }
 
             // Get event type
             let event_type: u64 = unsafe { msg_send![event, type] };
+            let first_new_event = PENDING_EVENTS.with(|events| events.borrow().len());
+
+            // Mouse downs on custom chrome move or resize the window instead
+            // of reaching the app
+            if event_type == 1 && self.handle_window_chrome(event) {
+                continue;
+            }
 
             // Handle different event types
             match event_type {
@@ -215,23 +617,101 @@ impl Window {
                 11 => self.handle_key_up(event),      // NSEventTypeKeyUp
                 12 => self.handle_flags_changed(event), // NSEventTypeFlagsChanged
                 22 => self.handle_scroll_wheel(event), // NSEventTypeScrollWheel
+                25 => self.handle_mouse_down(event),   // NSEventTypeOtherMouseDown
+                26 => self.handle_mouse_up(event),     // NSEventTypeOtherMouseUp
+                27 => self.handle_mouse_moved(event),  // NSEventTypeOtherMouseDragged
+                30 => self.handle_magnify(event),    // NSEventTypeMagnify
+                23 => self.handle_tablet_point(event), // NSEventTypeTabletPoint
                 _ => {}
             }
 
+            // Mouse events from a pen also carry its pressure and tilt
+            if (1..=7).contains(&event_type) {
+                let subtype: i16 = unsafe { msg_send![event, subtype] };
+                if subtype == 1 {
+                    // NSEventSubtypeTabletPoint
+                    self.handle_tablet_point(event);
+                }
+            }
+
+            // Remember when the platform received any input events just queued
+            let end = PENDING_EVENTS.with(|events| events.borrow().len());
+            if end > first_new_event {
+                let timestamp = event_timestamp(event);
+                PENDING_EVENT_TIMES.with(|times| {
+                    times
+                        .borrow_mut()
+                        .extend((first_new_event..end).map(|i| (i, timestamp)));
+                });
+            }
+
             let _: () = unsafe { msg_send![app, sendEvent: event] };
         }
 
+        if BACKING_CHANGED.with(|changed| changed.replace(false)) {
+            self.restore_forced_scale_factor();
+        }
+
         // Check if window is still valid
         let is_visible: BOOL = unsafe { msg_send![self.ns_window, isVisible] };
         is_visible == YES
     }
 
+    /// Device pixels per point the window renders at
+    ///
+    /// The display's backing scale, unless overridden with
+    /// [`force_scale_factor`](Self::force_scale_factor).
     pub fn scale_factor(&self) -> f32 {
-        let scale: f64 = unsafe { msg_send![self.ns_window, backingScaleFactor] };
-        scale as f32
+        let backing_scale: f64 = unsafe { msg_send![self.ns_window, backingScaleFactor] };
+        resolve_scale_factor(self.forced_scale_factor.get(), backing_scale)
+    }
+
+    /// Render at `scale` device pixels per point whatever display the window
+    /// is on, or follow the display again with `None`
+    ///
+    /// The drawable is sized for the forced scale and the system resamples
+    /// it to fit the window, so 1x output shows as it would on a 1x display.
+    /// The scale is applied again whenever the window changes display or
+    /// the display changes resolution.
+    pub fn force_scale_factor(&self, scale: Option<f32>) {
+        self.forced_scale_factor.set(scale);
+        let scale_factor = self.scale_factor() as f64;
+        let (width, height) = self.size();
+        self.metal_layer.set_contents_scale(scale_factor);
+        self.metal_layer.set_drawable_size(CGSize::new(
+            width as f64 * scale_factor,
+            height as f64 * scale_factor,
+        ));
+    }
+
+    /// Apply a forced scale factor again after AppKit resets the layer's
+    /// contents scale to the display's, as it does when the window moves to
+    /// another display or the display's resolution changes
+    pub(crate) fn restore_forced_scale_factor(&self) {
+        if let Some(scale) = self.forced_scale_factor.get() {
+            self.force_scale_factor(Some(scale));
+        }
+    }
+
+    /// Pin the cursor while an element holds the pointer lock and the
+    /// window is key
+    fn sync_pointer_lock(&self) {
+        let is_key: BOOL = unsafe { msg_send![self.ns_window, isKeyWindow] };
+        set_cursor_pinned(pointer_lock_owner().is_some() && is_key == YES);
     }
 
     fn handle_mouse_moved(&self, event: *mut Object) {
+        if is_cursor_pinned() {
+            let (dx, dy): (f64, f64) =
+                unsafe { (msg_send![event, deltaX], msg_send![event, deltaY]) };
+            PENDING_EVENTS.with(|events| {
+                events.borrow_mut().push(InputEvent::MouseDelta {
+                    delta: glam::Vec2::new(dx as f32, dy as f32),
+                });
+            });
+            return;
+        }
+
         let location = self.get_mouse_location(event);
         PENDING_EVENTS.with(|events| {
             events.borrow_mut().push(InputEvent::MouseMove {
@@ -242,14 +722,7 @@ impl Window {
 
     fn handle_mouse_down(&self, event: *mut Object) {
         let location = self.get_mouse_location(event);
-        let event_type: u64 = unsafe { msg_send![event, type] };
-        let button = if event_type == 1 {
-            MouseButton::Left
-        } else if event_type == 3 {
-            MouseButton::Right
-        } else {
-            MouseButton::Middle
-        };
+        let button = mouse_button(event);
 
         // Get click count from macOS for double/triple click detection
         let click_count: u64 = unsafe { msg_send![event, clickCount] };
@@ -263,16 +736,98 @@ impl Window {
         });
     }
 
+    /// Start a window move or resize if a left mouse down hit custom chrome
+    fn handle_window_chrome(&self, event: *mut Object) -> bool {
+        let (x, y) = self.get_mouse_location(event);
+        let Some(chrome) = window_chrome_at(Vec2::new(x as f32, y as f32)) else {
+            return false;
+        };
+
+        match chrome {
+            WindowChrome::Drag => {
+                let click_count: u64 = unsafe { msg_send![event, clickCount] };
+                if click_count == 2 {
+                    self.perform_titlebar_double_click();
+                } else {
+                    let _: () =
+                        unsafe { msg_send![self.ns_window, performWindowDragWithEvent: event] };
+                }
+            }
+            WindowChrome::Resize(edge) => self.track_window_resize(edge),
+        }
+        true
+    }
+
+    /// Zoom or minimize the window, following the system's titlebar
+    /// double-click setting
+    fn perform_titlebar_double_click(&self) {
+        use super::user_default_string;
+
+        match user_default_string("AppleActionOnDoubleClick").as_deref() {
+            Some("None") => {}
+            Some("Minimize") => self.minimize(),
+            _ => self.maximize(),
+        }
+    }
+
+    /// Resize the window from `edge` until the mouse is released
+    fn track_window_resize(&self, edge: WindowEdge) {
+        // NSEventMaskLeftMouseUp | NSEventMaskLeftMouseDragged
+        const TRACKING_MASK: u64 = (1 << 2) | (1 << 6);
+
+        let app = unsafe { NSApplication::shared() };
+        let mouse_location = || -> Vec2 {
+            let point: NSPoint = unsafe { msg_send![class!(NSEvent), mouseLocation] };
+            // Screen coordinates have y increasing upwards
+            Vec2::new(point.x as f32, -point.y as f32)
+        };
+        let start_mouse = mouse_location();
+        let (start_frame, min_size) = unsafe {
+            let frame: NSRect = msg_send![self.ns_window, frame];
+            let min_size: NSSize = msg_send![self.ns_window, minSize];
+            (frame, min_size)
+        };
+        // Flip the frame so y increases downwards, like window content
+        let start = Rect::new(
+            start_frame.origin.x as f32,
+            -(start_frame.origin.y + start_frame.size.height) as f32,
+            start_frame.size.width as f32,
+            start_frame.size.height as f32,
+        );
+        let min_size = Vec2::new(min_size.width as f32, min_size.height as f32);
+
+        loop {
+            let event: *mut Object = unsafe {
+                let future: *mut Object = msg_send![class!(NSDate), distantFuture];
+                msg_send![
+                    app,
+                    nextEventMatchingMask: TRACKING_MASK
+                    untilDate: future
+                    inMode: ns_string("NSEventTrackingRunLoopMode")
+                    dequeue: YES
+                ]
+            };
+            if event.is_null() {
+                break;
+            }
+
+            let frame = edge.resize(start, mouse_location() - start_mouse, min_size);
+            let frame = NSRect::new(
+                NSPoint::new(frame.pos.x as f64, -(frame.pos.y + frame.size.y) as f64),
+                NSSize::new(frame.size.x as f64, frame.size.y as f64),
+            );
+            let _: () = unsafe { msg_send![self.ns_window, setFrame: frame display: YES] };
+
+            let event_type: u64 = unsafe { msg_send![event, type] };
+            if event_type == 2 {
+                break;
+            }
+        }
+    }
+
     fn handle_mouse_up(&self, event: *mut Object) {
         let location = self.get_mouse_location(event);
-        let event_type: u64 = unsafe { msg_send![event, type] };
-        let button = if event_type == 2 {
-            MouseButton::Left
-        } else if event_type == 4 {
-            MouseButton::Right
-        } else {
-            MouseButton::Middle
-        };
+        let button = mouse_button(event);
 
         PENDING_EVENTS.with(|events| {
             events.borrow_mut().push(InputEvent::MouseUp {
@@ -405,17 +960,75 @@ impl Window {
             (delta_y * multiplier) as f32,
         );
 
-        // Only emit event if there's actual scrolling
-        if delta.x.abs() > 0.0 || delta.y.abs() > 0.0 {
+        // NSEventPhase bitmasks for the finger gesture and the system's momentum
+        let phase: u64 = unsafe { msg_send![event, phase] };
+        let momentum_phase: u64 = unsafe { msg_send![event, momentumPhase] };
+        let phase = if momentum_phase != 0 {
+            ScrollPhase::Momentum
+        } else if phase & (1 | 32) != 0 {
+            // Began or MayBegin
+            ScrollPhase::Began
+        } else if phase & (8 | 16) != 0 {
+            // Ended or Cancelled
+            ScrollPhase::Ended
+        } else if phase != 0 {
+            ScrollPhase::Changed
+        } else {
+            ScrollPhase::None
+        };
+
+        // Only emit event if there's actual scrolling, or a gesture starts or ends
+        let boundary = matches!(phase, ScrollPhase::Began | ScrollPhase::Ended);
+        if delta.x.abs() > 0.0 || delta.y.abs() > 0.0 || boundary {
             PENDING_EVENTS.with(|events| {
                 events.borrow_mut().push(InputEvent::ScrollWheel {
                     position: glam::Vec2::new(location.0 as f32, location.1 as f32),
                     delta,
+                    phase,
+                });
+            });
+        }
+    }
+
+    fn handle_magnify(&self, event: *mut Object) {
+        let location = self.get_mouse_location(event);
+        let magnification: f64 = unsafe { msg_send![event, magnification] };
+
+        if magnification != 0.0 {
+            PENDING_EVENTS.with(|events| {
+                events.borrow_mut().push(InputEvent::Magnify {
+                    position: glam::Vec2::new(location.0 as f32, location.1 as f32),
+                    magnification: magnification as f32,
                 });
             });
         }
     }
 
+    fn handle_tablet_point(&self, event: *mut Object) {
+        let location = self.get_mouse_location(event);
+        let (pressure, tilt, rotation, buttons): (f32, NSPoint, f32, u64) = unsafe {
+            (
+                msg_send![event, pressure],
+                msg_send![event, tilt],
+                msg_send![event, rotation],
+                msg_send![event, buttonMask],
+            )
+        };
+
+        PENDING_EVENTS.with(|events| {
+            events.borrow_mut().push(InputEvent::TabletMove {
+                position: glam::Vec2::new(location.0 as f32, location.1 as f32),
+                tablet: TabletData {
+                    pressure,
+                    tilt: glam::Vec2::new(tilt.x as f32, tilt.y as f32),
+                    rotation,
+                    // NSPenLowerSideMask | NSPenUpperSideMask
+                    barrel_button: buttons & (2 | 4) != 0,
+                },
+            });
+        });
+    }
+
     /// Get the current modifier state
     #[allow(dead_code)]
     pub fn current_modifiers(&self) -> Modifiers {
@@ -492,6 +1105,18 @@ impl Window {
         }
     }
 
+    /// Enter or exit fullscreen mode
+    ///
+    /// The window animates into or out of its own space, and the app gets
+    /// `WindowEnteredFullscreen` or `WindowExitedFullscreen` once it's done.
+    pub fn set_fullscreen(&self, fullscreen: bool) {
+        if fullscreen {
+            self.enter_fullscreen();
+        } else {
+            self.exit_fullscreen();
+        }
+    }
+
     /// Toggle fullscreen mode
     pub fn toggle_fullscreen(&self) {
         let _: () = unsafe { msg_send![self.ns_window, toggleFullScreen: nil] };
@@ -505,6 +1130,28 @@ impl Window {
         (style_mask & NS_FULLSCREEN_MASK) != 0
     }
 
+    /// Move the cursor to `position` in the window's content, in the same
+    /// coordinates as mouse events
+    pub fn set_cursor_position(&self, position: Vec2) {
+        unsafe {
+            let content_view: *mut Object = msg_send![self.ns_window, contentView];
+            let bounds: NSRect = msg_send![content_view, bounds];
+            let window_point =
+                NSPoint::new(position.x as f64, bounds.size.height - position.y as f64);
+            let rect = NSRect::new(window_point, NSSize::new(0.0, 0.0));
+            let screen_rect: NSRect = msg_send![self.ns_window, convertRectToScreen: rect];
+
+            // Screen coordinates start at the bottom-left of the main display
+            let screens: id = msg_send![class!(NSScreen), screens];
+            let main_display: id = msg_send![screens, objectAtIndex: 0u64];
+            let display_frame: NSRect = msg_send![main_display, frame];
+            set_cursor_position(Vec2::new(
+                screen_rect.origin.x as f32,
+                (display_frame.size.height - screen_rect.origin.y) as f32,
+            ));
+        }
+    }
+
     /// Get the window position (origin of the frame in screen coordinates)
     pub fn position(&self) -> (f32, f32) {
         let frame: NSRect = unsafe { msg_send![self.ns_window, frame] };
@@ -523,7 +1170,7 @@ impl Window {
         let _: () = unsafe { msg_send![self.ns_window, setContentSize: size] };
 
         // Also update the metal layer drawable size
-        let scale_factor: f64 = unsafe { msg_send![self.ns_window, backingScaleFactor] };
+        let scale_factor = self.scale_factor() as f64;
         self.metal_layer.set_drawable_size(CGSize::new(
             width as f64 * scale_factor,
             height as f64 * scale_factor,
@@ -539,7 +1186,7 @@ impl Window {
         let _: () = unsafe { msg_send![self.ns_window, setFrame: frame display: YES] };
 
         // Also update the metal layer drawable size
-        let scale_factor: f64 = unsafe { msg_send![self.ns_window, backingScaleFactor] };
+        let scale_factor = self.scale_factor() as f64;
         self.metal_layer.set_drawable_size(CGSize::new(
             width as f64 * scale_factor,
             height as f64 * scale_factor,
@@ -562,6 +1209,34 @@ impl Window {
         let _: () = unsafe { msg_send![self.ns_window, center] };
     }
 
+    /// The display the window is mostly on
+    pub fn display(&self) -> Option<Display> {
+        let screen: id = unsafe { msg_send![self.ns_window, screen] };
+        if screen == nil {
+            return None;
+        }
+        super::display::display(unsafe { display_id(screen) })
+    }
+
+    /// Move the window to the center of a display, keeping its size
+    ///
+    /// Returns false if the display isn't connected.
+    pub fn move_to_display(&self, display: DisplayId) -> bool {
+        let Some(screen) = (unsafe { screen_for_display(display) }) else {
+            return false;
+        };
+        unsafe {
+            let visible: NSRect = msg_send![screen, visibleFrame];
+            let frame: NSRect = msg_send![self.ns_window, frame];
+            let origin = NSPoint::new(
+                visible.origin.x + (visible.size.width - frame.size.width) / 2.0,
+                visible.origin.y + (visible.size.height - frame.size.height) / 2.0,
+            );
+            let _: () = msg_send![self.ns_window, setFrameOrigin: origin];
+        }
+        true
+    }
+
     /// Check if the window has focus (is key window)
     pub fn is_focused(&self) -> bool {
         let is_key: BOOL = unsafe { msg_send![self.ns_window, isKeyWindow] };
@@ -744,10 +1419,10 @@ unsafe fn create_window_delegate_class() {
     extern "C" fn window_did_resize(_: &Object, _: Sel, notification: *mut Object) {
         unsafe {
             let window: *mut Object = msg_send![notification, object];
-            let content_rect: NSRect = msg_send![window, contentLayoutRect];
+            let size = content_size(window);
             PENDING_EVENTS.with(|events| {
                 events.borrow_mut().push(InputEvent::WindowResized {
-                    size: Vec2::new(content_rect.size.width as f32, content_rect.size.height as f32),
+                    size: Vec2::new(size.width as f32, size.height as f32),
                 });
             });
         }
@@ -836,6 +1511,30 @@ unsafe fn create_window_delegate_class() {
         );
     }
 
+    // windowDidChangeBackingProperties: - scale factor changed
+    extern "C" fn window_did_change_backing_properties(_: &Object, _: Sel, _: *mut Object) {
+        BACKING_CHANGED.with(|changed| changed.set(true));
+    }
+
+    unsafe {
+        decl.add_method(
+            sel!(windowDidChangeBackingProperties:),
+            window_did_change_backing_properties as extern "C" fn(&Object, Sel, *mut Object),
+        );
+    }
+
+    // windowDidChangeScreen: - window moved to another display
+    extern "C" fn window_did_change_screen(_: &Object, _: Sel, _: *mut Object) {
+        BACKING_CHANGED.with(|changed| changed.set(true));
+    }
+
+    unsafe {
+        decl.add_method(
+            sel!(windowDidChangeScreen:),
+            window_did_change_screen as extern "C" fn(&Object, Sel, *mut Object),
+        );
+    }
+
     unsafe {
         WINDOW_DELEGATE_CLASS = decl.register();
     }
@@ -936,3 +1635,65 @@ impl NSApplication {
         msg_send![class!(NSApplication), sharedApplication]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_style_mask_per_kind() {
+        // NSWindowStyleMaskMiniaturizable = 1 << 2
+        assert_eq!(style_mask(WindowKind::Normal) & (1 << 2), 1 << 2);
+        // Panels can't be miniaturized and don't activate the app
+        // (NSWindowStyleMaskNonactivatingPanel = 1 << 7)
+        assert_eq!(style_mask(WindowKind::Panel) & (1 << 2), 0);
+        assert_eq!(style_mask(WindowKind::Panel) & (1 << 7), 1 << 7);
+        assert_eq!(style_mask(WindowKind::Normal) & (1 << 7), 0);
+    }
+
+    #[test]
+    fn test_collection_behavior_keeps_existing_flags() {
+        // NSWindowCollectionBehaviorManaged = 1 << 2
+        let managed = 1 << 2;
+        assert_eq!(
+            collection_behavior(managed, WindowKind::Normal),
+            managed | COLLECTION_FULL_SCREEN_PRIMARY
+        );
+        assert_eq!(
+            collection_behavior(managed, WindowKind::Panel),
+            managed | COLLECTION_CAN_JOIN_ALL_SPACES | COLLECTION_FULL_SCREEN_AUXILIARY
+        );
+        // Already set flags stay set
+        assert_eq!(
+            collection_behavior(COLLECTION_FULL_SCREEN_PRIMARY, WindowKind::Normal),
+            COLLECTION_FULL_SCREEN_PRIMARY
+        );
+    }
+
+    #[test]
+    fn test_forced_scale_factor_overrides_the_display() {
+        assert_eq!(resolve_scale_factor(None, 2.0), 2.0);
+        assert_eq!(resolve_scale_factor(Some(1.0), 2.0), 1.0);
+        assert_eq!(resolve_scale_factor(Some(3.0), 1.0), 3.0);
+    }
+
+    #[test]
+    fn test_material_view_rect_flips_to_bottom_origin() {
+        let rect = material_view_rect(Rect::new(10.0, 20.0, 100.0, 50.0), 400.0);
+        assert_eq!((rect.origin.x, rect.origin.y), (10.0, 330.0));
+        assert_eq!((rect.size.width, rect.size.height), (100.0, 50.0));
+
+        // A region at the top of the content sits at the top of the view
+        let top = material_view_rect(Rect::new(0.0, 0.0, 200.0, 40.0), 400.0);
+        assert_eq!(top.origin.y + top.size.height, 400.0);
+    }
+
+    #[test]
+    fn test_material_view_rect_follows_content_height() {
+        // The same region moves up as the window grows taller
+        let bounds = Rect::new(0.0, 0.0, 200.0, 40.0);
+        let short = material_view_rect(bounds, 400.0);
+        let tall = material_view_rect(bounds, 600.0);
+        assert_eq!(tall.origin.y - short.origin.y, 200.0);
+    }
+}
@@ -0,0 +1,354 @@
+//! Post-processing: shader passes run over a layer's rendered content
+//!
+//! A layer with post-process passes is drawn into an offscreen texture.
+//! Each active pass reads the previous result, and the last one is
+//! composited onto the window. Passes are plain Metal source, either a
+//! fragment function or a compute kernel, so apps can write their own by
+//! implementing [`PostProcess`].
+//!
+//! ```ignore
+//! let grayscale = Rc::new(Grayscale::new(0.0));
+//! layers.add_ui_layer(
+//!     0,
+//!     LayerOptions::default().with_post_process(grayscale.clone()),
+//!     render_main,
+//! );
+//!
+//! // Later, when a modal opens
+//! grayscale.set_amount(1.0);
+//! ```
+
+use std::cell::Cell;
+use std::fmt;
+
+/// Metal source for a post-process pass
+///
+/// Every pass can use this struct, which is declared before its source:
+///
+/// ```metal
+/// struct PostProcessUniforms {
+///     float2 resolution;   // Texture size in pixels
+///     float time;          // Seconds since the app started
+///     float scale_factor;  // Pixels per point
+///     float4 params;       // From PostProcess::params
+/// };
+/// ```
+///
+/// Colors in the source texture have premultiplied alpha, and passes should
+/// return them the same way.
+#[derive(Debug, Clone)]
+pub enum PostProcessShader {
+    /// Source defining a function called for every pixel:
+    ///
+    /// ```metal
+    /// float4 post_process(texture2d<float> source, sampler source_sampler,
+    ///                     float2 uv, constant PostProcessUniforms &uniforms)
+    /// ```
+    Fragment(String),
+    /// Source defining a kernel dispatched over every pixel:
+    ///
+    /// ```metal
+    /// kernel void post_process(texture2d<float, access::read> source [[texture(0)]],
+    ///                          texture2d<float, access::write> destination [[texture(1)]],
+    ///                          constant PostProcessUniforms &uniforms [[buffer(0)]],
+    ///                          uint2 gid [[thread_position_in_grid]])
+    /// ```
+    ///
+    /// The grid is rounded up to whole threadgroups, so kernels must skip
+    /// positions outside the texture.
+    Compute(String),
+}
+
+/// A shader pass applied to a layer's content after it's drawn
+///
+/// Passes are shared through `Rc`, so they can keep settings in `Cell`s and
+/// be adjusted while the app runs; the built-in passes work this way.
+pub trait PostProcess: fmt::Debug {
+    /// Identifies the shader; passes with the same key share one compiled
+    /// pipeline, so it must change whenever the source does
+    fn key(&self) -> &str;
+
+    /// Metal source for the pass, compiled the first time it runs
+    fn shader(&self) -> PostProcessShader;
+
+    /// Values the shader reads as `uniforms.params`, fetched every frame
+    fn params(&self) -> [f32; 4] {
+        [0.0; 4]
+    }
+
+    /// Whether to run the pass this frame
+    ///
+    /// A layer whose passes are all inactive draws straight to the window.
+    fn is_active(&self) -> bool {
+        true
+    }
+
+    /// Whether the pass changes over time, so frames must keep coming while
+    /// it's active
+    fn is_animated(&self) -> bool {
+        false
+    }
+}
+
+/// Fades a layer to grayscale, e.g. to push content back while a modal is
+/// open
+#[derive(Debug, Default)]
+pub struct Grayscale {
+    amount: Cell<f32>,
+}
+
+impl Grayscale {
+    /// Create a grayscale pass, from 0.0 (full color) to 1.0 (gray)
+    pub fn new(amount: f32) -> Self {
+        Self {
+            amount: Cell::new(amount.clamp(0.0, 1.0)),
+        }
+    }
+
+    /// How gray the layer is
+    pub fn amount(&self) -> f32 {
+        self.amount.get()
+    }
+
+    /// Set how gray the layer is; 0.0 turns the pass off
+    pub fn set_amount(&self, amount: f32) {
+        self.amount.set(amount.clamp(0.0, 1.0));
+    }
+}
+
+impl PostProcess for Grayscale {
+    fn key(&self) -> &str {
+        "sol.grayscale"
+    }
+
+    fn shader(&self) -> PostProcessShader {
+        PostProcessShader::Fragment(GRAYSCALE_SHADER.to_string())
+    }
+
+    fn params(&self) -> [f32; 4] {
+        [self.amount(), 0.0, 0.0, 0.0]
+    }
+
+    fn is_active(&self) -> bool {
+        self.amount() > 0.0
+    }
+}
+
+const GRAYSCALE_SHADER: &str = r#"
+    float4 post_process(texture2d<float> source, sampler source_sampler,
+                        float2 uv, constant PostProcessUniforms &uniforms) {
+        float4 color = source.sample(source_sampler, uv);
+        float luma = dot(color.rgb, float3(0.2126, 0.7152, 0.0722));
+        color.rgb = mix(color.rgb, float3(luma), uniforms.params.x);
+        return color;
+    }
+"#;
+
+/// An old CRT monitor look: curved glass, scanlines and color fringing
+#[derive(Debug, Clone)]
+pub struct Crt {
+    scanlines: f32,
+    curvature: f32,
+    aberration: f32,
+}
+
+impl Default for Crt {
+    fn default() -> Self {
+        Self {
+            scanlines: 0.35,
+            curvature: 0.15,
+            aberration: 1.0,
+        }
+    }
+}
+
+impl Crt {
+    /// Create a CRT pass with the default look
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how dark the scanlines are, from 0.0 to 1.0
+    pub fn scanlines(mut self, strength: f32) -> Self {
+        self.scanlines = strength.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Set how strongly the screen bulges; 0.0 keeps it flat
+    pub fn curvature(mut self, curvature: f32) -> Self {
+        self.curvature = curvature.max(0.0);
+        self
+    }
+
+    /// Set how far, in points, red and blue separate from green
+    pub fn aberration(mut self, points: f32) -> Self {
+        self.aberration = points.max(0.0);
+        self
+    }
+}
+
+impl PostProcess for Crt {
+    fn key(&self) -> &str {
+        "sol.crt"
+    }
+
+    fn shader(&self) -> PostProcessShader {
+        PostProcessShader::Fragment(CRT_SHADER.to_string())
+    }
+
+    fn params(&self) -> [f32; 4] {
+        [self.scanlines, self.curvature, self.aberration, 0.0]
+    }
+}
+
+const CRT_SHADER: &str = r#"
+    float4 post_process(texture2d<float> source, sampler source_sampler,
+                        float2 uv, constant PostProcessUniforms &uniforms) {
+        // Barrel distortion, pushing the corners off screen
+        float2 centered = uv - 0.5;
+        float bulge = dot(centered, centered) * uniforms.params.y;
+        float2 warped = uv + centered * (1.0 + bulge) * bulge;
+        if (any(warped < 0.0) || any(warped > 1.0)) {
+            return float4(0.0, 0.0, 0.0, 1.0);
+        }
+
+        float2 fringe = float2(uniforms.params.z * uniforms.scale_factor / uniforms.resolution.x, 0.0);
+        float4 color = source.sample(source_sampler, warped);
+        color.r = source.sample(source_sampler, warped + fringe).r;
+        color.b = source.sample(source_sampler, warped - fringe).b;
+
+        // One dark line every two points
+        float rows = uniforms.resolution.y / uniforms.scale_factor;
+        float line = 0.5 + 0.5 * sin(warped.y * rows * M_PI_F);
+        color.rgb *= mix(1.0, line, uniforms.params.x);
+        return color;
+    }
+"#;
+
+/// A kind of color vision deficiency to simulate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorVision {
+    /// No red cones
+    Protanopia,
+    /// No green cones
+    Deuteranopia,
+    /// No blue cones
+    Tritanopia,
+    /// No color vision at all
+    Achromatopsia,
+}
+
+/// Simulates how a layer looks with a color vision deficiency, for checking
+/// that a design doesn't rely on hues some people can't tell apart
+#[derive(Debug, Default)]
+pub struct ColorBlindness {
+    vision: Cell<Option<ColorVision>>,
+}
+
+impl ColorBlindness {
+    /// Create a simulation pass; `None` leaves colors alone
+    pub fn new(vision: Option<ColorVision>) -> Self {
+        Self {
+            vision: Cell::new(vision),
+        }
+    }
+
+    /// The deficiency being simulated
+    pub fn vision(&self) -> Option<ColorVision> {
+        self.vision.get()
+    }
+
+    /// Change the deficiency being simulated; `None` turns the pass off
+    pub fn set_vision(&self, vision: Option<ColorVision>) {
+        self.vision.set(vision);
+    }
+}
+
+impl PostProcess for ColorBlindness {
+    fn key(&self) -> &str {
+        "sol.color_blindness"
+    }
+
+    fn shader(&self) -> PostProcessShader {
+        PostProcessShader::Compute(COLOR_BLINDNESS_SHADER.to_string())
+    }
+
+    fn params(&self) -> [f32; 4] {
+        let kind = match self.vision() {
+            None | Some(ColorVision::Protanopia) => 0.0,
+            Some(ColorVision::Deuteranopia) => 1.0,
+            Some(ColorVision::Tritanopia) => 2.0,
+            Some(ColorVision::Achromatopsia) => 3.0,
+        };
+        [kind, 0.0, 0.0, 0.0]
+    }
+
+    fn is_active(&self) -> bool {
+        self.vision().is_some()
+    }
+}
+
+// Matrices from Machado, Oliveira and Fernandes (2009) at full severity,
+// applied in linear RGB
+const COLOR_BLINDNESS_SHADER: &str = r#"
+    kernel void post_process(texture2d<float, access::read> source [[texture(0)]],
+                             texture2d<float, access::write> destination [[texture(1)]],
+                             constant PostProcessUniforms &uniforms [[buffer(0)]],
+                             uint2 gid [[thread_position_in_grid]]) {
+        if (gid.x >= source.get_width() || gid.y >= source.get_height()) {
+            return;
+        }
+        float4 color = source.read(gid);
+        if (color.a <= 0.0) {
+            destination.write(color, gid);
+            return;
+        }
+        float3 rgb = pow(color.rgb / color.a, 2.2);
+
+        float3 rows[3];
+        switch (int(uniforms.params.x)) {
+            case 0:
+                rows[0] = float3(0.152286, 1.052583, -0.204868);
+                rows[1] = float3(0.114503, 0.786281, 0.099216);
+                rows[2] = float3(-0.003882, -0.048116, 1.051998);
+                break;
+            case 1:
+                rows[0] = float3(0.367322, 0.860646, -0.227968);
+                rows[1] = float3(0.280085, 0.672501, 0.047413);
+                rows[2] = float3(-0.011820, 0.042940, 0.968881);
+                break;
+            case 2:
+                rows[0] = float3(1.255528, -0.076749, -0.178779);
+                rows[1] = float3(-0.078411, 0.930809, 0.147602);
+                rows[2] = float3(0.004733, 0.691367, 0.303900);
+                break;
+            default:
+                rows[0] = rows[1] = rows[2] = float3(0.2126, 0.7152, 0.0722);
+                break;
+        }
+        rgb = saturate(float3(dot(rows[0], rgb), dot(rows[1], rgb), dot(rows[2], rgb)));
+        destination.write(float4(pow(rgb, 1.0 / 2.2) * color.a, color.a), gid);
+    }
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_passes_turn_off_at_rest() {
+        let grayscale = Grayscale::new(0.0);
+        assert!(!grayscale.is_active());
+        grayscale.set_amount(2.0);
+        assert!(grayscale.is_active());
+        assert_eq!(grayscale.params()[0], 1.0);
+
+        let simulation = ColorBlindness::default();
+        assert!(!simulation.is_active());
+        simulation.set_vision(Some(ColorVision::Tritanopia));
+        assert!(simulation.is_active());
+        assert_eq!(simulation.params()[0], 2.0);
+
+        assert!(Crt::new().is_active());
+    }
+}
@@ -0,0 +1,310 @@
+//! Adaptive quality: trading visual fidelity for frame time under load
+//!
+//! A [`QualityGovernor`] watches how long frames take. When they run over
+//! the budget for several frames in a row it turns off the next expensive
+//! feature on its list, and once frames are comfortably under budget again
+//! it turns features back on, most recently degraded first.
+//!
+//! ```ignore
+//! app()
+//!     .quality_governor(
+//!         QualityGovernor::new(Duration::from_millis(16))
+//!             .with_degradation("animations", |degraded| set_animations(!degraded)),
+//!     )
+//!     .run();
+//! ```
+
+use std::time::Duration;
+
+/// A feature the renderer can turn off when frames run over budget
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuiltinDegradation {
+    /// Draw large shadows with the SDF approximation instead of blurring
+    /// them on the GPU
+    BlurredShadows,
+    /// Stop drawing shadows
+    Shadows,
+    /// Draw shape edges hard instead of smoothing them over a pixel
+    Antialiasing,
+    /// Rasterize glyphs at whole pixel sizes, so text at nearby sizes
+    /// shares cached glyphs
+    CoarseText,
+}
+
+/// One step down in quality
+enum Degradation {
+    Builtin(BuiltinDegradation),
+    /// App-defined, called with `true` to degrade and `false` to restore
+    Custom {
+        name: String,
+        apply: Box<dyn FnMut(bool)>,
+    },
+}
+
+impl Degradation {
+    fn name(&self) -> &str {
+        match self {
+            Degradation::Builtin(BuiltinDegradation::BlurredShadows) => "blurred shadows",
+            Degradation::Builtin(BuiltinDegradation::Shadows) => "shadows",
+            Degradation::Builtin(BuiltinDegradation::Antialiasing) => "antialiasing",
+            Degradation::Builtin(BuiltinDegradation::CoarseText) => "text cache precision",
+            Degradation::Custom { name, .. } => name,
+        }
+    }
+}
+
+/// Degrades and restores features to keep frame times within a budget
+pub struct QualityGovernor {
+    budget: Duration,
+    /// Consecutive over-budget frames before degrading
+    degrade_after: u32,
+    /// Consecutive frames with headroom before restoring
+    restore_after: u32,
+    /// Fraction of the budget a frame must stay under to count as headroom
+    headroom: f32,
+    /// Degradations in the order they're applied
+    degradations: Vec<Degradation>,
+    /// How many degradations are applied
+    level: usize,
+    slow_frames: u32,
+    fast_frames: u32,
+}
+
+impl QualityGovernor {
+    /// Create a governor for the given frame budget, starting with the
+    /// built-in degradations
+    pub fn new(budget: Duration) -> Self {
+        Self {
+            budget,
+            degrade_after: 10,
+            restore_after: 120,
+            headroom: 0.75,
+            degradations: vec![
+                Degradation::Builtin(BuiltinDegradation::BlurredShadows),
+                Degradation::Builtin(BuiltinDegradation::Shadows),
+                Degradation::Builtin(BuiltinDegradation::Antialiasing),
+                Degradation::Builtin(BuiltinDegradation::CoarseText),
+            ],
+            level: 0,
+            slow_frames: 0,
+            fast_frames: 0,
+        }
+    }
+
+    /// Set how many over-budget frames in a row trigger a degradation
+    ///
+    /// Defaults to 10.
+    pub fn degrade_after(mut self, frames: u32) -> Self {
+        self.degrade_after = frames.max(1);
+        self
+    }
+
+    /// Set how many frames with headroom in a row restore a degradation
+    ///
+    /// Defaults to 120.
+    pub fn restore_after(mut self, frames: u32) -> Self {
+        self.restore_after = frames.max(1);
+        self
+    }
+
+    /// Set the fraction of the budget a frame must stay under to count
+    /// towards restoring quality
+    ///
+    /// Defaults to 0.75, so features aren't restored just to push frames
+    /// straight back over budget.
+    pub fn headroom(mut self, fraction: f32) -> Self {
+        self.headroom = fraction.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Skip the built-in degradations, leaving only ones added with
+    /// [`with_degradation`](Self::with_degradation)
+    pub fn without_builtin_degradations(mut self) -> Self {
+        self.degradations
+            .retain(|degradation| !matches!(degradation, Degradation::Builtin(_)));
+        self
+    }
+
+    /// Add an app-defined degradation, applied after those already added
+    ///
+    /// `apply` is called with `true` when the governor degrades it and
+    /// `false` when it's restored.
+    pub fn with_degradation(
+        mut self,
+        name: impl Into<String>,
+        apply: impl FnMut(bool) + 'static,
+    ) -> Self {
+        self.degradations.push(Degradation::Custom {
+            name: name.into(),
+            apply: Box::new(apply),
+        });
+        self
+    }
+
+    /// The frame budget
+    pub fn budget(&self) -> Duration {
+        self.budget
+    }
+
+    /// How many degradations are currently applied
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    /// Names of the currently applied degradations, in the order applied
+    pub fn degraded(&self) -> impl Iterator<Item = &str> {
+        self.degradations[..self.level]
+            .iter()
+            .map(Degradation::name)
+    }
+
+    /// Record how long a frame took, degrading or restoring a feature if
+    /// it's time to
+    ///
+    /// App-defined degradations are applied directly; built-in ones are
+    /// handed to `apply_builtin` along with whether they're now degraded.
+    /// Returns whether anything changed.
+    pub fn record_frame(
+        &mut self,
+        frame_time: Duration,
+        mut apply_builtin: impl FnMut(BuiltinDegradation, bool),
+    ) -> bool {
+        if frame_time > self.budget {
+            self.slow_frames += 1;
+            self.fast_frames = 0;
+        } else if frame_time.as_secs_f32() <= self.budget.as_secs_f32() * self.headroom {
+            self.fast_frames += 1;
+            self.slow_frames = 0;
+        } else {
+            // Within budget but without headroom: hold steady
+            self.slow_frames = 0;
+            self.fast_frames = 0;
+        }
+
+        let degrade = if self.slow_frames >= self.degrade_after {
+            if self.level == self.degradations.len() {
+                self.slow_frames = 0;
+                return false;
+            }
+            self.level += 1;
+            true
+        } else if self.fast_frames >= self.restore_after && self.level > 0 {
+            self.level -= 1;
+            false
+        } else {
+            return false;
+        };
+        self.slow_frames = 0;
+        self.fast_frames = 0;
+
+        // Degrading applies the next step; restoring undoes the last one
+        let step = if degrade { self.level - 1 } else { self.level };
+        match &mut self.degradations[step] {
+            Degradation::Builtin(builtin) => apply_builtin(*builtin, degrade),
+            Degradation::Custom { apply, .. } => apply(degrade),
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    const BUDGET: Duration = Duration::from_millis(10);
+    const SLOW: Duration = Duration::from_millis(20);
+    const FAST: Duration = Duration::from_millis(5);
+
+    fn run(
+        governor: &mut QualityGovernor,
+        frame_time: Duration,
+        frames: u32,
+    ) -> Vec<(BuiltinDegradation, bool)> {
+        let mut changes = Vec::new();
+        for _ in 0..frames {
+            governor.record_frame(frame_time, |builtin, degraded| {
+                changes.push((builtin, degraded))
+            });
+        }
+        changes
+    }
+
+    #[test]
+    fn test_degrades_after_consecutive_slow_frames() {
+        let mut governor = QualityGovernor::new(BUDGET).degrade_after(3);
+
+        assert!(run(&mut governor, SLOW, 2).is_empty());
+        // A frame within budget resets the count
+        run(&mut governor, BUDGET, 1);
+        assert!(run(&mut governor, SLOW, 2).is_empty());
+
+        assert_eq!(
+            run(&mut governor, SLOW, 1),
+            [(BuiltinDegradation::BlurredShadows, true)]
+        );
+        assert_eq!(
+            run(&mut governor, SLOW, 3),
+            [(BuiltinDegradation::Shadows, true)]
+        );
+        assert_eq!(
+            run(&mut governor, SLOW, 6),
+            [
+                (BuiltinDegradation::Antialiasing, true),
+                (BuiltinDegradation::CoarseText, true),
+            ]
+        );
+        assert_eq!(governor.level(), 4);
+        assert_eq!(
+            governor.degraded().collect::<Vec<_>>(),
+            [
+                "blurred shadows",
+                "shadows",
+                "antialiasing",
+                "text cache precision"
+            ]
+        );
+
+        // Nothing left to degrade
+        assert!(run(&mut governor, SLOW, 10).is_empty());
+    }
+
+    #[test]
+    fn test_restores_in_reverse_with_headroom() {
+        let mut governor = QualityGovernor::new(BUDGET)
+            .degrade_after(1)
+            .restore_after(2);
+        run(&mut governor, SLOW, 2);
+
+        // Under budget but without headroom doesn't restore
+        assert!(run(&mut governor, Duration::from_millis(9), 5).is_empty());
+
+        assert_eq!(
+            run(&mut governor, FAST, 4),
+            [
+                (BuiltinDegradation::Shadows, false),
+                (BuiltinDegradation::BlurredShadows, false),
+            ]
+        );
+        assert_eq!(governor.level(), 0);
+    }
+
+    #[test]
+    fn test_custom_degradations() {
+        let applied = Rc::new(RefCell::new(Vec::new()));
+        let log = applied.clone();
+        let mut governor = QualityGovernor::new(BUDGET)
+            .without_builtin_degradations()
+            .with_degradation("animations", move |degraded| {
+                log.borrow_mut().push(degraded)
+            })
+            .degrade_after(1)
+            .restore_after(1);
+
+        assert!(run(&mut governor, SLOW, 1).is_empty());
+        assert_eq!(governor.degraded().collect::<Vec<_>>(), ["animations"]);
+        run(&mut governor, FAST, 1);
+        assert_eq!(*applied.borrow(), [true, false]);
+    }
+}
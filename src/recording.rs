@@ -0,0 +1,327 @@
+//! Input event recording and deterministic playback
+//!
+//! `InputRecorder` captures mouse and keyboard events together with the frame
+//! they were processed on, and `InputPlayback` feeds them back on the same
+//! frames. During playback the app runs on a fixed frame clock, so animations
+//! and time-based state advance identically on every run.
+//!
+//! ```ignore
+//! // Record a session to a file (saved when the app exits)
+//! app().record_input("session.json").with_layers(setup).run();
+//!
+//! // Replay it later
+//! app().playback_input("session.json").with_layers(setup).run();
+//! ```
+//!
+//! Coalesced pointer moves are recorded with every sample they stand for, so
+//! [`pointer_samples`](crate::interaction::pointer_samples) sees the same
+//! positions on playback.
+//!
+//! Window events (resize, focus, close, ...) are not recorded; they always come
+//! from the live window so a replayed session can still be closed normally.
+
+use crate::{error::SolResult, interaction::coalesce::CoalescedEvent, layer::InputEvent};
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use std::time::Instant;
+
+/// Current recording file format version
+pub const RECORDING_FORMAT_VERSION: u32 = 1;
+
+/// Frame interval used for the fixed frame clock during playback (120 FPS)
+pub const PLAYBACK_FRAME_TIME: f32 = 1.0 / 120.0;
+
+/// A single recorded input event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    /// Frame number the event was processed on
+    pub frame: u64,
+    /// Seconds since recording started (informational)
+    pub timestamp: f64,
+    /// The recorded event
+    pub event: InputEvent,
+    /// Pointer positions of the moves the event was coalesced from, oldest
+    /// first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub samples: Vec<Vec2>,
+}
+
+/// A serializable recording of an input session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputRecording {
+    /// File format version
+    pub version: u32,
+    /// Total number of frames in the recorded session
+    pub frame_count: u64,
+    /// Recorded events, ordered by frame
+    pub events: Vec<RecordedEvent>,
+}
+
+impl InputRecording {
+    /// Load a recording from a JSON file
+    pub fn load(path: impl AsRef<Path>) -> SolResult<Self> {
+        let file = File::open(path)?;
+        let recording = serde_json::from_reader(BufReader::new(file))?;
+        Ok(recording)
+    }
+
+    /// Save the recording to a JSON file
+    pub fn save(&self, path: impl AsRef<Path>) -> SolResult<()> {
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(BufWriter::new(file), self)?;
+        Ok(())
+    }
+}
+
+/// Records input events as they are processed by the app loop
+pub struct InputRecorder {
+    start: Instant,
+    frame_count: u64,
+    events: Vec<RecordedEvent>,
+}
+
+impl InputRecorder {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            frame_count: 0,
+            events: Vec::new(),
+        }
+    }
+
+    /// Record an event processed on the given frame, along with the
+    /// pointer samples it was coalesced from
+    ///
+    /// Window events are ignored.
+    pub fn record(&mut self, frame: u64, event: &CoalescedEvent) {
+        if event.event.is_window_event() {
+            return;
+        }
+
+        self.frame_count = self.frame_count.max(frame + 1);
+        self.events.push(RecordedEvent {
+            frame,
+            timestamp: self.start.elapsed().as_secs_f64(),
+            event: event.event.clone(),
+            samples: event.samples.clone(),
+        });
+    }
+
+    /// Mark a frame as completed so trailing frames without input are kept
+    pub fn end_frame(&mut self, frame: u64) {
+        self.frame_count = self.frame_count.max(frame + 1);
+    }
+
+    /// Get the number of recorded events
+    pub fn event_count(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Finish recording and return the recording
+    pub fn finish(self) -> InputRecording {
+        InputRecording {
+            version: RECORDING_FORMAT_VERSION,
+            frame_count: self.frame_count,
+            events: self.events,
+        }
+    }
+}
+
+impl Default for InputRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Feeds recorded input events back frame by frame
+pub struct InputPlayback {
+    recording: InputRecording,
+    cursor: usize,
+}
+
+impl InputPlayback {
+    pub fn new(recording: InputRecording) -> Self {
+        Self {
+            recording,
+            cursor: 0,
+        }
+    }
+
+    /// Load a recording from a file and prepare it for playback
+    pub fn load(path: impl AsRef<Path>) -> SolResult<Self> {
+        Ok(Self::new(InputRecording::load(path)?))
+    }
+
+    /// Take all events recorded on the given frame, with their pointer
+    /// samples, stamped as received at `now`
+    ///
+    /// Events recorded on earlier frames that were never taken are returned as
+    /// well, so skipping a frame does not drop input.
+    pub fn events_for_frame(&mut self, frame: u64, now: Instant) -> Vec<CoalescedEvent> {
+        let mut events = Vec::new();
+        while let Some(recorded) = self.recording.events.get(self.cursor) {
+            if recorded.frame > frame {
+                break;
+            }
+            let mut event = CoalescedEvent::new(recorded.event.clone(), now);
+            // Recordings made before samples were kept have none
+            if !recorded.samples.is_empty() {
+                event.samples = recorded.samples.clone();
+            }
+            events.push(event);
+            self.cursor += 1;
+        }
+        events
+    }
+
+    /// Get the elapsed time for a frame on the fixed playback clock
+    pub fn elapsed_time(&self, frame: u64) -> f32 {
+        frame as f32 * PLAYBACK_FRAME_TIME
+    }
+
+    /// Check if every recorded frame has been played back
+    pub fn is_finished(&self, frame: u64) -> bool {
+        self.cursor >= self.recording.events.len() && frame >= self.recording.frame_count
+    }
+
+    /// Get the recording being played back
+    pub fn recording(&self) -> &InputRecording {
+        &self.recording
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        interaction::coalesce::coalesce_pointer_motion,
+        layer::{Key, Modifiers, MouseButton, ScrollPhase},
+    };
+
+    fn key_down(key: Key) -> CoalescedEvent {
+        coalesced(InputEvent::KeyDown {
+            key,
+            modifiers: Modifiers::new(),
+            character: None,
+            is_repeat: false,
+        })
+    }
+
+    fn coalesced(event: InputEvent) -> CoalescedEvent {
+        CoalescedEvent::new(event, Instant::now())
+    }
+
+    #[test]
+    fn test_recorder_skips_window_events() {
+        let mut recorder = InputRecorder::new();
+        recorder.record(0, &coalesced(InputEvent::WindowFocused));
+        recorder.record(
+            0,
+            &coalesced(InputEvent::MouseMove {
+                position: Vec2::new(10.0, 20.0),
+            }),
+        );
+        recorder.end_frame(4);
+
+        let recording = recorder.finish();
+        assert_eq!(recording.events.len(), 1);
+        assert_eq!(recording.frame_count, 5);
+    }
+
+    #[test]
+    fn test_playback_returns_events_per_frame() {
+        let mut recorder = InputRecorder::new();
+        recorder.record(1, &key_down(Key::A));
+        recorder.record(1, &key_down(Key::B));
+        recorder.record(
+            3,
+            &coalesced(InputEvent::MouseDown {
+                position: Vec2::new(5.0, 5.0),
+                button: MouseButton::Left,
+                click_count: 1,
+            }),
+        );
+
+        let mut playback = InputPlayback::new(recorder.finish());
+        assert!(playback.events_for_frame(0, Instant::now()).is_empty());
+        assert_eq!(playback.events_for_frame(1, Instant::now()).len(), 2);
+        assert!(playback.events_for_frame(2, Instant::now()).is_empty());
+        assert!(!playback.is_finished(3));
+        assert_eq!(playback.events_for_frame(3, Instant::now()).len(), 1);
+        assert!(playback.is_finished(4));
+    }
+
+    #[test]
+    fn test_playback_catches_up_on_skipped_frames() {
+        let mut recorder = InputRecorder::new();
+        recorder.record(1, &key_down(Key::A));
+        recorder.record(2, &key_down(Key::B));
+
+        let mut playback = InputPlayback::new(recorder.finish());
+        assert_eq!(playback.events_for_frame(5, Instant::now()).len(), 2);
+    }
+
+    #[test]
+    fn test_recording_round_trips_through_json() {
+        let mut recorder = InputRecorder::new();
+        recorder.record(
+            2,
+            &coalesced(InputEvent::ScrollWheel {
+                position: Vec2::new(1.0, 2.0),
+                delta: Vec2::new(0.0, -3.0),
+                phase: ScrollPhase::Changed,
+            }),
+        );
+        let recording = recorder.finish();
+
+        let json = serde_json::to_string(&recording).unwrap();
+        let loaded: InputRecording = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(loaded.version, RECORDING_FORMAT_VERSION);
+        assert_eq!(loaded.events.len(), 1);
+        assert_eq!(loaded.events[0].frame, 2);
+        match loaded.events[0].event {
+            InputEvent::ScrollWheel { delta, .. } => assert_eq!(delta, Vec2::new(0.0, -3.0)),
+            _ => panic!("Expected ScrollWheel event"),
+        }
+    }
+
+    #[test]
+    fn test_playback_replays_coalesced_samples() {
+        let now = Instant::now();
+        let positions = [
+            Vec2::new(1.0, 1.0),
+            Vec2::new(2.0, 3.0),
+            Vec2::new(4.0, 6.0),
+        ];
+        let events = coalesce_pointer_motion(
+            positions
+                .iter()
+                .map(|&position| (InputEvent::MouseMove { position }, now)),
+        );
+        assert_eq!(events.len(), 1);
+
+        let mut recorder = InputRecorder::new();
+        recorder.record(0, &events[0]);
+        let json = serde_json::to_string(&recorder.finish()).unwrap();
+        let mut playback = InputPlayback::new(serde_json::from_str(&json).unwrap());
+
+        let replayed = playback.events_for_frame(0, now);
+        assert_eq!(replayed.len(), 1);
+        assert!(matches!(
+            replayed[0].event,
+            InputEvent::MouseMove { position } if position == positions[2]
+        ));
+        assert_eq!(replayed[0].samples, positions);
+    }
+
+    #[test]
+    fn test_fixed_frame_clock() {
+        let playback = InputPlayback::new(InputRecorder::new().finish());
+        assert_eq!(playback.elapsed_time(0), 0.0);
+        assert!((playback.elapsed_time(120) - 1.0).abs() < 1e-5);
+    }
+}
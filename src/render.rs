@@ -1,14 +1,28 @@
 //! Types and utilites that sit between the UI system and rendering pipeline
 
-use std::{cell::RefCell, rc::Rc};
+pub mod frame_graph;
+
+use std::{cell::RefCell, rc::Rc, sync::Arc};
 
 use crate::{
-    color::{Color, ColorExt},
-    geometry::{Corners, Edges, Rect},
-    interaction::{ElementId, HitTestBuilder},
+    caret::{Caret, caret_config},
+    clock::frame_time,
+    color::{Color, ColorExt, colors::TRANSPARENT},
+    debug::attribute_element_id,
+    geometry::{Corners, Edges, Rect, Transform},
+    image::{ImageHandle, RgbaImage},
+    interaction::{
+        ElementId, HitTestBuilder,
+        hit_test::{grow_to_min_size, min_hit_target_size},
+    },
     layout_engine::TaffyLayoutEngine,
-    style::{ElementStyle, Fill, TextStyle},
-    text_system::TextSystem,
+    style::{
+        CornerRadii, CornerStyle, EdgeColors, ElementStyle, Fill, GlyphRendering, Outline, Shadow,
+        TextStyle,
+    },
+    text_effect::{GlyphEffect, GlyphInfo, GlyphTransform},
+    text_system::{TextConfig, TextSystem},
+    vibrancy::{Material, MaterialRegion, register_material},
 };
 use glam::Vec2;
 use taffy::NodeId;
@@ -88,12 +102,118 @@ impl<'a> PaintContext<'a> {
             .add_text(text.position, &text.text, text.style, text.measured_size);
     }
 
+    /// Paint text with each glyph placed by `effect`
+    ///
+    /// The effect sees this frame's clock time. Another frame is requested
+    /// while any visible glyph is away from its resting place.
+    pub fn paint_text_with_effect(&mut self, text: PaintText, effect: &GlyphEffect) {
+        let config = TextConfig::from(&text.style);
+        let Ok(shaped) = self
+            .text_system
+            .shape_text(&text.text, &config, None, self.scale_factor)
+        else {
+            self.paint_text(text);
+            return;
+        };
+        let size = text.measured_size.unwrap_or(shaped.size);
+        if !self.is_visible(&Rect::from_pos_size(text.position, size)) {
+            return;
+        }
+
+        let time = frame_time();
+        let count = shaped.glyphs.len();
+        let transforms: Rc<[GlyphTransform]> = shaped
+            .glyphs
+            .iter()
+            .enumerate()
+            .map(|(index, glyph)| {
+                effect.apply(&GlyphInfo {
+                    index,
+                    count,
+                    position: glyph.position,
+                    time,
+                })
+            })
+            .collect();
+        if transforms.iter().any(|t| !t.is_identity()) {
+            self.request_animation_frame();
+        }
+        self.draw_list.add_text_with_glyph_transforms(
+            text.position,
+            text.text,
+            text.style,
+            text.measured_size,
+            Some(transforms),
+        );
+    }
+
     /// Paint a shadow
     pub fn paint_shadow(&mut self, _shadow: PaintShadow) {
         // TODO: Add shadow support to draw list
         // For now this is a no-op
     }
 
+    /// Paint an image, clipped to its rounded corners if it has any
+    pub fn paint_image(&mut self, image: PaintImage) {
+        let rounded = image.corner_radii != Corners::zero();
+        if rounded {
+            self.draw_list
+                .push_rounded_clip(image.bounds, image.corner_radii);
+        }
+        self.draw_list.add_image(image.bounds, image.image);
+        if rounded {
+            self.draw_list.pop_clip();
+        }
+    }
+
+    /// Paint with raw Metal: `draw` encodes its own draws over `bounds`, at
+    /// this point in the paint order
+    ///
+    /// See [`CustomDraw`] for what the callback can rely on.
+    #[cfg(target_os = "macos")]
+    pub fn paint_custom(
+        &mut self,
+        bounds: Rect,
+        draw: impl FnMut(&metal::RenderCommandEncoderRef, &CustomDrawContext) + 'static,
+    ) {
+        self.draw_list.add_custom(bounds, CustomDraw::new(draw));
+    }
+
+    /// Show a system material behind `bounds`
+    ///
+    /// Cuts a hole through everything painted so far, in this layer and the
+    /// ones below, for the material to show through. Paint the element's
+    /// content afterwards. The material and the hole both have their
+    /// corners rounded by `corner_radius`.
+    pub fn paint_material(&mut self, bounds: Rect, material: Material, corner_radius: f32) {
+        let transform = self.draw_list.current_transform();
+        let Some(region) = self.clip_hit_bounds(transform.apply_rect(bounds)) else {
+            return;
+        };
+        register_material(MaterialRegion {
+            bounds: region,
+            material,
+            corner_radius: corner_radius * transform.scale,
+        });
+        self.draw_list.add_cutout(bounds, corner_radius);
+    }
+
+    /// Blur everything painted so far under `bounds`, for frosted panels
+    ///
+    /// Paint the element's translucent background and content afterwards.
+    pub fn paint_backdrop_blur(&mut self, bounds: Rect, radius: f32, corner_radii: Corners) {
+        self.draw_list
+            .add_backdrop_blur(bounds, radius, corner_radii);
+    }
+
+    /// Draw `outline` around `bounds`, rounded to follow `corner_radii`
+    ///
+    /// The ring is outside the bounds and doesn't affect layout; see
+    /// [`Outline::focus_ring`] for the focus ring controls draw.
+    pub fn paint_outline(&mut self, bounds: Rect, corner_radii: Corners, outline: Outline) {
+        self.draw_list.add_outline(bounds, corner_radii, outline);
+    }
+
     /// Helper to create a simple filled quad
     pub fn paint_solid_quad(&mut self, bounds: Rect, color: Color) {
         self.paint_quad(PaintQuad::filled(bounds, color));
@@ -102,7 +222,8 @@ impl<'a> PaintContext<'a> {
     /// Check if a rect is visible (for culling)
     pub fn is_visible(&self, rect: &Rect) -> bool {
         if let Some(viewport) = self.draw_list.viewport() {
-            viewport.intersect(rect).is_some()
+            let rect = self.draw_list.current_transform().apply_rect(*rect);
+            viewport.intersect(&rect).is_some()
         } else {
             true
         }
@@ -127,22 +248,153 @@ impl<'a> PaintContext<'a> {
     }
 
     /// Register an element for hit testing
+    ///
+    /// Only the part inside the current clip rect can be hit. Bounds smaller
+    /// than [`min_hit_target_size`] are grown to it first.
     pub fn register_hit_test(&mut self, element_id: ElementId, bounds: Rect, z_index: i32) {
+        attribute_element_id(element_id);
         if let Some(builder) = &self.hit_test_builder {
-            // bounds are already in screen coordinates (absolute position)
-            builder.borrow_mut().add_entry(element_id, bounds, z_index);
+            // Hit testing happens in screen coordinates
+            let transform = self.draw_list.current_transform();
+            let bounds = transform.apply_rect(bounds);
+            builder.borrow_mut().add_scroll_target(element_id, bounds);
+            let bounds = grow_to_min_size(bounds, min_hit_target_size());
+            let Some(bounds) = self.clip_hit_bounds(bounds) else {
+                return;
+            };
+            builder.borrow_mut().add_scaled_entry(
+                element_id,
+                bounds,
+                z_index,
+                transform.scale,
+                false,
+            );
         }
     }
 
     /// Register a focusable element for hit testing and focus management
+    ///
+    /// Only the part inside the current clip rect can be hit, but clipped
+    /// elements stay in the focus order. Bounds smaller than
+    /// [`min_hit_target_size`] are grown to it first.
     pub fn register_focusable(&mut self, element_id: ElementId, bounds: Rect, z_index: i32) {
         if let Some(builder) = &self.hit_test_builder {
-            // bounds are already in screen coordinates (absolute position)
-            builder
-                .borrow_mut()
-                .add_focusable_entry(element_id, bounds, z_index);
+            let transform = self.draw_list.current_transform();
+            let bounds = transform.apply_rect(bounds);
+            builder.borrow_mut().add_scroll_target(element_id, bounds);
+            let bounds = grow_to_min_size(bounds, min_hit_target_size());
+            let bounds = self
+                .clip_hit_bounds(bounds)
+                .unwrap_or(Rect::from_pos_size(bounds.pos, Vec2::ZERO));
+            builder.borrow_mut().add_scaled_entry(
+                element_id,
+                bounds,
+                z_index,
+                transform.scale,
+                true,
+            );
         }
     }
+
+    /// Record where an element is so scroll containers can bring it into
+    /// view, without making it hit-testable
+    ///
+    /// Hit-testable and focusable elements are recorded already; use this
+    /// for content that skips painting while off-screen.
+    pub fn register_scroll_target(&mut self, element_id: ElementId, bounds: Rect) {
+        if let Some(builder) = &self.hit_test_builder {
+            let bounds = self.draw_list.current_transform().apply_rect(bounds);
+            builder.borrow_mut().add_scroll_target(element_id, bounds);
+        }
+    }
+
+    /// The part of screen `bounds` inside the current clip rect, if any
+    fn clip_hit_bounds(&self, bounds: Rect) -> Option<Rect> {
+        match self.draw_list.current_clip() {
+            Some(clip) => clip.intersect(&bounds),
+            None => Some(bounds),
+        }
+    }
+
+    /// Queue a quad for the layer's overlay pass
+    ///
+    /// The quad is drawn above everything else in the layer, including
+    /// elements painted after this one, but stays inside the current clip.
+    /// For transient chrome such as a selection marquee.
+    pub fn paint_overlay_quad(&mut self, quad: PaintQuad) {
+        if !self.is_visible(&quad.bounds) {
+            return;
+        }
+        let clip = self.draw_list.current_clip().copied();
+        let start = self.draw_list.commands.len();
+        self.paint_quad(quad);
+        let quad_commands = self.draw_list.commands.drain(start..);
+        match clip {
+            Some(rect) => {
+                let commands: Vec<DrawCommand> = std::iter::once(DrawCommand::PushClip { rect })
+                    .chain(quad_commands)
+                    .chain(std::iter::once(DrawCommand::PopClip))
+                    .collect();
+                self.draw_list.add_overlay(commands);
+            }
+            None => {
+                let commands: Vec<DrawCommand> = quad_commands.collect();
+                self.draw_list.add_overlay(commands);
+            }
+        }
+    }
+
+    /// Paint a text caret
+    ///
+    /// The caret blinks on the frame clock without the layer being painted
+    /// again. `last_activity` is the [`frame_time`](crate::clock::frame_time)
+    /// of the last edit or caret movement; a `None` color uses the configured
+    /// caret color.
+    pub fn paint_caret(&mut self, bounds: Rect, color: Option<Color>, last_activity: f32) {
+        if !self.is_visible(&bounds) {
+            return;
+        }
+        self.draw_list.add_caret(bounds, color, last_activity);
+    }
+
+    /// Request another frame, for elements animating on the frame clock
+    pub fn request_animation_frame(&mut self) {
+        self.draw_list.request_animation_frame();
+    }
+
+    /// Paint everything drawn by `f` through `transform`
+    ///
+    /// Bounds passed to drawing and hit testing inside `f` are in the
+    /// transformed space; they're mapped to screen coordinates as they're added.
+    pub fn with_transform(&mut self, transform: Transform, f: impl FnOnce(&mut Self)) {
+        self.draw_list.push_transform(transform);
+        f(self);
+        self.draw_list.pop_transform();
+    }
+
+    /// Capture everything painted in this layer within `bounds` to an image
+    ///
+    /// The region is rendered offscreen once the layer has finished painting,
+    /// and the returned handle holds the pixels after this frame is rendered.
+    pub fn capture_region(&mut self, bounds: Rect) -> ImageHandle {
+        let handle = ImageHandle::new();
+        self.draw_list.add_capture(CaptureRequest {
+            bounds,
+            commands: None,
+            handle: handle.clone(),
+        });
+        handle
+    }
+
+    /// Capture only the commands painted since `start`, e.g. a single subtree
+    pub fn capture_since(&mut self, start: DrawListPos, bounds: Rect, handle: &ImageHandle) {
+        let commands = self.draw_list.commands_since(start);
+        self.draw_list.add_capture(CaptureRequest {
+            bounds,
+            commands: Some(commands),
+            handle: handle.clone(),
+        });
+    }
 }
 
 /// A quad to be rendered
@@ -206,12 +458,62 @@ pub struct PaintShadow {
 pub struct PaintImage {
     /// The bounds of the image
     pub bounds: Rect,
-    /// Path or identifier for the image
-    pub source: String,
+    /// The pixels, stretched to fill the bounds
+    pub image: Arc<RgbaImage>,
     /// Corner radii for rounded images
     pub corner_radii: Corners,
 }
 
+/// What a [`CustomDraw`] callback is given to place its draws
+#[cfg(target_os = "macos")]
+pub struct CustomDrawContext<'a> {
+    /// Device for creating buffers, textures and pipelines
+    pub device: &'a metal::DeviceRef,
+    /// Bounds the command was added with, in window points
+    pub bounds: Rect,
+    /// Window size in points
+    pub screen_size: Vec2,
+    /// Pixels per point
+    pub scale_factor: f32,
+    /// Opacity the layer is drawn at
+    pub opacity: f32,
+    /// Pixel format pipelines must render to
+    pub pixel_format: metal::MTLPixelFormat,
+}
+
+/// A callback that encodes its own Metal draws at its place in a draw list
+///
+/// It's called with the layer's render encoder, its scissor set to the
+/// current clip rect; rounded clips aren't applied. The callback may bind any
+/// pipeline state, buffers and textures, since later draws rebind what they
+/// need, and the scissor is restored after it returns. Clones share the
+/// same callback.
+#[cfg(target_os = "macos")]
+#[derive(Clone)]
+pub struct CustomDraw(Rc<RefCell<dyn FnMut(&metal::RenderCommandEncoderRef, &CustomDrawContext)>>);
+
+#[cfg(target_os = "macos")]
+impl CustomDraw {
+    /// Wrap a draw callback
+    pub fn new(
+        draw: impl FnMut(&metal::RenderCommandEncoderRef, &CustomDrawContext) + 'static,
+    ) -> Self {
+        Self(Rc::new(RefCell::new(draw)))
+    }
+
+    /// Encode the callback's draws
+    pub(crate) fn draw(&self, encoder: &metal::RenderCommandEncoderRef, ctx: &CustomDrawContext) {
+        (self.0.borrow_mut())(encoder, ctx);
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl std::fmt::Debug for CustomDraw {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CustomDraw")
+    }
+}
+
 /// A draw command represents a single drawing operation
 #[derive(Debug, Clone)]
 pub enum DrawCommand {
@@ -221,21 +523,146 @@ pub enum DrawCommand {
     Text {
         position: Vec2,
         text: String,
+        /// Shaped with the style's font family, weight, axes and line
+        /// height, matching how text elements measure it
         style: TextStyle,
+        /// Placement of each shaped glyph, for text effects
+        glyph_transforms: Option<Rc<[GlyphTransform]>>,
     },
     /// Draw an SDF frame with rounded corners and optional border
     Frame { rect: Rect, style: ElementStyle },
+    /// Draw an image stretched over a rectangle
+    Image { rect: Rect, image: Arc<RgbaImage> },
     /// Push a clipping rectangle
     PushClip { rect: Rect },
+    /// Push a clipping rectangle with rounded corners
+    ///
+    /// `rect` is the rounded shape itself, not intersected with enclosing
+    /// clips.
+    PushRoundedClip { rect: Rect, corner_radii: Corners },
     /// Pop the current clipping rectangle
     PopClip,
+    /// Encode raw Metal draws over a rectangle
+    #[cfg(target_os = "macos")]
+    Custom { rect: Rect, draw: CustomDraw },
+    /// Make a rounded rectangle fully transparent, replacing what's drawn
+    /// below
+    Cutout { rect: Rect, corner_radius: f32 },
+    /// Blur what's already drawn below a rectangle, masked to its corners
+    ///
+    /// `radius` is the Gaussian standard deviation in points, as for CSS
+    /// `blur()`.
+    BackdropBlur {
+        rect: Rect,
+        radius: f32,
+        corner_radii: Corners,
+    },
+    /// Render the commands up to the matching `PopBlur` offscreen, then
+    /// draw them blurred by `radius`
+    ///
+    /// `rect` bounds the blurred content before the blur spreads it.
+    PushBlur { rect: Rect, radius: f32 },
+    /// End the content blurred by the last `PushBlur`
+    PopBlur,
+}
+
+impl DrawCommand {
+    /// Move the command by `offset`
+    pub fn translate(&mut self, offset: Vec2) {
+        match self {
+            DrawCommand::Rect { rect, .. }
+            | DrawCommand::Frame { rect, .. }
+            | DrawCommand::Image { rect, .. }
+            | DrawCommand::PushClip { rect }
+            | DrawCommand::PushRoundedClip { rect, .. }
+            | DrawCommand::Cutout { rect, .. }
+            | DrawCommand::BackdropBlur { rect, .. }
+            | DrawCommand::PushBlur { rect, .. } => rect.pos += offset,
+            #[cfg(target_os = "macos")]
+            DrawCommand::Custom { rect, .. } => rect.pos += offset,
+            DrawCommand::Text { position, .. } => *position += offset,
+            DrawCommand::PopClip | DrawCommand::PopBlur => {}
+        }
+    }
+}
+
+/// Scale glyph offsets along with the text they move
+fn scale_glyph_transforms(transforms: &[GlyphTransform], scale: f32) -> Rc<[GlyphTransform]> {
+    transforms
+        .iter()
+        .map(|t| GlyphTransform {
+            offset: t.offset * scale,
+            ..*t
+        })
+        .collect()
+}
+
+/// Scale the border, corner radii and shadow of a frame style
+fn scale_element_style(style: &mut ElementStyle, scale: f32) {
+    style.border_widths = style.border_widths.map(|width| width * scale);
+    let radii = style.corner_radii;
+    style.corner_radii = CornerRadii::new(
+        radii.top_left * scale,
+        radii.top_right * scale,
+        radii.bottom_right * scale,
+        radii.bottom_left * scale,
+    );
+    if let Some(shadow) = &mut style.shadow {
+        shadow.offset *= scale;
+        shadow.blur *= scale;
+    }
+}
+
+fn scale_corners(corners: Corners, scale: f32) -> Corners {
+    Corners::new(
+        corners.top_left * scale,
+        corners.top_right * scale,
+        corners.bottom_right * scale,
+        corners.bottom_left * scale,
+    )
+}
+
+/// A request to render part of a draw list to an image
+#[derive(Clone)]
+pub struct CaptureRequest {
+    /// Region to capture, in screen coordinates
+    pub bounds: Rect,
+    /// Commands to render, or `None` for the whole draw list
+    pub commands: Option<DrawList>,
+    /// Handle that receives the captured image
+    pub handle: ImageHandle,
+}
+
+/// A range of commands painted by an element with a z-index
+#[derive(Debug, Clone)]
+struct ZGroup {
+    start: usize,
+    end: usize,
+    z_index: i32,
+    /// Clip rect active when the group started
+    clip: Option<Rect>,
+    /// Number of enclosing groups
+    depth: usize,
 }
 
 /// A list of draw commands to be rendered
 #[derive(Clone)]
 pub struct DrawList {
     commands: Vec<DrawCommand>,
+    captures: Vec<CaptureRequest>,
+    carets: Vec<Caret>,
+    /// Commands drawn above the rest of the layer in its overlay pass
+    overlays: Vec<DrawCommand>,
     clip_stack: Vec<Rect>,
+    z_groups: Vec<ZGroup>,
+    /// Indices of the z groups still being painted
+    z_stack: Vec<usize>,
+    /// Transforms applied to commands as they're added, innermost last
+    transform_stack: Vec<Transform>,
+    /// Scale factor to snap rects and borders to device pixels at, if enabled
+    pixel_snap: Option<f32>,
+    /// Whether something painted needs another frame to keep animating
+    animation_frame_requested: bool,
     /// The viewport bounds for culling (None means no culling)
     viewport: Option<Rect>,
     /// Statistics for culling
@@ -287,11 +714,30 @@ impl DrawListPos {
     }
 }
 
+/// A marker for everything collected in a draw list so far
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DrawListMark {
+    commands: usize,
+    captures: usize,
+    carets: usize,
+    overlays: usize,
+    z_groups: usize,
+    animation_frame_requested: bool,
+}
+
 impl DrawList {
     pub fn new() -> Self {
         Self {
             commands: Vec::new(),
+            captures: Vec::new(),
+            carets: Vec::new(),
+            overlays: Vec::new(),
             clip_stack: Vec::new(),
+            z_groups: Vec::new(),
+            z_stack: Vec::new(),
+            transform_stack: Vec::new(),
+            pixel_snap: None,
+            animation_frame_requested: false,
             viewport: None,
             culling_stats: CullingStats::default(),
             debug_culling: false,
@@ -302,7 +748,15 @@ impl DrawList {
     pub fn with_viewport(viewport: Rect) -> Self {
         Self {
             commands: Vec::new(),
+            captures: Vec::new(),
+            carets: Vec::new(),
+            overlays: Vec::new(),
             clip_stack: Vec::new(),
+            z_groups: Vec::new(),
+            z_stack: Vec::new(),
+            transform_stack: Vec::new(),
+            pixel_snap: None,
+            animation_frame_requested: false,
             viewport: Some(viewport),
             culling_stats: CullingStats::default(),
             debug_culling: false,
@@ -319,6 +773,24 @@ impl DrawList {
         &self.viewport
     }
 
+    /// Snap rects, frames, clips and borders added from now on to the device
+    /// pixels of `scale_factor`, or stop snapping with `None`
+    ///
+    /// Edges at fractional device pixels are blended across two pixels, so
+    /// a 1pt line at x = 10.25 on a 2x display comes out as two faint rows.
+    /// Snapping keeps edges and thin lines crisp; text is left as shaped.
+    pub fn set_snap_to_pixels(&mut self, scale_factor: Option<f32>) {
+        self.pixel_snap = scale_factor.filter(|scale| *scale > 0.0);
+    }
+
+    /// Snap a rect in screen coordinates, if snapping is enabled
+    fn snap(&self, rect: Rect) -> Rect {
+        match self.pixel_snap {
+            Some(scale_factor) => rect.snap_to_pixels(scale_factor),
+            None => rect,
+        }
+    }
+
     /// Enable or disable debug visualization of culled elements
     pub fn set_debug_culling(&mut self, enabled: bool) {
         self.debug_culling = enabled;
@@ -352,6 +824,7 @@ impl DrawList {
         if color.alpha <= 0.0 {
             return;
         }
+        let rect = self.snap(self.current_transform().apply_rect(rect));
 
         // Skip if not visible (viewport culling)
         if !self.is_visible(&rect) {
@@ -372,6 +845,104 @@ impl DrawList {
         self.commands.push(DrawCommand::Rect { rect, color });
     }
 
+    /// Add an image stretched over `rect`
+    pub fn add_image(&mut self, rect: Rect, image: Arc<RgbaImage>) {
+        let rect = self.snap(self.current_transform().apply_rect(rect));
+
+        // Skip if not visible (viewport culling)
+        if !self.is_visible(&rect) {
+            self.culling_stats.culled_count += 1;
+            return;
+        }
+
+        self.culling_stats.rendered_count += 1;
+        self.commands.push(DrawCommand::Image { rect, image });
+    }
+
+    /// Add raw Metal draws over `rect`, see [`CustomDraw`]
+    #[cfg(target_os = "macos")]
+    pub fn add_custom(&mut self, rect: Rect, draw: CustomDraw) {
+        let rect = self.current_transform().apply_rect(rect);
+
+        // Skip if not visible (viewport culling)
+        if !self.is_visible(&rect) {
+            self.culling_stats.culled_count += 1;
+            return;
+        }
+
+        self.culling_stats.rendered_count += 1;
+        self.commands.push(DrawCommand::Custom { rect, draw });
+    }
+
+    /// Clear `rect` to transparent, including what earlier layers drew,
+    /// with its corners rounded by `corner_radius`
+    pub fn add_cutout(&mut self, rect: Rect, corner_radius: f32) {
+        let transform = self.current_transform();
+        let rect = self.snap(transform.apply_rect(rect));
+        let corner_radius = corner_radius * transform.scale;
+
+        // Skip if not visible (viewport culling)
+        if !self.is_visible(&rect) {
+            self.culling_stats.culled_count += 1;
+            return;
+        }
+
+        self.culling_stats.rendered_count += 1;
+        self.commands.push(DrawCommand::Cutout {
+            rect,
+            corner_radius,
+        });
+    }
+
+    /// Blur what's been drawn so far under `rect`, with rounded corners
+    ///
+    /// `radius` is the blur's standard deviation in points.
+    pub fn add_backdrop_blur(&mut self, rect: Rect, radius: f32, corner_radii: Corners) {
+        let transform = self.current_transform();
+        let rect = self.snap(transform.apply_rect(rect));
+
+        if radius <= 0.0 || !self.is_visible(&rect) {
+            self.culling_stats.culled_count += 1;
+            return;
+        }
+
+        self.culling_stats.rendered_count += 1;
+        self.commands.push(DrawCommand::BackdropBlur {
+            rect,
+            radius: radius * transform.scale,
+            corner_radii: scale_corners(corner_radii, transform.scale),
+        });
+    }
+
+    /// Start blurring everything drawn until the matching
+    /// [`pop_blur`](Self::pop_blur)
+    ///
+    /// `rect` bounds the content to blur; `radius` is the blur's standard
+    /// deviation in points.
+    pub fn push_blur(&mut self, rect: Rect, radius: f32) {
+        let transform = self.current_transform();
+        self.commands.push(DrawCommand::PushBlur {
+            rect: transform.apply_rect(rect),
+            radius: radius * transform.scale,
+        });
+    }
+
+    /// End the content blurred by the last [`push_blur`](Self::push_blur)
+    pub fn pop_blur(&mut self) {
+        self.commands.push(DrawCommand::PopBlur);
+    }
+
+    /// Whether any commands need the renderer to blur, splitting its passes
+    #[cfg(target_os = "macos")]
+    pub(crate) fn has_blurs(&self) -> bool {
+        self.commands.iter().any(|command| {
+            matches!(
+                command,
+                DrawCommand::BackdropBlur { .. } | DrawCommand::PushBlur { .. }
+            )
+        })
+    }
+
     /// Add text to the draw list
     ///
     /// If `measured_size` is provided, it will be used for accurate culling.
@@ -382,23 +953,69 @@ impl DrawList {
         text: impl Into<String>,
         style: TextStyle,
         measured_size: Option<Vec2>,
+    ) {
+        self.add_text_with_glyph_transforms(position, text, style, measured_size, None);
+    }
+
+    /// Add text with each shaped glyph moved, scaled or faded
+    ///
+    /// `glyph_transforms` follows the order of the shaped glyphs; glyphs
+    /// past its end are drawn in place.
+    pub fn add_text_with_glyph_transforms(
+        &mut self,
+        position: Vec2,
+        text: impl Into<String>,
+        style: TextStyle,
+        measured_size: Option<Vec2>,
+        glyph_transforms: Option<Rc<[GlyphTransform]>>,
     ) {
         let text = text.into();
         if text.is_empty() {
             return;
         }
 
+        let transform = self.current_transform();
+        let position = transform.apply(position);
+        let measured_size = measured_size.map(|size| size * transform.scale);
+        let mut style = style.scaled(transform.scale);
+        let glyph_transforms = match glyph_transforms {
+            Some(transforms) if transform.scale != 1.0 => {
+                Some(scale_glyph_transforms(&transforms, transform.scale))
+            }
+            transforms => transforms,
+        };
+
+        // Bitmaps rasterized for one size blur when scaled, so scaled text
+        // is drawn from distance fields
+        let glyphs_scaled = glyph_transforms
+            .as_ref()
+            .is_some_and(|transforms| transforms.iter().any(|t| t.scale != 1.0));
+        if style.glyph_rendering == GlyphRendering::Auto
+            && (transform.scale != 1.0 || glyphs_scaled)
+        {
+            style.glyph_rendering = GlyphRendering::DistanceField;
+        }
+
         // Use measured size if available, otherwise estimate
         let text_size = measured_size.unwrap_or_else(|| {
             // Fallback estimation: assumes average character width ~0.6x font size
             let approx_width = text.len() as f32 * style.size * 0.6;
-            let approx_height = style.size * style.line_height;
+            let approx_height = style.line_height.resolve(style.size);
             Vec2::new(approx_width, approx_height)
         });
         let text_rect = Rect::from_pos_size(position, text_size);
+        let shadow_rect = style.shadow.map(|shadow| {
+            let spread = Vec2::splat(shadow.blur);
+            Rect::from_pos_size(
+                text_rect.pos + shadow.offset - spread,
+                text_rect.size + spread * 2.0,
+            )
+        });
 
         // Skip if not visible (viewport culling)
-        if !self.is_visible(&text_rect) {
+        let visible =
+            self.is_visible(&text_rect) || shadow_rect.is_some_and(|rect| self.is_visible(&rect));
+        if !visible {
             self.culling_stats.culled_count += 1;
 
             // In debug mode, render culled text with a special style
@@ -411,21 +1028,73 @@ impl DrawList {
                     position,
                     text,
                     style: debug_style,
+                    glyph_transforms: None,
                 });
             }
             return;
         }
 
         self.culling_stats.rendered_count += 1;
+        if let (Some(shadow), Some(shadow_rect)) = (style.shadow, shadow_rect) {
+            self.push_text_shadow(
+                position,
+                &text,
+                &style,
+                shadow,
+                shadow_rect,
+                &glyph_transforms,
+            );
+        }
         self.commands.push(DrawCommand::Text {
             position,
             text,
             style,
+            glyph_transforms,
         });
     }
 
+    /// Draw the glyphs again in the shadow's color, offset and blurred,
+    /// before the text itself
+    fn push_text_shadow(
+        &mut self,
+        position: Vec2,
+        text: &str,
+        style: &TextStyle,
+        shadow: Shadow,
+        shadow_rect: Rect,
+        glyph_transforms: &Option<Rc<[GlyphTransform]>>,
+    ) {
+        if shadow.color.alpha <= 0.0 {
+            return;
+        }
+        // Like CSS, the blur radius is twice the standard deviation
+        let sigma = shadow.blur / 2.0;
+        if sigma > 0.0 {
+            self.commands.push(DrawCommand::PushBlur {
+                rect: shadow_rect,
+                radius: sigma,
+            });
+        }
+        self.commands.push(DrawCommand::Text {
+            position: position + shadow.offset,
+            text: text.to_string(),
+            style: TextStyle {
+                color: shadow.color,
+                fill: None,
+                shadow: None,
+                ..style.clone()
+            },
+            glyph_transforms: glyph_transforms.clone(),
+        });
+        if sigma > 0.0 {
+            self.commands.push(DrawCommand::PopBlur);
+        }
+    }
+
     /// Push a clipping rectangle
     pub fn push_clip(&mut self, rect: Rect) {
+        let rect = self.snap(self.current_transform().apply_rect(rect));
+
         // Calculate intersection with current clip rect if any
         let clip_rect = if let Some(current) = self.clip_stack.last() {
             match current.intersect(&rect) {
@@ -444,6 +1113,28 @@ impl DrawList {
             .push(DrawCommand::PushClip { rect: clip_rect });
     }
 
+    /// Push a clipping rectangle with rounded corners
+    ///
+    /// Content is clipped to the rounded shape, e.g. to make an image
+    /// circular. Only the innermost rounded clip rounds its corners; clips
+    /// outside it still clip to their rectangles.
+    pub fn push_rounded_clip(&mut self, rect: Rect, corner_radii: Corners) {
+        let transform = self.current_transform();
+        let rect = self.snap(transform.apply_rect(rect));
+        let corner_radii = scale_corners(corner_radii, transform.scale);
+
+        let clip_rect = match self.clip_stack.last() {
+            Some(current) => current
+                .intersect(&rect)
+                .unwrap_or(Rect::new(rect.pos.x, rect.pos.y, 0.0, 0.0)),
+            None => rect,
+        };
+
+        self.clip_stack.push(clip_rect);
+        self.commands
+            .push(DrawCommand::PushRoundedClip { rect, corner_radii });
+    }
+
     /// Pop the current clipping rectangle
     pub fn pop_clip(&mut self) {
         if self.clip_stack.pop().is_some() {
@@ -456,10 +1147,103 @@ impl DrawList {
         self.clip_stack.last()
     }
 
+    /// Start a group of commands painted at `z_index` relative to its siblings
+    ///
+    /// Groups are reordered by [`sort_by_z_index`](Self::sort_by_z_index);
+    /// commands outside any group have z-index 0, and equal z-indices keep
+    /// their paint order.
+    pub fn push_z_index(&mut self, z_index: i32) {
+        self.z_stack.push(self.z_groups.len());
+        self.z_groups.push(ZGroup {
+            start: self.commands.len(),
+            end: self.commands.len(),
+            z_index,
+            clip: self.clip_stack.last().copied(),
+            depth: self.z_stack.len() - 1,
+        });
+    }
+
+    /// End the current z-index group
+    pub fn pop_z_index(&mut self) {
+        if let Some(index) = self.z_stack.pop() {
+            self.z_groups[index].end = self.commands.len();
+        }
+    }
+
+    /// Reorder z-index groups into paint order, ready for rendering
+    ///
+    /// Moved groups are wrapped in the clip they were painted with, so they
+    /// stay clipped correctly at their new position.
+    pub fn sort_by_z_index(&mut self) {
+        // Close any groups left open
+        while !self.z_stack.is_empty() {
+            self.pop_z_index();
+        }
+        let groups = std::mem::take(&mut self.z_groups);
+        if groups.iter().all(|g| g.z_index == 0) {
+            return;
+        }
+
+        let commands = std::mem::take(&mut self.commands);
+        let mut sorted = Vec::with_capacity(commands.len());
+        let mut sources = Vec::with_capacity(commands.len());
+        reorder_by_z_index(
+            &commands,
+            0,
+            commands.len(),
+            &groups,
+            0,
+            &mut sorted,
+            &mut sources,
+        );
+        self.commands = sorted;
+
+        // Carets point at their commands' new positions
+        if !self.carets.is_empty() {
+            let mut moved_to = vec![0; commands.len()];
+            for (index, source) in sources.into_iter().enumerate() {
+                if let Some(source) = source {
+                    moved_to[source] = index;
+                }
+            }
+            for caret in &mut self.carets {
+                caret.command = moved_to[caret.command];
+            }
+        }
+    }
+
+    /// Push a transform applied to everything added until the matching pop
+    ///
+    /// Nested transforms combine with the enclosing one.
+    pub fn push_transform(&mut self, transform: Transform) {
+        let combined = self.current_transform().then(transform);
+        self.transform_stack.push(combined);
+    }
+
+    /// Pop the current transform
+    pub fn pop_transform(&mut self) {
+        self.transform_stack.pop();
+    }
+
+    /// Get the transform currently applied to added commands
+    pub fn current_transform(&self) -> Transform {
+        self.transform_stack
+            .last()
+            .copied()
+            .unwrap_or(Transform::IDENTITY)
+    }
+
     /// Clear all commands
     pub fn clear(&mut self) {
         self.commands.clear();
+        self.captures.clear();
+        self.carets.clear();
+        self.overlays.clear();
         self.clip_stack.clear();
+        self.z_groups.clear();
+        self.z_stack.clear();
+        self.transform_stack.clear();
+        self.animation_frame_requested = false;
         self.culling_stats.reset();
     }
 
@@ -483,6 +1267,143 @@ impl DrawList {
         DrawListPos(self.commands.len())
     }
 
+    /// Number of commands added so far, including overlays
+    pub(crate) fn command_count(&self) -> usize {
+        self.commands.len() + self.overlays.len()
+    }
+
+    /// Record how much of each kind of output the draw list holds
+    pub(crate) fn mark(&self) -> DrawListMark {
+        DrawListMark {
+            commands: self.commands.len(),
+            captures: self.captures.len(),
+            carets: self.carets.len(),
+            overlays: self.overlays.len(),
+            z_groups: self.z_groups.len(),
+            animation_frame_requested: self.animation_frame_requested,
+        }
+    }
+
+    /// The commands added since `mark`, if that's all that was added
+    ///
+    /// `None` if z-index groups, captures, carets or overlays were added or
+    /// another frame was requested, since replaying the commands alone
+    /// wouldn't reproduce them.
+    pub(crate) fn plain_commands_since(&self, mark: DrawListMark) -> Option<&[DrawCommand]> {
+        let now = self.mark();
+        let only_commands = DrawListMark {
+            commands: now.commands,
+            ..mark
+        };
+        (now == only_commands).then(|| &self.commands[mark.commands.min(now.commands)..])
+    }
+
+    /// Copy the commands added since `start` into a new draw list
+    pub fn commands_since(&self, start: DrawListPos) -> DrawList {
+        let mut list = DrawList::new();
+        list.commands = self.commands[start.0.min(self.commands.len())..].to_vec();
+        list
+    }
+
+    /// Make a draw list of already transformed commands
+    #[cfg(target_os = "macos")]
+    pub(crate) fn from_commands(commands: Vec<DrawCommand>) -> DrawList {
+        let mut list = DrawList::new();
+        list.commands = commands;
+        list
+    }
+
+    /// Copy the draw list with every command moved by `offset`
+    pub fn translated(&self, offset: Vec2) -> DrawList {
+        let mut list = DrawList::new();
+        list.commands = self.commands.clone();
+        for command in &mut list.commands {
+            command.translate(offset);
+        }
+        list
+    }
+
+    /// Request that part of this draw list be captured to an image
+    pub fn add_capture(&mut self, request: CaptureRequest) {
+        self.captures.push(request);
+    }
+
+    /// Take the pending capture requests
+    pub fn take_captures(&mut self) -> Vec<CaptureRequest> {
+        std::mem::take(&mut self.captures)
+    }
+
+    /// Add a caret, shown or hidden for the current frame clock time
+    ///
+    /// The caret stays at its place among the commands, so anything drawn
+    /// after it, such as a popover, covers it. Its blinking is updated with
+    /// [`update_carets`](Self::update_carets) when the list is drawn again.
+    pub fn add_caret(&mut self, bounds: Rect, color: Option<Color>, last_activity: f32) {
+        let rect = self.snap(self.current_transform().apply_rect(bounds));
+        if !self.is_visible(&rect) {
+            self.culling_stats.culled_count += 1;
+            return;
+        }
+        self.culling_stats.rendered_count += 1;
+
+        self.carets.push(Caret {
+            bounds: rect,
+            color,
+            last_activity,
+            command: self.commands.len(),
+        });
+        self.commands.push(DrawCommand::Rect {
+            rect,
+            color: TRANSPARENT,
+        });
+        self.update_carets(frame_time());
+    }
+
+    /// Carets painted into the list
+    pub fn carets(&self) -> &[Caret] {
+        &self.carets
+    }
+
+    /// Show or hide each caret for frame clock time `now`, returning when
+    /// the next one blinks
+    pub(crate) fn update_carets(&mut self, now: f32) -> Option<f32> {
+        let config = caret_config();
+        let mut next_blink: Option<f32> = None;
+        for caret in &self.carets {
+            if let Some(DrawCommand::Rect { color, .. }) = self.commands.get_mut(caret.command) {
+                *color = if config.is_visible(now, caret.last_activity) {
+                    caret.color.unwrap_or(config.color)
+                } else {
+                    TRANSPARENT
+                };
+            }
+            if let Some(at) = config.next_transition(now, caret.last_activity) {
+                next_blink = Some(next_blink.map_or(at, |next| next.min(at)));
+            }
+        }
+        next_blink
+    }
+
+    /// Queue already transformed commands for the overlay pass
+    pub fn add_overlay(&mut self, commands: impl IntoIterator<Item = DrawCommand>) {
+        self.overlays.extend(commands);
+    }
+
+    /// Take the commands queued for the overlay pass
+    pub fn take_overlays(&mut self) -> Vec<DrawCommand> {
+        std::mem::take(&mut self.overlays)
+    }
+
+    /// Ask for another frame after this one
+    pub fn request_animation_frame(&mut self) {
+        self.animation_frame_requested = true;
+    }
+
+    /// Check and reset whether another frame was requested
+    pub fn take_animation_frame_request(&mut self) -> bool {
+        std::mem::take(&mut self.animation_frame_requested)
+    }
+
     /// Get the culling statistics
     pub fn culling_stats(&self) -> &CullingStats {
         &self.culling_stats
@@ -494,26 +1415,95 @@ impl DrawList {
         if color.alpha <= 0.0 {
             return;
         }
+        let rect = self.snap(self.current_transform().apply_rect(rect));
 
         self.commands
             .insert(pos.0, DrawCommand::Rect { rect, color });
+        for group in &mut self.z_groups {
+            if group.start >= pos.0 {
+                group.start += 1;
+            }
+            if group.end > pos.0 {
+                group.end += 1;
+            }
+        }
+        for caret in &mut self.carets {
+            if caret.command >= pos.0 {
+                caret.command += 1;
+            }
+        }
+    }
+
+    /// Add an SDF frame to the draw list, with its outline on top
+    pub fn add_frame(&mut self, rect: Rect, mut style: ElementStyle) {
+        let outline = style.outline.take();
+        let radii = style.corner_radii;
+        let corner_style = style.corner_style;
+        self.push_frame(rect, style);
+        if let Some(outline) = outline {
+            let radii = Corners::new(
+                radii.top_left,
+                radii.top_right,
+                radii.bottom_right,
+                radii.bottom_left,
+            );
+            self.push_outline(rect, radii, corner_style, outline);
+        }
+    }
+
+    /// Add a ring outside `rect`, rounded to follow `corner_radii`
+    pub fn add_outline(&mut self, rect: Rect, corner_radii: Corners, outline: Outline) {
+        self.push_outline(rect, corner_radii, CornerStyle::Circular, outline);
     }
 
-    /// Add an SDF frame to the draw list
-    pub fn add_frame(&mut self, rect: Rect, style: ElementStyle) {
+    fn push_outline(
+        &mut self,
+        rect: Rect,
+        corner_radii: Corners,
+        corner_style: CornerStyle,
+        outline: Outline,
+    ) {
+        if outline.width <= 0.0 || outline.color.alpha <= 0.0 {
+            return;
+        }
+        let style = ElementStyle::new()
+            .with_background(crate::color::colors::TRANSPARENT)
+            .with_border(outline.width, outline.color)
+            .with_corner_radii(CornerRadii::new(
+                outline.outer_radius(corner_radii.top_left),
+                outline.outer_radius(corner_radii.top_right),
+                outline.outer_radius(corner_radii.bottom_right),
+                outline.outer_radius(corner_radii.bottom_left),
+            ))
+            .with_corner_style(corner_style);
+        self.push_frame(outline.outer_bounds(rect), style);
+    }
+
+    fn push_frame(&mut self, rect: Rect, mut style: ElementStyle) {
         // Skip if completely transparent
-        let has_visible_fill = match &style.fill {
-            Fill::Solid(color) => color.alpha > 0.0,
-            Fill::LinearGradient { start, end, .. } => start.alpha > 0.0 || end.alpha > 0.0,
-            Fill::RadialGradient { center, edge } => center.alpha > 0.0 || edge.alpha > 0.0,
-        };
-        let has_visible_border = style.border_width > 0.0 && style.border_color.alpha > 0.0;
+        let has_visible_fill = style.fill.is_visible();
+        let has_visible_border = style.has_visible_border();
         let has_visible_shadow = style.shadow.as_ref().map_or(false, |s| s.color.alpha > 0.0);
 
         if !has_visible_fill && !has_visible_border && !has_visible_shadow {
             return;
         }
 
+        let transform = self.current_transform();
+        let rect = self.snap(transform.apply_rect(rect));
+        scale_element_style(&mut style, transform.scale);
+        // Borders cover whole device pixels, at least one
+        if let Some(scale_factor) = self.pixel_snap {
+            let snap = |width: f32| {
+                if width > 0.0 {
+                    (width * scale_factor).round().max(1.0) / scale_factor
+                } else {
+                    0.0
+                }
+            };
+            style.border_widths = style.border_widths.map(snap);
+        }
+
         // Expand rect to account for shadow if present
         let expanded_rect = if let Some(shadow) = &style.shadow {
             let offset = shadow.offset.abs();
@@ -532,8 +1522,8 @@ impl DrawList {
                 let mut debug_style = style.clone();
                 // Make the frame semi-transparent red
                 debug_style.fill = Fill::Solid(Color::rgba(1.0, 0.0, 0.0, 0.2));
-                debug_style.border_color = Color::rgba(1.0, 0.0, 0.0, 0.5);
-                debug_style.border_width = debug_style.border_width.max(1.0);
+                debug_style.border_colors = EdgeColors::all(Color::rgba(1.0, 0.0, 0.0, 0.5));
+                debug_style.border_widths = debug_style.border_widths.map(|w| w.max(1.0));
                 self.commands.push(DrawCommand::Frame {
                     rect,
                     style: debug_style,
@@ -552,3 +1542,402 @@ impl Default for DrawList {
         Self::new()
     }
 }
+
+/// Append `commands[start..end]` to `out` with the groups at `depth` sorted
+///
+/// `sources` gets the index each appended command came from, or `None` for
+/// the clip commands added around moved groups.
+fn reorder_by_z_index(
+    commands: &[DrawCommand],
+    start: usize,
+    end: usize,
+    groups: &[ZGroup],
+    depth: usize,
+    out: &mut Vec<DrawCommand>,
+    sources: &mut Vec<Option<usize>>,
+) {
+    // Split the range into ungrouped runs (z-index 0) and child groups
+    let mut items: Vec<(i32, Option<&ZGroup>, usize, usize)> = Vec::new();
+    let mut pos = start;
+    for group in groups
+        .iter()
+        .filter(|g| g.depth == depth && g.start >= start && g.end <= end)
+    {
+        if group.start > pos {
+            items.push((0, None, pos, group.start));
+        }
+        items.push((group.z_index, Some(group), group.start, group.end));
+        pos = group.end;
+    }
+    if pos < end {
+        items.push((0, None, pos, end));
+    }
+
+    // Stable, so equal z-indices keep paint order
+    items.sort_by_key(|(z_index, ..)| *z_index);
+
+    for (z_index, group, start, end) in items {
+        let Some(group) = group else {
+            out.extend_from_slice(&commands[start..end]);
+            sources.extend((start..end).map(Some));
+            continue;
+        };
+
+        let clip = group.clip.filter(|_| z_index != 0);
+        if let Some(rect) = clip {
+            out.push(DrawCommand::PushClip { rect });
+            sources.push(None);
+        }
+        reorder_by_z_index(commands, start, end, groups, depth + 1, out, sources);
+        if clip.is_some() {
+            out.push(DrawCommand::PopClip);
+            sources.push(None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::colors;
+
+    fn rect_x(command: &DrawCommand) -> f32 {
+        match command {
+            DrawCommand::Rect { rect, .. } => rect.pos.x,
+            other => panic!("expected a rect, got {:?}", other),
+        }
+    }
+
+    fn add(list: &mut DrawList, x: f32) {
+        list.add_rect(Rect::new(x, 0.0, 1.0, 1.0), colors::BLACK);
+    }
+
+    #[test]
+    fn test_z_index_sorts_groups() {
+        let mut list = DrawList::new();
+        add(&mut list, 0.0);
+        list.push_z_index(1);
+        add(&mut list, 1.0);
+        list.pop_z_index();
+        add(&mut list, 2.0);
+        list.push_z_index(-1);
+        add(&mut list, 3.0);
+        list.pop_z_index();
+
+        list.sort_by_z_index();
+        let order: Vec<f32> = list.commands().iter().map(rect_x).collect();
+        assert_eq!(order, vec![3.0, 0.0, 2.0, 1.0]);
+    }
+
+    #[test]
+    fn test_z_index_nested_groups_sort_within_parent() {
+        let mut list = DrawList::new();
+        list.push_z_index(1);
+        list.push_z_index(5);
+        add(&mut list, 0.0);
+        list.pop_z_index();
+        add(&mut list, 1.0);
+        list.pop_z_index();
+        add(&mut list, 2.0);
+
+        list.sort_by_z_index();
+        let order: Vec<f32> = list.commands().iter().map(rect_x).collect();
+        assert_eq!(order, vec![2.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_caret_stays_under_later_content() {
+        let mut list = DrawList::new();
+        list.push_z_index(1);
+        add(&mut list, 0.0);
+        list.pop_z_index();
+        list.add_caret(Rect::new(5.0, 0.0, 2.0, 10.0), Some(colors::BLUE_500), 0.0);
+        // A popover painted after the caret
+        add(&mut list, 10.0);
+
+        list.sort_by_z_index();
+        let caret = list.carets()[0].command;
+        let order: Vec<f32> = list.commands().iter().map(rect_x).collect();
+        assert_eq!(order, vec![5.0, 10.0, 0.0]);
+        assert_eq!(caret, 0);
+    }
+
+    #[test]
+    fn test_update_carets_blinks_in_place() {
+        let mut list = DrawList::new();
+        crate::caret::set_caret_config(crate::caret::CaretConfig::default());
+        list.add_caret(Rect::new(5.0, 0.0, 2.0, 10.0), Some(colors::BLUE_500), 0.0);
+        let config = caret_config();
+        let hidden_at = config.next_transition(0.0, 0.0).unwrap();
+
+        let color = |list: &DrawList| match &list.commands()[0] {
+            DrawCommand::Rect { color, .. } => *color,
+            other => panic!("expected a rect, got {:?}", other),
+        };
+        assert_eq!(list.update_carets(0.0), Some(hidden_at));
+        assert_eq!(color(&list), colors::BLUE_500);
+        list.update_carets(hidden_at + 0.01);
+        assert_eq!(color(&list).alpha, 0.0);
+    }
+
+    #[test]
+    fn test_insert_rect_before_caret_keeps_caret_command() {
+        let mut list = DrawList::new();
+        crate::caret::set_caret_config(crate::caret::CaretConfig::default());
+        let start = list.current_pos();
+        list.add_caret(Rect::new(5.0, 0.0, 2.0, 10.0), Some(colors::BLUE_500), 0.0);
+        list.insert_rect_at(start, Rect::new(0.0, 0.0, 20.0, 10.0), colors::WHITE);
+
+        assert_eq!(list.carets()[0].command, 1);
+        list.update_carets(0.0);
+        let color = |index: usize| match &list.commands()[index] {
+            DrawCommand::Rect { color, .. } => *color,
+            other => panic!("expected a rect, got {:?}", other),
+        };
+        assert_eq!(color(0), colors::WHITE);
+        assert_eq!(color(1), colors::BLUE_500);
+    }
+
+    #[test]
+    fn test_snap_to_pixels() {
+        let mut list = DrawList::new();
+        list.add_rect(Rect::new(10.3, 0.0, 1.0, 1.0), colors::BLACK);
+        list.set_snap_to_pixels(Some(2.0));
+        list.add_rect(Rect::new(10.3, 0.0, 1.0, 1.0), colors::BLACK);
+        list.add_frame(
+            Rect::new(0.2, 0.2, 10.0, 10.0),
+            ElementStyle::new().with_border(0.3, colors::BLACK),
+        );
+
+        let commands = list.commands();
+        assert_eq!(rect_x(&commands[0]), 10.3);
+        assert_eq!(rect_x(&commands[1]), 10.5);
+        match &commands[2] {
+            DrawCommand::Frame { rect, style } => {
+                assert_eq!(*rect, Rect::new(0.0, 0.0, 10.0, 10.0));
+                assert_eq!(style.border_widths, Edges::all(0.5));
+            }
+            other => panic!("expected a frame, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_text_shadow_draws_blurred_copy_first() {
+        let mut list = DrawList::new();
+        let style = TextStyle::new().shadow(Vec2::new(0.0, 2.0), 4.0, colors::BLACK);
+        list.add_text(
+            Vec2::new(10.0, 10.0),
+            "Title",
+            style,
+            Some(Vec2::new(40.0, 20.0)),
+        );
+
+        let commands = list.commands();
+        assert_eq!(commands.len(), 4);
+        match &commands[0] {
+            DrawCommand::PushBlur { rect, radius } => {
+                assert_eq!(*rect, Rect::new(6.0, 8.0, 48.0, 28.0));
+                assert_eq!(*radius, 2.0);
+            }
+            other => panic!("expected a blur, got {:?}", other),
+        }
+        match &commands[1] {
+            DrawCommand::Text {
+                position, style, ..
+            } => {
+                assert_eq!(*position, Vec2::new(10.0, 12.0));
+                assert_eq!(style.color, colors::BLACK);
+                assert_eq!(style.shadow, None);
+            }
+            other => panic!("expected text, got {:?}", other),
+        }
+        assert!(matches!(commands[2], DrawCommand::PopBlur));
+        match &commands[3] {
+            DrawCommand::Text { position, .. } => assert_eq!(*position, Vec2::new(10.0, 10.0)),
+            other => panic!("expected text, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_scaled_text_uses_distance_fields() {
+        let mut list = DrawList::new();
+        list.add_text(Vec2::ZERO, "Plain", TextStyle::new(), None);
+        list.push_transform(Transform::new(Vec2::ZERO, 1.5));
+        list.add_text(Vec2::ZERO, "Scaled", TextStyle::new(), None);
+        list.add_text(
+            Vec2::ZERO,
+            "Bitmap",
+            TextStyle::new().glyph_rendering(GlyphRendering::Bitmap),
+            None,
+        );
+
+        let rendering: Vec<_> = list
+            .commands()
+            .iter()
+            .map(|command| match command {
+                DrawCommand::Text { style, .. } => style.glyph_rendering,
+                other => panic!("expected text, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            rendering,
+            [
+                GlyphRendering::Auto,
+                GlyphRendering::DistanceField,
+                GlyphRendering::Bitmap
+            ]
+        );
+    }
+
+    #[test]
+    fn test_outline_draws_outside_frame() {
+        let mut list = DrawList::new();
+        list.add_frame(
+            Rect::new(10.0, 10.0, 20.0, 20.0),
+            ElementStyle::new()
+                .with_corner_radius(4.0)
+                .with_outline(Outline::new(2.0, colors::BLACK).offset(1.0)),
+        );
+
+        let commands = list.commands();
+        assert_eq!(commands.len(), 2);
+        match &commands[1] {
+            DrawCommand::Frame { rect, style } => {
+                assert_eq!(*rect, Rect::new(7.0, 7.0, 26.0, 26.0));
+                assert_eq!(style.border_widths, Edges::all(2.0));
+                assert_eq!(style.corner_radii, CornerRadii::uniform(7.0));
+                assert!(!style.fill.is_visible());
+            }
+            other => panic!("expected a frame, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_outline_follows_corner_style() {
+        let mut list = DrawList::new();
+        let outline = Outline::new(2.0, colors::BLACK);
+        list.add_frame(
+            Rect::new(10.0, 10.0, 20.0, 20.0),
+            ElementStyle::new()
+                .with_corner_radius(4.0)
+                .with_corner_style(CornerStyle::Continuous)
+                .with_outline(outline),
+        );
+        list.add_outline(
+            Rect::new(10.0, 10.0, 20.0, 20.0),
+            Corners::all(4.0),
+            outline,
+        );
+
+        let corner_styles: Vec<_> = list
+            .commands()
+            .iter()
+            .map(|command| match command {
+                DrawCommand::Frame { style, .. } => style.corner_style,
+                other => panic!("expected a frame, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            corner_styles,
+            [
+                CornerStyle::Continuous,
+                CornerStyle::Continuous,
+                CornerStyle::Circular
+            ]
+        );
+    }
+
+    #[test]
+    fn test_blurs_follow_transform() {
+        let mut list = DrawList::new();
+        list.push_transform(Transform::new(Vec2::new(10.0, 0.0), 2.0));
+        list.add_backdrop_blur(Rect::new(0.0, 0.0, 5.0, 5.0), 4.0, Corners::all(1.0));
+        list.add_backdrop_blur(Rect::new(0.0, 0.0, 5.0, 5.0), 0.0, Corners::zero());
+        list.push_blur(Rect::new(0.0, 0.0, 5.0, 5.0), 3.0);
+        list.pop_blur();
+
+        #[cfg(target_os = "macos")]
+        assert!(list.has_blurs());
+        let commands = list.commands();
+        assert_eq!(commands.len(), 3);
+        match &commands[0] {
+            DrawCommand::BackdropBlur {
+                rect,
+                radius,
+                corner_radii,
+            } => {
+                assert_eq!(*rect, Rect::new(10.0, 0.0, 10.0, 10.0));
+                assert_eq!(*radius, 8.0);
+                assert_eq!(*corner_radii, Corners::all(2.0));
+            }
+            other => panic!("expected a backdrop blur, got {:?}", other),
+        }
+        assert!(matches!(commands[1], DrawCommand::PushBlur { radius, .. } if radius == 6.0));
+        assert!(matches!(commands[2], DrawCommand::PopBlur));
+    }
+
+    #[test]
+    fn test_rounded_clip() {
+        let mut list = DrawList::new();
+        list.push_clip(Rect::new(0.0, 0.0, 20.0, 20.0));
+        list.push_transform(Transform::new(Vec2::ZERO, 2.0));
+        list.push_rounded_clip(Rect::new(5.0, 5.0, 10.0, 10.0), Corners::all(5.0));
+
+        // The shape is kept whole; only the clip stack is intersected
+        assert_eq!(
+            list.current_clip(),
+            Some(&Rect::new(10.0, 10.0, 10.0, 10.0))
+        );
+        match &list.commands()[1] {
+            DrawCommand::PushRoundedClip { rect, corner_radii } => {
+                assert_eq!(*rect, Rect::new(10.0, 10.0, 20.0, 20.0));
+                assert_eq!(*corner_radii, Corners::all(10.0));
+            }
+            other => panic!("expected a rounded clip, got {:?}", other),
+        }
+
+        list.pop_clip();
+        assert_eq!(list.current_clip(), Some(&Rect::new(0.0, 0.0, 20.0, 20.0)));
+    }
+
+    #[test]
+    fn test_z_index_keeps_clip() {
+        let mut list = DrawList::new();
+        let clip = Rect::new(0.0, 0.0, 10.0, 10.0);
+        list.push_clip(clip);
+        list.push_z_index(1);
+        add(&mut list, 1.0);
+        list.pop_z_index();
+        list.pop_clip();
+        add(&mut list, 2.0);
+
+        list.sort_by_z_index();
+        let commands = list.commands();
+        assert!(matches!(commands[0], DrawCommand::PushClip { .. }));
+        assert!(matches!(commands[1], DrawCommand::PopClip));
+        assert_eq!(rect_x(&commands[2]), 2.0);
+        assert!(matches!(commands[3], DrawCommand::PushClip { rect } if rect == clip));
+        assert_eq!(rect_x(&commands[4]), 1.0);
+        assert!(matches!(commands[5], DrawCommand::PopClip));
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn test_custom_draw_keeps_paint_order() {
+        let mut list = DrawList::with_viewport(Rect::new(0.0, 0.0, 100.0, 100.0));
+        add(&mut list, 0.0);
+        list.push_transform(Transform::new(Vec2::new(10.0, 0.0), 1.0));
+        list.add_custom(Rect::new(0.0, 0.0, 5.0, 5.0), CustomDraw::new(|_, _| {}));
+        list.add_custom(Rect::new(200.0, 0.0, 5.0, 5.0), CustomDraw::new(|_, _| {}));
+        list.pop_transform();
+        add(&mut list, 2.0);
+
+        let commands = list.commands();
+        assert_eq!(commands.len(), 3);
+        assert!(matches!(
+            commands[1],
+            DrawCommand::Custom { rect, .. } if rect == Rect::new(10.0, 0.0, 5.0, 5.0)
+        ));
+        assert_eq!(list.culling_stats().culled_count, 1);
+    }
+}
@@ -0,0 +1,521 @@
+//! Frame graph: ordering render passes by the textures they share
+//!
+//! Multi-pass effects such as post-processing are described each frame as
+//! a [`FrameGraph`]. Passes declare the textures they read and write, in any
+//! order. Compiling the graph orders the passes so every texture is written
+//! before it's read, drops passes that nothing visible depends on, and packs
+//! transient textures into as few physical textures as their lifetimes allow.
+//! The backend then runs the passes in order, allocating one texture per
+//! slot.
+//!
+//! ```ignore
+//! let mut graph = FrameGraph::new();
+//! let drawable = graph.import_texture("drawable", desc);
+//! let content = graph.create_texture("content", desc);
+//! let blurred = graph.create_texture("blurred", desc);
+//! graph.add_pass("composite", &[blurred], &[drawable], Step::Composite);
+//! graph.add_pass("blur", &[content], &[blurred], Step::Blur);
+//! graph.add_pass("draw", &[], &[content], Step::Draw);
+//!
+//! let graph = graph.compile()?;
+//! for pass in graph.passes() {
+//!     // "draw", then "blur", then "composite"
+//! }
+//! ```
+//!
+//! [`CompiledFrameGraph::to_dot`] renders the graph for Graphviz, to see how
+//! a frame was put together.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::error::{SolError, SolResult};
+
+/// Pixel format of a frame graph texture
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextureFormat {
+    /// Four 8-bit channels, as drawables use
+    Bgra8,
+    /// One 8-bit channel, for masks
+    R8,
+}
+
+impl TextureFormat {
+    /// Bytes per pixel
+    pub fn bytes_per_pixel(self) -> u64 {
+        match self {
+            TextureFormat::Bgra8 => 4,
+            TextureFormat::R8 => 1,
+        }
+    }
+}
+
+/// Size in pixels and format of a texture
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextureDesc {
+    pub width: u64,
+    pub height: u64,
+    pub format: TextureFormat,
+}
+
+impl TextureDesc {
+    pub fn new(width: u64, height: u64, format: TextureFormat) -> Self {
+        Self {
+            width,
+            height,
+            format,
+        }
+    }
+
+    /// Memory the texture takes
+    pub fn bytes(&self) -> u64 {
+        self.width * self.height * self.format.bytes_per_pixel()
+    }
+}
+
+/// A texture declared in a [`FrameGraph`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextureId(usize);
+
+/// A pass added to a [`FrameGraph`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PassId(usize);
+
+#[derive(Debug)]
+struct TextureNode {
+    name: String,
+    desc: TextureDesc,
+    /// Owned outside the graph, such as the window's drawable
+    imported: bool,
+}
+
+#[derive(Debug)]
+struct PassNode<P> {
+    name: String,
+    reads: Vec<TextureId>,
+    writes: Vec<TextureId>,
+    data: P,
+}
+
+/// Render passes and the textures they read and write, for one frame
+///
+/// `P` is whatever the backend needs to encode a pass.
+#[derive(Debug)]
+pub struct FrameGraph<P> {
+    textures: Vec<TextureNode>,
+    passes: Vec<PassNode<P>>,
+}
+
+impl<P> FrameGraph<P> {
+    pub fn new() -> Self {
+        Self {
+            textures: Vec::new(),
+            passes: Vec::new(),
+        }
+    }
+
+    /// Declare a texture that only lives while passes use it
+    ///
+    /// It must be written by exactly one pass.
+    pub fn create_texture(&mut self, name: impl Into<String>, desc: TextureDesc) -> TextureId {
+        self.add_texture(name.into(), desc, false)
+    }
+
+    /// Declare a texture owned outside the graph, such as the drawable
+    ///
+    /// Passes writing imported textures are the graph's outputs: they always
+    /// run, along with every pass they depend on. Several passes may write
+    /// the same imported texture, and run in the order they were added.
+    pub fn import_texture(&mut self, name: impl Into<String>, desc: TextureDesc) -> TextureId {
+        self.add_texture(name.into(), desc, true)
+    }
+
+    fn add_texture(&mut self, name: String, desc: TextureDesc, imported: bool) -> TextureId {
+        self.textures.push(TextureNode {
+            name,
+            desc,
+            imported,
+        });
+        TextureId(self.textures.len() - 1)
+    }
+
+    /// Add a pass that reads `reads` and writes `writes`
+    pub fn add_pass(
+        &mut self,
+        name: impl Into<String>,
+        reads: &[TextureId],
+        writes: &[TextureId],
+        data: P,
+    ) -> PassId {
+        self.passes.push(PassNode {
+            name: name.into(),
+            reads: reads.to_vec(),
+            writes: writes.to_vec(),
+            data,
+        });
+        PassId(self.passes.len() - 1)
+    }
+
+    /// Order the passes, drop unused ones and assign textures to slots
+    ///
+    /// Fails if a transient texture is read but never written or written
+    /// more than once, or if passes depend on each other in a cycle.
+    pub fn compile(self) -> SolResult<CompiledFrameGraph<P>> {
+        let writers = self.writers()?;
+
+        // Each pass depends on the writers of what it reads. Passes writing
+        // the same imported texture also keep the order they were added in.
+        let mut dependencies: Vec<Vec<usize>> = vec![Vec::new(); self.passes.len()];
+        for (index, pass) in self.passes.iter().enumerate() {
+            for texture in &pass.reads {
+                dependencies[index].extend(writers[texture.0].iter().copied());
+            }
+            for texture in &pass.writes {
+                let earlier = writers[texture.0].iter().take_while(|&&w| w < index);
+                dependencies[index].extend(earlier);
+            }
+            dependencies[index].retain(|&dependency| dependency != index);
+        }
+
+        // Keep the outputs and everything they depend on
+        let mut live = vec![false; self.passes.len()];
+        let mut stack: Vec<usize> = (0..self.passes.len())
+            .filter(|&index| {
+                self.passes[index]
+                    .writes
+                    .iter()
+                    .any(|texture| self.textures[texture.0].imported)
+            })
+            .collect();
+        while let Some(index) = stack.pop() {
+            if !live[index] {
+                live[index] = true;
+                stack.extend(dependencies[index].iter().copied());
+            }
+        }
+
+        let order = self.topological_order(&dependencies, &live)?;
+        let (slots, transient_textures) = self.assign_slots(&order);
+        Ok(CompiledFrameGraph {
+            textures: self.textures,
+            passes: self.passes,
+            order,
+            slots,
+            transient_textures,
+        })
+    }
+
+    /// The passes writing each texture, in the order they were added
+    fn writers(&self) -> SolResult<Vec<Vec<usize>>> {
+        let mut writers = vec![Vec::new(); self.textures.len()];
+        for (index, pass) in self.passes.iter().enumerate() {
+            for texture in &pass.writes {
+                writers[texture.0].push(index);
+            }
+        }
+        for (texture, writers) in self.textures.iter().zip(&writers) {
+            if !texture.imported && writers.len() > 1 {
+                return Err(SolError::FrameGraph(format!(
+                    "transient texture {:?} is written by more than one pass",
+                    texture.name
+                )));
+            }
+        }
+        for pass in &self.passes {
+            for texture in &pass.reads {
+                let node = &self.textures[texture.0];
+                if !node.imported && writers[texture.0].is_empty() {
+                    return Err(SolError::FrameGraph(format!(
+                        "pass {:?} reads {:?}, which no pass writes",
+                        pass.name, node.name
+                    )));
+                }
+            }
+        }
+        Ok(writers)
+    }
+
+    /// Live passes with each after its dependencies, otherwise in the order
+    /// they were added
+    fn topological_order(
+        &self,
+        dependencies: &[Vec<usize>],
+        live: &[bool],
+    ) -> SolResult<Vec<usize>> {
+        let mut order = Vec::new();
+        let mut done = vec![false; self.passes.len()];
+        let remaining = live.iter().filter(|&&live| live).count();
+        while order.len() < remaining {
+            let next = (0..self.passes.len()).find(|&index| {
+                live[index] && !done[index] && dependencies[index].iter().all(|&d| done[d])
+            });
+            let Some(next) = next else {
+                let stuck: Vec<&str> = (0..self.passes.len())
+                    .filter(|&index| live[index] && !done[index])
+                    .map(|index| self.passes[index].name.as_str())
+                    .collect();
+                return Err(SolError::FrameGraph(format!(
+                    "passes depend on each other in a cycle: {}",
+                    stuck.join(", ")
+                )));
+            };
+            done[next] = true;
+            order.push(next);
+        }
+        Ok(order)
+    }
+
+    /// Give transient textures slots, sharing a slot between textures of
+    /// the same size and format whose lifetimes don't overlap
+    fn assign_slots(&self, order: &[usize]) -> (Vec<Option<usize>>, Vec<TextureDesc>) {
+        // First and last position in the order each texture is used
+        let mut lifetimes: Vec<Option<(usize, usize)>> = vec![None; self.textures.len()];
+        for (position, &index) in order.iter().enumerate() {
+            let pass = &self.passes[index];
+            for texture in pass.reads.iter().chain(&pass.writes) {
+                let lifetime = lifetimes[texture.0].get_or_insert((position, position));
+                lifetime.1 = position;
+            }
+        }
+
+        let mut by_first_use: Vec<usize> = (0..self.textures.len())
+            .filter(|&t| !self.textures[t].imported && lifetimes[t].is_some())
+            .collect();
+        by_first_use.sort_by_key(|&t| lifetimes[t].map(|(first, _)| first));
+
+        let mut slots = vec![None; self.textures.len()];
+        // Each slot's description and the last position it's in use
+        let mut slot_uses: Vec<(TextureDesc, usize)> = Vec::new();
+        for texture in by_first_use {
+            let (first, last) = lifetimes[texture].expect("only used textures get slots");
+            let desc = self.textures[texture].desc;
+            let free = slot_uses
+                .iter()
+                .position(|&(slot_desc, busy_until)| slot_desc == desc && busy_until < first);
+            let slot = match free {
+                Some(slot) => {
+                    slot_uses[slot].1 = last;
+                    slot
+                }
+                None => {
+                    slot_uses.push((desc, last));
+                    slot_uses.len() - 1
+                }
+            };
+            slots[texture] = Some(slot);
+        }
+        let transient_textures = slot_uses.into_iter().map(|(desc, _)| desc).collect();
+        (slots, transient_textures)
+    }
+}
+
+impl<P> Default for FrameGraph<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A pass ready to encode, from [`CompiledFrameGraph::passes`]
+#[derive(Debug)]
+pub struct CompiledPass<'a, P> {
+    pub name: &'a str,
+    pub reads: &'a [TextureId],
+    pub writes: &'a [TextureId],
+    pub data: &'a P,
+}
+
+/// A frame graph ordered and with textures assigned, ready to run
+#[derive(Debug)]
+pub struct CompiledFrameGraph<P> {
+    textures: Vec<TextureNode>,
+    passes: Vec<PassNode<P>>,
+    /// Indexes of the passes to run, in order
+    order: Vec<usize>,
+    /// Slot of each transient texture, or `None` if imported or unused
+    slots: Vec<Option<usize>>,
+    transient_textures: Vec<TextureDesc>,
+}
+
+impl<P> CompiledFrameGraph<P> {
+    /// The passes to run, in order
+    pub fn passes(&self) -> impl Iterator<Item = CompiledPass<'_, P>> {
+        self.order.iter().map(|&index| {
+            let pass = &self.passes[index];
+            CompiledPass {
+                name: &pass.name,
+                reads: &pass.reads,
+                writes: &pass.writes,
+                data: &pass.data,
+            }
+        })
+    }
+
+    /// Number of passes dropped because nothing visible depends on them
+    pub fn culled_count(&self) -> usize {
+        self.passes.len() - self.order.len()
+    }
+
+    /// The textures to allocate, one per slot
+    pub fn transient_textures(&self) -> &[TextureDesc] {
+        &self.transient_textures
+    }
+
+    /// The slot holding a transient texture, or `None` for imported textures
+    pub fn slot(&self, texture: TextureId) -> Option<usize> {
+        self.slots[texture.0]
+    }
+
+    /// The graph in Graphviz DOT format
+    ///
+    /// Passes are boxes numbered in the order they run, and culled passes are
+    /// dashed. Textures are ellipses labelled with their size and slot.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph frame {\n    rankdir=LR;\n");
+        let position: Vec<Option<usize>> = (0..self.passes.len())
+            .map(|index| self.order.iter().position(|&i| i == index))
+            .collect();
+        for (index, pass) in self.passes.iter().enumerate() {
+            let (label, style) = match position[index] {
+                Some(position) => (format!("{}. {}", position + 1, pass.name), "solid"),
+                None => (pass.name.clone(), "dashed"),
+            };
+            let _ = writeln!(
+                dot,
+                "    pass{index} [shape=box, style={style}, label={label:?}];"
+            );
+        }
+        let used: HashSet<usize> = self
+            .passes
+            .iter()
+            .flat_map(|pass| pass.reads.iter().chain(&pass.writes))
+            .map(|texture| texture.0)
+            .collect();
+        for (index, texture) in self.textures.iter().enumerate() {
+            if !used.contains(&index) {
+                continue;
+            }
+            let storage = match self.slots[index] {
+                Some(slot) => format!("slot {slot}"),
+                None if texture.imported => "imported".to_string(),
+                None => "unused".to_string(),
+            };
+            let label = format!(
+                "{}\n{}x{} {:?}, {}",
+                texture.name, texture.desc.width, texture.desc.height, texture.desc.format, storage
+            );
+            let _ = writeln!(dot, "    texture{index} [shape=ellipse, label={label:?}];");
+        }
+        for (index, pass) in self.passes.iter().enumerate() {
+            for texture in &pass.reads {
+                let _ = writeln!(dot, "    texture{} -> pass{index};", texture.0);
+            }
+            for texture in &pass.writes {
+                let _ = writeln!(dot, "    pass{index} -> texture{};", texture.0);
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn desc() -> TextureDesc {
+        TextureDesc::new(64, 64, TextureFormat::Bgra8)
+    }
+
+    fn names<P>(graph: &CompiledFrameGraph<P>) -> Vec<&str> {
+        graph.passes().map(|pass| pass.name).collect()
+    }
+
+    #[test]
+    fn test_orders_passes_by_dependencies() {
+        let mut graph = FrameGraph::new();
+        let drawable = graph.import_texture("drawable", desc());
+        let content = graph.create_texture("content", desc());
+        let blurred = graph.create_texture("blurred", desc());
+        graph.add_pass("composite", &[blurred], &[drawable], ());
+        graph.add_pass("blur", &[content], &[blurred], ());
+        graph.add_pass("draw", &[], &[content], ());
+
+        let graph = graph.compile().unwrap();
+        assert_eq!(names(&graph), vec!["draw", "blur", "composite"]);
+        assert_eq!(graph.slot(drawable), None);
+    }
+
+    #[test]
+    fn test_culls_passes_nothing_depends_on() {
+        let mut graph = FrameGraph::new();
+        let drawable = graph.import_texture("drawable", desc());
+        let content = graph.create_texture("content", desc());
+        let unused = graph.create_texture("unused", desc());
+        graph.add_pass("draw", &[], &[content], ());
+        graph.add_pass("debug", &[content], &[unused], ());
+        graph.add_pass("composite", &[content], &[drawable], ());
+
+        let graph = graph.compile().unwrap();
+        assert_eq!(names(&graph), vec!["draw", "composite"]);
+        assert_eq!(graph.culled_count(), 1);
+        assert_eq!(graph.slot(unused), None);
+    }
+
+    #[test]
+    fn test_chain_ping_pongs_between_two_textures() {
+        let mut graph = FrameGraph::new();
+        let drawable = graph.import_texture("drawable", desc());
+        let mut current = graph.create_texture("content", desc());
+        graph.add_pass("draw", &[], &[current], ());
+        for i in 0..4 {
+            let output = graph.create_texture(format!("pass {i}"), desc());
+            graph.add_pass(format!("pass {i}"), &[current], &[output], ());
+            current = output;
+        }
+        graph.add_pass("composite", &[current], &[drawable], ());
+
+        let graph = graph.compile().unwrap();
+        assert_eq!(graph.transient_textures(), &[desc(), desc()]);
+        for pass in graph.passes() {
+            if let (Some(read), Some(write)) = (pass.reads.first(), pass.writes.first()) {
+                assert_ne!(graph.slot(*read), graph.slot(*write), "{}", pass.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_imported_writers_keep_their_order() {
+        let mut graph = FrameGraph::new();
+        let drawable = graph.import_texture("drawable", desc());
+        graph.add_pass("background", &[], &[drawable], ());
+        graph.add_pass("foreground", &[], &[drawable], ());
+
+        let graph = graph.compile().unwrap();
+        assert_eq!(names(&graph), vec!["background", "foreground"]);
+        assert!(graph.to_dot().contains("\"2. foreground\""));
+    }
+
+    #[test]
+    fn test_invalid_graphs() {
+        let mut graph: FrameGraph<()> = FrameGraph::new();
+        let drawable = graph.import_texture("drawable", desc());
+        let missing = graph.create_texture("missing", desc());
+        graph.add_pass("composite", &[missing], &[drawable], ());
+        assert!(graph.compile().is_err());
+
+        let mut graph: FrameGraph<()> = FrameGraph::new();
+        let drawable = graph.import_texture("drawable", desc());
+        let a = graph.create_texture("a", desc());
+        let b = graph.create_texture("b", desc());
+        graph.add_pass("first", &[b], &[a], ());
+        graph.add_pass("second", &[a], &[b], ());
+        graph.add_pass("composite", &[a], &[drawable], ());
+        assert!(graph.compile().is_err());
+
+        let mut graph: FrameGraph<()> = FrameGraph::new();
+        let a = graph.create_texture("a", desc());
+        graph.add_pass("first", &[], &[a], ());
+        graph.add_pass("second", &[], &[a], ());
+        assert!(graph.compile().is_err());
+    }
+}
@@ -0,0 +1,123 @@
+//! Backend-independent execution of draw lists
+//!
+//! Painting produces a [`DrawList`]; a [`Renderer`] turns it into pixels.
+//! The Metal renderer draws into window drawables, and the
+//! [software renderer](crate::software_renderer::SoftwareRenderer) into
+//! images on the CPU. Code that only renders, captures and reports on draw
+//! lists can be written against the trait and run on either, or on a mock
+//! in tests. The [layer manager](crate::layer::LayerManager) renders through
+//! it, so a whole app can be drawn by either backend.
+
+use std::rc::Rc;
+
+use glam::Vec2;
+
+#[cfg(target_os = "macos")]
+use crate::platform::mac::metal_renderer::{MetalRenderer, MetalTarget};
+use crate::{
+    color::Color, debug::RendererStats, error::SolResult, geometry::Rect, image::RgbaImage,
+    post_process::PostProcess, render::DrawList, text_system::TextSystem,
+};
+
+/// How a draw list is combined with what's already in the target
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Composite {
+    /// Color to clear the target to before drawing, or `None` to draw over
+    /// its contents
+    pub clear: Option<Color>,
+    /// Opacity everything in the draw list is drawn at; the clear color is
+    /// not faded
+    pub opacity: f32,
+}
+
+impl Default for Composite {
+    fn default() -> Self {
+        Self {
+            clear: None,
+            opacity: 1.0,
+        }
+    }
+}
+
+/// The target size, scale and time of a frame being rendered
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameInfo {
+    /// Size of the target in points
+    pub size: Vec2,
+    /// Device pixels per point
+    pub scale_factor: f32,
+    /// Seconds since the app started, for animations and post-process passes
+    pub elapsed_time: f32,
+}
+
+/// A backend that executes draw lists
+pub trait Renderer {
+    /// What a draw list is rendered into, such as a drawable and the command
+    /// buffer drawing into it
+    type Target<'a>;
+
+    /// Create pipelines and other resources; call before rendering
+    fn initialize(&mut self) -> SolResult<()>;
+
+    /// Reset per-frame state; call before rendering each frame
+    fn begin_frame(&mut self);
+
+    /// Render a draw list into `target`, `screen_size` points across at
+    /// `scale_factor`
+    fn render_draw_list(
+        &mut self,
+        draw_list: &DrawList,
+        target: &mut Self::Target<'_>,
+        composite: Composite,
+        screen_size: Vec2,
+        scale_factor: f32,
+        text_system: &mut TextSystem,
+    );
+
+    /// Render a draw list through post-process passes
+    ///
+    /// Backends that can't run the passes render the list as it is, which
+    /// is what the default does.
+    fn render_post_processed(
+        &mut self,
+        draw_list: &DrawList,
+        target: &mut Self::Target<'_>,
+        composite: Composite,
+        _passes: &[Rc<dyn PostProcess>],
+        frame: FrameInfo,
+        text_system: &mut TextSystem,
+    ) {
+        self.render_draw_list(
+            draw_list,
+            target,
+            composite,
+            frame.size,
+            frame.scale_factor,
+            text_system,
+        );
+    }
+
+    /// Render `bounds` of a draw list into an image
+    fn capture(
+        &mut self,
+        draw_list: &DrawList,
+        bounds: Rect,
+        scale_factor: f32,
+        text_system: &mut TextSystem,
+    ) -> SolResult<RgbaImage>;
+
+    /// Resource usage for the frame rendered since `begin_frame`
+    fn stats(&self, text_system: &TextSystem) -> RendererStats;
+
+    /// The Metal renderer and drawable behind `target`, for layers that
+    /// encode Metal commands themselves
+    ///
+    /// `None` for other backends, which skip those layers.
+    #[cfg(target_os = "macos")]
+    fn metal<'a>(
+        &'a mut self,
+        _target: &'a Self::Target<'_>,
+    ) -> Option<(&'a mut MetalRenderer, MetalTarget<'a>)> {
+        None
+    }
+}
@@ -0,0 +1,1020 @@
+//! CPU rasterizer for draw lists
+//!
+//! [`SoftwareRenderer`] draws a [`DrawList`] into an [`RgbaImage`] without a
+//! GPU, so paint output can be rendered and checked on CI machines and in
+//! headless environments where Metal isn't available. Frames, borders,
+//! shadows, clips, blurs, images and text follow the Metal renderer's
+//! shaders. Text decorations are skipped, as are custom Metal draws, which
+//! log a warning the first time one is met.
+
+use std::collections::HashMap;
+
+use glam::Vec2;
+use parley::{FontContext, Layout, LayoutContext, PositionedLayoutItem};
+use swash::FontRef;
+use swash::scale::{Render, ScaleContext, Source};
+#[cfg(target_os = "macos")]
+use tracing::warn;
+
+use crate::{
+    color::{Color, colors::TRANSPARENT},
+    debug::RendererStats,
+    error::{SolError, SolResult},
+    geometry::{Corners, Rect},
+    image::RgbaImage,
+    render::{DrawCommand, DrawList},
+    renderer::{Composite, Renderer},
+    style::{CornerStyle, ElementStyle, Fill, TextStyle},
+    text_effect::GlyphTransform,
+    text_system::{TextConfig, TextSystem, run_color},
+};
+
+/// Renders draw lists into images on the CPU
+pub struct SoftwareRenderer {
+    font_context: FontContext,
+    layout_context: LayoutContext<[u8; 4]>,
+    scale_context: ScaleContext,
+    /// Rasterized glyphs, or `None` for glyphs without an outline
+    glyph_cache: HashMap<GlyphKey, Option<GlyphMask>>,
+    /// Commands drawn since the last `begin_frame`
+    commands_drawn: usize,
+    /// Whether a skipped custom Metal draw has been logged
+    #[cfg(target_os = "macos")]
+    warned_custom: bool,
+}
+
+impl SoftwareRenderer {
+    pub fn new() -> Self {
+        Self {
+            font_context: FontContext::new(),
+            layout_context: LayoutContext::new(),
+            scale_context: ScaleContext::new(),
+            glyph_cache: HashMap::new(),
+            commands_drawn: 0,
+            #[cfg(target_os = "macos")]
+            warned_custom: false,
+        }
+    }
+
+    /// Draw `draw_list` over `background` into an image of `size` points at
+    /// `scale_factor`
+    pub fn render(
+        &mut self,
+        draw_list: &DrawList,
+        size: Vec2,
+        scale_factor: f32,
+        background: Color,
+    ) -> RgbaImage {
+        let (width, height) = pixel_size(size, scale_factor);
+        let canvas = Canvas::new(width, height, scale_factor, background);
+        self.draw(draw_list, canvas, size).into_image()
+    }
+
+    /// Draw `draw_list` onto `base`, which covers `size` points
+    fn draw(&mut self, draw_list: &DrawList, base: Canvas, size: Vec2) -> Canvas {
+        let (width, height, scale_factor) = (base.width, base.height, base.scale_factor);
+        let opacity = base.opacity;
+        let screen = Clip {
+            rect: Rect::from_pos_size(Vec2::ZERO, size),
+            rounded: None,
+        };
+
+        // Content between `PushBlur` and `PopBlur` is drawn into its own
+        // layer, then blurred onto the one below
+        let mut layers = vec![base];
+        let mut blurs: Vec<(Rect, f32)> = Vec::new();
+        let mut clips: Vec<Clip> = Vec::new();
+
+        self.commands_drawn += draw_list.commands().len();
+        for command in draw_list.commands() {
+            let clip = clips.last().copied().unwrap_or(screen);
+            let canvas = layers.last_mut().expect("the base layer is never popped");
+            match command {
+                DrawCommand::Rect { rect, color } => {
+                    canvas.paint(*rect, &clip, |_| (*color, 1.0));
+                }
+                DrawCommand::Frame { rect, style } => draw_frame(canvas, &clip, *rect, style),
+                DrawCommand::Text {
+                    position,
+                    text,
+                    style,
+                    glyph_transforms,
+                } => self.draw_text(
+                    canvas,
+                    &clip,
+                    *position,
+                    text,
+                    style,
+                    glyph_transforms.as_deref(),
+                ),
+                DrawCommand::Image { rect, image } => {
+                    canvas.paint(*rect, &clip, |point| {
+                        let uv = (point - rect.pos) / rect.size;
+                        let x = (uv.x * image.width() as f32) as u32;
+                        let y = (uv.y * image.height() as f32) as u32;
+                        let [r, g, b, a] = image
+                            .pixel(
+                                x.min(image.width().saturating_sub(1)),
+                                y.min(image.height().saturating_sub(1)),
+                            )
+                            .unwrap_or([0; 4]);
+                        let channel = |value: u8| value as f32 / 255.0;
+                        (
+                            Color::new(channel(r), channel(g), channel(b), channel(a)),
+                            1.0,
+                        )
+                    });
+                }
+                DrawCommand::PushClip { rect } => {
+                    // Stays inside any rounded clip, as in the Metal renderer
+                    clips.push(Clip {
+                        rect: *rect,
+                        rounded: clip.rounded,
+                    });
+                }
+                DrawCommand::PushRoundedClip { rect, corner_radii } => {
+                    let clip_rect = match clips.last() {
+                        Some(parent) => parent
+                            .rect
+                            .intersect(rect)
+                            .unwrap_or(Rect::new(rect.pos.x, rect.pos.y, 0.0, 0.0)),
+                        None => *rect,
+                    };
+                    clips.push(Clip {
+                        rect: clip_rect,
+                        rounded: Some((*rect, *corner_radii)),
+                    });
+                }
+                DrawCommand::PopClip => {
+                    clips.pop();
+                }
+                // Encodes Metal commands, which have nothing to run on here
+                #[cfg(target_os = "macos")]
+                DrawCommand::Custom { rect, .. } => {
+                    if !self.warned_custom {
+                        warn!(
+                            "Skipping custom Metal draw at {:?}: not supported on the CPU",
+                            rect
+                        );
+                        self.warned_custom = true;
+                    }
+                }
+                DrawCommand::Cutout {
+                    rect,
+                    corner_radius,
+                } => canvas.cut_out(*rect, *corner_radius, &clip),
+                DrawCommand::BackdropBlur {
+                    rect,
+                    radius,
+                    corner_radii,
+                } => canvas.backdrop_blur(*rect, &clip, *radius, *corner_radii),
+                DrawCommand::PushBlur { rect, radius } => {
+                    blurs.push((*rect, *radius));
+                    layers.push(Canvas {
+                        opacity,
+                        ..Canvas::new(width, height, scale_factor, TRANSPARENT)
+                    });
+                }
+                DrawCommand::PopBlur => pop_blur(&mut layers, &mut blurs),
+            }
+        }
+
+        // Blurs that are never popped end with the list
+        while !blurs.is_empty() {
+            pop_blur(&mut layers, &mut blurs);
+        }
+        layers.pop().expect("the base layer is never popped")
+    }
+
+    /// Lay out and draw text with its top-left at `position`
+    fn draw_text(
+        &mut self,
+        canvas: &mut Canvas,
+        clip: &Clip,
+        position: Vec2,
+        text: &str,
+        style: &TextStyle,
+        transforms: Option<&[GlyphTransform]>,
+    ) {
+        if text.is_empty() {
+            return;
+        }
+        let scale_factor = canvas.scale_factor;
+
+        // Lay out in device pixels, as the text system does
+        let config = TextConfig::from(style);
+        let settings = config.font_settings();
+        let mut builder =
+            self.layout_context
+                .ranged_builder(&mut self.font_context, text, scale_factor, true);
+        config.push_styles(&mut builder, &settings);
+        let mut layout: Layout<[u8; 4]> = builder.build(text);
+        layout.break_all_lines(None);
+
+        let bounds = Rect::from_pos_size(
+            position,
+            Vec2::new(layout.width(), layout.height()) / scale_factor,
+        );
+        let mut index = 0;
+        for line in layout.lines() {
+            for item in line.items() {
+                let PositionedLayoutItem::GlyphRun(glyph_run) = item else {
+                    continue;
+                };
+                let run = glyph_run.run();
+                let font = run.font();
+                let Some(font_ref) = FontRef::from_index(font.data.as_ref(), font.index as usize)
+                else {
+                    continue;
+                };
+                let mut scaler = self
+                    .scale_context
+                    .builder(font_ref)
+                    .size(run.font_size())
+                    .hint(true)
+                    .normalized_coords(run.normalized_coords())
+                    .build();
+
+                let run_color = run_color(&glyph_run, &config.color_ranges);
+                let mut run_x = glyph_run.offset();
+                let run_y = glyph_run.baseline();
+                for glyph in glyph_run.glyphs() {
+                    let origin =
+                        position + Vec2::new(run_x + glyph.x, run_y - glyph.y) / scale_factor;
+                    run_x += glyph.advance;
+                    let transform = transforms
+                        .and_then(|transforms| transforms.get(index))
+                        .copied()
+                        .unwrap_or(GlyphTransform::IDENTITY);
+                    index += 1;
+                    if transform.opacity <= 0.0 || transform.scale <= 0.0 {
+                        continue;
+                    }
+
+                    let key = GlyphKey {
+                        font: font.data.id(),
+                        index: font.index,
+                        coords: run.normalized_coords().to_vec(),
+                        glyph_id: glyph.id,
+                        size: run.font_size().to_bits(),
+                    };
+                    let mask = self.glyph_cache.entry(key).or_insert_with(|| {
+                        Render::new(&[Source::Outline])
+                            .format(swash::zeno::Format::Alpha)
+                            .render(&mut scaler, glyph.id)
+                            .map(|image| GlyphMask {
+                                left: image.placement.left,
+                                top: image.placement.top,
+                                width: image.placement.width,
+                                height: image.placement.height,
+                                data: image.data,
+                            })
+                    });
+                    let Some(mask) = mask else {
+                        continue;
+                    };
+
+                    // Bitmap offsets are in device pixels; scale around the
+                    // glyph's center, then move it
+                    let size = Vec2::new(mask.width as f32, mask.height as f32) / scale_factor;
+                    let top_left =
+                        origin + Vec2::new(mask.left as f32, -mask.top as f32) / scale_factor;
+                    let rect = Rect::from_pos_size(
+                        top_left + size * (1.0 - transform.scale) / 2.0 + transform.offset,
+                        size * transform.scale,
+                    );
+                    canvas.paint(rect, clip, |point| {
+                        let color = match (&style.fill, run_color) {
+                            (Some(Fill::Solid(_)) | None, Some(color)) => color,
+                            (Some(fill), _) => fill_color(fill, point - bounds.pos, bounds.size),
+                            (None, None) => style.color,
+                        };
+                        let coverage = mask.sample((point - rect.pos) / rect.size);
+                        (color, coverage * transform.opacity)
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl Renderer for SoftwareRenderer {
+    type Target<'a> = RgbaImage;
+
+    fn initialize(&mut self) -> SolResult<()> {
+        Ok(())
+    }
+
+    fn begin_frame(&mut self) {
+        self.commands_drawn = 0;
+    }
+
+    /// Draw over the image's contents, or over the clear color, resizing the
+    /// image first if it doesn't match the screen
+    ///
+    /// Text is laid out and rasterized here rather than taken from the text
+    /// system's atlas, so a [headless](TextSystem::headless) one will do.
+    fn render_draw_list(
+        &mut self,
+        draw_list: &DrawList,
+        target: &mut RgbaImage,
+        composite: Composite,
+        screen_size: Vec2,
+        scale_factor: f32,
+        _text_system: &mut TextSystem,
+    ) {
+        let (width, height) = pixel_size(screen_size, scale_factor);
+        if (target.width(), target.height()) != (width, height) {
+            *target = RgbaImage::new(width, height);
+        }
+        let canvas = match composite.clear {
+            Some(color) => Canvas::new(width, height, scale_factor, color),
+            None => Canvas::from_image(target, scale_factor),
+        };
+        let canvas = Canvas {
+            opacity: composite.opacity,
+            ..canvas
+        };
+        *target = self.draw(draw_list, canvas, screen_size).into_image();
+    }
+
+    fn capture(
+        &mut self,
+        draw_list: &DrawList,
+        bounds: Rect,
+        scale_factor: f32,
+        _text_system: &mut TextSystem,
+    ) -> SolResult<RgbaImage> {
+        let (width, height) = pixel_size(bounds.size, scale_factor);
+        if width == 0 || height == 0 {
+            return Err(SolError::Image(
+                "Cannot capture an empty region".to_string(),
+            ));
+        }
+        let local = draw_list.translated(-bounds.pos);
+        Ok(self.render(&local, bounds.size, scale_factor, TRANSPARENT))
+    }
+
+    fn stats(&self, _text_system: &TextSystem) -> RendererStats {
+        // Each command is drawn directly, standing in for a draw call
+        RendererStats {
+            draw_calls: self.commands_drawn,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for SoftwareRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Identifies a rasterized glyph: its font, variable axis values, glyph
+/// and size in device pixels
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct GlyphKey {
+    font: u64,
+    index: u32,
+    coords: Vec<i16>,
+    glyph_id: u16,
+    size: u32,
+}
+
+/// A glyph's coverage, rasterized at device pixels
+struct GlyphMask {
+    /// Offset from the glyph origin to the top-left of the bitmap
+    left: i32,
+    top: i32,
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+}
+
+impl GlyphMask {
+    /// Coverage at `uv` across the bitmap, from 0.0 to 1.0
+    fn sample(&self, uv: Vec2) -> f32 {
+        if self.width == 0 || self.height == 0 {
+            return 0.0;
+        }
+        let x = ((uv.x * self.width as f32) as u32).min(self.width - 1);
+        let y = ((uv.y * self.height as f32) as u32).min(self.height - 1);
+        self.data[(y * self.width + x) as usize] as f32 / 255.0
+    }
+}
+
+/// A clip rectangle, and the rounded clip it's inside of, in points
+#[derive(Debug, Clone, Copy)]
+struct Clip {
+    rect: Rect,
+    rounded: Option<(Rect, Corners)>,
+}
+
+impl Clip {
+    /// How much of `point` the rounded clip lets through
+    fn coverage(&self, point: Vec2, scale_factor: f32) -> f32 {
+        let Some((rect, radii)) = self.rounded else {
+            return 1.0;
+        };
+        let half_size = rect.size / 2.0;
+        let d = sd_rounded_rect(point - rect.pos - half_size, half_size, corner_radii(radii));
+        let aa = 0.5 / scale_factor;
+        1.0 - smoothstep(-aa, aa, d)
+    }
+}
+
+/// Premultiplied RGBA pixels being drawn into
+struct Canvas {
+    width: u32,
+    height: u32,
+    scale_factor: f32,
+    /// Opacity everything painted is drawn at
+    opacity: f32,
+    pixels: Vec<[f32; 4]>,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32, scale_factor: f32, background: Color) -> Self {
+        let a = background.alpha;
+        Self {
+            width,
+            height,
+            scale_factor,
+            opacity: 1.0,
+            pixels: vec![
+                [
+                    background.red * a,
+                    background.green * a,
+                    background.blue * a,
+                    a
+                ];
+                width as usize * height as usize
+            ],
+        }
+    }
+
+    /// A canvas holding an image's pixels
+    fn from_image(image: &RgbaImage, scale_factor: f32) -> Self {
+        let pixels = image
+            .as_raw()
+            .chunks_exact(4)
+            .map(|pixel| {
+                let a = pixel[3] as f32 / 255.0;
+                let channel = |value: u8| value as f32 / 255.0 * a;
+                [channel(pixel[0]), channel(pixel[1]), channel(pixel[2]), a]
+            })
+            .collect();
+        Self {
+            width: image.width(),
+            height: image.height(),
+            scale_factor,
+            opacity: 1.0,
+            pixels,
+        }
+    }
+
+    /// Device pixels whose centers fall in `rect`, as `(x0, y0, x1, y1)`
+    fn pixel_bounds(&self, rect: Rect) -> (u32, u32, u32, u32) {
+        let min = (rect.min() * self.scale_factor - 0.5)
+            .ceil()
+            .max(Vec2::ZERO);
+        let max = (rect.max() * self.scale_factor - 0.5)
+            .ceil()
+            .max(Vec2::ZERO);
+        (
+            (min.x as u32).min(self.width),
+            (min.y as u32).min(self.height),
+            (max.x as u32).min(self.width),
+            (max.y as u32).min(self.height),
+        )
+    }
+
+    /// Blend `shade` over each pixel under `rect` and `clip`
+    ///
+    /// `shade` takes a pixel's center in points and returns its color and
+    /// coverage.
+    fn paint(&mut self, rect: Rect, clip: &Clip, mut shade: impl FnMut(Vec2) -> (Color, f32)) {
+        let Some(area) = rect.intersect(&clip.rect) else {
+            return;
+        };
+        let (x0, y0, x1, y1) = self.pixel_bounds(area);
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let point = Vec2::new(x as f32 + 0.5, y as f32 + 0.5) / self.scale_factor;
+                let clip_coverage = clip.coverage(point, self.scale_factor);
+                if clip_coverage <= 0.0 {
+                    continue;
+                }
+                let (color, coverage) = shade(point);
+                let alpha = color.alpha * coverage * clip_coverage * self.opacity;
+                if alpha <= 0.0 {
+                    continue;
+                }
+                let pixel = &mut self.pixels[(y * self.width + x) as usize];
+                let rest = 1.0 - alpha;
+                *pixel = [
+                    color.red * alpha + pixel[0] * rest,
+                    color.green * alpha + pixel[1] * rest,
+                    color.blue * alpha + pixel[2] * rest,
+                    alpha + pixel[3] * rest,
+                ];
+            }
+        }
+    }
+
+    /// Make `rect` transparent, replacing what's drawn below
+    fn cut_out(&mut self, rect: Rect, corner_radius: f32, clip: &Clip) {
+        let Some(area) = rect.intersect(&clip.rect) else {
+            return;
+        };
+        let shape = Clip {
+            rect,
+            rounded: Some((rect, Corners::all(corner_radius))),
+        };
+        let (x0, y0, x1, y1) = self.pixel_bounds(area);
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let point = Vec2::new(x as f32 + 0.5, y as f32 + 0.5) / self.scale_factor;
+                let keep = 1.0
+                    - clip.coverage(point, self.scale_factor)
+                        * shape.coverage(point, self.scale_factor);
+                let pixel = &mut self.pixels[(y * self.width + x) as usize];
+                *pixel = pixel.map(|channel| channel * keep);
+            }
+        }
+    }
+
+    /// Blur what's drawn under `rect`, masked to its corners
+    ///
+    /// `radius` is the Gaussian standard deviation in points.
+    fn backdrop_blur(&mut self, rect: Rect, clip: &Clip, radius: f32, corner_radii: Corners) {
+        if radius <= 0.0 {
+            return;
+        }
+        let mut blurred = Canvas {
+            pixels: self.pixels.clone(),
+            ..*self
+        };
+        blurred.blur(rect, radius);
+
+        let Some(area) = rect.intersect(&clip.rect) else {
+            return;
+        };
+        let shape = Clip {
+            rect,
+            rounded: Some((rect, corner_radii)),
+        };
+        let (x0, y0, x1, y1) = self.pixel_bounds(area);
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let point = Vec2::new(x as f32 + 0.5, y as f32 + 0.5) / self.scale_factor;
+                let t = clip.coverage(point, self.scale_factor)
+                    * shape.coverage(point, self.scale_factor);
+                let i = (y * self.width + x) as usize;
+                let (pixel, target) = (&mut self.pixels[i], blurred.pixels[i]);
+                for (channel, target) in pixel.iter_mut().zip(target) {
+                    *channel += (target - *channel) * t;
+                }
+            }
+        }
+    }
+
+    /// Gaussian blur everything within three standard deviations of `rect`
+    ///
+    /// `radius` is the standard deviation in points. Samples past the
+    /// blurred area repeat its edge, like a clamped texture.
+    fn blur(&mut self, rect: Rect, radius: f32) {
+        let sigma = radius * self.scale_factor;
+        if sigma <= 0.0 {
+            return;
+        }
+        let kernel_radius = (sigma * 3.0).ceil() as i32;
+        let weights: Vec<f32> = (-kernel_radius..=kernel_radius)
+            .map(|i| (-(i * i) as f32 / (2.0 * sigma * sigma)).exp())
+            .collect();
+        let total: f32 = weights.iter().sum();
+        let weights: Vec<f32> = weights.iter().map(|weight| weight / total).collect();
+
+        let (x0, y0, x1, y1) = self.pixel_bounds(rect.expand(radius * 3.0));
+        if x0 >= x1 || y0 >= y1 {
+            return;
+        }
+        let (x0, y0, x1, y1) = (x0 as i32, y0 as i32, x1 as i32, y1 as i32);
+        let width = self.width as i32;
+        let mut pass = |horizontal: bool| {
+            let source = self.pixels.clone();
+            for y in y0..y1 {
+                for x in x0..x1 {
+                    let mut sum = [0.0; 4];
+                    for (i, weight) in (-kernel_radius..=kernel_radius).zip(&weights) {
+                        let (sx, sy) = if horizontal {
+                            ((x + i).clamp(x0, x1 - 1), y)
+                        } else {
+                            (x, (y + i).clamp(y0, y1 - 1))
+                        };
+                        let sample = source[(sy * width + sx) as usize];
+                        for (sum, channel) in sum.iter_mut().zip(sample) {
+                            *sum += channel * weight;
+                        }
+                    }
+                    self.pixels[(y * width + x) as usize] = sum;
+                }
+            }
+        };
+        pass(true);
+        pass(false);
+    }
+
+    /// Draw `layer` over this canvas
+    fn composite(&mut self, layer: &Canvas) {
+        for (pixel, top) in self.pixels.iter_mut().zip(&layer.pixels) {
+            let rest = 1.0 - top[3];
+            for (channel, top) in pixel.iter_mut().zip(top) {
+                *channel = top + *channel * rest;
+            }
+        }
+    }
+
+    fn into_image(self) -> RgbaImage {
+        let data = self
+            .pixels
+            .iter()
+            .flat_map(|&[r, g, b, a]| {
+                let unpremultiply = if a > 0.0 { 1.0 / a } else { 0.0 };
+                let byte = |channel: f32| (channel.clamp(0.0, 1.0) * 255.0).round() as u8;
+                [
+                    byte(r * unpremultiply),
+                    byte(g * unpremultiply),
+                    byte(b * unpremultiply),
+                    byte(a),
+                ]
+            })
+            .collect();
+        RgbaImage::from_raw(self.width, self.height, data)
+            .expect("the canvas has four channels per pixel")
+    }
+}
+
+/// Device pixels covering `size` points at `scale_factor`
+fn pixel_size(size: Vec2, scale_factor: f32) -> (u32, u32) {
+    let pixels = (size * scale_factor).round().max(Vec2::ZERO);
+    (pixels.x as u32, pixels.y as u32)
+}
+
+/// Blur the top layer and draw it onto the one below
+fn pop_blur(layers: &mut Vec<Canvas>, blurs: &mut Vec<(Rect, f32)>) {
+    let Some((rect, radius)) = blurs.pop() else {
+        return;
+    };
+    if layers.len() < 2 {
+        return;
+    }
+    let mut layer = layers.pop().expect("checked above");
+    layer.blur(rect, radius);
+    layers.last_mut().expect("checked above").composite(&layer);
+}
+
+/// Draw a frame the way the frame shader does: shadow, fill, then border
+fn draw_frame(canvas: &mut Canvas, clip: &Clip, rect: Rect, style: &ElementStyle) {
+    let aa = 0.5 / canvas.scale_factor;
+    let half_size = rect.size / 2.0;
+    let center = rect.pos + half_size;
+    let radii = [
+        style.corner_radii.top_left,
+        style.corner_radii.top_right,
+        style.corner_radii.bottom_right,
+        style.corner_radii.bottom_left,
+    ];
+    let sd = |p: Vec2, half_size: Vec2, radii: [f32; 4]| {
+        sd_frame(p, half_size, radii, style.corner_style)
+    };
+
+    if let Some(shadow) = style.shadow.filter(|shadow| shadow.color.alpha > 0.0) {
+        let area = Rect::from_pos_size(rect.pos + shadow.offset, rect.size).expand(shadow.blur);
+        canvas.paint(area, clip, |point| {
+            let d = sd(point - center - shadow.offset, half_size, radii);
+            let coverage = if shadow.blur > 0.0 {
+                1.0 - smoothstep(-shadow.blur, shadow.blur, d)
+            } else if d <= 0.0 {
+                1.0
+            } else {
+                0.0
+            };
+            (shadow.color, coverage)
+        });
+    }
+
+    let widths = style.border_widths;
+    let has_border = [widths.top, widths.right, widths.bottom, widths.left]
+        .iter()
+        .any(|&width| width > 0.0);
+    canvas.paint(rect, clip, |point| {
+        let p = point - center;
+        let fill_mask = 1.0 - smoothstep(-aa, aa, sd(p, half_size, radii));
+        let mut color = fill_color(&style.fill, point - rect.pos, rect.size);
+        if has_border {
+            // The inside of the border is the frame inset by each edge's
+            // width, with the radii shrunk to match
+            let inner_offset =
+                Vec2::new(widths.left - widths.right, widths.top - widths.bottom) / 2.0;
+            let inner_half_size = (half_size
+                - Vec2::new(widths.left + widths.right, widths.top + widths.bottom) / 2.0)
+                .max(Vec2::ZERO);
+            let inner_radii = [
+                (radii[0] - widths.left.max(widths.top)).max(0.0),
+                (radii[1] - widths.right.max(widths.top)).max(0.0),
+                (radii[2] - widths.right.max(widths.bottom)).max(0.0),
+                (radii[3] - widths.left.max(widths.bottom)).max(0.0),
+            ];
+            let inner_d = sd(p - inner_offset, inner_half_size, inner_radii);
+            let border_mask = smoothstep(-aa, aa, inner_d) * fill_mask;
+            color = mix(color, border_color(p, half_size, style), border_mask);
+        }
+        (color, fill_mask)
+    });
+}
+
+/// The color of the edge `p` is deepest into relative to that edge's width,
+/// so corners split along the diagonal between the outer and inner corner
+fn border_color(p: Vec2, half_size: Vec2, style: &ElementStyle) -> Color {
+    let widths = style.border_widths;
+    let colors = style.border_colors;
+    let t_top = (p.y + half_size.y) / widths.top.max(0.0001);
+    let t_right = (half_size.x - p.x) / widths.right.max(0.0001);
+    let t_bottom = (half_size.y - p.y) / widths.bottom.max(0.0001);
+    let t_left = (p.x + half_size.x) / widths.left.max(0.0001);
+    if t_top.min(t_bottom) <= t_left.min(t_right) {
+        if t_top < t_bottom {
+            colors.top
+        } else {
+            colors.bottom
+        }
+    } else if t_left < t_right {
+        colors.left
+    } else {
+        colors.right
+    }
+}
+
+/// The fill's color at `point`, relative to the top-left of bounds of `size`
+fn fill_color(fill: &Fill, point: Vec2, size: Vec2) -> Color {
+    match fill {
+        Fill::Solid(color) => *color,
+        Fill::Gradient(gradient) => gradient.color_at(gradient.position_at(point, size)),
+    }
+}
+
+fn mix(from: Color, to: Color, t: f32) -> Color {
+    Color::new(
+        from.red + (to.red - from.red) * t,
+        from.green + (to.green - from.green) * t,
+        from.blue + (to.blue - from.blue) * t,
+        from.alpha + (to.alpha - from.alpha) * t,
+    )
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn corner_radii(corners: Corners) -> [f32; 4] {
+    [
+        corners.top_left,
+        corners.top_right,
+        corners.bottom_right,
+        corners.bottom_left,
+    ]
+}
+
+/// The radius of the corner in `p`'s quadrant, with y pointing down
+fn quadrant_radius(p: Vec2, radii: [f32; 4]) -> f32 {
+    match (p.x > 0.0, p.y > 0.0) {
+        (false, false) => radii[0],
+        (true, false) => radii[1],
+        (true, true) => radii[2],
+        (false, true) => radii[3],
+    }
+}
+
+/// Distance from `p`, relative to the center, to a rounded rectangle's edge
+fn sd_rounded_rect(p: Vec2, half_size: Vec2, radii: [f32; 4]) -> f32 {
+    let radius = quadrant_radius(p, radii);
+    let q = p.abs() - half_size + radius;
+    q.x.max(q.y).min(0.0) + q.max(Vec2::ZERO).length() - radius
+}
+
+/// Distance to a rectangle with continuous corners, approximated by a
+/// superellipse of degree 5 as in the frame shader
+fn sd_continuous_rect(p: Vec2, half_size: Vec2, radii: [f32; 4]) -> f32 {
+    let radius = quadrant_radius(p, radii);
+    let extent = (radius * 1.528).min(half_size.x.min(half_size.y));
+    let q = p.abs() - half_size + extent;
+    let m = q.max(Vec2::ZERO) / extent.max(0.0001);
+    let corner = (m.x.powi(5) + m.y.powi(5)).powf(0.2) * extent;
+    q.x.max(q.y).min(0.0) + corner - extent
+}
+
+fn sd_frame(p: Vec2, half_size: Vec2, radii: [f32; 4], corner_style: CornerStyle) -> f32 {
+    match corner_style {
+        CornerStyle::Circular => sd_rounded_rect(p, half_size, radii),
+        CornerStyle::Continuous => sd_continuous_rect(p, half_size, radii),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::colors;
+
+    fn render(list: &DrawList, size: Vec2, scale_factor: f32) -> RgbaImage {
+        SoftwareRenderer::new().render(list, size, scale_factor, colors::TRANSPARENT)
+    }
+
+    #[test]
+    fn test_rect_covers_pixels_at_scale() {
+        let mut list = DrawList::new();
+        list.add_rect(Rect::new(1.0, 1.0, 2.0, 2.0), colors::RED);
+
+        let image = render(&list, Vec2::new(4.0, 4.0), 2.0);
+        assert_eq!((image.width(), image.height()), (8, 8));
+        assert_eq!(image.pixel(2, 2), Some([255, 0, 0, 255]));
+        assert_eq!(image.pixel(5, 5), Some([255, 0, 0, 255]));
+        assert_eq!(image.pixel(1, 1), Some([0, 0, 0, 0]));
+        assert_eq!(image.pixel(6, 6), Some([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn test_frame_rounds_corners_and_draws_borders() {
+        let mut list = DrawList::new();
+        let style = ElementStyle::new()
+            .with_background(colors::WHITE)
+            .with_border_top(2.0, colors::BLUE)
+            .with_corner_radius(5.0);
+        list.add_frame(Rect::new(0.0, 0.0, 20.0, 20.0), style);
+
+        let image = render(&list, Vec2::new(20.0, 20.0), 1.0);
+        // The corner is cut away
+        assert_eq!(image.pixel(0, 0).unwrap()[3], 0);
+        // The top edge is the border color, and the middle is filled
+        assert_eq!(image.pixel(10, 0), Some([0, 0, 255, 255]));
+        assert_eq!(image.pixel(10, 10), Some([255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn test_frame_draws_continuous_corners() {
+        let frame = |corner_style| {
+            let mut list = DrawList::new();
+            let style = ElementStyle::new()
+                .with_background(colors::WHITE)
+                .with_corner_radius(10.0)
+                .with_corner_style(corner_style);
+            list.add_frame(Rect::new(0.0, 0.0, 40.0, 40.0), style);
+            render(&list, Vec2::new(40.0, 40.0), 1.0)
+        };
+
+        // Continuous corners start curving further along the edges but
+        // reach closer to the corner along the diagonal
+        let circular = frame(CornerStyle::Circular);
+        let continuous = frame(CornerStyle::Continuous);
+        assert_eq!(circular.pixel(2, 2).unwrap()[3], 0);
+        assert_eq!(continuous.pixel(2, 2).unwrap()[3], 255);
+        assert_eq!(continuous.pixel(0, 0).unwrap()[3], 0);
+    }
+
+    #[test]
+    fn test_clips() {
+        let mut list = DrawList::new();
+        list.push_clip(Rect::new(0.0, 0.0, 5.0, 10.0));
+        list.add_rect(Rect::new(0.0, 0.0, 10.0, 10.0), colors::GREEN);
+        list.pop_clip();
+        list.push_rounded_clip(Rect::new(0.0, 10.0, 10.0, 10.0), Corners::all(5.0));
+        list.add_rect(Rect::new(0.0, 10.0, 10.0, 10.0), colors::GREEN);
+        list.pop_clip();
+
+        let image = render(&list, Vec2::new(10.0, 20.0), 1.0);
+        assert_eq!(image.pixel(2, 5).unwrap()[3], 255);
+        assert_eq!(image.pixel(7, 5).unwrap()[3], 0);
+        assert_eq!(image.pixel(0, 10).unwrap()[3], 0);
+        assert_eq!(image.pixel(5, 15).unwrap()[3], 255);
+    }
+
+    #[test]
+    fn test_blur_spreads_content() {
+        let mut list = DrawList::new();
+        list.push_blur(Rect::new(10.0, 10.0, 10.0, 10.0), 2.0);
+        list.add_rect(Rect::new(10.0, 10.0, 10.0, 10.0), colors::BLACK);
+        list.pop_blur();
+
+        let image = render(&list, Vec2::new(30.0, 30.0), 1.0);
+        let alpha = |x, y| image.pixel(x, y).unwrap()[3];
+        assert!(alpha(8, 15) > 0);
+        assert!(alpha(10, 15) < 255);
+        assert!(alpha(15, 15) > 240);
+        assert_eq!(alpha(0, 0), 0);
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn test_custom_draws_are_skipped() {
+        use crate::render::CustomDraw;
+
+        let mut list = DrawList::new();
+        list.add_custom(Rect::new(0.0, 0.0, 4.0, 4.0), CustomDraw::new(|_, _| {}));
+        list.add_rect(Rect::new(2.0, 0.0, 2.0, 4.0), colors::RED);
+
+        let mut renderer = SoftwareRenderer::new();
+        let image = renderer.render(&list, Vec2::new(4.0, 4.0), 1.0, colors::TRANSPARENT);
+        assert!(renderer.warned_custom);
+        // Drawing carries on past the skipped command
+        assert_eq!(image.pixel(1, 1), Some([0, 0, 0, 0]));
+        assert_eq!(image.pixel(3, 1), Some([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_cutout_clears_pixels() {
+        let mut list = DrawList::new();
+        list.add_rect(Rect::new(0.0, 0.0, 4.0, 4.0), colors::RED);
+        list.add_cutout(Rect::new(0.0, 0.0, 2.0, 4.0), 0.0);
+
+        let image = render(&list, Vec2::new(4.0, 4.0), 1.0);
+        assert_eq!(image.pixel(1, 1), Some([0, 0, 0, 0]));
+        assert_eq!(image.pixel(3, 1), Some([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_cutout_rounds_corners() {
+        let mut list = DrawList::new();
+        list.add_rect(Rect::new(0.0, 0.0, 20.0, 20.0), colors::RED);
+        list.add_cutout(Rect::new(0.0, 0.0, 20.0, 20.0), 8.0);
+
+        let image = render(&list, Vec2::new(20.0, 20.0), 1.0);
+        // The corner outside the radius keeps what was drawn
+        assert_eq!(image.pixel(0, 0), Some([255, 0, 0, 255]));
+        assert_eq!(image.pixel(10, 10), Some([0, 0, 0, 0]));
+        assert_eq!(image.pixel(10, 0), Some([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn test_renderer_trait_draws_over_target_and_captures() {
+        let mut renderer = SoftwareRenderer::new();
+        let mut text_system = TextSystem::headless();
+        renderer.initialize().unwrap();
+        renderer.begin_frame();
+
+        let mut first = DrawList::new();
+        first.add_rect(Rect::new(0.0, 0.0, 2.0, 4.0), colors::RED);
+        let mut second = DrawList::new();
+        second.add_rect(Rect::new(2.0, 0.0, 2.0, 4.0), colors::BLUE);
+
+        let mut target = RgbaImage::new(1, 1);
+        let size = Vec2::new(4.0, 4.0);
+        let over = Composite::default();
+        renderer.render_draw_list(&first, &mut target, over, size, 1.0, &mut text_system);
+        renderer.render_draw_list(&second, &mut target, over, size, 1.0, &mut text_system);
+        assert_eq!((target.width(), target.height()), (4, 4));
+        assert_eq!(target.pixel(1, 1), Some([255, 0, 0, 255]));
+        assert_eq!(target.pixel(3, 1), Some([0, 0, 255, 255]));
+        assert_eq!(renderer.stats(&text_system).draw_calls, 2);
+
+        let capture = renderer
+            .capture(
+                &second,
+                Rect::new(2.0, 0.0, 2.0, 2.0),
+                1.0,
+                &mut text_system,
+            )
+            .unwrap();
+        assert_eq!((capture.width(), capture.height()), (2, 2));
+        assert_eq!(capture.pixel(0, 0), Some([0, 0, 255, 255]));
+        assert!(
+            renderer
+                .capture(
+                    &second,
+                    Rect::new(0.0, 0.0, 0.0, 0.0),
+                    1.0,
+                    &mut text_system
+                )
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_composite_clears_and_fades() {
+        let mut renderer = SoftwareRenderer::new();
+        let mut text_system = TextSystem::headless();
+        let mut list = DrawList::new();
+        list.add_rect(Rect::new(0.0, 0.0, 2.0, 4.0), colors::WHITE);
+
+        let mut target = RgbaImage::new(4, 4);
+        let composite = Composite {
+            clear: Some(colors::BLACK),
+            opacity: 0.5,
+        };
+        let size = Vec2::new(4.0, 4.0);
+        renderer.render_draw_list(&list, &mut target, composite, size, 1.0, &mut text_system);
+        // The content is faded over the clear color, which isn't
+        assert_eq!(target.pixel(0, 0), Some([128, 128, 128, 255]));
+        assert_eq!(target.pixel(3, 0), Some([0, 0, 0, 255]));
+    }
+}
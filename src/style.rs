@@ -1,12 +1,217 @@
 use glam::Vec2;
+use std::ops::Range;
 
-use crate::color::{
-    Color,
-    colors::{BLACK, WHITE},
+use crate::{
+    color::{
+        Color, ColorExt,
+        colors::{BLACK, BLUE_500, WHITE},
+    },
+    geometry::{Edges, Rect},
+    interaction::InteractionState,
 };
 
-// Re-export FontWeight for public API
-pub use parley::FontWeight;
+// Re-export font types for public API
+pub use parley::{FontStyle, FontWeight, FontWidth};
+
+/// Variable font axes beyond weight
+///
+/// Width and style choose between faces of a family the same way weight
+/// does. With a variable font they set its `wdth`, `slnt` and `ital` axes
+/// instead, so a single font file covers every instance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontAxes {
+    /// Font width, from condensed to expanded
+    pub width: FontWidth,
+    /// Upright, italic or oblique
+    pub style: FontStyle,
+    /// Whether to set the optical size axis (`opsz`) to the font size, so
+    /// small text gets sturdier shapes and large text finer ones
+    pub optical_sizing: bool,
+    /// Values for any other axes by tag, e.g. `(*b"GRAD", 50.0)`
+    ///
+    /// These take precedence over optical sizing.
+    pub variations: Vec<([u8; 4], f32)>,
+}
+
+impl Default for FontAxes {
+    fn default() -> Self {
+        Self {
+            width: FontWidth::NORMAL,
+            style: FontStyle::Normal,
+            optical_sizing: true,
+            variations: Vec::new(),
+        }
+    }
+}
+
+impl FontAxes {
+    /// Axis values to apply for text at `size`, in CSS
+    /// `font-variation-settings` syntax, or `None` if there are none
+    ///
+    /// Axes a font doesn't have are ignored when it's shaped.
+    pub fn variation_settings(&self, size: f32) -> Option<String> {
+        let mut settings: Vec<String> = self
+            .variations
+            .iter()
+            .map(|(tag, value)| font_setting(tag, value))
+            .collect();
+        if self.optical_sizing && !self.variations.iter().any(|(tag, _)| tag == b"opsz") {
+            settings.push(font_setting(b"opsz", size));
+        }
+        (!settings.is_empty()).then(|| settings.join(", "))
+    }
+}
+
+/// OpenType features in CSS `font-feature-settings` syntax, or `None` if
+/// there are none
+pub fn feature_settings(features: &[([u8; 4], u16)]) -> Option<String> {
+    let settings: Vec<String> = features
+        .iter()
+        .map(|(tag, value)| font_setting(tag, value))
+        .collect();
+    (!settings.is_empty()).then(|| settings.join(", "))
+}
+
+/// One tag and value in CSS font settings syntax, e.g. `"tnum" 1`
+fn font_setting(tag: &[u8; 4], value: impl std::fmt::Display) -> String {
+    format!("\"{}\" {}", String::from_utf8_lossy(tag), value)
+}
+
+/// Height of a line of text
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineHeight {
+    /// Multiple of the font size
+    Relative(f32),
+    /// Fixed height in logical pixels
+    Px(f32),
+}
+
+impl LineHeight {
+    /// Line height in logical pixels for text at `font_size`
+    pub fn resolve(&self, font_size: f32) -> f32 {
+        match *self {
+            LineHeight::Relative(multiplier) => font_size * multiplier,
+            LineHeight::Px(px) => px,
+        }
+    }
+
+    /// Line height as a multiple of `font_size`
+    pub fn multiplier(&self, font_size: f32) -> f32 {
+        match *self {
+            LineHeight::Relative(multiplier) => multiplier,
+            LineHeight::Px(_) if font_size <= 0.0 => 1.0,
+            LineHeight::Px(px) => px / font_size,
+        }
+    }
+}
+
+impl Default for LineHeight {
+    fn default() -> Self {
+        LineHeight::Relative(1.2)
+    }
+}
+
+impl From<f32> for LineHeight {
+    fn from(multiplier: f32) -> Self {
+        LineHeight::Relative(multiplier)
+    }
+}
+
+/// Which line a [`TextDecoration`] draws
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecorationLine {
+    /// Below the baseline, e.g. for links
+    Underline,
+    /// Through the middle of lowercase letters, e.g. for completed items
+    Strikethrough,
+    /// Above the tallest letters
+    Overline,
+}
+
+/// How a [`TextDecoration`] line is drawn
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DecorationStyle {
+    #[default]
+    Solid,
+    Dashed,
+    /// A squiggle, e.g. for spelling errors
+    Wavy,
+}
+
+/// How glyphs are rasterized and drawn
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum GlyphRendering {
+    /// Bitmaps for regular text, and distance fields for large text or text
+    /// drawn under a scale transform
+    #[default]
+    Auto,
+    /// Bitmaps rasterized and hinted at each size, the sharpest for body text
+    Bitmap,
+    /// Distance fields rasterized once per glyph and drawn at any size, which
+    /// stay crisp while text scales and use less atlas space for large text
+    DistanceField,
+}
+
+/// A line drawn under, through or over text
+///
+/// Positions and the default thickness come from the font's metrics.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextDecoration {
+    pub line: DecorationLine,
+    pub style: DecorationStyle,
+    /// Line color, or `None` for the text color
+    pub color: Option<Color>,
+    /// Line thickness in logical pixels, or `None` for the font's
+    pub thickness: Option<f32>,
+}
+
+impl TextDecoration {
+    /// A solid line in the text color
+    pub fn new(line: DecorationLine) -> Self {
+        Self {
+            line,
+            style: DecorationStyle::Solid,
+            color: None,
+            thickness: None,
+        }
+    }
+
+    pub fn underline() -> Self {
+        Self::new(DecorationLine::Underline)
+    }
+
+    pub fn strikethrough() -> Self {
+        Self::new(DecorationLine::Strikethrough)
+    }
+
+    pub fn overline() -> Self {
+        Self::new(DecorationLine::Overline)
+    }
+
+    /// Draw the line as dashes
+    pub fn dashed(mut self) -> Self {
+        self.style = DecorationStyle::Dashed;
+        self
+    }
+
+    /// Draw the line as a squiggle
+    pub fn wavy(mut self) -> Self {
+        self.style = DecorationStyle::Wavy;
+        self
+    }
+
+    /// Set the line color
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Set the line thickness in logical pixels
+    pub fn thickness(mut self, thickness: f32) -> Self {
+        self.thickness = Some(thickness);
+        self
+    }
+}
 
 /// Text styling information
 #[derive(Debug, Clone, PartialEq)]
@@ -15,13 +220,34 @@ pub struct TextStyle {
     pub size: f32,
     /// Text color
     pub color: Color,
+    /// Colors for byte ranges of the text, used instead of `color`
+    ///
+    /// The text is still shaped as one run, so kerning and ligatures carry
+    /// across the ranges.
+    pub color_ranges: Vec<(Range<usize>, Color)>,
+    /// Gradient across the text's bounds, used instead of `color`
+    pub fill: Option<Fill>,
     /// Font family name (e.g., "system-ui", "Helvetica", "monospace")
     /// Must be a static string literal for use with parley's FontStack
     pub font_family: &'static str,
     /// Font weight (e.g., FontWeight::NORMAL, FontWeight::BOLD)
     pub weight: FontWeight,
-    /// Line height multiplier (1.0 = same as font size)
-    pub line_height: f32,
+    /// Width, style, optical sizing and other variable font axes
+    pub axes: FontAxes,
+    /// OpenType features to turn on or off by tag, e.g. `(*b"tnum", 1)`
+    pub font_features: Vec<([u8; 4], u16)>,
+    /// Height of each line, as a multiple of the font size or in pixels
+    pub line_height: LineHeight,
+    /// Extra space between characters in logical pixels (negative tightens)
+    pub letter_spacing: f32,
+    /// Extra space between words in logical pixels
+    pub word_spacing: f32,
+    /// Lines drawn under, through or over the text
+    pub decorations: Vec<TextDecoration>,
+    /// Shadow drawn behind the glyphs
+    pub shadow: Option<Shadow>,
+    /// How the glyphs are rasterized and drawn
+    pub glyph_rendering: GlyphRendering,
 }
 
 impl Default for TextStyle {
@@ -29,9 +255,18 @@ impl Default for TextStyle {
         Self {
             size: 16.0,
             color: WHITE,
+            color_ranges: Vec::new(),
+            fill: None,
             font_family: "system-ui",
             weight: FontWeight::NORMAL,
-            line_height: 1.2,
+            axes: FontAxes::default(),
+            font_features: Vec::new(),
+            line_height: LineHeight::default(),
+            letter_spacing: 0.0,
+            word_spacing: 0.0,
+            decorations: Vec::new(),
+            shadow: None,
+            glyph_rendering: GlyphRendering::Auto,
         }
     }
 }
@@ -54,6 +289,40 @@ impl TextStyle {
         self
     }
 
+    /// Color the bytes in `range` of the text
+    pub fn color_range(mut self, range: Range<usize>, color: Color) -> Self {
+        self.color_ranges.push((range, color));
+        self
+    }
+
+    /// Fill the text with a gradient across its bounds instead of its color
+    pub fn with_fill(mut self, fill: Fill) -> Self {
+        self.fill = Some(fill);
+        self
+    }
+
+    /// Fill the text with a linear gradient across its bounds
+    pub fn with_linear_gradient(self, start: Color, end: Color, angle: f32) -> Self {
+        self.with_fill(Fill::linear_gradient(start, end, angle))
+    }
+
+    /// Draw a shadow behind the glyphs, offset by `offset` and blurred by
+    /// `blur` pixels, to keep text legible over images and gradients
+    pub fn shadow(mut self, offset: Vec2, blur: f32, color: Color) -> Self {
+        self.shadow = Some(Shadow {
+            offset,
+            blur,
+            color,
+        });
+        self
+    }
+
+    /// Choose between bitmap and distance field glyphs
+    pub fn glyph_rendering(mut self, glyph_rendering: GlyphRendering) -> Self {
+        self.glyph_rendering = glyph_rendering;
+        self
+    }
+
     /// Set the font family (must be a static string)
     pub fn with_font_family(mut self, family: &'static str) -> Self {
         self.font_family = family;
@@ -66,11 +335,128 @@ impl TextStyle {
         self
     }
 
-    /// Set the line height multiplier
-    pub fn line_height(mut self, line_height: f32) -> Self {
-        self.line_height = line_height;
+    /// Set the font width, e.g. `FontWidth::CONDENSED`
+    pub fn with_width(mut self, width: FontWidth) -> Self {
+        self.axes.width = width;
         self
     }
+
+    /// Set the font style, e.g. `FontStyle::Italic`
+    pub fn with_font_style(mut self, style: FontStyle) -> Self {
+        self.axes.style = style;
+        self
+    }
+
+    /// Set whether the optical size axis follows the font size
+    pub fn with_optical_sizing(mut self, enabled: bool) -> Self {
+        self.axes.optical_sizing = enabled;
+        self
+    }
+
+    /// Set a variable font axis by tag, e.g. `with_variation(*b"GRAD", 50.0)`
+    pub fn with_variation(mut self, tag: [u8; 4], value: f32) -> Self {
+        self.axes
+            .variations
+            .retain(|(existing, _)| *existing != tag);
+        self.axes.variations.push((tag, value));
+        self
+    }
+
+    /// Set an OpenType feature by tag, e.g. `with_font_feature(*b"liga", 0)`
+    ///
+    /// Most features are turned on with 1 and off with 0. Alternates such
+    /// as `salt` take the number of the alternate to use.
+    pub fn with_font_feature(mut self, tag: [u8; 4], value: u16) -> Self {
+        self.font_features.retain(|(existing, _)| *existing != tag);
+        self.font_features.push((tag, value));
+        self
+    }
+
+    /// Use tabular numbers, so digits line up in columns
+    pub fn tabular_numbers(self) -> Self {
+        self.with_font_feature(*b"tnum", 1)
+    }
+
+    /// Turn off standard and contextual ligatures, e.g. for code
+    pub fn without_ligatures(self) -> Self {
+        self.with_font_feature(*b"liga", 0)
+            .with_font_feature(*b"calt", 0)
+    }
+
+    /// Draw lowercase letters as small capitals
+    pub fn small_caps(self) -> Self {
+        self.with_font_feature(*b"smcp", 1)
+    }
+
+    /// Set the line height, as a multiplier of the font size or a
+    /// [`LineHeight`]
+    pub fn line_height(mut self, line_height: impl Into<LineHeight>) -> Self {
+        self.line_height = line_height.into();
+        self
+    }
+
+    /// Set a fixed line height in logical pixels
+    pub fn line_height_px(self, px: f32) -> Self {
+        self.line_height(LineHeight::Px(px))
+    }
+
+    /// Set the extra space between characters in logical pixels
+    pub fn letter_spacing(mut self, spacing: f32) -> Self {
+        self.letter_spacing = spacing;
+        self
+    }
+
+    /// Set the extra space between words in logical pixels
+    pub fn word_spacing(mut self, spacing: f32) -> Self {
+        self.word_spacing = spacing;
+        self
+    }
+
+    /// Add a line under, through or over the text
+    pub fn with_decoration(mut self, decoration: TextDecoration) -> Self {
+        self.decorations.push(decoration);
+        self
+    }
+
+    /// Underline the text
+    pub fn underline(self) -> Self {
+        self.with_decoration(TextDecoration::underline())
+    }
+
+    /// Strike through the text
+    pub fn strikethrough(self) -> Self {
+        self.with_decoration(TextDecoration::strikethrough())
+    }
+
+    /// The style with every length multiplied by `scale`
+    pub(crate) fn scaled(self, scale: f32) -> Self {
+        let line_height = match self.line_height {
+            LineHeight::Px(px) => LineHeight::Px(px * scale),
+            relative => relative,
+        };
+        let decorations = self
+            .decorations
+            .iter()
+            .map(|decoration| TextDecoration {
+                thickness: decoration.thickness.map(|thickness| thickness * scale),
+                ..*decoration
+            })
+            .collect();
+        let shadow = self.shadow.map(|shadow| Shadow {
+            offset: shadow.offset * scale,
+            blur: shadow.blur * scale,
+            ..shadow
+        });
+        Self {
+            size: self.size * scale,
+            line_height,
+            letter_spacing: self.letter_spacing * scale,
+            word_spacing: self.word_spacing * scale,
+            decorations,
+            shadow,
+            ..self
+        }
+    }
 }
 
 /// Corner radii for a frame (top-left, top-right, bottom-right, bottom-left)
@@ -104,6 +490,42 @@ impl CornerRadii {
     }
 }
 
+/// How a frame's corners are rounded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CornerStyle {
+    /// Circular arcs, like CSS `border-radius`
+    #[default]
+    Circular,
+    /// Continuous curvature, like the "squircle" corners of native macOS
+    /// and iOS controls
+    ///
+    /// The curve starts further from the corner than a circular arc of the
+    /// same radius and eases into the straight edges, so the two look
+    /// about equally round.
+    Continuous,
+}
+
+/// A color for each edge of a frame's border
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgeColors {
+    pub top: Color,
+    pub right: Color,
+    pub bottom: Color,
+    pub left: Color,
+}
+
+impl EdgeColors {
+    /// The same color on every edge
+    pub fn all(color: Color) -> Self {
+        Self {
+            top: color,
+            right: color,
+            bottom: color,
+            left: color,
+        }
+    }
+}
+
 /// Shadow properties for frames
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Shadow {
@@ -115,52 +537,338 @@ pub struct Shadow {
     pub color: Color,
 }
 
+/// A ring drawn around an element, outside its bounds
+///
+/// Unlike a border, an outline takes no space in layout, so it can come
+/// and go, as focus rings and highlights do, without moving anything.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Outline {
+    /// Width in pixels
+    pub width: f32,
+    pub color: Color,
+    /// Gap between the element's bounds and the ring; negative values
+    /// draw the ring inside the bounds
+    pub offset: f32,
+}
+
+impl Outline {
+    /// An outline hugging the element's bounds
+    pub fn new(width: f32, color: Color) -> Self {
+        Self {
+            width,
+            color,
+            offset: 0.0,
+        }
+    }
+
+    /// Set the gap between the element's bounds and the ring
+    pub fn offset(mut self, offset: f32) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// The keyboard focus ring, drawn like macOS draws it around focused
+    /// controls
+    pub fn focus_ring() -> Self {
+        Self::new(3.0, BLUE_500.with_alpha(0.5)).offset(1.0)
+    }
+
+    /// Bounds of the ring's outer edge around `bounds`
+    pub fn outer_bounds(&self, bounds: Rect) -> Rect {
+        let spread = Vec2::splat(self.offset + self.width);
+        Rect::from_pos_size(bounds.pos - spread, bounds.size + spread * 2.0)
+    }
+
+    /// Radius of the ring's outer edge at a corner of radius `radius`,
+    /// following the element's rounding; square corners stay square
+    pub fn outer_radius(&self, radius: f32) -> f32 {
+        if radius > 0.0 {
+            (radius + self.offset + self.width).max(0.0)
+        } else {
+            0.0
+        }
+    }
+}
+
 /// Background fill type for frames
 #[derive(Debug, Clone, PartialEq)]
 pub enum Fill {
     /// Solid color fill
     Solid(Color),
-    /// Linear gradient fill
-    LinearGradient {
-        /// Start color
-        start: Color,
-        /// End color
-        end: Color,
+    /// Gradient fill
+    Gradient(Gradient),
+}
+
+impl Fill {
+    /// A two-color linear gradient
+    pub fn linear_gradient(start: Color, end: Color, angle: f32) -> Self {
+        Fill::Gradient(Gradient::linear(angle).stop(0.0, start).stop(1.0, end))
+    }
+
+    /// A two-color radial gradient
+    pub fn radial_gradient(center: Color, edge: Color) -> Self {
+        Fill::Gradient(Gradient::radial().stop(0.0, center).stop(1.0, edge))
+    }
+
+    /// Whether any part of the fill is visible
+    pub fn is_visible(&self) -> bool {
+        match self {
+            Fill::Solid(color) => color.alpha > 0.0,
+            Fill::Gradient(gradient) => gradient.stops.iter().any(|stop| stop.color.alpha > 0.0),
+        }
+    }
+}
+
+impl From<Gradient> for Fill {
+    fn from(gradient: Gradient) -> Self {
+        Fill::Gradient(gradient)
+    }
+}
+
+/// Maximum number of stops drawn for a gradient
+pub const MAX_GRADIENT_STOPS: usize = 8;
+
+/// Shape of a gradient
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GradientKind {
+    /// Stops run along a line through the center of the bounds
+    Linear {
         /// Angle in radians (0 = left to right, PI/2 = bottom to top)
         angle: f32,
     },
-    /// Radial gradient fill
-    RadialGradient {
-        /// Center color
-        center: Color,
-        /// Edge color
-        edge: Color,
+    /// Stops run outward from the center to the corners
+    Radial,
+    /// Stops sweep clockwise around `center`, meeting again after a full turn
+    Conic {
+        /// Center as a fraction of the bounds ((0.5, 0.5) is the middle)
+        center: Vec2,
+        /// Angle in radians where the sweep starts (0 = right, -PI/2 = top)
+        angle: f32,
     },
 }
 
+/// Color space gradient stops are blended in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GradientSpace {
+    /// Blend sRGB components, like CSS by default
+    #[default]
+    Srgb,
+    /// Blend lightness, chroma and hue, taking the shorter way around the
+    /// hue circle, so midpoints stay vivid instead of going gray
+    Oklch,
+}
+
+/// A color at a position along a gradient
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GradientStop {
+    /// Position from 0.0 (start or center) to 1.0 (end or edge)
+    pub position: f32,
+    pub color: Color,
+}
+
+/// A gradient with any number of color stops
+///
+/// # Example
+/// ```ignore
+/// let sunset = Gradient::linear(std::f32::consts::FRAC_PI_2)
+///     .stop(0.0, Color::hex("#1e1b4b"))
+///     .stop(0.6, Color::hex("#db2777"))
+///     .stop(1.0, Color::hex("#fbbf24"))
+///     .space(GradientSpace::Oklch);
+/// let style = ElementStyle::new().with_gradient(sunset);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    pub kind: GradientKind,
+    /// Stops in the order they were added; see [`Gradient::stop`]
+    pub stops: Vec<GradientStop>,
+    pub space: GradientSpace,
+}
+
+impl Gradient {
+    /// A linear gradient at `angle` radians, with no stops yet
+    pub fn linear(angle: f32) -> Self {
+        Self {
+            kind: GradientKind::Linear { angle },
+            stops: Vec::new(),
+            space: GradientSpace::default(),
+        }
+    }
+
+    /// A radial gradient, with no stops yet
+    pub fn radial() -> Self {
+        Self {
+            kind: GradientKind::Radial,
+            stops: Vec::new(),
+            space: GradientSpace::default(),
+        }
+    }
+
+    /// A conic gradient around `center` (a fraction of the bounds),
+    /// starting at `angle` radians, with no stops yet
+    ///
+    /// Hard stops make pie charts and progress rings; stops spanning the
+    /// hue circle make a color wheel.
+    ///
+    /// # Example
+    /// ```ignore
+    /// // A ring 40% full, starting at the top
+    /// let progress = Gradient::conic(Vec2::splat(0.5), -std::f32::consts::FRAC_PI_2)
+    ///     .stop(0.0, colors::BLUE_500)
+    ///     .stop(0.4, colors::BLUE_500)
+    ///     .stop(0.4, colors::GRAY_200)
+    ///     .stop(1.0, colors::GRAY_200);
+    /// ```
+    pub fn conic(center: Vec2, angle: f32) -> Self {
+        Self {
+            kind: GradientKind::Conic { center, angle },
+            stops: Vec::new(),
+            space: GradientSpace::default(),
+        }
+    }
+
+    /// Add a stop at `position` (0.0 to 1.0)
+    ///
+    /// Stops can be added in any order. Up to [`MAX_GRADIENT_STOPS`] are
+    /// drawn.
+    pub fn stop(mut self, position: f32, color: Color) -> Self {
+        self.stops.push(GradientStop {
+            position: position.clamp(0.0, 1.0),
+            color,
+        });
+        self
+    }
+
+    /// Add stops spaced evenly from 0.0 to 1.0
+    pub fn even_stops(mut self, colors: impl IntoIterator<Item = Color>) -> Self {
+        let colors: Vec<Color> = colors.into_iter().collect();
+        let last = colors.len().saturating_sub(1).max(1) as f32;
+        for (i, color) in colors.into_iter().enumerate() {
+            self = self.stop(i as f32 / last, color);
+        }
+        self
+    }
+
+    /// Set the color space stops are blended in
+    pub fn space(mut self, space: GradientSpace) -> Self {
+        self.space = space;
+        self
+    }
+
+    /// The drawn stops, sorted by position
+    pub fn sorted_stops(&self) -> Vec<GradientStop> {
+        let mut stops = self.stops.clone();
+        stops.truncate(MAX_GRADIENT_STOPS);
+        stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+        stops
+    }
+
+    /// The color at `t` along the gradient, as the renderer draws it
+    pub fn color_at(&self, t: f32) -> Color {
+        let stops = self.sorted_stops();
+        let (Some(first), Some(last)) = (stops.first(), stops.last()) else {
+            return Color::rgba(0.0, 0.0, 0.0, 0.0);
+        };
+        if t <= first.position {
+            return first.color;
+        }
+        for pair in stops.windows(2) {
+            let (from, to) = (pair[0], pair[1]);
+            if t <= to.position {
+                let f = (t - from.position) / (to.position - from.position).max(1e-5);
+                return self.blend(from.color, to.color, f);
+            }
+        }
+        last.color
+    }
+
+    /// How far along the gradient `point` falls, for bounds of `size` with
+    /// `point` relative to their top-left, as the renderer computes it
+    ///
+    /// Pass the result to [`Gradient::color_at`] to pick the color under
+    /// the cursor, like in a color wheel.
+    pub fn position_at(&self, point: Vec2, size: Vec2) -> f32 {
+        let p = point - size / 2.0;
+        match self.kind {
+            GradientKind::Linear { angle } => {
+                let dir = Vec2::new(angle.cos(), angle.sin());
+                let t = p.dot(dir) / size.dot(dir.abs()).max(1e-4);
+                (t + 1.0) * 0.5
+            }
+            GradientKind::Radial => p.length() / (size / 2.0).length().max(1e-4),
+            GradientKind::Conic { center, angle } => {
+                let q = point - center * size;
+                let turn = (q.y.atan2(q.x) - angle) / std::f32::consts::TAU;
+                turn - turn.floor()
+            }
+        }
+    }
+
+    /// Blend two stop colors in the gradient's space
+    fn blend(&self, from: Color, to: Color, t: f32) -> Color {
+        match self.space {
+            GradientSpace::Srgb => Color::rgba(
+                from.red + (to.red - from.red) * t,
+                from.green + (to.green - from.green) * t,
+                from.blue + (to.blue - from.blue) * t,
+                from.alpha + (to.alpha - from.alpha) * t,
+            ),
+            GradientSpace::Oklch => {
+                let [l0, c0, h0] = oklch_stop(from, to);
+                let [l1, c1, h1] = oklch_stop(to, from);
+                let mut dh = h1 - h0;
+                dh -= 360.0 * (dh / 360.0).round();
+                Color::oklcha(
+                    l0 + (l1 - l0) * t,
+                    c0 + (c1 - c0) * t,
+                    h0 + dh * t,
+                    from.alpha + (to.alpha - from.alpha) * t,
+                )
+            }
+        }
+    }
+}
+
+/// OKLCH components of a stop, taking the hue of `other` for grays so the
+/// blend doesn't sweep through unrelated hues
+fn oklch_stop(color: Color, other: Color) -> [f32; 3] {
+    let (l, c, h) = color.to_oklch();
+    if c < 1e-4 {
+        [l, c, other.to_oklch().2]
+    } else {
+        [l, c, h]
+    }
+}
+
 /// Frame styling information for SDF-based rendering
 #[derive(Debug, Clone, PartialEq)]
 pub struct ElementStyle {
     /// Background fill of the frame
     pub fill: Fill,
-    /// Border width in pixels (0 for no border)
-    pub border_width: f32,
-    /// Border color
-    pub border_color: Color,
+    /// Border width of each edge in pixels (0 for no border)
+    pub border_widths: Edges,
+    /// Border color of each edge
+    pub border_colors: EdgeColors,
     /// Corner radii
     pub corner_radii: CornerRadii,
+    /// How the corners are rounded
+    pub corner_style: CornerStyle,
     /// Optional shadow
     pub shadow: Option<Shadow>,
+    /// Optional ring outside the frame
+    pub outline: Option<Outline>,
 }
 
 impl Default for ElementStyle {
     fn default() -> Self {
         Self {
             fill: Fill::Solid(WHITE),
-            border_width: 0.0,
-            border_color: BLACK,
+            border_widths: Edges::zero(),
+            border_colors: EdgeColors::all(BLACK),
             corner_radii: CornerRadii::uniform(0.0),
+            corner_style: CornerStyle::Circular,
             shadow: None,
+            outline: None,
         }
     }
 }
@@ -179,23 +887,71 @@ impl ElementStyle {
 
     /// Set a linear gradient background
     pub fn with_linear_gradient(mut self, start: Color, end: Color, angle: f32) -> Self {
-        self.fill = Fill::LinearGradient { start, end, angle };
+        self.fill = Fill::linear_gradient(start, end, angle);
         self
     }
 
     /// Set a radial gradient background
     pub fn with_radial_gradient(mut self, center: Color, edge: Color) -> Self {
-        self.fill = Fill::RadialGradient { center, edge };
+        self.fill = Fill::radial_gradient(center, edge);
+        self
+    }
+
+    /// Set a multi-stop gradient background
+    pub fn with_gradient(mut self, gradient: Gradient) -> Self {
+        self.fill = Fill::Gradient(gradient);
         self
     }
 
-    /// Set the border width and color
+    /// Set the border width and color of every edge
     pub fn with_border(mut self, width: f32, color: Color) -> Self {
-        self.border_width = width;
-        self.border_color = color;
+        self.border_widths = Edges::all(width);
+        self.border_colors = EdgeColors::all(color);
         self
     }
 
+    /// Set the top border, like an accent bar
+    pub fn with_border_top(mut self, width: f32, color: Color) -> Self {
+        self.border_widths.top = width;
+        self.border_colors.top = color;
+        self
+    }
+
+    /// Set the right border
+    pub fn with_border_right(mut self, width: f32, color: Color) -> Self {
+        self.border_widths.right = width;
+        self.border_colors.right = color;
+        self
+    }
+
+    /// Set the bottom border
+    pub fn with_border_bottom(mut self, width: f32, color: Color) -> Self {
+        self.border_widths.bottom = width;
+        self.border_colors.bottom = color;
+        self
+    }
+
+    /// Set the left border, like a status stripe
+    pub fn with_border_left(mut self, width: f32, color: Color) -> Self {
+        self.border_widths.left = width;
+        self.border_colors.left = color;
+        self
+    }
+
+    /// Whether any edge has a visible border
+    pub fn has_visible_border(&self) -> bool {
+        let widths = self.border_widths;
+        let colors = self.border_colors;
+        [
+            (widths.top, colors.top),
+            (widths.right, colors.right),
+            (widths.bottom, colors.bottom),
+            (widths.left, colors.left),
+        ]
+        .iter()
+        .any(|(width, color)| *width > 0.0 && color.alpha > 0.0)
+    }
+
     /// Set uniform corner radius
     pub fn with_corner_radius(mut self, radius: f32) -> Self {
         self.corner_radii = CornerRadii::uniform(radius);
@@ -208,6 +964,12 @@ impl ElementStyle {
         self
     }
 
+    /// Set how the corners are rounded
+    pub fn with_corner_style(mut self, style: CornerStyle) -> Self {
+        self.corner_style = style;
+        self
+    }
+
     /// Add a shadow to the frame
     pub fn with_shadow(mut self, offset: Vec2, blur: f32, color: Color) -> Self {
         self.shadow = Some(Shadow {
@@ -217,4 +979,430 @@ impl ElementStyle {
         });
         self
     }
+
+    /// Draw a ring outside the frame
+    pub fn with_outline(mut self, outline: Outline) -> Self {
+        self.outline = Some(outline);
+        self
+    }
+}
+
+/// What an element does with children that don't fit in its bounds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Overflow {
+    /// Children paint outside the bounds
+    #[default]
+    Visible,
+    /// Children are cut off at the bounds, including rounded corners, and
+    /// can't be clicked outside them
+    Clip,
+    /// Children are clipped and can be scrolled into view
+    Scroll,
+}
+
+/// Interaction state that per-state styles are picked by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StyleState {
+    pub hovered: bool,
+    pub pressed: bool,
+    pub focused: bool,
+    pub disabled: bool,
+}
+
+impl StyleState {
+    /// The style state of an element with the given interaction state
+    pub fn from_interaction(state: &InteractionState, disabled: bool) -> Self {
+        Self {
+            hovered: state.is_hovered,
+            pressed: state.is_pressed,
+            focused: state.is_focused,
+            disabled,
+        }
+    }
+}
+
+/// Colors for one interaction state; unset colors fall through to the
+/// states below
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StateColors {
+    /// Background color
+    pub bg: Option<Color>,
+    /// Border color
+    pub border: Option<Color>,
+    /// Text color
+    pub text_color: Option<Color>,
+    /// Ring outside the bounds
+    pub outline: Option<Outline>,
+}
+
+impl StateColors {
+    /// Replace colors with the ones set in `over`
+    fn layer(&mut self, over: &StateColors) {
+        self.bg = over.bg.or(self.bg);
+        self.border = over.border.or(self.border);
+        self.text_color = over.text_color.or(self.text_color);
+        self.outline = over.outline.or(self.outline);
+    }
+}
+
+/// Colors that follow an element's interaction state
+///
+/// Elements declare how they look in each state and the paint phase picks
+/// the right colors, rather than each element branching on hover and press
+/// itself:
+///
+/// ```
+/// use sol_ui::color::colors;
+/// use sol_ui::style::StyleStates;
+///
+/// let states = StyleStates::new()
+///     .bg(colors::BLUE_500)
+///     .hover_bg(colors::BLUE_400)
+///     .pressed_bg(colors::BLUE_600)
+///     .disabled_opacity(0.5);
+/// ```
+///
+/// States stack from focused to hovered to pressed, so a pressed color wins
+/// over a hover color. When disabled, only the disabled colors apply, and
+/// everything is faded by the disabled opacity.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StyleStates {
+    base: StateColors,
+    hover: StateColors,
+    pressed: StateColors,
+    focused: StateColors,
+    disabled: StateColors,
+    disabled_opacity: Option<f32>,
+}
+
+impl StyleStates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the background color
+    pub fn bg(mut self, color: Color) -> Self {
+        self.base.bg = Some(color);
+        self
+    }
+
+    /// Set the background color while hovered
+    pub fn hover_bg(mut self, color: Color) -> Self {
+        self.hover.bg = Some(color);
+        self
+    }
+
+    /// Set the background color while pressed
+    pub fn pressed_bg(mut self, color: Color) -> Self {
+        self.pressed.bg = Some(color);
+        self
+    }
+
+    /// Set the background color while focused
+    pub fn focused_bg(mut self, color: Color) -> Self {
+        self.focused.bg = Some(color);
+        self
+    }
+
+    /// Set the background color while disabled
+    pub fn disabled_bg(mut self, color: Color) -> Self {
+        self.disabled.bg = Some(color);
+        self
+    }
+
+    /// Set the border color
+    pub fn border(mut self, color: Color) -> Self {
+        self.base.border = Some(color);
+        self
+    }
+
+    /// Set the border color while hovered
+    pub fn hover_border(mut self, color: Color) -> Self {
+        self.hover.border = Some(color);
+        self
+    }
+
+    /// Set the border color while pressed
+    pub fn pressed_border(mut self, color: Color) -> Self {
+        self.pressed.border = Some(color);
+        self
+    }
+
+    /// Set the border color while focused
+    pub fn focused_border(mut self, color: Color) -> Self {
+        self.focused.border = Some(color);
+        self
+    }
+
+    /// Set the border color while disabled
+    pub fn disabled_border(mut self, color: Color) -> Self {
+        self.disabled.border = Some(color);
+        self
+    }
+
+    /// Set the text color
+    pub fn text_color(mut self, color: Color) -> Self {
+        self.base.text_color = Some(color);
+        self
+    }
+
+    /// Set the text color while hovered
+    pub fn hover_text_color(mut self, color: Color) -> Self {
+        self.hover.text_color = Some(color);
+        self
+    }
+
+    /// Set the text color while pressed
+    pub fn pressed_text_color(mut self, color: Color) -> Self {
+        self.pressed.text_color = Some(color);
+        self
+    }
+
+    /// Set the text color while focused
+    pub fn focused_text_color(mut self, color: Color) -> Self {
+        self.focused.text_color = Some(color);
+        self
+    }
+
+    /// Set the text color while disabled
+    pub fn disabled_text_color(mut self, color: Color) -> Self {
+        self.disabled.text_color = Some(color);
+        self
+    }
+
+    /// Set the outline
+    pub fn outline(mut self, outline: Outline) -> Self {
+        self.base.outline = Some(outline);
+        self
+    }
+
+    /// Set the outline while hovered
+    pub fn hover_outline(mut self, outline: Outline) -> Self {
+        self.hover.outline = Some(outline);
+        self
+    }
+
+    /// Set the outline while pressed
+    pub fn pressed_outline(mut self, outline: Outline) -> Self {
+        self.pressed.outline = Some(outline);
+        self
+    }
+
+    /// Set the outline while focused
+    pub fn focused_outline(mut self, outline: Outline) -> Self {
+        self.focused.outline = Some(outline);
+        self
+    }
+
+    /// Fade all colors to `opacity` while disabled
+    pub fn disabled_opacity(mut self, opacity: f32) -> Self {
+        self.disabled_opacity = Some(opacity.clamp(0.0, 1.0));
+        self
+    }
+
+    /// Check if no state changes anything
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Pick the colors for `state`
+    ///
+    /// Colors that aren't set for the state or the base are `None`, for the
+    /// element to fill in with its own defaults. Opacity isn't applied; see
+    /// [`StyleStates::apply_opacity`].
+    pub fn resolve(&self, state: StyleState) -> StateColors {
+        let mut colors = self.base;
+        if state.disabled {
+            colors.layer(&self.disabled);
+        } else {
+            if state.focused {
+                colors.layer(&self.focused);
+            }
+            if state.hovered {
+                colors.layer(&self.hover);
+            }
+            if state.pressed {
+                colors.layer(&self.pressed);
+            }
+        }
+        colors
+    }
+
+    /// Fade a resolved color by the opacity for `state`
+    pub fn apply_opacity(&self, color: Color, state: StyleState) -> Color {
+        match self.disabled_opacity {
+            Some(opacity) if state.disabled => color.with_alpha(color.alpha * opacity),
+            _ => color,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::colors::{BLUE_400, BLUE_500, BLUE_600, GRAY_400};
+
+    #[test]
+    fn test_font_variation_settings() {
+        let style = TextStyle::new().size(12.0);
+        assert_eq!(
+            style.axes.variation_settings(style.size).as_deref(),
+            Some("\"opsz\" 12")
+        );
+
+        // Explicit axes replace earlier values and override optical sizing
+        let style = style
+            .with_variation(*b"GRAD", 20.0)
+            .with_variation(*b"GRAD", 50.0)
+            .with_variation(*b"opsz", 24.0);
+        assert_eq!(
+            style.axes.variation_settings(style.size).as_deref(),
+            Some("\"GRAD\" 50, \"opsz\" 24")
+        );
+
+        let style = TextStyle::new().with_optical_sizing(false);
+        assert_eq!(style.axes.variation_settings(style.size), None);
+    }
+
+    #[test]
+    fn test_font_feature_settings() {
+        assert_eq!(feature_settings(&TextStyle::new().font_features), None);
+
+        let style = TextStyle::new()
+            .tabular_numbers()
+            .without_ligatures()
+            .with_font_feature(*b"tnum", 0);
+        assert_eq!(
+            feature_settings(&style.font_features).as_deref(),
+            Some("\"liga\" 0, \"calt\" 0, \"tnum\" 0")
+        );
+    }
+
+    #[test]
+    fn test_line_height_resolve() {
+        let style = TextStyle::new().size(10.0).line_height(1.5);
+        assert_eq!(style.line_height.resolve(style.size), 15.0);
+
+        let style = style.line_height_px(20.0);
+        assert_eq!(style.line_height.resolve(style.size), 20.0);
+        assert_eq!(style.line_height.multiplier(style.size), 2.0);
+    }
+
+    #[test]
+    fn test_style_states_resolve() {
+        let states = StyleStates::new()
+            .bg(BLUE_500)
+            .hover_bg(BLUE_400)
+            .pressed_bg(BLUE_600)
+            .disabled_text_color(GRAY_400)
+            .disabled_opacity(0.5);
+
+        let idle = StyleState::default();
+        assert_eq!(states.resolve(idle).bg, Some(BLUE_500));
+        assert_eq!(states.resolve(idle).text_color, None);
+
+        let hovered = StyleState {
+            hovered: true,
+            ..idle
+        };
+        assert_eq!(states.resolve(hovered).bg, Some(BLUE_400));
+
+        // Pressed wins over hovered
+        let pressed = StyleState {
+            pressed: true,
+            ..hovered
+        };
+        assert_eq!(states.resolve(pressed).bg, Some(BLUE_600));
+
+        // Disabled ignores the other states and fades everything
+        let disabled = StyleState {
+            disabled: true,
+            ..pressed
+        };
+        let colors = states.resolve(disabled);
+        assert_eq!(colors.bg, Some(BLUE_500));
+        assert_eq!(colors.text_color, Some(GRAY_400));
+        assert_eq!(states.apply_opacity(BLUE_500, disabled).alpha, 0.5);
+        assert_eq!(states.apply_opacity(BLUE_500, pressed).alpha, 1.0);
+    }
+
+    #[test]
+    fn test_per_edge_borders() {
+        let style = ElementStyle::new().with_border_left(4.0, BLUE_500);
+        assert_eq!(style.border_widths.left, 4.0);
+        assert_eq!(style.border_widths.top, 0.0);
+        assert!(style.has_visible_border());
+
+        // An edge needs both a width and a visible color
+        let hidden = ElementStyle::new()
+            .with_border_top(2.0, WHITE.with_alpha(0.0))
+            .with_border_bottom(0.0, BLUE_500);
+        assert!(!hidden.has_visible_border());
+    }
+
+    #[test]
+    fn test_outline() {
+        let outline = Outline::new(2.0, BLUE_500).offset(1.0);
+        let bounds = Rect::from_pos_size(Vec2::new(10.0, 10.0), Vec2::new(20.0, 10.0));
+        let outer = outline.outer_bounds(bounds);
+        assert_eq!(outer.pos, Vec2::new(7.0, 7.0));
+        assert_eq!(outer.size, Vec2::new(26.0, 16.0));
+        assert_eq!(outline.outer_radius(4.0), 7.0);
+        assert_eq!(outline.outer_radius(0.0), 0.0);
+
+        // Outlines follow the same state stacking as colors
+        let states = StyleStates::new().focused_outline(Outline::focus_ring());
+        let focused = StyleState {
+            focused: true,
+            ..Default::default()
+        };
+        assert_eq!(states.resolve(StyleState::default()).outline, None);
+        assert_eq!(states.resolve(focused).outline, Some(Outline::focus_ring()));
+    }
+
+    #[test]
+    fn test_gradient_stops() {
+        let red = Color::rgb(1.0, 0.0, 0.0);
+        let green = Color::rgb(0.0, 1.0, 0.0);
+        let blue = Color::rgb(0.0, 0.0, 1.0);
+        // Stops are sorted, and the ends extend past the outer stops
+        let gradient = Gradient::linear(0.0)
+            .stop(0.75, blue)
+            .stop(0.25, red)
+            .stop(0.5, green);
+        assert_eq!(gradient.color_at(0.0), red);
+        assert_eq!(gradient.color_at(0.5), green);
+        assert_eq!(gradient.color_at(1.0), blue);
+        assert_eq!(gradient.color_at(0.375), Color::rgb(0.5, 0.5, 0.0));
+
+        // OKLCH keeps the midpoint more vivid than blending sRGB does
+        let srgb_chroma = gradient.color_at(0.375).to_oklch().1;
+        let midpoint = gradient.space(GradientSpace::Oklch).color_at(0.375);
+        assert!(midpoint.to_oklch().1 > srgb_chroma + 0.03);
+
+        let clear = Gradient::radial().stop(0.0, WHITE.with_alpha(0.0));
+        assert!(!Fill::from(clear).is_visible());
+    }
+
+    #[test]
+    fn test_conic_gradient_position() {
+        let size = Vec2::new(100.0, 50.0);
+        let top = -std::f32::consts::FRAC_PI_2;
+        let gradient = Gradient::conic(Vec2::splat(0.5), top)
+            .stop(0.0, BLUE_500)
+            .stop(0.25, BLUE_500)
+            .stop(0.25, WHITE)
+            .stop(1.0, WHITE);
+
+        // Sweeps clockwise from the top, with y going down
+        let at = |x: f32, y: f32| gradient.position_at(Vec2::new(x, y), size);
+        assert!(at(50.0, 0.0) < 1e-4);
+        assert!((at(100.0, 25.0) - 0.25).abs() < 1e-4);
+        assert!((at(50.0, 50.0) - 0.5).abs() < 1e-4);
+        assert!((at(0.0, 25.0) - 0.75).abs() < 1e-4);
+
+        // Hard stops keep the order they were added in
+        assert_eq!(gradient.color_at(at(80.0, 10.0)), BLUE_500);
+        assert_eq!(gradient.color_at(at(20.0, 10.0)), WHITE);
+    }
 }
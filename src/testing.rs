@@ -45,14 +45,17 @@
 //! ```
 
 use crate::{
+    color::colors::TRANSPARENT,
     geometry::Rect,
+    image::RgbaImage,
     interaction::{
         ElementId, HitTestBuilder, HitTestEntry, InteractionEvent, InteractionState,
         InteractionSystem,
     },
-    layer::{InputEvent, Key, Modifiers, MouseButton},
+    layer::{InputEvent, Key, Modifiers, MouseButton, ScrollPhase},
     layout_engine::{ElementData, TaffyLayoutEngine},
     render::{DrawCommand, DrawList},
+    software_renderer::SoftwareRenderer,
     style::TextStyle,
 };
 use glam::Vec2;
@@ -253,9 +256,11 @@ impl TestInteractionContext {
     /// Simulate scroll wheel
     pub fn scroll(&mut self, position: Vec2, delta: Vec2) -> Vec<InteractionEvent> {
         self.sync_hit_test();
-        let events = self
-            .system
-            .handle_input(&InputEvent::ScrollWheel { position, delta });
+        let events = self.system.handle_input(&InputEvent::ScrollWheel {
+            position,
+            delta,
+            phase: ScrollPhase::None,
+        });
         self.collected_events.extend(events.clone());
         events
     }
@@ -414,12 +419,21 @@ impl TestPaintContext {
                     position,
                     text,
                     style,
+                    ..
                 } => Some((position, text.as_str(), style)),
                 _ => None,
             })
             .collect()
     }
 
+    /// Rasterize the captured draw commands on the CPU
+    ///
+    /// Renders `size` points at the context's scale factor over a
+    /// transparent background, without needing Metal.
+    pub fn render(&self, size: Vec2) -> RgbaImage {
+        SoftwareRenderer::new().render(&self.draw_list, size, self.scale_factor, TRANSPARENT)
+    }
+
     /// Clear all captured data
     pub fn clear(&mut self) {
         self.draw_list.clear();
@@ -484,6 +498,8 @@ where
         let matches_element = match e {
             InteractionEvent::MouseEnter { element_id: id }
             | InteractionEvent::MouseLeave { element_id: id }
+            | InteractionEvent::HoverStart { element_id: id }
+            | InteractionEvent::HoverEnd { element_id: id }
             | InteractionEvent::MouseMove { element_id: id, .. }
             | InteractionEvent::MouseDown { element_id: id, .. }
             | InteractionEvent::MouseUp { element_id: id, .. }
@@ -492,6 +508,9 @@ where
             | InteractionEvent::TripleClick { element_id: id, .. }
             | InteractionEvent::RightClick { element_id: id, .. }
             | InteractionEvent::ScrollWheel { element_id: id, .. }
+            | InteractionEvent::Magnify { element_id: id, .. }
+            | InteractionEvent::MouseDelta { element_id: id, .. }
+            | InteractionEvent::TabletMove { element_id: id, .. }
             | InteractionEvent::KeyDown { element_id: id, .. }
             | InteractionEvent::KeyUp { element_id: id, .. }
             | InteractionEvent::FocusIn { element_id: id }
@@ -0,0 +1,183 @@
+//! Per-glyph animation for text effects
+//!
+//! A [`GlyphEffect`] maps each shaped glyph of a string to a
+//! [`GlyphTransform`] when the text is painted. It's evaluated every frame
+//! against the frame clock, so waves, typewriter reveals and staggered
+//! fade-ins animate without the element keeping any state.
+//!
+//! ```ignore
+//! text("Welcome", style).glyph_effect(GlyphEffect::fade_in(start, 0.04, 0.3))
+//! ```
+//!
+//! Text keeps requesting frames while any glyph is away from its resting
+//! place, so effects that finish stop redrawing once every glyph is back at
+//! [`GlyphTransform::IDENTITY`].
+
+use glam::Vec2;
+use std::fmt;
+use std::rc::Rc;
+
+/// A glyph being placed by an effect
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlyphInfo {
+    /// Position of the glyph in the string's glyphs, in visual order
+    pub index: usize,
+    /// Number of glyphs in the string
+    pub count: usize,
+    /// Glyph origin on its baseline, relative to the text origin
+    pub position: Vec2,
+    /// Seconds on the frame clock, as from [`frame_time`](crate::clock::frame_time)
+    pub time: f32,
+}
+
+/// How to draw one glyph, relative to where the text layout put it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlyphTransform {
+    /// Offset in points
+    pub offset: Vec2,
+    /// Scale around the glyph's center
+    pub scale: f32,
+    /// Opacity multiplier, from 0.0 (hidden) to 1.0
+    pub opacity: f32,
+}
+
+impl GlyphTransform {
+    /// The glyph where layout put it
+    pub const IDENTITY: Self = Self {
+        offset: Vec2::ZERO,
+        scale: 1.0,
+        opacity: 1.0,
+    };
+
+    /// Whether the glyph is drawn where layout put it
+    pub fn is_identity(&self) -> bool {
+        *self == Self::IDENTITY
+    }
+
+    /// Move the glyph by `offset` points
+    pub fn offset(mut self, offset: Vec2) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Scale the glyph around its center
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Set the glyph's opacity
+    pub fn opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity.clamp(0.0, 1.0);
+        self
+    }
+}
+
+impl Default for GlyphTransform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// Callback placing each glyph of a string when it's painted
+#[derive(Clone)]
+pub struct GlyphEffect(Rc<dyn Fn(&GlyphInfo) -> GlyphTransform>);
+
+impl GlyphEffect {
+    /// Wrap a callback
+    pub fn new(transform: impl Fn(&GlyphInfo) -> GlyphTransform + 'static) -> Self {
+        Self(Rc::new(transform))
+    }
+
+    /// Place a glyph
+    pub fn apply(&self, glyph: &GlyphInfo) -> GlyphTransform {
+        (self.0)(glyph)
+    }
+
+    /// Bob glyphs up and down in a wave travelling along the text
+    ///
+    /// `amplitude` is in points, `wavelength` in glyphs and `speed` in waves
+    /// per second. Runs until the effect is removed.
+    pub fn wave(amplitude: f32, wavelength: f32, speed: f32) -> Self {
+        Self::new(move |glyph| {
+            let phase = glyph.index as f32 / wavelength.max(f32::EPSILON) - glyph.time * speed;
+            let y = (phase * std::f32::consts::TAU).sin() * amplitude;
+            GlyphTransform::IDENTITY.offset(Vec2::new(0.0, y))
+        })
+    }
+
+    /// Reveal glyphs one at a time, starting at frame time `start`
+    pub fn typewriter(start: f32, glyphs_per_second: f32) -> Self {
+        Self::new(move |glyph| {
+            let shown = (glyph.time - start) * glyphs_per_second;
+            if (glyph.index as f32) < shown {
+                GlyphTransform::IDENTITY
+            } else {
+                GlyphTransform::IDENTITY.opacity(0.0)
+            }
+        })
+    }
+
+    /// Fade glyphs in one after another, starting at frame time `start`
+    ///
+    /// Each glyph starts `stagger` seconds after the one before it and takes
+    /// `duration` seconds, rising slightly into place as it appears.
+    pub fn fade_in(start: f32, stagger: f32, duration: f32) -> Self {
+        Self::new(move |glyph| {
+            let elapsed = glyph.time - start - glyph.index as f32 * stagger;
+            let t = if duration > 0.0 {
+                (elapsed / duration).clamp(0.0, 1.0)
+            } else if elapsed >= 0.0 {
+                1.0
+            } else {
+                0.0
+            };
+            if t >= 1.0 {
+                return GlyphTransform::IDENTITY;
+            }
+            // Ease out cubic
+            let eased = 1.0 - (1.0 - t).powi(3);
+            GlyphTransform::IDENTITY
+                .offset(Vec2::new(0.0, (1.0 - eased) * 4.0))
+                .opacity(eased)
+        })
+    }
+}
+
+impl fmt::Debug for GlyphEffect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("GlyphEffect(..)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glyph(index: usize, time: f32) -> GlyphInfo {
+        GlyphInfo {
+            index,
+            count: 5,
+            position: Vec2::ZERO,
+            time,
+        }
+    }
+
+    #[test]
+    fn test_typewriter_reveals_in_order() {
+        let effect = GlyphEffect::typewriter(1.0, 10.0);
+        // 0.25s in, glyphs 0 to 2 are shown
+        assert!(effect.apply(&glyph(2, 1.25)).is_identity());
+        assert_eq!(effect.apply(&glyph(3, 1.25)).opacity, 0.0);
+        assert_eq!(effect.apply(&glyph(0, 0.5)).opacity, 0.0);
+    }
+
+    #[test]
+    fn test_fade_in_settles() {
+        let effect = GlyphEffect::fade_in(0.0, 0.1, 0.2);
+        let fading = effect.apply(&glyph(1, 0.2));
+        assert!(fading.opacity > 0.0 && fading.opacity < 1.0);
+        assert_eq!(effect.apply(&glyph(1, 0.05)).opacity, 0.0);
+        assert!(effect.apply(&glyph(4, 0.6)).is_identity());
+    }
+}
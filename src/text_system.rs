@@ -1,16 +1,24 @@
 //! A [Parley](https://crates.io/crates/parley) based system for laying out and rendering rich text
 
 use glam::Vec2;
-use metal::{Device, Texture};
+#[cfg(target_os = "macos")]
+use metal::{Device, Texture, TextureRef};
 use parley::{
-    FontContext, FontStack, FontWeight, GlyphRun, Layout, LayoutContext, LineHeight,
-    PositionedLayoutItem, StyleProperty,
+    FontContext, FontSettings, FontStack, FontWeight, GlyphRun, Layout, LayoutContext,
+    PositionedLayoutItem, RangedBuilder, StyleProperty,
 };
 use std::collections::{HashMap, VecDeque};
 use swash::FontRef;
 use swash::scale::{Render, ScaleContext, Source};
 
 use crate::color::{Color, ColorExt};
+use crate::error::{SolError, SolResult};
+use crate::geometry::Rect;
+use crate::style::{
+    DecorationLine, DecorationStyle, FontAxes, GlyphRendering, LineHeight, TextDecoration,
+    TextStyle, feature_settings,
+};
+use std::ops::Range;
 use std::time::Instant;
 use tracing::{debug, info, info_span};
 
@@ -25,10 +33,22 @@ pub struct TextConfig {
     pub size: f32,
     /// Font weight
     pub weight: FontWeight,
+    /// Width, style, optical sizing and other variable font axes
+    pub axes: FontAxes,
+    /// OpenType features to turn on or off by tag
+    pub font_features: Vec<([u8; 4], u16)>,
     /// Text color
     pub color: Color,
-    /// Line height multiplier
-    pub line_height: f32,
+    /// Colors for byte ranges of the text, used instead of `color`
+    pub color_ranges: Vec<(Range<usize>, Color)>,
+    /// Height of each line
+    pub line_height: LineHeight,
+    /// Extra space between characters in logical pixels
+    pub letter_spacing: f32,
+    /// Extra space between words in logical pixels
+    pub word_spacing: f32,
+    /// Whether glyphs are drawn from bitmaps or distance fields
+    pub glyph_rendering: GlyphRendering,
 }
 
 impl Default for TextConfig {
@@ -37,22 +57,113 @@ impl Default for TextConfig {
             font_stack: FontStack::from("system-ui"),
             size: 16.0,
             weight: FontWeight::NORMAL,
+            axes: FontAxes::default(),
+            font_features: Vec::new(),
             color: Color::new(0.0, 0.0, 0.0, 1.0),
-            line_height: 1.2,
+            color_ranges: Vec::new(),
+            line_height: LineHeight::default(),
+            letter_spacing: 0.0,
+            word_spacing: 0.0,
+            glyph_rendering: GlyphRendering::Auto,
         }
     }
 }
 
+impl From<&TextStyle> for TextConfig {
+    fn from(style: &TextStyle) -> Self {
+        Self {
+            font_stack: FontStack::from(style.font_family),
+            size: style.size,
+            weight: style.weight,
+            axes: style.axes.clone(),
+            font_features: style.font_features.clone(),
+            color: style.color,
+            color_ranges: style.color_ranges.clone(),
+            line_height: style.line_height,
+            letter_spacing: style.letter_spacing,
+            word_spacing: style.word_spacing,
+            glyph_rendering: style.glyph_rendering,
+        }
+    }
+}
+
+impl TextConfig {
+    /// Push this configuration's styles as the defaults for a layout
+    ///
+    /// `settings` holds the variation and feature settings from
+    /// [`font_settings`](Self::font_settings), which the builder borrows.
+    pub(crate) fn push_styles<'a>(
+        &self,
+        builder: &mut RangedBuilder<'a, [u8; 4]>,
+        (variations, features): &'a (Option<String>, Option<String>),
+    ) {
+        builder.push_default(StyleProperty::Brush(self.color.as_u8_arr()));
+        builder.push_default(self.font_stack.clone());
+        builder.push_default(StyleProperty::FontSize(self.size));
+        builder.push_default(StyleProperty::FontWeight(self.weight));
+        builder.push_default(StyleProperty::FontWidth(self.axes.width));
+        builder.push_default(StyleProperty::FontStyle(self.axes.style));
+        if let Some(variations) = variations {
+            builder.push_default(StyleProperty::FontVariations(FontSettings::from(
+                variations.as_str(),
+            )));
+        }
+        if let Some(features) = features {
+            builder.push_default(StyleProperty::FontFeatures(FontSettings::from(
+                features.as_str(),
+            )));
+        }
+        // Pixel line heights are passed relative to the font size so they
+        // scale with it to device pixels
+        builder.push_default(StyleProperty::LineHeight(
+            parley::LineHeight::FontSizeRelative(self.line_height.multiplier(self.size)),
+        ));
+        builder.push_default(StyleProperty::LetterSpacing(self.letter_spacing));
+        builder.push_default(StyleProperty::WordSpacing(self.word_spacing));
+        // Colors don't split shaping runs, so kerning carries across them
+        for (range, color) in &self.color_ranges {
+            builder.push(StyleProperty::Brush(color.as_u8_arr()), range.clone());
+        }
+    }
+
+    /// Layout inputs that aren't font settings, rounded for cache keys:
+    /// (line height, letter spacing, word spacing)
+    fn spacing_key(&self) -> (u32, i32, i32) {
+        (
+            (self.line_height.resolve(self.size) * 100.0) as u32,
+            (self.letter_spacing * 100.0) as i32,
+            (self.word_spacing * 100.0) as i32,
+        )
+    }
+
+    /// Whether glyphs are drawn from distance fields at `scale_factor`
+    fn uses_distance_field(&self, scale_factor: f32) -> bool {
+        match self.glyph_rendering {
+            GlyphRendering::Auto => self.size * scale_factor >= DISTANCE_FIELD_THRESHOLD,
+            GlyphRendering::Bitmap => false,
+            GlyphRendering::DistanceField => true,
+        }
+    }
+
+    /// Variable font axis and OpenType feature settings for the shaper
+    pub(crate) fn font_settings(&self) -> (Option<String>, Option<String>) {
+        (
+            self.axes.variation_settings(self.size),
+            feature_settings(&self.font_features),
+        )
+    }
+}
+
 /// Information about a glyph in the atlas
 #[derive(Debug, Clone, Copy)]
 pub struct GlyphInfo {
     /// UV coordinates in the atlas (0.0 to 1.0)
     pub uv_min: (f32, f32),
     pub uv_max: (f32, f32),
-    /// Size of the glyph in pixels
+    /// Size of the glyph bitmap in device pixels
     pub width: u32,
     pub height: u32,
-    /// Offset from the glyph origin to the top-left of the bitmap
+    /// Offset from the glyph origin to the top-left of the bitmap, in device pixels
     pub left: i32,
     pub top: i32,
 }
@@ -63,6 +174,7 @@ struct GlyphKey {
     font_id: u64,
     glyph_id: u16,
     size: u32,
+    scale: u32,
 }
 
 /// A shelf in the atlas for packing glyphs
@@ -81,18 +193,76 @@ struct Shelf {
 /// neighboring glyph data.
 const GLYPH_ATLAS_PADDING: u32 = 1;
 
+/// Font size in device pixels at and above which [`GlyphRendering::Auto`]
+/// draws glyphs from distance fields
+const DISTANCE_FIELD_THRESHOLD: f32 = 64.0;
+
+/// Font size in pixels distance field glyphs are rasterized at
+const DISTANCE_FIELD_SIZE: u32 = 64;
+
+/// Distance in pixels a distance field covers on each side of the outline
+///
+/// Glyph fields are padded by this much so the field fades out before the
+/// edge of the glyph's quad.
+const DISTANCE_FIELD_SPREAD: u32 = 8;
+
+/// Atlas scale key for distance field glyphs, which don't depend on the
+/// display's scale factor
+const DISTANCE_FIELD_SCALE_KEY: u32 = 0;
+
+/// The color range covering a glyph run, if any
+///
+/// Runs are split where the color changes, and each carries its range's
+/// color as its brush.
+pub(crate) fn run_color(
+    glyph_run: &GlyphRun<'_, [u8; 4]>,
+    color_ranges: &[(Range<usize>, Color)],
+) -> Option<Color> {
+    let brush = glyph_run.style().brush;
+    color_ranges
+        .iter()
+        .rev()
+        .map(|(_, color)| *color)
+        .find(|color| color.as_u8_arr() == brush)
+}
+
+/// Atlas size and scale keys for a glyph rasterized at `font_size` device
+/// pixels
+///
+/// Keyed on the exact rasterized size, so fractional sizes that round to the
+/// same logical size don't share bitmaps.
+fn raster_key(font_size: f32, scale_factor: f32) -> (u32, u32) {
+    (font_size.to_bits(), (scale_factor * 100.0).round() as u32)
+}
+
+/// Device pixel size to rasterize a glyph at, rounded to a whole pixel when
+/// glyph sizes are coarse
+fn raster_size(font_size: f32, coarse: bool) -> f32 {
+    if coarse {
+        font_size.round().max(1.0)
+    } else {
+        font_size
+    }
+}
+
 /// Glyph atlas that manages glyph textures
 pub struct GlyphAtlas {
-    texture: Texture,
+    /// Texture glyphs are uploaded to, or `None` for a headless atlas that
+    /// only tracks placement
+    #[cfg(target_os = "macos")]
+    texture: Option<Texture>,
     width: u32,
     height: u32,
     glyphs: HashMap<GlyphKey, GlyphInfo>,
     shelves: Vec<Shelf>,
+    /// Pixels allocated to glyphs, including padding
+    used_pixels: u64,
 }
 
 impl GlyphAtlas {
     /// Create a new glyph atlas with the given dimensions
-    pub fn new(device: &Device, width: u32, height: u32) -> Result<Self, String> {
+    #[cfg(target_os = "macos")]
+    pub fn new(device: &Device, width: u32, height: u32) -> SolResult<Self> {
         let descriptor = metal::TextureDescriptor::new();
         descriptor.set_pixel_format(metal::MTLPixelFormat::R8Unorm);
         descriptor.set_width(width as u64);
@@ -120,40 +290,56 @@ impl GlyphAtlas {
         );
 
         Ok(Self {
-            texture,
+            texture: Some(texture),
+            ..Self::headless(width, height)
+        })
+    }
+
+    /// Create an atlas that places glyphs without uploading them anywhere,
+    /// for rendering without a GPU
+    pub fn headless(width: u32, height: u32) -> Self {
+        Self {
+            #[cfg(target_os = "macos")]
+            texture: None,
             width,
             height,
             glyphs: HashMap::new(),
             shelves: vec![],
-        })
+            used_pixels: 0,
+        }
     }
 
     /// Check if a glyph is in the atlas
-    pub fn contains(&self, font_id: u64, glyph_id: u16, size: u32) -> bool {
+    pub fn contains(&self, font_id: u64, glyph_id: u16, size: u32, scale: u32) -> bool {
         let key = GlyphKey {
             font_id,
             glyph_id,
             size,
+            scale,
         };
         self.glyphs.contains_key(&key)
     }
 
     /// Add a glyph to the atlas
+    ///
+    /// `data` is the glyph bitmap rasterized at device pixels for `scale`.
     pub fn add_glyph(
         &mut self,
         font_id: u64,
         glyph_id: u16,
         size: u32,
+        scale: u32,
         data: &[u8],
         width: u32,
         height: u32,
         left: i32,
         top: i32,
-    ) -> Result<(), String> {
+    ) -> SolResult<()> {
         let key = GlyphKey {
             font_id,
             glyph_id,
             size,
+            scale,
         };
 
         if self.glyphs.contains_key(&key) {
@@ -162,9 +348,16 @@ impl GlyphAtlas {
 
         let (x, y) = self.find_position(width, height)?;
 
-        // Upload glyph data to texture
-        if !data.is_empty() && width > 0 && height > 0 {
-            self.texture.replace_region(
+        // Upload glyph data to texture; headless atlases only track placement
+        #[cfg(not(target_os = "macos"))]
+        let _ = data;
+        #[cfg(target_os = "macos")]
+        if let Some(texture) = self
+            .texture
+            .as_ref()
+            .filter(|_| !data.is_empty() && width > 0 && height > 0)
+        {
+            texture.replace_region(
                 metal::MTLRegion {
                     origin: metal::MTLOrigin {
                         x: x as u64,
@@ -203,31 +396,64 @@ impl GlyphAtlas {
     }
 
     /// Get information about a glyph in the atlas
-    pub fn get_glyph(&self, font_id: u64, glyph_id: u16, size: u32) -> Option<&GlyphInfo> {
+    pub fn get_glyph(
+        &self,
+        font_id: u64,
+        glyph_id: u16,
+        size: u32,
+        scale: u32,
+    ) -> Option<&GlyphInfo> {
         let key = GlyphKey {
             font_id,
             glyph_id,
             size,
+            scale,
         };
         self.glyphs.get(&key)
     }
 
-    /// Get the atlas texture
-    pub fn texture(&self) -> &Texture {
-        &self.texture
+    /// Get the atlas texture, if the atlas has one
+    #[cfg(target_os = "macos")]
+    pub fn texture(&self) -> Option<&TextureRef> {
+        self.texture.as_deref()
+    }
+
+    /// Number of textures backing the atlas
+    ///
+    /// Glyphs share a single texture; once it's full, adding more fails with
+    /// [`SolError::AtlasFull`] rather than opening another page.
+    pub fn page_count(&self) -> usize {
+        1
+    }
+
+    /// Number of glyphs in the atlas
+    pub fn glyph_count(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    /// Pixels allocated to glyphs, including padding
+    pub fn used_pixels(&self) -> u64 {
+        self.used_pixels
+    }
+
+    /// Total pixels across the atlas pages
+    pub fn total_pixels(&self) -> u64 {
+        self.page_count() as u64 * self.width as u64 * self.height as u64
     }
 
     /// Find a position for a glyph using shelf packing
-    fn find_position(&mut self, width: u32, height: u32) -> Result<(u32, u32), String> {
+    fn find_position(&mut self, width: u32, height: u32) -> SolResult<(u32, u32)> {
         // Add padding on each side to prevent texture bleeding during bilinear filtering
         let padded_width = width + GLYPH_ATLAS_PADDING * 2;
         let padded_height = height + GLYPH_ATLAS_PADDING * 2;
+        let padded_pixels = padded_width as u64 * padded_height as u64;
 
         // Try to fit in an existing shelf
         for shelf in &mut self.shelves {
             if shelf.height >= padded_height && shelf.next_x + padded_width <= self.width {
                 let x = shelf.next_x;
                 shelf.next_x += padded_width;
+                self.used_pixels += padded_pixels;
                 // Skip the padding at the start of the allocation
                 return Ok((x + GLYPH_ATLAS_PADDING, shelf.y + GLYPH_ATLAS_PADDING));
             }
@@ -241,7 +467,12 @@ impl GlyphAtlas {
         };
 
         if next_y + padded_height > self.height {
-            return Err("Atlas is full".to_string());
+            return Err(SolError::AtlasFull {
+                atlas_width: self.width,
+                atlas_height: self.height,
+                glyph_width: width,
+                glyph_height: height,
+            });
         }
 
         self.shelves.push(Shelf {
@@ -249,6 +480,7 @@ impl GlyphAtlas {
             height: padded_height,
             next_x: padded_width,
         });
+        self.used_pixels += padded_pixels;
 
         // Skip the padding at the start of the allocation
         Ok((GLYPH_ATLAS_PADDING, next_y + GLYPH_ATLAS_PADDING))
@@ -258,14 +490,108 @@ impl GlyphAtlas {
 /// A shaped glyph ready for rendering
 #[derive(Debug, Clone)]
 pub struct ShapedGlyph {
-    /// Font ID (unique identifier for font)
+    /// Font ID, unique to the font and its variable axis values
     pub font_id: u64,
     /// Glyph ID in the font
     pub glyph_id: u16,
-    /// Size in pixels
+    /// Atlas key for the size the glyph was rasterized at: the bits of its
+    /// size in device pixels, or the rasterized size for distance field
+    /// glyphs
     pub size: u32,
-    /// Position relative to text origin
+    /// Scale factor the glyph was rasterized for, in hundredths, or 0 for
+    /// distance field glyphs
+    pub scale: u32,
+    /// Position relative to text origin, in logical pixels
     pub position: Vec2,
+    /// Logical pixels per atlas pixel, for glyphs drawn from distance fields
+    pub distance_field_scale: Option<f32>,
+    /// Color from the text's color ranges, used instead of the text color
+    pub color: Option<Color>,
+}
+
+impl ShapedGlyph {
+    /// Whether the glyph is drawn from a distance field
+    pub fn is_distance_field(&self) -> bool {
+        self.distance_field_scale.is_some()
+    }
+}
+
+/// A position between characters, as a byte offset into the shaped string
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct CharIndex(pub usize);
+
+/// Which character a caret position sticks to
+///
+/// Where left-to-right and right-to-left text meet, one offset is at two
+/// places on screen: after the character before it, and before the
+/// character after it. The affinity picks one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CaretAffinity {
+    /// Next to the character after the offset
+    #[default]
+    Downstream,
+    /// Next to the character before the offset, e.g. just after typing it
+    Upstream,
+}
+
+impl CaretAffinity {
+    fn opposite(self) -> Self {
+        match self {
+            CaretAffinity::Downstream => CaretAffinity::Upstream,
+            CaretAffinity::Upstream => CaretAffinity::Downstream,
+        }
+    }
+}
+
+/// Where to draw a caret, as zero-width rects relative to the text origin
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CaretRects {
+    /// The caret for the position's affinity, where typed text goes
+    pub primary: Rect,
+    /// The other place the position appears, at a direction boundary
+    pub secondary: Option<Rect>,
+}
+
+/// A cluster of glyphs that maps to one or more characters
+#[derive(Debug, Clone)]
+pub struct ShapedCluster {
+    /// Byte range of the characters in the shaped string
+    pub text_range: Range<usize>,
+    /// Area covered by the cluster, relative to the text origin
+    pub bounds: Rect,
+    /// Index of the line in [`ShapedText::lines`]
+    pub line: usize,
+    /// Whether the cluster is in a right-to-left run
+    pub is_rtl: bool,
+}
+
+/// A laid out line of text
+#[derive(Debug, Clone)]
+pub struct ShapedLine {
+    /// Byte range of the line in the shaped string
+    pub text_range: Range<usize>,
+    /// Top of the line, relative to the text origin
+    pub top: f32,
+    /// Height of the line
+    pub height: f32,
+    /// Baseline of the line, relative to the text origin
+    pub baseline: f32,
+    /// Where the font puts decoration lines
+    pub decoration: DecorationMetrics,
+}
+
+/// Font metrics for text decorations, relative to the baseline with
+/// positive values above it
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DecorationMetrics {
+    /// Height of the font above the baseline, where overlines go
+    pub ascent: f32,
+    /// Top of the underline
+    pub underline_offset: f32,
+    pub underline_size: f32,
+    /// Top of the strikethrough
+    pub strikethrough_offset: f32,
+    pub strikethrough_size: f32,
 }
 
 /// Result of text shaping
@@ -273,7 +599,11 @@ pub struct ShapedGlyph {
 pub struct ShapedText {
     /// Individual glyphs with positions
     pub glyphs: Vec<ShapedGlyph>,
-    /// Total size of the shaped text
+    /// Character clusters in visual order, excluding line breaks
+    pub clusters: Vec<ShapedCluster>,
+    /// Lines from top to bottom
+    pub lines: Vec<ShapedLine>,
+    /// Total size of the shaped text, in logical pixels
     pub size: Vec2,
 }
 
@@ -284,9 +614,14 @@ struct ShapedTextCacheKey {
     font_stack: String,
     size: u32,
     weight: u16,
-    line_height: u32,
+    axes: String,
+    font_features: Vec<([u8; 4], u16)>,
+    /// Line height, letter spacing and word spacing
+    spacing: (u32, i32, i32),
     max_width: Option<u32>,
     scale_factor: u32,
+    distance_field: bool,
+    color_ranges: Vec<(Range<usize>, [u8; 4])>,
 }
 
 /// Maximum number of entries in the shaped text cache before eviction.
@@ -299,8 +634,8 @@ pub struct TextSystem {
     layout_context: LayoutContext,
     scale_context: ScaleContext,
     glyph_atlas: GlyphAtlas,
-    /// Cache of font data to ID mappings
-    font_id_cache: HashMap<Vec<u8>, u64>,
+    /// Cache of font data and normalized axis values to ID mappings
+    font_id_cache: HashMap<(Vec<u8>, Vec<i16>), u64>,
     next_font_id: u64,
     /// Cache of shaped text (bounded LRU-style cache)
     shaped_text_cache: HashMap<ShapedTextCacheKey, ShapedText>,
@@ -308,6 +643,12 @@ pub struct TextSystem {
     shaped_text_cache_order: VecDeque<ShapedTextCacheKey>,
     /// Frame-based cache for text measurements to avoid duplicate work
     measurement_cache: HashMap<MeasurementCacheKey, Vec2>,
+    /// Cache of single-line text fitted to a width, so truncated text
+    /// isn't searched for again each frame
+    truncation_cache: HashMap<(MeasurementCacheKey, Truncation), FittedText>,
+    /// Rasterize glyphs at whole device pixel sizes, so nearby sizes share
+    /// atlas entries
+    coarse_glyph_sizes: bool,
 }
 
 /// Key for text measurement cache
@@ -317,15 +658,133 @@ struct MeasurementCacheKey {
     font_stack: String,
     size: u32,
     weight: u16,
-    line_height: u32,
+    axes: String,
+    font_features: Vec<([u8; 4], u16)>,
+    /// Line height, letter spacing and word spacing
+    spacing: (u32, i32, i32),
     max_width: Option<u32>,
     scale_factor: u32,
 }
 
+impl MeasurementCacheKey {
+    fn new(text: &str, config: &TextConfig, max_width: Option<f32>, scale_factor: f32) -> Self {
+        Self {
+            text: text.to_string(),
+            font_stack: format!("{:?}", config.font_stack),
+            size: (config.size * 100.0) as u32,
+            weight: config.weight.value() as u16,
+            axes: format!("{:?}", config.axes),
+            font_features: config.font_features.clone(),
+            spacing: config.spacing_key(),
+            max_width: max_width.map(|w| (w * 100.0) as u32),
+            scale_factor: (scale_factor * 100.0) as u32,
+        }
+    }
+}
+
+/// How text that doesn't fit its width is shortened
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Truncation {
+    /// Wrap onto more lines instead of truncating
+    #[default]
+    None,
+    /// Cut the end and append an ellipsis: "A long sente…"
+    Ellipsis,
+    /// Cut the middle and keep both ends, e.g. for paths: "/Users/…/main.rs"
+    Middle,
+}
+
+const ELLIPSIS: &str = "\u{2026}";
+
+/// Shorten `text` to fit `max_width`, or return `None` if it already fits
+///
+/// `measure` returns the single-line width of a string. Keeps as many
+/// characters as fit alongside the ellipsis.
+pub fn truncate_text(
+    text: &str,
+    max_width: f32,
+    mode: Truncation,
+    mut measure: impl FnMut(&str) -> f32,
+) -> Option<String> {
+    if mode == Truncation::None || text.is_empty() || measure(text) <= max_width {
+        return None;
+    }
+
+    let chars: Vec<char> = text.chars().collect();
+    let candidate = |keep: usize| -> String {
+        let head_len = match mode {
+            Truncation::Middle => keep.div_ceil(2),
+            _ => keep,
+        };
+        let head: String = chars[..head_len].iter().collect();
+        let tail: String = chars[chars.len() - (keep - head_len)..].iter().collect();
+        format!("{}{ELLIPSIS}{}", head.trim_end(), tail.trim_start())
+    };
+
+    // Binary search for the most characters that still fit
+    let (mut low, mut high) = (0, chars.len() - 1);
+    while low < high {
+        let mid = (low + high).div_ceil(2);
+        if measure(&candidate(mid)) <= max_width {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Some(candidate(low))
+}
+
+/// Single-line text fitted to a width by [`TextSystem::measure_truncated`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct FittedText {
+    /// The shortened text, or `None` when the full text fits
+    pub truncated: Option<String>,
+    /// Size of the text as it will be painted
+    pub size: Vec2,
+}
+
+impl FittedText {
+    /// Whether the text had to be shortened to fit
+    pub fn is_truncated(&self) -> bool {
+        self.truncated.is_some()
+    }
+}
+
 impl TextSystem {
     /// Create a new text system with the given Metal device
-    pub fn new(device: &Device) -> Result<Self, String> {
+    #[cfg(target_os = "macos")]
+    pub fn new(device: &Device) -> SolResult<Self> {
+        Self::with_atlas_size(device, 2048, 2048)
+    }
+
+    /// Create a new text system with a glyph atlas of the given dimensions
+    ///
+    /// Useful for retrying with a smaller atlas on memory-constrained devices.
+    #[cfg(target_os = "macos")]
+    pub fn with_atlas_size(
+        device: &Device,
+        atlas_width: u32,
+        atlas_height: u32,
+    ) -> SolResult<Self> {
         let _new_span = info_span!("text_system_new").entered();
+        let start = Instant::now();
+        let glyph_atlas = GlyphAtlas::new(device, atlas_width, atlas_height)?;
+        info!("GlyphAtlas created in {:?}", start.elapsed());
+        Ok(Self::with_glyph_atlas(glyph_atlas))
+    }
+
+    /// Create a text system whose glyph atlas has no texture, for laying
+    /// out and measuring text without a GPU
+    ///
+    /// Renderers that rasterize glyphs themselves, such as the
+    /// [software renderer](crate::software_renderer::SoftwareRenderer), can
+    /// draw text shaped by it.
+    pub fn headless() -> Self {
+        let _new_span = info_span!("text_system_new_headless").entered();
+        Self::with_glyph_atlas(GlyphAtlas::headless(2048, 2048))
+    }
+
+    fn with_glyph_atlas(glyph_atlas: GlyphAtlas) -> Self {
         let total_start = Instant::now();
 
         let start = Instant::now();
@@ -340,16 +799,12 @@ impl TextSystem {
         let scale_context = ScaleContext::new();
         info!("ScaleContext created in {:?}", start.elapsed());
 
-        let start = Instant::now();
-        let glyph_atlas = GlyphAtlas::new(device, 2048, 2048)?;
-        info!("GlyphAtlas created in {:?}", start.elapsed());
-
         info!(
             "Total TextSystem initialization: {:?}",
             total_start.elapsed()
         );
 
-        Ok(Self {
+        Self {
             font_context,
             layout_context,
             scale_context,
@@ -359,7 +814,9 @@ impl TextSystem {
             shaped_text_cache: HashMap::new(),
             shaped_text_cache_order: VecDeque::new(),
             measurement_cache: HashMap::new(),
-        })
+            truncation_cache: HashMap::new(),
+            coarse_glyph_sizes: false,
+        }
     }
 
     /// Called at the start of each frame - maintains caches
@@ -374,6 +831,9 @@ impl TextSystem {
             );
             self.measurement_cache.clear();
         }
+        if self.truncation_cache.len() > MAX_MEASUREMENT_CACHE_SIZE {
+            self.truncation_cache.clear();
+        }
 
         // Similarly for shaped text cache
         const MAX_SHAPED_TEXT_CACHE_SIZE: usize = 500;
@@ -386,6 +846,21 @@ impl TextSystem {
         }
     }
 
+    /// Rasterize glyphs at whole device pixel sizes instead of their exact
+    /// sizes
+    ///
+    /// Text at nearby sizes then shares cached glyphs, at the cost of glyphs
+    /// up to half a pixel too large or small. Turned on by the quality
+    /// governor when frames run over budget.
+    pub fn set_coarse_glyph_sizes(&mut self, coarse: bool) {
+        if self.coarse_glyph_sizes != coarse {
+            self.coarse_glyph_sizes = coarse;
+            // Cached text refers to glyphs at the old sizes
+            self.shaped_text_cache.clear();
+            self.shaped_text_cache_order.clear();
+        }
+    }
+
     /// Measure text with the given configuration
     pub fn measure_text(
         &mut self,
@@ -400,15 +875,7 @@ impl TextSystem {
         }
 
         // Create cache key
-        let cache_key = MeasurementCacheKey {
-            text: text.to_string(),
-            font_stack: format!("{:?}", config.font_stack),
-            size: (config.size * 100.0) as u32,
-            weight: config.weight.value() as u16,
-            line_height: (config.line_height * 100.0) as u32,
-            max_width: max_width.map(|w| (w * 100.0) as u32),
-            scale_factor: (scale_factor * 100.0) as u32,
-        };
+        let cache_key = MeasurementCacheKey::new(text, config, max_width, scale_factor);
 
         // Check cache
         if let Some(&cached_size) = self.measurement_cache.get(&cache_key) {
@@ -426,6 +893,7 @@ impl TextSystem {
         }
 
         // Create a layout
+        let settings = config.font_settings();
         let mut builder = self.layout_context.ranged_builder(
             &mut self.font_context,
             text,
@@ -434,19 +902,13 @@ impl TextSystem {
         );
 
         // Apply text styles
-        let brush = config.color.as_u8_arr();
-        builder.push_default(StyleProperty::Brush(brush));
-        builder.push_default(config.font_stack.clone());
-        builder.push_default(StyleProperty::FontSize(config.size));
-        builder.push_default(StyleProperty::FontWeight(config.weight));
-        builder.push_default(StyleProperty::LineHeight(LineHeight::FontSizeRelative(
-            config.line_height,
-        )));
+        config.push_styles(&mut builder, &settings);
 
         let mut layout: Layout<[u8; 4]> = builder.build(text);
-        layout.break_all_lines(max_width);
+        layout.break_all_lines(max_width.map(|w| w * scale_factor));
 
-        let size = Vec2::new(layout.width(), layout.height());
+        // Layout is in device pixels, report logical size
+        let size = Vec2::new(layout.width(), layout.height()) / scale_factor;
 
         // Store in cache
         self.measurement_cache.insert(cache_key, size);
@@ -464,6 +926,41 @@ impl TextSystem {
         size
     }
 
+    /// Measure `text` on a single line, shortening it with `mode` when it is
+    /// wider than `max_width`
+    ///
+    /// The result is cached by text, width and style, so text that stays the
+    /// same between frames is only fitted once.
+    pub fn measure_truncated(
+        &mut self,
+        text: &str,
+        config: &TextConfig,
+        max_width: f32,
+        mode: Truncation,
+        scale_factor: f32,
+    ) -> FittedText {
+        let cache_key = (
+            MeasurementCacheKey::new(text, config, Some(max_width), scale_factor),
+            mode,
+        );
+        if let Some(fitted) = self.truncation_cache.get(&cache_key) {
+            return fitted.clone();
+        }
+
+        let truncated = truncate_text(text, max_width, mode, |s| {
+            self.measure_text(s, config, None, scale_factor).x
+        });
+        let size = self.measure_text(
+            truncated.as_deref().unwrap_or(text),
+            config,
+            None,
+            scale_factor,
+        );
+        let fitted = FittedText { truncated, size };
+        self.truncation_cache.insert(cache_key, fitted.clone());
+        fitted
+    }
+
     /// Shape and prepare text for rendering
     pub fn shape_text(
         &mut self,
@@ -471,11 +968,13 @@ impl TextSystem {
         config: &TextConfig,
         max_width: Option<f32>,
         scale_factor: f32,
-    ) -> Result<ShapedText, String> {
+    ) -> SolResult<ShapedText> {
         let _shape_span = info_span!("shape_text", text_len = text.len()).entered();
         if text.is_empty() {
             return Ok(ShapedText {
                 glyphs: vec![],
+                clusters: vec![],
+                lines: vec![],
                 size: Vec2::ZERO,
             });
         }
@@ -486,9 +985,17 @@ impl TextSystem {
             font_stack: format!("{:?}", config.font_stack),
             size: (config.size * 100.0) as u32,
             weight: config.weight.value() as u16,
-            line_height: (config.line_height * 100.0) as u32,
+            axes: format!("{:?}", config.axes),
+            font_features: config.font_features.clone(),
+            spacing: config.spacing_key(),
             max_width: max_width.map(|w| (w * 100.0) as u32),
             scale_factor: (scale_factor * 100.0) as u32,
+            distance_field: config.uses_distance_field(scale_factor),
+            color_ranges: config
+                .color_ranges
+                .iter()
+                .map(|(range, color)| (range.clone(), color.as_u8_arr()))
+                .collect(),
         };
 
         // Check cache
@@ -497,10 +1004,12 @@ impl TextSystem {
             // Ensure all glyphs are still in the atlas
             let mut all_glyphs_cached = true;
             for glyph in &cached.glyphs {
-                if !self
-                    .glyph_atlas
-                    .contains(glyph.font_id, glyph.glyph_id, glyph.size)
-                {
+                if !self.glyph_atlas.contains(
+                    glyph.font_id,
+                    glyph.glyph_id,
+                    glyph.size,
+                    glyph.scale,
+                ) {
                     all_glyphs_cached = false;
                     break;
                 }
@@ -513,7 +1022,8 @@ impl TextSystem {
             }
         }
 
-        // Create a layout
+        // Lay out in device pixels so glyphs rasterize at full resolution
+        let settings = config.font_settings();
         let mut builder = self.layout_context.ranged_builder(
             &mut self.font_context,
             text,
@@ -522,32 +1032,83 @@ impl TextSystem {
         );
 
         // Apply text styles
-        let brush = config.color.as_u8_arr();
-        builder.push_default(StyleProperty::Brush(brush));
-        builder.push_default(config.font_stack.clone());
-        builder.push_default(StyleProperty::FontSize(config.size));
-        builder.push_default(StyleProperty::FontWeight(config.weight));
-        builder.push_default(StyleProperty::LineHeight(LineHeight::FontSizeRelative(
-            config.line_height,
-        )));
+        config.push_styles(&mut builder, &settings);
 
         let mut layout: Layout<[u8; 4]> = builder.build(text);
-        layout.break_all_lines(max_width);
+        layout.break_all_lines(max_width.map(|w| w * scale_factor));
 
         let mut shaped_glyphs = Vec::new();
+        let mut clusters = Vec::new();
+        let mut lines = Vec::new();
 
         // Process each line and glyph run
-        for line in layout.lines() {
+        for (line_index, line) in layout.lines().enumerate() {
+            let metrics = line.metrics();
+            let half_leading = (metrics.line_height - metrics.ascent - metrics.descent) / 2.0;
+            lines.push(ShapedLine {
+                text_range: line.text_range(),
+                top: (metrics.baseline - metrics.ascent - half_leading) / scale_factor,
+                height: metrics.line_height / scale_factor,
+                baseline: metrics.baseline / scale_factor,
+                decoration: DecorationMetrics::default(),
+            });
+            let line_top = lines[line_index].top;
+            let line_height = lines[line_index].height;
+            let mut decoration = None;
+
             for item in line.items() {
                 if let PositionedLayoutItem::GlyphRun(glyph_run) = item {
-                    self.process_glyph_run(&glyph_run, &mut shaped_glyphs)?;
+                    self.process_glyph_run(
+                        &glyph_run,
+                        scale_factor,
+                        cache_key.distance_field,
+                        &config.color_ranges,
+                        &mut shaped_glyphs,
+                    )?;
+
+                    // Decorations follow the line's first font
+                    let run = glyph_run.run();
+                    decoration.get_or_insert_with(|| {
+                        let metrics = run.metrics();
+                        DecorationMetrics {
+                            ascent: metrics.ascent / scale_factor,
+                            underline_offset: metrics.underline_offset / scale_factor,
+                            underline_size: metrics.underline_size / scale_factor,
+                            strikethrough_offset: metrics.strikethrough_offset / scale_factor,
+                            strikethrough_size: metrics.strikethrough_size / scale_factor,
+                        }
+                    });
+
+                    // Record where each cluster landed for hit testing
+                    let mut x = glyph_run.offset();
+                    for cluster in run.visual_clusters() {
+                        let text_range = cluster.text_range();
+                        let advance = cluster.advance();
+                        if !text[text_range.clone()].ends_with('\n') {
+                            clusters.push(ShapedCluster {
+                                text_range,
+                                bounds: Rect::new(
+                                    x / scale_factor,
+                                    line_top,
+                                    advance / scale_factor,
+                                    line_height,
+                                ),
+                                line: line_index,
+                                is_rtl: run.is_rtl(),
+                            });
+                        }
+                        x += advance;
+                    }
                 }
             }
+            lines[line_index].decoration = decoration.unwrap_or_default();
         }
 
         let shaped_text = ShapedText {
             glyphs: shaped_glyphs,
-            size: Vec2::new(layout.width(), layout.height()),
+            clusters,
+            lines,
+            size: Vec2::new(layout.width(), layout.height()) / scale_factor,
         };
 
         // Store in cache with bounded eviction
@@ -569,29 +1130,52 @@ impl TextSystem {
     }
 
     /// Process a glyph run, rasterizing glyphs as needed
+    ///
+    /// The run is laid out in device pixels, so glyphs are rasterized at their
+    /// device size and positions are converted back to logical pixels.
+    /// Distance field glyphs are rasterized once at [`DISTANCE_FIELD_SIZE`]
+    /// and scaled to the run's size when drawn.
     fn process_glyph_run(
         &mut self,
         glyph_run: &GlyphRun<'_, [u8; 4]>,
+        scale_factor: f32,
+        distance_field: bool,
+        color_ranges: &[(Range<usize>, Color)],
         shaped_glyphs: &mut Vec<ShapedGlyph>,
-    ) -> Result<(), String> {
+    ) -> SolResult<()> {
         let run = glyph_run.run();
+        let color = run_color(glyph_run, color_ranges);
         let font = run.font();
         let font_size = run.font_size();
+        let ((size_key, scale), raster_size, distance_field_scale) = if distance_field {
+            (
+                (DISTANCE_FIELD_SIZE, DISTANCE_FIELD_SCALE_KEY),
+                DISTANCE_FIELD_SIZE as f32,
+                Some(font_size / DISTANCE_FIELD_SIZE as f32 / scale_factor),
+            )
+        } else {
+            let raster_size = raster_size(font_size, self.coarse_glyph_sizes);
+            (raster_key(raster_size, scale_factor), raster_size, None)
+        };
         let normalized_coords = run.normalized_coords();
 
-        // Get or create font ID
-        let font_id = self.get_or_create_font_id(font.data.as_ref());
+        // Each set of variable axis values is its own font for the atlas
+        let font_id = self.get_or_create_font_id(font.data.as_ref(), normalized_coords);
 
         // Convert to swash font
-        let font_ref = FontRef::from_index(font.data.as_ref(), font.index as usize)
-            .ok_or_else(|| "Failed to create font reference".to_string())?;
+        let font_ref =
+            FontRef::from_index(font.data.as_ref(), font.index as usize).ok_or_else(|| {
+                SolError::FontLoading(format!("invalid font data at index {}", font.index))
+            })?;
 
         // Create scaler for this run
         let mut scaler = self
             .scale_context
             .builder(font_ref)
-            .size(font_size)
-            .hint(true)
+            .size(raster_size)
+            // Distance fields are scaled, so hinting to the pixel grid at
+            // one size would distort them at others
+            .hint(!distance_field)
             .normalized_coords(normalized_coords)
             .build();
 
@@ -605,43 +1189,71 @@ impl TextSystem {
             run_x += glyph.advance;
 
             // Ensure glyph is in atlas
-            let size_u32 = font_size.round() as u32;
-            let needs_rasterization = !self.glyph_atlas.contains(font_id, glyph.id, size_u32);
+            let needs_rasterization = !self
+                .glyph_atlas
+                .contains(font_id, glyph.id, size_key, scale);
 
             if needs_rasterization {
                 // Render the glyph
                 let rendered = Render::new(&[Source::Outline])
                     .format(swash::zeno::Format::Alpha)
                     .render(&mut scaler, glyph.id)
-                    .ok_or_else(|| "Failed to render glyph".to_string())?;
+                    .ok_or(SolError::GlyphMissing { glyph_id: glyph.id })?;
+                let placement = rendered.placement;
 
                 // Add to atlas
-                self.glyph_atlas.add_glyph(
-                    font_id,
-                    glyph.id,
-                    size_u32,
-                    &rendered.data,
-                    rendered.placement.width,
-                    rendered.placement.height,
-                    rendered.placement.left,
-                    rendered.placement.top,
-                )?;
+                if distance_field && placement.width > 0 && placement.height > 0 {
+                    let spread = DISTANCE_FIELD_SPREAD;
+                    let field = distance_field_from_coverage(
+                        &rendered.data,
+                        placement.width,
+                        placement.height,
+                        spread,
+                    );
+                    self.glyph_atlas.add_glyph(
+                        font_id,
+                        glyph.id,
+                        size_key,
+                        scale,
+                        &field,
+                        placement.width + spread * 2,
+                        placement.height + spread * 2,
+                        placement.left - spread as i32,
+                        placement.top + spread as i32,
+                    )?;
+                } else {
+                    self.glyph_atlas.add_glyph(
+                        font_id,
+                        glyph.id,
+                        size_key,
+                        scale,
+                        &rendered.data,
+                        placement.width,
+                        placement.height,
+                        placement.left,
+                        placement.top,
+                    )?;
+                }
             }
 
             shaped_glyphs.push(ShapedGlyph {
                 font_id,
                 glyph_id: glyph.id,
-                size: size_u32,
-                position: Vec2::new(glyph_x, glyph_y),
+                size: size_key,
+                scale,
+                position: Vec2::new(glyph_x, glyph_y) / scale_factor,
+                distance_field_scale,
+                color,
             });
         }
 
         Ok(())
     }
 
-    /// Get or create a font ID for the given font data
-    fn get_or_create_font_id(&mut self, font_data: &[u8]) -> u64 {
-        let key = font_data.to_vec();
+    /// Get or create a font ID for the given font data and variable axis
+    /// values
+    fn get_or_create_font_id(&mut self, font_data: &[u8], normalized_coords: &[i16]) -> u64 {
+        let key = (font_data.to_vec(), normalized_coords.to_vec());
         if let Some(&id) = self.font_id_cache.get(&key) {
             id
         } else {
@@ -652,13 +1264,554 @@ impl TextSystem {
         }
     }
 
-    /// Get the glyph atlas texture
-    pub fn atlas_texture(&self) -> &Texture {
+    /// Get the glyph atlas
+    pub fn glyph_atlas(&self) -> &GlyphAtlas {
+        &self.glyph_atlas
+    }
+
+    /// Get the glyph atlas texture, if the atlas has one
+    #[cfg(target_os = "macos")]
+    pub fn atlas_texture(&self) -> Option<&TextureRef> {
         self.glyph_atlas.texture()
     }
 
+    /// Find the character boundary closest to `point`
+    ///
+    /// `point` is relative to the text origin. Points above or below the text
+    /// snap to the first or last line, and points past either end of a line
+    /// snap to that end.
+    pub fn hit_test_text(shaped: &ShapedText, point: Vec2) -> CharIndex {
+        let Some(line_index) = shaped
+            .lines
+            .iter()
+            .position(|line| point.y < line.top + line.height)
+            .or(shaped.lines.len().checked_sub(1))
+        else {
+            return CharIndex(0);
+        };
+
+        let mut line_clusters = shaped.clusters.iter().filter(|c| c.line == line_index);
+        let Some(first) = line_clusters.next() else {
+            return CharIndex(shaped.lines[line_index].text_range.start);
+        };
+
+        // Clusters are in visual order, so the first one is the leftmost
+        let mut last = first;
+        if point.x < first.bounds.pos.x {
+            return CharIndex(Self::cluster_edge(first, true));
+        }
+        for cluster in std::iter::once(first).chain(line_clusters) {
+            let left = cluster.bounds.pos.x;
+            let right = left + cluster.bounds.size.x;
+            if point.x < right {
+                return CharIndex(Self::cluster_edge(cluster, point.x < (left + right) / 2.0));
+            }
+            last = cluster;
+        }
+        CharIndex(Self::cluster_edge(last, false))
+    }
+
+    /// Get the area of the character at `index`, relative to the text origin
+    ///
+    /// An index at the end of a line (or of the text) gives a zero-width rect
+    /// at the line end, suitable for placing a caret.
+    pub fn char_bounds(shaped: &ShapedText, index: CharIndex) -> Rect {
+        if let Some(cluster) = shaped
+            .clusters
+            .iter()
+            .find(|c| c.text_range.contains(&index.0))
+        {
+            return cluster.bounds;
+        }
+
+        // Past the last character of its line: use the line's trailing edge
+        let line_index = shaped
+            .lines
+            .iter()
+            .position(|line| index.0 < line.text_range.end)
+            .unwrap_or(shaped.lines.len().saturating_sub(1));
+        let Some(line) = shaped.lines.get(line_index) else {
+            return Rect::new(0.0, 0.0, 0.0, 0.0);
+        };
+        let end_x = shaped
+            .clusters
+            .iter()
+            .filter(|c| c.line == line_index)
+            .map(|c| c.bounds.pos.x + c.bounds.size.x)
+            .fold(0.0, f32::max);
+        Rect::new(end_x, line.top, 0.0, line.height)
+    }
+
+    /// Get where to draw the caret for `index`
+    ///
+    /// At a boundary between text directions the caret splits: the primary
+    /// caret follows `affinity` and the secondary one marks the position's
+    /// other edge.
+    pub fn caret_rects(
+        shaped: &ShapedText,
+        index: CharIndex,
+        affinity: CaretAffinity,
+    ) -> CaretRects {
+        let slot_rect = |(line, slot): (usize, usize)| {
+            let clusters = Self::line_clusters(shaped, line);
+            let x = match clusters.get(slot) {
+                Some(cluster) => cluster.bounds.pos.x,
+                None => clusters
+                    .last()
+                    .map_or(0.0, |c| c.bounds.pos.x + c.bounds.size.x),
+            };
+            let line = &shaped.lines[line];
+            Rect::new(x, line.top, 0.0, line.height)
+        };
+
+        let slot = Self::caret_slot(shaped, index, affinity);
+        let other_slot = Self::caret_slot(shaped, index, affinity.opposite());
+        let Some(primary_slot) = slot.or(other_slot) else {
+            // No characters around the position, e.g. on an empty line
+            let bounds = Self::char_bounds(shaped, index);
+            return CaretRects {
+                primary: Rect::from_pos_size(bounds.pos, Vec2::new(0.0, bounds.size.y)),
+                secondary: None,
+            };
+        };
+
+        let primary = slot_rect(primary_slot);
+        let secondary = other_slot
+            .filter(|_| slot.is_some())
+            .map(slot_rect)
+            .filter(|rect| (rect.pos.x - primary.pos.x).abs() > 0.5);
+        CaretRects { primary, secondary }
+    }
+
+    /// Get the highlight rects for the characters in `range`, relative to
+    /// the text origin
+    ///
+    /// Mixed-direction text can put a logical range in several places on a
+    /// line, so there's one rect per visually contiguous piece.
+    pub fn selection_rects(shaped: &ShapedText, range: Range<usize>) -> Vec<Rect> {
+        let mut rects: Vec<(usize, Rect)> = Vec::new();
+        let selected = shaped
+            .clusters
+            .iter()
+            .filter(|c| c.text_range.start >= range.start && c.text_range.end <= range.end);
+        for cluster in selected {
+            let bounds = cluster.bounds;
+            match rects.last_mut() {
+                // Extend the previous rect when the cluster is right after it
+                Some((line, rect))
+                    if *line == cluster.line
+                        && (bounds.pos.x - (rect.pos.x + rect.size.x)).abs() < 0.5 =>
+                {
+                    rect.size.x = bounds.pos.x + bounds.size.x - rect.pos.x;
+                }
+                _ => rects.push((cluster.line, bounds)),
+            }
+        }
+        rects.into_iter().map(|(_, rect)| rect).collect()
+    }
+
+    /// Get the rects that draw `decoration` on each line, relative to the
+    /// text origin
+    pub fn decoration_rects(shaped: &ShapedText, decoration: &TextDecoration) -> Vec<Rect> {
+        let mut rects = Vec::new();
+        for (index, line) in shaped.lines.iter().enumerate() {
+            let Some((left, right)) = Self::line_clusters(shaped, index)
+                .iter()
+                .map(|c| (c.bounds.pos.x, c.bounds.pos.x + c.bounds.size.x))
+                .reduce(|(left, right), (l, r)| (left.min(l), right.max(r)))
+            else {
+                continue;
+            };
+
+            let metrics = &line.decoration;
+            let (offset, size) = match decoration.line {
+                DecorationLine::Underline => (metrics.underline_offset, metrics.underline_size),
+                DecorationLine::Strikethrough => {
+                    (metrics.strikethrough_offset, metrics.strikethrough_size)
+                }
+                DecorationLine::Overline => (metrics.ascent, metrics.underline_size),
+            };
+            let thickness = decoration.thickness.unwrap_or(size).max(1.0);
+            let top = line.baseline - offset;
+
+            match decoration.style {
+                DecorationStyle::Solid => {
+                    rects.push(Rect::new(left, top, right - left, thickness));
+                }
+                DecorationStyle::Dashed => {
+                    let mut x = left;
+                    while x < right {
+                        let dash = (thickness * 3.0).min(right - x);
+                        rects.push(Rect::new(x, top, dash, thickness));
+                        x += thickness * 5.0;
+                    }
+                }
+                DecorationStyle::Wavy => {
+                    // Overlapping squares along a sine wave read as a curve
+                    let wavelength = thickness * 6.0;
+                    let mut x = left;
+                    while x < right {
+                        let phase = (x - left) / wavelength * std::f32::consts::TAU;
+                        let y = top + thickness * phase.sin();
+                        rects.push(Rect::new(x, y, thickness.min(right - x), thickness));
+                        x += thickness / 2.0;
+                    }
+                }
+            }
+        }
+        rects
+    }
+
+    /// Move a caret one character left or right on screen
+    ///
+    /// In mixed-direction text this crosses the character drawn next to the
+    /// caret, which may be far from `index` in the string. Returns `None` at
+    /// either end of the line.
+    pub fn move_visually(
+        shaped: &ShapedText,
+        index: CharIndex,
+        affinity: CaretAffinity,
+        right: bool,
+    ) -> Option<(CharIndex, CaretAffinity)> {
+        let (line, slot) = Self::caret_slot(shaped, index, affinity)
+            .or_else(|| Self::caret_slot(shaped, index, affinity.opposite()))?;
+        let clusters = Self::line_clusters(shaped, line);
+        let crossed = if right {
+            clusters.get(slot)?
+        } else {
+            clusters.get(slot.checked_sub(1)?)?
+        };
+
+        // Stay attached to the crossed cluster, on its far side
+        let offset = Self::cluster_edge(crossed, !right);
+        let affinity = if offset == crossed.text_range.start {
+            CaretAffinity::Downstream
+        } else {
+            CaretAffinity::Upstream
+        };
+        Some((CharIndex(offset), affinity))
+    }
+
+    /// Line and visual slot of a caret position, where slot `n` is before
+    /// the line's `n`th cluster from the left
+    fn caret_slot(
+        shaped: &ShapedText,
+        index: CharIndex,
+        affinity: CaretAffinity,
+    ) -> Option<(usize, usize)> {
+        let upstream = affinity == CaretAffinity::Upstream;
+        let cluster_index = shaped.clusters.iter().position(|c| {
+            if upstream {
+                c.text_range.end == index.0
+            } else {
+                c.text_range.start == index.0
+            }
+        })?;
+        let cluster = &shaped.clusters[cluster_index];
+        let line_start = shaped
+            .clusters
+            .iter()
+            .position(|c| c.line == cluster.line)
+            .unwrap_or(cluster_index);
+
+        // The start of an LTR cluster and the end of an RTL one are on its
+        // left
+        let position = cluster_index - line_start;
+        let on_left = upstream == cluster.is_rtl;
+        Some((cluster.line, if on_left { position } else { position + 1 }))
+    }
+
+    /// Clusters of a line, left to right
+    fn line_clusters(shaped: &ShapedText, line: usize) -> Vec<&ShapedCluster> {
+        shaped.clusters.iter().filter(|c| c.line == line).collect()
+    }
+
+    /// Byte offset at the left (`left == true`) or right edge of a cluster
+    fn cluster_edge(cluster: &ShapedCluster, left: bool) -> usize {
+        if left != cluster.is_rtl {
+            cluster.text_range.start
+        } else {
+            cluster.text_range.end
+        }
+    }
+
     /// Get information about a glyph in the atlas
-    pub fn glyph_info(&self, font_id: u64, glyph_id: u16, size: u32) -> Option<&GlyphInfo> {
-        self.glyph_atlas.get_glyph(font_id, glyph_id, size)
+    pub fn glyph_info(
+        &self,
+        font_id: u64,
+        glyph_id: u16,
+        size: u32,
+        scale: u32,
+    ) -> Option<&GlyphInfo> {
+        self.glyph_atlas.get_glyph(font_id, glyph_id, size, scale)
+    }
+}
+
+/// Build a signed distance field from a glyph's coverage bitmap
+///
+/// The field is padded by `spread` pixels on each side. Values are 128 on the
+/// outline, rising to 255 `spread` pixels inside the glyph and falling to 0
+/// `spread` pixels outside it.
+fn distance_field_from_coverage(coverage: &[u8], width: u32, height: u32, spread: u32) -> Vec<u8> {
+    let (width, height, spread) = (width as i32, height as i32, spread as i32);
+    let inside = |x: i32, y: i32| {
+        x >= 0 && y >= 0 && x < width && y < height && coverage[(y * width + x) as usize] >= 128
+    };
+
+    let field_width = width + spread * 2;
+    let field_height = height + spread * 2;
+    let mut field = Vec::with_capacity((field_width * field_height) as usize);
+    for y in -spread..height + spread {
+        for x in -spread..width + spread {
+            let is_inside = inside(x, y);
+
+            // Nearest pixel on the other side of the outline, past the
+            // spread if there's none within it
+            let mut nearest_squared = (spread as f32 + 0.5).powi(2);
+            for dy in -spread..=spread {
+                for dx in -spread..=spread {
+                    if inside(x + dx, y + dy) != is_inside {
+                        nearest_squared = nearest_squared.min((dx * dx + dy * dy) as f32);
+                    }
+                }
+            }
+
+            // The outline runs halfway between neighboring pixel centers
+            let distance = (nearest_squared.sqrt() - 0.5).max(0.0);
+            let signed = if is_inside { distance } else { -distance };
+            let value = 0.5 + signed / (spread as f32 * 2.0);
+            field.push((value.clamp(0.0, 1.0) * 255.0).round() as u8);
+        }
+    }
+    field
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two lines of 10px wide characters: "ab\ncd"
+    fn shaped() -> ShapedText {
+        let cluster = |start: usize, x: f32, line: usize| ShapedCluster {
+            text_range: start..start + 1,
+            bounds: Rect::new(x, line as f32 * 20.0, 10.0, 20.0),
+            line,
+            is_rtl: false,
+        };
+        ShapedText {
+            glyphs: vec![],
+            clusters: vec![
+                cluster(0, 0.0, 0),
+                cluster(1, 10.0, 0),
+                cluster(3, 0.0, 1),
+                cluster(4, 10.0, 1),
+            ],
+            lines: vec![
+                ShapedLine {
+                    text_range: 0..3,
+                    top: 0.0,
+                    height: 20.0,
+                    baseline: 15.0,
+                    decoration: DecorationMetrics::default(),
+                },
+                ShapedLine {
+                    text_range: 3..5,
+                    top: 20.0,
+                    height: 20.0,
+                    baseline: 35.0,
+                    decoration: DecorationMetrics::default(),
+                },
+            ],
+            size: Vec2::new(20.0, 40.0),
+        }
+    }
+
+    #[test]
+    fn test_hit_test_text() {
+        let shaped = shaped();
+        let hit = |x: f32, y: f32| TextSystem::hit_test_text(&shaped, Vec2::new(x, y)).0;
+
+        assert_eq!(hit(2.0, 5.0), 0);
+        assert_eq!(hit(8.0, 5.0), 1);
+        assert_eq!(hit(100.0, 5.0), 2);
+        assert_eq!(hit(-5.0, 25.0), 3);
+        assert_eq!(hit(14.0, 25.0), 4);
+        // Below the last line snaps to it
+        assert_eq!(hit(100.0, 100.0), 5);
+    }
+
+    #[test]
+    fn test_char_bounds() {
+        let shaped = shaped();
+        assert_eq!(
+            TextSystem::char_bounds(&shaped, CharIndex(1)),
+            Rect::new(10.0, 0.0, 10.0, 20.0)
+        );
+        // The newline and the end of the text sit at their line's end
+        assert_eq!(
+            TextSystem::char_bounds(&shaped, CharIndex(2)),
+            Rect::new(20.0, 0.0, 0.0, 20.0)
+        );
+        assert_eq!(
+            TextSystem::char_bounds(&shaped, CharIndex(5)),
+            Rect::new(20.0, 20.0, 0.0, 20.0)
+        );
+    }
+
+    #[test]
+    fn test_decoration_rects() {
+        let shaped = shaped();
+        let underline = TextDecoration::underline().thickness(2.0);
+        assert_eq!(
+            TextSystem::decoration_rects(&shaped, &underline),
+            vec![
+                Rect::new(0.0, 15.0, 20.0, 2.0),
+                Rect::new(0.0, 35.0, 20.0, 2.0),
+            ]
+        );
+
+        // Dashes are three times as long as they're thick, and cut off at
+        // the end of the line
+        let dashed = TextSystem::decoration_rects(&shaped, &underline.dashed());
+        assert_eq!(dashed.len(), 4);
+        assert_eq!(dashed[1], Rect::new(10.0, 15.0, 6.0, 2.0));
+    }
+
+    #[test]
+    fn test_hit_test_empty_text() {
+        let shaped = ShapedText {
+            glyphs: vec![],
+            clusters: vec![],
+            lines: vec![],
+            size: Vec2::ZERO,
+        };
+        assert_eq!(
+            TextSystem::hit_test_text(&shaped, Vec2::new(5.0, 5.0)),
+            CharIndex(0)
+        );
+    }
+
+    /// "ab" followed by the right-to-left "אב", drawn as "ab" then "בא"
+    fn mixed_direction() -> ShapedText {
+        let cluster = |text_range: Range<usize>, x: f32, is_rtl: bool| ShapedCluster {
+            text_range,
+            bounds: Rect::new(x, 0.0, 10.0, 20.0),
+            line: 0,
+            is_rtl,
+        };
+        ShapedText {
+            glyphs: vec![],
+            clusters: vec![
+                cluster(0..1, 0.0, false),
+                cluster(1..2, 10.0, false),
+                cluster(4..6, 20.0, true),
+                cluster(2..4, 30.0, true),
+            ],
+            lines: vec![ShapedLine {
+                text_range: 0..6,
+                top: 0.0,
+                height: 20.0,
+                baseline: 15.0,
+                decoration: DecorationMetrics::default(),
+            }],
+            size: Vec2::new(40.0, 20.0),
+        }
+    }
+
+    #[test]
+    fn test_split_caret_at_direction_boundary() {
+        let shaped = mixed_direction();
+        let caret = TextSystem::caret_rects(&shaped, CharIndex(2), CaretAffinity::Upstream);
+        assert_eq!(caret.primary.pos.x, 20.0);
+        assert_eq!(caret.secondary.map(|rect| rect.pos.x), Some(40.0));
+
+        let caret = TextSystem::caret_rects(&shaped, CharIndex(1), CaretAffinity::Downstream);
+        assert_eq!(caret.primary.pos.x, 10.0);
+        assert_eq!(caret.secondary, None);
+    }
+
+    #[test]
+    fn test_selection_rects_split_across_runs() {
+        let shaped = mixed_direction();
+        assert_eq!(
+            TextSystem::selection_rects(&shaped, 1..4),
+            vec![
+                Rect::new(10.0, 0.0, 10.0, 20.0),
+                Rect::new(30.0, 0.0, 10.0, 20.0)
+            ]
+        );
+        assert_eq!(
+            TextSystem::selection_rects(&shaped, 2..6),
+            vec![Rect::new(20.0, 0.0, 20.0, 20.0)]
+        );
+    }
+
+    #[test]
+    fn test_move_visually() {
+        let shaped = mixed_direction();
+        let right = |index: usize, affinity| {
+            TextSystem::move_visually(&shaped, CharIndex(index), affinity, true)
+        };
+        assert_eq!(
+            right(2, CaretAffinity::Upstream),
+            Some((CharIndex(4), CaretAffinity::Downstream))
+        );
+        assert_eq!(
+            right(4, CaretAffinity::Downstream),
+            Some((CharIndex(2), CaretAffinity::Downstream))
+        );
+        assert_eq!(right(2, CaretAffinity::Downstream), None);
+
+        assert_eq!(
+            TextSystem::move_visually(&shaped, CharIndex(2), CaretAffinity::Downstream, false),
+            Some((CharIndex(4), CaretAffinity::Upstream))
+        );
+    }
+
+    #[test]
+    fn test_distance_field_from_coverage() {
+        // A 4x4 square filling the middle of an 8x8 bitmap
+        let mut coverage = vec![0u8; 64];
+        for y in 2..6 {
+            for x in 2..6 {
+                coverage[y * 8 + x] = 255;
+            }
+        }
+        let spread = 4;
+        let field = distance_field_from_coverage(&coverage, 8, 8, spread);
+        let field_width = 8 + spread as usize * 2;
+        assert_eq!(field.len(), field_width * field_width);
+        let at =
+            |x: usize, y: usize| field[(y + spread as usize) * field_width + x + spread as usize];
+
+        // Pixels on either side of the outline straddle the midpoint
+        assert!(at(2, 4) >= 128);
+        assert!(at(1, 4) < 128);
+        // Deeper inside is higher, further outside is lower
+        assert!(at(3, 4) > at(2, 4));
+        assert!(at(0, 4) < at(1, 4));
+        // The padding past the spread is fully outside
+        assert_eq!(field[0], 0);
+    }
+
+    #[test]
+    fn test_raster_key_uses_device_size() {
+        // 13.4pt and 13.0pt both round to 13 logical pixels, but rasterize
+        // at different device sizes
+        assert_ne!(raster_key(13.4 * 2.0, 2.0), raster_key(13.0 * 2.0, 2.0));
+        assert_eq!(raster_key(26.0, 2.0), raster_key(26.0, 2.0));
+        // The same device size at another scale is a separate entry
+        assert_ne!(raster_key(26.0, 2.0).1, raster_key(26.0, 1.0).1);
+    }
+
+    #[test]
+    fn test_coarse_raster_size_shares_keys() {
+        assert_eq!(raster_size(26.8, false), 26.8);
+        assert_eq!(raster_size(26.8, true), 27.0);
+        assert_eq!(raster_size(0.2, true), 1.0);
+        assert_eq!(
+            raster_key(raster_size(26.8, true), 2.0),
+            raster_key(raster_size(27.1, true), 2.0)
+        );
     }
 }
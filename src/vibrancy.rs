@@ -0,0 +1,68 @@
+//! Native window materials behind parts of the UI
+//!
+//! Elements with a [`Material`] background cut a transparent hole in the
+//! layers painted below them and register the region here. After each full
+//! frame the platform layer places a system visual effect view behind each
+//! region, so sidebars and HUD panels get the same translucent, blurred
+//! backgrounds as native apps. Content painted on top of the element draws
+//! over the material as usual.
+
+use crate::geometry::Rect;
+use std::cell::RefCell;
+
+/// A system material for the background of part of the window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Material {
+    /// Window sidebars, such as a source list
+    Sidebar,
+    /// Heads-up display panels, dark and strongly blurred
+    HudWindow,
+    /// Popovers and other transient panels
+    Popover,
+    /// Menus
+    Menu,
+    /// Titlebars and toolbars
+    Titlebar,
+}
+
+impl Material {
+    /// The matching `NSVisualEffectMaterial` value
+    pub fn ns_material(self) -> i64 {
+        match self {
+            Material::Titlebar => 3,
+            Material::Menu => 5,
+            Material::Popover => 6,
+            Material::Sidebar => 7,
+            Material::HudWindow => 13,
+        }
+    }
+}
+
+/// A region of the window shown with a material, in window content
+/// coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MaterialRegion {
+    pub bounds: Rect,
+    pub material: Material,
+    pub corner_radius: f32,
+}
+
+thread_local! {
+    /// Regions registered during the current full frame, bottom layer first
+    static MATERIAL_REGIONS: RefCell<Vec<MaterialRegion>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Start collecting regions for a full frame
+pub(crate) fn begin_material_frame() {
+    MATERIAL_REGIONS.with(|regions| regions.borrow_mut().clear());
+}
+
+/// Show `region` with its material this frame
+pub fn register_material(region: MaterialRegion) {
+    MATERIAL_REGIONS.with(|regions| regions.borrow_mut().push(region));
+}
+
+/// Regions registered during the last full frame, in paint order
+pub fn material_regions() -> Vec<MaterialRegion> {
+    MATERIAL_REGIONS.with(|regions| regions.borrow().clone())
+}